@@ -0,0 +1,39 @@
+//! Collector benchmarks on a large generated state: snapshot construction, the "today" range
+//! queries replayed from event chunks, and saving the analytics files.
+//!
+//! Run with `cargo bench --features bench-support`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use typepulse_lib::bench_support::{save_input_analytics, BenchState};
+
+fn collector_benches(c: &mut Criterion) {
+    let mut state = BenchState::large();
+
+    c.bench_function("snapshot", |b| b.iter(|| black_box(state.snapshot())));
+    c.bench_function("snapshot_rows", |b| {
+        b.iter(|| black_box(state.snapshot_rows()))
+    });
+    c.bench_function("today_shortcuts", |b| {
+        b.iter(|| black_box(state.today_shortcuts()))
+    });
+    c.bench_function("today_top_keys", |b| {
+        b.iter(|| black_box(state.today_top_keys()))
+    });
+
+    let analytics = state.analytics();
+    let dir = std::env::temp_dir().join(format!("typepulse-bench-{}", std::process::id()));
+    let mut group = c.benchmark_group("save_input_analytics");
+    group.sample_size(10);
+    group.bench_function("daily_files", |b| {
+        b.iter_batched(
+            || (),
+            |()| save_input_analytics(&dir, &analytics).unwrap(),
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+criterion_group!(benches, collector_benches);
+criterion_main!(benches);
@@ -1,5 +1,12 @@
-use std::{path::PathBuf, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    path::{Component, Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 pub(crate) const DEFAULT_EXCLUDED_BUNDLE_IDS: [&str; 8] = [
@@ -13,12 +20,59 @@ pub(crate) const DEFAULT_EXCLUDED_BUNDLE_IDS: [&str; 8] = [
     "com.stickypassword.mac",
 ];
 
-#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+/// Focused-field labels that mark a plain text field as sensitive for `ax_sensitive_detection`.
+pub(crate) const DEFAULT_SENSITIVE_TITLE_KEYWORDS: [&str; 7] = [
+    "password",
+    "passcode",
+    "token",
+    "api key",
+    "secret",
+    "密码",
+    "验证码",
+];
+
+/// Launch argument that forces the main window to show even with `start_hidden`.
+pub(crate) const SHOW_WINDOW_ARG: &str = "--show";
+/// Launch argument that starts in demo mode regardless of `demo_mode`.
+pub(crate) const DEMO_ARG: &str = "--demo";
+
+// Set for demo mode: `save_app_config` keeps changes in memory and leaves the file alone.
+static CONFIG_WRITES_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// GitHub API endpoint of the latest published release.
+pub(crate) const DEFAULT_UPDATE_CHECK_URL: &str =
+    "https://api.github.com/repos/Fullstop000/typepulse/releases/latest";
+
+/// Config keys whose values never leave the backend through `get_config`.
+/// Add API tokens and encryption markers here when they are introduced.
+pub(crate) const SENSITIVE_CONFIG_FIELDS: [&str; 0] = [];
+
+const REDACTED_VALUE: &str = "[redacted]";
+
+/// Profile every install starts with: the data dir root and the top-level settings, so
+/// single-profile users keep their files where they are.
+pub(crate) const DEFAULT_PROFILE: &str = "default";
+
+/// Accepted range (seconds) of each timing setting. Loaded values are clamped into it;
+/// `update_timing_settings` rejects values outside it.
+pub(crate) const COLLECTOR_TICK_INTERVAL_RANGE_SECS: RangeInclusive<u64> = 1..=60;
+pub(crate) const FLUSH_INTERVAL_RANGE_SECS: RangeInclusive<u64> = 5..=3600;
+pub(crate) const SESSION_GAP_RANGE_SECS: RangeInclusive<u64> = 1..=600;
+pub(crate) const TRAY_UPDATE_INTERVAL_RANGE_SECS: RangeInclusive<u64> = 1..=60;
+pub(crate) const MAINTENANCE_IDLE_RANGE_SECS: RangeInclusive<u64> = 5..=600;
+pub(crate) const MAINTENANCE_MAX_DEFER_RANGE_SECS: RangeInclusive<u64> = 60..=21_600;
+
+fn clamp_secs(value: u64, range: &RangeInclusive<u64>) -> u64 {
+    value.clamp(*range.start(), *range.end())
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub(crate) enum MenuBarDisplayMode {
     IconOnly,
     TextOnly,
     IconText,
+    Sparkline,
 }
 
 impl Default for MenuBarDisplayMode {
@@ -33,6 +87,7 @@ impl MenuBarDisplayMode {
             Self::IconOnly => "icon_only",
             Self::TextOnly => "text_only",
             Self::IconText => "icon_text",
+            Self::Sparkline => "sparkline",
         }
     }
 
@@ -41,12 +96,322 @@ impl MenuBarDisplayMode {
             "icon_only" => Some(Self::IconOnly),
             "text_only" => Some(Self::TextOnly),
             "icon_text" => Some(Self::IconText),
+            "sparkline" => Some(Self::Sparkline),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TrayLeftClickAction {
+    Menu,
+    OpenWindow,
+    TogglePause,
+}
+
+impl Default for TrayLeftClickAction {
+    fn default() -> Self {
+        Self::Menu
+    }
+}
+
+impl TrayLeftClickAction {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Menu => "menu",
+            Self::OpenWindow => "open_window",
+            Self::TogglePause => "toggle_pause",
+        }
+    }
+
+    pub(crate) fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "menu" => Some(Self::Menu),
+            "open_window" => Some(Self::OpenWindow),
+            "toggle_pause" => Some(Self::TogglePause),
+            _ => None,
+        }
+    }
+}
+
+/// How much of the window title is kept in stats rows.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum WindowTitleMode {
+    /// Keep the full window title.
+    Full,
+    /// Drop the title and aggregate at app level only.
+    Hidden,
+}
+
+impl Default for WindowTitleMode {
+    fn default() -> Self {
+        Self::Full
+    }
+}
+
+impl WindowTitleMode {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Hidden => "hidden",
+        }
+    }
+
+    pub(crate) fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "full" => Some(Self::Full),
+            "hidden" => Some(Self::Hidden),
+            _ => None,
+        }
+    }
+}
+
+/// File format of end-of-day auto exports.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AutoExportFormat {
+    /// Same columns as the CSV summary file.
+    #[default]
+    Csv,
+    /// Array of snapshot stats rows.
+    Json,
+}
+
+impl AutoExportFormat {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+
+    pub(crate) fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
             _ => None,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+/// End-of-day export settings: after each local midnight the finished day is written to
+/// `dest_dir`, optionally followed by a user script.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(default)]
+pub(crate) struct AutoExportConfig {
+    /// 是否在每天结束后自动导出当天明细。
+    pub(crate) enabled: bool,
+    /// 导出文件格式：csv / json。
+    pub(crate) format: AutoExportFormat,
+    /// 导出目录（绝对路径），文件名为 `typepulse-YYYY-MM-DD.<格式>`。
+    pub(crate) dest_dir: String,
+    /// 导出完成后执行的脚本（可选），导出文件路径作为唯一参数，输出写入应用日志。
+    pub(crate) script_path: Option<String>,
+    /// 是否为导出文件签名（SHA-256 哈希链，写入 CSV 末尾注释行或 JSON 的 signature 字段），用于发现被改动的导出；仅防篡改提示，不是安全机制。
+    pub(crate) sign: bool,
+}
+
+/// Field separator of written CSV files.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CsvDelimiter {
+    /// `,`, what most tools expect.
+    #[default]
+    Comma,
+    /// `;`, what Excel expects in locales with a decimal comma.
+    Semicolon,
+    /// Tab character.
+    Tab,
+}
+
+impl CsvDelimiter {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Comma => "comma",
+            Self::Semicolon => "semicolon",
+            Self::Tab => "tab",
+        }
+    }
+
+    pub(crate) fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "comma" => Some(Self::Comma),
+            "semicolon" => Some(Self::Semicolon),
+            "tab" => Some(Self::Tab),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_char(&self) -> char {
+        match self {
+            Self::Comma => ',',
+            Self::Semicolon => ';',
+            Self::Tab => '\t',
+        }
+    }
+}
+
+/// How shifted digit and punctuation keys are named in key and shortcut counts.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SymbolKeyNames {
+    /// The key pressed: shift+1 counts as `1` with shift.
+    #[default]
+    Physical,
+    /// The character typed: shift+1 counts as `!` with shift.
+    Produced,
+}
+
+impl SymbolKeyNames {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Physical => "physical",
+            Self::Produced => "produced",
+        }
+    }
+
+    pub(crate) fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "physical" => Some(Self::Physical),
+            "produced" => Some(Self::Produced),
+            _ => None,
+        }
+    }
+}
+
+/// Where the stats and input analytics are stored.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum StorageBackend {
+    /// One stats and one analytics JSON file per local day.
+    #[default]
+    Json,
+    /// One SQLite database; the JSON daily files are migrated into it on first use.
+    Sqlite,
+}
+
+/// Window rectangle in physical pixels.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
+pub(crate) struct WindowBounds {
+    pub(crate) x: i32,
+    pub(crate) y: i32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+impl WindowBounds {
+    /// Fit bounds into the monitor they overlap most (or the first monitor when none overlaps),
+    /// so a window saved on a disconnected display is moved back on-screen.
+    pub(crate) fn clamp_to_monitors(&self, monitors: &[WindowBounds]) -> WindowBounds {
+        let Some(monitor) = monitors
+            .iter()
+            .max_by_key(|monitor| self.overlap_area(monitor))
+            .filter(|monitor| self.overlap_area(monitor) > 0)
+            .or_else(|| monitors.first())
+        else {
+            return *self;
+        };
+        let width = self.width.min(monitor.width).max(1);
+        let height = self.height.min(monitor.height).max(1);
+        let max_x = monitor.x as i64 + monitor.width as i64 - width as i64;
+        let max_y = monitor.y as i64 + monitor.height as i64 - height as i64;
+        WindowBounds {
+            x: (self.x as i64).clamp(monitor.x as i64, max_x) as i32,
+            y: (self.y as i64).clamp(monitor.y as i64, max_y) as i32,
+            width,
+            height,
+        }
+    }
+
+    fn overlap_area(&self, other: &WindowBounds) -> u64 {
+        let left = (self.x as i64).max(other.x as i64);
+        let top = (self.y as i64).max(other.y as i64);
+        let right = (self.x as i64 + self.width as i64).min(other.x as i64 + other.width as i64);
+        let bottom = (self.y as i64 + self.height as i64).min(other.y as i64 + other.height as i64);
+        if right <= left || bottom <= top {
+            return 0;
+        }
+        ((right - left) * (bottom - top)) as u64
+    }
+}
+
+/// Shortcut counting rules as a profile stores them in place of the top-level fields.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(default)]
+pub(crate) struct ShortcutRules {
+    /// 快捷键统计是否要求包含 Cmd 或 Ctrl。
+    pub(crate) require_cmd_or_ctrl: bool,
+    /// 是否允许仅 Alt/Opt 作为快捷键主修饰键。
+    pub(crate) allow_alt_only: bool,
+    /// 快捷键最小修饰键数量（至少为 1）。
+    pub(crate) min_modifiers: u8,
+    /// 快捷键白名单（标准化 id，非空时仅统计列表内组合）。
+    pub(crate) allowlist: Vec<String>,
+    /// 快捷键黑名单（标准化 id，优先级高于白名单）。
+    pub(crate) blocklist: Vec<String>,
+}
+
+impl Default for ShortcutRules {
+    fn default() -> Self {
+        Self {
+            require_cmd_or_ctrl: true,
+            allow_alt_only: false,
+            min_modifiers: 1,
+            allowlist: vec![],
+            blocklist: vec![],
+        }
+    }
+}
+
+impl ShortcutRules {
+    /// Trim, lowercase, sort and dedupe both lists, and keep at least one modifier.
+    pub(crate) fn normalized(self) -> Self {
+        Self {
+            min_modifiers: self.min_modifiers.max(1),
+            allowlist: normalize_shortcut_list(&self.allowlist),
+            blocklist: normalize_shortcut_list(&self.blocklist),
+            ..self
+        }
+    }
+}
+
+/// A separate data set (e.g. work / personal) kept in its own subdirectory of the data dir.
+/// Overrides left unset fall back to the top-level settings.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(default)]
+pub(crate) struct ProfileConfig {
+    /// 配置名称（唯一），托盘菜单按此名称切换；"default" 保留给数据目录根目录下的默认数据。
+    pub(crate) name: String,
+    /// 数据子目录名（数据目录下的单级目录），为空时使用配置名称。
+    pub(crate) data_subdir: String,
+    /// 该配置使用的忽略应用列表，为空（null）时沿用全局列表。
+    pub(crate) excluded_bundle_ids: Option<Vec<String>>,
+    /// 该配置使用的快捷键统计规则，为空（null）时沿用全局规则。
+    pub(crate) shortcut_rules: Option<ShortcutRules>,
+}
+
+/// Rule that picks a profile automatically. It fires while the local time is inside its
+/// window or while one of its apps is frontmost; a rule with neither trigger never fires.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(default)]
+pub(crate) struct ProfileAutoRule {
+    /// 命中时切换到的数据配置名称。
+    pub(crate) profile: String,
+    /// 优先级，多条规则同时命中时取数值最大的一条；相同时取列表中靠前的一条。
+    pub(crate) priority: i32,
+    /// 时间段开始（本地时间 HH:MM），与结束时间都为空时不按时间触发。
+    pub(crate) start: String,
+    /// 时间段结束（本地时间 HH:MM，不含），早于开始时间表示跨午夜。
+    pub(crate) end: String,
+    /// 时间段生效的星期（1 = 周一 … 7 = 周日），为空表示每天。
+    pub(crate) weekdays: Vec<u8>,
+    /// 前台应用 Bundle ID 列表，其中任一应用在前台时命中。
+    pub(crate) bundle_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(default)]
 pub(crate) struct AppConfig {
     /// 是否忽略组合键（ctrl/alt/shift/cmd/fn + 其他键）。
@@ -57,14 +422,63 @@ pub(crate) struct AppConfig {
     pub(crate) flush_interval_secs: u64,
     /// 两次按键间隔不超过该值（秒）时，计入活跃打字时长；超过则视为新会话。
     pub(crate) session_gap_secs: u64,
+    /// 连续输入段（burst）的最少按键数，达到后才计入 burst 统计。
+    pub(crate) burst_min_keys: u64,
+    /// 连续输入段内相邻两次按键的最大间隔（毫秒），达到该值即结束当前段。
+    pub(crate) burst_max_gap_ms: u64,
+    /// 复制快捷键之后在该时间窗（秒）内出现的第一次粘贴才与之配对，计入复制到粘贴延迟。
+    pub(crate) copy_paste_window_secs: u64,
+    /// 深度工作块的最短时长（分钟），按 5 分钟对齐，不足 5 分钟按 5 分钟计。
+    pub(crate) deep_work_min_minutes: u64,
+    /// 深度工作块内每个 5 分钟桶至少需要的按键数，任一桶低于该值即结束当前块。
+    pub(crate) deep_work_min_keys_per_5min: u64,
+    /// 分钟级明细保留天数，超过该天数的历史按小时汇总以缩小存储。
+    pub(crate) minute_resolution_days: u64,
+    /// 演示模式：启动时生成一周的示例数据，只保存在内存中，不写入数据、CSV、配置与日志文件（日志输出到标准输出）。
+    pub(crate) demo_mode: bool,
+    /// 数据存储方式：json（每日 JSON 文件）/ sqlite（单个数据库文件，首次使用时导入已有的 JSON 文件，原文件保留）；重启后生效。
+    pub(crate) storage_backend: StorageBackend,
     /// 托盘摘要信息刷新周期（秒），越小显示越及时。
     pub(crate) tray_update_interval_secs: u64,
-    /// 菜单栏小组件显示模式：仅图标 / 仅文本 / 图标+文本。
+    /// 连续该秒数没有按键后才执行重负载磁盘任务（大体积分析数据保存、小时汇总、月度归档）。
+    pub(crate) maintenance_idle_secs: u64,
+    /// 重负载磁盘任务最多推迟的秒数，超过后即使仍在输入也会执行。
+    pub(crate) maintenance_max_defer_secs: u64,
+    /// 菜单栏小组件显示模式：仅图标 / 仅文本 / 图标+文本 / 图标+最近一小时按键迷你图。
     pub(crate) menu_bar_display_mode: MenuBarDisplayMode,
+    /// 托盘图标左键单击行为：弹出菜单 / 打开主面板 / 切换暂停。双击始终打开主面板。
+    pub(crate) tray_left_click_action: TrayLeftClickAction,
+    /// 主面板上次关闭前的位置与尺寸（物理像素），为空时使用默认布局。
+    pub(crate) main_window_bounds: Option<WindowBounds>,
+    /// 启动时是否隐藏主面板（仅显示托盘），`--show` 启动参数可强制显示。
+    pub(crate) start_hidden: bool,
+    /// 应用在查询范围内的按键总数低于该值时，汇总中并入“其他”一行（0 表示不合并），只影响展示，不修改已保存的数据。
+    pub(crate) min_keys_to_display: u64,
+    /// 快照（每秒轮询）只携带今天的明细行与前 50 个快捷键，其余范围由专用命令查询。
+    /// 新安装默认开启；旧配置文件缺少该字段时保持关闭，过渡期内继续返回完整快照。
+    #[serde(default)]
+    pub(crate) snapshot_v2: bool,
+    /// 托盘“退出”是否需要 3 秒内再次点击确认。
+    pub(crate) confirm_quit: bool,
+    /// 托盘菜单是否显示“暂停快捷键统计”开关，重启后生效。
+    pub(crate) tray_shortcuts_toggle: bool,
+    /// 窗口标题记录方式（全局）：完整记录 / 隐藏（仅按应用统计）。
+    pub(crate) window_title_mode: WindowTitleMode,
+    /// 按应用覆盖的窗口标题记录方式（key 为小写 Bundle ID），优先级高于全局设置。
+    pub(crate) title_privacy_overrides: HashMap<String, WindowTitleMode>,
+    /// 窗口标题别名：key 为小写 Bundle ID（无 Bundle ID 时为应用名），值为“被合并的标题 -> 目标标题”，
+    /// 由 merge_window_titles 写入，采集时按此改写标题。
+    pub(crate) window_title_aliases: HashMap<String, HashMap<String, String>>,
     /// 忽略采集的应用 Bundle ID 列表。
     pub(crate) excluded_bundle_ids: Vec<String>,
     /// 是否已经处理过首次 1Password 忽略建议。
     pub(crate) one_password_suggestion_handled: bool,
+    /// 已接受或忽略过“非打字应用”（游戏/导航类）忽略建议的应用 Bundle ID，不再重复提示。
+    pub(crate) non_typing_suggestions_handled: Vec<String>,
+    /// 是否通过 macOS 辅助功能读取焦点输入框的角色，识别未启用安全输入的密码类输入框（安全文本框，或标题、描述、占位文字含关键词）；识别到时不记录按键事件与快捷键，按键数与打字时长照常统计。默认关闭。
+    pub(crate) ax_sensitive_detection: bool,
+    /// 焦点输入框的标题、描述或占位文字含其中任一关键词（不区分大小写）时视为敏感输入框。
+    pub(crate) ax_sensitive_title_keywords: Vec<String>,
     /// 快捷键统计是否要求包含 Cmd 或 Ctrl。
     pub(crate) shortcut_require_cmd_or_ctrl: bool,
     /// 是否允许仅 Alt/Opt 作为快捷键主修饰键。
@@ -75,6 +489,60 @@ pub(crate) struct AppConfig {
     pub(crate) shortcut_allowlist: Vec<String>,
     /// 快捷键黑名单（标准化 id，优先级高于白名单）。
     pub(crate) shortcut_blocklist: Vec<String>,
+    /// 是否暂停快捷键统计（打字时长与按键数照常记录），如结对编程时不想记录快捷键习惯。
+    pub(crate) shortcuts_paused: bool,
+    /// 按 Shift 输入的数字与符号键的命名方式：physical（按键本身，如 shift+1 记为 1）/ produced（输出的字符，如 !），统一后按键排行与快捷键不会拆成两条。
+    pub(crate) symbol_key_names: SymbolKeyNames,
+    /// 是否统计未完成的组合键（按下 Cmd/Ctrl/Opt 后未按其他键即松开）。macOS 需额外监听修饰键变化，重启后生效。
+    pub(crate) track_chord_aborts: bool,
+    /// 采集期间是否阻止 macOS App Nap，避免主面板长时间隐藏后计时被系统降频；暂停或空闲时自动放开。
+    pub(crate) prevent_app_nap: bool,
+    /// 是否记录按键事件序列（5 秒一段的事件 chunk），关闭后只保留快捷键等汇总统计，按时间范围的查询退化为全部历史；已保存的序列在下次落盘时从磁盘移除。
+    pub(crate) record_event_chunks: bool,
+    /// 显示器名称（key 为显示器 id，如 display-1），用于按显示器统计时替代系统名称。
+    pub(crate) display_labels: BTreeMap<String, String>,
+    /// 按应用的每日活跃打字时长目标（key 为小写 Bundle ID，value 为分钟）。
+    pub(crate) app_time_goals: HashMap<String, u64>,
+    /// 应用时长目标达成时是否发送通知（每个应用每天最多一次）。
+    pub(crate) app_time_goal_notifications: bool,
+    /// 各应用最近一次发送目标达成通知的日期（YYYY-MM-DD），重启后仍保证当天不重复通知。
+    pub(crate) app_time_goals_notified: HashMap<String, String>,
+    /// 是否在每周一上午发送上周与前一周的对比通知（按键数、活跃时长、最常用应用与增长最多的快捷键）。
+    pub(crate) weekly_comparison_notifications: bool,
+    /// 每日结束后的自动导出设置。
+    pub(crate) auto_export: AutoExportConfig,
+    /// CSV 分隔符：comma（,）/ semicolon（;）/ tab，同时用于汇总 CSV 与自动导出。
+    pub(crate) csv_delimiter: CsvDelimiter,
+    /// CSV 文件开头是否写入 UTF-8 BOM（Windows 版 Excel 据此识别编码）。
+    pub(crate) csv_utf8_bom: bool,
+    /// CSV 表头是否使用中文列名，关闭时使用英文字段名。
+    pub(crate) csv_headers_localized: bool,
+    /// 是否在数据目录持续写入 status.json（今日按键数与活跃时长、暂停状态、更新时间与版本），供脚本读取；最多每 5 秒写一次，内容不变时不写。
+    pub(crate) write_status_file: bool,
+    /// 是否允许通过 AppleScript 控制采集（pause / resume / toggle）与读取今日按键数、活跃分钟数，仅 macOS；每次调用都会记录来源应用。
+    pub(crate) applescript_enabled: bool,
+    /// 原始按键事件导出（仅调试构建）单次允许的最长时间窗口（小时，1–168）。
+    pub(crate) raw_export_max_window_hours: u64,
+    /// 是否同意匿名使用统计：仅本地累计命令调用次数与功能开关，每周上报一次，不含任何输入内容、应用名或窗口标题。
+    pub(crate) telemetry_enabled: bool,
+    /// 匿名使用统计的上报地址（http/https），为空时只在本地累计不上报。
+    pub(crate) telemetry_endpoint: String,
+    /// 是否每天自动检查一次新版本（后台进行，失败时仅记录日志）。
+    pub(crate) auto_update_check: bool,
+    /// 查询最新版本的 GitHub Releases API 地址。
+    pub(crate) update_check_url: String,
+    /// 额外的数据配置（如工作 / 个人），各自使用独立的数据子目录，可覆盖忽略列表与快捷键规则。
+    pub(crate) profiles: Vec<ProfileConfig>,
+    /// 当前使用的数据配置名称，"default" 为数据目录根目录下的默认数据。
+    pub(crate) active_profile: String,
+    /// 自动切换数据配置的规则（按时间段或前台应用），未配置时不自动切换。
+    pub(crate) profile_auto_rules: Vec<ProfileAutoRule>,
+    /// 没有规则命中时切回的数据配置，为空时保持当前配置。
+    pub(crate) profile_auto_fallback: String,
+    /// 规则结果需持续多少秒才自动切换，避免在应用间来回切换时频繁换配置。
+    pub(crate) profile_auto_hold_secs: u64,
+    /// 手动切换数据配置后暂停自动切换的分钟数。
+    pub(crate) profile_auto_cooldown_mins: u64,
 }
 
 impl Default for AppConfig {
@@ -84,37 +552,505 @@ impl Default for AppConfig {
             collector_tick_interval_secs: 1,
             flush_interval_secs: 60,
             session_gap_secs: 5,
+            burst_min_keys: 10,
+            burst_max_gap_ms: 2_000,
+            copy_paste_window_secs: 120,
+            deep_work_min_minutes: 25,
+            deep_work_min_keys_per_5min: 50,
+            minute_resolution_days: 14,
+            demo_mode: false,
+            storage_backend: StorageBackend::Json,
             tray_update_interval_secs: 1,
+            maintenance_idle_secs: 30,
+            maintenance_max_defer_secs: 900,
             menu_bar_display_mode: MenuBarDisplayMode::IconText,
+            tray_left_click_action: TrayLeftClickAction::Menu,
+            main_window_bounds: None,
+            start_hidden: false,
+            min_keys_to_display: 0,
+            snapshot_v2: true,
+            confirm_quit: false,
+            tray_shortcuts_toggle: false,
+            window_title_mode: WindowTitleMode::Full,
+            title_privacy_overrides: HashMap::new(),
+            window_title_aliases: HashMap::new(),
             excluded_bundle_ids: DEFAULT_EXCLUDED_BUNDLE_IDS
                 .iter()
                 .map(|v| v.to_ascii_lowercase())
                 .collect(),
             one_password_suggestion_handled: false,
+            non_typing_suggestions_handled: Vec::new(),
+            ax_sensitive_detection: false,
+            ax_sensitive_title_keywords: DEFAULT_SENSITIVE_TITLE_KEYWORDS
+                .iter()
+                .map(|keyword| keyword.to_string())
+                .collect(),
             shortcut_require_cmd_or_ctrl: true,
             shortcut_allow_alt_only: false,
             shortcut_min_modifiers: 1,
             shortcut_allowlist: vec![],
             shortcut_blocklist: vec![],
+            shortcuts_paused: false,
+            symbol_key_names: SymbolKeyNames::Physical,
+            track_chord_aborts: false,
+            prevent_app_nap: true,
+            record_event_chunks: true,
+            display_labels: BTreeMap::new(),
+            app_time_goals: HashMap::new(),
+            app_time_goal_notifications: true,
+            app_time_goals_notified: HashMap::new(),
+            weekly_comparison_notifications: true,
+            auto_export: AutoExportConfig::default(),
+            csv_delimiter: CsvDelimiter::Comma,
+            csv_utf8_bom: false,
+            csv_headers_localized: false,
+            write_status_file: false,
+            applescript_enabled: false,
+            raw_export_max_window_hours: 24,
+            telemetry_enabled: false,
+            telemetry_endpoint: String::new(),
+            auto_update_check: true,
+            update_check_url: DEFAULT_UPDATE_CHECK_URL.to_string(),
+            profiles: Vec::new(),
+            active_profile: DEFAULT_PROFILE.to_string(),
+            profile_auto_rules: Vec::new(),
+            profile_auto_fallback: String::new(),
+            profile_auto_hold_secs: 60,
+            profile_auto_cooldown_mins: 60,
         }
     }
 }
 
 impl AppConfig {
     pub(crate) fn collector_tick_interval(&self) -> Duration {
-        Duration::from_secs(self.collector_tick_interval_secs.max(1))
+        Duration::from_secs(clamp_secs(
+            self.collector_tick_interval_secs,
+            &COLLECTOR_TICK_INTERVAL_RANGE_SECS,
+        ))
     }
 
     pub(crate) fn flush_interval(&self) -> Duration {
-        Duration::from_secs(self.flush_interval_secs.max(1))
+        Duration::from_secs(clamp_secs(
+            self.flush_interval_secs,
+            &FLUSH_INTERVAL_RANGE_SECS,
+        ))
     }
 
     pub(crate) fn session_gap(&self) -> Duration {
-        Duration::from_secs(self.session_gap_secs.max(1))
+        Duration::from_secs(clamp_secs(self.session_gap_secs, &SESSION_GAP_RANGE_SECS))
     }
 
     pub(crate) fn tray_update_interval(&self) -> Duration {
-        Duration::from_secs(self.tray_update_interval_secs.max(1))
+        Duration::from_secs(clamp_secs(
+            self.tray_update_interval_secs,
+            &TRAY_UPDATE_INTERVAL_RANGE_SECS,
+        ))
+    }
+
+    pub(crate) fn maintenance_idle(&self) -> Duration {
+        Duration::from_secs(clamp_secs(
+            self.maintenance_idle_secs,
+            &MAINTENANCE_IDLE_RANGE_SECS,
+        ))
+    }
+
+    pub(crate) fn maintenance_max_defer(&self) -> Duration {
+        Duration::from_secs(clamp_secs(
+            self.maintenance_max_defer_secs,
+            &MAINTENANCE_MAX_DEFER_RANGE_SECS,
+        ))
+    }
+
+    /// Config as the app actually applies it: the active profile's overrides in place,
+    /// intervals clamped to their ranges, retention and thresholds to their minimums.
+    pub(crate) fn effective(&self) -> AppConfig {
+        let config = self.for_active_profile();
+        AppConfig {
+            collector_tick_interval_secs: config.collector_tick_interval().as_secs(),
+            flush_interval_secs: config.flush_interval().as_secs(),
+            session_gap_secs: config.session_gap().as_secs(),
+            burst_min_keys: config.burst_min_keys.max(1),
+            burst_max_gap_ms: config.burst_max_gap_ms.max(1),
+            copy_paste_window_secs: config.copy_paste_window_secs.max(1),
+            deep_work_min_minutes: config.deep_work_min_minutes.max(5),
+            deep_work_min_keys_per_5min: config.deep_work_min_keys_per_5min.max(1),
+            minute_resolution_days: config.minute_resolution_days.max(1),
+            tray_update_interval_secs: config.tray_update_interval().as_secs(),
+            maintenance_idle_secs: config.maintenance_idle().as_secs(),
+            maintenance_max_defer_secs: config.maintenance_max_defer().as_secs(),
+            shortcut_min_modifiers: config.shortcut_min_modifiers.max(1),
+            raw_export_max_window_hours: config.raw_export_max_window_hours.clamp(1, 168),
+            ..config
+        }
+    }
+
+    /// Per field group, whether every value still matches the built-in default. A value the
+    /// user explicitly set back to its default reads as `Default`; the file keeps no history.
+    pub(crate) fn config_source(&self) -> BTreeMap<&'static str, ConfigSource> {
+        let defaults = AppConfig::default();
+        let source = |is_default: bool| {
+            if is_default {
+                ConfigSource::Default
+            } else {
+                ConfigSource::User
+            }
+        };
+        BTreeMap::from([
+            (
+                "capture",
+                source(
+                    self.ignore_key_combos == defaults.ignore_key_combos
+                        && self.track_chord_aborts == defaults.track_chord_aborts
+                        && self.prevent_app_nap == defaults.prevent_app_nap
+                        && self.record_event_chunks == defaults.record_event_chunks
+                        && self.display_labels == defaults.display_labels
+                        && self.window_title_aliases == defaults.window_title_aliases,
+                ),
+            ),
+            (
+                "timing",
+                source(
+                    self.collector_tick_interval_secs == defaults.collector_tick_interval_secs
+                        && self.flush_interval_secs == defaults.flush_interval_secs
+                        && self.session_gap_secs == defaults.session_gap_secs
+                        && self.burst_min_keys == defaults.burst_min_keys
+                        && self.burst_max_gap_ms == defaults.burst_max_gap_ms
+                        && self.copy_paste_window_secs == defaults.copy_paste_window_secs
+                        && self.deep_work_min_minutes == defaults.deep_work_min_minutes
+                        && self.deep_work_min_keys_per_5min == defaults.deep_work_min_keys_per_5min
+                        && self.tray_update_interval_secs == defaults.tray_update_interval_secs
+                        && self.maintenance_idle_secs == defaults.maintenance_idle_secs
+                        && self.maintenance_max_defer_secs == defaults.maintenance_max_defer_secs,
+                ),
+            ),
+            (
+                "storage",
+                source(
+                    self.minute_resolution_days == defaults.minute_resolution_days
+                        && self.demo_mode == defaults.demo_mode
+                        && self.storage_backend == defaults.storage_backend,
+                ),
+            ),
+            (
+                "tray",
+                source(
+                    self.menu_bar_display_mode == defaults.menu_bar_display_mode
+                        && self.tray_left_click_action == defaults.tray_left_click_action
+                        && self.confirm_quit == defaults.confirm_quit
+                        && self.tray_shortcuts_toggle == defaults.tray_shortcuts_toggle,
+                ),
+            ),
+            (
+                "window",
+                source(
+                    self.main_window_bounds == defaults.main_window_bounds
+                        && self.start_hidden == defaults.start_hidden
+                        && self.min_keys_to_display == defaults.min_keys_to_display
+                        && self.snapshot_v2 == defaults.snapshot_v2,
+                ),
+            ),
+            (
+                "privacy",
+                source(
+                    self.window_title_mode == defaults.window_title_mode
+                        && self.title_privacy_overrides == defaults.title_privacy_overrides
+                        && normalize_bundle_list(&self.excluded_bundle_ids)
+                            == normalize_bundle_list(&defaults.excluded_bundle_ids)
+                        && self.one_password_suggestion_handled
+                            == defaults.one_password_suggestion_handled
+                        && self.non_typing_suggestions_handled
+                            == defaults.non_typing_suggestions_handled
+                        && self.ax_sensitive_detection == defaults.ax_sensitive_detection
+                        && self.ax_sensitive_title_keywords == defaults.ax_sensitive_title_keywords
+                        && self.telemetry_enabled == defaults.telemetry_enabled
+                        && self.telemetry_endpoint == defaults.telemetry_endpoint,
+                ),
+            ),
+            (
+                "shortcuts",
+                source(
+                    self.shortcut_require_cmd_or_ctrl == defaults.shortcut_require_cmd_or_ctrl
+                        && self.shortcut_allow_alt_only == defaults.shortcut_allow_alt_only
+                        && self.shortcut_min_modifiers == defaults.shortcut_min_modifiers
+                        && self.shortcut_allowlist == defaults.shortcut_allowlist
+                        && self.shortcut_blocklist == defaults.shortcut_blocklist
+                        && self.shortcuts_paused == defaults.shortcuts_paused
+                        && self.symbol_key_names == defaults.symbol_key_names,
+                ),
+            ),
+            (
+                "goals",
+                source(
+                    self.app_time_goals == defaults.app_time_goals
+                        && self.app_time_goal_notifications == defaults.app_time_goal_notifications
+                        && self.weekly_comparison_notifications
+                            == defaults.weekly_comparison_notifications,
+                ),
+            ),
+            (
+                "export",
+                source(
+                    self.auto_export == defaults.auto_export
+                        && self.csv_delimiter == defaults.csv_delimiter
+                        && self.csv_utf8_bom == defaults.csv_utf8_bom
+                        && self.csv_headers_localized == defaults.csv_headers_localized
+                        && self.write_status_file == defaults.write_status_file
+                        && self.applescript_enabled == defaults.applescript_enabled
+                        && self.raw_export_max_window_hours == defaults.raw_export_max_window_hours,
+                ),
+            ),
+            (
+                "updates",
+                source(
+                    self.auto_update_check == defaults.auto_update_check
+                        && self.update_check_url == defaults.update_check_url,
+                ),
+            ),
+            (
+                "profiles",
+                source(
+                    self.profiles == defaults.profiles
+                        && self.active_profile == defaults.active_profile
+                        && self.profile_auto_rules == defaults.profile_auto_rules
+                        && self.profile_auto_fallback == defaults.profile_auto_fallback
+                        && self.profile_auto_hold_secs == defaults.profile_auto_hold_secs
+                        && self.profile_auto_cooldown_mins == defaults.profile_auto_cooldown_mins,
+                ),
+            ),
+        ])
+    }
+
+    /// Profile names in menu order: the implicit default first, then the configured ones.
+    pub(crate) fn profile_names(&self) -> Vec<String> {
+        let mut names = vec![DEFAULT_PROFILE.to_string()];
+        for profile in &self.profiles {
+            if !profile.name.is_empty() && !names.contains(&profile.name) {
+                names.push(profile.name.clone());
+            }
+        }
+        names
+    }
+
+    // Configured profile by name; the default profile has no entry.
+    fn profile(&self, name: &str) -> Option<&ProfileConfig> {
+        if name == DEFAULT_PROFILE {
+            return None;
+        }
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    fn active_profile_mut(&mut self) -> Option<&mut ProfileConfig> {
+        if self.active_profile == DEFAULT_PROFILE {
+            return None;
+        }
+        let name = self.active_profile.clone();
+        self.profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+    }
+
+    /// Data directory of profile `name` under `data_dir`. The default profile uses `data_dir`
+    /// itself; other profiles use a single subdirectory, never a path leaving `data_dir`.
+    pub(crate) fn profile_data_dir(&self, data_dir: &Path, name: &str) -> Result<PathBuf, String> {
+        if name == DEFAULT_PROFILE {
+            return Ok(data_dir.to_path_buf());
+        }
+        let profile = self
+            .profile(name)
+            .ok_or_else(|| format!("unknown profile: {name}"))?;
+        let subdir = match profile.data_subdir.trim() {
+            "" => profile.name.trim(),
+            subdir => subdir,
+        };
+        let mut components = Path::new(subdir).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => Ok(data_dir.join(subdir)),
+            _ => Err(format!(
+                "invalid data subdirectory for profile {name}: {subdir:?}"
+            )),
+        }
+    }
+
+    /// Config as the collector applies it for the active profile: its overrides replace the
+    /// top-level exclusion list and shortcut rules. Unknown profiles change nothing.
+    pub(crate) fn for_active_profile(&self) -> AppConfig {
+        let mut config = self.clone();
+        if let Some(profile) = self.profile(&self.active_profile) {
+            if let Some(bundle_ids) = &profile.excluded_bundle_ids {
+                config.excluded_bundle_ids = bundle_ids.clone();
+            }
+            if let Some(rules) = &profile.shortcut_rules {
+                config.write_top_level_shortcut_rules(rules.clone());
+            }
+        }
+        config
+    }
+
+    /// Store the exclusion list where the active profile reads it: its own override when it
+    /// has one, the top-level list otherwise.
+    pub(crate) fn set_excluded_bundle_ids(&mut self, bundle_ids: Vec<String>) {
+        match self
+            .active_profile_mut()
+            .and_then(|profile| profile.excluded_bundle_ids.as_mut())
+        {
+            Some(overridden) => *overridden = bundle_ids,
+            None => self.excluded_bundle_ids = bundle_ids,
+        }
+    }
+
+    /// Shortcut rules in effect for the active profile.
+    pub(crate) fn shortcut_rules(&self) -> ShortcutRules {
+        if let Some(rules) = self
+            .profile(&self.active_profile)
+            .and_then(|profile| profile.shortcut_rules.as_ref())
+        {
+            return rules.clone();
+        }
+        ShortcutRules {
+            require_cmd_or_ctrl: self.shortcut_require_cmd_or_ctrl,
+            allow_alt_only: self.shortcut_allow_alt_only,
+            min_modifiers: self.shortcut_min_modifiers,
+            allowlist: self.shortcut_allowlist.clone(),
+            blocklist: self.shortcut_blocklist.clone(),
+        }
+    }
+
+    /// Store shortcut rules where the active profile reads them, like
+    /// `set_excluded_bundle_ids`.
+    pub(crate) fn set_shortcut_rules(&mut self, rules: ShortcutRules) {
+        let rules = rules.normalized();
+        match self
+            .active_profile_mut()
+            .and_then(|profile| profile.shortcut_rules.as_mut())
+        {
+            Some(overridden) => *overridden = rules,
+            None => self.write_top_level_shortcut_rules(rules),
+        }
+    }
+
+    fn write_top_level_shortcut_rules(&mut self, rules: ShortcutRules) {
+        self.shortcut_require_cmd_or_ctrl = rules.require_cmd_or_ctrl;
+        self.shortcut_allow_alt_only = rules.allow_alt_only;
+        self.shortcut_min_modifiers = rules.min_modifiers;
+        self.shortcut_allowlist = rules.allowlist;
+        self.shortcut_blocklist = rules.blocklist;
+    }
+
+    /// Replace the six timing settings (seconds) after checking each against its range.
+    /// Nothing changes when any value is out of range.
+    pub(crate) fn set_timing_settings(
+        &mut self,
+        collector_tick_interval_secs: u64,
+        flush_interval_secs: u64,
+        session_gap_secs: u64,
+        tray_update_interval_secs: u64,
+        maintenance_idle_secs: u64,
+        maintenance_max_defer_secs: u64,
+    ) -> Result<(), String> {
+        for (name, value, range) in [
+            (
+                "collector_tick_interval_secs",
+                collector_tick_interval_secs,
+                COLLECTOR_TICK_INTERVAL_RANGE_SECS,
+            ),
+            (
+                "flush_interval_secs",
+                flush_interval_secs,
+                FLUSH_INTERVAL_RANGE_SECS,
+            ),
+            ("session_gap_secs", session_gap_secs, SESSION_GAP_RANGE_SECS),
+            (
+                "tray_update_interval_secs",
+                tray_update_interval_secs,
+                TRAY_UPDATE_INTERVAL_RANGE_SECS,
+            ),
+            (
+                "maintenance_idle_secs",
+                maintenance_idle_secs,
+                MAINTENANCE_IDLE_RANGE_SECS,
+            ),
+            (
+                "maintenance_max_defer_secs",
+                maintenance_max_defer_secs,
+                MAINTENANCE_MAX_DEFER_RANGE_SECS,
+            ),
+        ] {
+            if !range.contains(&value) {
+                return Err(format!(
+                    "{name} must be between {} and {}",
+                    range.start(),
+                    range.end()
+                ));
+            }
+        }
+        self.collector_tick_interval_secs = collector_tick_interval_secs;
+        self.flush_interval_secs = flush_interval_secs;
+        self.session_gap_secs = session_gap_secs;
+        self.tray_update_interval_secs = tray_update_interval_secs;
+        self.maintenance_idle_secs = maintenance_idle_secs;
+        self.maintenance_max_defer_secs = maintenance_max_defer_secs;
+        Ok(())
+    }
+
+    /// Whether a launch with `args` should show the main window.
+    /// An explicit `--show` always wins over `start_hidden`.
+    pub(crate) fn show_main_window_on_launch<I, S>(&self, args: I) -> bool
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        !self.start_hidden || args.into_iter().any(|arg| arg.as_ref() == SHOW_WINDOW_ARG)
+    }
+
+    /// Whether a launch with `args` runs in demo mode: `demo_mode` or `--demo`.
+    pub(crate) fn demo_mode_on_launch<I, S>(&self, args: I) -> bool
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.demo_mode || args.into_iter().any(|arg| arg.as_ref() == DEMO_ARG)
+    }
+}
+
+/// Whether a config field group still uses built-in defaults or carries user changes.
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ConfigSource {
+    Default,
+    User,
+}
+
+/// `get_config` payload: effective config with sensitive fields redacted, plus where each
+/// field group comes from.
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct EffectiveConfig {
+    pub(crate) config: serde_json::Value,
+    pub(crate) config_source: BTreeMap<&'static str, ConfigSource>,
+}
+
+impl EffectiveConfig {
+    pub(crate) fn from_config(config: &AppConfig) -> Result<Self, String> {
+        let effective = config.effective();
+        let mut value = serde_json::to_value(&effective).map_err(|e| e.to_string())?;
+        redact_fields(&mut value, &SENSITIVE_CONFIG_FIELDS);
+        Ok(Self {
+            config: value,
+            config_source: config.config_source(),
+        })
+    }
+}
+
+// Replace present sensitive keys with a marker so the UI can still tell the field is set.
+fn redact_fields(value: &mut serde_json::Value, fields: &[&str]) {
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+    for field in fields {
+        if let Some(entry) = object.get_mut(*field) {
+            if !entry.is_null() {
+                *entry = serde_json::Value::String(REDACTED_VALUE.to_string());
+            }
+        }
     }
 }
 
@@ -128,7 +1064,15 @@ pub(crate) fn load_app_config(path: &PathBuf) -> Result<AppConfig, String> {
     }
 }
 
+/// Stop writing the config file for the rest of the process, for demo mode.
+pub(crate) fn disable_config_writes() {
+    CONFIG_WRITES_DISABLED.store(true, Ordering::Relaxed);
+}
+
 pub(crate) fn save_app_config(path: &PathBuf, config: &AppConfig) -> Result<(), String> {
+    if CONFIG_WRITES_DISABLED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
     if let Some(parent) = path.parent() {
         let _ = std::fs::create_dir_all(parent);
     }
@@ -139,32 +1083,485 @@ pub(crate) fn save_app_config(path: &PathBuf, config: &AppConfig) -> Result<(),
     Ok(())
 }
 
-fn normalize_excluded_bundle_ids(mut config: AppConfig) -> AppConfig {
-    config.excluded_bundle_ids = config
-        .excluded_bundle_ids
+/// Canonical form of a bundle id list: trimmed, lowercased, non-empty, sorted and deduped.
+/// Config, collector state and snapshots all store excluded apps in this form.
+pub(crate) fn normalize_bundle_list<S: AsRef<str>>(bundle_ids: &[S]) -> Vec<String> {
+    let mut normalized: Vec<String> = bundle_ids
         .iter()
-        .map(|v| v.trim().to_ascii_lowercase())
+        .map(|v| v.as_ref().trim().to_ascii_lowercase())
         .filter(|v| !v.is_empty())
         .collect();
-    config.excluded_bundle_ids.sort();
-    config.excluded_bundle_ids.dedup();
-    // Normalize shortcut rules for deterministic matching.
-    config.shortcut_min_modifiers = config.shortcut_min_modifiers.max(1);
-    config.shortcut_allowlist = config
-        .shortcut_allowlist
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+// Shortcut ids are matched trimmed and lowercased; lists are kept sorted and deduped.
+fn normalize_shortcut_list(shortcut_ids: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = shortcut_ids
         .iter()
         .map(|v| v.trim().to_ascii_lowercase())
         .filter(|v| !v.is_empty())
         .collect();
-    config.shortcut_allowlist.sort();
-    config.shortcut_allowlist.dedup();
-    config.shortcut_blocklist = config
-        .shortcut_blocklist
+    normalized.sort();
+    normalized.dedup();
+    normalized
+}
+
+// Migrate configs written by older builds (raw user input) into the canonical form.
+fn normalize_excluded_bundle_ids(mut config: AppConfig) -> AppConfig {
+    config.excluded_bundle_ids = normalize_bundle_list(&config.excluded_bundle_ids);
+    config.title_privacy_overrides = config
+        .title_privacy_overrides
         .iter()
-        .map(|v| v.trim().to_ascii_lowercase())
-        .filter(|v| !v.is_empty())
+        .map(|(bundle_id, mode)| (bundle_id.trim().to_ascii_lowercase(), *mode))
+        .filter(|(bundle_id, _)| !bundle_id.is_empty())
         .collect();
-    config.shortcut_blocklist.sort();
-    config.shortcut_blocklist.dedup();
+    config.window_title_aliases = std::mem::take(&mut config.window_title_aliases)
+        .into_iter()
+        .map(|(app_id, aliases)| (app_id.trim().to_ascii_lowercase(), aliases))
+        .filter(|(app_id, aliases)| !app_id.is_empty() && !aliases.is_empty())
+        .collect();
+    // Normalize shortcut rules for deterministic matching.
+    config.shortcut_min_modifiers = config.shortcut_min_modifiers.max(1);
+    config.shortcut_allowlist = normalize_shortcut_list(&config.shortcut_allowlist);
+    config.shortcut_blocklist = normalize_shortcut_list(&config.shortcut_blocklist);
+    for profile in &mut config.profiles {
+        profile.name = profile.name.trim().to_string();
+        if let Some(bundle_ids) = profile.excluded_bundle_ids.take() {
+            profile.excluded_bundle_ids = Some(normalize_bundle_list(&bundle_ids));
+        }
+        profile.shortcut_rules = profile.shortcut_rules.take().map(ShortcutRules::normalized);
+    }
+    for rule in &mut config.profile_auto_rules {
+        rule.profile = rule.profile.trim().to_string();
+        rule.bundle_ids = normalize_bundle_list(&rule.bundle_ids);
+    }
+    config.profile_auto_fallback = config.profile_auto_fallback.trim().to_string();
+    config.active_profile = match config.active_profile.trim() {
+        "" => DEFAULT_PROFILE.to_string(),
+        name => name.to_string(),
+    };
     config
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        load_app_config, normalize_bundle_list, redact_fields, save_app_config, AppConfig,
+        ConfigSource, EffectiveConfig, MenuBarDisplayMode, ProfileConfig, ShortcutRules,
+        WindowBounds, WindowTitleMode, DEFAULT_PROFILE,
+    };
+    use std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+        time::{Duration, SystemTime},
+    };
+
+    fn bounds(x: i32, y: i32, width: u32, height: u32) -> WindowBounds {
+        WindowBounds {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn clamp_keeps_bounds_inside_overlapping_monitor() {
+        let monitors = [bounds(0, 0, 1920, 1080), bounds(1920, 0, 2560, 1440)];
+        let saved = bounds(2000, 100, 1280, 800);
+        assert_eq!(saved.clamp_to_monitors(&monitors), saved);
+        let partially_off = bounds(1800, 900, 1280, 800);
+        assert_eq!(
+            partially_off.clamp_to_monitors(&monitors),
+            bounds(1920, 640, 1280, 800)
+        );
+    }
+
+    #[test]
+    fn clamp_moves_bounds_from_disconnected_monitor_to_first_monitor() {
+        let monitors = [bounds(0, 0, 1440, 900)];
+        let saved = bounds(3000, 200, 1600, 1000);
+        assert_eq!(saved.clamp_to_monitors(&monitors), bounds(0, 0, 1440, 900));
+        assert_eq!(saved.clamp_to_monitors(&[]), saved);
+    }
+
+    #[test]
+    fn start_hidden_is_overridden_by_show_arg() {
+        let mut config = AppConfig::default();
+        assert!(config.show_main_window_on_launch(["typepulse"]));
+        config.start_hidden = true;
+        assert!(!config.show_main_window_on_launch(["typepulse"]));
+        assert!(config.show_main_window_on_launch(["typepulse", "--show"]));
+    }
+
+    #[test]
+    fn snapshot_v2_is_on_for_new_installs_only() {
+        assert!(AppConfig::default().snapshot_v2);
+        let legacy: AppConfig = serde_json::from_str(r#"{"session_gap_secs":5}"#).unwrap();
+        assert!(!legacy.snapshot_v2);
+    }
+
+    #[test]
+    fn demo_mode_follows_config_or_demo_arg() {
+        let mut config = AppConfig::default();
+        assert!(!config.demo_mode_on_launch(["typepulse"]));
+        assert!(config.demo_mode_on_launch(["typepulse", "--demo"]));
+        config.demo_mode = true;
+        assert!(config.demo_mode_on_launch(["typepulse"]));
+    }
+
+    #[test]
+    fn title_privacy_overrides_persist_normalized() {
+        let mut path = std::env::temp_dir();
+        let stamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        path.push(format!("typepulse-config-{stamp}.json"));
+        let mut config = AppConfig::default();
+        config.window_title_mode = WindowTitleMode::Hidden;
+        config
+            .title_privacy_overrides
+            .insert(" Com.Test.Editor ".to_string(), WindowTitleMode::Full);
+        save_app_config(&path, &config).unwrap();
+
+        let loaded = load_app_config(&path).unwrap();
+        assert_eq!(loaded.window_title_mode, WindowTitleMode::Hidden);
+        assert_eq!(
+            loaded.title_privacy_overrides.get("com.test.editor"),
+            Some(&WindowTitleMode::Full)
+        );
+        assert_eq!(loaded.title_privacy_overrides.len(), 1);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn bundle_list_is_trimmed_lowercased_sorted_and_deduped() {
+        let normalized = normalize_bundle_list(&[
+            " Com.Zeta.App ",
+            "com.alpha.app",
+            "",
+            "COM.ALPHA.APP",
+            "   ",
+            "com.zeta.app",
+        ]);
+        assert_eq!(normalized, vec!["com.alpha.app", "com.zeta.app"]);
+        assert_eq!(normalize_bundle_list(&normalized), normalized);
+    }
+
+    #[test]
+    fn excluded_bundle_ids_are_normalized_on_load() {
+        let mut path = std::env::temp_dir();
+        let stamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        path.push(format!("typepulse-config-excluded-{stamp}.json"));
+        let config = AppConfig {
+            excluded_bundle_ids: vec![
+                "com.zeta.app".to_string(),
+                " COM.Alpha.App".to_string(),
+                "com.alpha.app".to_string(),
+            ],
+            ..AppConfig::default()
+        };
+        save_app_config(&path, &config).unwrap();
+
+        let loaded = load_app_config(&path).unwrap();
+        assert_eq!(
+            loaded.excluded_bundle_ids,
+            vec!["com.alpha.app".to_string(), "com.zeta.app".to_string()]
+        );
+        save_app_config(&path, &loaded).unwrap();
+        assert_eq!(
+            load_app_config(&path).unwrap().excluded_bundle_ids,
+            loaded.excluded_bundle_ids
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn config_source_marks_only_changed_groups_as_user() {
+        let defaults = AppConfig::default();
+        assert!(defaults
+            .config_source()
+            .values()
+            .all(|source| *source == ConfigSource::Default));
+
+        let config = AppConfig {
+            flush_interval_secs: 30,
+            menu_bar_display_mode: MenuBarDisplayMode::IconOnly,
+            ..AppConfig::default()
+        };
+        let source = config.config_source();
+        assert_eq!(source["timing"], ConfigSource::User);
+        assert_eq!(source["tray"], ConfigSource::User);
+        assert_eq!(source["capture"], ConfigSource::Default);
+        assert_eq!(source["privacy"], ConfigSource::Default);
+        assert_eq!(source["shortcuts"], ConfigSource::Default);
+        assert_eq!(source["goals"], ConfigSource::Default);
+
+        // Announcement bookkeeping is not a user setting.
+        let config = AppConfig {
+            app_time_goals_notified: HashMap::from([(
+                "com.test.editor".to_string(),
+                "2026-02-09".to_string(),
+            )]),
+            ..AppConfig::default()
+        };
+        assert_eq!(config.config_source()["goals"], ConfigSource::Default);
+    }
+
+    #[test]
+    fn effective_config_clamps_zero_intervals() {
+        let config = AppConfig {
+            collector_tick_interval_secs: 0,
+            flush_interval_secs: 0,
+            session_gap_secs: 0,
+            tray_update_interval_secs: 0,
+            burst_min_keys: 0,
+            burst_max_gap_ms: 0,
+            deep_work_min_minutes: 0,
+            deep_work_min_keys_per_5min: 0,
+            minute_resolution_days: 0,
+            shortcut_min_modifiers: 0,
+            ..AppConfig::default()
+        };
+        let effective = config.effective();
+        assert_eq!(effective.flush_interval_secs, 5);
+        assert_eq!(effective.session_gap_secs, 1);
+        assert_eq!(effective.tray_update_interval_secs, 1);
+        assert_eq!(effective.burst_min_keys, 1);
+        assert_eq!(effective.burst_max_gap_ms, 1);
+        assert_eq!(effective.deep_work_min_minutes, 5);
+        assert_eq!(effective.deep_work_min_keys_per_5min, 1);
+        assert_eq!(effective.minute_resolution_days, 1);
+        assert_eq!(effective.shortcut_min_modifiers, 1);
+        assert_eq!(effective.collector_tick_interval_secs, 1);
+    }
+
+    #[test]
+    fn timing_settings_clamp_on_load_and_reject_out_of_range_updates() {
+        let loaded = AppConfig {
+            collector_tick_interval_secs: 600,
+            flush_interval_secs: 86_400,
+            session_gap_secs: 3_600,
+            tray_update_interval_secs: 120,
+            maintenance_idle_secs: 1,
+            maintenance_max_defer_secs: 86_400,
+            ..AppConfig::default()
+        };
+        assert_eq!(loaded.collector_tick_interval(), Duration::from_secs(60));
+        assert_eq!(loaded.flush_interval(), Duration::from_secs(3600));
+        assert_eq!(loaded.session_gap(), Duration::from_secs(600));
+        assert_eq!(loaded.tray_update_interval(), Duration::from_secs(60));
+        assert_eq!(loaded.maintenance_idle(), Duration::from_secs(5));
+        assert_eq!(loaded.maintenance_max_defer(), Duration::from_secs(21_600));
+
+        let mut config = AppConfig::default();
+        assert_eq!(
+            config.set_timing_settings(1, 4, 5, 1, 30, 900),
+            Err("flush_interval_secs must be between 5 and 3600".to_string())
+        );
+        assert_eq!(
+            config.set_timing_settings(2, 30, 601, 1, 30, 900),
+            Err("session_gap_secs must be between 1 and 600".to_string())
+        );
+        assert_eq!(
+            config.set_timing_settings(2, 30, 10, 5, 30, 30),
+            Err("maintenance_max_defer_secs must be between 60 and 21600".to_string())
+        );
+        assert_eq!(config.flush_interval_secs, 60);
+        assert_eq!(config.set_timing_settings(2, 30, 10, 5, 60, 1_800), Ok(()));
+        assert_eq!(config.collector_tick_interval_secs, 2);
+        assert_eq!(config.flush_interval_secs, 30);
+        assert_eq!(config.session_gap_secs, 10);
+        assert_eq!(config.tray_update_interval_secs, 5);
+        assert_eq!(config.maintenance_idle_secs, 60);
+        assert_eq!(config.maintenance_max_defer_secs, 1_800);
+    }
+
+    #[test]
+    fn redact_fields_masks_present_values_only() {
+        let mut value = serde_json::json!({
+            "api_token": "secret",
+            "encryption_key_id": null,
+            "flush_interval_secs": 60,
+        });
+        redact_fields(&mut value, &["api_token", "encryption_key_id", "missing"]);
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "api_token": "[redacted]",
+                "encryption_key_id": null,
+                "flush_interval_secs": 60,
+            })
+        );
+    }
+
+    #[test]
+    fn profiles_resolve_data_dirs_and_route_overridden_settings() {
+        let mut config = AppConfig {
+            profiles: vec![
+                ProfileConfig {
+                    name: "work".to_string(),
+                    excluded_bundle_ids: Some(vec!["com.slack.slack".to_string()]),
+                    ..ProfileConfig::default()
+                },
+                ProfileConfig {
+                    name: "escape".to_string(),
+                    data_subdir: "../outside".to_string(),
+                    ..ProfileConfig::default()
+                },
+            ],
+            ..AppConfig::default()
+        };
+        let data_dir = Path::new("/data");
+        assert_eq!(
+            config.profile_names(),
+            vec![
+                "default".to_string(),
+                "work".to_string(),
+                "escape".to_string()
+            ]
+        );
+        assert_eq!(
+            config.profile_data_dir(data_dir, DEFAULT_PROFILE),
+            Ok(PathBuf::from("/data"))
+        );
+        assert_eq!(
+            config.profile_data_dir(data_dir, "work"),
+            Ok(PathBuf::from("/data/work"))
+        );
+        assert!(config.profile_data_dir(data_dir, "escape").is_err());
+        assert!(config.profile_data_dir(data_dir, "missing").is_err());
+
+        // The default profile reads and writes the top-level fields.
+        let global = config.excluded_bundle_ids.clone();
+        assert_eq!(config.for_active_profile().excluded_bundle_ids, global);
+
+        // An override replaces the top-level list for its profile and takes its edits; the
+        // rules it does not override still come from the top level.
+        config.active_profile = "work".to_string();
+        assert_eq!(
+            config.for_active_profile().excluded_bundle_ids,
+            vec!["com.slack.slack".to_string()]
+        );
+        config.set_excluded_bundle_ids(vec!["com.tinyspeck.slackmacgap".to_string()]);
+        config.set_shortcut_rules(ShortcutRules {
+            min_modifiers: 2,
+            ..ShortcutRules::default()
+        });
+        assert_eq!(config.excluded_bundle_ids, global);
+        assert_eq!(
+            config.profiles[0].excluded_bundle_ids,
+            Some(vec!["com.tinyspeck.slackmacgap".to_string()])
+        );
+        assert_eq!(config.shortcut_min_modifiers, 2);
+        assert_eq!(config.profiles[0].shortcut_rules, None);
+    }
+
+    #[test]
+    fn effective_config_payload_shape_is_stable() {
+        let payload =
+            serde_json::to_value(EffectiveConfig::from_config(&AppConfig::default()).unwrap())
+                .unwrap();
+        let object = payload.as_object().unwrap();
+        assert_eq!(
+            object.keys().collect::<Vec<_>>(),
+            vec!["config", "config_source"]
+        );
+        let config_keys: Vec<&str> = object["config"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            config_keys,
+            vec![
+                "active_profile",
+                "app_time_goal_notifications",
+                "app_time_goals",
+                "app_time_goals_notified",
+                "applescript_enabled",
+                "auto_export",
+                "auto_update_check",
+                "ax_sensitive_detection",
+                "ax_sensitive_title_keywords",
+                "burst_max_gap_ms",
+                "burst_min_keys",
+                "collector_tick_interval_secs",
+                "confirm_quit",
+                "copy_paste_window_secs",
+                "csv_delimiter",
+                "csv_headers_localized",
+                "csv_utf8_bom",
+                "deep_work_min_keys_per_5min",
+                "deep_work_min_minutes",
+                "demo_mode",
+                "display_labels",
+                "excluded_bundle_ids",
+                "flush_interval_secs",
+                "ignore_key_combos",
+                "main_window_bounds",
+                "maintenance_idle_secs",
+                "maintenance_max_defer_secs",
+                "menu_bar_display_mode",
+                "min_keys_to_display",
+                "minute_resolution_days",
+                "non_typing_suggestions_handled",
+                "one_password_suggestion_handled",
+                "prevent_app_nap",
+                "profile_auto_cooldown_mins",
+                "profile_auto_fallback",
+                "profile_auto_hold_secs",
+                "profile_auto_rules",
+                "profiles",
+                "raw_export_max_window_hours",
+                "record_event_chunks",
+                "session_gap_secs",
+                "shortcut_allow_alt_only",
+                "shortcut_allowlist",
+                "shortcut_blocklist",
+                "shortcut_min_modifiers",
+                "shortcut_require_cmd_or_ctrl",
+                "shortcuts_paused",
+                "snapshot_v2",
+                "start_hidden",
+                "storage_backend",
+                "symbol_key_names",
+                "telemetry_enabled",
+                "telemetry_endpoint",
+                "title_privacy_overrides",
+                "track_chord_aborts",
+                "tray_left_click_action",
+                "tray_shortcuts_toggle",
+                "tray_update_interval_secs",
+                "update_check_url",
+                "weekly_comparison_notifications",
+                "window_title_aliases",
+                "window_title_mode",
+                "write_status_file",
+            ]
+        );
+        assert_eq!(
+            object["config_source"],
+            serde_json::json!({
+                "capture": "default",
+                "export": "default",
+                "goals": "default",
+                "privacy": "default",
+                "profiles": "default",
+                "shortcuts": "default",
+                "storage": "default",
+                "timing": "default",
+                "tray": "default",
+                "updates": "default",
+                "window": "default",
+            })
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! App Nap prevention.
+//! Holds an NSProcessInfo activity while capture is running so macOS does not throttle the
+//! collector tick, which would leave the capture context stale while the window is hidden.
+
+use std::time::Duration;
+
+/// No counted key-down for this long counts as idle; the assertion is released until typing
+/// resumes.
+pub(crate) const APP_NAP_IDLE_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// Whether the activity assertion should be held: the setting is on, capture is neither
+/// paused nor auto-paused, and typing was seen recently.
+pub(crate) fn should_prevent_app_nap(
+    enabled: bool,
+    capturing: bool,
+    since_typing: Duration,
+) -> bool {
+    enabled && capturing && since_typing < APP_NAP_IDLE_AFTER
+}
+
+/// Owner of the activity assertion; the activity ends when it is released or dropped.
+#[derive(Default)]
+pub(crate) struct AppNapAssertion {
+    activity: Option<platform::Activity>,
+}
+
+impl AppNapAssertion {
+    /// Whether an activity is currently held. Always false where App Nap does not exist.
+    pub(crate) fn is_held(&self) -> bool {
+        self.activity.is_some()
+    }
+
+    /// Take or release the assertion to match `hold`. Returns the new held state when it
+    /// changed, so the caller can log the transition.
+    pub(crate) fn sync(&mut self, hold: bool) -> Option<bool> {
+        if hold == self.is_held() {
+            return None;
+        }
+        self.activity = if hold {
+            platform::Activity::begin()
+        } else {
+            None
+        };
+        (self.is_held() == hold).then_some(hold)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    // NSActivityUserInitiatedAllowingIdleSystemSleep: opts out of App Nap and timer
+    // coalescing but still lets the Mac go to sleep when the user walks away.
+    const ACTIVITY_OPTIONS: u64 = 0x00FF_FFFF & !(1 << 20);
+
+    pub(super) struct Activity(id);
+
+    // The activity token is an opaque immutable NSObject; NSProcessInfo accepts
+    // `endActivity:` from any thread, so the token may move with the collector state.
+    unsafe impl Send for Activity {}
+
+    impl Activity {
+        pub(super) fn begin() -> Option<Self> {
+            unsafe {
+                let info: id = msg_send![class!(NSProcessInfo), processInfo];
+                if info == nil {
+                    return None;
+                }
+                let reason =
+                    NSString::alloc(nil).init_str("TypePulse is recording typing activity");
+                let token: id =
+                    msg_send![info, beginActivityWithOptions: ACTIVITY_OPTIONS reason: reason];
+                let _: () = msg_send![reason, release];
+                if token == nil {
+                    return None;
+                }
+                // The token is autoreleased and the collector thread has no pool to drain.
+                let _: id = msg_send![token, retain];
+                Some(Activity(token))
+            }
+        }
+    }
+
+    impl Drop for Activity {
+        fn drop(&mut self) {
+            unsafe {
+                let info: id = msg_send![class!(NSProcessInfo), processInfo];
+                if info != nil {
+                    let _: () = msg_send![info, endActivity: self.0];
+                }
+                let _: () = msg_send![self.0, release];
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    pub(super) struct Activity;
+
+    impl Activity {
+        pub(super) fn begin() -> Option<Self> {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{should_prevent_app_nap, APP_NAP_IDLE_AFTER};
+
+    #[test]
+    fn app_nap_is_prevented_only_while_capturing_recent_typing() {
+        let recent = Duration::from_secs(3);
+        assert!(should_prevent_app_nap(true, true, recent));
+        assert!(!should_prevent_app_nap(false, true, recent));
+        assert!(!should_prevent_app_nap(true, false, recent));
+        assert!(should_prevent_app_nap(
+            true,
+            true,
+            APP_NAP_IDLE_AFTER - Duration::from_millis(1)
+        ));
+        assert!(!should_prevent_app_nap(true, true, APP_NAP_IDLE_AFTER));
+    }
+}
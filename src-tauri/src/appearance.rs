@@ -0,0 +1,103 @@
+//! System appearance.
+//! Reads the menu bar appearance (light/dark) and the "reduce transparency" accessibility
+//! setting so the tray can pick legible icons and drop vibrancy when asked to.
+
+/// Menu bar appearance the tray icon has to stay legible on.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum SystemAppearance {
+    #[default]
+    Light,
+    Dark,
+}
+
+/// Current system appearance. Only macOS reports dark mode; other platforms stay light.
+pub(crate) fn current_appearance() -> SystemAppearance {
+    if is_dark_mode() {
+        SystemAppearance::Dark
+    } else {
+        SystemAppearance::Light
+    }
+}
+
+/// Remembers the last observed appearance so pollers only react to real changes.
+#[derive(Debug, Default)]
+pub(crate) struct AppearanceWatcher {
+    last: Option<SystemAppearance>,
+}
+
+impl AppearanceWatcher {
+    /// Record `appearance` and return true when it differs from the previous observation.
+    /// The first observation always counts as a change.
+    pub(crate) fn observe(&mut self, appearance: SystemAppearance) -> bool {
+        self.last.replace(appearance) != Some(appearance)
+    }
+}
+
+// `AppleInterfaceStyle` is only present in the global domain (as "Dark") while dark mode is
+// on. NSUserDefaults is thread-safe and picks up changes from other processes, so the tray
+// thread can poll it without hopping to the main thread.
+#[cfg(target_os = "macos")]
+fn is_dark_mode() -> bool {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+        if defaults == nil {
+            return false;
+        }
+        let key = NSString::alloc(nil).init_str("AppleInterfaceStyle");
+        let style: id = msg_send![defaults, stringForKey: key];
+        let _: () = msg_send![key, release];
+        if style == nil {
+            return false;
+        }
+        let bytes: *const std::os::raw::c_char = msg_send![style, UTF8String];
+        !bytes.is_null()
+            && std::ffi::CStr::from_ptr(bytes)
+                .to_string_lossy()
+                .eq_ignore_ascii_case("dark")
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn is_dark_mode() -> bool {
+    false
+}
+
+/// Whether the user asked the system to reduce transparency (Accessibility > Display).
+#[cfg(target_os = "macos")]
+pub(crate) fn reduce_transparency() -> bool {
+    use cocoa::base::{id, nil, BOOL, NO};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+        if workspace == nil {
+            return false;
+        }
+        let reduce: BOOL = msg_send![workspace, accessibilityDisplayShouldReduceTransparency];
+        reduce != NO
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn reduce_transparency() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AppearanceWatcher, SystemAppearance};
+
+    #[test]
+    fn appearance_watcher_reports_first_observation_and_changes_only() {
+        let mut watcher = AppearanceWatcher::default();
+        assert!(watcher.observe(SystemAppearance::Light));
+        assert!(!watcher.observe(SystemAppearance::Light));
+        assert!(watcher.observe(SystemAppearance::Dark));
+        assert!(!watcher.observe(SystemAppearance::Dark));
+        assert!(watcher.observe(SystemAppearance::Light));
+    }
+}
@@ -0,0 +1,281 @@
+//! AppleScript control.
+//! Handles the Apple Events declared in `TypePulse.sdef` (pause, resume, toggle and today's
+//! totals) through the same state paths as the commands. Gated by `applescript_enabled`;
+//! every invocation is logged with the app that sent it.
+
+// Only the macOS handler reaches the verbs; they still build everywhere so the tests run on
+// every platform.
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use tauri::Manager;
+
+use crate::{collector, set_paused_and_notify, AppState};
+
+/// Event class shared by every verb; the sdef code of a command is this class plus the
+/// verb's event id.
+pub(crate) const EVENT_CLASS: u32 = four_char_code(b"TyPl");
+
+/// Verbs scripts can send.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ScriptVerb {
+    Pause,
+    Resume,
+    Toggle,
+    TodayKeys,
+    TodayActiveMinutes,
+}
+
+impl ScriptVerb {
+    pub(crate) const ALL: [ScriptVerb; 5] = [
+        ScriptVerb::Pause,
+        ScriptVerb::Resume,
+        ScriptVerb::Toggle,
+        ScriptVerb::TodayKeys,
+        ScriptVerb::TodayActiveMinutes,
+    ];
+
+    pub(crate) fn event_id(self) -> u32 {
+        four_char_code(match self {
+            ScriptVerb::Pause => b"paus",
+            ScriptVerb::Resume => b"resm",
+            ScriptVerb::Toggle => b"togl",
+            ScriptVerb::TodayKeys => b"tkey",
+            ScriptVerb::TodayActiveMinutes => b"tmin",
+        })
+    }
+
+    pub(crate) fn from_event_id(event_id: u32) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|verb| verb.event_id() == event_id)
+    }
+
+    /// Command name as scripts spell it.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ScriptVerb::Pause => "pause",
+            ScriptVerb::Resume => "resume",
+            ScriptVerb::Toggle => "toggle",
+            ScriptVerb::TodayKeys => "today keys",
+            ScriptVerb::TodayActiveMinutes => "today active minutes",
+        }
+    }
+}
+
+/// Value returned to the script: the paused flag after a pause verb, or a getter's count.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ScriptReply {
+    Paused(bool),
+    Count(u64),
+}
+
+pub(crate) const fn four_char_code(code: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*code)
+}
+
+/// Run one scripted verb for `source` (the sending app). Rejected while the setting is off.
+pub(crate) fn run_script_verb(
+    app: &tauri::AppHandle,
+    verb: ScriptVerb,
+    source: &str,
+) -> Result<ScriptReply, String> {
+    let state = app.state::<AppState>();
+    let enabled = state
+        .config
+        .lock()
+        .map(|config| config.applescript_enabled)
+        .unwrap_or(false);
+    if !enabled {
+        let _ = collector::append_app_log(
+            &state.app_log_path,
+            &format!(
+                "applescript {} from {} rejected: disabled",
+                verb.as_str(),
+                source
+            ),
+        );
+        return Err("AppleScript control is disabled in TypePulse settings".to_string());
+    }
+    let _ = collector::append_app_log(
+        &state.app_log_path,
+        &format!("applescript {} from {}", verb.as_str(), source),
+    );
+    let via = format!("applescript from {}", source);
+    match verb {
+        ScriptVerb::Pause => set_paused_and_notify(app, &via, |_| true),
+        ScriptVerb::Resume => set_paused_and_notify(app, &via, |_| false),
+        ScriptVerb::Toggle => set_paused_and_notify(app, &via, |paused| !paused),
+        ScriptVerb::TodayKeys | ScriptVerb::TodayActiveMinutes => {}
+    }
+    let summary = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?
+        .today_summary();
+    Ok(match verb {
+        ScriptVerb::Pause | ScriptVerb::Resume | ScriptVerb::Toggle => {
+            ScriptReply::Paused(summary.paused)
+        }
+        ScriptVerb::TodayKeys => ScriptReply::Count(summary.key_count),
+        ScriptVerb::TodayActiveMinutes => ScriptReply::Count(summary.active_typing_ms / 60_000),
+    })
+}
+
+/// Register the Apple Event handlers. A no-op where AppleScript does not exist.
+pub(crate) fn install(app: &tauri::AppHandle) {
+    platform::install(app);
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::sync::OnceLock;
+
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Object, Sel};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    use super::{four_char_code, run_script_verb, ScriptReply, ScriptVerb, EVENT_CLASS};
+
+    const KEY_DIRECT_OBJECT: u32 = four_char_code(b"----");
+    const KEY_ERROR_NUMBER: u32 = four_char_code(b"errn");
+    const KEY_ERROR_STRING: u32 = four_char_code(b"errs");
+    const KEY_SENDER_PID_ATTR: u32 = four_char_code(b"spid");
+    // errAEEventFailed: the script shows the error string.
+    const ERR_EVENT_FAILED: i32 = -10000;
+
+    // Apple Events arrive on the main thread with no way to pass context, so the handler
+    // reaches the app through this handle.
+    static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+    pub(super) fn install(app: &tauri::AppHandle) {
+        if APP.set(app.clone()).is_err() {
+            return;
+        }
+        let Some(mut decl) = ClassDecl::new("TypePulseAppleEventHandler", class!(NSObject)) else {
+            return;
+        };
+        unsafe {
+            decl.add_method(
+                sel!(handleAppleEvent:withReplyEvent:),
+                handle_apple_event as extern "C" fn(&Object, Sel, id, id),
+            );
+            let class = decl.register();
+            // Never released: the manager does not retain handlers and they serve until exit.
+            let handler: id = msg_send![class, new];
+            let manager: id = msg_send![class!(NSAppleEventManager), sharedAppleEventManager];
+            if manager == nil {
+                return;
+            }
+            for verb in ScriptVerb::ALL {
+                let _: () = msg_send![manager,
+                    setEventHandler: handler
+                    andSelector: sel!(handleAppleEvent:withReplyEvent:)
+                    forEventClass: EVENT_CLASS
+                    andEventID: verb.event_id()];
+            }
+        }
+    }
+
+    extern "C" fn handle_apple_event(_this: &Object, _cmd: Sel, event: id, reply: id) {
+        let Some(app) = APP.get() else {
+            return;
+        };
+        unsafe {
+            let event_id: u32 = msg_send![event, eventID];
+            let Some(verb) = ScriptVerb::from_event_id(event_id) else {
+                return;
+            };
+            let result = run_script_verb(app, verb, &sender(event));
+            if reply == nil {
+                return;
+            }
+            match result {
+                Ok(ScriptReply::Paused(paused)) => {
+                    let value: id = msg_send![class!(NSAppleEventDescriptor),
+                        descriptorWithBoolean: paused as u8];
+                    let _: () = msg_send![reply,
+                        setParamDescriptor: value forKeyword: KEY_DIRECT_OBJECT];
+                }
+                Ok(ScriptReply::Count(count)) => {
+                    let value: id = msg_send![class!(NSAppleEventDescriptor),
+                        descriptorWithInt32: count.min(i32::MAX as u64) as i32];
+                    let _: () = msg_send![reply,
+                        setParamDescriptor: value forKeyword: KEY_DIRECT_OBJECT];
+                }
+                Err(err) => {
+                    let number: id = msg_send![class!(NSAppleEventDescriptor),
+                        descriptorWithInt32: ERR_EVENT_FAILED];
+                    let _: () = msg_send![reply,
+                        setParamDescriptor: number forKeyword: KEY_ERROR_NUMBER];
+                    let text = NSString::alloc(nil).init_str(&err);
+                    let message: id =
+                        msg_send![class!(NSAppleEventDescriptor), descriptorWithString: text];
+                    let _: () = msg_send![reply,
+                        setParamDescriptor: message forKeyword: KEY_ERROR_STRING];
+                    let _: () = msg_send![text, release];
+                }
+            }
+        }
+    }
+
+    // Sending app as "bundle id (pid N)", falling back to the pid alone.
+    unsafe fn sender(event: id) -> String {
+        let pid_desc: id = msg_send![event, attributeDescriptorForKeyword: KEY_SENDER_PID_ATTR];
+        if pid_desc == nil {
+            return "unknown sender".to_string();
+        }
+        let pid: i32 = msg_send![pid_desc, int32Value];
+        let app: id = msg_send![class!(NSRunningApplication),
+            runningApplicationWithProcessIdentifier: pid];
+        if app == nil {
+            return format!("pid {}", pid);
+        }
+        let bundle_id: id = msg_send![app, bundleIdentifier];
+        if bundle_id == nil {
+            return format!("pid {}", pid);
+        }
+        let bytes: *const std::os::raw::c_char = msg_send![bundle_id, UTF8String];
+        if bytes.is_null() {
+            return format!("pid {}", pid);
+        }
+        let bundle_id = std::ffi::CStr::from_ptr(bytes).to_string_lossy();
+        format!("{} (pid {})", bundle_id, pid)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    pub(super) fn install(_app: &tauri::AppHandle) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScriptVerb, EVENT_CLASS};
+
+    #[test]
+    fn script_verbs_round_trip_and_match_the_scripting_definition() {
+        let sdef = include_str!("../TypePulse.sdef");
+        let class = EVENT_CLASS.to_be_bytes();
+        let class = std::str::from_utf8(&class).unwrap();
+        for verb in ScriptVerb::ALL {
+            assert_eq!(ScriptVerb::from_event_id(verb.event_id()), Some(verb));
+            let id = verb.event_id().to_be_bytes();
+            let code = format!("{}{}", class, std::str::from_utf8(&id).unwrap());
+            assert!(
+                sdef.contains(&format!(
+                    "<command name=\"{}\" code=\"{}\"",
+                    verb.as_str(),
+                    code
+                )),
+                "sdef lacks {}",
+                verb.as_str()
+            );
+        }
+        assert_eq!(
+            ScriptVerb::from_event_id(super::four_char_code(b"quit")),
+            None
+        );
+    }
+}
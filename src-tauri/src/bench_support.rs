@@ -0,0 +1,75 @@
+//! Bench support module.
+//! Public entry points for the criterion benchmarks in `benches/`, which only see the crate's
+//! public API. Built with the `bench-support` feature; the app itself never uses it.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::Local;
+
+use crate::app_config::AppConfig;
+use crate::collector::{
+    generate_large_history, seeded_collector_state, snapshot_shortcut_rows_by_range,
+    snapshot_top_keys_by_range, stored_input_analytics, CollectorState, ShortcutRangeStats,
+    StatsRow, StatsSnapshot, TopKeysRangeStats, BENCH_HISTORY,
+};
+use crate::shutdown::Shutdown;
+use crate::storage::{DetailStorage, JsonFileStorage, StoredInputAnalytics};
+
+/// Seed of the benchmark history, fixed so runs compare like with like.
+const BENCH_SEED: u64 = 0x4245_4e43_4821;
+
+/// Collector state loaded with the benchmark history: 100k stats rows and 20k event chunks of
+/// 500 events over the last 30 days.
+pub struct BenchState {
+    state: CollectorState,
+}
+
+/// Analytics payload of a `BenchState`, ready to be saved.
+pub struct BenchAnalytics(StoredInputAnalytics);
+
+impl BenchState {
+    /// Generate the benchmark history ending now. Takes a few seconds in release builds.
+    pub fn large() -> Self {
+        let history = generate_large_history(BENCH_SEED, Local::now().naive_local(), BENCH_HISTORY);
+        let state = seeded_collector_state(
+            PathBuf::new(),
+            PathBuf::new(),
+            PathBuf::new(),
+            &AppConfig::default(),
+            Arc::new(Shutdown::default()),
+            history,
+        );
+        Self { state }
+    }
+
+    /// Full snapshot the frontend polls every second.
+    pub fn snapshot(&self) -> StatsSnapshot {
+        self.state.snapshot()
+    }
+
+    /// Every stats row, sorted.
+    pub fn snapshot_rows(&self) -> Vec<StatsRow> {
+        self.state.snapshot_rows().unwrap_or_default()
+    }
+
+    /// Today's shortcut leaderboard, replayed from the event chunks.
+    pub fn today_shortcuts(&self) -> ShortcutRangeStats {
+        snapshot_shortcut_rows_by_range(&self.state, "today", 5)
+    }
+
+    /// Today's top keys, replayed from the event chunks.
+    pub fn today_top_keys(&self) -> TopKeysRangeStats {
+        snapshot_top_keys_by_range(&self.state, "today")
+    }
+
+    /// Analytics as the next flush would save them.
+    pub fn analytics(&mut self) -> BenchAnalytics {
+        BenchAnalytics(stored_input_analytics(&mut self.state))
+    }
+}
+
+/// Save `analytics` as daily files next to `dir/typingstats-details.json`.
+pub fn save_input_analytics(dir: &Path, analytics: &BenchAnalytics) -> Result<(), String> {
+    JsonFileStorage::new(dir.join("typingstats-details.json")).save_input_analytics(&analytics.0)
+}
@@ -2,52 +2,210 @@
 //! Owns runtime state, lifecycle bootstrap, and cross-module wiring.
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
+    panic::AssertUnwindSafe,
     path::PathBuf,
     sync::{Arc, Mutex},
+    thread::JoinHandle,
     time::{Duration, Instant},
 };
 
+use chrono::{Local, NaiveDate};
 use serde::Serialize;
 
-use crate::app_config::{AppConfig, MenuBarDisplayMode};
-use crate::storage::{DetailStorage, JsonFileStorage, StoredInputAnalytics};
+use crate::app_config::{
+    normalize_bundle_list, AppConfig, AutoExportConfig, MenuBarDisplayMode, StorageBackend,
+    SymbolKeyNames, TrayLeftClickAction, WindowTitleMode,
+};
+use crate::app_nap::AppNapAssertion;
+use crate::shutdown::Shutdown;
+use crate::storage::{
+    load_auto_export_state, DetailStorage, JsonFileStorage, LoadProgress, SqliteStorage,
+    StoredKeySpan, StoredStatusFile,
+};
 
+mod activity;
+mod app_goal;
+mod app_log;
+mod app_summary;
+mod app_switch;
+mod auto_export;
+mod burst;
+mod chord;
+mod chunk_stats;
+mod comparison;
 mod context;
+mod copy_paste;
+mod coverage;
+mod crash_dump;
+mod current_context;
+mod deep_work;
+mod demo;
+mod destructive;
+mod display;
+mod effort;
+mod event_time;
 mod events;
+mod export_chain;
+mod fixtures;
+mod focus;
+mod heatmap;
+mod history_load;
 mod io;
+mod key_hold;
+mod key_names;
+mod key_span;
 mod listener;
+mod live_session;
+mod maintenance;
+mod minute_key;
+mod minute_series;
 mod modifier;
+mod non_typing;
+mod parquet_export;
+mod profile_auto;
+mod purge;
+mod raw_export;
+mod reconstruct;
+mod rolling_avg;
+mod runtime;
+mod sensitive_field;
 mod shortcut;
+mod shortcut_breadth;
+mod shortcut_series;
 mod state_api;
+mod stats_query;
+mod stats_rows;
+mod status_file;
+mod storage_health;
+mod timeline;
+mod title_alias;
+mod today_json;
+mod top_windows;
+mod watchdog;
+mod weekly;
+#[cfg(not(target_os = "macos"))]
+mod window_apps;
+mod window_count;
+mod xlsx_export;
 
+use self::activity::ActivityClass;
+use self::app_goal::parse_goal_notified;
+use self::app_switch::AppSwitchDay;
+use self::auto_export::{run_auto_export, AUTO_EXPORT_STATE_FILE};
+use self::burst::{expire_burst, BurstDay, BurstRun, BurstThresholds};
+use self::chunk_stats::ChunkCounters;
 use self::context::{capture_context, CaptureContext, CollectorEvent};
+use self::copy_paste::{expire_pending_copy, CopyPasteDay, PendingCopy};
+use self::coverage::CoverageDay;
+use self::current_context::{CurrentContext, TypingContext};
+use self::deep_work::DeepWorkThresholds;
+#[cfg(not(target_os = "macos"))]
+use self::event_time::system_time_ms;
 use self::events::{
-    apply_collector_event, on_non_modifier_key_down, on_non_modifier_key_up,
+    apply_collector_event, on_modifiers_changed, on_non_modifier_key_down, on_non_modifier_key_up,
     reset_active_typing_state,
 };
+use self::export_chain::EXPORT_CHAIN_FILE;
+use self::key_names::canonical_shortcut_id_set;
+use self::maintenance::{
+    queue_maintenance, run_maintenance_if_due, MaintenanceJob, MaintenanceScheduler,
+};
 use self::modifier::ModifierSnapshot;
 #[cfg(not(target_os = "macos"))]
 use self::modifier::ModifierState;
+use self::runtime::{begin_runtime_span, record_runtime, RuntimeSpan};
+use self::status_file::{write_status_file_if_due, STATUS_FILE};
+use self::storage_health::{flush_if_due, probe_storage, probe_storage_if_due, StorageHealth};
 
+pub use self::app_goal::AppGoalProgress;
+pub use self::app_log::{append_app_log, flush_app_log, flush_logs, log_to_stdout_only};
+pub use self::app_summary::{snapshot_app_summary, AppSummary};
+pub use self::app_switch::{snapshot_app_switch_stats, AppSwitchStats};
+pub use self::burst::{snapshot_burst_stats, BurstStats};
+pub use self::chord::{snapshot_chord_abort_stats, ChordAbortStats};
+pub use self::chunk_stats::{snapshot_analytics_stats, AnalyticsStats};
+pub use self::comparison::{snapshot_comparison, Comparison, ComparisonError};
 pub use self::context::{bundle_id_from_app_path, running_apps, RunningAppInfo};
+pub use self::copy_paste::{snapshot_copy_paste_stats, CopyPasteStats};
+pub use self::coverage::{snapshot_coverage, Coverage};
+pub use self::crash_dump::{recover_crash_dumps, write_crash_dump};
+pub use self::deep_work::{snapshot_deep_work_blocks, DeepWorkBlock};
+pub use self::demo::new_demo_collector_state;
+pub use self::destructive::{preview_destructive_action, DestructiveAction, DestructivePreview};
+pub use self::display::{
+    set_displays, snapshot_display_stats, watch_displays, DisplayBounds, DisplayStats,
+};
+pub use self::effort::{snapshot_effort_stats, EffortStats};
 #[cfg(test)]
 use self::events::should_ignore_keypress;
-pub use self::io::append_app_log;
+pub use self::export_chain::{verify_export, ExportVerification};
+#[cfg(feature = "bench-support")]
+pub(crate) use self::fixtures::{
+    generate_large_history, seeded_collector_state, stored_input_analytics, BENCH_HISTORY,
+};
+pub use self::focus::{snapshot_focus_scores, FocusDayScore};
+pub use self::heatmap::{render_key_heatmap_svg, snapshot_key_heatmap, KeyHeatmap};
+pub use self::history_load::start_history_load;
+use self::history_load::{load_history, merge_history};
 use self::io::write_csv;
+pub(crate) use self::io::{CsvDialect, CSV_COLUMNS};
+pub use self::key_hold::{snapshot_key_hold_stats, KeyHoldStats};
 #[cfg(target_os = "macos")]
 use self::listener::listen_keypress_macos;
 #[cfg(not(target_os = "macos"))]
 use self::listener::on_key_event_non_macos;
+use self::live_session::LiveSession;
+pub(crate) use self::live_session::LiveSessionSummary;
+pub use self::minute_key::MinuteKey;
+pub(crate) use self::minute_key::DAY_KEY_FORMAT;
+use self::minute_series::{rotate_minute_ring, MinuteRing};
+pub use self::minute_series::{snapshot_minute_series, MinuteSeries};
+use self::non_typing::refresh_non_typing_suggestions;
+pub use self::non_typing::NonTypingSuggestion;
+pub use self::parquet_export::{
+    ensure_parquet_export_enabled, snapshot_parquet_export, write_parquet_export,
+    ParquetExportReport,
+};
+pub(crate) use self::parquet_export::{PARQUET_EVENT_COLUMNS, PARQUET_STATS_COLUMNS};
+use self::profile_auto::{evaluate_profile_rules, ProfileAutoSwitch};
+pub use self::purge::AppPurgeReport;
+pub use self::raw_export::{
+    snapshot_raw_events, write_raw_events, RawExportConsent, RawExportReport,
+};
+pub use self::reconstruct::{
+    ensure_debug_tools_enabled, reconstruct_day, AppReconstructionDiff, DayReconstruction,
+};
+use self::rolling_avg::{refresh_rolling_averages, RollingAverages};
+use self::sensitive_field::{focused_field, sensitive_keywords, FocusedField, SensitiveFieldCache};
 use self::shortcut::{
-    build_stored_input_analytics, flush_expired_open_chunk, rebuild_shortcut_usage_from_chunks,
-    snapshot_shortcut_rows, InputEventChunk, OpenInputEventChunk,
+    build_stored_input_analytics, flush_expired_open_chunk, snapshot_shortcut_rows,
+    InputEventChunk, OpenInputEventChunk,
 };
+pub(crate) use self::shortcut::{canonical_shortcut_id, shortcut_app_limit};
 pub use self::shortcut::{snapshot_shortcut_rows_by_range, snapshot_top_keys_by_range};
+pub use self::shortcut_breadth::{snapshot_shortcut_breadth, ShortcutBreadth};
+pub(crate) use self::shortcut_series::DEFAULT_SHORTCUT_SERIES_DAYS;
+pub use self::shortcut_series::{snapshot_shortcut_daily_series, ShortcutDayCount};
+use self::state_api::compute_today_totals;
+pub(crate) use self::stats_query::DEFAULT_STATS_PAGE_SIZE;
+pub use self::stats_query::{snapshot_stats_row_page, StatsRowFilter, StatsRowPage, StatsRowSort};
+pub(crate) use self::stats_rows::DEFAULT_DAILY_TOTAL_DAYS;
+pub use self::stats_rows::{snapshot_daily_totals, snapshot_stats_rows, DailyTotal};
+pub use self::storage_health::flush_now;
+pub use self::timeline::{snapshot_app_timeline, AppTimeline};
+pub(crate) use self::today_json::TODAY_JSON_ARG;
+pub use self::today_json::{load_today_summary_json, TodaySummaryJson};
+pub(crate) use self::top_windows::top_windows_limit;
+pub use self::top_windows::{snapshot_top_windows, TopWindows};
+pub use self::watchdog::start_tick_watchdog;
+use self::watchdog::{panic_message, TickWatchdog};
+pub use self::weekly::{snapshot_weekly_comparison, WeeklyComparison};
+pub use self::xlsx_export::{snapshot_stats_export, write_stats_xlsx, XlsxExportReport};
 
 #[derive(Clone, Hash, Eq, PartialEq)]
 pub(crate) struct StatsKey {
-    pub(crate) date: String,
+    pub(crate) date: MinuteKey,
     pub(crate) app_name: String,
     pub(crate) window_title: String,
 }
@@ -57,16 +215,22 @@ pub(crate) struct StatsValue {
     pub(crate) active_typing_ms: u64,
     pub(crate) key_count: u64,
     pub(crate) session_count: u64,
+    /// Part of `active_typing_ms` held after a writing key (letters, digits, punctuation).
+    pub(crate) writing_ms: u64,
+    /// Part of `active_typing_ms` held after a navigation key or a counted shortcut.
+    pub(crate) navigation_ms: u64,
 }
 
 #[derive(Serialize, Clone)]
 pub struct StatsRow {
-    pub date: String,
+    pub date: MinuteKey,
     pub app_name: String,
     pub window_title: String,
     pub active_typing_ms: u64,
     pub key_count: u64,
     pub session_count: u64,
+    pub writing_ms: u64,
+    pub navigation_ms: u64,
 }
 
 /// App-level usage count for one shortcut in snapshot payload.
@@ -82,6 +246,8 @@ pub struct ShortcutStatRow {
     pub shortcut_id: String,
     pub count: u64,
     pub apps: Vec<ShortcutAppUsageRow>,
+    /// Uses in apps beyond the per-row app limit, so `apps` plus this adds up to `count`.
+    pub other_count: u64,
 }
 
 /// Per-key usage count row used by top-key ranking payload.
@@ -89,6 +255,26 @@ pub struct ShortcutStatRow {
 pub struct KeyUsageRow {
     pub key: String,
     pub count: u64,
+    /// Most used apps, as in `ShortcutStatRow`; empty where counts are not kept per app (the
+    /// heat map's unplaced keys).
+    pub apps: Vec<ShortcutAppUsageRow>,
+    pub other_count: u64,
+}
+
+/// Shortcut leaderboard for one range. With event chunk recording off the rows are the
+/// lifetime aggregates and `degraded_range_support` is set.
+#[derive(Serialize, Clone, Default)]
+pub struct ShortcutRangeStats {
+    pub rows: Vec<ShortcutStatRow>,
+    pub degraded_range_support: bool,
+}
+
+/// Top keys for one range. With event chunk recording off the rows cover every chunk still
+/// held in memory, regardless of range, and `degraded_range_support` is set.
+#[derive(Serialize, Clone, Default)]
+pub struct TopKeysRangeStats {
+    pub rows: Vec<KeyUsageRow>,
+    pub degraded_range_support: bool,
 }
 
 #[derive(Serialize, Clone)]
@@ -97,14 +283,121 @@ pub struct StatsSnapshot {
     pub paused: bool,
     pub auto_paused: bool,
     pub auto_pause_reason: Option<String>,
+    /// Shortcut counting is paused while key counts and typing time keep recording.
+    pub shortcuts_paused: bool,
     pub keyboard_active: bool,
     pub ignore_key_combos: bool,
+    pub track_chord_aborts: bool,
+    pub prevent_app_nap: bool,
+    pub app_nap_assertion_held: bool,
     pub excluded_bundle_ids: Vec<String>,
     pub one_password_suggestion_pending: bool,
+    /// Apps whose keys look like game or navigation input, pending an exclusion decision.
+    pub non_typing_suggestions: Vec<NonTypingSuggestion>,
     pub tray_display_mode: String,
+    pub tray_left_click_action: String,
+    pub start_hidden: bool,
+    pub min_keys_to_display: u64,
+    pub window_title_mode: String,
+    pub title_privacy_overrides: BTreeMap<String, String>,
     pub last_error: Option<String>,
+    pub storage_degraded: bool,
+    /// The data directory exists and accepted the latest probe write.
+    pub storage_writable: bool,
+    /// Stored history is still loading in the background; rows only cover keys since launch.
+    pub loading: bool,
+    /// Demo mode: the stats are generated and nothing is written to disk.
+    pub demo_mode: bool,
+    /// Daily files read so far out of `load_files_total`, for a load progress bar.
+    pub load_files_processed: u64,
+    pub load_files_total: u64,
+    /// Daily data files were deleted or modified outside the app since launch.
+    pub external_changes_detected: bool,
+    /// The tick loop stopped reporting and was restarted by the watchdog since launch.
+    pub tick_stalled: bool,
+    pub update_available: bool,
     pub log_path: String,
+    /// Every shortcut, or the 50 most used with `snapshot_v2`.
     pub shortcut_stats: Vec<ShortcutStatRow>,
+    /// Shortcuts recorded in total, embedded in `shortcut_stats` or not.
+    pub shortcut_total_count: u64,
+    /// Stats rows in memory in total; `rows` only carries today's with `snapshot_v2`.
+    pub row_total_count: u64,
+    /// `rows` and `shortcut_stats` are capped; ranges come from `get_stats_rows` and
+    /// `get_daily_totals`.
+    pub snapshot_v2: bool,
+    pub today_focus_score: Option<u8>,
+    pub today_coverage_pct: Option<f64>,
+    pub goal_progress: Vec<AppGoalProgress>,
+    pub current_context: Option<CurrentContext>,
+    /// Mean daily key count and active typing time over the last 7 and 30 finished days;
+    /// days the app did not record are left out. None without any such day.
+    pub avg_keys_7d: Option<f64>,
+    pub avg_keys_30d: Option<f64>,
+    pub avg_active_ms_7d: Option<f64>,
+    pub avg_active_ms_30d: Option<f64>,
+    /// Today's key count above (positive) or below (negative) the 7-day average, in percent.
+    pub today_vs_avg_pct: Option<f64>,
+    /// Today's key-downs weighted by finger travel; a home-row press adds 1.0.
+    pub effort_today: f64,
+    /// Minutes of today inside deep work blocks.
+    pub deep_work_minutes_today: u64,
+    /// Wall-clock epoch ms of today's first and last counted key-down; None before the first.
+    pub day_first_key_ms: Option<i64>,
+    pub day_last_key_ms: Option<i64>,
+}
+
+/// Lightweight today-only view used by tray rendering without building a full snapshot.
+#[derive(Clone, Default)]
+pub(crate) struct TodaySummary {
+    pub(crate) key_count: u64,
+    pub(crate) active_typing_ms: u64,
+    /// Writing and navigation parts of `active_typing_ms`.
+    pub(crate) writing_ms: u64,
+    pub(crate) navigation_ms: u64,
+    pub(crate) paused: bool,
+    pub(crate) auto_paused: bool,
+    pub(crate) auto_pause_reason: Option<String>,
+    pub(crate) shortcuts_paused: bool,
+    pub(crate) menu_bar_display_mode: MenuBarDisplayMode,
+    pub(crate) goal_progress: Vec<AppGoalProgress>,
+    /// Running typing session; None while paused or after the session gap elapsed.
+    pub(crate) live_session: Option<LiveSessionSummary>,
+    /// Key counts of the last 60 minutes, oldest first, for the sparkline tray mode.
+    pub(crate) minute_counts: Vec<u64>,
+    /// The data directory probe failed; inverted so the default summary shows no warning.
+    pub(crate) storage_unwritable: bool,
+}
+
+impl TodaySummary {
+    pub(crate) fn capture_state(&self) -> CaptureStateChanged {
+        CaptureStateChanged {
+            paused: self.paused,
+            auto_paused: self.auto_paused,
+            reason: self.auto_pause_reason.clone(),
+        }
+    }
+}
+
+/// Payload of the `capture-state-changed` event pushed to the webview on pause/resume.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CaptureStateChanged {
+    pub(crate) paused: bool,
+    pub(crate) auto_paused: bool,
+    pub(crate) reason: Option<String>,
+}
+
+/// Cached totals for one local day, kept in sync by the event handlers. `date` is None until
+/// the first scan.
+#[derive(Clone, Default)]
+pub(crate) struct TodayTotals {
+    pub(crate) date: Option<NaiveDate>,
+    pub(crate) key_count: u64,
+    pub(crate) active_typing_ms: u64,
+    pub(crate) writing_ms: u64,
+    pub(crate) navigation_ms: u64,
+    /// Active typing time per app id (bundle id or app name), lowercased.
+    pub(crate) app_active_typing_ms: HashMap<String, u64>,
 }
 
 /// Runtime aggregate for one normalized shortcut id.
@@ -117,6 +410,8 @@ pub(crate) struct ShortcutUsageValue {
 pub struct CollectorState {
     // 统计聚合的明细数据（按时间/应用/窗口维度）
     stats: HashMap<StatsKey, StatsValue>,
+    // 当日汇总缓存（托盘刷新使用，避免每秒构建完整快照）
+    today_totals: TodayTotals,
     // 最近一次“有效输入活动”时间点，用于计算会话间隔
     last_typing_instant: Instant,
     // 最近一次 tick 时间点，用于精确累加 active_typing_ms
@@ -127,8 +422,18 @@ pub struct CollectorState {
     collector_tick_interval: Duration,
     // 统计刷盘周期
     flush_interval: Duration,
+    // 托盘摘要刷新周期（托盘线程每次休眠前读取）
+    tray_update_interval: Duration,
     // 会话判定阈值
     session_gap: Duration,
+    // 分钟级明细保留天数，更早的数据按小时汇总
+    minute_resolution_days: u64,
+    // 最近一次执行小时汇总的日期（每天最多自动执行一次）
+    last_rollup_date: Option<String>,
+    // 最近一次执行月度归档的月份（每月最多自动执行一次）
+    last_archive_month: Option<String>,
+    // 重负载磁盘任务（大体积分析数据保存、小时汇总、月度归档）的空闲调度队列
+    maintenance: MaintenanceScheduler,
     // 是否暂停采集
     paused: bool,
     // 当前是否因黑名单/安全输入而自动暂停记录
@@ -141,16 +446,61 @@ pub struct CollectorState {
     ignore_key_combos: bool,
     // 菜单栏显示模式
     menu_bar_display_mode: MenuBarDisplayMode,
+    // 托盘图标左键单击行为
+    tray_left_click_action: TrayLeftClickAction,
+    // 启动时是否隐藏主面板
+    start_hidden: bool,
+    // 应用汇总中单独列出的最少按键数，低于该值的应用并入“其他”（0 表示全部列出）
+    min_keys_to_display: u64,
+    // 全局窗口标题记录方式
+    window_title_mode: WindowTitleMode,
+    // 按应用覆盖的窗口标题记录方式（key 为小写 Bundle ID）
+    title_privacy_overrides: HashMap<String, WindowTitleMode>,
+    // 窗口标题别名（小写应用 ID -> 被合并的标题 -> 目标标题），采集时改写标题
+    window_title_aliases: HashMap<String, HashMap<String, String>>,
     // 忽略采集应用的 Bundle ID 列表
     excluded_bundle_ids: HashSet<String>,
     // 首次 1Password 建议是否待处理
     one_password_suggestion_pending: bool,
+    // 待处理的“非打字应用”忽略建议（游戏/导航类按键占比过高）
+    non_typing_suggestions: Vec<NonTypingSuggestion>,
+    // 已接受或忽略过建议的应用（小写 Bundle ID），不再重复提示
+    non_typing_handled: HashSet<String>,
+    // 最近一次评估非打字应用的日期（每天最多一次）
+    last_non_typing_check: Option<NaiveDate>,
+    // 近 7 天与 30 天的日均按键数与输入时长（按日期缓存，跨过午夜后重新计算）
+    rolling_averages: RollingAverages,
     // 最近一次错误信息（用于前端提示）
     last_error: Option<String>,
-    // 当前按下的非修饰键集合（用于消除长按自动重复）
-    pressed_non_modifier_keys: HashSet<String>,
+    // 刷盘失败记录：连续失败次数、退避重试时间与是否已降级。
+    storage_health: StorageHealth,
+    // 数据目录是否存在且可写（启动时与 tick 中定期探测；不可写时暂停刷盘，恢复后立即刷盘）
+    storage_writable: bool,
+    // 快照是否只携带今天的明细行与前 50 个快捷键（snapshot_v2）。
+    snapshot_v2: bool,
+    // 演示模式：统计数据为生成的假数据，存于内存，不写入任何文件。
+    demo_mode: bool,
+    // 最近一次探测数据目录的时间点
+    last_storage_probe: Instant,
+    // 运行期间是否发现数据文件被外部删除或修改（刷盘前检查）
+    external_changes_detected: bool,
+    // tick 循环心跳与代数：看门狗据此发现卡死并重启循环。
+    tick_watchdog: TickWatchdog,
+    // 最近 60 分钟每分钟按键数（环形缓冲，托盘迷你图与实时图表使用）
+    minute_ring: MinuteRing,
+    // 最近一次版本检查是否发现更新的版本（未检查或检查失败时为 false）。
+    update_available: bool,
+    // 当前按下的非修饰键及其最近一次按下/自动重复的时间（用于消除长按自动重复；
+    // 超过 MAX_KEY_HOLD 未松开视为丢失 key-up 并清除，前台应用切换时整体清空）
+    pressed_non_modifier_keys: HashMap<String, Instant>,
     // 当前持续输入归属的统计维度键（用于 tick 累加 active_typing_ms）
     active_stats_key: Option<StatsKey>,
+    // 最近一次计入的按键类别（书写/导航），决定 tick 累加的活跃时长记入哪一项
+    activity_class: ActivityClass,
+    // 当前正在接收输入的应用及开始时间（暂停、自动暂停或超过会话间隔未输入时清空）
+    typing_context: Option<TypingContext>,
+    // 当前会话的开始时间与按键数（跨过会话间隔的按键开启新会话，暂停不会清空）
+    live_session: Option<LiveSession>,
     // 快捷键聚合统计（key 为标准化 shortcut id）
     shortcut_usage: HashMap<String, ShortcutUsageValue>,
     // 应用字典（app_ref -> app_id），用于压缩事件 chunk 存储。
@@ -161,6 +511,8 @@ pub struct CollectorState {
     next_app_ref: u32,
     // 已完成的事件 chunk（用于可选重算/调试）。
     event_chunks: Vec<InputEventChunk>,
+    // 已完成 chunk 的增量计数（事件总数、估算体积、按日事件数），随 chunk 写入/淘汰同步更新。
+    chunk_counters: ChunkCounters,
     // 当前正在写入的 chunk。
     open_event_chunk: Option<OpenInputEventChunk>,
     // 快捷键规则：是否必须包含 Cmd/Ctrl。
@@ -173,119 +525,322 @@ pub struct CollectorState {
     shortcut_allowlist: HashSet<String>,
     // 快捷键黑名单（优先级高于白名单）。
     shortcut_blocklist: HashSet<String>,
+    // 是否暂停快捷键统计：不累计快捷键次数、不记录快捷键按键事件，按键数与打字时长照常记录。
+    shortcuts_paused: bool,
+    // 按 Shift 输入的数字与符号键的命名方式（记录按键、重放事件与快捷键 id 共用）
+    symbol_key_names: SymbolKeyNames,
+    // 是否统计未完成的组合键尝试。
+    track_chord_aborts: bool,
+    // 采集期间是否阻止 App Nap。
+    prevent_app_nap: bool,
+    // 是否记录按键事件 chunk；关闭时不再追加事件、落盘不写 chunk，范围查询退化为全部历史。
+    record_event_chunks: bool,
+    // 是否通过辅助功能识别密码类输入框；识别到时不记录按键事件与快捷键。
+    ax_sensitive_detection: bool,
+    // 识别敏感输入框的关键词（小写）。
+    ax_sensitive_title_keywords: Vec<String>,
+    // 最近一次焦点输入框探测结果（按前台应用与窗口标题缓存，限频）。
+    sensitive_field: SensitiveFieldCache,
+    // 当前持有的 App Nap 活动断言，暂停、自动暂停或空闲时释放。
+    app_nap: AppNapAssertion,
+    // 进行中的组合键尝试：按下修饰键后尚未按下其他键，记录期间按过的修饰键并集。
+    chord_attempt: Option<ModifierSnapshot>,
+    // 未完成组合键计数（本地日期 -> 应用 -> 次数）。
+    chord_aborts: HashMap<String, HashMap<String, u64>>,
+    // 最近一次 tick 或按键时的前台应用，用于统计应用切换。
+    last_foreground_app: Option<String>,
+    // 输入过程中的应用切换（本地日期 -> 总次数与来源/目标应用对计数）。
+    app_switches: HashMap<String, AppSwitchDay>,
+    // 按应用的每日活跃打字时长目标（key 为小写 Bundle ID，value 为分钟）。
+    app_time_goals: HashMap<String, u64>,
+    // 应用时长目标达成时是否发送通知。
+    app_time_goal_notifications: bool,
+    // 各应用最近一次发送目标达成通知的日期，保证每个应用每天最多通知一次。
+    app_goal_notified: HashMap<String, NaiveDate>,
+    // 每周一是否发送上周与前一周的对比通知。
+    weekly_comparison_notifications: bool,
+    // 已发送的周对比（发送当天的本地日期 -> 对比的周一日期），随分析数据保存，重启后不重复通知。
+    weekly_comparisons: HashMap<String, String>,
+    // 每日结束后的自动导出设置
+    auto_export: AutoExportConfig,
+    // 自动导出进度标记文件路径（与明细文件同目录）
+    auto_export_state_path: PathBuf,
+    // 最近一次完成导出的本地日期，None 表示尚未开始导出
+    last_auto_export_day: Option<NaiveDate>,
+    // 导出失败后的下次重试时间，避免每个 tick 重复失败
+    auto_export_retry_at: Option<Instant>,
+    // 签名导出的哈希链文件路径（与明细文件同目录，每个数据集一条链）
+    pub(crate) export_chain_path: PathBuf,
+    // 是否定时写入数据目录下的 status.json，供脚本读取
+    write_status_file: bool,
+    // status.json 路径（与明细文件同目录）
+    status_file_path: PathBuf,
+    // 最近一次检查/写入 status.json 的时间，用于限制写入频率
+    last_status_write: Option<Instant>,
+    // 最近一次写入的内容，除时间戳外未变化时跳过写入
+    last_status_file: Option<StoredStatusFile>,
+    // 每日专注度汇总（本地日期 -> 汇总），在 flush 时刷新今天和昨天。
+    focus_days: HashMap<String, FocusDayScore>,
+    // 采集覆盖台账（本地日期 -> 记录中/暂停/监听失效的时长），由 tick 累加。
+    coverage: HashMap<String, CoverageDay>,
+    // 应用运行时段台账（按开始时间排序，最后一段为当前运行段），用于区分未运行与无输入。
+    runtime_spans: Vec<RuntimeSpan>,
+    // 数据配置自动切换：规则、防抖候选与手动切换后的冷却期，待切换请求由托盘循环执行。
+    profile_auto: ProfileAutoSwitch,
+    // 连续输入段（burst）判定阈值：最少按键数与最大按键间隔。
+    burst_thresholds: BurstThresholds,
+    // 进行中的连续输入段，按键间隔超过阈值或 tick 发现已过期时结算。
+    burst_run: Option<BurstRun>,
+    // 每日连续输入段汇总（本地日期 -> 段数、总按键数、总时长与最长段），按段开始时间归属日期。
+    burst_days: HashMap<String, BurstDay>,
+    // 深度工作块判定阈值：最短时长与每 5 分钟的最少按键数。
+    deep_work_thresholds: DeepWorkThresholds,
+    // 每日输入负荷（本地日期 -> 按键位权重累加的千分值），主键区静止位一次按键记 1000。
+    effort_days: HashMap<String, u64>,
+    // 每日各显示器按键数（本地日期 -> 显示器 id -> 按键数），无法确定显示器的按键不计入。
+    display_days: HashMap<String, HashMap<String, u64>>,
+    // 复制后等待配对的粘贴时间窗（毫秒）。
+    copy_paste_window_ms: i64,
+    // 尚未配对粘贴的复制（时间与应用），只记录这两项，从不读取剪贴板内容；暂停时清空。
+    pending_copy: Option<PendingCopy>,
+    // 每日复制到粘贴的配对（本地日期 -> 配对数、未配对复制数、延迟样本与来源/目标应用对）。
+    copy_paste_days: HashMap<String, CopyPasteDay>,
+    // 每日第一次与最后一次计数按键的时间（本地日期 -> 毫秒时间戳），跨过本地午夜后按新日期重新记录。
+    key_spans: HashMap<String, StoredKeySpan>,
+    // CSV 写出格式（分隔符、UTF-8 BOM、表头语言），汇总 CSV 与自动导出共用
+    csv_dialect: CsvDialect,
     // CSV 汇总文件路径
     pub log_path: PathBuf,
     // 应用运行日志文件路径
     pub app_log_path: PathBuf,
     // 明细数据的存储实现
-    storage: Box<dyn DetailStorage>,
+    storage: Arc<dyn DetailStorage>,
+    // 启动时后台加载历史数据的进度；加载并合并完成前为 Some，期间暂停写盘
+    history_load: Option<Arc<LoadProgress>>,
+    // 前台应用上下文采集函数（测试中可替换为固定上下文）
+    context_provider: Box<dyn Fn() -> CaptureContext + Send>,
+    // 焦点输入框探测函数（测试中可替换为固定结果）
+    focused_field_provider: Box<dyn Fn() -> Option<FocusedField> + Send>,
+    // 后台线程停止信号（请求停止后 tick 循环退出，键盘回调不再修改状态）
+    shutdown: Arc<Shutdown>,
     #[cfg(not(target_os = "macos"))]
     modifier_state: ModifierState,
 }
 
+/// Build the runtime state without its stored history, which `start_history_load` then loads
+/// on a background thread; keys are recorded into the fresh state meanwhile.
 pub fn new_collector_state(
     log_path: PathBuf,
     app_log_path: PathBuf,
     detail_path: PathBuf,
     config: &AppConfig,
+    shutdown: Arc<Shutdown>,
+) -> CollectorState {
+    let load_log_path = app_log_path.clone();
+    let mut state = empty_collector_state(
+        log_path,
+        app_log_path,
+        detail_path,
+        config,
+        shutdown,
+        &|message| {
+            let _ = append_app_log(&load_log_path, message);
+        },
+    );
+    state.history_load = Some(Arc::new(LoadProgress::default()));
+    probe_storage(&mut state, Instant::now());
+    state
+}
+
+// Load stored stats and analytics into a fresh state. Load notes go to `log`, so read-only
+// callers such as the `--today-json` CLI can stay out of the app log.
+fn load_collector_state(
+    log_path: PathBuf,
+    app_log_path: PathBuf,
+    detail_path: PathBuf,
+    config: &AppConfig,
+    shutdown: Arc<Shutdown>,
+    log: &dyn Fn(&str),
+) -> CollectorState {
+    let mut state =
+        empty_collector_state(log_path, app_log_path, detail_path, config, shutdown, log);
+    let history = load_history(state.storage.as_ref(), &LoadProgress::default());
+    merge_history(&mut state, history, log);
+    state
+}
+
+// State with `config` applied and no stored history loaded yet.
+fn empty_collector_state(
+    log_path: PathBuf,
+    app_log_path: PathBuf,
+    detail_path: PathBuf,
+    config: &AppConfig,
+    shutdown: Arc<Shutdown>,
+    log: &dyn Fn(&str),
 ) -> CollectorState {
+    let config = &config.for_active_profile();
     let now = Instant::now();
-    let storage: Box<dyn DetailStorage> = Box::new(JsonFileStorage { path: detail_path });
-    let stats = storage.load_stats().unwrap_or_default();
-    let analytics = storage.load_input_analytics().unwrap_or_default();
-    let StoredInputAnalytics {
-        shortcut_usage: stored_shortcut_usage,
-        app_dict,
-        next_app_ref,
-        event_chunks: stored_event_chunks,
-    } = analytics;
-    let app_ref_by_app: HashMap<String, u32> = app_dict
-        .iter()
-        .map(|(app_ref, app_id)| (app_id.clone(), *app_ref))
-        .collect();
-    let shortcut_usage = stored_shortcut_usage
-        .into_iter()
-        .map(|(shortcut_id, usage)| {
-            (
-                shortcut_id,
-                ShortcutUsageValue {
-                    count: usage.count,
-                    by_app: usage.by_app,
-                },
-            )
-        })
-        .collect();
-    let event_chunks = stored_event_chunks
-        .into_iter()
-        .map(|chunk| InputEventChunk {
-            v: chunk.v,
-            chunk_start_ms: chunk.chunk_start_ms,
-            app_ref: chunk.app_ref,
-            events: chunk.events,
-        })
-        .collect();
-    if !stats.is_empty() {
-        let _ = append_app_log(
-            &app_log_path,
-            &format!("loaded {} detail rows from storage", stats.len()),
-        );
-    }
+    let auto_export_state_path = detail_path.with_file_name(AUTO_EXPORT_STATE_FILE);
+    let export_chain_path = detail_path.with_file_name(EXPORT_CHAIN_FILE);
+    let status_file_path = detail_path.with_file_name(STATUS_FILE);
+    let last_auto_export_day = match load_auto_export_state(&auto_export_state_path) {
+        Ok(marker) => marker
+            .last_exported_day
+            .and_then(|day| NaiveDate::parse_from_str(&day, DAY_KEY_FORMAT).ok()),
+        Err(err) => {
+            log(&format!("auto export marker load failed: {}", err));
+            None
+        }
+    };
+    let storage: Arc<dyn DetailStorage> = match config.storage_backend {
+        StorageBackend::Json => Arc::new(JsonFileStorage::new(detail_path)),
+        StorageBackend::Sqlite => Arc::new(SqliteStorage::new(detail_path, app_log_path.clone())),
+    };
     let mut state = CollectorState {
-        stats,
+        stats: HashMap::new(),
+        today_totals: TodayTotals::default(),
         last_typing_instant: now,
         last_tick_instant: now,
         last_flush_instant: now,
         collector_tick_interval: config.collector_tick_interval(),
         flush_interval: config.flush_interval(),
+        tray_update_interval: config.tray_update_interval(),
         session_gap: config.session_gap(),
+        minute_resolution_days: config.minute_resolution_days.max(1),
+        last_rollup_date: None,
+        last_archive_month: None,
+        maintenance: MaintenanceScheduler::new(
+            config.maintenance_idle(),
+            config.maintenance_max_defer(),
+        ),
         paused: false,
         auto_paused: false,
         auto_pause_reason: None,
         keyboard_active: true,
         ignore_key_combos: config.ignore_key_combos,
         menu_bar_display_mode: config.menu_bar_display_mode,
-        excluded_bundle_ids: config
-            .excluded_bundle_ids
+        tray_left_click_action: config.tray_left_click_action,
+        start_hidden: config.start_hidden,
+        min_keys_to_display: config.min_keys_to_display,
+        window_title_mode: config.window_title_mode,
+        title_privacy_overrides: config
+            .title_privacy_overrides
+            .iter()
+            .map(|(bundle_id, mode)| (bundle_id.to_ascii_lowercase(), *mode))
+            .collect(),
+        window_title_aliases: config
+            .window_title_aliases
             .iter()
-            .map(|v| v.to_ascii_lowercase())
+            .map(|(app_id, aliases)| (app_id.to_ascii_lowercase(), aliases.clone()))
+            .collect(),
+        excluded_bundle_ids: normalize_bundle_list(&config.excluded_bundle_ids)
+            .into_iter()
             .collect(),
         one_password_suggestion_pending: false,
+        non_typing_suggestions: Vec::new(),
+        non_typing_handled: normalize_bundle_list(&config.non_typing_suggestions_handled)
+            .into_iter()
+            .collect(),
+        last_non_typing_check: None,
+        rolling_averages: RollingAverages::default(),
         last_error: None,
-        pressed_non_modifier_keys: HashSet::new(),
+        pressed_non_modifier_keys: HashMap::new(),
         active_stats_key: None,
-        shortcut_usage,
-        app_dict,
-        app_ref_by_app,
-        next_app_ref: next_app_ref.max(1),
-        event_chunks,
+        activity_class: ActivityClass::default(),
+        typing_context: None,
+        live_session: None,
+        shortcut_usage: HashMap::new(),
+        app_dict: HashMap::new(),
+        app_ref_by_app: HashMap::new(),
+        next_app_ref: 1,
+        chunk_counters: ChunkCounters::default(),
+        event_chunks: Vec::new(),
         open_event_chunk: None,
         shortcut_require_cmd_or_ctrl: config.shortcut_require_cmd_or_ctrl,
         shortcut_allow_alt_only: config.shortcut_allow_alt_only,
         shortcut_min_modifiers: config.shortcut_min_modifiers.max(1),
-        shortcut_allowlist: config
-            .shortcut_allowlist
-            .iter()
-            .map(|v| v.to_ascii_lowercase())
-            .collect(),
-        shortcut_blocklist: config
-            .shortcut_blocklist
+        shortcut_allowlist: canonical_shortcut_id_set(
+            &config.shortcut_allowlist,
+            config.symbol_key_names,
+        ),
+        shortcut_blocklist: canonical_shortcut_id_set(
+            &config.shortcut_blocklist,
+            config.symbol_key_names,
+        ),
+        shortcuts_paused: config.shortcuts_paused,
+        symbol_key_names: config.symbol_key_names,
+        track_chord_aborts: config.track_chord_aborts,
+        prevent_app_nap: config.prevent_app_nap,
+        record_event_chunks: config.record_event_chunks,
+        ax_sensitive_detection: config.ax_sensitive_detection,
+        ax_sensitive_title_keywords: sensitive_keywords(&config.ax_sensitive_title_keywords),
+        sensitive_field: SensitiveFieldCache::default(),
+        app_nap: AppNapAssertion::default(),
+        storage_health: StorageHealth::default(),
+        history_load: None,
+        storage_writable: true,
+        demo_mode: false,
+        snapshot_v2: config.snapshot_v2,
+        last_storage_probe: now,
+        external_changes_detected: false,
+        tick_watchdog: TickWatchdog::new(now),
+        minute_ring: MinuteRing::default(),
+        update_available: false,
+        chord_attempt: None,
+        chord_aborts: HashMap::new(),
+        last_foreground_app: None,
+        app_switches: HashMap::new(),
+        app_time_goals: config
+            .app_time_goals
             .iter()
-            .map(|v| v.to_ascii_lowercase())
+            .filter(|(_, target_min)| **target_min > 0)
+            .map(|(bundle_id, target_min)| (bundle_id.to_ascii_lowercase(), *target_min))
             .collect(),
+        app_time_goal_notifications: config.app_time_goal_notifications,
+        app_goal_notified: parse_goal_notified(&config.app_time_goals_notified),
+        weekly_comparison_notifications: config.weekly_comparison_notifications,
+        weekly_comparisons: HashMap::new(),
+        auto_export: config.auto_export.clone(),
+        auto_export_state_path,
+        last_auto_export_day,
+        auto_export_retry_at: None,
+        export_chain_path,
+        write_status_file: config.write_status_file,
+        status_file_path,
+        last_status_write: None,
+        last_status_file: None,
+        focus_days: HashMap::new(),
+        coverage: HashMap::new(),
+        runtime_spans: Vec::new(),
+        profile_auto: ProfileAutoSwitch::from_config(config),
+        burst_thresholds: BurstThresholds::from_config(config),
+        burst_run: None,
+        deep_work_thresholds: DeepWorkThresholds::from_config(config),
+        burst_days: HashMap::new(),
+        effort_days: HashMap::new(),
+        display_days: HashMap::new(),
+        copy_paste_window_ms: (config.effective().copy_paste_window_secs * 1_000) as i64,
+        pending_copy: None,
+        copy_paste_days: HashMap::new(),
+        key_spans: HashMap::new(),
+        csv_dialect: CsvDialect::from_config(config),
         log_path,
         app_log_path,
         storage,
+        context_provider: Box::new(capture_context),
+        focused_field_provider: Box::new(focused_field),
+        shutdown,
         #[cfg(not(target_os = "macos"))]
         modifier_state: ModifierState::default(),
     };
-    // Rebuild shortcut aggregates when historical analytics only contains event chunks.
-    if state.shortcut_usage.is_empty() && !state.event_chunks.is_empty() {
-        rebuild_shortcut_usage_from_chunks(&mut state);
-    }
+    begin_runtime_span(&mut state, chrono::Utc::now().timestamp_millis());
     state
 }
 
-pub fn start_collector(state: Arc<Mutex<CollectorState>>) {
+/// Start the keyboard listener and the tick/flush loop; returns the tick loop's join handle.
+/// The OS listener blocks inside the platform hook and cannot be joined; once shutdown is
+/// requested its callbacks stop touching state.
+pub fn start_collector(state: Arc<Mutex<CollectorState>>) -> JoinHandle<()> {
     let key_state = state.clone();
     let error_state = state.clone();
     std::thread::spawn(move || {
@@ -294,8 +849,12 @@ pub fn start_collector(state: Arc<Mutex<CollectorState>>) {
 
         #[cfg(not(target_os = "macos"))]
         let result = rdev::listen(move |event| match event.event_type {
-            rdev::EventType::KeyPress(key) => on_key_event_non_macos(&key_state, key, true),
-            rdev::EventType::KeyRelease(key) => on_key_event_non_macos(&key_state, key, false),
+            rdev::EventType::KeyPress(key) => {
+                on_key_event_non_macos(&key_state, key, true, system_time_ms(event.time))
+            }
+            rdev::EventType::KeyRelease(key) => {
+                on_key_event_non_macos(&key_state, key, false, system_time_ms(event.time))
+            }
             _ => {}
         })
         .map_err(|e| format!("{:?}", e));
@@ -312,6 +871,17 @@ pub fn start_collector(state: Arc<Mutex<CollectorState>>) {
         }
     });
 
+    start_tick_loop(state)
+}
+
+// Spawn the tick/flush loop. Intervals come from state, so tests can run it with
+// millisecond ticks; it sleeps on the shutdown signal and exits as soon as stop is requested,
+// or once the watchdog retired its generation. A panicking tick is logged and the loop goes on.
+fn start_tick_loop(state: Arc<Mutex<CollectorState>>) -> JoinHandle<()> {
+    let (shutdown, generation) = match state.lock() {
+        Ok(locked) => (locked.shutdown.clone(), locked.tick_watchdog.generation()),
+        Err(_) => (Arc::new(Shutdown::default()), 0),
+    };
     let tick_state = state;
     std::thread::spawn(move || loop {
         let tick_interval = if let Ok(locked) = tick_state.lock() {
@@ -319,89 +889,361 @@ pub fn start_collector(state: Arc<Mutex<CollectorState>>) {
         } else {
             Duration::from_secs(1)
         };
-        std::thread::sleep(tick_interval);
+        if shutdown.wait_timeout(tick_interval) {
+            break;
+        }
         if let Ok(mut locked) = tick_state.lock() {
-            let now = Instant::now();
-            let elapsed = now.duration_since(locked.last_tick_instant);
-            locked.last_tick_instant = now;
-            flush_expired_open_chunk(&mut locked, chrono::Utc::now().timestamp_millis());
-            apply_collector_event(
-                &mut locked,
-                CollectorEvent::Tick {
-                    elapsed,
-                    capture_context: capture_context(),
-                    at: now,
-                },
-            );
-            if now.duration_since(locked.last_flush_instant) >= locked.flush_interval {
-                locked.last_flush_instant = now;
-                let _ = locked.storage.save_stats(&locked.stats);
-                let analytics = build_stored_input_analytics(&mut locked);
-                let _ = locked.storage.save_input_analytics(&analytics);
-                if let Ok(rows) = locked.snapshot_rows() {
-                    let _ = write_csv(&locked.log_path, &rows);
-                }
+            if locked.tick_watchdog.generation() != generation {
+                break;
+            }
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| run_tick(&mut locked)));
+            if let Err(payload) = result {
+                let error = format!("tick panicked: {}", panic_message(payload.as_ref()));
+                locked.last_error = Some(error.clone());
+                let _ = append_app_log(&locked.app_log_path, &error);
             }
+            locked.tick_watchdog.beat(Instant::now());
         }
-    });
+    })
+}
+
+// One tick: accumulate active time, evaluate auto-pause, run due exports and flushes, queue
+// rollups and archives, and run queued maintenance once the user is idle.
+fn run_tick(locked: &mut CollectorState) {
+    let now = Instant::now();
+    let elapsed = now.duration_since(locked.last_tick_instant);
+    locked.last_tick_instant = now;
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    probe_storage_if_due(locked, now);
+    flush_expired_open_chunk(locked, now);
+    record_runtime(locked, now_ms);
+    expire_burst(locked, now_ms);
+    expire_pending_copy(locked, now_ms);
+    rotate_minute_ring(locked, now_ms);
+    refresh_non_typing_suggestions(locked, Local::now().date_naive());
+    refresh_rolling_averages(locked, Local::now().date_naive());
+    locked.refresh_today_totals();
+    let capture_context = locked.current_context();
+    evaluate_profile_rules(
+        locked,
+        now,
+        Local::now().naive_local(),
+        capture_context.bundle_id.as_deref(),
+    );
+    apply_collector_event(
+        locked,
+        CollectorEvent::Tick {
+            elapsed,
+            capture_context,
+            at: now,
+        },
+    );
+    if !locked.demo_mode {
+        run_auto_export(locked, Local::now().date_naive(), now);
+        write_status_file_if_due(locked, now, now_ms);
+    }
+    if flush_if_due(locked, now) {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        if locked.last_rollup_date.as_deref() != Some(today.as_str()) {
+            locked.last_rollup_date = Some(today);
+            queue_maintenance(locked, MaintenanceJob::Rollup, now);
+        }
+        let month = Local::now().format("%Y-%m").to_string();
+        if locked.last_archive_month.as_deref() != Some(month.as_str()) {
+            locked.last_archive_month = Some(month);
+            queue_maintenance(locked, MaintenanceJob::Archive, now);
+        }
+    }
+    run_maintenance_if_due(locked, now);
 }
 
 #[cfg(test)]
 mod tests {
+    use super::app_log::LogBatcher;
+    use super::app_switch::{AppSwitchDay, MAX_SWITCH_PAIRS_PER_DAY};
+    use super::chunk_stats::ChunkCounters;
+    use super::deep_work::{detect_deep_work_blocks, DeepWorkBlock, DeepWorkThresholds};
+    use super::event_time::{epoch_ms_at, EventTime};
+    use super::events::{on_non_modifier_key_down, on_non_modifier_key_up};
+    use super::focus::{
+        focus_score, refresh_recent_focus_days, sessions_from_key_times, FocusSession,
+    };
+    use super::history_load::{load_history, merge_history, start_history_load};
+    use super::maintenance::MaintenanceScheduler;
+    use super::profile_auto::{evaluate_profile_rules, matching_rule, ProfileAutoSwitch};
+    use super::sensitive_field::SensitiveFieldCache;
+    use super::storage_health::StorageHealth;
+    #[cfg(not(target_os = "macos"))]
+    use super::ModifierState;
     use super::{
-        apply_collector_event, should_ignore_keypress, CaptureContext, CollectorEvent,
-        CollectorState, ModifierSnapshot, StatsKey, StatsValue,
+        append_app_log, apply_collector_event, build_stored_input_analytics, flush_logs,
+        rotate_minute_ring, should_ignore_keypress, snapshot_display_stats,
+        snapshot_parquet_export, snapshot_stats_export, start_tick_loop, write_parquet_export,
+        write_stats_xlsx, ActivityClass, AppPurgeReport, BurstThresholds, CaptureContext,
+        CaptureStateChanged, CollectorEvent, CollectorState, CsvDialect, MinuteKey, MinuteRing,
+        ModifierSnapshot, RollingAverages, StatsKey, StatsValue, TickWatchdog, TodayTotals,
+    };
+    use crate::app_config::{
+        AppConfig, AutoExportConfig, AutoExportFormat, MenuBarDisplayMode, ProfileAutoRule,
+        ProfileConfig, SymbolKeyNames, TrayLeftClickAction, WindowTitleMode,
+    };
+    use crate::app_nap::AppNapAssertion;
+    use crate::shutdown::Shutdown;
+    use crate::storage::{
+        DetailStorage, JsonFileStorage, LoadProgress, StoredHistory, StoredInputAnalytics,
     };
-    use crate::app_config::MenuBarDisplayMode;
-    use crate::storage::JsonFileStorage;
+    use crate::test_support::{temp_dir, TempDir};
+    use chrono::{NaiveDate, TimeZone};
     use std::{
-        collections::{HashMap, HashSet},
+        collections::{BTreeMap, HashMap, HashSet},
         path::PathBuf,
+        sync::{Arc, Mutex, OnceLock},
+        thread::JoinHandle,
         time::{Duration, Instant},
     };
 
+    // Event time of epoch ms `at_ms` on a monotonic clock that agrees with the wall clock, for
+    // feeding `append_input_event` hand-picked timestamps.
+    fn event_time_at_ms(at_ms: i64) -> EventTime {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        let origin = *ORIGIN.get_or_init(Instant::now);
+        EventTime {
+            at: origin + Duration::from_millis(at_ms as u64),
+            at_ms,
+        }
+    }
+
     fn build_state(stats: HashMap<StatsKey, StatsValue>) -> CollectorState {
         let now = Instant::now();
         CollectorState {
             stats,
+            today_totals: TodayTotals::default(),
             last_typing_instant: now,
             last_tick_instant: now,
             last_flush_instant: now,
             collector_tick_interval: Duration::from_secs(1),
+            tray_update_interval: Duration::from_secs(1),
             flush_interval: Duration::from_secs(60),
             session_gap: Duration::from_secs(5),
+            minute_resolution_days: 14,
+            last_rollup_date: None,
+            last_archive_month: None,
+            maintenance: MaintenanceScheduler::new(
+                Duration::from_secs(30),
+                Duration::from_secs(900),
+            ),
             paused: false,
             auto_paused: false,
             auto_pause_reason: None,
             keyboard_active: true,
             ignore_key_combos: false,
             menu_bar_display_mode: MenuBarDisplayMode::IconText,
+            tray_left_click_action: TrayLeftClickAction::Menu,
+            start_hidden: false,
+            min_keys_to_display: 0,
+            window_title_mode: WindowTitleMode::Full,
+            title_privacy_overrides: HashMap::new(),
+            window_title_aliases: HashMap::new(),
             excluded_bundle_ids: HashSet::new(),
             one_password_suggestion_pending: false,
+            non_typing_suggestions: Vec::new(),
+            non_typing_handled: HashSet::new(),
+            last_non_typing_check: None,
+            rolling_averages: RollingAverages::default(),
             last_error: None,
-            pressed_non_modifier_keys: HashSet::new(),
+            pressed_non_modifier_keys: HashMap::new(),
             active_stats_key: None,
+            activity_class: ActivityClass::default(),
+            typing_context: None,
+            live_session: None,
             shortcut_usage: HashMap::new(),
             app_dict: HashMap::new(),
             app_ref_by_app: HashMap::new(),
             next_app_ref: 1,
             event_chunks: Vec::new(),
+            chunk_counters: ChunkCounters::default(),
             open_event_chunk: None,
             shortcut_require_cmd_or_ctrl: true,
             shortcut_allow_alt_only: false,
             shortcut_min_modifiers: 1,
             shortcut_allowlist: HashSet::new(),
             shortcut_blocklist: HashSet::new(),
+            shortcuts_paused: false,
+            symbol_key_names: SymbolKeyNames::Physical,
+            track_chord_aborts: false,
+            prevent_app_nap: false,
+            record_event_chunks: true,
+            ax_sensitive_detection: false,
+            ax_sensitive_title_keywords: Vec::new(),
+            sensitive_field: SensitiveFieldCache::default(),
+            app_nap: AppNapAssertion::default(),
+            storage_health: StorageHealth::default(),
+            history_load: None,
+            storage_writable: true,
+            demo_mode: false,
+            snapshot_v2: false,
+            last_storage_probe: now,
+            external_changes_detected: false,
+            tick_watchdog: TickWatchdog::new(now),
+            minute_ring: MinuteRing::default(),
+            update_available: false,
+            chord_attempt: None,
+            chord_aborts: HashMap::new(),
+            last_foreground_app: None,
+            app_switches: HashMap::new(),
+            app_time_goals: HashMap::new(),
+            app_time_goal_notifications: true,
+            app_goal_notified: HashMap::new(),
+            weekly_comparison_notifications: true,
+            weekly_comparisons: HashMap::new(),
+            auto_export: AutoExportConfig::default(),
+            auto_export_state_path: PathBuf::from("auto-export-state.json"),
+            last_auto_export_day: None,
+            auto_export_retry_at: None,
+            export_chain_path: PathBuf::from("export-chain.json"),
+            write_status_file: false,
+            status_file_path: PathBuf::from("status.json"),
+            last_status_write: None,
+            last_status_file: None,
+            focus_days: HashMap::new(),
+            coverage: HashMap::new(),
+            runtime_spans: Vec::new(),
+            profile_auto: ProfileAutoSwitch::from_config(&AppConfig::default()),
+            burst_thresholds: BurstThresholds {
+                min_keys: 10,
+                max_gap_ms: 2_000,
+            },
+            burst_run: None,
+            burst_days: HashMap::new(),
+            deep_work_thresholds: DeepWorkThresholds {
+                min_minutes: 25,
+                min_keys_per_bucket: 50,
+            },
+            effort_days: HashMap::new(),
+            display_days: HashMap::new(),
+            copy_paste_window_ms: 120_000,
+            pending_copy: None,
+            copy_paste_days: HashMap::new(),
+            key_spans: HashMap::new(),
+            csv_dialect: CsvDialect::default(),
             log_path: PathBuf::from("log.csv"),
             app_log_path: PathBuf::from("app.log"),
-            storage: Box::new(JsonFileStorage {
-                path: PathBuf::from("detail.json"),
+            storage: Arc::new(JsonFileStorage::new(PathBuf::from("detail.json"))),
+            context_provider: Box::new(|| CaptureContext {
+                app_name: "Editor".to_string(),
+                window_title: "Doc".to_string(),
+                bundle_id: Some("com.test.editor".to_string()),
+                secure_input: false,
+                display_id: None,
             }),
+            focused_field_provider: Box::new(|| None),
+            shutdown: Arc::new(Shutdown::default()),
             #[cfg(not(target_os = "macos"))]
             modifier_state: ModifierState::default(),
         }
     }
 
+    // In-memory detail storage that records every stats save, and counts analytics saves, for
+    // loop integration tests.
+    #[derive(Clone, Default)]
+    struct RecordingStorage {
+        saves: Arc<Mutex<Vec<HashMap<StatsKey, StatsValue>>>>,
+        analytics_saves: Arc<Mutex<usize>>,
+    }
+
+    impl DetailStorage for RecordingStorage {
+        fn load_stats(&self) -> Result<HashMap<StatsKey, StatsValue>, String> {
+            Ok(HashMap::new())
+        }
+
+        fn save_stats(&self, stats: &HashMap<StatsKey, StatsValue>) -> Result<(), String> {
+            self.saves
+                .lock()
+                .map_err(|_| "saves lock failed".to_string())?
+                .push(stats.clone());
+            Ok(())
+        }
+
+        fn is_rolled_up(&self, _day: NaiveDate) -> bool {
+            false
+        }
+
+        fn mark_rolled_up(&self, _days: &[NaiveDate]) {}
+
+        fn load_input_analytics(&self) -> Result<StoredInputAnalytics, String> {
+            Ok(StoredInputAnalytics::default())
+        }
+
+        fn save_input_analytics(&self, _analytics: &StoredInputAnalytics) -> Result<(), String> {
+            *self
+                .analytics_saves
+                .lock()
+                .map_err(|_| "analytics saves lock failed".to_string())? += 1;
+            Ok(())
+        }
+    }
+
+    // Collector state wired to the real tick loop with millisecond intervals and memory storage.
+    struct TickLoopHarness {
+        state: Arc<Mutex<CollectorState>>,
+        handle: Option<JoinHandle<()>>,
+        shutdown: Arc<Shutdown>,
+        storage: RecordingStorage,
+        // Holds the log files; removed when the harness drops.
+        _dir: TempDir,
+    }
+
+    impl TickLoopHarness {
+        fn start(name: &str, tick_interval: Duration) -> Self {
+            let dir = temp_dir(name);
+            let storage = RecordingStorage::default();
+            let mut state = build_state(HashMap::new());
+            state.collector_tick_interval = tick_interval;
+            state.flush_interval = Duration::from_millis(20);
+            state.log_path = dir.join("log.csv");
+            state.app_log_path = dir.join("app.log");
+            state.storage = Arc::new(storage.clone());
+            let shutdown = state.shutdown.clone();
+            let state = Arc::new(Mutex::new(state));
+            let handle = Some(start_tick_loop(state.clone()));
+            Self {
+                state,
+                handle,
+                shutdown,
+                storage,
+                _dir: dir,
+            }
+        }
+
+        fn save_count(&self) -> usize {
+            self.storage.saves.lock().unwrap().len()
+        }
+
+        // Poll until `check` passes or the deadline expires.
+        fn wait_until(&self, timeout: Duration, check: impl Fn(&Self) -> bool) -> bool {
+            let deadline = Instant::now() + timeout;
+            while Instant::now() < deadline {
+                if check(self) {
+                    return true;
+                }
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            check(self)
+        }
+
+        // Request shutdown and join the loop thread.
+        fn join(&mut self) {
+            self.shutdown.request();
+            if let Some(handle) = self.handle.take() {
+                handle.join().unwrap();
+            }
+        }
+
+        fn stop(mut self) {
+            self.join();
+        }
+    }
+
     // Event-stream harness for collector unit tests. Tests can feed key/tick events in order.
     struct CollectorEventHarness {
         state: CollectorState,
@@ -417,6 +1259,7 @@ mod tests {
                     window_title: "Doc".to_string(),
                     bundle_id: Some("com.test.editor".to_string()),
                     secure_input: false,
+                    display_id: None,
                 },
             }
         }
@@ -426,7 +1269,7 @@ mod tests {
             apply_collector_event(&mut self.state, event);
         }
 
-        // Push key-down with default capture context.
+        // Push key-down with default capture context, timestamped at `at`.
         fn key_down(&mut self, key_id: &str, is_key_combo: bool, at: Instant) {
             self.push(CollectorEvent::NonModifierKeyDown {
                 physical_key_id: key_id.to_string(),
@@ -435,16 +1278,27 @@ mod tests {
                 is_key_combo,
                 capture_context: self.default_context.clone(),
                 at,
+                at_ms: epoch_ms_at(at),
             });
         }
 
-        // Push key-up for one key id.
+        // Push key-up for one key id, timestamped now.
         fn key_up(&mut self, key_id: &str) {
             self.push(CollectorEvent::NonModifierKeyUp {
                 physical_key_id: key_id.to_string(),
                 shortcut_key: key_id.to_string(),
                 modifiers: ModifierSnapshot::default(),
                 capture_context: self.default_context.clone(),
+                at: Instant::now(),
+                at_ms: chrono::Utc::now().timestamp_millis(),
+            });
+        }
+
+        // Push a modifier transition carrying the modifiers still held afterwards.
+        fn modifiers(&mut self, modifiers: ModifierSnapshot, capture_context: CaptureContext) {
+            self.push(CollectorEvent::ModifiersChanged {
+                modifiers,
+                capture_context,
             });
         }
 
@@ -476,12 +1330,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn minute_key_round_trips_every_minute_of_a_leap_day() {
+        let day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let mut previous: Option<(MinuteKey, String)> = None;
+        for minute_of_day in 0..24 * 60u16 {
+            let key = MinuteKey::new(day, minute_of_day / 60, minute_of_day % 60).unwrap();
+            let text = key.to_string();
+            assert_eq!(
+                text,
+                format!(
+                    "2024-02-29 {:02}:{:02}",
+                    minute_of_day / 60,
+                    minute_of_day % 60
+                )
+            );
+            assert_eq!(text.parse::<MinuteKey>().unwrap(), key);
+            assert_eq!(key.day(), day);
+            assert_eq!(key.minute_of_day(), minute_of_day);
+            assert_eq!(key.hour_start().minute_of_day(), minute_of_day / 60 * 60);
+            if let Some((prev_key, prev_text)) = &previous {
+                assert!(prev_key < &key && prev_text < &text);
+            }
+            previous = Some((key, text));
+        }
+        let next_day: MinuteKey = "2024-03-01 00:00".parse().unwrap();
+        assert!(previous.unwrap().0 < next_day);
+    }
+
+    #[test]
+    fn minute_key_rejects_out_of_range_values() {
+        let day = NaiveDate::from_ymd_opt(2026, 2, 9).unwrap();
+        assert!(MinuteKey::new(day, 24, 0).is_none());
+        assert!(MinuteKey::new(day, 10, 60).is_none());
+        for value in ["2026-02-30 10:00", "2026-02-09", "2026-02-09 10:00:00", ""] {
+            assert!(value.parse::<MinuteKey>().is_err(), "{value}");
+        }
+    }
+
     #[test]
     fn snapshot_rows_sorted_by_keys() {
         let mut stats = HashMap::new();
         stats.insert(
             StatsKey {
-                date: "2026-02-09 10:01".to_string(),
+                date: "2026-02-09 10:01".parse().unwrap(),
                 app_name: "B".to_string(),
                 window_title: "TitleB".to_string(),
             },
@@ -489,11 +1381,13 @@ mod tests {
                 active_typing_ms: 500,
                 key_count: 5,
                 session_count: 1,
+                writing_ms: 0,
+                navigation_ms: 0,
             },
         );
         stats.insert(
             StatsKey {
-                date: "2026-02-09 10:00".to_string(),
+                date: "2026-02-09 10:00".parse().unwrap(),
                 app_name: "A".to_string(),
                 window_title: "TitleA".to_string(),
             },
@@ -501,14 +1395,16 @@ mod tests {
                 active_typing_ms: 800,
                 key_count: 8,
                 session_count: 2,
+                writing_ms: 0,
+                navigation_ms: 0,
             },
         );
         let state = build_state(stats);
         let rows = state.snapshot_rows().unwrap();
         assert_eq!(rows.len(), 2);
-        assert_eq!(rows[0].date, "2026-02-09 10:00");
+        assert_eq!(rows[0].date.to_string(), "2026-02-09 10:00");
         assert_eq!(rows[0].app_name, "A");
-        assert_eq!(rows[1].date, "2026-02-09 10:01");
+        assert_eq!(rows[1].date.to_string(), "2026-02-09 10:01");
         assert_eq!(rows[1].app_name, "B");
     }
 
@@ -521,141 +1417,6351 @@ mod tests {
     }
 
     #[test]
-    fn set_ignore_key_combos_reflects_in_snapshot() {
-        let state = build_state(HashMap::new());
-        let mut state = state;
-        assert!(!state.snapshot().ignore_key_combos);
-        state.set_ignore_key_combos(true);
-        assert!(state.snapshot().ignore_key_combos);
-        state.set_ignore_key_combos(false);
-        assert!(!state.snapshot().ignore_key_combos);
+    fn shortcut_pause_and_capture_pause_combine_independently() {
+        let cmd = ModifierSnapshot {
+            cmd: true,
+            ..ModifierSnapshot::default()
+        };
+        for (paused, shortcuts_paused) in
+            [(false, false), (false, true), (true, false), (true, true)]
+        {
+            let mut harness = CollectorEventHarness::new();
+            harness.state.set_paused(paused);
+            harness
+                .state
+                .set_shortcuts_paused_from(shortcuts_paused, "test");
+            // cmd+k counts as a shortcut, a plain key never does.
+            let at = Instant::now();
+            harness.push(CollectorEvent::NonModifierKeyDown {
+                physical_key_id: "k".to_string(),
+                shortcut_key: "k".to_string(),
+                modifiers: cmd,
+                is_key_combo: true,
+                capture_context: harness.default_context.clone(),
+                at,
+                at_ms: epoch_ms_at(at),
+            });
+            harness.push(CollectorEvent::NonModifierKeyUp {
+                physical_key_id: "k".to_string(),
+                shortcut_key: "k".to_string(),
+                modifiers: cmd,
+                capture_context: harness.default_context.clone(),
+                at: Instant::now(),
+                at_ms: chrono::Utc::now().timestamp_millis(),
+            });
+            harness.key_down("a", false, Instant::now());
+            harness.key_up("a");
+
+            let case = format!("paused={paused} shortcuts_paused={shortcuts_paused}");
+            let key_count: u64 = harness.state.stats.values().map(|v| v.key_count).sum();
+            assert_eq!(key_count, if paused { 0 } else { 2 }, "{case}");
+            let shortcut_count: u64 = harness
+                .state
+                .shortcut_usage
+                .values()
+                .map(|usage| usage.count)
+                .sum();
+            let shortcuts_counted = !paused && !shortcuts_paused;
+            assert_eq!(shortcut_count, u64::from(shortcuts_counted), "{case}");
+            let events: Vec<&String> = harness
+                .state
+                .event_chunks
+                .iter()
+                .map(|chunk| &chunk.events)
+                .chain(
+                    harness
+                        .state
+                        .open_event_chunk
+                        .iter()
+                        .map(|chunk| &chunk.events),
+                )
+                .flatten()
+                .collect();
+            let recorded_keys: Vec<&str> = events
+                .iter()
+                .filter_map(|event| event.split(',').nth(3))
+                .collect();
+            let expected: Vec<&str> = match (paused, shortcuts_paused) {
+                (true, _) => vec![],
+                (false, true) => vec!["a", "a"],
+                (false, false) => vec!["k", "k", "a", "a"],
+            };
+            assert_eq!(recorded_keys, expected, "{case}");
+            assert_eq!(harness.state.snapshot().shortcuts_paused, shortcuts_paused);
+        }
     }
 
     #[test]
-    fn repeated_key_down_is_counted_once_until_key_up() {
+    fn sensitive_fields_count_keys_without_recording_events_or_shortcuts() {
+        use super::sensitive_field::{sensitive_keywords, FocusedField, SENSITIVE_PROBE_INTERVAL};
+        use crate::app_config::DEFAULT_SENSITIVE_TITLE_KEYWORDS;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cmd = ModifierSnapshot {
+            cmd: true,
+            ..ModifierSnapshot::default()
+        };
+        let field = |role: &str, subrole: Option<&str>, label: &str| FocusedField {
+            role: role.to_string(),
+            subrole: subrole.map(str::to_string),
+            labels: vec![label.to_string()],
+        };
+        let focused: Arc<Mutex<Option<FocusedField>>> =
+            Arc::new(Mutex::new(Some(field("AXTextField", None, "API Token"))));
+        let probes = Arc::new(AtomicUsize::new(0));
         let mut harness = CollectorEventHarness::new();
-        let now = Instant::now();
+        harness.state.ax_sensitive_title_keywords = sensitive_keywords(
+            &DEFAULT_SENSITIVE_TITLE_KEYWORDS.map(|keyword| keyword.to_string()),
+        );
+        let (provider_field, provider_probes) = (focused.clone(), probes.clone());
+        harness.state.focused_field_provider = Box::new(move || {
+            provider_probes.fetch_add(1, Ordering::SeqCst);
+            provider_field.lock().unwrap().clone()
+        });
+        let recorded = |harness: &CollectorEventHarness| {
+            let open = harness.state.open_event_chunk.as_ref();
+            harness.state.event_chunks.len() + open.map_or(0, |open| open.events.len())
+        };
+        let key_count = |harness: &CollectorEventHarness| -> u64 {
+            harness
+                .state
+                .stats
+                .values()
+                .map(|value| value.key_count)
+                .sum()
+        };
+        let base = Instant::now();
 
-        harness.key_down("k:a", false, now);
-        harness.key_down("k:a", false, now + Duration::from_millis(100));
+        // Off by default: the field is never probed.
+        harness.key_down("a", false, base);
+        harness.key_up("a");
+        assert_eq!(probes.load(Ordering::SeqCst), 0);
+        assert_eq!(recorded(&harness), 2);
 
-        let rows = harness.rows();
-        assert_eq!(rows.len(), 1);
-        assert_eq!(rows[0].key_count, 1);
+        // A token field: keys count, but neither events nor the paste shortcut are recorded.
+        harness.state.set_ax_sensitive_detection(true);
+        let start = base + Duration::from_secs(1);
+        harness.key_down("b", false, start);
+        harness.key_up("b");
+        let at = start + Duration::from_millis(100);
+        harness.push(CollectorEvent::NonModifierKeyDown {
+            physical_key_id: "v".to_string(),
+            shortcut_key: "v".to_string(),
+            modifiers: cmd,
+            is_key_combo: true,
+            capture_context: harness.default_context.clone(),
+            at,
+            at_ms: epoch_ms_at(at),
+        });
+        harness.push(CollectorEvent::NonModifierKeyUp {
+            physical_key_id: "v".to_string(),
+            shortcut_key: "v".to_string(),
+            modifiers: cmd,
+            capture_context: harness.default_context.clone(),
+            at,
+            at_ms: epoch_ms_at(at),
+        });
+        assert_eq!(key_count(&harness), 3);
+        assert_eq!(recorded(&harness), 2);
+        assert!(harness.state.shortcut_usage.is_empty());
+        assert!(harness.state.pending_copy.is_none());
+        // Both key-downs came within one probe interval of the same window.
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
 
-        harness.key_up("k:a");
-        harness.key_down("k:a", false, now + Duration::from_millis(200));
+        // Focus moved to a search box: picked up once the cached result is stale.
+        *focused.lock().unwrap() = Some(field("AXTextField", None, "Search"));
+        let later = start + SENSITIVE_PROBE_INTERVAL;
+        harness.key_down("c", false, later);
+        harness.key_up("c");
+        assert_eq!(probes.load(Ordering::SeqCst), 2);
+        assert_eq!(recorded(&harness), 4);
 
-        let rows = harness.rows();
-        assert_eq!(rows[0].key_count, 2);
+        // Secure text fields are flagged whatever their label; unreadable fields never are.
+        *focused.lock().unwrap() = Some(field("AXTextField", Some("AXSecureTextField"), ""));
+        harness.key_down("d", false, later + SENSITIVE_PROBE_INTERVAL);
+        harness.key_up("d");
+        assert_eq!(recorded(&harness), 4);
+        *focused.lock().unwrap() = None;
+        harness.key_down("e", false, later + 2 * SENSITIVE_PROBE_INTERVAL);
+        harness.key_up("e");
+        assert_eq!(recorded(&harness), 6);
+        assert_eq!(key_count(&harness), 6);
     }
 
     #[test]
-    fn active_typing_ms_is_accumulated_from_tick_while_key_held() {
-        let mut harness = CollectorEventHarness::new();
-        let now = Instant::now();
+    fn symbol_key_names_map_both_listener_spellings() {
+        use super::key_names::{canonical_key_name, canonical_shortcut_key_id};
 
-        harness.key_down("k:a", false, now);
-        harness.tick(
-            Duration::from_millis(1200),
-            now + Duration::from_millis(1200),
+        // (recorded key, shift held, physical name, produced name); macOS records symbols,
+        // older rdev builds recorded lowercased variant names.
+        let cases = [
+            ("1", true, "1", "!"),
+            ("!", true, "1", "!"),
+            ("2", true, "2", "@"),
+            ("@", true, "2", "@"),
+            ("3", true, "3", "#"),
+            ("4", true, "4", "$"),
+            ("5", true, "5", "%"),
+            ("6", true, "6", "^"),
+            ("7", true, "7", "&"),
+            ("8", true, "8", "*"),
+            ("9", true, "9", "("),
+            ("0", true, "0", ")"),
+            (")", true, "0", ")"),
+            ("1", false, "1", "1"),
+            ("-", true, "-", "_"),
+            ("minus", true, "-", "_"),
+            ("minus", false, "-", "-"),
+            ("=", true, "=", "+"),
+            ("equal", true, "=", "+"),
+            ("[", true, "[", "{"),
+            ("leftbracket", true, "[", "{"),
+            ("]", true, "]", "}"),
+            ("rightbracket", true, "]", "}"),
+            (";", true, ";", ":"),
+            ("semicolon", true, ";", ":"),
+            (":", true, ";", ":"),
+            ("'", true, "'", "\""),
+            ("quote", true, "'", "\""),
+            ("\\", true, "\\", "|"),
+            ("backslash", true, "\\", "|"),
+            (",", true, ",", "<"),
+            ("comma", true, ",", "<"),
+            (".", true, ".", ">"),
+            ("dot", false, ".", "."),
+            ("/", true, "/", "?"),
+            ("slash", true, "/", "?"),
+            ("`", true, "`", "~"),
+            ("backquote", true, "`", "~"),
+            ("a", true, "a", "a"),
+            ("enter", true, "enter", "enter"),
+        ];
+        for (key, shift, physical, produced) in cases {
+            let case = format!("{key} shift={shift}");
+            assert_eq!(
+                canonical_key_name(key, shift, SymbolKeyNames::Physical),
+                physical,
+                "{case}"
+            );
+            assert_eq!(
+                canonical_key_name(key, shift, SymbolKeyNames::Produced),
+                produced,
+                "{case}"
+            );
+        }
+        // `_` is a key of its own, not another separator.
+        assert_eq!(
+            canonical_shortcut_key_id("shift_cmd__", SymbolKeyNames::Physical),
+            "shift_cmd_-"
+        );
+        assert_eq!(
+            canonical_shortcut_key_id("ctrl_shift_1", SymbolKeyNames::Produced),
+            "ctrl_shift_!"
+        );
+        assert_eq!(
+            canonical_shortcut_key_id("cmd_1", SymbolKeyNames::Produced),
+            "cmd_1"
         );
-
-        let rows = harness.rows();
-        assert_eq!(rows.len(), 1);
-        assert_eq!(rows[0].active_typing_ms, 1200);
     }
 
     #[test]
-    fn event_stream_stops_accumulating_after_key_up() {
-        let mut harness = CollectorEventHarness::new();
-        let now = Instant::now();
+    fn shifted_symbol_keys_share_counts_and_stored_shortcuts_migrate() {
+        use super::shortcut::key_counts_in_window;
 
-        harness.key_down("k:a", false, now);
-        harness.tick(Duration::from_millis(500), now + Duration::from_millis(500));
-        harness.key_up("k:a");
-        harness.tick(
-            Duration::from_millis(700),
-            now + Duration::from_millis(1200),
+        let shift_cmd = ModifierSnapshot {
+            shift: true,
+            cmd: true,
+            ..ModifierSnapshot::default()
+        };
+        let mut harness = CollectorEventHarness::new();
+        // Stored before symbol keys had one name.
+        harness.state.shortcut_usage.insert(
+            "shift_cmd_!".to_string(),
+            super::ShortcutUsageValue {
+                count: 3,
+                by_app: HashMap::from([("com.test.editor".to_string(), 3)]),
+            },
         );
+        harness.state.set_symbol_key_names(SymbolKeyNames::Physical);
+        for key in ["1", "!"] {
+            let at = Instant::now();
+            harness.push(CollectorEvent::NonModifierKeyDown {
+                physical_key_id: "mac:18".to_string(),
+                shortcut_key: key.to_string(),
+                modifiers: shift_cmd,
+                is_key_combo: true,
+                capture_context: harness.default_context.clone(),
+                at,
+                at_ms: epoch_ms_at(at),
+            });
+            harness.push(CollectorEvent::NonModifierKeyUp {
+                physical_key_id: "mac:18".to_string(),
+                shortcut_key: key.to_string(),
+                modifiers: shift_cmd,
+                capture_context: harness.default_context.clone(),
+                at,
+                at_ms: epoch_ms_at(at),
+            });
+        }
+        let usage = &harness.state.shortcut_usage;
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage["shift_cmd_1"].count, 5);
+        assert_eq!(usage["shift_cmd_1"].by_app["com.test.editor"], 5);
+        let counts = key_counts_in_window(&harness.state, i64::MIN, i64::MAX, None);
+        assert_eq!(counts, HashMap::from([("1".to_string(), 2)]));
 
-        let rows = harness.rows();
-        assert_eq!(rows.len(), 1);
-        assert_eq!(rows[0].active_typing_ms, 500);
-        assert_eq!(rows[0].key_count, 1);
+        // Switching to produced characters renames the counts, the replayed chunks and the
+        // shortcut lists alike.
+        harness
+            .state
+            .set_shortcut_rules(true, false, 1, &[], &["Shift_Cmd_1".to_string()]);
+        assert_eq!(
+            harness.state.set_symbol_key_names(SymbolKeyNames::Produced),
+            1
+        );
+        assert_eq!(harness.state.shortcut_usage["shift_cmd_!"].count, 5);
+        let counts = key_counts_in_window(&harness.state, i64::MIN, i64::MAX, None);
+        assert_eq!(counts, HashMap::from([("!".to_string(), 2)]));
+        assert!(harness.state.shortcut_blocklist.contains("shift_cmd_!"));
     }
 
     #[test]
-    fn auto_pause_tick_resets_pressed_state_until_new_key_down() {
+    fn chord_aborts_count_released_modifier_presses_per_app() {
+        let cmd = ModifierSnapshot {
+            cmd: true,
+            ..ModifierSnapshot::default()
+        };
+        let shift = ModifierSnapshot {
+            shift: true,
+            ..ModifierSnapshot::default()
+        };
+        let none = ModifierSnapshot::default();
         let mut harness = CollectorEventHarness::new();
-        let now = Instant::now();
+        let editor = harness.default_context.clone();
+        let term = CaptureContext {
+            app_name: "Term".to_string(),
+            window_title: "zsh".to_string(),
+            bundle_id: None,
+            secure_input: false,
+            display_id: None,
+        };
 
-        harness.key_down("k:a", false, now);
-        harness.tick(Duration::from_millis(500), now + Duration::from_millis(500));
-        harness.tick_with_context(
-            Duration::from_millis(300),
-            now + Duration::from_millis(800),
-            CaptureContext {
-                app_name: "Editor".to_string(),
-                window_title: "Doc".to_string(),
-                bundle_id: Some("com.test.editor".to_string()),
-                secure_input: true,
-            },
-        );
-        harness.tick(
-            Duration::from_millis(400),
-            now + Duration::from_millis(1200),
-        );
-        harness.key_down("k:a", false, now + Duration::from_millis(1300));
-        harness.tick(
-            Duration::from_millis(200),
-            now + Duration::from_millis(1500),
+        // Disabled by default: nothing is tracked.
+        harness.modifiers(cmd, editor.clone());
+        harness.modifiers(none, editor.clone());
+        assert!(harness.state.chord_aborts.is_empty());
+
+        harness.state.set_track_chord_aborts(true);
+        // cmd pressed and released alone is an aborted chord.
+        harness.modifiers(cmd, editor.clone());
+        harness.modifiers(none, editor.clone());
+        // cmd+c completes the chord.
+        harness.modifiers(cmd, editor.clone());
+        harness.key_down("c", true, Instant::now());
+        harness.key_up("c");
+        harness.modifiers(none, editor.clone());
+        // Shift taps alone never count.
+        harness.modifiers(shift, editor.clone());
+        harness.modifiers(none, editor.clone());
+        // shift then cmd, released one by one, counts once with the peak modifier set.
+        harness.modifiers(shift, term.clone());
+        harness.modifiers(shift.union(cmd), term.clone());
+        harness.modifiers(cmd, term.clone());
+        harness.modifiers(none, term.clone());
+        // Pausing drops a pending attempt.
+        harness.modifiers(cmd, term.clone());
+        harness.state.set_paused(true);
+        harness.state.set_paused(false);
+        harness.modifiers(none, term.clone());
+
+        let stats = super::snapshot_chord_abort_stats(&harness.state, "today");
+        assert_eq!(stats.total, 2);
+        let by_app: Vec<(&str, u64)> = stats
+            .by_app
+            .iter()
+            .map(|row| (row.app_name.as_str(), row.count))
+            .collect();
+        assert_eq!(by_app, vec![("Term", 1), ("com.test.editor", 1)]);
+        assert_eq!(stats.by_day.len(), 1);
+        assert_eq!(
+            super::snapshot_chord_abort_stats(&harness.state, "yesterday").total,
+            0
         );
 
-        let rows = harness.rows();
-        assert_eq!(rows.len(), 1);
-        assert_eq!(rows[0].key_count, 2);
-        assert_eq!(rows[0].active_typing_ms, 700);
+        let stored = build_stored_input_analytics(&mut harness.state);
+        assert_eq!(stored.chord_aborts, harness.state.chord_aborts);
+    }
+
+    fn focus_session(start_min: i64, minutes: i64, keys: u64) -> FocusSession {
+        FocusSession {
+            start_ms: start_min * 60_000,
+            end_ms: (start_min + minutes) * 60_000,
+            key_count: keys,
+        }
     }
 
     #[test]
-    fn ignore_key_combo_event_is_not_counted_in_event_stream() {
-        let mut harness = CollectorEventHarness::new();
-        let now = Instant::now();
+    fn focus_sessions_split_on_gaps_longer_than_two_minutes() {
+        let sessions = sessions_from_key_times(&[0, 60_000, 180_000, 300_001]);
+        assert_eq!(
+            sessions,
+            vec![
+                FocusSession {
+                    start_ms: 0,
+                    end_ms: 180_000,
+                    key_count: 3,
+                },
+                FocusSession {
+                    start_ms: 300_001,
+                    end_ms: 300_001,
+                    key_count: 1,
+                },
+            ]
+        );
+        assert!(sessions_from_key_times(&[]).is_empty());
+    }
+
+    #[test]
+    fn focus_score_rewards_long_sessions_and_penalizes_switching() {
+        assert_eq!(focus_score(&[], 5), 0);
+        // One dense 50 minute session without switching saturates every component.
+        let deep = [focus_session(0, 50, 3000)];
+        assert_eq!(focus_score(&deep, 0), 100);
+        // The same time and keys split into ten 5 minute sessions: continuity 0.2.
+        let fragmented: Vec<FocusSession> =
+            (0..10).map(|i| focus_session(i * 10, 5, 300)).collect();
+        assert_eq!(focus_score(&fragmented, 0), 60);
+        // 24 switches over 50 focused minutes: 28.8/h, calm = 1 / 3.4.
+        assert_eq!(focus_score(&deep, 24), 79);
+        // Sparse typing only loses the density share.
+        assert_eq!(focus_score(&[focus_session(0, 50, 1500)], 0), 90);
+    }
+
+    #[test]
+    fn deep_work_blocks_need_dense_buckets_for_the_whole_minimum_length() {
+        // (first minute, minutes, keys per minute, app) segments of a synthetic series.
+        type Segment = (i64, i64, u64, &'static str);
+        let thresholds = DeepWorkThresholds {
+            min_minutes: 25,
+            min_keys_per_bucket: 50,
+        };
+        let midnight = 20_000 * 1_440;
+        let block = |start: i64, minutes: i64, key_count: u64, app: &str| DeepWorkBlock {
+            start_ms: start * 60_000,
+            end_ms: (start + minutes) * 60_000,
+            minutes: minutes as u64,
+            key_count,
+            dominant_app: app.to_string(),
+        };
+        let cases: Vec<(&str, Vec<Segment>, DeepWorkThresholds, Vec<DeepWorkBlock>)> = vec![
+            ("empty", vec![], thresholds, vec![]),
+            (
+                "exactly the minimum",
+                vec![(600, 25, 20, "editor")],
+                thresholds,
+                vec![block(600, 25, 500, "editor")],
+            ),
+            (
+                "too short",
+                vec![(600, 20, 20, "editor")],
+                thresholds,
+                vec![],
+            ),
+            (
+                "auto-pause gap splits the run",
+                vec![(600, 15, 20, "editor"), (625, 15, 20, "editor")],
+                thresholds,
+                vec![],
+            ),
+            (
+                "one sparse bucket splits the run",
+                vec![
+                    (600, 15, 20, "editor"),
+                    (615, 5, 9, "editor"),
+                    (620, 15, 20, "editor"),
+                ],
+                thresholds,
+                vec![],
+            ),
+            (
+                "sparse minutes inside a dense bucket keep the run",
+                vec![
+                    (600, 13, 20, "editor"),
+                    (613, 2, 0, "editor"),
+                    (615, 15, 20, "editor"),
+                ],
+                thresholds,
+                vec![block(600, 30, 560, "editor")],
+            ),
+            (
+                "crosses midnight as one block",
+                vec![(midnight - 15, 30, 20, "editor")],
+                thresholds,
+                vec![block(midnight - 15, 30, 600, "editor")],
+            ),
+            (
+                "dominant app has the most keys",
+                vec![(600, 10, 20, "term"), (610, 20, 20, "editor")],
+                thresholds,
+                vec![block(600, 30, 600, "editor")],
+            ),
+            (
+                "two blocks in one day",
+                vec![(540, 30, 20, "editor"), (720, 40, 10, "term")],
+                thresholds,
+                vec![block(540, 30, 600, "editor"), block(720, 40, 400, "term")],
+            ),
+            (
+                "stricter thresholds",
+                vec![(600, 30, 20, "editor"), (700, 45, 20, "editor")],
+                DeepWorkThresholds {
+                    min_minutes: 40,
+                    min_keys_per_bucket: 100,
+                },
+                vec![block(700, 45, 900, "editor")],
+            ),
+        ];
+        for (name, segments, thresholds, expected) in cases {
+            let mut minutes: BTreeMap<i64, HashMap<String, u64>> = BTreeMap::new();
+            for (start, length, keys, app) in segments {
+                for minute in start..start + length {
+                    if keys > 0 {
+                        minutes
+                            .entry(minute)
+                            .or_default()
+                            .insert(app.to_string(), keys);
+                    }
+                }
+            }
+            assert_eq!(
+                detect_deep_work_blocks(&minutes, thresholds),
+                expected,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn deep_work_today_counts_only_the_part_after_midnight() {
+        let today = chrono::Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        let mut stats = HashMap::new();
+        let mut add_minutes = |day: NaiveDate, hour: u16, first: u16, count: u16| {
+            for minute in first..first + count {
+                stats.insert(
+                    StatsKey {
+                        date: MinuteKey::new(day, hour, minute).unwrap(),
+                        app_name: "com.test.editor".to_string(),
+                        window_title: "Doc".to_string(),
+                    },
+                    StatsValue {
+                        active_typing_ms: 30_000,
+                        key_count: 20,
+                        session_count: 1,
+                        writing_ms: 30_000,
+                        navigation_ms: 0,
+                    },
+                );
+            }
+        };
+        add_minutes(yesterday, 23, 50, 10);
+        add_minutes(today, 0, 0, 20);
+        add_minutes(today, 9, 0, 30);
+        let state = build_state(stats);
+
+        let blocks = super::snapshot_deep_work_blocks(&state, "today");
+        let spans: Vec<(u64, u64)> = blocks
+            .iter()
+            .map(|block| (block.minutes, block.key_count))
+            .collect();
+        assert_eq!(spans, vec![(30, 600), (30, 600)]);
+        assert_eq!(blocks[0].dominant_app, "com.test.editor");
+        assert_eq!(state.snapshot().deep_work_minutes_today, 50);
+        assert_eq!(
+            super::snapshot_deep_work_blocks(&state, "yesterday").len(),
+            1
+        );
+    }
+
+    // Sorted by id and one entry per id, as the exclusion picker expects on every platform.
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    fn assert_running_apps_sorted_and_unique(apps: &[super::RunningAppInfo]) {
+        assert!(apps.iter().all(|app| !app.bundle_id.is_empty()));
+        assert!(apps
+            .windows(2)
+            .all(|pair| pair[0].bundle_id < pair[1].bundle_id));
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn running_apps_smoke_windows() {
+        assert_running_apps_sorted_and_unique(&super::running_apps());
+        assert_eq!(
+            super::bundle_id_from_app_path(r"C:\Program Files\Microsoft VS Code\Code.exe"),
+            Some("code.exe".to_string())
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn running_apps_smoke_linux() {
+        assert_running_apps_sorted_and_unique(&super::running_apps());
+        assert_eq!(
+            super::window_apps::process_executable(std::process::id()),
+            std::env::current_exe().ok()
+        );
+        assert_eq!(
+            super::bundle_id_from_app_path("/usr/lib/firefox/Firefox"),
+            Some("firefox".to_string())
+        );
+    }
+
+    #[test]
+    fn app_summary_folds_small_apps_into_other_without_losing_totals() {
+        let today = chrono::Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        let mut stats = HashMap::new();
+        let mut add_row = |day: NaiveDate, minute: u16, app: &str, title: &str, keys: u64| {
+            stats.insert(
+                StatsKey {
+                    date: MinuteKey::new(day, 0, minute).unwrap(),
+                    app_name: app.to_string(),
+                    window_title: title.to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: keys * 150,
+                    key_count: keys,
+                    session_count: 1,
+                    writing_ms: keys * 100,
+                    navigation_ms: keys * 50,
+                },
+            );
+        };
+        add_row(today, 1, "com.test.editor", "a.rs", 300);
+        add_row(today, 2, "com.test.editor", "b.rs", 200);
+        add_row(today, 3, "com.test.term", "zsh", 40);
+        add_row(today, 4, "com.test.dialog", "Save", 1);
+        add_row(today, 5, "com.test.finder", "Downloads", 3);
+        add_row(yesterday, 1, "com.test.dialog", "Save", 100);
+        let mut state = build_state(stats);
+        state.set_min_keys_to_display(10);
+
+        let unfiltered = |day_filter: &dyn Fn(NaiveDate) -> bool| {
+            let mut sum = [0u64; 5];
+            for (key, value) in &state.stats {
+                if day_filter(key.date.day()) {
+                    sum[0] += value.key_count;
+                    sum[1] += value.active_typing_ms;
+                    sum[2] += value.session_count;
+                    sum[3] += value.writing_ms;
+                    sum[4] += value.navigation_ms;
+                }
+            }
+            sum
+        };
+        let bucket_sum = |summary: &super::AppSummary| {
+            let mut sum = [0u64; 5];
+            for row in summary.apps.iter().chain(summary.other.iter()) {
+                sum[0] += row.key_count;
+                sum[1] += row.active_typing_ms;
+                sum[2] += row.session_count;
+                sum[3] += row.writing_ms;
+                sum[4] += row.navigation_ms;
+            }
+            sum
+        };
+
+        let summary = super::snapshot_app_summary(&state, "today", true);
+        let apps: Vec<(&str, u64)> = summary
+            .apps
+            .iter()
+            .map(|row| (row.app_name.as_str(), row.key_count))
+            .collect();
+        assert_eq!(apps, vec![("com.test.editor", 500), ("com.test.term", 40)]);
+        let other = summary.other.as_ref().unwrap();
+        assert_eq!((other.app_name.as_str(), other.key_count), ("Other", 4));
+        assert_eq!(summary.other_app_count, 2);
+        assert_eq!(bucket_sum(&summary), unfiltered(&|day| day == today));
+
+        // The threshold applies to the app's total over the range, not per day.
+        let week = super::snapshot_app_summary(&state, "7d", true);
+        assert!(week
+            .apps
+            .iter()
+            .any(|row| row.app_name == "com.test.dialog"));
+        assert_eq!(week.other_app_count, 1);
+        assert_eq!(bucket_sum(&week), unfiltered(&|_| true));
+
+        let without_other = super::snapshot_app_summary(&state, "today", false);
+        assert_eq!(without_other.other, None);
+        assert_eq!(without_other.other_app_count, 2);
+        assert_eq!(without_other.apps, summary.apps);
+
+        state.set_min_keys_to_display(0);
+        let all = super::snapshot_app_summary(&state, "today", true);
+        assert_eq!(all.apps.len(), 4);
+        assert_eq!(all.other, None);
+        assert_eq!(state.stats.len(), 6);
+    }
+
+    #[test]
+    fn top_windows_group_titles_of_one_app_including_hidden_ones() {
+        let today = chrono::Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        let last_week = today - chrono::Duration::days(8);
+        let mut stats = HashMap::new();
+        let mut add_row = |day: NaiveDate, minute: u16, app: &str, title: &str, keys: u64| {
+            stats.insert(
+                StatsKey {
+                    date: MinuteKey::new(day, 0, minute).unwrap(),
+                    app_name: app.to_string(),
+                    window_title: title.to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: keys * 150,
+                    key_count: keys,
+                    session_count: 1,
+                    writing_ms: keys * 150,
+                    navigation_ms: 0,
+                },
+            );
+        };
+        add_row(today, 1, "com.test.editor", "a.rs", 30);
+        add_row(yesterday, 1, "com.test.editor", "a.rs", 20);
+        add_row(yesterday, 2, "com.test.editor", "b.rs", 40);
+        // Two minutes typed with titles hidden share one group.
+        add_row(today, 2, "com.test.editor", "", 5);
+        add_row(today, 3, "com.test.editor", "", 7);
+        add_row(today, 4, "com.test.editor", "c.rs", 1);
+        add_row(last_week, 1, "com.test.editor", "old.rs", 500);
+        add_row(today, 5, "com.test.term", "zsh", 900);
+        let state = build_state(stats);
+
+        let top = super::snapshot_top_windows(&state, "COM.TEST.EDITOR", "7d", 3);
+        assert_eq!(top.window_count, 4);
+        let rows: Vec<(&str, bool, u64, u64, String)> = top
+            .windows
+            .iter()
+            .map(|row| {
+                (
+                    row.window_title.as_str(),
+                    row.title_hidden,
+                    row.key_count,
+                    row.active_typing_ms,
+                    row.last_seen_date.clone(),
+                )
+            })
+            .collect();
+        let day = |date: NaiveDate| date.format(super::DAY_KEY_FORMAT).to_string();
+        assert_eq!(
+            rows,
+            vec![
+                ("a.rs", false, 50, 7_500, day(today)),
+                ("b.rs", false, 40, 6_000, day(yesterday)),
+                ("", true, 12, 1_800, day(today)),
+            ]
+        );
+
+        let yesterday_top = super::snapshot_top_windows(&state, "com.test.editor", "yesterday", 10);
+        let titles: Vec<&str> = yesterday_top
+            .windows
+            .iter()
+            .map(|row| row.window_title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["b.rs", "a.rs"]);
+        assert_eq!(super::top_windows_limit(None), 10);
+        assert_eq!(super::top_windows_limit(Some(0)), 1);
+        assert_eq!(super::top_windows_limit(Some(1_000)), 100);
+    }
+
+    #[test]
+    fn app_switches_count_changes_while_typing_outside_pauses() {
+        let mut harness = CollectorEventHarness::new();
+        let editor = harness.default_context.clone();
+        let term = CaptureContext {
+            app_name: "Term".to_string(),
+            window_title: "zsh".to_string(),
+            bundle_id: None,
+            secure_input: false,
+            display_id: None,
+        };
+        let locked = CaptureContext {
+            secure_input: true,
+            ..term.clone()
+        };
+        let now = Instant::now();
+        let step = Duration::from_millis(100);
+        let tick = |harness: &mut CollectorEventHarness, context: &CaptureContext| {
+            harness.tick_with_context(step, now, context.clone());
+        };
+
+        tick(&mut harness, &editor);
+        tick(&mut harness, &editor);
+        tick(&mut harness, &term);
+        tick(&mut harness, &editor);
+        // Switches while paused or auto-paused are not counted, but the app is still
+        // tracked so returning to it is not a switch either.
+        harness.state.set_paused(true);
+        tick(&mut harness, &term);
+        harness.state.set_paused(false);
+        tick(&mut harness, &term);
+        tick(&mut harness, &locked);
+        // Leaving the auto-paused app for another one counts again.
+        tick(&mut harness, &editor);
+        // An empty app id is ignored.
+        tick(
+            &mut harness,
+            &CaptureContext {
+                app_name: String::new(),
+                bundle_id: None,
+                ..editor.clone()
+            },
+        );
+        tick(&mut harness, &editor);
+        // Switching long after the last key-down is not a switch while typing.
+        harness.tick_with_context(step, now + Duration::from_secs(10), term.clone());
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        assert_eq!(harness.state.app_switches[&today].total, 3);
+        let stats = super::snapshot_app_switch_stats(&harness.state, "today");
+        assert_eq!(stats.total, 3);
+        let pairs: Vec<(&str, &str, u64)> = stats
+            .top_pairs
+            .iter()
+            .map(|row| (row.from.as_str(), row.to.as_str(), row.count))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("Term", "com.test.editor", 2),
+                ("com.test.editor", "Term", 1)
+            ]
+        );
+        assert_eq!(
+            super::snapshot_app_switch_stats(&harness.state, "yesterday").total,
+            0
+        );
+
+        // Nor is the first key-down in another app after such a break.
+        harness.key_down("k:a", false, now + Duration::from_secs(11));
+        let scores = super::snapshot_focus_scores(&harness.state, "today");
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].session_count, 1);
+        assert_eq!(scores[0].app_switches, 3);
+        assert_eq!(
+            harness.state.snapshot().today_focus_score,
+            Some(scores[0].score)
+        );
+
+        refresh_recent_focus_days(&mut harness.state);
+        let stored = build_stored_input_analytics(&mut harness.state);
+        assert_eq!(stored.focus_days[&today].app_switches, 3);
+        assert_eq!(stored.app_switches[&today].pairs.len(), 2);
+    }
+
+    #[test]
+    fn copy_paste_pairs_copies_with_the_next_paste_inside_the_window() {
+        use super::copy_paste::{expire_pending_copy, CopyPasteDay};
+
+        let mut harness = CollectorEventHarness::new();
+        let editor = harness.default_context.clone();
+        let browser = CaptureContext {
+            app_name: "Browser".to_string(),
+            window_title: "Docs".to_string(),
+            bundle_id: Some("com.test.browser".to_string()),
+            secure_input: false,
+            display_id: None,
+        };
+        let cmd = ModifierSnapshot {
+            cmd: true,
+            ..ModifierSnapshot::default()
+        };
+        let now = Instant::now();
+        let start_ms = epoch_ms_at(now);
+        let press = |harness: &mut CollectorEventHarness,
+                     key: &str,
+                     context: &CaptureContext,
+                     offset_ms: i64| {
+            harness.push(CollectorEvent::NonModifierKeyDown {
+                physical_key_id: key.to_string(),
+                shortcut_key: key.to_string(),
+                modifiers: cmd,
+                is_key_combo: true,
+                capture_context: context.clone(),
+                at: now,
+                at_ms: start_ms + offset_ms,
+            });
+        };
+
+        // Copy in the editor, paste in the browser 1.5s later.
+        press(&mut harness, "c", &editor, 0);
+        press(&mut harness, "v", &browser, 1_500);
+        // A second paste has no copy left to pair with.
+        press(&mut harness, "v", &browser, 2_000);
+        // A copy replaced by a newer one stays unmatched.
+        press(&mut harness, "c", &editor, 3_000);
+        press(&mut harness, "c", &browser, 4_000);
+        press(&mut harness, "v", &editor, 4_500);
+        // A paste after the window does not pair.
+        press(&mut harness, "c", &editor, 5_000);
+        press(&mut harness, "v", &browser, 5_000 + 120_001);
+        // Nor does a copy that expires on the tick before any paste.
+        press(&mut harness, "c", &browser, 130_000);
+        expire_pending_copy(&mut harness.state, start_ms + 130_000 + 119_000);
+        assert!(harness.state.pending_copy.is_some());
+        expire_pending_copy(&mut harness.state, start_ms + 130_000 + 120_001);
+        assert!(harness.state.pending_copy.is_none());
+        // Pausing drops the pending copy without counting it.
+        press(&mut harness, "c", &editor, 260_000);
+        harness.state.set_paused(true);
+        assert!(harness.state.pending_copy.is_none());
+        harness.state.set_paused(false);
+        press(&mut harness, "v", &browser, 260_500);
+
+        let stats = super::snapshot_copy_paste_stats(&harness.state, "today");
+        assert_eq!((stats.pairs, stats.unmatched_copies), (2, 3));
+        assert_eq!(
+            (stats.p50_ms, stats.p90_ms, stats.p99_ms),
+            (Some(500), Some(1_500), Some(1_500))
+        );
+        assert_eq!(stats.window_ms, 120_000);
+        let pairs: Vec<(&str, &str, u64)> = stats
+            .top_pairs
+            .iter()
+            .map(|row| (row.from.as_str(), row.to.as_str(), row.count))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("com.test.browser", "com.test.editor", 1),
+                ("com.test.editor", "com.test.browser", 1)
+            ]
+        );
+        let empty = super::snapshot_copy_paste_stats(&harness.state, "yesterday");
+        assert_eq!(empty.p50_ms, None);
+
+        // The day survives the stored analytics round trip.
+        let stored = build_stored_input_analytics(&mut harness.state);
+        let restored: HashMap<String, CopyPasteDay> = stored
+            .copy_pastes
+            .into_iter()
+            .map(|(date, day)| (date, CopyPasteDay::from_stored(day)))
+            .collect();
+        assert_eq!(restored, harness.state.copy_paste_days);
+    }
+
+    #[test]
+    fn day_key_span_rolls_over_at_midnight_and_survives_a_restart() {
+        use super::history_load::{load_history, merge_history};
+        use super::key_span::key_span_on;
+        use crate::storage::{MemoryStorage, StoredKeySpan};
+
+        let at = |day: u32, hour: u32, minute: u32, second: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2024, 3, day, hour, minute, second)
+                .single()
+                .unwrap()
+                .timestamp_millis()
+        };
+        let tuesday = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let wednesday = NaiveDate::from_ymd_opt(2024, 3, 6).unwrap();
+        let mut harness = CollectorEventHarness::new();
+        let context = harness.default_context.clone();
+        let now = Instant::now();
+        let press = |harness: &mut CollectorEventHarness, key: &str, at_ms: i64| {
+            harness.push(CollectorEvent::NonModifierKeyDown {
+                physical_key_id: key.to_string(),
+                shortcut_key: key.to_string(),
+                modifiers: ModifierSnapshot::default(),
+                is_key_combo: false,
+                capture_context: context.clone(),
+                at: now,
+                at_ms,
+            });
+            harness.key_up(key);
+        };
+
+        press(&mut harness, "a", at(5, 8, 42, 0));
+        press(&mut harness, "b", at(5, 12, 0, 0));
+        press(&mut harness, "c", at(5, 23, 59, 30));
+        // The first key after midnight opens the next day's pair.
+        press(&mut harness, "d", at(6, 0, 0, 30));
+        let span = key_span_on(&harness.state, tuesday).unwrap();
+        assert_eq!(
+            (span.first_ms, span.last_ms),
+            (at(5, 8, 42, 0), at(5, 23, 59, 30))
+        );
+        let span = key_span_on(&harness.state, wednesday).unwrap();
+        assert_eq!(
+            (span.first_ms, span.last_ms),
+            (at(6, 0, 0, 30), at(6, 0, 0, 30))
+        );
+        // Paused keys do not move the span.
+        harness.state.set_paused(true);
+        press(&mut harness, "e", at(6, 1, 0, 0));
+        harness.state.set_paused(false);
+        assert_eq!(
+            key_span_on(&harness.state, wednesday).unwrap().last_ms,
+            at(6, 0, 0, 30)
+        );
+
+        // A restart mid-morning: keys since launch merge with the stored night.
+        let stored = build_stored_input_analytics(&mut harness.state);
+        let storage = MemoryStorage::with_history(HashMap::new(), stored);
+        let mut restarted = CollectorEventHarness::new();
+        press(&mut restarted, "f", at(6, 10, 15, 0));
+        let history = load_history(&storage, &LoadProgress::default());
+        merge_history(&mut restarted.state, history, &|_| {});
+        let span = key_span_on(&restarted.state, wednesday).unwrap();
+        assert_eq!(
+            (span.first_ms, span.last_ms),
+            (at(6, 0, 0, 30), at(6, 10, 15, 0))
+        );
+        assert_eq!(
+            key_span_on(&restarted.state, tuesday),
+            key_span_on(&harness.state, tuesday)
+        );
+
+        // Week of Monday 2024-03-04: days averaging 08:42 to 19:10.
+        restarted.state.key_spans.insert(
+            "2024-03-07".to_string(),
+            StoredKeySpan {
+                first_ms: at(7, 8, 42, 0),
+                last_ms: at(7, 19, 10, 0),
+            },
+        );
+        restarted.state.key_spans.insert(
+            "2024-03-06".to_string(),
+            StoredKeySpan {
+                first_ms: at(6, 8, 40, 0),
+                last_ms: at(6, 19, 0, 0),
+            },
+        );
+        restarted.state.key_spans.insert(
+            "2024-03-05".to_string(),
+            StoredKeySpan {
+                first_ms: at(5, 8, 44, 0),
+                last_ms: at(5, 19, 20, 0),
+            },
+        );
+        let comparison =
+            super::snapshot_weekly_comparison(&restarted.state, Some("2024-03-04"), true).unwrap();
+        assert_eq!(comparison.week.avg_day_start_min, Some(8 * 60 + 42));
+        assert_eq!(comparison.week.avg_day_end_min, Some(19 * 60 + 10));
+        assert_eq!(comparison.previous_week.avg_day_start_min, None);
+        assert!(comparison.summary.contains("平均 08:42 开始、19:10 结束"));
+
+        // Today's pair feeds the snapshot, the daily totals and the today JSON alike.
+        let mut today = CollectorEventHarness::new();
+        today.key_down("k:a", false, now);
+        // The harness converts `now` once; a second conversion can land a millisecond later.
+        let now_ms = today.state.key_spans.values().next().unwrap().first_ms;
+        assert!((now_ms - epoch_ms_at(now)).abs() <= 5);
+        let snapshot = today.state.snapshot();
+        assert_eq!(
+            (snapshot.day_first_key_ms, snapshot.day_last_key_ms),
+            (Some(now_ms), Some(now_ms))
+        );
+        let totals = super::snapshot_daily_totals(&today.state, 1);
+        assert_eq!(totals[0].day_first_key_ms, Some(now_ms));
+        assert_eq!(
+            today.state.today_summary_json().day_last_key_ms,
+            Some(now_ms)
+        );
+    }
+
+    #[test]
+    fn app_switch_pairs_evict_least_frequent_beyond_cap() {
+        let mut day = AppSwitchDay::default();
+        for i in 0..MAX_SWITCH_PAIRS_PER_DAY {
+            day.record(format!("app{i:03}"), "hub".to_string());
+        }
+        day.record("app000".to_string(), "hub".to_string());
+        day.record("new".to_string(), "hub".to_string());
+
+        assert_eq!(day.pairs.len(), MAX_SWITCH_PAIRS_PER_DAY);
+        assert_eq!(day.total, MAX_SWITCH_PAIRS_PER_DAY as u64 + 2);
+        assert_eq!(day.pairs[&("app000".to_string(), "hub".to_string())], 2);
+        assert_eq!(day.pairs[&("new".to_string(), "hub".to_string())], 1);
+        // Among the pairs seen once, the largest one is evicted.
+        let last = format!("app{:03}", MAX_SWITCH_PAIRS_PER_DAY - 1);
+        assert!(!day.pairs.contains_key(&(last, "hub".to_string())));
+    }
+
+    #[test]
+    fn coverage_ledger_accrues_recording_pause_and_listener_downtime() {
+        let mut harness = CollectorEventHarness::new();
+        let locked = CaptureContext {
+            secure_input: true,
+            ..harness.default_context.clone()
+        };
+        let now = Instant::now();
+        let second = Duration::from_secs(1);
+        assert_eq!(harness.state.snapshot().today_coverage_pct, None);
+
+        for _ in 0..6 {
+            harness.tick(second, now);
+        }
+        harness.state.set_paused(true);
+        harness.tick(second, now);
+        harness.state.set_paused(false);
+        harness.tick_with_context(second, now, locked);
+        // Simulate a listener outage: the OS hook died but the tick loop keeps running.
+        harness.state.keyboard_active = false;
+        harness.tick(second, now);
+        harness.tick(second, now);
+        // A tick after system sleep only credits the capped gap.
+        harness.state.keyboard_active = true;
+        harness.tick(Duration::from_secs(3600), now);
+
+        let coverage = super::snapshot_coverage(&harness.state, "today");
+        assert_eq!(coverage.recording_ms, 16_000);
+        assert_eq!(coverage.paused_ms, 2_000);
+        assert_eq!(coverage.listener_down_ms, 2_000);
+        assert_eq!(coverage.days.len(), 1);
+        assert_eq!(coverage.coverage_pct, Some(80.0));
+        assert_eq!(harness.state.snapshot().today_coverage_pct, Some(80.0));
+        // Days without ledger entries are still listed, flagged as untracked.
+        let yesterday = super::snapshot_coverage(&harness.state, "yesterday");
+        assert_eq!(yesterday.days.len(), 1);
+        assert_eq!(yesterday.days[0].recording_ms, 0);
+        assert_eq!(yesterday.days[0].coverage_pct, None);
+        assert_eq!(
+            yesterday.days[0].coverage,
+            super::runtime::DayCoverage::NotRunning
+        );
+
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let stored = build_stored_input_analytics(&mut harness.state);
+        assert_eq!(stored.coverage[&today].listener_down_ms, 2_000);
+    }
+
+    #[test]
+    fn crash_dump_merges_newer_counters_and_unsaved_chunks() {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let yesterday = (chrono::Local::now() - chrono::Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+        let key = |date: &str, title: &str| StatsKey {
+            date: format!("{date} 09:00").parse().unwrap(),
+            app_name: "Editor".to_string(),
+            window_title: title.to_string(),
+        };
+        let value = |active_typing_ms, key_count, session_count| StatsValue {
+            active_typing_ms,
+            key_count,
+            session_count,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let chunk = |chunk_start_ms, app_ref, events: &[&str]| super::shortcut::InputEventChunk {
+            v: 1,
+            chunk_start_ms,
+            app_ref,
+            events: events.iter().map(|event| event.to_string()).collect(),
+        };
+
+        // The crashed run: newer counters, one flushed chunk, one unsaved and one open chunk.
+        let mut crashed = build_state(HashMap::from([
+            (key(&today, "Doc"), value(9_000, 90, 3)),
+            (key(&today, "Notes"), value(1_000, 10, 1)),
+            (key(&yesterday, "Doc"), value(5_000, 50, 2)),
+        ]));
+        crashed.app_dict = HashMap::from([(1, "com.test.editor".to_string())]);
+        crashed.event_chunks = vec![
+            chunk(now_ms, 1, &["0,d,a,0"]),
+            chunk(now_ms + 5_000, 1, &["0,d,b,0", "5,d,c,0"]),
+        ];
+        crashed.open_event_chunk = Some(super::shortcut::OpenInputEventChunk {
+            chunk_start_ms: now_ms + 10_000,
+            started_at: Instant::now(),
+            app_ref: 1,
+            events: vec!["0,d,8,v".to_string()],
+        });
+        let dump = super::crash_dump::build_crash_dump(&crashed, now_ms);
+        assert_eq!(dump.rows.len(), 2);
+        assert_eq!(dump.event_chunks.len(), 3);
+        assert_eq!(dump.app_dict.len(), 1);
+
+        // The restarted run loaded the last flush, where the same app got a different ref.
+        let mut restored = build_state(HashMap::from([
+            (key(&today, "Doc"), value(6_000, 60, 2)),
+            (key(&yesterday, "Doc"), value(5_000, 50, 2)),
+        ]));
+        restored.app_dict = HashMap::from([(7, "com.test.editor".to_string())]);
+        restored.app_ref_by_app = HashMap::from([("com.test.editor".to_string(), 7)]);
+        restored.next_app_ref = 8;
+        restored.event_chunks = vec![chunk(now_ms, 7, &["0,d,a,0"])];
+
+        assert_eq!(
+            super::crash_dump::merge_crash_dump(&mut restored, dump),
+            (2, 2)
+        );
+        let doc = &restored.stats[&key(&today, "Doc")];
+        assert_eq!(
+            (doc.active_typing_ms, doc.key_count, doc.session_count),
+            (9_000, 90, 3)
+        );
+        assert_eq!(restored.stats[&key(&today, "Notes")].key_count, 10);
+        assert_eq!(restored.stats[&key(&yesterday, "Doc")].key_count, 50);
+        assert_eq!(restored.today_totals.key_count, 100);
+        assert_eq!(
+            restored
+                .event_chunks
+                .iter()
+                .map(|chunk| (chunk.chunk_start_ms - now_ms, chunk.app_ref))
+                .collect::<Vec<_>>(),
+            vec![(0, 7), (5_000, 7), (10_000, 7)]
+        );
+        assert_eq!(restored.app_dict.len(), 1);
+    }
+
+    #[test]
+    fn excluded_bundle_ids_stay_canonical_across_updates() {
+        let mut state = build_state(HashMap::new());
+        state.set_excluded_bundle_ids(&[
+            "com.zeta.app".to_string(),
+            " Com.Alpha.App ".to_string(),
+            "COM.ZETA.APP".to_string(),
+            String::new(),
+        ]);
+        let expected = vec!["com.alpha.app".to_string(), "com.zeta.app".to_string()];
+        assert_eq!(state.excluded_bundle_ids(), expected);
+        assert_eq!(state.snapshot().excluded_bundle_ids, expected);
+
+        assert!(state.add_excluded_bundle_id(" Com.Mid.App"));
+        assert!(!state.add_excluded_bundle_id("com.mid.app"));
+        assert!(state.remove_excluded_bundle_id("COM.ZETA.APP"));
+        let expected = vec!["com.alpha.app".to_string(), "com.mid.app".to_string()];
+        assert_eq!(state.excluded_bundle_ids(), expected);
+        assert_eq!(state.snapshot().excluded_bundle_ids, expected);
+    }
+
+    #[test]
+    fn chunk_intervals_follow_event_timestamps_not_processing_time() {
+        let mut harness = CollectorEventHarness::new();
+        // Three events the OS stamped 3s ago, all processed in one backlog burst right now.
+        let base = Instant::now() - Duration::from_secs(3);
+        let base_ms = chrono::Utc::now().timestamp_millis() - 3_000;
+        let key_down = |key: &str, offset_ms: i64, context: &CaptureContext| {
+            CollectorEvent::NonModifierKeyDown {
+                physical_key_id: key.to_string(),
+                shortcut_key: key.to_string(),
+                modifiers: ModifierSnapshot::default(),
+                is_key_combo: false,
+                capture_context: context.clone(),
+                at: base + Duration::from_millis(offset_ms as u64),
+                at_ms: base_ms + offset_ms,
+            }
+        };
+        harness.push(key_down("a", 0, &harness.default_context));
+        harness.push(CollectorEvent::NonModifierKeyUp {
+            physical_key_id: "a".to_string(),
+            shortcut_key: "a".to_string(),
+            modifiers: ModifierSnapshot::default(),
+            capture_context: harness.default_context.clone(),
+            at: base + Duration::from_millis(45),
+            at_ms: base_ms + 45,
+        });
+        harness.push(key_down("b", 130, &harness.default_context));
+
+        let open = harness.state.open_event_chunk.as_ref().unwrap();
+        assert_eq!(open.chunk_start_ms, base_ms);
+        assert_eq!(open.events, vec!["0,d,0,a", "45,u,0,a", "130,d,0,b"]);
+
+        let wall_ms = chrono::Utc::now().timestamp_millis();
+        let late = EventTime::from_event_ms(Some(wall_ms - 250));
+        assert!((late.at_ms - (wall_ms - 250)).abs() <= 5);
+        let lag = Instant::now().duration_since(late.at);
+        assert!(lag >= Duration::from_millis(245) && lag < Duration::from_secs(1));
+        // Missing, future and implausibly old timestamps fall back to or clamp at now.
+        for event_ms in [None, Some(wall_ms + 60_000)] {
+            let time = EventTime::from_event_ms(event_ms);
+            assert!((time.at_ms - wall_ms).abs() <= 5);
+        }
+        let stale = EventTime::from_event_ms(Some(wall_ms - 86_400_000));
+        assert!((stale.at_ms - (wall_ms - 10_000)).abs() <= 5);
+    }
+
+    #[test]
+    fn excluding_the_frontmost_app_mid_hold_stops_recording_right_away() {
+        let mut harness = CollectorEventHarness::new();
+        harness.state.storage = Arc::new(RecordingStorage::default());
+        let start = Instant::now();
+        harness.key_down("a", false, start);
+        harness.tick(
+            Duration::from_millis(100),
+            start + Duration::from_millis(100),
+        );
+        let recorded_events = |state: &CollectorState| -> usize {
+            state
+                .event_chunks
+                .iter()
+                .map(|chunk| chunk.events.len())
+                .sum::<usize>()
+                + state
+                    .open_event_chunk
+                    .as_ref()
+                    .map_or(0, |chunk| chunk.events.len())
+        };
+        let active_ms = |state: &CollectorState| -> u64 {
+            state
+                .stats
+                .values()
+                .map(|value| value.active_typing_ms)
+                .sum()
+        };
+        assert_eq!(active_ms(&harness.state), 100);
+        assert_eq!(recorded_events(&harness.state), 1);
+
+        assert!(harness.state.add_excluded_bundle_id("com.test.editor"));
+        assert!(harness.state.auto_paused);
+        assert_eq!(
+            harness.state.auto_pause_reason.as_deref(),
+            Some("blacklist")
+        );
+        assert!(harness.state.pressed_non_modifier_keys.is_empty());
+        assert!(harness.state.open_event_chunk.is_none());
+        assert_eq!(harness.state.event_chunks.len(), 1);
+
+        harness.tick(
+            Duration::from_millis(100),
+            start + Duration::from_millis(200),
+        );
+        harness.key_up("a");
+        harness.key_down("b", false, start + Duration::from_millis(250));
+        assert_eq!(active_ms(&harness.state), 100);
+        assert_eq!(recorded_events(&harness.state), 1);
+        assert_eq!(harness.state.today_summary().key_count, 1);
+
+        assert!(harness.state.remove_excluded_bundle_id("com.test.editor"));
+        assert!(!harness.state.auto_paused);
+        assert_eq!(harness.state.auto_pause_reason, None);
+    }
+
+    #[test]
+    fn today_summary_rolls_over_at_midnight_without_a_key() {
+        use super::{TodayTotals, DAY_KEY_FORMAT};
+        use chrono::Local;
+
+        let today = Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        let row = |day: NaiveDate, key_count: u64| {
+            (
+                StatsKey {
+                    date: format!("{} 23:59", day.format(DAY_KEY_FORMAT))
+                        .parse()
+                        .unwrap(),
+                    app_name: "com.test.editor".to_string(),
+                    window_title: "Doc".to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: key_count * 100,
+                    key_count,
+                    session_count: 1,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            )
+        };
+        let mut state = build_state(HashMap::from([row(yesterday, 40), row(today, 3)]));
+        // Totals as the last key before midnight left them.
+        state.today_totals = TodayTotals {
+            date: Some(yesterday),
+            key_count: 40,
+            active_typing_ms: 4_000,
+            ..TodayTotals::default()
+        };
+
+        let summary = state.today_summary();
+        assert_eq!(summary.key_count, 3);
+        assert_eq!(summary.active_typing_ms, 300);
+        assert_eq!(state.today_totals.date, Some(yesterday));
+
+        // The refresh moves the cache to the new day, so later reads skip the scan.
+        state.refresh_today_totals();
+        assert_eq!(state.today_totals.date, Some(today));
+        assert_eq!(state.today_totals.key_count, 3);
+        state.stats.clear();
+        assert_eq!(state.today_summary().key_count, 3);
+    }
+
+    #[test]
+    fn compute_today_totals_counts_only_the_given_day() {
+        use super::compute_today_totals;
+
+        let today = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let row = |date: &str, app_name: &str, key_count: u64| {
+            (
+                StatsKey {
+                    date: date.parse().unwrap(),
+                    app_name: app_name.to_string(),
+                    window_title: "Doc".to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: key_count * 100,
+                    key_count,
+                    session_count: 1,
+                    writing_ms: key_count * 60,
+                    navigation_ms: key_count * 40,
+                },
+            )
+        };
+        let stats = HashMap::from([
+            row("2026-03-01 23:59", "com.test.editor", 40),
+            row("2026-03-02 00:00", "com.test.editor", 3),
+            row("2026-03-02 10:00", "Com.Test.Editor", 2),
+            row("2026-03-02 10:00", "com.test.terminal", 5),
+            row("2026-03-03 00:00", "com.test.editor", 7),
+        ]);
+
+        let totals = compute_today_totals(&stats, today);
+        assert_eq!(totals.date, Some(today));
+        assert_eq!(
+            (
+                totals.key_count,
+                totals.active_typing_ms,
+                totals.writing_ms,
+                totals.navigation_ms
+            ),
+            (10, 1_000, 600, 400)
+        );
+        // Per-app time is keyed by the lowercased app id.
+        assert_eq!(totals.app_active_typing_ms["com.test.editor"], 500);
+        assert_eq!(totals.app_active_typing_ms["com.test.terminal"], 500);
+        assert_eq!(totals.app_active_typing_ms.len(), 2);
+        let empty = compute_today_totals(&stats, today.succ_opt().unwrap().succ_opt().unwrap());
+        assert_eq!(empty.key_count, 0);
+        assert!(empty.app_active_typing_ms.is_empty());
+    }
+
+    #[test]
+    fn app_nap_assertion_follows_setting_pause_and_idle() {
+        use super::events::sync_app_nap;
+        use crate::app_nap::APP_NAP_IDLE_AFTER;
+
+        let dir = temp_dir("app-nap");
+        let mut state = build_state(HashMap::new());
+        state.app_log_path = dir.join("app.log");
+        // Only macOS can take the assertion; elsewhere it is never reported as held.
+        let holdable = cfg!(target_os = "macos");
+        let now = Instant::now();
+        state.last_typing_instant = now;
+
+        state.set_prevent_app_nap(true);
+        let snapshot = state.snapshot();
+        assert!(snapshot.prevent_app_nap);
+        assert_eq!(snapshot.app_nap_assertion_held, holdable);
+
+        state.paused = true;
+        sync_app_nap(&mut state, now);
+        assert!(!state.snapshot().app_nap_assertion_held);
+        state.paused = false;
+        sync_app_nap(&mut state, now);
+        assert_eq!(state.snapshot().app_nap_assertion_held, holdable);
+
+        sync_app_nap(&mut state, now + APP_NAP_IDLE_AFTER);
+        assert!(!state.snapshot().app_nap_assertion_held);
+        sync_app_nap(&mut state, now);
+
+        state.set_prevent_app_nap(false);
+        let snapshot = state.snapshot();
+        assert!(!snapshot.prevent_app_nap);
+        assert!(!snapshot.app_nap_assertion_held);
+    }
+
+    #[test]
+    fn set_ignore_key_combos_reflects_in_snapshot() {
+        let state = build_state(HashMap::new());
+        let mut state = state;
+        assert!(!state.snapshot().ignore_key_combos);
+        state.set_ignore_key_combos(true);
+        assert!(state.snapshot().ignore_key_combos);
+        state.set_ignore_key_combos(false);
+        assert!(!state.snapshot().ignore_key_combos);
+    }
+
+    #[test]
+    fn set_paused_from_logs_source_and_reports_capture_state() {
+        let dir = temp_dir("pause-source");
+        let mut state = build_state(HashMap::new());
+        state.app_log_path = dir.join("app.log");
+
+        let summary = state.set_paused_from(true, "tray");
+        assert_eq!(
+            summary.capture_state(),
+            CaptureStateChanged {
+                paused: true,
+                auto_paused: false,
+                reason: None,
+            }
+        );
+        assert!(state.snapshot().paused);
+        let summary = state.set_paused_from(false, "command");
+        assert!(!summary.capture_state().paused);
+
+        flush_logs();
+        let log = std::fs::read_to_string(&state.app_log_path).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("paused via tray"));
+        assert!(lines[1].ends_with("resumed via command"));
+    }
+
+    #[test]
+    fn set_tray_left_click_action_reflects_in_snapshot() {
+        let mut state = build_state(HashMap::new());
+        assert_eq!(state.snapshot().tray_left_click_action, "menu");
+        state.set_tray_left_click_action(TrayLeftClickAction::TogglePause);
+        assert_eq!(state.snapshot().tray_left_click_action, "toggle_pause");
+        assert_eq!(
+            TrayLeftClickAction::from_str("open_window"),
+            Some(TrayLeftClickAction::OpenWindow)
+        );
+        assert_eq!(TrayLeftClickAction::from_str("double"), None);
+    }
+
+    #[test]
+    fn repeated_key_down_is_counted_once_until_key_up() {
+        let mut harness = CollectorEventHarness::new();
+        let now = Instant::now();
+
+        harness.key_down("k:a", false, now);
+        harness.key_down("k:a", false, now + Duration::from_millis(100));
+
+        let rows = harness.rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].key_count, 1);
+
+        harness.key_up("k:a");
+        harness.key_down("k:a", false, now + Duration::from_millis(200));
+
+        let rows = harness.rows();
+        assert_eq!(rows[0].key_count, 2);
+    }
+
+    #[test]
+    fn lost_key_up_expires_so_a_later_press_counts_again() {
+        let dir = temp_dir("lost-key-up");
+        let mut harness = CollectorEventHarness::new();
+        harness.state.app_log_path = dir.join("app.log");
+        let now = Instant::now();
+
+        // The key-up of the first press never arrives; repeats keep the hold alive.
+        harness.key_down("k:a", false, now);
+        harness.key_down("k:a", false, now + Duration::from_secs(20));
+        harness.tick(Duration::from_secs(1), now + Duration::from_secs(45));
+        assert_eq!(harness.rows()[0].key_count, 1);
+        assert_eq!(harness.rows()[0].active_typing_ms, 1_000);
+
+        harness.tick(Duration::from_secs(1), now + Duration::from_secs(51));
+        assert!(harness.state.pressed_non_modifier_keys.is_empty());
+        assert!(harness.state.active_stats_key.is_none());
+        assert_eq!(harness.rows()[0].active_typing_ms, 1_000);
+
+        harness.key_down("k:a", false, now + Duration::from_secs(60));
+        assert_eq!(harness.rows()[0].key_count, 2);
+
+        // Without ticks in between, the stale entry is expired by the key-down itself.
+        harness.key_down("k:a", false, now + Duration::from_secs(95));
+        assert_eq!(harness.rows()[0].key_count, 3);
+        flush_logs();
+        let log = std::fs::read_to_string(&harness.state.app_log_path).unwrap();
+        // The second, identical expiry is folded into a repeat count.
+        assert_eq!(
+            log.matches("expired 1 held key(s) without key-up after 30s")
+                .count(),
+            1
+        );
+        assert!(log.contains("last message repeated 1 times"));
+    }
+
+    #[test]
+    fn frontmost_app_change_clears_held_keys() {
+        let mut harness = CollectorEventHarness::new();
+        let now = Instant::now();
+        let other_app = CaptureContext {
+            app_name: "Browser".to_string(),
+            bundle_id: Some("com.test.browser".to_string()),
+            ..harness.default_context.clone()
+        };
+
+        harness.key_down("k:a", false, now);
+        harness.tick_with_context(
+            Duration::from_millis(500),
+            now + Duration::from_millis(500),
+            other_app.clone(),
+        );
+        assert!(harness.state.pressed_non_modifier_keys.is_empty());
+        assert!(harness.state.active_stats_key.is_none());
+
+        harness.push(CollectorEvent::NonModifierKeyDown {
+            physical_key_id: "k:a".to_string(),
+            shortcut_key: "k:a".to_string(),
+            modifiers: ModifierSnapshot::default(),
+            is_key_combo: false,
+            capture_context: other_app,
+            at: now + Duration::from_millis(700),
+            at_ms: epoch_ms_at(now + Duration::from_millis(700)),
+        });
+        let browser = harness
+            .rows()
+            .into_iter()
+            .find(|row| row.app_name == "com.test.browser")
+            .unwrap();
+        assert_eq!(browser.key_count, 1);
+    }
+
+    #[test]
+    fn active_typing_ms_is_accumulated_from_tick_while_key_held() {
+        let mut harness = CollectorEventHarness::new();
+        let now = Instant::now();
+
+        harness.key_down("k:a", false, now);
+        harness.tick(
+            Duration::from_millis(1200),
+            now + Duration::from_millis(1200),
+        );
+
+        let rows = harness.rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].active_typing_ms, 1200);
+    }
+
+    #[test]
+    fn held_time_splits_into_writing_and_navigation_by_latest_key_down() {
+        let mut harness = CollectorEventHarness::new();
+        let now = Instant::now();
+        let at = |ms| now + Duration::from_millis(ms);
+
+        harness.key_down("a", false, at(0));
+        harness.tick(Duration::from_millis(500), at(500));
+        harness.key_up("a");
+        harness.key_down("left", false, at(600));
+        harness.tick(Duration::from_millis(300), at(900));
+        // A digit pressed while the arrow is still held switches the class back.
+        harness.key_down("7", false, at(950));
+        harness.tick(Duration::from_millis(200), at(1_100));
+        harness.key_up("left");
+        harness.key_up("7");
+        // A counted shortcut is navigation even though its key is a letter.
+        harness.push(CollectorEvent::NonModifierKeyDown {
+            physical_key_id: "c".to_string(),
+            shortcut_key: "c".to_string(),
+            modifiers: ModifierSnapshot {
+                cmd: true,
+                ..ModifierSnapshot::default()
+            },
+            is_key_combo: true,
+            capture_context: harness.default_context.clone(),
+            at: at(1_200),
+            at_ms: epoch_ms_at(at(1_200)),
+        });
+        harness.tick(Duration::from_millis(100), at(1_300));
+        harness.key_up("c");
+        harness.key_down("pagedown", false, at(1_400));
+        harness.tick(Duration::from_millis(400), at(1_800));
+
+        let rows = harness.rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].active_typing_ms, 1_500);
+        assert_eq!((rows[0].writing_ms, rows[0].navigation_ms), (700, 800));
+        let summary = harness.state.today_summary();
+        assert_eq!((summary.writing_ms, summary.navigation_ms), (700, 800));
+    }
+
+    #[test]
+    fn held_key_time_follows_window_title_change_within_same_app() {
+        let mut harness = CollectorEventHarness::new();
+        let now = Instant::now();
+        let other_doc = CaptureContext {
+            window_title: "Other".to_string(),
+            ..harness.default_context.clone()
+        };
+
+        harness.key_down("k:down", false, now);
+        harness.tick(Duration::from_millis(500), now + Duration::from_millis(500));
+        harness.tick_with_context(
+            Duration::from_millis(700),
+            now + Duration::from_millis(1200),
+            other_doc.clone(),
+        );
+        harness.tick_with_context(
+            Duration::from_millis(300),
+            now + Duration::from_millis(1500),
+            other_doc,
+        );
+
+        let rows = harness.rows();
+        assert_eq!(rows.len(), 2);
+        let doc = rows.iter().find(|row| row.window_title == "Doc").unwrap();
+        assert_eq!((doc.active_typing_ms, doc.key_count), (500, 1));
+        let other = rows.iter().find(|row| row.window_title == "Other").unwrap();
+        assert_eq!((other.active_typing_ms, other.key_count), (1000, 0));
+        assert_eq!(other.app_name, "com.test.editor");
+    }
+
+    #[test]
+    fn event_stream_stops_accumulating_after_key_up() {
+        let mut harness = CollectorEventHarness::new();
+        let now = Instant::now();
+
+        harness.key_down("k:a", false, now);
+        harness.tick(Duration::from_millis(500), now + Duration::from_millis(500));
+        harness.key_up("k:a");
+        harness.tick(
+            Duration::from_millis(700),
+            now + Duration::from_millis(1200),
+        );
+
+        let rows = harness.rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].active_typing_ms, 500);
+        assert_eq!(rows[0].key_count, 1);
+    }
+
+    #[test]
+    fn auto_pause_tick_resets_pressed_state_until_new_key_down() {
+        let mut harness = CollectorEventHarness::new();
+        let now = Instant::now();
+
+        harness.key_down("k:a", false, now);
+        harness.tick(Duration::from_millis(500), now + Duration::from_millis(500));
+        harness.tick_with_context(
+            Duration::from_millis(300),
+            now + Duration::from_millis(800),
+            CaptureContext {
+                app_name: "Editor".to_string(),
+                window_title: "Doc".to_string(),
+                bundle_id: Some("com.test.editor".to_string()),
+                secure_input: true,
+                display_id: None,
+            },
+        );
+        harness.tick(
+            Duration::from_millis(400),
+            now + Duration::from_millis(1200),
+        );
+        harness.key_down("k:a", false, now + Duration::from_millis(1300));
+        harness.tick(
+            Duration::from_millis(200),
+            now + Duration::from_millis(1500),
+        );
+
+        let rows = harness.rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].key_count, 2);
+        assert_eq!(rows[0].active_typing_ms, 700);
+    }
+
+    #[test]
+    fn ignore_key_combo_event_is_not_counted_in_event_stream() {
+        let mut harness = CollectorEventHarness::new();
+        let now = Instant::now();
         harness.state.set_ignore_key_combos(true);
 
-        harness.key_down("k:a", true, now);
-        harness.tick(Duration::from_millis(300), now + Duration::from_millis(300));
-        harness.key_down("k:a", false, now + Duration::from_millis(400));
-        harness.tick(Duration::from_millis(200), now + Duration::from_millis(600));
+        harness.key_down("k:a", true, now);
+        harness.tick(Duration::from_millis(300), now + Duration::from_millis(300));
+        harness.key_down("k:a", false, now + Duration::from_millis(400));
+        harness.tick(Duration::from_millis(200), now + Duration::from_millis(600));
+
+        let rows = harness.rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].key_count, 1);
+        assert_eq!(rows[0].active_typing_ms, 200);
+    }
+
+    #[test]
+    fn top_keys_counts_key_down_only_and_sorts() {
+        let mut state = build_state(HashMap::new());
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        state.event_chunks.push(super::shortcut::InputEventChunk {
+            v: 1,
+            chunk_start_ms: now_ms,
+            app_ref: 1,
+            events: vec![
+                "0,d,a,0".to_string(),
+                "50,d,a,0".to_string(),
+                "100,d,tab,0".to_string(),
+                "150,u,a,0".to_string(),
+            ],
+        });
+
+        let rows = super::snapshot_top_keys_by_range(&state, "today").rows;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].key, "a");
+        assert_eq!(rows[0].count, 2);
+        assert_eq!(rows[1].key, "tab");
+        assert_eq!(rows[1].count, 1);
+    }
+
+    #[test]
+    fn compact_events_round_trip_punctuation_and_fallback_keys() {
+        use super::shortcut::{append_input_event, parse_compact_event, INPUT_CHUNK_VERSION};
+
+        let mut state = build_state(HashMap::new());
+        let context = state.current_context();
+        let shift = ModifierSnapshot {
+            shift: true,
+            ..ModifierSnapshot::default()
+        };
+        let keys = [
+            (",", ModifierSnapshot::default()),
+            (".", shift),
+            ("/", shift),
+            ("k43", ModifierSnapshot::default()),
+        ];
+        let base = Instant::now();
+        let base_ms = chrono::Utc::now().timestamp_millis();
+        for (index, (key, modifiers)) in keys.iter().enumerate() {
+            let offset = index as i64 * 10;
+            let time = EventTime {
+                at: base + Duration::from_millis(offset as u64),
+                at_ms: base_ms + offset,
+            };
+            append_input_event(&mut state, &context, 'd', key, *modifiers, time);
+        }
+
+        let open = state.open_event_chunk.as_ref().unwrap();
+        let decoded: Vec<(i64, char, String, u8)> = open
+            .events
+            .iter()
+            .map(|raw| parse_compact_event(INPUT_CHUNK_VERSION, raw).unwrap())
+            .map(|(dt, kind, key, modifiers)| (dt, kind, key, modifiers.bitmask()))
+            .collect();
+        let expected: Vec<(i64, char, String, u8)> = keys
+            .iter()
+            .enumerate()
+            .map(|(index, (key, modifiers))| {
+                (index as i64 * 10, 'd', key.to_string(), modifiers.bitmask())
+            })
+            .collect();
+        assert_eq!(decoded, expected);
+
+        // Version 1 put the key before the mask; a comma key there still parses.
+        let (_, _, key, modifiers) = parse_compact_event(1, "12,d,,,1").unwrap();
+        assert_eq!((key.as_str(), modifiers.bitmask()), (",", 1));
+        let (_, _, key, _) = parse_compact_event(1, "12,u,k43,0").unwrap();
+        assert_eq!(key, "k43");
+        assert!(parse_compact_event(INPUT_CHUNK_VERSION + 1, "12,d,0,a").is_none());
+    }
+
+    #[test]
+    fn wall_clock_jumps_close_the_chunk_and_keep_dt_monotonic() {
+        use super::shortcut::{
+            append_input_event, flush_expired_open_chunk, CLOCK_JUMP_TOLERANCE_MS,
+            INPUT_CHUNK_WINDOW_MS,
+        };
+
+        let mut state = build_state(HashMap::new());
+        let context = state.current_context();
+        let modifiers = ModifierSnapshot::default();
+        let base = Instant::now();
+        let base_ms = chrono::Utc::now().timestamp_millis();
+        // Injected clocks: `mono_ms` on the monotonic clock, `wall_ms` as the wall clock read.
+        let append = |state: &mut CollectorState, kind: char, key: &str, mono_ms: u64, wall_ms| {
+            let time = EventTime {
+                at: base + Duration::from_millis(mono_ms),
+                at_ms: wall_ms,
+            };
+            append_input_event(state, &context, kind, key, modifiers, time);
+        };
+
+        append(&mut state, 'd', "a", 0, base_ms);
+        append(&mut state, 'u', "a", 100, base_ms + 100);
+        // NTP steps the clock back a minute: the chunk closes instead of clamping dt to 0.
+        let back_ms = base_ms - 60_000;
+        append(&mut state, 'd', "b", 200, back_ms + 200);
+        append(&mut state, 'u', "b", 300, back_ms + 300);
+        // Jitter under the tolerance stays in the chunk, with dt from the monotonic clock.
+        append(
+            &mut state,
+            'd',
+            "c",
+            400,
+            back_ms + 400 + CLOCK_JUMP_TOLERANCE_MS - 500,
+        );
+        // A manual change an hour forward closes it too, instead of a dt of an hour.
+        let forward_ms = back_ms + 3_600_000;
+        append(&mut state, 'u', "c", 500, forward_ms + 500);
+
+        let chunks: Vec<(i64, Vec<String>)> = state
+            .event_chunks
+            .iter()
+            .map(|chunk| (chunk.chunk_start_ms, chunk.events.clone()))
+            .collect();
+        assert_eq!(
+            chunks,
+            vec![
+                (
+                    base_ms,
+                    vec!["0,d,0,a".to_string(), "100,u,0,a".to_string()]
+                ),
+                (
+                    back_ms + 200,
+                    vec![
+                        "0,d,0,b".to_string(),
+                        "100,u,0,b".to_string(),
+                        "200,d,0,c".to_string()
+                    ]
+                ),
+            ]
+        );
+        let open = state.open_event_chunk.as_ref().unwrap();
+        assert_eq!(open.chunk_start_ms, forward_ms + 500);
+        assert_eq!(open.events, vec!["0,u,0,c"]);
+
+        // Expiry follows the monotonic clock too, whatever the wall clock says.
+        let window = Duration::from_millis(INPUT_CHUNK_WINDOW_MS as u64);
+        flush_expired_open_chunk(&mut state, base + Duration::from_millis(500) + window / 2);
+        assert!(state.open_event_chunk.is_some());
+        flush_expired_open_chunk(&mut state, base + Duration::from_millis(500) + window);
+        assert!(state.open_event_chunk.is_none());
+        assert_eq!(state.event_chunks.len(), 3);
+        for chunk in &state.event_chunks {
+            let dts: Vec<i64> = chunk
+                .events
+                .iter()
+                .map(|event| event.split(',').next().unwrap().parse().unwrap())
+                .collect();
+            assert!(dts.windows(2).all(|pair| pair[0] <= pair[1]), "{dts:?}");
+        }
+    }
+
+    #[test]
+    fn event_chunks_rotate_and_split_at_local_midnight() {
+        use super::shortcut::{
+            append_input_event, key_counts_in_window, local_day_window_ms, split_cross_day_chunks,
+            InputEventChunk,
+        };
+
+        let day = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let before = local_day_window_ms(day).unwrap();
+        let after = local_day_window_ms(day.succ_opt().unwrap()).unwrap();
+        let midnight_ms = before.1;
+        let chunk_day = |chunk_start_ms: i64| {
+            chrono::Local
+                .timestamp_millis_opt(chunk_start_ms)
+                .unwrap()
+                .date_naive()
+        };
+
+        // A clock stepping across midnight: the chunk opened at 23:59:58 closes at 00:00.
+        let mut state = build_state(HashMap::new());
+        let context = state.current_context();
+        let modifiers = ModifierSnapshot::default();
+        append_input_event(
+            &mut state,
+            &context,
+            'd',
+            "a",
+            modifiers,
+            event_time_at_ms(midnight_ms - 2_000),
+        );
+        append_input_event(
+            &mut state,
+            &context,
+            'u',
+            "a",
+            modifiers,
+            event_time_at_ms(midnight_ms - 1_500),
+        );
+        append_input_event(
+            &mut state,
+            &context,
+            'd',
+            "b",
+            modifiers,
+            event_time_at_ms(midnight_ms + 500),
+        );
+        append_input_event(
+            &mut state,
+            &context,
+            'u',
+            "b",
+            modifiers,
+            event_time_at_ms(midnight_ms + 900),
+        );
+        assert_eq!(state.event_chunks.len(), 1);
+        assert_eq!(chunk_day(state.event_chunks[0].chunk_start_ms), day);
+        assert_eq!(state.event_chunks[0].events.len(), 2);
+        let open = state.open_event_chunk.as_ref().unwrap();
+        assert_eq!(open.chunk_start_ms, midnight_ms + 500);
+        assert_eq!(open.events, vec!["0,d,0,b", "400,u,0,b"]);
+        let counts_before = key_counts_in_window(&state, before.0, before.1, None);
+        let counts_after = key_counts_in_window(&state, after.0, after.1, None);
+        assert_eq!(counts_before, HashMap::from([("a".to_string(), 1)]));
+        assert_eq!(counts_after, HashMap::from([("b".to_string(), 1)]));
+
+        // A chunk written before the rotation existed is split at load.
+        let legacy = InputEventChunk {
+            v: 1,
+            chunk_start_ms: midnight_ms - 2_000,
+            app_ref: 1,
+            events: vec![
+                "0,d,a,0".to_string(),
+                "500,u,a,0".to_string(),
+                "2500,d,b,0".to_string(),
+                "bad".to_string(),
+                "2900,u,b,0".to_string(),
+            ],
+        };
+        let untouched = InputEventChunk {
+            v: 1,
+            chunk_start_ms: midnight_ms - 10_000,
+            app_ref: 2,
+            events: vec!["0,d,c,0".to_string()],
+        };
+        let (chunks, split) = split_cross_day_chunks(vec![untouched, legacy]);
+        assert_eq!(split, 1);
+        let layout: Vec<(i64, u32, Vec<&str>)> = chunks
+            .iter()
+            .map(|chunk| {
+                (
+                    chunk.chunk_start_ms - midnight_ms,
+                    chunk.app_ref,
+                    chunk.events.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            layout,
+            vec![
+                (-10_000, 2, vec!["0,d,c,0"]),
+                (-2_000, 1, vec!["0,d,a,0", "500,u,a,0"]),
+                (500, 1, vec!["0,d,b,0", "bad", "400,u,b,0"]),
+            ]
+        );
+        assert_eq!(chunk_day(chunks[2].chunk_start_ms), day.succ_opt().unwrap());
+        let (again, split) = split_cross_day_chunks(chunks);
+        assert_eq!((again.len(), split), (3, 0));
+    }
+
+    #[test]
+    fn app_dict_gc_remaps_refs_without_changing_replay() {
+        let mut state = build_state(HashMap::new());
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        for (app_ref, app_id) in [(1, "com.gone.a"), (2, "com.test.b"), (3, "com.gone.c")] {
+            state.app_dict.insert(app_ref, app_id.to_string());
+            state.app_ref_by_app.insert(app_id.to_string(), app_ref);
+        }
+        state.app_dict.insert(5, "com.test.e".to_string());
+        state.app_ref_by_app.insert("com.test.e".to_string(), 5);
+        state.next_app_ref = 6;
+        for (offset, app_ref) in [(0, 2), (10, 5), (20, 2)] {
+            state.event_chunks.push(super::shortcut::InputEventChunk {
+                v: 1,
+                chunk_start_ms: now_ms + offset,
+                app_ref,
+                events: vec!["0,d,c,8".to_string(), "5,d,a,0".to_string()],
+            });
+        }
+        state.open_event_chunk = Some(super::shortcut::OpenInputEventChunk {
+            chunk_start_ms: now_ms + 30,
+            started_at: Instant::now(),
+            app_ref: 5,
+            events: vec!["0,d,1,v".to_string()],
+        });
+        let replay = |state: &CollectorState| {
+            let shortcuts: Vec<_> = super::snapshot_shortcut_rows_by_range(state, "7d", 8)
+                .rows
+                .into_iter()
+                .map(|row| {
+                    let apps: Vec<(String, u64)> = row
+                        .apps
+                        .into_iter()
+                        .map(|app| (app.app_name, app.count))
+                        .collect();
+                    (row.shortcut_id, row.count, apps)
+                })
+                .collect();
+            let keys: Vec<(String, u64)> = super::snapshot_top_keys_by_range(state, "7d")
+                .rows
+                .into_iter()
+                .map(|row| (row.key, row.count))
+                .collect();
+            (shortcuts, keys)
+        };
+        let before = replay(&state);
+
+        assert_eq!(super::shortcut::gc_app_dict(&mut state, true), 2);
+        assert_eq!(replay(&state), before);
+        assert_eq!(
+            state.app_dict.get(&1).map(String::as_str),
+            Some("com.test.b")
+        );
+        assert_eq!(
+            state.app_dict.get(&2).map(String::as_str),
+            Some("com.test.e")
+        );
+        assert_eq!(state.app_ref_by_app.get("com.test.e"), Some(&2));
+        assert_eq!(state.next_app_ref, 3);
+
+        let stored = build_stored_input_analytics(&mut state);
+        assert_eq!(stored.app_dict.len(), 2);
+        assert_eq!(stored.next_app_ref, 3);
+        assert!(stored
+            .event_chunks
+            .iter()
+            .all(|chunk| stored.app_dict.contains_key(&chunk.app_ref)));
+        assert_eq!(replay(&state), before);
+    }
+
+    #[test]
+    fn shortcut_rows_report_uses_of_apps_beyond_the_limit() {
+        let mut state = build_state(HashMap::new());
+        let by_app: HashMap<String, u64> = (1..=10)
+            .map(|n| (format!("com.test.app{n:02}"), n))
+            .collect();
+        state.shortcut_usage.insert(
+            "cmd_c".to_string(),
+            super::ShortcutUsageValue {
+                count: by_app.values().sum(),
+                by_app,
+            },
+        );
+        let rows = super::shortcut::snapshot_shortcut_rows(&state, 3);
+        assert_eq!(
+            rows[0].apps.iter().map(|app| app.count).collect::<Vec<_>>(),
+            vec![10, 9, 8]
+        );
+        assert_eq!(rows[0].other_count, (1..=7).sum::<u64>());
+        let listed: u64 = rows[0].apps.iter().map(|app| app.count).sum();
+        assert_eq!(listed + rows[0].other_count, rows[0].count);
+        let rows = super::shortcut::snapshot_shortcut_rows(&state, 50);
+        assert_eq!((rows[0].apps.len(), rows[0].other_count), (10, 0));
+
+        // Range replay goes through the same row builder.
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        for (app_ref, app_id) in [(1, "com.test.a"), (2, "com.test.b"), (3, "com.test.c")] {
+            state.app_dict.insert(app_ref, app_id.to_string());
+        }
+        for (offset, app_ref, events) in [(0, 1, 3), (10, 2, 2), (20, 3, 1)] {
+            state.event_chunks.push(super::shortcut::InputEventChunk {
+                v: 1,
+                chunk_start_ms: now_ms + offset,
+                app_ref,
+                events: vec!["0,d,v,8".to_string(); events],
+            });
+        }
+        let rows = super::snapshot_shortcut_rows_by_range(&state, "today", 1).rows;
+        assert_eq!(rows[0].shortcut_id, "cmd_v");
+        assert_eq!(rows[0].apps[0].app_name, "com.test.a");
+        assert_eq!(rows[0].other_count, 3);
+
+        assert_eq!(super::shortcut_app_limit(None), 8);
+        assert_eq!(super::shortcut_app_limit(Some(0)), 1);
+        assert_eq!(super::shortcut_app_limit(Some(500)), 50);
+    }
+
+    #[test]
+    fn concentration_index_ranges_from_even_spread_to_single_app() {
+        use super::shortcut_breadth::concentration_index;
+
+        assert_eq!(concentration_index(&[]), 0.0);
+        assert_eq!(concentration_index(&[0, 0]), 0.0);
+        assert_eq!(concentration_index(&[7]), 1.0);
+        assert!((concentration_index(&[5, 5, 5, 5]) - 0.25).abs() < 1e-9);
+        // 0.75^2 + 0.25^2
+        assert!((concentration_index(&[3, 1]) - 0.625).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shortcut_breadth_replays_only_the_requested_range() {
+        let mut state = build_state(HashMap::new());
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let three_days_ago_ms = now_ms - 3 * 24 * 60 * 60 * 1000;
+        for (app_ref, app_id) in [(1, "com.test.a"), (2, "com.test.b"), (3, "com.test.c")] {
+            state.app_dict.insert(app_ref, app_id.to_string());
+        }
+        // cmd_c is used evenly in three apps today; cmd_b only in app a. Older uses of cmd_b
+        // in app b fall outside `today`.
+        for (start_ms, app_ref, event, count) in [
+            (now_ms, 1, "0,d,c,8", 2),
+            (now_ms + 10, 2, "0,d,c,8", 2),
+            (now_ms + 20, 3, "0,d,c,8", 2),
+            (now_ms + 30, 1, "0,d,b,8", 3),
+            (three_days_ago_ms, 2, "0,d,b,8", 9),
+        ] {
+            state.event_chunks.push(super::shortcut::InputEventChunk {
+                v: 1,
+                chunk_start_ms: start_ms,
+                app_ref,
+                events: vec![event.to_string(); count],
+            });
+        }
+        state.event_chunks.sort_by_key(|chunk| chunk.chunk_start_ms);
+
+        let today = super::snapshot_shortcut_breadth(&state, "today");
+        assert_eq!(today.range, "today");
+        let ids: Vec<&str> = today
+            .shortcuts
+            .iter()
+            .map(|row| row.shortcut_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["cmd_c", "cmd_b"]);
+        let copy = &today.shortcuts[0];
+        assert_eq!((copy.count, copy.app_count), (6, 3));
+        assert!((copy.concentration - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            (copy.dominant_app.as_str(), copy.dominant_app_count),
+            ("com.test.a", 2)
+        );
+        let bold = &today.shortcuts[1];
+        assert_eq!((bold.count, bold.app_count), (3, 1));
+        assert_eq!(bold.concentration, 1.0);
+
+        let week = super::snapshot_shortcut_breadth(&state, "7d");
+        let bold = &week.shortcuts[0];
+        assert_eq!(bold.shortcut_id, "cmd_b");
+        assert_eq!((bold.count, bold.app_count), (12, 2));
+        assert_eq!(bold.dominant_app, "com.test.b");
+    }
+
+    #[test]
+    fn minute_ring_counts_keys_per_minute_and_rotates_idle_minutes_out() {
+        use super::minute_series::{minute_counts, record_minute_key};
+
+        let mut state = build_state(HashMap::new());
+        let base_ms = 1_700_000_000_000 / 60_000 * 60_000;
+        record_minute_key(&mut state, base_ms + 1_000);
+        record_minute_key(&mut state, base_ms + 59_000);
+        record_minute_key(&mut state, base_ms + 2 * 60_000);
+        assert_eq!(
+            minute_counts(&state, base_ms + 2 * 60_000, 4),
+            vec![0, 2, 0, 1]
+        );
+        // Reads between ticks already treat unrotated minutes as empty.
+        assert_eq!(
+            minute_counts(&state, base_ms + 4 * 60_000, 3),
+            vec![1, 0, 0]
+        );
+
+        rotate_minute_ring(&mut state, base_ms + 61 * 60_000);
+        let mut expected = vec![0; 60];
+        expected[0] = 1;
+        assert_eq!(minute_counts(&state, base_ms + 61 * 60_000, 60), expected);
+        rotate_minute_ring(&mut state, base_ms + 3 * 60 * 60_000);
+        assert_eq!(minute_counts(&state, base_ms + 3 * 60 * 60_000, 0), vec![0]);
+        assert!(minute_counts(&state, base_ms + 3 * 60 * 60_000, 600)
+            .iter()
+            .all(|count| *count == 0));
+    }
+
+    #[test]
+    fn non_typing_likelihood_separates_movement_keys_from_prose() {
+        use super::non_typing::{non_typing_likelihood, KeyClassCounts};
+
+        let prose = KeyClassCounts {
+            movement: 80,
+            letters: 320,
+            other: 100,
+        };
+        assert_eq!(non_typing_likelihood(&prose), 0.0);
+        let game = KeyClassCounts {
+            movement: 900,
+            letters: 60,
+            other: 240,
+        };
+        assert!(non_typing_likelihood(&game) > 0.9);
+        let short_game_day = KeyClassCounts {
+            movement: 150,
+            letters: 0,
+            other: 0,
+        };
+        assert_eq!(non_typing_likelihood(&short_game_day), 0.0);
+        let digits_only = KeyClassCounts {
+            movement: 0,
+            letters: 0,
+            other: 500,
+        };
+        assert_eq!(non_typing_likelihood(&digits_only), 0.0);
+    }
+
+    #[test]
+    fn non_typing_suggestion_needs_three_flagged_days_and_is_raised_once() {
+        use super::non_typing::refresh_non_typing_suggestions;
+        use super::shortcut::{local_day_window_ms, InputEventChunk};
+
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let mut state = build_state(HashMap::new());
+        for (app_ref, app) in [
+            (1, "com.test.game"),
+            (2, "com.test.editor"),
+            (3, "com.test.maps"),
+        ] {
+            state.app_dict.insert(app_ref, app.to_string());
+        }
+        state
+            .excluded_bundle_ids
+            .insert("com.test.maps".to_string());
+        let keys_for = |app_ref: u32| -> Vec<&str> {
+            match app_ref {
+                1 | 3 => vec!["w", "w", "a", "d", "space", "s", "w", "left"],
+                _ => vec!["t", "h", "e", "space", "a", "n", "d", "o"],
+            }
+        };
+        for days_ago in 1..=3 {
+            let day = today - chrono::Duration::days(days_ago);
+            let (day_start_ms, _) = local_day_window_ms(day).unwrap();
+            for app_ref in 1..=3 {
+                let events = keys_for(app_ref)
+                    .into_iter()
+                    .cycle()
+                    .take(320)
+                    .enumerate()
+                    .map(|(index, key)| format!("{},d,{key},0", index * 10))
+                    .collect();
+                state.event_chunks.push(InputEventChunk {
+                    v: 1,
+                    chunk_start_ms: day_start_ms + 12 * 60 * 60 * 1000 + i64::from(app_ref),
+                    app_ref,
+                    events,
+                });
+            }
+        }
+
+        // Two flagged days are not enough.
+        let yesterday = today - chrono::Duration::days(1);
+        refresh_non_typing_suggestions(&mut state, yesterday);
+        assert!(state.non_typing_suggestions.is_empty());
+
+        refresh_non_typing_suggestions(&mut state, today);
+        let pending: Vec<(&str, usize)> = state
+            .non_typing_suggestions
+            .iter()
+            .map(|suggestion| (suggestion.bundle_id.as_str(), suggestion.days))
+            .collect();
+        assert_eq!(pending, vec![("com.test.game", 3)]);
+        assert!(state.non_typing_suggestions[0].likelihood >= 0.6);
+        assert_eq!(state.snapshot().non_typing_suggestions.len(), 1);
+
+        // Dismissed apps are remembered and not raised again on later days.
+        assert!(state.resolve_non_typing_suggestion("COM.test.game"));
+        assert_eq!(
+            state.non_typing_handled(),
+            vec!["com.test.game".to_string()]
+        );
+        refresh_non_typing_suggestions(&mut state, today + chrono::Duration::days(1));
+        assert!(state.non_typing_suggestions.is_empty());
+    }
+
+    #[test]
+    fn shortcut_daily_series_matches_typed_variants_and_fills_zero_days() {
+        use super::shortcut::{local_day_window_ms, InputEventChunk};
+        use super::shortcut_series::shortcut_daily_series_until;
+
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let (today_start_ms, _) = local_day_window_ms(today).unwrap();
+        let (two_days_ago_ms, _) = local_day_window_ms(today - chrono::Duration::days(2)).unwrap();
+        let mut state = build_state(HashMap::new());
+        state.app_dict.insert(1, "com.test.editor".to_string());
+        for (chunk_start_ms, events) in [
+            // shift+cmd p, cmd p (another shortcut) and shift+cmd+fn p.
+            (
+                two_days_ago_ms + 60_000,
+                vec!["0,d,p,12", "40,u,p,12", "90,d,p,8"],
+            ),
+            (today_start_ms + 60_000, vec!["0,d,p,12", "200,d,p,28"]),
+            // Before the window.
+            (today_start_ms - 10 * 86_400_000, vec!["0,d,p,12"]),
+        ] {
+            state.event_chunks.push(InputEventChunk {
+                v: 1,
+                chunk_start_ms,
+                app_ref: 1,
+                events: events.into_iter().map(str::to_string).collect(),
+            });
+        }
+
+        let expected: Vec<(&str, u64)> = vec![
+            ("2026-03-07", 0),
+            ("2026-03-08", 1),
+            ("2026-03-09", 0),
+            ("2026-03-10", 2),
+        ];
+        for typed in ["shift_cmd_p", "cmd_shift_p", " Cmd+Shift+P "] {
+            let series = shortcut_daily_series_until(&state, typed, 4, today);
+            let counts: Vec<(&str, u64)> = series
+                .iter()
+                .map(|day| (day.date.as_str(), day.count))
+                .collect();
+            assert_eq!(counts, expected, "{typed}");
+        }
+        for invalid in ["", "p", "cmd_shift", "hyper_p"] {
+            assert!(shortcut_daily_series_until(&state, invalid, 4, today).is_empty());
+        }
+    }
+
+    #[test]
+    fn reconstruct_day_ignores_chunks_started_after_as_of() {
+        use super::shortcut::{local_day_window_ms, InputEventChunk};
+
+        let day = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let (day_start_ms, _) = local_day_window_ms(day).unwrap();
+        let ten_am_ms = day_start_ms + 10 * 60 * 60 * 1000;
+        let stored = |app: &str, key_count: u64| {
+            (
+                StatsKey {
+                    date: MinuteKey::new(day, 10, 0).unwrap(),
+                    app_name: app.to_string(),
+                    window_title: String::new(),
+                },
+                StatsValue {
+                    active_typing_ms: 0,
+                    key_count,
+                    session_count: 1,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            )
+        };
+        let mut state = build_state(HashMap::from([
+            stored("com.test.a", 3),
+            stored("com.test.b", 1),
+        ]));
+        state.app_dict.insert(1, "com.test.a".to_string());
+        state.app_dict.insert(2, "com.test.b".to_string());
+        for (chunk_start_ms, app_ref, events) in [
+            // The previous day is outside the window whatever the cut.
+            (day_start_ms - 1_000, 1, vec!["0,d,a,0"]),
+            (ten_am_ms, 1, vec!["0,d,a,0", "80,u,a,0", "120,d,b,0"]),
+            (ten_am_ms + 10_000, 1, vec!["0,d,c,0"]),
+            (ten_am_ms + 20_000, 2, vec!["0,d,d,0"]),
+        ] {
+            state.event_chunks.push(InputEventChunk {
+                v: 1,
+                chunk_start_ms,
+                app_ref,
+                events: events.into_iter().map(str::to_string).collect(),
+            });
+        }
+
+        let early = super::reconstruct_day(&state, "2026-03-10", ten_am_ms + 5_000).unwrap();
+        assert_eq!(early.chunk_count, 1);
+        assert_eq!(early.reconstructed_keys, 2);
+        assert_eq!(early.stored_keys, 4);
+        let diffs: Vec<(&str, u64, u64, i64)> = early
+            .apps
+            .iter()
+            .map(|app| {
+                (
+                    app.app_name.as_str(),
+                    app.reconstructed_keys,
+                    app.stored_keys,
+                    app.diff,
+                )
+            })
+            .collect();
+        assert_eq!(
+            diffs,
+            vec![("com.test.a", 2, 3, -1), ("com.test.b", 0, 1, -1)]
+        );
+
+        let late = super::reconstruct_day(&state, "2026-03-10", ten_am_ms + 20_000).unwrap();
+        assert_eq!(late.chunk_count, 3);
+        assert!(late.apps.iter().all(|app| app.diff == 0));
+
+        assert!(super::reconstruct_day(&state, "03/10/2026", ten_am_ms).is_err());
+    }
+
+    #[test]
+    fn raw_event_export_decodes_the_window_and_refuses_long_ranges() {
+        use super::shortcut::{InputEventChunk, OpenInputEventChunk};
+        use super::{snapshot_raw_events, write_raw_events};
+
+        let hour_ms = 60 * 60 * 1000;
+        let start_ms = 1_773_136_800_000;
+        let mut state = build_state(HashMap::new());
+        state.app_dict.insert(1, "com.test.a".to_string());
+        state.app_dict.insert(2, "com.test.b".to_string());
+        for (chunk_start_ms, app_ref, events) in [
+            (start_ms - 1_000, 1, vec!["0,d,x,0", "1200,d,a,0"]),
+            (start_ms + 5_000, 2, vec!["0,d,s,9", "40,u,s,9"]),
+            (start_ms + hour_ms, 1, vec!["0,d,late,0"]),
+        ] {
+            state.event_chunks.push(InputEventChunk {
+                v: 1,
+                chunk_start_ms,
+                app_ref,
+                events: events.into_iter().map(str::to_string).collect(),
+            });
+        }
+        state.open_event_chunk = Some(OpenInputEventChunk {
+            chunk_start_ms: start_ms + 10_000,
+            started_at: Instant::now(),
+            app_ref: 1,
+            events: vec!["0,d,4,b".to_string()],
+        });
+
+        let records = snapshot_raw_events(&state, start_ms, start_ms + hour_ms, hour_ms).unwrap();
+        let decoded: Vec<(i64, &str, &str, Vec<&str>, &str)> = records
+            .iter()
+            .map(|record| {
+                (
+                    record.ts_ms - start_ms,
+                    record.app_id.as_str(),
+                    record.key.as_str(),
+                    record.modifiers.clone(),
+                    record.event_type,
+                )
+            })
+            .collect();
+        assert_eq!(
+            decoded,
+            vec![
+                (200, "com.test.a", "a", vec![], "down"),
+                (5_000, "com.test.b", "s", vec!["ctrl", "cmd"], "down"),
+                (5_040, "com.test.b", "s", vec!["ctrl", "cmd"], "up"),
+                (10_000, "com.test.a", "b", vec!["shift"], "down"),
+            ]
+        );
+
+        let dir = temp_dir("raw-events");
+        let path = dir.join("events.jsonl");
+        let report = write_raw_events(&records, &path, start_ms, start_ms + hour_ms).unwrap();
+        assert_eq!(report.events, 4);
+        let jsonl = std::fs::read_to_string(&path).unwrap();
+        let first: serde_json::Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(
+            first,
+            serde_json::json!({
+                "ts_ms": start_ms + 200,
+                "app_id": "com.test.a",
+                "key": "a",
+                "modifiers": [],
+                "event_type": "down",
+            })
+        );
+        assert_eq!(jsonl.lines().count(), 4);
+
+        assert!(snapshot_raw_events(&state, start_ms, start_ms + hour_ms + 1, hour_ms).is_err());
+        assert!(snapshot_raw_events(&state, start_ms, start_ms, hour_ms).is_err());
+    }
+
+    #[test]
+    fn weekly_comparison_fires_once_from_monday_morning_with_a_localized_summary() {
+        use super::shortcut::{local_day_window_ms, InputEventChunk};
+        use super::weekly::weekly_comparison_text;
+
+        let row = |day: &str, app: &str, key_count: u64| {
+            (
+                StatsKey {
+                    date: format!("{day} 10:00").parse().unwrap(),
+                    app_name: app.to_string(),
+                    window_title: String::new(),
+                },
+                StatsValue {
+                    active_typing_ms: key_count * 360,
+                    key_count,
+                    session_count: 1,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            )
+        };
+        // 2026-03-16 is a Monday; last week starts 2026-03-09.
+        let mut state = build_state(HashMap::from([
+            row("2026-03-03", "com.test.editor", 10_000),
+            row("2026-03-10", "com.test.editor", 4_000),
+            row("2026-03-15", "com.test.terminal", 7_200),
+        ]));
+        state.app_dict.insert(1, "com.test.editor".to_string());
+        let (tuesday_ms, _) =
+            local_day_window_ms(NaiveDate::from_ymd_opt(2026, 3, 10).unwrap()).unwrap();
+        state.event_chunks.push(InputEventChunk {
+            v: 1,
+            chunk_start_ms: tuesday_ms + 36_000_000,
+            app_ref: 1,
+            events: ["0,d,s,8", "50,u,s,8", "900,d,s,8", "950,u,s,8"]
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        });
+        let at = |day: u32, hour: u32| {
+            chrono::Local
+                .with_ymd_and_hms(2026, 3, day, hour, 0, 0)
+                .single()
+                .unwrap()
+        };
+
+        assert!(state.take_weekly_comparison_due(at(16, 8)).is_none());
+        let comparison = state.take_weekly_comparison_due(at(16, 10)).unwrap();
+        assert_eq!(comparison.week.week_start, "2026-03-09");
+        assert_eq!(comparison.week.key_count, 11_200);
+        assert_eq!(comparison.previous_week.key_count, 10_000);
+        assert_eq!(
+            comparison.week.top_app.as_deref(),
+            Some("com.test.terminal")
+        );
+        assert_eq!(comparison.key_change_pct, Some(12.0));
+        let gain = comparison.top_shortcut_gain.clone().unwrap();
+        assert_eq!((gain.shortcut_id.as_str(), gain.count), ("cmd_s", 2));
+        assert_eq!(
+            comparison.summary,
+            "2026-03-09 起的一周输入 11,200 键，比前一周多 12%；活跃打字 1.1 小时（前一周 1.0 小时）；\
+             最常用应用从 com.test.editor 变为 com.test.terminal；增长最多的快捷键是 cmd_s（+2）。"
+        );
+        assert_eq!(
+            weekly_comparison_text(&comparison, false),
+            "Week of 2026-03-09: 11,200 keys, 12% more than the week before; 1.1 active typing \
+             hours (1.0 the week before); top app changed from com.test.editor to \
+             com.test.terminal; biggest shortcut gainer cmd_s (+2)."
+        );
+
+        // Announced weeks persist with the analytics and stay quiet for the rest of the week.
+        assert!(state.take_weekly_comparison_due(at(16, 11)).is_none());
+        assert!(state.take_weekly_comparison_due(at(20, 11)).is_none());
+        let stored = build_stored_input_analytics(&mut state);
+        assert_eq!(
+            stored.weekly_comparisons,
+            HashMap::from([("2026-03-16".to_string(), "2026-03-09".to_string())])
+        );
+
+        state.set_weekly_comparison_notifications(false);
+        assert!(state.take_weekly_comparison_due(at(23, 10)).is_none());
+        assert_eq!(state.weekly_comparisons.len(), 1);
+
+        // Two empty weeks are marked without a notification.
+        state.set_weekly_comparison_notifications(true);
+        assert!(state.take_weekly_comparison_due(at(30, 10)).is_none());
+        assert_eq!(state.weekly_comparisons.len(), 2);
+    }
+
+    #[test]
+    fn top_keys_count_key_downs_per_app_inside_the_day_window() {
+        use super::shortcut::{
+            local_day_window_ms, snapshot_top_keys_in_window, InputEventChunk, OpenInputEventChunk,
+            DEFAULT_SHORTCUT_APP_LIMIT,
+        };
+
+        let day = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let (day_start_ms, day_end_ms) = local_day_window_ms(day).unwrap();
+        let mut state = build_state(HashMap::new());
+        for app_ref in 1..=10u32 {
+            state
+                .app_dict
+                .insert(app_ref, format!("com.test.app{app_ref:02}"));
+        }
+        let chunk = |chunk_start_ms: i64, app_ref: u32, events: &[&str]| InputEventChunk {
+            v: 1,
+            chunk_start_ms,
+            app_ref,
+            events: events.iter().map(|event| event.to_string()).collect(),
+        };
+        state.event_chunks = vec![
+            // Starts the evening before; only the events past midnight count.
+            chunk(
+                day_start_ms - 2_000,
+                1,
+                &[
+                    "0,d,a,0",
+                    "500,u,a,0",
+                    "2000,d,a,0",
+                    "2100,d,c,0",
+                    "2300,d,space,0",
+                ],
+            ),
+            // Malformed events are skipped like everywhere else chunks are replayed.
+            chunk(
+                day_start_ms + 60_000,
+                2,
+                &["0,d,a,0", "garbage", "x,d,a,0", "10,q,a,0", "20,d,a,0"],
+            ),
+            // The next day is outside the window.
+            chunk(day_end_ms, 1, &["0,d,a,0", "10,d,a,0"]),
+        ];
+        // Presses in more apps than a row lists end up in `other_count`.
+        for app_ref in 3..=10u32 {
+            state.event_chunks.push(chunk(
+                day_start_ms + 120_000 + i64::from(app_ref),
+                app_ref,
+                &["0,d,space,0"],
+            ));
+        }
+        state
+            .event_chunks
+            .push(chunk(day_start_ms + 130_000, 10, &["0,d,space,0"]));
+        state.open_event_chunk = Some(OpenInputEventChunk {
+            chunk_start_ms: day_end_ms - 1_000,
+            started_at: Instant::now(),
+            app_ref: 2,
+            events: vec!["0,d,0,b".to_string(), "2000,d,0,b".to_string()],
+        });
+
+        let rows = snapshot_top_keys_in_window(&state, day_start_ms, day_end_ms, None);
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| {
+                (
+                    row.key.clone(),
+                    row.count,
+                    row.apps
+                        .iter()
+                        .map(|app| (app.app_name.clone(), app.count))
+                        .collect::<Vec<_>>(),
+                    row.other_count,
+                )
+            })
+            .collect();
+        let apps = |apps: &[(u32, u64)]| -> Vec<(String, u64)> {
+            apps.iter()
+                .map(|(app_ref, count)| (format!("com.test.app{app_ref:02}"), *count))
+                .collect()
+        };
+        // Ten presses of space in nine apps: the busiest app first, ties by name, the ninth
+        // app's press in `other_count`.
+        let space_apps = apps(&[
+            (10, 2),
+            (1, 1),
+            (3, 1),
+            (4, 1),
+            (5, 1),
+            (6, 1),
+            (7, 1),
+            (8, 1),
+        ]);
+        assert_eq!(space_apps.len(), DEFAULT_SHORTCUT_APP_LIMIT);
+        assert_eq!(
+            summary,
+            vec![
+                ("space".to_string(), 10, space_apps, 1),
+                ("a".to_string(), 3, apps(&[(2, 2), (1, 1)]), 0),
+                ("b".to_string(), 1, apps(&[(2, 1)]), 0),
+                ("c".to_string(), 1, apps(&[(1, 1)]), 0),
+            ]
+        );
+
+        let editor =
+            snapshot_top_keys_in_window(&state, day_start_ms, day_end_ms, Some("com.test.app01"));
+        assert_eq!(
+            editor
+                .iter()
+                .map(|row| (row.key.as_str(), row.count))
+                .collect::<Vec<_>>(),
+            vec![("a", 1), ("c", 1), ("space", 1)]
+        );
+    }
+
+    #[test]
+    fn range_queries_fall_back_to_lifetime_aggregates_without_event_chunks() {
+        let mut harness = CollectorEventHarness::new();
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let three_days_ago_ms = now_ms - 3 * 24 * 60 * 60 * 1000;
+        harness.state.app_dict.insert(1, "com.test.a".to_string());
+        harness
+            .state
+            .app_ref_by_app
+            .insert("com.test.a".to_string(), 1);
+        harness.state.next_app_ref = 2;
+        harness.state.shortcut_usage.insert(
+            "cmd_c".to_string(),
+            super::ShortcutUsageValue {
+                count: 5,
+                by_app: HashMap::from([("com.test.a".to_string(), 5)]),
+            },
+        );
+        for (start_ms, events) in [
+            (three_days_ago_ms, vec!["0,d,b,8".to_string(); 4]),
+            (now_ms, vec!["0,d,v,8".to_string(), "5,d,a,0".to_string()]),
+        ] {
+            harness
+                .state
+                .event_chunks
+                .push(super::shortcut::InputEventChunk {
+                    v: 1,
+                    chunk_start_ms: start_ms,
+                    app_ref: 1,
+                    events,
+                });
+        }
+
+        // Recording on: ranges replay chunks.
+        let today = super::snapshot_shortcut_rows_by_range(&harness.state, "today", 8);
+        assert!(!today.degraded_range_support);
+        assert_eq!(today.rows.len(), 1);
+        assert_eq!(today.rows[0].shortcut_id, "cmd_v");
+        let keys = super::snapshot_top_keys_by_range(&harness.state, "today");
+        assert!(!keys.degraded_range_support);
+        assert_eq!(keys.rows.len(), 2);
+        assert!(!super::snapshot_shortcut_breadth(&harness.state, "today").degraded_range_support);
+
+        // A key typed while recording is on leaves an open chunk; turning recording off closes
+        // it whole instead of dropping it.
+        harness.key_down("x", false, Instant::now());
+        harness.key_up("x");
+        assert!(harness.state.open_event_chunk.is_some());
+        harness.state.set_record_event_chunks(false);
+        assert!(harness.state.open_event_chunk.is_none());
+        assert_eq!(harness.state.event_chunks.len(), 3);
+
+        // Recording off: every range reports the lifetime aggregates with the marker set.
+        let yesterday = super::snapshot_shortcut_rows_by_range(&harness.state, "yesterday", 8);
+        assert!(yesterday.degraded_range_support);
+        let ids: Vec<&str> = yesterday
+            .rows
+            .iter()
+            .map(|row| row.shortcut_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["cmd_c"]);
+        let keys = super::snapshot_top_keys_by_range(&harness.state, "yesterday");
+        assert!(keys.degraded_range_support);
+        assert_eq!(
+            keys.rows
+                .iter()
+                .map(|row| (row.key.as_str(), row.count))
+                .collect::<Vec<_>>(),
+            vec![("b", 4), ("a", 1), ("v", 1), ("x", 1)]
+        );
+        let breadth = super::snapshot_shortcut_breadth(&harness.state, "yesterday");
+        assert!(breadth.degraded_range_support);
+        assert_eq!(breadth.shortcuts[0].shortcut_id, "cmd_c");
+
+        // No events are appended and nothing is persisted, but in-memory chunks stay intact.
+        harness.key_down("y", false, Instant::now());
+        harness.key_up("y");
+        assert!(harness.state.open_event_chunk.is_none());
+        let stored = build_stored_input_analytics(&mut harness.state);
+        assert!(stored.event_chunks.is_empty());
+        assert_eq!(stored.shortcut_usage["cmd_c"].count, 5);
+        assert_eq!(harness.state.event_chunks.len(), 3);
+
+        // Turning it back on resumes recording and persists every chunk with a valid app ref.
+        harness.state.set_record_event_chunks(true);
+        harness.key_down("z", false, Instant::now());
+        harness.key_up("z");
+        let stored = build_stored_input_analytics(&mut harness.state);
+        assert_eq!(stored.event_chunks.len(), 4);
+        assert!(stored
+            .event_chunks
+            .iter()
+            .all(|chunk| stored.app_dict.contains_key(&chunk.app_ref)));
+    }
+
+    fn heatmap_counts(entries: &[(&str, u64)]) -> HashMap<String, u64> {
+        entries
+            .iter()
+            .map(|(key, count)| (key.to_string(), *count))
+            .collect()
+    }
+
+    #[test]
+    fn key_heatmap_joins_layout_and_collects_unplaced_keys() {
+        let counts = heatmap_counts(&[
+            ("a", 40),
+            ("enter", 7),
+            ("minus", 3),
+            ("-", 2),
+            ("k160", 5),
+            ("volumeup", 1),
+        ]);
+        let heatmap = super::heatmap::build_key_heatmap(
+            &counts,
+            "today",
+            super::heatmap::KeyboardLayout::Iso,
+        );
+        assert_eq!(heatmap.layout, "iso");
+        assert_eq!(heatmap.max_count, 40);
+        let find = |name: &str| heatmap.keys.iter().find(|key| key.key == name).unwrap();
+        assert_eq!(find("a").count, 40);
+        assert_eq!(find("-").count, 5);
+        assert_eq!(find("enter").height, 2.0);
+        assert_eq!(find("intlbackslash").count, 0);
+        let other: Vec<(&str, u64)> = heatmap
+            .other
+            .iter()
+            .map(|row| (row.key.as_str(), row.count))
+            .collect();
+        assert_eq!(other, vec![("k160", 5), ("volumeup", 1)]);
+
+        let ansi = super::heatmap::build_key_heatmap(
+            &counts,
+            "today",
+            super::heatmap::KeyboardLayout::Ansi,
+        );
+        assert!(ansi.keys.iter().all(|key| key.key != "intlbackslash"));
+        assert!(super::snapshot_key_heatmap(&build_state(HashMap::new()), "today", "jis").is_err());
+    }
+
+    #[test]
+    fn key_heatmap_svg_matches_golden_file() {
+        let counts = heatmap_counts(&[
+            ("e", 120),
+            ("a", 60),
+            ("space", 200),
+            ("backspace", 35),
+            ("'", 1),
+            ("left", 4),
+        ]);
+        let heatmap =
+            super::heatmap::build_key_heatmap(&counts, "7d", super::heatmap::KeyboardLayout::Ansi);
+        let svg = super::render_key_heatmap_svg(&heatmap);
+        // Regenerate by writing `svg` to testdata/key-heatmap-ansi.svg when the renderer changes.
+        assert_eq!(svg, include_str!("../testdata/key-heatmap-ansi.svg"));
+    }
+
+    #[test]
+    fn app_timeline_buckets_interleaved_apps_from_local_midnight() {
+        let mut stats = HashMap::new();
+        let mut insert = |date: &str, app: &str, keys: u64, active: u64| {
+            stats.insert(
+                StatsKey {
+                    date: date.parse().unwrap(),
+                    app_name: app.to_string(),
+                    window_title: "Doc".to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: active,
+                    key_count: keys,
+                    session_count: 1,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            );
+        };
+        insert("2026-03-02 09:05", "AppA", 5, 500);
+        insert("2026-03-02 09:10", "AppB", 20, 2000);
+        insert("2026-03-02 09:20", "AppA", 6, 600);
+        insert("2026-03-02 09:50", "AppB", 3, 300);
+        insert("2026-03-02 10:10", "AppA", 4, 400);
+        // Below the activity threshold, and a row from another day.
+        insert("2026-03-02 09:15", "AppC", 2, 200);
+        insert("2026-03-03 09:05", "AppA", 50, 5000);
+        let state = build_state(stats);
+
+        let timeline = super::snapshot_app_timeline(&state, "2026-03-02", 30).unwrap();
+        let midnight = chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .and_then(|day| day.and_hms_opt(0, 0, 0))
+            .unwrap();
+        let day_start_ms = chrono::Local
+            .from_local_datetime(&midnight)
+            .earliest()
+            .unwrap()
+            .timestamp_millis();
+        let at = |hour: i64, minute: i64| day_start_ms + (hour * 60 + minute) * 60_000;
+        assert_eq!(timeline.bucket_minutes, 30);
+        assert_eq!(timeline.day_start_ms, day_start_ms);
+        assert!(timeline.day_end_ms > timeline.day_start_ms);
+
+        let strips: Vec<_> = timeline
+            .apps
+            .iter()
+            .map(|app| {
+                let buckets: Vec<(i64, u64, u64)> = app
+                    .buckets
+                    .iter()
+                    .map(|bucket| (bucket.bucket_start_ms, bucket.key_count, bucket.active_ms))
+                    .collect();
+                (app.app_name.clone(), app.key_count, buckets)
+            })
+            .collect();
+        assert_eq!(
+            strips,
+            vec![
+                (
+                    "AppB".to_string(),
+                    23,
+                    vec![(at(9, 0), 20, 2000), (at(9, 30), 3, 300)],
+                ),
+                (
+                    "AppA".to_string(),
+                    15,
+                    vec![(at(9, 0), 11, 1100), (at(10, 0), 4, 400)],
+                ),
+            ]
+        );
+        assert!(super::snapshot_app_timeline(&state, "2026-03-02", 0).is_err());
+        assert!(super::snapshot_app_timeline(&state, "not-a-date", 30).is_err());
+    }
+
+    fn comparison_state() -> CollectorState {
+        let local_ms = |day: u32, hour: u32| {
+            let naive = chrono::NaiveDate::from_ymd_opt(2026, 3, day)
+                .and_then(|date| date.and_hms_opt(hour, 0, 0))
+                .unwrap();
+            chrono::Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .unwrap()
+                .timestamp_millis()
+        };
+        let mut stats = HashMap::new();
+        let mut insert = |date: &str, app: &str, keys: u64, active: u64, sessions: u64| {
+            stats.insert(
+                StatsKey {
+                    date: date.parse().unwrap(),
+                    app_name: app.to_string(),
+                    window_title: "Doc".to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: active,
+                    key_count: keys,
+                    session_count: sessions,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            );
+        };
+        insert("2026-03-02 09:05", "com.test.a", 30, 3000, 2);
+        insert("2026-03-02 10:05", "com.test.b", 10, 1000, 1);
+        insert("2026-03-03 09:05", "com.test.a", 5, 500, 1);
+        let mut state = build_state(stats);
+        state.app_dict.insert(1, "com.test.a".to_string());
+        state.app_dict.insert(2, "com.test.b".to_string());
+        let day_a = local_ms(2, 9);
+        let day_b = local_ms(3, 9);
+        for (start, app_ref, events) in [
+            (day_a, 1, vec!["0,d,c,8", "10,d,c,8", "20,d,a,0"]),
+            (day_a + 60_000, 2, vec!["0,d,v,8"]),
+            (day_b, 1, vec!["0,d,z,8"]),
+        ] {
+            state.event_chunks.push(super::shortcut::InputEventChunk {
+                v: 1,
+                chunk_start_ms: start,
+                app_ref,
+                events: events.into_iter().map(str::to_string).collect(),
+            });
+        }
+        state
+    }
+
+    #[test]
+    fn comparison_of_two_days_aligns_metrics_and_deltas() {
+        let state = comparison_state();
+        let Ok(comparison) =
+            super::snapshot_comparison(&state, "2026-03-02", "2026-03-03", "date", None)
+        else {
+            panic!("date comparison failed");
+        };
+        assert_eq!(comparison.kind, "date");
+        assert_eq!(comparison.range, None);
+        assert_eq!(comparison.a.subject, "2026-03-02");
+        assert_eq!(
+            (
+                comparison.a.key_count,
+                comparison.a.active_typing_ms,
+                comparison.a.session_count
+            ),
+            (40, 4000, 3)
+        );
+        assert_eq!(
+            (
+                comparison.b.key_count,
+                comparison.b.active_typing_ms,
+                comparison.b.session_count
+            ),
+            (5, 500, 1)
+        );
+        assert_eq!(comparison.delta.key_count, -35);
+        assert_eq!(comparison.delta.active_typing_ms, -3500);
+        assert_eq!(comparison.delta.session_count, -2);
+        let shortcuts: Vec<(String, u64)> = comparison
+            .a
+            .top_shortcuts
+            .iter()
+            .map(|row| (row.shortcut_id.clone(), row.count))
+            .collect();
+        assert_eq!(
+            shortcuts,
+            vec![("cmd_c".to_string(), 2), ("cmd_v".to_string(), 1)]
+        );
+        assert_eq!(comparison.b.top_keys.len(), 1);
+        assert_eq!(comparison.b.top_keys[0].key, "z");
+    }
+
+    #[test]
+    fn comparison_of_two_apps_filters_by_app_and_rejects_bad_subjects() {
+        let mut state = comparison_state();
+        let today = chrono::Local::now();
+        let minute = today.format("%Y-%m-%d %H:%M").to_string();
+        for (app, keys) in [("com.test.a", 7), ("com.test.b", 3)] {
+            state.stats.insert(
+                StatsKey {
+                    date: minute.parse().unwrap(),
+                    app_name: app.to_string(),
+                    window_title: "Doc".to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: keys * 100,
+                    key_count: keys,
+                    session_count: 1,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            );
+        }
+        state.event_chunks.push(super::shortcut::InputEventChunk {
+            v: 1,
+            chunk_start_ms: today.timestamp_millis(),
+            app_ref: 2,
+            events: vec!["0,d,s,8".to_string()],
+        });
+        let Ok(comparison) =
+            super::snapshot_comparison(&state, "com.test.a", "com.test.b", "app", Some("today"))
+        else {
+            panic!("app comparison failed");
+        };
+        assert_eq!(comparison.range.as_deref(), Some("today"));
+        assert_eq!((comparison.a.key_count, comparison.b.key_count), (7, 3));
+        assert_eq!(comparison.delta.key_count, -4);
+        assert!(comparison.a.top_shortcuts.is_empty());
+        assert_eq!(comparison.b.top_shortcuts.len(), 1);
+        assert_eq!(comparison.b.top_shortcuts[0].shortcut_id, "cmd_s");
+
+        let error = |result: Result<super::Comparison, super::ComparisonError>| result.err();
+        assert_eq!(
+            error(super::snapshot_comparison(
+                &state,
+                "com.test.a",
+                "com.missing",
+                "app",
+                None
+            )),
+            Some(super::ComparisonError::UnknownApp {
+                subject: "com.missing".to_string()
+            })
+        );
+        assert_eq!(
+            error(super::snapshot_comparison(
+                &state,
+                "2026-02-30",
+                "2026-03-03",
+                "date",
+                None
+            )),
+            Some(super::ComparisonError::InvalidDate {
+                subject: "2026-02-30".to_string()
+            })
+        );
+        assert_eq!(
+            error(super::snapshot_comparison(&state, "a", "b", "week", None)),
+            Some(super::ComparisonError::InvalidKind {
+                kind: "week".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn tick_loop_flushes_key_events_to_storage() {
+        let harness = TickLoopHarness::start("tick-flush", Duration::from_millis(5));
+        on_non_modifier_key_down(
+            &harness.state,
+            "k:a".to_string(),
+            "a".to_string(),
+            ModifierSnapshot::default(),
+            false,
+            None,
+        );
+        on_non_modifier_key_up(
+            &harness.state,
+            "k:a",
+            "a",
+            ModifierSnapshot::default(),
+            None,
+        );
+
+        let flushed = harness.wait_until(Duration::from_secs(2), |harness| {
+            harness.storage.saves.lock().unwrap().iter().any(|stats| {
+                stats.iter().any(|(key, value)| {
+                    key.app_name == "com.test.editor"
+                        && key.window_title == "Doc"
+                        && value.key_count == 1
+                })
+            })
+        });
+        assert!(flushed, "key event was not flushed to storage in time");
+        assert!(harness.state.lock().unwrap().log_path.exists());
+        harness.stop();
+    }
+
+    #[test]
+    fn tick_loop_exits_within_one_tick_after_shutdown() {
+        let tick = Duration::from_millis(200);
+        let mut harness = TickLoopHarness::start("tick-stop", tick);
+        assert!(harness.wait_until(Duration::from_secs(2), |harness| harness.save_count() > 0));
+
+        let started = Instant::now();
+        harness.join();
+        assert!(started.elapsed() < tick);
+        let saves_after_stop = harness.save_count();
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(harness.save_count(), saves_after_stop);
+        harness.stop();
+    }
+
+    #[test]
+    fn tick_loop_picks_up_new_interval_within_one_old_interval() {
+        let old_tick = Duration::from_millis(150);
+        let harness = TickLoopHarness::start("tick-interval", old_tick);
+        harness.state.lock().unwrap().set_timing_intervals(
+            Duration::from_millis(5),
+            Duration::from_millis(20),
+            Duration::from_secs(2),
+            Duration::from_millis(5),
+        );
+        // The sleep in progress still uses the old interval; the one after it is short.
+        std::thread::sleep(old_tick + Duration::from_millis(20));
+        let mut ticks = HashSet::new();
+        let sampled_until = Instant::now() + Duration::from_millis(60);
+        while Instant::now() < sampled_until {
+            ticks.insert(harness.state.lock().unwrap().last_tick_instant);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(ticks.len() >= 3, "only {} ticks in 60ms", ticks.len());
+        let locked = harness.state.lock().unwrap();
+        assert_eq!(locked.session_gap, Duration::from_secs(2));
+        assert_eq!(locked.tray_update_interval(), Duration::from_millis(5));
+        drop(locked);
+        harness.stop();
+    }
+
+    #[test]
+    fn tick_loop_survives_a_panicking_tick() {
+        let harness = TickLoopHarness::start("tick-panic", Duration::from_millis(5));
+        harness.state.lock().unwrap().context_provider =
+            Box::new(|| panic!("context capture failed"));
+        let logged = harness.wait_until(Duration::from_secs(2), |harness| {
+            harness.state.lock().unwrap().last_error.as_deref()
+                == Some("tick panicked: context capture failed")
+        });
+        assert!(logged, "tick panic was not turned into an error");
+
+        // The same thread keeps ticking once the cause is gone.
+        harness.state.lock().unwrap().context_provider =
+            build_state(HashMap::new()).context_provider;
+        let saves_before = harness.save_count();
+        assert!(harness.wait_until(Duration::from_secs(2), |harness| {
+            harness.save_count() > saves_before
+        }));
+        assert!(!harness.handle.as_ref().unwrap().is_finished());
+        harness.stop();
+    }
+
+    #[test]
+    fn watchdog_retires_stale_tick_loop_and_starts_one_replacement() {
+        use super::watchdog::check_tick_heartbeat;
+
+        let mut harness = TickLoopHarness::start("tick-watchdog", Duration::from_millis(5));
+        {
+            let mut locked = harness.state.lock().unwrap();
+            assert!(!check_tick_heartbeat(&mut locked, Instant::now()));
+            assert!(check_tick_heartbeat(
+                &mut locked,
+                Instant::now() + Duration::from_secs(31)
+            ));
+            let snapshot = locked.snapshot();
+            assert!(snapshot.tick_stalled);
+            assert!(snapshot
+                .last_error
+                .unwrap()
+                .starts_with("tick loop stalled for"));
+        }
+
+        // The retired loop exits on its next tick; the replacement takes over flushing.
+        let retired = harness.handle.take().unwrap();
+        assert!(harness.wait_until(Duration::from_secs(2), |_| retired.is_finished()));
+        retired.join().unwrap();
+        harness.handle = Some(start_tick_loop(harness.state.clone()));
+        let saves_before = harness.save_count();
+        assert!(harness.wait_until(Duration::from_secs(2), |harness| {
+            harness.save_count() > saves_before
+        }));
+        harness.stop();
+    }
+
+    // Detail storage whose stats saves fail a set number of times before succeeding.
+    #[derive(Clone, Default)]
+    struct FlakyStorage {
+        failures_left: Arc<Mutex<u32>>,
+        saves: Arc<Mutex<Vec<HashMap<StatsKey, StatsValue>>>>,
+        rolled_up: Arc<Mutex<Vec<NaiveDate>>>,
+    }
+
+    impl DetailStorage for FlakyStorage {
+        fn load_stats(&self) -> Result<HashMap<StatsKey, StatsValue>, String> {
+            Ok(HashMap::new())
+        }
+
+        fn save_stats(&self, stats: &HashMap<StatsKey, StatsValue>) -> Result<(), String> {
+            let mut failures_left = self.failures_left.lock().unwrap();
+            if *failures_left > 0 {
+                *failures_left -= 1;
+                return Err("No space left on device".to_string());
+            }
+            self.saves.lock().unwrap().push(stats.clone());
+            Ok(())
+        }
+
+        fn is_rolled_up(&self, day: NaiveDate) -> bool {
+            self.rolled_up.lock().unwrap().contains(&day)
+        }
+
+        fn mark_rolled_up(&self, days: &[NaiveDate]) {
+            self.rolled_up.lock().unwrap().extend_from_slice(days);
+        }
+
+        fn load_input_analytics(&self) -> Result<StoredInputAnalytics, String> {
+            Ok(StoredInputAnalytics::default())
+        }
+
+        fn save_input_analytics(&self, _analytics: &StoredInputAnalytics) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rollup_marks_days_only_after_the_save_succeeds() {
+        use super::DAY_KEY_FORMAT;
+        use chrono::Local;
+
+        let old_day = (Local::now() - chrono::Duration::days(30)).date_naive();
+        let key = StatsKey {
+            date: format!("{} 10:05", old_day.format(DAY_KEY_FORMAT))
+                .parse()
+                .unwrap(),
+            app_name: "com.test.editor".to_string(),
+            window_title: "Doc".to_string(),
+        };
+        let value = StatsValue {
+            active_typing_ms: 100,
+            key_count: 3,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let storage = FlakyStorage {
+            failures_left: Arc::new(Mutex::new(1)),
+            ..FlakyStorage::default()
+        };
+        let mut state = build_state(HashMap::from([(key, value)]));
+        state.storage = Arc::new(storage.clone());
+
+        // A failed save leaves the day unmarked, so the next rollup writes it again.
+        assert!(state.rollup_old_stats().is_err());
+        assert!(storage.rolled_up.lock().unwrap().is_empty());
+        assert_eq!(state.rollup_old_stats(), Ok(1));
+        assert_eq!(*storage.rolled_up.lock().unwrap(), vec![old_day]);
+        assert_eq!(storage.saves.lock().unwrap().len(), 1);
+        assert_eq!(state.rollup_old_stats(), Ok(0));
+    }
+
+    #[test]
+    fn failed_flushes_retry_with_backoff_without_losing_stats() {
+        use super::storage_health::{flush_if_due, STORAGE_DEGRADED_AFTER};
+
+        let dir = temp_dir("storage-retry");
+        let key = |title: &str| StatsKey {
+            date: "2026-03-02 10:00".parse().unwrap(),
+            app_name: "com.test.editor".to_string(),
+            window_title: title.to_string(),
+        };
+        let value = |key_count| StatsValue {
+            active_typing_ms: 100,
+            key_count,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let storage = FlakyStorage {
+            failures_left: Arc::new(Mutex::new(2)),
+            ..FlakyStorage::default()
+        };
+        let mut state = build_state(HashMap::from([(key("Doc"), value(3))]));
+        state.storage = Arc::new(storage.clone());
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = dir.join("app.log");
+        let start = state.last_flush_instant + state.flush_interval;
+
+        assert!(flush_if_due(&mut state, start));
+        assert!(state
+            .snapshot()
+            .last_error
+            .is_some_and(|err| err.contains("No space left")));
+        // The first retry waits out its backoff instead of failing on every tick.
+        assert!(!flush_if_due(&mut state, start + Duration::from_secs(1)));
+        state.stats.insert(key("Notes"), value(2));
+        assert!(flush_if_due(&mut state, start + Duration::from_secs(5)));
+        assert!(!flush_if_due(&mut state, start + Duration::from_secs(14)));
+        state.stats.get_mut(&key("Doc")).unwrap().key_count += 4;
+        assert!(flush_if_due(&mut state, start + Duration::from_secs(15)));
+
+        let saves = storage.saves.lock().unwrap().clone();
+        assert_eq!(saves.len(), 1);
+        assert_eq!(saves[0][&key("Doc")].key_count, 7);
+        assert_eq!(saves[0][&key("Notes")].key_count, 2);
+        let snapshot = state.snapshot();
+        assert_eq!(snapshot.last_error, None);
+        assert!(!snapshot.storage_degraded);
+        // Back on the regular interval after recovering.
+        assert!(!flush_if_due(&mut state, start + Duration::from_secs(20)));
+
+        // Failures that outlast the degraded threshold raise the flag and one announcement.
+        *storage.failures_left.lock().unwrap() = u32::MAX;
+        let failing = start + Duration::from_secs(15) + state.flush_interval;
+        assert!(flush_if_due(&mut state, failing));
+        assert!(!state.snapshot().storage_degraded);
+        assert_eq!(state.take_storage_degraded_announcement(), None);
+        assert!(flush_if_due(&mut state, failing + STORAGE_DEGRADED_AFTER));
+        assert!(state.snapshot().storage_degraded);
+        assert!(state.take_storage_degraded_announcement().is_some());
+        assert_eq!(state.take_storage_degraded_announcement(), None);
+    }
+
+    #[test]
+    fn flush_now_saves_everything_and_finishes_the_open_chunk() {
+        use super::flush_now;
+        use super::maintenance::HEAVY_ANALYTICS_SAVE_BYTES;
+
+        let dir = temp_dir("flush-now");
+        let key = StatsKey {
+            date: "2026-03-02 10:00".parse().unwrap(),
+            app_name: "com.test.editor".to_string(),
+            window_title: "Doc".to_string(),
+        };
+        let value = StatsValue {
+            active_typing_ms: 100,
+            key_count: 3,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let storage = RecordingStorage::default();
+        let mut state = build_state(HashMap::from([(key.clone(), value)]));
+        state.storage = Arc::new(storage.clone());
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = dir.join("app.log");
+        state.app_dict = HashMap::from([(1, "com.test.editor".to_string())]);
+        state.open_event_chunk = Some(super::shortcut::OpenInputEventChunk {
+            chunk_start_ms: chrono::Utc::now().timestamp_millis(),
+            started_at: Instant::now(),
+            app_ref: 1,
+            events: vec!["0,d,1,v".to_string()],
+        });
+
+        // Unlike the periodic flush, large analytics are saved right away while typing.
+        state.chunk_counters.approx_bytes = HEAVY_ANALYTICS_SAVE_BYTES;
+        state.maintenance.note_key_down(Instant::now());
+        flush_now(&mut state).unwrap();
+        assert_eq!(storage.saves.lock().unwrap()[0][&key].key_count, 3);
+        assert_eq!(*storage.analytics_saves.lock().unwrap(), 1);
+        assert!(state.open_event_chunk.is_none());
+        assert_eq!(state.event_chunks.len(), 1);
+        assert!(state.log_path.exists());
+
+        // Nothing is written while the data directory probe fails.
+        state.storage_writable = false;
+        assert!(flush_now(&mut state).is_err());
+        assert_eq!(storage.saves.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn heavy_disk_work_waits_for_idle_or_the_maximum_deferral() {
+        use super::maintenance::{
+            queue_maintenance, run_maintenance_if_due, MaintenanceJob, HEAVY_ANALYTICS_SAVE_BYTES,
+        };
+        use super::storage_health::flush_if_due;
+
+        let dir = temp_dir("maintenance");
+        let storage = RecordingStorage::default();
+        let mut state = build_state(HashMap::new());
+        state.storage = Arc::new(storage.clone());
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = dir.join("app.log");
+        let analytics_saves = || *storage.analytics_saves.lock().unwrap();
+        let start = state.last_flush_instant + state.flush_interval;
+
+        // Small analytics are saved with every flush, typing or not.
+        state.maintenance.note_key_down(start);
+        assert!(flush_if_due(&mut state, start));
+        assert_eq!(
+            (storage.saves.lock().unwrap().len(), analytics_saves()),
+            (1, 1)
+        );
+
+        // Large ones wait while keys keep going down; the stats save keeps its cadence.
+        state.chunk_counters.approx_bytes = HEAVY_ANALYTICS_SAVE_BYTES;
+        let second_flush = start + state.flush_interval;
+        state.maintenance.note_key_down(second_flush);
+        assert!(flush_if_due(&mut state, second_flush));
+        queue_maintenance(&mut state, MaintenanceJob::Rollup, second_flush);
+        assert_eq!(
+            (storage.saves.lock().unwrap().len(), analytics_saves()),
+            (2, 1)
+        );
+        assert!(state.maintenance.is_queued(MaintenanceJob::AnalyticsSave));
+        run_maintenance_if_due(&mut state, second_flush + Duration::from_secs(20));
+        state
+            .maintenance
+            .note_key_down(second_flush + Duration::from_secs(20));
+        run_maintenance_if_due(&mut state, second_flush + Duration::from_secs(49));
+        assert_eq!(analytics_saves(), 1);
+
+        // 30s without a key-down run every queued job.
+        run_maintenance_if_due(&mut state, second_flush + Duration::from_secs(50));
+        assert_eq!(analytics_saves(), 2);
+        assert!(!state.maintenance.is_queued(MaintenanceJob::AnalyticsSave));
+        assert!(!state.maintenance.is_queued(MaintenanceJob::Rollup));
+
+        // Without a pause, a job runs once it waited the maximum deferral.
+        let queued_at = second_flush + Duration::from_secs(60);
+        queue_maintenance(&mut state, MaintenanceJob::AnalyticsSave, queued_at);
+        for secs in (0..900).step_by(10) {
+            let at = queued_at + Duration::from_secs(secs);
+            state.maintenance.note_key_down(at);
+            run_maintenance_if_due(&mut state, at);
+        }
+        assert_eq!(analytics_saves(), 2);
+        let overdue = queued_at + Duration::from_secs(900);
+        state.maintenance.note_key_down(overdue);
+        run_maintenance_if_due(&mut state, overdue);
+        assert_eq!(analytics_saves(), 3);
+
+        // An idle flush saves large analytics right away and drops a queued save.
+        queue_maintenance(&mut state, MaintenanceJob::AnalyticsSave, overdue);
+        let idle_flush = overdue + Duration::from_secs(60);
+        state.last_flush_instant = idle_flush - state.flush_interval;
+        assert!(flush_if_due(&mut state, idle_flush));
+        assert_eq!(analytics_saves(), 4);
+        assert!(!state.maintenance.is_queued(MaintenanceJob::AnalyticsSave));
+
+        flush_logs();
+        let app_log = std::fs::read_to_string(dir.join("app.log")).unwrap();
+        let deferred = app_log.find("maintenance deferred until idle: analytics save");
+        let saved = app_log.find("maintenance running: analytics save after 50s deferred");
+        let rolled_up = app_log.find("maintenance running: stats rollup after 50s deferred");
+        assert!(deferred.is_some() && deferred < saved && saved < rolled_up);
+        assert!(app_log.contains("maintenance running: analytics save after 900s deferred"));
+    }
+
+    #[test]
+    fn missing_data_dir_holds_flushes_and_flushes_once_it_is_back() {
+        use super::storage_health::{flush_if_due, probe_storage, probe_storage_if_due};
+
+        let sandbox = temp_dir("storage-probe");
+        let dir = sandbox.join("data");
+        let storage = FlakyStorage::default();
+        let mut state = build_state(HashMap::from([(
+            StatsKey {
+                date: "2026-03-02 10:00".parse().unwrap(),
+                app_name: "com.test.editor".to_string(),
+                window_title: "Doc".to_string(),
+            },
+            StatsValue {
+                active_typing_ms: 100,
+                key_count: 3,
+                session_count: 1,
+                writing_ms: 0,
+                navigation_ms: 0,
+            },
+        )]));
+        state.storage = Arc::new(storage.clone());
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = sandbox.join("app.log");
+        let start = state.last_flush_instant;
+
+        probe_storage(&mut state, start);
+        let snapshot = state.snapshot();
+        assert!(!snapshot.storage_writable);
+        assert!(snapshot
+            .last_error
+            .is_some_and(|err| err.contains("is missing")));
+        assert!(state.today_summary().storage_unwritable);
+        let due = start + state.flush_interval;
+        assert!(!flush_if_due(&mut state, due));
+        assert!(storage.saves.lock().unwrap().is_empty());
+
+        // The volume comes back: the next due probe clears the error and flushes at once.
+        std::fs::create_dir_all(&dir).unwrap();
+        probe_storage_if_due(&mut state, start + Duration::from_secs(1));
+        assert!(!state.snapshot().storage_writable);
+        probe_storage_if_due(&mut state, start + Duration::from_secs(30));
+        let snapshot = state.snapshot();
+        assert!(snapshot.storage_writable);
+        assert_eq!(snapshot.last_error, None);
+        assert_eq!(storage.saves.lock().unwrap().len(), 1);
+        assert!(!dir.join(".write-probe.tmp").exists());
+    }
+
+    #[test]
+    fn externally_deleted_day_file_stays_deleted_while_changed_days_are_written() {
+        let dir = temp_dir("external-changes");
+        let row = |date: &str| StatsKey {
+            date: date.parse().unwrap(),
+            app_name: "com.test.editor".to_string(),
+            window_title: "Doc".to_string(),
+        };
+        let value = StatsValue {
+            active_typing_ms: 100,
+            key_count: 3,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let old_key = row("2026-03-01 10:00");
+        let today_key = row("2026-03-02 10:00");
+        let mut state = build_state(HashMap::from([
+            (old_key.clone(), value.clone()),
+            (today_key.clone(), value),
+        ]));
+        state.storage = Arc::new(JsonFileStorage::new(dir.join("details.json")));
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = dir.join("app.log");
+        let old_file = dir.join("2026-03-01-details.json");
+        let today_file = dir.join("2026-03-02-details.json");
+
+        state.flush_to_disk().unwrap();
+        assert!(old_file.exists() && today_file.exists());
+        assert!(!state.snapshot().external_changes_detected);
+
+        std::fs::remove_file(&old_file).unwrap();
+        state.stats.get_mut(&today_key).unwrap().key_count += 2;
+        state.flush_to_disk().unwrap();
+        assert!(!old_file.exists());
+        let today_rows = std::fs::read_to_string(&today_file).unwrap();
+        assert!(today_rows.contains("\"key_count\":5"));
+        assert!(state.snapshot().external_changes_detected);
+        assert!(state.stats.contains_key(&old_key));
+
+        // Reported once; a later flush neither repeats the warning nor brings the file back.
+        state.flush_to_disk().unwrap();
+        assert!(!old_file.exists());
+        flush_logs();
+        let log = std::fs::read_to_string(&state.app_log_path).unwrap();
+        assert_eq!(
+            log.matches("data files changed outside TypePulse: deleted [2026-03-01-details.json]")
+                .count(),
+            1
+        );
+    }
+
+    // Storage whose history load blocks until the test releases it.
+    struct GatedStorage {
+        inner: JsonFileStorage,
+        gate: Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl DetailStorage for GatedStorage {
+        fn load_stats(&self) -> Result<HashMap<StatsKey, StatsValue>, String> {
+            self.inner.load_stats()
+        }
+
+        fn save_stats(&self, stats: &HashMap<StatsKey, StatsValue>) -> Result<(), String> {
+            self.inner.save_stats(stats)
+        }
+
+        fn is_rolled_up(&self, day: NaiveDate) -> bool {
+            self.inner.is_rolled_up(day)
+        }
+
+        fn mark_rolled_up(&self, days: &[NaiveDate]) {
+            self.inner.mark_rolled_up(days)
+        }
+
+        fn load_input_analytics(&self) -> Result<StoredInputAnalytics, String> {
+            self.inner.load_input_analytics()
+        }
+
+        fn save_input_analytics(&self, analytics: &StoredInputAnalytics) -> Result<(), String> {
+            self.inner.save_input_analytics(analytics)
+        }
+
+        fn load_history(&self, progress: &LoadProgress) -> StoredHistory {
+            let _ = self.gate.lock().unwrap().recv();
+            self.inner.load_history(progress)
+        }
+    }
+
+    #[test]
+    fn background_history_load_merges_keys_recorded_while_loading() {
+        use super::shortcut::key_counts_in_window;
+        let dir = temp_dir("history-load");
+        let editor = CaptureContext {
+            app_name: "Editor".to_string(),
+            window_title: "Doc".to_string(),
+            bundle_id: Some("com.test.editor".to_string()),
+            secure_input: false,
+            display_id: None,
+        };
+        let terminal = CaptureContext {
+            app_name: "Terminal".to_string(),
+            window_title: "Shell".to_string(),
+            bundle_id: Some("com.test.terminal".to_string()),
+            secure_input: false,
+            display_id: None,
+        };
+        let press = |state: &mut CollectorState, context: &CaptureContext, key: &str| {
+            let at = Instant::now();
+            apply_collector_event(
+                state,
+                CollectorEvent::NonModifierKeyDown {
+                    physical_key_id: key.to_string(),
+                    shortcut_key: key.to_string(),
+                    modifiers: ModifierSnapshot::default(),
+                    is_key_combo: false,
+                    capture_context: context.clone(),
+                    at,
+                    at_ms: epoch_ms_at(at),
+                },
+            );
+            apply_collector_event(
+                state,
+                CollectorEvent::NonModifierKeyUp {
+                    physical_key_id: key.to_string(),
+                    shortcut_key: key.to_string(),
+                    modifiers: ModifierSnapshot::default(),
+                    capture_context: context.clone(),
+                    at: Instant::now(),
+                    at_ms: chrono::Utc::now().timestamp_millis(),
+                },
+            );
+        };
+        let app_keys = |state: &CollectorState, app_id: &str| -> u64 {
+            state
+                .stats
+                .iter()
+                .filter(|(key, _)| key.app_name == app_id)
+                .map(|(_, value)| value.key_count)
+                .sum()
+        };
+
+        // History from an earlier run: three editor keys, so the editor holds app ref 1.
+        let mut stored = build_state(HashMap::new());
+        stored.storage = Arc::new(JsonFileStorage::new(dir.join("details.json")));
+        stored.log_path = dir.join("log.csv");
+        stored.app_log_path = dir.join("app.log");
+        for _ in 0..3 {
+            press(&mut stored, &editor, "a");
+        }
+        stored.flush_to_disk().unwrap();
+
+        let (release, gate) = std::sync::mpsc::channel();
+        let mut state = build_state(HashMap::new());
+        state.storage = Arc::new(GatedStorage {
+            inner: JsonFileStorage::new(dir.join("details.json")),
+            gate: Mutex::new(gate),
+        });
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = dir.join("app.log");
+        state.history_load = Some(Arc::new(LoadProgress::default()));
+        let state = Arc::new(Mutex::new(state));
+        let handle = start_history_load(state.clone(), dir.clone());
+
+        // While loading, the terminal is seen first and takes app ref 1 in the fresh state.
+        {
+            let mut locked = state.lock().unwrap();
+            for _ in 0..2 {
+                press(&mut locked, &terminal, "b");
+            }
+            for _ in 0..2 {
+                press(&mut locked, &editor, "c");
+            }
+            assert!(locked.snapshot().loading);
+            assert_eq!(locked.app_dict.get(&1).unwrap(), "com.test.terminal");
+            assert!(locked.flush_to_disk().is_err());
+        }
+        release.send(()).unwrap();
+        handle.join().unwrap();
+
+        let mut locked = state.lock().unwrap();
+        let snapshot = locked.snapshot();
+        assert!(!snapshot.loading);
+        assert_eq!(app_keys(&locked, "com.test.editor"), 5);
+        assert_eq!(app_keys(&locked, "com.test.terminal"), 2);
+        assert_eq!(locked.today_totals.key_count, 7);
+        let editor_keys = key_counts_in_window(&locked, 0, i64::MAX, Some("com.test.editor"));
+        let terminal_keys = key_counts_in_window(&locked, 0, i64::MAX, Some("com.test.terminal"));
+        assert_eq!(
+            editor_keys,
+            HashMap::from([("a".to_string(), 3), ("c".to_string(), 2)])
+        );
+        assert_eq!(terminal_keys, HashMap::from([("b".to_string(), 2)]));
+
+        // Saving after the merge keeps both the stored and the live keys.
+        locked.flush_to_disk().unwrap();
+        let progress = LoadProgress::default();
+        let reloaded = load_history(&JsonFileStorage::new(dir.join("details.json")), &progress);
+        let (files_done, files_total) = progress.files();
+        assert!(files_total > 0);
+        assert_eq!(files_done, files_total);
+        let mut fresh = build_state(HashMap::new());
+        merge_history(&mut fresh, reloaded, &|_| {});
+        assert_eq!(app_keys(&fresh, "com.test.editor"), 5);
+        assert_eq!(app_keys(&fresh, "com.test.terminal"), 2);
+        drop(locked);
+    }
+
+    #[test]
+    fn switching_data_sets_saves_the_current_one_and_loads_the_other() {
+        let root = temp_dir("profile-switch");
+        let work = root.join("work");
+        std::fs::create_dir_all(&work).unwrap();
+        let key = StatsKey {
+            date: "2026-03-02 10:00".parse().unwrap(),
+            app_name: "com.test.editor".to_string(),
+            window_title: "Doc".to_string(),
+        };
+        let mut state = build_state(HashMap::from([(
+            key.clone(),
+            StatsValue {
+                active_typing_ms: 100,
+                key_count: 3,
+                session_count: 1,
+                writing_ms: 0,
+                navigation_ms: 0,
+            },
+        )]));
+        state.storage = Arc::new(JsonFileStorage::new(root.join("details.json")));
+        state.log_path = root.join("log.csv");
+        state.app_log_path = root.join("app.log");
+        state.paused = true;
+        let config = AppConfig {
+            profiles: vec![ProfileConfig {
+                name: "work".to_string(),
+                excluded_bundle_ids: Some(vec!["com.test.chat".to_string()]),
+                ..ProfileConfig::default()
+            }],
+            active_profile: "work".to_string(),
+            ..AppConfig::default()
+        };
+
+        state
+            .switch_data_set(work.join("log.csv"), work.join("details.json"), &config)
+            .unwrap();
+        assert!(state.stats.is_empty());
+        assert!(state.paused);
+        assert_eq!(
+            state.excluded_bundle_ids(),
+            vec!["com.test.chat".to_string()]
+        );
+
+        let config = AppConfig::default();
+        state
+            .switch_data_set(root.join("log.csv"), root.join("details.json"), &config)
+            .unwrap();
+        assert_eq!(state.stats.get(&key).map(|value| value.key_count), Some(3));
+        assert_eq!(state.log_path, root.join("log.csv"));
+    }
+
+    #[test]
+    fn demo_mode_seeds_a_deterministic_week_and_writes_nothing() {
+        use super::demo::{new_demo_collector_state, DEMO_SEED};
+        use super::fixtures::generate_history;
+
+        // Friday afternoon: five weekdays and a weekend before it, today cut at 15:00.
+        let end = NaiveDate::from_ymd_opt(2026, 3, 6)
+            .unwrap()
+            .and_hms_opt(15, 0, 0)
+            .unwrap();
+        let seed = generate_history(DEMO_SEED, end, 7);
+        let again = generate_history(DEMO_SEED, end, 7);
+        let totals = |stats: &HashMap<StatsKey, StatsValue>| {
+            let mut keys: Vec<(String, String, u64)> = stats
+                .iter()
+                .map(|(key, value)| (key.date.to_string(), key.app_name.clone(), value.key_count))
+                .collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(totals(&seed.stats), totals(&again.stats));
+        assert_ne!(
+            totals(&seed.stats),
+            totals(&generate_history(DEMO_SEED + 1, end, 7).stats)
+        );
+        let days: HashSet<NaiveDate> = seed.stats.keys().map(|key| key.date.day()).collect();
+        assert_eq!(days.len(), 7);
+        let apps: HashSet<&str> = seed.stats.keys().map(|key| key.app_name.as_str()).collect();
+        assert!(apps.len() >= 3);
+        assert!(seed
+            .stats
+            .keys()
+            .all(|key| key.date.to_string().as_str() < "2026-03-06 15:00"));
+        assert!(seed
+            .stats
+            .values()
+            .all(|value| value.writing_ms + value.navigation_ms == value.active_typing_ms));
+        assert!(!seed.analytics.shortcut_usage.is_empty());
+
+        let sandbox = temp_dir("demo");
+        let root = sandbox.join("profile");
+        let mut state = new_demo_collector_state(
+            root.join("log.csv"),
+            root.join("app.log"),
+            root.join("details.json"),
+            &AppConfig::default(),
+            Arc::new(Shutdown::default()),
+            end,
+        );
+        assert!(state.snapshot().demo_mode);
+        assert_eq!(totals(&state.stats), totals(&seed.stats));
+        assert!(!state.shortcut_usage.is_empty());
+        state.flush_to_disk().unwrap();
+        assert!(state
+            .switch_data_set(
+                root.join("log.csv"),
+                root.join("details.json"),
+                &AppConfig::default()
+            )
+            .is_err());
+        assert!(!root.exists());
+    }
+
+    fn profile_rule(
+        profile: &str,
+        priority: i32,
+        window: &str,
+        bundle_ids: &[&str],
+    ) -> ProfileAutoRule {
+        let (start, end) = window.split_once('-').unwrap_or(("", ""));
+        ProfileAutoRule {
+            profile: profile.to_string(),
+            priority,
+            start: start.to_string(),
+            end: end.to_string(),
+            weekdays: Vec::new(),
+            bundle_ids: bundle_ids.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn overlapping_profile_rules_pick_highest_priority_then_list_order() {
+        // Monday 2026-03-02.
+        let at = |time: &str| {
+            chrono::NaiveDateTime::parse_from_str(&format!("2026-03-02 {time}"), "%Y-%m-%d %H:%M")
+                .unwrap()
+        };
+        let rules = vec![
+            profile_rule("work", 1, "09:00-18:00", &[]),
+            profile_rule("personal", 5, "", &["com.valvesoftware.steam"]),
+            profile_rule("focus", 1, "10:00-12:00", &[]),
+            profile_rule("night", 0, "22:00-02:00", &[]),
+        ];
+        let picked = |time: &str, frontmost: Option<&str>| {
+            matching_rule(&rules, at(time), frontmost)
+                .map(|(index, rule, trigger)| (index, rule.profile.clone(), trigger))
+        };
+
+        // Equal priorities: the earlier rule wins the overlap.
+        assert_eq!(
+            picked("10:30", None),
+            Some((0, "work".to_string(), "time 09:00-18:00".to_string()))
+        );
+        // A higher priority app trigger beats the time window.
+        assert_eq!(
+            picked("10:30", Some("com.ValveSoftware.Steam")),
+            Some((
+                1,
+                "personal".to_string(),
+                "frontmost com.valvesoftware.steam".to_string()
+            ))
+        );
+        // Windows are end-exclusive and may wrap past midnight.
+        assert_eq!(picked("18:00", None), None);
+        assert_eq!(picked("01:59", None).map(|(index, ..)| index), Some(3));
+        assert_eq!(picked("23:00", None).map(|(index, ..)| index), Some(3));
+
+        // Weekdays apply to the day the window started: Sunday night's window ends on Monday.
+        let mut weekend_night = rules[3].clone();
+        weekend_night.weekdays = vec![6, 7];
+        let weekend = [weekend_night];
+        assert!(matching_rule(&weekend, at("01:00"), None).is_some());
+        assert!(matching_rule(&weekend, at("23:00"), None).is_none());
+    }
+
+    #[test]
+    fn profile_rules_switch_after_hold_and_pause_after_manual_switch() {
+        let mut state = build_state(HashMap::new());
+        let config = AppConfig {
+            profiles: vec![
+                ProfileConfig {
+                    name: "work".to_string(),
+                    ..ProfileConfig::default()
+                },
+                ProfileConfig {
+                    name: "personal".to_string(),
+                    ..ProfileConfig::default()
+                },
+            ],
+            active_profile: "personal".to_string(),
+            profile_auto_rules: vec![
+                profile_rule("work", 0, "", &["com.slack.slack"]),
+                profile_rule("typo", 9, "", &["com.slack.slack"]),
+            ],
+            profile_auto_fallback: "personal".to_string(),
+            profile_auto_hold_secs: 60,
+            profile_auto_cooldown_mins: 10,
+            ..AppConfig::default()
+        };
+        state.profile_auto = ProfileAutoSwitch::from_config(&config);
+        let at = chrono::NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let start = Instant::now();
+        let slack = Some("com.slack.slack");
+
+        // Leaving the work app before the hold runs out starts the hold over.
+        evaluate_profile_rules(&mut state, start, at, slack);
+        evaluate_profile_rules(
+            &mut state,
+            start + Duration::from_secs(50),
+            at,
+            Some("com.apple.safari"),
+        );
+        evaluate_profile_rules(&mut state, start + Duration::from_secs(70), at, slack);
+        assert_eq!(state.take_profile_switch_request(), None);
+        evaluate_profile_rules(&mut state, start + Duration::from_secs(130), at, slack);
+        let request = state.take_profile_switch_request().unwrap();
+        // The rule naming an unknown profile was dropped despite its priority.
+        assert_eq!(request.profile, "work");
+        assert_eq!(request.reason, "rule 1 (frontmost com.slack.slack)");
+
+        // A manual switch pauses the rules for the cooldown.
+        state.suppress_profile_auto_switch(start + Duration::from_secs(200));
+        evaluate_profile_rules(&mut state, start + Duration::from_secs(300), at, slack);
+        evaluate_profile_rules(&mut state, start + Duration::from_secs(700), at, slack);
+        assert_eq!(state.take_profile_switch_request(), None);
+        evaluate_profile_rules(&mut state, start + Duration::from_secs(800), at, slack);
+        evaluate_profile_rules(&mut state, start + Duration::from_secs(860), at, slack);
+        assert_eq!(
+            state
+                .take_profile_switch_request()
+                .map(|request| request.profile),
+            Some("work".to_string())
+        );
+    }
+
+    #[test]
+    fn today_summary_matches_snapshot_rows_for_seeded_data() {
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let mut stats = HashMap::new();
+        stats.insert(
+            StatsKey {
+                date: format!("{today} 00:00").parse().unwrap(),
+                app_name: "A".to_string(),
+                window_title: "TitleA".to_string(),
+            },
+            StatsValue {
+                active_typing_ms: 1500,
+                key_count: 15,
+                session_count: 1,
+                writing_ms: 0,
+                navigation_ms: 0,
+            },
+        );
+        stats.insert(
+            StatsKey {
+                date: "2000-01-01 10:00".parse().unwrap(),
+                app_name: "B".to_string(),
+                window_title: "TitleB".to_string(),
+            },
+            StatsValue {
+                active_typing_ms: 900,
+                key_count: 9,
+                session_count: 1,
+                writing_ms: 0,
+                navigation_ms: 0,
+            },
+        );
+        let mut harness = CollectorEventHarness::new();
+        harness.state = build_state(stats);
+        let now = Instant::now();
+        harness.key_down("k:a", false, now);
+        harness.tick(Duration::from_millis(400), now + Duration::from_millis(400));
+        harness.key_up("k:a");
+        harness.key_down("k:b", false, now + Duration::from_millis(500));
+
+        let summary = harness.state.today_summary();
+        let (active, keys) = harness
+            .rows()
+            .iter()
+            .filter(|row| row.date.to_string().starts_with(&today))
+            .fold((0u64, 0u64), |acc, row| {
+                (acc.0 + row.active_typing_ms, acc.1 + row.key_count)
+            });
+        assert_eq!(summary.key_count, keys);
+        assert_eq!(summary.active_typing_ms, active);
+        assert_eq!(summary.key_count, 17);
+        assert_eq!(summary.active_typing_ms, 1900);
+    }
+
+    #[test]
+    fn snapshot_v2_payload_stays_within_budget_for_large_histories() {
+        use super::demo::DEMO_SEED;
+        use super::fixtures::generate_history;
+        use super::{snapshot_daily_totals, snapshot_stats_rows, ShortcutUsageValue};
+
+        // A snapshot is polled every second; keep it well under what the webview handles
+        // without stutter however much history is loaded.
+        const SNAPSHOT_BUDGET_BYTES: usize = 96 * 1024;
+
+        let today = chrono::Local::now().date_naive();
+        let seed = generate_history(DEMO_SEED, today.and_hms_opt(23, 59, 0).unwrap(), 90);
+        let row_total_count = seed.stats.len() as u64;
+        let mut state = build_state(seed.stats);
+        for index in 0..500u64 {
+            state.shortcut_usage.insert(
+                format!("cmd_shift_{index:03}"),
+                ShortcutUsageValue {
+                    count: index + 1,
+                    by_app: HashMap::from([("com.apple.Terminal".to_string(), index + 1)]),
+                },
+            );
+        }
+
+        state.snapshot_v2 = true;
+        let snapshot = state.snapshot();
+        let payload = serde_json::to_vec(&snapshot).unwrap();
+        assert!(
+            payload.len() < SNAPSHOT_BUDGET_BYTES,
+            "snapshot payload is {} bytes",
+            payload.len()
+        );
+        assert!(!snapshot.rows.is_empty());
+        assert!(snapshot.rows.iter().all(|row| row.date.day() == today));
+        assert_eq!(snapshot.row_total_count, row_total_count);
+        assert_eq!(snapshot.shortcut_stats.len(), 50);
+        assert_eq!(snapshot.shortcut_total_count, 500);
+        assert_eq!(snapshot.shortcut_stats[0].count, 500);
+
+        state.snapshot_v2 = false;
+        let legacy = state.snapshot();
+        assert_eq!(legacy.rows.len() as u64, row_total_count);
+        assert_eq!(legacy.shortcut_stats.len(), 500);
+        assert!(serde_json::to_vec(&legacy).unwrap().len() > SNAPSHOT_BUDGET_BYTES);
+
+        let yesterday = today - chrono::Duration::days(1);
+        let rows = snapshot_stats_rows(&state, "yesterday");
+        assert!(!rows.is_empty());
+        assert!(rows.iter().all(|row| row.date.day() == yesterday));
+        let week = snapshot_stats_rows(&state, "7d");
+        assert!(week.len() > rows.len());
+
+        let totals = snapshot_daily_totals(&state, 30);
+        assert!(totals.len() <= 30);
+        assert_eq!(
+            totals.last().unwrap().date,
+            today.format("%Y-%m-%d").to_string()
+        );
+        assert!(totals.windows(2).all(|pair| pair[0].date < pair[1].date));
+        let first_day = today - chrono::Duration::days(29);
+        let expected: u64 = state
+            .stats
+            .iter()
+            .filter(|(key, _)| key.date.day() >= first_day)
+            .map(|(_, value)| value.key_count)
+            .sum();
+        assert_eq!(
+            totals.iter().map(|total| total.key_count).sum::<u64>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn stats_row_pages_stay_stable_when_rows_arrive_between_pages() {
+        use super::stats_query::{StatsRowColumn, MAX_STATS_PAGE_SIZE};
+        use super::{snapshot_stats_row_page, StatsRowFilter, StatsRowSort};
+
+        let day = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let stored = |day: NaiveDate, minute: u16, app: &str, title: &str, key_count: u64| {
+            (
+                StatsKey {
+                    date: MinuteKey::new(day, 10, minute).unwrap(),
+                    app_name: app.to_string(),
+                    window_title: title.to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: key_count * 100,
+                    key_count,
+                    session_count: 1,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            )
+        };
+        let previous = day - chrono::Duration::days(1);
+        let mut state = build_state(HashMap::from([
+            stored(previous, 0, "com.test.term", "zsh", 5),
+            stored(previous, 1, "com.test.editor", "notes.md", 9),
+            stored(day, 0, "com.test.term", "zsh", 5),
+            stored(day, 0, "com.test.editor", "Draft Notes", 7),
+            stored(day, 2, "com.test.browser", "Inbox", 3),
+        ]));
+        let by_date = StatsRowSort::default();
+        let first =
+            snapshot_stats_row_page(&state, &StatsRowFilter::default(), by_date, 0, 2).unwrap();
+        assert_eq!(first.total, 5);
+        assert_eq!(first.page_size, 2);
+        let first_rows: Vec<_> = first
+            .rows
+            .iter()
+            .map(|row| (row.date, row.app_name.clone()))
+            .collect();
+        assert_eq!(
+            first_rows,
+            vec![
+                (
+                    MinuteKey::new(previous, 10, 0).unwrap(),
+                    "com.test.term".to_string()
+                ),
+                (
+                    MinuteKey::new(previous, 10, 1).unwrap(),
+                    "com.test.editor".to_string()
+                ),
+            ]
+        );
+
+        // Typing goes on between the two page requests: a new row for a later minute and
+        // more keys in a row already shown.
+        state
+            .stats
+            .extend([stored(day, 5, "com.test.term", "zsh", 1)]);
+        state
+            .stats
+            .get_mut(&stored(previous, 0, "com.test.term", "zsh", 0).0)
+            .unwrap()
+            .key_count += 4;
+        let second =
+            snapshot_stats_row_page(&state, &StatsRowFilter::default(), by_date, 1, 2).unwrap();
+        assert_eq!(second.total, 6);
+        let second_rows: Vec<_> = second
+            .rows
+            .iter()
+            .map(|row| (row.date, row.app_name.as_str()))
+            .collect();
+        // Same-minute rows are ordered by app, then title.
+        assert_eq!(
+            second_rows,
+            vec![
+                (MinuteKey::new(day, 10, 0).unwrap(), "com.test.editor"),
+                (MinuteKey::new(day, 10, 0).unwrap(), "com.test.term"),
+            ]
+        );
+        let last =
+            snapshot_stats_row_page(&state, &StatsRowFilter::default(), by_date, 2, 2).unwrap();
+        assert_eq!(last.rows.len(), 2);
+        assert_eq!(last.rows[1].date, MinuteKey::new(day, 10, 5).unwrap());
+        let beyond =
+            snapshot_stats_row_page(&state, &StatsRowFilter::default(), by_date, 9, 2).unwrap();
+        assert!(beyond.rows.is_empty());
+        assert_eq!(beyond.total, 6);
+
+        // Ties on a counter column fall back to date, app and title.
+        let by_keys = StatsRowSort {
+            column: StatsRowColumn::KeyCount,
+            descending: true,
+        };
+        let page =
+            snapshot_stats_row_page(&state, &StatsRowFilter::default(), by_keys, 0, 3).unwrap();
+        let counts: Vec<_> = page
+            .rows
+            .iter()
+            .map(|row| (row.key_count, row.date.day()))
+            .collect();
+        assert_eq!(counts, vec![(9, previous), (9, previous), (7, day)]);
+        assert_eq!(page.rows[0].app_name, "com.test.term");
+
+        let filter = StatsRowFilter {
+            from: Some(day.format("%Y-%m-%d").to_string()),
+            to: Some(day.format("%Y-%m-%d").to_string()),
+            app: Some("COM.TEST.TERM".to_string()),
+            title: Some("ZS".to_string()),
+        };
+        let filtered = snapshot_stats_row_page(&state, &filter, by_date, 0, 0).unwrap();
+        assert_eq!(filtered.total, 2);
+        assert_eq!(filtered.page_size, 1);
+        assert_eq!(filtered.rows.len(), 1);
+        let notes = StatsRowFilter {
+            title: Some("notes".to_string()),
+            ..StatsRowFilter::default()
+        };
+        let capped = snapshot_stats_row_page(&state, &notes, by_date, 0, 10_000).unwrap();
+        assert_eq!(capped.total, 2);
+        assert_eq!(capped.page_size, MAX_STATS_PAGE_SIZE);
+        assert!(!capped.history_loading);
+
+        let invalid = StatsRowFilter {
+            from: Some("2026-13-01".to_string()),
+            ..StatsRowFilter::default()
+        };
+        assert_eq!(
+            snapshot_stats_row_page(&state, &invalid, by_date, 0, 10).err(),
+            Some("invalid date: 2026-13-01".to_string())
+        );
+    }
+
+    #[test]
+    fn today_shortcut_query_stays_within_wall_time_budget_on_large_histories() {
+        use super::fixtures::{generate_large_history, seeded_collector_state, HistorySize};
+        use super::snapshot_shortcut_rows_by_range;
+
+        // The range query replays today's event chunks on each poll of the shortcut panel.
+        // Generous enough for an unoptimized build on a loaded CI runner; a regression to
+        // replaying every chunk instead of today's blows through it.
+        const TODAY_QUERY_BUDGET: Duration = Duration::from_secs(2);
+
+        let size = HistorySize {
+            days: 7,
+            stats_rows: 10_000,
+            chunks: 2_100,
+            events_per_chunk: 500,
+        };
+        let history = generate_large_history(7, chrono::Local::now().naive_local(), size);
+        let state = seeded_collector_state(
+            PathBuf::new(),
+            PathBuf::new(),
+            PathBuf::new(),
+            &AppConfig::default(),
+            Arc::new(Shutdown::default()),
+            history,
+        );
+        assert_eq!(state.stats.len(), 10_000);
+
+        let started = Instant::now();
+        let today = snapshot_shortcut_rows_by_range(&state, "today", 5);
+        let elapsed = started.elapsed();
+        assert!(
+            elapsed < TODAY_QUERY_BUDGET,
+            "today shortcut query took {elapsed:?}"
+        );
+        assert!(!today.rows.is_empty());
+    }
+
+    #[test]
+    fn window_title_aliases_rewrite_captured_titles_per_app() {
+        let mut harness = CollectorEventHarness::new();
+        let base = Instant::now();
+        harness.state.window_title_aliases = HashMap::from([(
+            "com.test.editor".to_string(),
+            HashMap::from([("Doc".to_string(), "Project".to_string())]),
+        )]);
+        harness.key_down("a", false, base);
+        harness.key_up("a");
+        // Same title in another app, and another title in the aliased app, stay as captured.
+        harness.default_context.bundle_id = Some("com.test.browser".to_string());
+        harness.key_down("b", false, base + Duration::from_millis(100));
+        harness.key_up("b");
+        harness.default_context.bundle_id = Some("COM.TEST.EDITOR".to_string());
+        harness.default_context.window_title = "Notes".to_string();
+        harness.key_down("c", false, base + Duration::from_millis(200));
+        harness.key_up("c");
+        // Hidden titles stay hidden.
+        harness.state.set_window_title_mode(WindowTitleMode::Hidden);
+        harness.default_context.window_title = "Doc".to_string();
+        harness.key_down("d", false, base + Duration::from_millis(300));
+
+        let mut titles: Vec<(String, String)> = harness
+            .rows()
+            .into_iter()
+            .map(|row| (row.app_name, row.window_title))
+            .collect();
+        titles.sort();
+        assert_eq!(
+            titles,
+            vec![
+                ("COM.TEST.EDITOR".to_string(), String::new()),
+                ("COM.TEST.EDITOR".to_string(), "Notes".to_string()),
+                ("com.test.browser".to_string(), "Doc".to_string()),
+                ("com.test.editor".to_string(), "Project".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_window_titles_sums_collided_rows_and_rewrites_daily_files() {
+        let dir = temp_dir("title-merge");
+        let key = |day: &str, app: &str, title: &str| StatsKey {
+            date: format!("{day} 10:00").parse().unwrap(),
+            app_name: app.to_string(),
+            window_title: title.to_string(),
+        };
+        let value = |key_count| StatsValue {
+            active_typing_ms: key_count * 100,
+            key_count,
+            session_count: 1,
+            writing_ms: key_count * 100,
+            navigation_ms: 0,
+        };
+        let mut state = build_state(HashMap::from([
+            (key("2026-02-09", "com.test.editor", "proj - a"), value(2)),
+            (key("2026-02-09", "com.test.editor", "Proj"), value(3)),
+            (key("2026-02-09", "com.test.editor", "proj (1)"), value(4)),
+            (key("2026-02-09", "com.test.browser", "proj - a"), value(5)),
+            (key("2026-02-10", "com.test.editor", "notes"), value(6)),
+        ]));
+        state.storage = Arc::new(JsonFileStorage::new(dir.join("details.json")));
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = dir.join("app.log");
+        state.flush_to_disk().unwrap();
+        let untouched_day = dir.join("2026-02-10-details.json");
+        let untouched_modified = std::fs::metadata(&untouched_day)
+            .unwrap()
+            .modified()
+            .unwrap();
+
+        let from = vec!["proj - a".to_string(), "proj (1)".to_string()];
+        assert_eq!(
+            state.merge_window_titles("COM.TEST.EDITOR", &from, "Proj"),
+            Ok(2)
+        );
+        let merged = &state.stats[&key("2026-02-09", "com.test.editor", "Proj")];
+        assert_eq!((merged.key_count, merged.session_count), (9, 3));
+        assert_eq!(merged.writing_ms, 900);
+        assert_eq!(state.stats.len(), 3);
+        assert!(state
+            .stats
+            .contains_key(&key("2026-02-09", "com.test.browser", "proj - a")));
+
+        // The rewritten day reloads merged; the other day's file was not touched.
+        let reloaded = JsonFileStorage::new(dir.join("details.json"))
+            .load_stats()
+            .unwrap();
+        assert_eq!(
+            reloaded[&key("2026-02-09", "com.test.editor", "Proj")].key_count,
+            9
+        );
+        assert_eq!(reloaded.len(), 3);
+        assert_eq!(
+            std::fs::metadata(&untouched_day)
+                .unwrap()
+                .modified()
+                .unwrap(),
+            untouched_modified
+        );
+
+        // Merging the target onward repoints the earlier aliases instead of chaining them.
+        assert_eq!(
+            state.merge_window_titles("com.test.editor", &["Proj".to_string()], "Project"),
+            Ok(1)
+        );
+        let aliases = &state.window_title_aliases()["com.test.editor"];
+        assert_eq!(aliases["proj - a"], "Project");
+        assert_eq!(aliases["proj (1)"], "Project");
+        assert_eq!(aliases["Proj"], "Project");
+        assert_eq!(
+            state.merge_window_titles("com.test.editor", &["x".to_string()], "Proj"),
+            Ok(0)
+        );
+        assert_eq!(
+            state.window_title_aliases()["com.test.editor"]["x"],
+            "Project"
+        );
+    }
+
+    #[test]
+    fn per_app_title_privacy_overrides_global_mode() {
+        let mut harness = CollectorEventHarness::new();
+        let base = Instant::now();
+        let browser = CaptureContext {
+            app_name: "Browser".to_string(),
+            window_title: "Inbox".to_string(),
+            bundle_id: Some("com.test.browser".to_string()),
+            secure_input: false,
+            display_id: None,
+        };
+        harness.state.set_window_title_mode(WindowTitleMode::Full);
+        harness
+            .state
+            .set_app_title_privacy("COM.TEST.BROWSER", WindowTitleMode::Hidden);
+        harness.key_down("a", false, base);
+        harness.key_up("a");
+        harness.default_context = browser.clone();
+        harness.key_down("b", false, base + Duration::from_millis(100));
+        harness.key_up("b");
+
+        let rows = harness.rows();
+        let titles: Vec<(&str, &str)> = rows
+            .iter()
+            .map(|row| (row.app_name.as_str(), row.window_title.as_str()))
+            .collect();
+        assert!(titles.contains(&("com.test.editor", "Doc")));
+        assert!(titles.contains(&("com.test.browser", "")));
+
+        // Global hidden still lets a per-app override keep full titles.
+        harness.state.set_window_title_mode(WindowTitleMode::Hidden);
+        harness
+            .state
+            .set_app_title_privacy("com.test.browser", WindowTitleMode::Full);
+        assert_eq!(
+            harness
+                .state
+                .resolve_window_title_mode(Some("com.test.browser")),
+            WindowTitleMode::Full
+        );
+        assert_eq!(
+            harness
+                .state
+                .resolve_window_title_mode(Some("com.test.editor")),
+            WindowTitleMode::Hidden
+        );
+        harness.state.remove_app_title_privacy("com.test.browser");
+        assert_eq!(
+            harness
+                .state
+                .resolve_window_title_mode(Some("com.test.browser")),
+            WindowTitleMode::Hidden
+        );
+        assert!(harness.state.snapshot().title_privacy_overrides.is_empty());
+    }
+
+    #[test]
+    fn app_time_goal_progress_follows_today_totals_and_announces_once() {
+        let mut harness = CollectorEventHarness::new();
+        let base = Instant::now();
+        harness.state.set_app_time_goal("COM.TEST.EDITOR", 1);
+        harness.state.set_app_time_goal("com.test.browser", 30);
+        // Auto-repeats keep the hold alive past the stale-key expiry.
+        harness.key_down("a", false, base);
+        harness.tick(Duration::from_secs(20), base + Duration::from_secs(20));
+        harness.key_down("a", false, base + Duration::from_secs(20));
+        harness.tick(Duration::from_secs(20), base + Duration::from_secs(40));
+
+        let progress = harness.state.snapshot().goal_progress;
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress[0].bundle_id, "com.test.browser");
+        assert_eq!(progress[0].actual_min, 0);
+        assert_eq!(progress[1].bundle_id, "com.test.editor");
+        assert_eq!(progress[1].target_min, 1);
+        assert_eq!(progress[1].actual_min, 0);
+        assert!((progress[1].pct - 40.0 * 100.0 / 60.0).abs() < 1e-9);
+        assert!(harness.state.take_newly_met_app_goals().is_empty());
+
+        harness.key_down("a", false, base + Duration::from_secs(40));
+        harness.tick(Duration::from_secs(30), base + Duration::from_secs(70));
+        harness.key_up("a");
+        let summary = harness.state.today_summary();
+        assert_eq!(summary.active_typing_ms, 70_000);
+        assert_eq!(summary.goal_progress[1].actual_min, 1);
+        let met = harness.state.take_newly_met_app_goals();
+        assert_eq!(met.len(), 1);
+        assert_eq!(met[0].bundle_id, "com.test.editor");
+        assert!(harness.state.take_newly_met_app_goals().is_empty());
+        assert_eq!(
+            harness.state.app_goal_notified().get("com.test.editor"),
+            Some(&chrono::Local::now().format("%Y-%m-%d").to_string())
+        );
+
+        // A rescan from stats yields the same per-app time as the incremental cache.
+        let rescanned =
+            super::compute_today_totals(&harness.state.stats, chrono::Local::now().date_naive());
+        assert_eq!(
+            rescanned.app_active_typing_ms,
+            harness.state.today_totals.app_active_typing_ms
+        );
+
+        harness.state.remove_app_time_goal("com.test.editor");
+        assert!(harness.state.app_goal_notified().is_empty());
+        assert_eq!(harness.state.snapshot().goal_progress.len(), 1);
+    }
+
+    #[test]
+    fn app_time_goal_announcements_respect_the_notification_switch() {
+        let mut harness = CollectorEventHarness::new();
+        let base = Instant::now();
+        harness.state.set_app_time_goal("com.test.editor", 1);
+        harness.state.set_app_time_goal_notifications(false);
+        harness.key_down("a", false, base);
+        harness.tick(Duration::from_secs(30), base + Duration::from_secs(30));
+        harness.key_down("a", false, base + Duration::from_secs(30));
+        harness.tick(Duration::from_secs(30), base + Duration::from_secs(60));
+        harness.key_down("a", false, base + Duration::from_secs(60));
+        harness.tick(Duration::from_secs(1), base + Duration::from_secs(61));
+        assert!(harness.state.take_newly_met_app_goals().is_empty());
+        assert!(harness.state.snapshot().goal_progress[0].pct > 100.0);
+
+        harness.state.set_app_time_goal_notifications(true);
+        assert_eq!(harness.state.take_newly_met_app_goals().len(), 1);
+    }
+
+    #[test]
+    fn current_context_follows_typing_app_switches_and_pauses() {
+        let mut harness = CollectorEventHarness::new();
+        let base = Instant::now();
+        let second = Duration::from_secs(1);
+        let browser = CaptureContext {
+            app_name: "Browser".to_string(),
+            window_title: "Inbox".to_string(),
+            bundle_id: Some("com.test.browser".to_string()),
+            secure_input: false,
+            display_id: None,
+        };
+
+        // Ticks alone never report a context: nothing is being typed yet.
+        harness.tick(second, base);
+        assert_eq!(harness.state.snapshot().current_context, None);
+
+        harness.key_down("a", false, base + second);
+        harness.key_up("a");
+        let first = harness.state.snapshot().current_context.unwrap();
+        assert_eq!(first.bundle_id.as_deref(), Some("com.test.editor"));
+        assert_eq!(first.app_name, "Editor");
+        assert_eq!(first.window_title, "Doc");
+
+        // A new title in the same app keeps `since_ms`.
+        let renamed = CaptureContext {
+            window_title: "Notes".to_string(),
+            ..harness.default_context.clone()
+        };
+        harness.tick_with_context(second, base + 2 * second, renamed);
+        let same_app = harness.state.snapshot().current_context.unwrap();
+        assert_eq!(same_app.window_title, "Notes");
+        assert_eq!(same_app.since_ms, first.since_ms);
+
+        // Switching apps while still typing restarts the clock.
+        harness.tick_with_context(second, base + 3 * second, browser);
+        let switched = harness.state.snapshot().current_context.unwrap();
+        assert_eq!(switched.bundle_id.as_deref(), Some("com.test.browser"));
+        assert_eq!(switched.window_title, "Inbox");
+        assert!(switched.since_ms >= first.since_ms + 1_500);
+
+        // The title privacy mode applies to the reported title.
+        harness.state.set_window_title_mode(WindowTitleMode::Hidden);
+        let hidden = harness.state.snapshot().current_context.unwrap();
+        assert_eq!(hidden.window_title, "");
+        harness.state.set_window_title_mode(WindowTitleMode::Full);
+
+        // Pausing clears it, and resuming does not bring it back until the next key.
+        harness.state.set_paused(true);
+        assert_eq!(harness.state.snapshot().current_context, None);
+        harness.state.set_paused(false);
+        harness.tick(second, base + 4 * second);
+        assert_eq!(harness.state.snapshot().current_context, None);
+
+        // Auto-pause (secure input) clears it on the tick.
+        harness.key_down("b", false, base + 5 * second);
+        harness.key_up("b");
+        assert!(harness.state.snapshot().current_context.is_some());
+        let secure = CaptureContext {
+            secure_input: true,
+            ..harness.default_context.clone()
+        };
+        harness.tick_with_context(second, base + 6 * second, secure);
+        assert_eq!(harness.state.snapshot().current_context, None);
+
+        // Idle past the session gap clears it as well.
+        harness.key_down("c", false, base + 7 * second);
+        harness.key_up("c");
+        harness.tick(second, base + 10 * second);
+        assert!(harness.state.snapshot().current_context.is_some());
+        harness.tick(second, base + 13 * second);
+        assert_eq!(harness.state.snapshot().current_context, None);
+    }
+
+    #[test]
+    fn live_session_resets_on_session_boundary_and_hides_while_paused() {
+        use super::live_session::live_session_summary;
+        use super::LiveSessionSummary;
+
+        let mut harness = CollectorEventHarness::new();
+        let base = Instant::now();
+        let second = Duration::from_secs(1);
+        let session_count = |harness: &CollectorEventHarness| -> u64 {
+            harness.rows().iter().map(|row| row.session_count).sum()
+        };
+
+        assert_eq!(live_session_summary(&harness.state, base), None);
+        harness.key_down("a", false, base + second);
+        harness.key_up("a");
+        harness.key_down("b", false, base + 2 * second);
+        harness.key_up("b");
+        assert_eq!(
+            live_session_summary(&harness.state, base + 3 * second),
+            Some(LiveSessionSummary {
+                duration_ms: 2_000,
+                key_count: 2,
+            })
+        );
+        let sessions_before_pause = session_count(&harness);
+
+        // A pause shorter than the session gap hides the line but keeps counting the session.
+        harness.state.set_paused(true);
+        assert_eq!(
+            live_session_summary(&harness.state, base + 3 * second),
+            None
+        );
+        assert_eq!(harness.state.today_summary().live_session, None);
+        harness.state.set_paused(false);
+        harness.key_down("c", false, base + 4 * second);
+        harness.key_up("c");
+        assert_eq!(session_count(&harness), sessions_before_pause);
+        assert_eq!(
+            live_session_summary(&harness.state, base + 4 * second),
+            Some(LiveSessionSummary {
+                duration_ms: 3_000,
+                key_count: 3,
+            })
+        );
+
+        // Idle past the session gap ends it; the next key opens a new session from zero.
+        assert_eq!(
+            live_session_summary(&harness.state, base + 10 * second),
+            None
+        );
+        harness.key_down("d", false, base + 10 * second);
+        harness.key_up("d");
+        assert_eq!(session_count(&harness), sessions_before_pause + 1);
+        assert_eq!(
+            live_session_summary(&harness.state, base + 11 * second),
+            Some(LiveSessionSummary {
+                duration_ms: 1_000,
+                key_count: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn today_summary_json_matches_versioned_shape() {
+        let mut state = build_state(HashMap::new());
+        let now = MinuteKey::now();
+        for (app, keys, active_ms, sessions) in [
+            ("com.test.editor", 120, 90_000, 2),
+            ("com.test.browser", 30, 60_000, 1),
+        ] {
+            state.stats.insert(
+                StatsKey {
+                    date: now,
+                    app_name: app.to_string(),
+                    window_title: String::new(),
+                },
+                StatsValue {
+                    active_typing_ms: active_ms,
+                    key_count: keys,
+                    session_count: sessions,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            );
+        }
+        state.set_app_time_goal("com.test.editor", 2);
+        state.app_dict.insert(1, "com.test.editor".to_string());
+        state.event_chunks.push(super::shortcut::InputEventChunk {
+            v: 1,
+            chunk_start_ms: chrono::Utc::now().timestamp_millis(),
+            app_ref: 1,
+            events: vec![
+                "0,d,c,8".to_string(),
+                "10,d,c,8".to_string(),
+                "20,d,v,8".to_string(),
+            ],
+        });
+        state.auto_paused = true;
+
+        let json = serde_json::to_value(state.today_summary_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "v": 1,
+                "keys": 150,
+                "active_ms": 150_000,
+                "sessions": 3,
+                "top_app": "com.test.editor",
+                "top_shortcut": "cmd_c",
+                "paused": true,
+                "goal_progress": [{
+                    "bundle_id": "com.test.editor",
+                    "target_min": 2,
+                    "actual_min": 1,
+                    "pct": 75.0,
+                }],
+                "day_first_key_ms": null,
+                "day_last_key_ms": null,
+            })
+        );
+
+        // The CLI path reads saved files only: no pause state, and empty days report nothing.
+        let sandbox = temp_dir("today-json");
+        let dir = sandbox.join("profile");
+        let empty = super::load_today_summary_json(
+            dir.join("typingstats-details.json"),
+            &crate::app_config::AppConfig::default(),
+        );
+        assert_eq!(
+            serde_json::to_value(empty).unwrap(),
+            serde_json::json!({
+                "v": 1,
+                "keys": 0,
+                "active_ms": 0,
+                "sessions": 0,
+                "top_app": null,
+                "top_shortcut": null,
+                "paused": null,
+                "goal_progress": [],
+                "day_first_key_ms": null,
+                "day_last_key_ms": null,
+            })
+        );
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn runtime_ledger_flags_untracked_idle_and_active_days() {
+        use super::runtime::{
+            record_runtime, runtime_days, runtime_from_stored, runtime_to_stored, DayCoverage,
+            RuntimeSpan,
+        };
+        use super::shortcut::local_day_window_ms;
+
+        let day = |value: &str| NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+        let (feb9, _) = local_day_window_ms(day("2026-02-09")).unwrap();
+        let (feb11, _) = local_day_window_ms(day("2026-02-11")).unwrap();
+        let (_, feb12_end) = local_day_window_ms(day("2026-02-12")).unwrap();
+        let mut stats = HashMap::new();
+        stats.insert(
+            StatsKey {
+                date: "2026-02-09 10:00".parse().unwrap(),
+                app_name: "com.test.editor".to_string(),
+                window_title: "Doc".to_string(),
+            },
+            StatsValue {
+                active_typing_ms: 1_000,
+                key_count: 10,
+                session_count: 1,
+                writing_ms: 0,
+                navigation_ms: 0,
+            },
+        );
+        let mut state = build_state(stats);
+
+        // Running late on Feb 10 into Feb 11; checkpoints a minute apart extend one span.
+        let start = feb11 - 60_000;
+        record_runtime(&mut state, start);
+        record_runtime(&mut state, start + 60_000);
+        record_runtime(&mut state, start + 120_000);
+        assert_eq!(state.runtime_spans.len(), 1);
+        // A checkpoint after sleeping through the rest of the day opens a new span.
+        record_runtime(&mut state, feb11 + 3_600_000);
+        assert_eq!(
+            state.runtime_spans,
+            vec![
+                RuntimeSpan {
+                    start_ms: start,
+                    end_ms: start + 120_000,
+                },
+                RuntimeSpan {
+                    start_ms: feb11 + 3_600_000,
+                    end_ms: feb11 + 3_600_000,
+                },
+            ]
+        );
+
+        let stored = runtime_to_stored(&state.runtime_spans);
+        assert_eq!(stored["2026-02-10"].len(), 1);
+        assert_eq!(stored["2026-02-10"][0].end_ms, feb11);
+        assert_eq!(stored["2026-02-11"].len(), 2);
+        assert_eq!(runtime_from_stored(&stored), state.runtime_spans);
+
+        let days = runtime_days(&state, feb9, feb12_end);
+        let flags: Vec<(&str, u64, DayCoverage)> = days
+            .iter()
+            .map(|row| (row.date.as_str(), row.runtime_ms, row.coverage))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![
+                ("2026-02-09", 0, DayCoverage::Active),
+                ("2026-02-10", 60_000, DayCoverage::Idle),
+                ("2026-02-11", 60_000, DayCoverage::Idle),
+                ("2026-02-12", 0, DayCoverage::NotRunning),
+            ]
+        );
+    }
+
+    fn auto_export_state(name: &str) -> (CollectorState, TempDir) {
+        let dir = temp_dir(&format!("auto-export-{name}"));
+        let mut stats = HashMap::new();
+        for date in ["2026-02-09 10:00", "2026-02-10 23:59", "2026-02-11 00:01"] {
+            stats.insert(
+                StatsKey {
+                    date: date.parse().unwrap(),
+                    app_name: "com.test.editor".to_string(),
+                    window_title: "Doc".to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: 1_000,
+                    key_count: 10,
+                    session_count: 1,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            );
+        }
+        let mut state = build_state(stats);
+        state.app_log_path = dir.join("app.log");
+        state.auto_export_state_path = dir.join("auto-export-state.json");
+        state.set_auto_export(AutoExportConfig {
+            enabled: true,
+            format: AutoExportFormat::Csv,
+            dest_dir: dir.join("exports").to_string_lossy().to_string(),
+            script_path: None,
+            sign: false,
+        });
+        (state, dir)
+    }
+
+    #[test]
+    fn status_file_is_rate_limited_and_skips_unchanged_content() {
+        use super::status_file::{write_status_file_if_due, STATUS_FILE_MIN_INTERVAL};
+
+        let dir = temp_dir("status-file");
+        let mut state = build_state(HashMap::new());
+        state.status_file_path = dir.join("status.json");
+        let now = Instant::now();
+        let read = |state: &CollectorState| -> serde_json::Value {
+            serde_json::from_str(&std::fs::read_to_string(&state.status_file_path).unwrap())
+                .unwrap()
+        };
+
+        write_status_file_if_due(&mut state, now, 1_000);
+        assert!(!state.status_file_path.exists());
+
+        state.set_write_status_file(true);
+        write_status_file_if_due(&mut state, now, 1_000);
+        let status = read(&state);
+        assert_eq!(status["v"], 1);
+        assert_eq!(status["updated_at_ms"], 1_000);
+        assert_eq!(status["keys"], 0);
+        assert_eq!(status["paused"], false);
+        assert!(!dir.join("status.json.tmp").exists());
+
+        // Inside the minimum interval nothing is written, even after a change.
+        state.paused = true;
+        write_status_file_if_due(&mut state, now + Duration::from_secs(1), 2_000);
+        assert_eq!(read(&state)["updated_at_ms"], 1_000);
+
+        let later = now + STATUS_FILE_MIN_INTERVAL;
+        write_status_file_if_due(&mut state, later, 6_000);
+        assert_eq!(read(&state)["updated_at_ms"], 6_000);
+        assert_eq!(read(&state)["paused"], true);
+
+        // Only the timestamp would change, so the file is left alone.
+        write_status_file_if_due(&mut state, later + STATUS_FILE_MIN_INTERVAL, 11_000);
+        assert_eq!(read(&state)["updated_at_ms"], 6_000);
+
+        state.set_write_status_file(false);
+        assert!(!state.status_file_path.exists());
+    }
+
+    #[test]
+    fn auto_export_catches_up_missed_days_exactly_once() {
+        let (mut state, dir) = auto_export_state("catch-up");
+        let day = |value: &str| NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+        let now = Instant::now();
+
+        // The first run only records where exporting starts.
+        super::run_auto_export(&mut state, day("2026-02-09"), now);
+        assert_eq!(state.last_auto_export_day, Some(day("2026-02-08")));
+        assert!(!dir.join("exports").exists());
+
+        // A sleep across two midnights exports both finished days on the next tick.
+        super::run_auto_export(&mut state, day("2026-02-11"), now);
+        let first = dir.join("exports/typepulse-2026-02-09.csv");
+        let second = dir.join("exports/typepulse-2026-02-10.csv");
+        let csv = std::fs::read_to_string(&second).unwrap();
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.contains("2026-02-10 23:59"));
+        assert!(std::fs::read_to_string(&first)
+            .unwrap()
+            .contains("2026-02-09 10:00"));
+        let marker = crate::storage::load_auto_export_state(&state.auto_export_state_path).unwrap();
+        assert_eq!(marker.last_exported_day.as_deref(), Some("2026-02-10"));
+
+        // Later ticks of the same day do not export again.
+        std::fs::remove_file(&first).unwrap();
+        std::fs::remove_file(&second).unwrap();
+        super::run_auto_export(&mut state, day("2026-02-11"), now);
+        assert!(!first.exists());
+        assert!(!second.exists());
+    }
+
+    #[test]
+    fn signed_exports_form_a_chain_and_detect_modified_files() {
+        use super::verify_export;
+
+        let (mut state, dir) = auto_export_state("signed");
+        let day = |value: &str| NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+        let now = Instant::now();
+        state.export_chain_path = dir.join("export-chain.json");
+        let mut config = state.auto_export.clone();
+        config.sign = true;
+        state.set_auto_export(config.clone());
+        super::run_auto_export(&mut state, day("2026-02-09"), now);
+        super::run_auto_export(&mut state, day("2026-02-11"), now);
+        let first = dir.join("exports/typepulse-2026-02-09.csv");
+        let second = dir.join("exports/typepulse-2026-02-10.csv");
+
+        let first_check = verify_export(&first, &state.export_chain_path).unwrap();
+        let second_check = verify_export(&second, &state.export_chain_path).unwrap();
+        assert!(first_check.valid && second_check.valid);
+        assert_eq!(first_check.in_chain, Some(true));
+        let first_signature = first_check.signature.unwrap();
+        let second_signature = second_check.signature.unwrap();
+        assert_eq!(first_signature.position, 1);
+        assert_eq!(first_signature.prev_hash, "0".repeat(64));
+        assert_eq!(second_signature.position, 2);
+        assert_eq!(second_signature.prev_hash, first_signature.hash);
+
+        // Line endings do not count, edited values do.
+        let csv = std::fs::read_to_string(&first).unwrap();
+        std::fs::write(&first, csv.replace('\n', "\r\n")).unwrap();
+        assert!(
+            verify_export(&first, &state.export_chain_path)
+                .unwrap()
+                .valid
+        );
+        let csv = std::fs::read_to_string(&second).unwrap();
+        std::fs::write(&second, csv.replacen(",10,", ",11,", 1)).unwrap();
+        let tampered = verify_export(&second, &state.export_chain_path).unwrap();
+        assert!(tampered.signed && !tampered.valid);
+        assert_eq!(
+            tampered.reason.as_deref(),
+            Some("content does not match the signature")
+        );
+
+        // A JSON export continues the chain and moves its rows under `rows`.
+        config.format = AutoExportFormat::Json;
+        state.set_auto_export(config);
+        super::run_auto_export(&mut state, day("2026-02-12"), now);
+        let third = dir.join("exports/typepulse-2026-02-11.json");
+        let third_check = verify_export(&third, &state.export_chain_path).unwrap();
+        assert!(third_check.valid);
+        assert_eq!(
+            third_check.signature.unwrap().prev_hash,
+            second_signature.hash
+        );
+        let mut document: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&third).unwrap()).unwrap();
+        document["rows"][0]["key_count"] = serde_json::json!(99);
+        std::fs::write(&third, serde_json::to_vec(&document).unwrap()).unwrap();
+        assert!(
+            !verify_export(&third, &state.export_chain_path)
+                .unwrap()
+                .valid
+        );
+
+        // Workbooks carry the signature in their document properties.
+        let export = snapshot_stats_export(&state, "7d").unwrap();
+        let workbook = dir.join("exports/report.xlsx");
+        let report = write_stats_xlsx(&export, &workbook, Some(&state.export_chain_path)).unwrap();
+        assert_eq!(report.signature.unwrap().position, 4);
+        assert!(
+            verify_export(&workbook, &state.export_chain_path)
+                .unwrap()
+                .valid
+        );
+
+        // A chain from another profile cannot vouch for the file.
+        let other_chain = dir.join("other-chain.json");
+        assert_eq!(verify_export(&first, &other_chain).unwrap().in_chain, None);
+    }
+
+    #[test]
+    fn auto_export_failure_keeps_marker_and_backs_off() {
+        let (mut state, dir) = auto_export_state("failure");
+        let day = |value: &str| NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+        let blocked = dir.join("blocked");
+        std::fs::write(&blocked, "not a directory").unwrap();
+        state.auto_export.dest_dir = blocked.to_string_lossy().to_string();
+        state.last_auto_export_day = Some(day("2026-02-09"));
+        let now = Instant::now();
+
+        super::run_auto_export(&mut state, day("2026-02-11"), now);
+        assert_eq!(state.last_auto_export_day, Some(day("2026-02-09")));
+        assert!(state.auto_export_retry_at.is_some_and(|at| at > now));
+        flush_logs();
+        let log = std::fs::read_to_string(&state.app_log_path).unwrap();
+        assert!(log.contains("auto export of 2026-02-10 failed"));
+
+        // Once the directory is usable again the retry exports the missed day.
+        state.auto_export.dest_dir = dir.join("exports").to_string_lossy().to_string();
+        super::run_auto_export(&mut state, day("2026-02-11"), now);
+        assert_eq!(state.last_auto_export_day, Some(day("2026-02-09")));
+        super::run_auto_export(
+            &mut state,
+            day("2026-02-11"),
+            now + Duration::from_secs(301),
+        );
+        assert_eq!(state.last_auto_export_day, Some(day("2026-02-10")));
+        assert!(dir.join("exports/typepulse-2026-02-10.csv").exists());
+    }
+
+    #[test]
+    fn auto_export_pending_days_are_capped_after_long_gaps() {
+        use super::auto_export::{export_file_name, pending_export_days};
+
+        let day = |value: &str| NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+        let (days, skipped) = pending_export_days(day("2026-02-10"), day("2026-02-11"));
+        assert!(days.is_empty());
+        assert_eq!(skipped, 0);
+
+        let (days, skipped) = pending_export_days(day("2025-12-01"), day("2026-02-11"));
+        assert_eq!(days.len(), 31);
+        assert_eq!(days[0], day("2026-01-11"));
+        assert_eq!(days[30], day("2026-02-10"));
+        assert_eq!(skipped, 40);
+        assert_eq!(
+            export_file_name(day("2026-02-10"), AutoExportFormat::Json),
+            "typepulse-2026-02-10.json"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn auto_export_script_output_and_exit_status_are_logged() {
+        use super::auto_export::run_export_script;
+        use std::path::Path;
+
+        let export = Path::new("/tmp/typepulse-2026-02-10.csv");
+        let lines = run_export_script(Path::new("/bin/echo"), export);
+        assert_eq!(
+            lines,
+            vec![
+                "auto export script stdout: /tmp/typepulse-2026-02-10.csv".to_string(),
+                "auto export script finished for /tmp/typepulse-2026-02-10.csv".to_string(),
+            ]
+        );
+
+        let lines = run_export_script(Path::new("/bin/false"), export);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("auto export script error: /bin/false exited with"));
+
+        let lines = run_export_script(Path::new("/nonexistent/typepulse-script"), export);
+        assert!(lines[0].contains("is not a regular file"));
+        let lines = run_export_script(Path::new("/tmp"), export);
+        assert!(lines[0].contains("is not a regular file"));
+        let dir = temp_dir("export-script");
+        let not_executable = dir.join("upload.sh");
+        std::fs::write(&not_executable, "#!/bin/sh\n").unwrap();
+        let lines = run_export_script(&not_executable, export);
+        assert!(lines[0].contains("failed to start"));
+    }
+
+    #[test]
+    fn chunk_counters_track_push_prune_and_clear() {
+        use super::shortcut::{append_input_event, INPUT_CHUNK_MAX_STORED, INPUT_CHUNK_WINDOW_MS};
+
+        let mut state = build_state(HashMap::new());
+        state.storage = Arc::new(RecordingStorage::default());
+        let context = state.current_context();
+        let start_ms = chrono::Utc::now().timestamp_millis()
+            - (INPUT_CHUNK_MAX_STORED as i64 + 10) * INPUT_CHUNK_WINDOW_MS;
+        let append = |state: &mut CollectorState, index: i64| {
+            let at_ms = start_ms + index * INPUT_CHUNK_WINDOW_MS;
+            append_input_event(
+                state,
+                &context,
+                'd',
+                "k:a",
+                ModifierSnapshot::default(),
+                event_time_at_ms(at_ms),
+            );
+            append_input_event(
+                state,
+                &context,
+                'u',
+                "k:a",
+                ModifierSnapshot::default(),
+                event_time_at_ms(at_ms + 1),
+            );
+        };
+
+        for index in 0..3 {
+            append(&mut state, index);
+        }
+        assert_eq!(state.event_chunks.len(), 2);
+        assert_eq!(
+            state.chunk_counters,
+            ChunkCounters::from_chunks(&state.event_chunks)
+        );
+        let stats = super::snapshot_analytics_stats(&state);
+        assert_eq!(stats.total_events, 4);
+        assert_eq!(stats.oldest_chunk_ms, Some(start_ms));
+        assert_eq!(
+            stats.newest_chunk_ms,
+            Some(start_ms + INPUT_CHUNK_WINDOW_MS)
+        );
+        assert_eq!(stats.max_chunks, INPUT_CHUNK_MAX_STORED);
+        assert_eq!(stats.events_by_day.len(), 7);
+
+        // Push past the cap so the oldest chunks are pruned.
+        for index in 3..(INPUT_CHUNK_MAX_STORED as i64 + 5) {
+            append(&mut state, index);
+        }
+        assert_eq!(state.event_chunks.len(), INPUT_CHUNK_MAX_STORED);
+        assert_eq!(
+            state.chunk_counters,
+            ChunkCounters::from_chunks(&state.event_chunks)
+        );
+        let stats = super::snapshot_analytics_stats(&state);
+        assert_eq!(stats.total_events, 2 * INPUT_CHUNK_MAX_STORED as u64);
+        assert_eq!(
+            stats.oldest_chunk_ms,
+            Some(start_ms + 4 * INPUT_CHUNK_WINDOW_MS)
+        );
+        let recent_events: u64 = stats.events_by_day.iter().map(|day| day.events).sum();
+        assert!(recent_events > 0 && recent_events <= stats.total_events);
+
+        state.clear_stats();
+        assert_eq!(state.chunk_counters, ChunkCounters::default());
+        let stats = super::snapshot_analytics_stats(&state);
+        assert_eq!(stats.chunk_count, 0);
+        assert_eq!(stats.total_events, 0);
+        assert_eq!(stats.approx_bytes, 0);
+        assert_eq!(stats.oldest_chunk_ms, None);
+    }
 
-        let rows = harness.rows();
-        assert_eq!(rows.len(), 1);
-        assert_eq!(rows[0].key_count, 1);
-        assert_eq!(rows[0].active_typing_ms, 200);
+    #[test]
+    fn incremental_bursts_match_offline_replay_over_chunks() {
+        use super::burst::{bursts_from_key_times, expire_burst, record_burst_key, BurstDay};
+        use super::shortcut::{append_input_event, for_each_key_down_in_window};
+
+        let mut state = build_state(HashMap::new());
+        let editor = state.current_context();
+        let mut terminal = editor.clone();
+        terminal.app_name = "Terminal".to_string();
+        terminal.bundle_id = Some("com.test.term".to_string());
+        let mut at_ms = chrono::Local
+            .with_ymd_and_hms(2024, 3, 5, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp_millis();
+        let press = |state: &mut CollectorState, context: &CaptureContext, at_ms: i64| {
+            append_input_event(
+                state,
+                context,
+                'd',
+                "k:a",
+                ModifierSnapshot::default(),
+                event_time_at_ms(at_ms),
+            );
+            record_burst_key(state, at_ms);
+        };
+
+        // Too short to count.
+        for _ in 0..5 {
+            press(&mut state, &editor, at_ms);
+            at_ms += 100;
+        }
+        // 12 keys 150ms apart, finalized by a tick noticing the run went stale.
+        at_ms += 3_000;
+        for index in 0..12 {
+            if index > 0 {
+                at_ms += 150;
+            }
+            press(&mut state, &editor, at_ms);
+        }
+        expire_burst(&mut state, at_ms + 1_000);
+        assert!(state.burst_run.is_some());
+        expire_burst(&mut state, at_ms + 2_500);
+        assert!(state.burst_run.is_none());
+        // 10 keys just under the gap threshold.
+        at_ms += 5_000;
+        for index in 0..10 {
+            if index > 0 {
+                at_ms += 1_999;
+            }
+            press(&mut state, &editor, at_ms);
+        }
+        // A gap equal to the threshold starts a new run; this one spans two apps and chunks.
+        at_ms += 2_000;
+        for index in 0..25 {
+            if index > 0 {
+                at_ms += 80;
+            }
+            let context = if (index / 7) % 2 == 0 {
+                &editor
+            } else {
+                &terminal
+            };
+            press(&mut state, context, at_ms);
+        }
+        expire_burst(&mut state, at_ms + 2_000);
+
+        let day = state.burst_days["2024-03-05"];
+        assert_eq!(
+            day,
+            BurstDay {
+                count: 3,
+                total_keys: 47,
+                total_ms: 1_650 + 17_991 + 1_920,
+                longest_keys: 25,
+                longest_ms: 17_991,
+            }
+        );
+        let mut times = Vec::new();
+        for_each_key_down_in_window(&state, i64::MIN, i64::MAX, None, |at_ms, _| {
+            times.push(at_ms)
+        });
+        assert_eq!(times.len(), 52);
+        assert_eq!(
+            bursts_from_key_times(times, state.burst_thresholds),
+            state.burst_days
+        );
     }
 
     #[test]
-    fn top_keys_counts_key_down_only_and_sorts() {
+    fn key_effort_weights_follow_rows_and_pinky_columns() {
+        use super::effort::key_effort_milli;
+
+        assert_eq!(key_effort_milli("f"), 1_000);
+        assert_eq!(key_effort_milli("a"), 1_000);
+        assert_eq!(key_effort_milli("space"), 1_000);
+        assert!(key_effort_milli("g") > key_effort_milli("f"));
+        // Rows away from home cost more, the number row more than the top row.
+        assert!(key_effort_milli("e") > key_effort_milli("d"));
+        assert_eq!(key_effort_milli("e"), key_effort_milli("c"));
+        assert!(key_effort_milli("3") > key_effort_milli("e"));
+        assert!(key_effort_milli("esc") > key_effort_milli("3"));
+        // Pinky columns cost more than the same row under the other fingers.
+        assert!(key_effort_milli("q") > key_effort_milli("w"));
+        assert!(key_effort_milli("p") > key_effort_milli("o"));
+        assert!(key_effort_milli("/") > key_effort_milli("."));
+        assert!(key_effort_milli("backspace") > key_effort_milli("9"));
+        assert_eq!(key_effort_milli("enter"), key_effort_milli("q"));
+        // Unknown keys weigh like a home-row key.
+        assert_eq!(key_effort_milli("f19"), 1_000);
+        assert_eq!(key_effort_milli(""), 1_000);
+    }
+
+    #[test]
+    fn key_effort_accumulates_per_local_day_and_clears_with_today() {
+        use super::effort::{add_key_effort, record_key_effort, snapshot_effort_stats};
+        use super::DestructiveAction;
+
+        let late = chrono::Local
+            .with_ymd_and_hms(2024, 3, 5, 23, 59, 59)
+            .single()
+            .unwrap()
+            .timestamp_millis();
+        let mut days = HashMap::new();
+        for key in ["f", "j", "q", "3", "left"] {
+            add_key_effort(&mut days, key, late);
+        }
+        add_key_effort(&mut days, "space", late + 2_000);
+        assert_eq!(
+            days,
+            HashMap::from([
+                (
+                    "2024-03-05".to_string(),
+                    1_000 + 1_000 + 2_000 + 2_000 + 2_500
+                ),
+                ("2024-03-06".to_string(), 1_000),
+            ])
+        );
+
+        let dir = temp_dir("effort");
         let mut state = build_state(HashMap::new());
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = dir.join("app.log");
         let now_ms = chrono::Utc::now().timestamp_millis();
-        state.event_chunks.push(super::shortcut::InputEventChunk {
+        for key in ["h", "e", "l", "l", "o"] {
+            record_key_effort(&mut state, key, now_ms);
+        }
+        let today = snapshot_effort_stats(&state, "today");
+        assert_eq!(today.days.len(), 1);
+        assert!((today.effort - 6.2).abs() < 1e-9);
+        assert_eq!(today.days[0].effort, today.effort);
+        assert_eq!(state.snapshot().effort_today, today.effort);
+        assert_eq!(snapshot_effort_stats(&state, "7d").effort, today.effort);
+
+        state
+            .run_destructive_action(DestructiveAction::ResetToday)
+            .unwrap();
+        assert_eq!(state.snapshot().effort_today, 0.0);
+    }
+
+    #[test]
+    fn keys_are_counted_per_display_of_the_active_window() {
+        use super::display::{display_at, DisplayBounds};
+
+        let display = |id: &str, x: f64, width: f64, height: f64| DisplayBounds {
+            id: id.to_string(),
+            name: id.to_string(),
+            x,
+            y: 0.0,
+            width,
+            height,
+        };
+        let displays = [
+            display("display-1", 0.0, 1440.0, 900.0),
+            display("display-2", 1440.0, 2560.0, 1440.0),
+        ];
+        // Straddling window: most of it is on the external display.
+        assert_eq!(
+            display_at(&displays, 1200.0, 100.0, 800.0, 600.0).as_deref(),
+            Some("display-2")
+        );
+        assert_eq!(
+            display_at(&displays, 10.0, 10.0, 0.0, 0.0).as_deref(),
+            Some("display-1")
+        );
+        assert_eq!(display_at(&displays, -900.0, 0.0, 500.0, 500.0), None);
+        assert_eq!(display_at(&[], 0.0, 0.0, 100.0, 100.0), None);
+
+        let mut harness = CollectorEventHarness::new();
+        let now = Instant::now();
+        harness.default_context.display_id = Some("display-2".to_string());
+        harness.key_down("a", false, now);
+        harness.key_up("a");
+        harness.key_down("b", false, now + Duration::from_millis(100));
+        harness.key_up("b");
+        harness.default_context.display_id = Some("display-1".to_string());
+        harness.key_down("c", false, now + Duration::from_millis(200));
+        harness.key_up("c");
+        harness.default_context.display_id = None;
+        harness.key_down("d", false, now + Duration::from_millis(300));
+
+        let labels = BTreeMap::from([("display-2".to_string(), "Studio Display".to_string())]);
+        let stats = snapshot_display_stats(&harness.state, "today", &labels);
+        assert_eq!(stats.total_keys, 3);
+        let rows: Vec<(&str, &str, u64)> = stats
+            .displays
+            .iter()
+            .map(|row| (row.display_id.as_str(), row.label.as_str(), row.key_count))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("display-2", "Studio Display", 2),
+                ("display-1", "display-1", 1)
+            ]
+        );
+        assert!((stats.displays[0].share_pct - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.days.len(), 1);
+        assert_eq!(stats.days[0].counts.values().sum::<u64>(), 3);
+        assert_eq!(
+            build_stored_input_analytics(&mut harness.state).displays,
+            harness.state.display_days
+        );
+    }
+
+    #[test]
+    fn rolling_average_skips_unrecorded_days_and_handles_short_histories() {
+        use super::coverage::CoverageDay;
+        use super::rolling_avg::{daily_average, DailyAverage, DayTotal};
+
+        let day = |value: &str| NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap();
+        let total = |key_count, active_typing_ms| DayTotal {
+            key_count,
+            active_typing_ms,
+        };
+        let today = day("2024-03-10");
+        let totals = HashMap::from([
+            // Today is still running and never counts.
+            (day("2024-03-10"), total(999, 9_990)),
+            (day("2024-03-09"), total(100, 1_000)),
+            (day("2024-03-08"), total(300, 3_000)),
+        ]);
+        let coverage = HashMap::from([
+            // Recorded without typing: counts as a zero day.
+            (
+                "2024-03-06".to_string(),
+                CoverageDay {
+                    recording_ms: 3_600_000,
+                    ..CoverageDay::default()
+                },
+            ),
+            // Paused the whole day: left out.
+            (
+                "2024-03-07".to_string(),
+                CoverageDay {
+                    paused_ms: 3_600_000,
+                    ..CoverageDay::default()
+                },
+            ),
+        ]);
+        let expected = DailyAverage {
+            key_count: 400.0 / 3.0,
+            active_typing_ms: 4_000.0 / 3.0,
+        };
+        assert_eq!(daily_average(&totals, &coverage, today, 7), Some(expected));
+        // A history shorter than the window averages over the days it has.
+        assert_eq!(daily_average(&totals, &coverage, today, 30), Some(expected));
+        assert_eq!(
+            daily_average(&totals, &coverage, today, 1),
+            Some(DailyAverage {
+                key_count: 100.0,
+                active_typing_ms: 1_000.0,
+            })
+        );
+        assert_eq!(
+            daily_average(&totals, &HashMap::new(), day("2024-03-08"), 7),
+            None
+        );
+        assert_eq!(
+            daily_average(&HashMap::new(), &HashMap::new(), today, 30),
+            None
+        );
+    }
+
+    #[test]
+    fn rolling_averages_feed_the_snapshot_and_refresh_when_the_date_changes() {
+        use super::rolling_avg::refresh_rolling_averages;
+
+        let today = chrono::Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        let key = |day: NaiveDate| StatsKey {
+            date: format!("{} 10:00", day.format("%Y-%m-%d")).parse().unwrap(),
+            app_name: "com.test.editor".to_string(),
+            window_title: "Doc".to_string(),
+        };
+        let value = |key_count| StatsValue {
+            active_typing_ms: key_count * 100,
+            key_count,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let mut state = build_state(HashMap::from([
+            (key(yesterday), value(40)),
+            (key(today), value(50)),
+        ]));
+        let snapshot = state.snapshot();
+        assert_eq!(snapshot.avg_keys_7d, None);
+        assert_eq!(snapshot.today_vs_avg_pct, None);
+
+        refresh_rolling_averages(&mut state, today);
+        let snapshot = state.snapshot();
+        assert_eq!(snapshot.avg_keys_7d, Some(40.0));
+        assert_eq!(snapshot.avg_keys_30d, Some(40.0));
+        assert_eq!(snapshot.avg_active_ms_7d, Some(4_000.0));
+        assert_eq!(snapshot.avg_active_ms_30d, Some(4_000.0));
+        assert_eq!(snapshot.today_vs_avg_pct, Some(25.0));
+
+        // Cached for the day; the next day folds the finished day in.
+        refresh_rolling_averages(&mut state, today);
+        assert_eq!(state.rolling_averages.short.unwrap().key_count, 40.0);
+        refresh_rolling_averages(&mut state, today.succ_opt().unwrap());
+        assert_eq!(state.rolling_averages.short.unwrap().key_count, 45.0);
+    }
+
+    #[test]
+    fn purge_app_data_dry_run_reports_and_purge_removes_every_trace() {
+        use super::shortcut::append_input_event;
+        use super::ShortcutUsageValue;
+
+        let dir = temp_dir("purge");
+        let key = |minute: &str, app: &str| StatsKey {
+            date: format!("2026-02-09 10:{minute}").parse().unwrap(),
+            app_name: app.to_string(),
+            window_title: "Doc".to_string(),
+        };
+        let value = |key_count| StatsValue {
+            active_typing_ms: key_count * 100,
+            key_count,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let mut state = build_state(HashMap::from([
+            (key("00", "com.test.editor"), value(4)),
+            (key("01", "com.test.editor"), value(6)),
+            (key("00", "com.test.Term"), value(7)),
+        ]));
+        state.storage = Arc::new(RecordingStorage::default());
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = dir.join("app.log");
+        state.shortcut_usage = HashMap::from([
+            (
+                "cmd_c".to_string(),
+                ShortcutUsageValue {
+                    count: 5,
+                    by_app: HashMap::from([
+                        ("com.test.editor".to_string(), 2),
+                        ("com.test.Term".to_string(), 3),
+                    ]),
+                },
+            ),
+            (
+                "cmd_t".to_string(),
+                ShortcutUsageValue {
+                    count: 1,
+                    by_app: HashMap::from([("com.test.Term".to_string(), 1)]),
+                },
+            ),
+        ]);
+        let editor = state.current_context();
+        let mut terminal = editor.clone();
+        terminal.app_name = "Terminal".to_string();
+        terminal.bundle_id = Some("com.test.Term".to_string());
+        let start_ms = chrono::Utc::now().timestamp_millis() - 60_000;
+        for (offset, context) in [
+            (0, &editor),
+            (10, &terminal),
+            (20, &editor),
+            (30, &terminal),
+        ] {
+            append_input_event(
+                &mut state,
+                context,
+                'd',
+                "k:a",
+                ModifierSnapshot::default(),
+                event_time_at_ms(start_ms + offset),
+            );
+        }
+        state.chord_aborts.insert(
+            "2026-02-09".to_string(),
+            HashMap::from([
+                ("com.test.editor".to_string(), 1),
+                ("com.test.Term".to_string(), 2),
+            ]),
+        );
+        let switches = state
+            .app_switches
+            .entry("2026-02-09".to_string())
+            .or_default();
+        switches.record("com.test.editor".to_string(), "com.test.Term".to_string());
+        switches.record("com.test.Term".to_string(), "com.test.editor".to_string());
+        switches.record("com.test.editor".to_string(), "com.test.other".to_string());
+
+        let preview = state.purge_app_data("com.test.term", true).unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(
+            (
+                preview.stats_rows,
+                preview.key_count,
+                preview.active_typing_ms
+            ),
+            (1, 7, 700)
+        );
+        assert_eq!((preview.shortcut_uses, preview.shortcuts_dropped), (4, 1));
+        assert_eq!((preview.event_chunks, preview.events), (2, 2));
+        assert_eq!(preview.app_dict_entries, 1);
+        assert_eq!((preview.chord_aborts, preview.app_switches), (2, 2));
+        assert_eq!(state.stats.len(), 3);
+        assert_eq!(state.shortcut_usage.len(), 2);
+        assert_eq!(state.app_dict.len(), 2);
+        flush_logs();
+        assert!(!dir.join("app.log").exists());
+
+        let report = state.purge_app_data("com.test.term", false).unwrap();
+        assert!(!report.dry_run);
+        assert_eq!(
+            AppPurgeReport {
+                dry_run: true,
+                purged_at_ms: preview.purged_at_ms,
+                ..report.clone()
+            },
+            preview
+        );
+        assert!(state
+            .stats
+            .keys()
+            .all(|key| key.app_name == "com.test.editor"));
+        assert_eq!(state.stats.len(), 2);
+        assert_eq!(state.shortcut_usage["cmd_c"].count, 2);
+        assert!(!state.shortcut_usage.contains_key("cmd_t"));
+        assert_eq!(state.app_dict.len(), 1);
+        assert!(!state.app_ref_by_app.contains_key("com.test.Term"));
+        assert_eq!(
+            state.chunk_counters,
+            ChunkCounters::from_chunks(&state.event_chunks)
+        );
+        let remaining_refs: HashSet<u32> = state
+            .event_chunks
+            .iter()
+            .map(|chunk| chunk.app_ref)
+            .chain(state.open_event_chunk.iter().map(|chunk| chunk.app_ref))
+            .collect();
+        assert!(remaining_refs
+            .iter()
+            .all(|app_ref| state.app_dict.contains_key(app_ref)));
+        assert_eq!(
+            state.chord_aborts["2026-02-09"],
+            HashMap::from([("com.test.editor".to_string(), 1)])
+        );
+        assert_eq!(state.app_switches["2026-02-09"].total, 1);
+        let csv = std::fs::read_to_string(dir.join("log.csv")).unwrap();
+        assert!(!csv.to_ascii_lowercase().contains("com.test.term"));
+        flush_logs();
+        let app_log = std::fs::read_to_string(dir.join("app.log")).unwrap();
+        assert!(app_log.contains("app data purge: app=com.test.term stats_rows=1 key_count=7"));
+    }
+
+    #[test]
+    fn csv_dialects_quote_fields_containing_the_active_delimiter() {
+        use super::io::{format_csv, write_csv};
+        use super::StatsRow;
+        use crate::app_config::CsvDelimiter;
+
+        let rows = vec![StatsRow {
+            date: "2026-02-09 10:00".parse().unwrap(),
+            app_name: "com.test.editor".to_string(),
+            window_title: "a,b;c\td \"e\"".to_string(),
+            active_typing_ms: 1_500,
+            key_count: 12,
+            session_count: 1,
+            writing_ms: 1_200,
+            navigation_ms: 300,
+        }];
+        let dialect = |delimiter, utf8_bom, headers_localized| CsvDialect {
+            delimiter,
+            utf8_bom,
+            headers_localized,
+        };
+
+        assert_eq!(
+            format_csv(&rows, CsvDialect::default()),
+            "date,app_name,window_title,active_typing_ms,key_count,session_count,distinct_windows,\
+             writing_ms,navigation_ms\n\
+             2026-02-09 10:00,com.test.editor,\"a,b;c\td \"\"e\"\"\",1500,12,1,1,1200,300\n"
+        );
+        assert_eq!(
+            format_csv(&rows, dialect(CsvDelimiter::Semicolon, true, false)),
+            "\u{feff}date;app_name;window_title;active_typing_ms;key_count;session_count;distinct_windows;\
+             writing_ms;navigation_ms\n\
+             2026-02-09 10:00;com.test.editor;\"a,b;c\td \"\"e\"\"\";1500;12;1;1;1200;300\n"
+        );
+        assert_eq!(
+            format_csv(&rows, dialect(CsvDelimiter::Tab, false, true)),
+            "日期\t应用\t窗口标题\t活跃打字时长（毫秒）\t按键数\t会话数\t当日窗口数\t\
+             书写时长（毫秒）\t导航时长（毫秒）\n\
+             2026-02-09 10:00\tcom.test.editor\t\"a,b;c\td \"\"e\"\"\"\t1500\t12\t1\t1\t1200\t300\n"
+        );
+
+        // Without a quote in the title only the active delimiter forces quoting.
+        let plain = vec![StatsRow {
+            window_title: "a,b".to_string(),
+            ..rows[0].clone()
+        }];
+        let semicolon = format_csv(&plain, dialect(CsvDelimiter::Semicolon, false, false));
+        assert!(semicolon.ends_with("com.test.editor;a,b;1500;12;1;1;1200;300\n"));
+        let comma = format_csv(&plain, CsvDialect::default());
+        assert!(comma.ends_with("com.test.editor,\"a,b\",1500,12,1,1,1200,300\n"));
+
+        // The written file starts with the BOM bytes.
+        let dir = temp_dir("csv-bom");
+        let path = dir.join("log.csv");
+        write_csv(&path, &rows, dialect(CsvDelimiter::Semicolon, true, false)).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..3], &[0xef, 0xbb, 0xbf]);
+    }
+
+    #[test]
+    fn distinct_windows_count_titles_per_app_day_and_skip_hidden_titles() {
+        use super::window_count::{distinct_windows_by_app_day, distinct_windows_of};
+        use super::StatsRow;
+
+        let row = |date: &str, app: &str, title: &str| StatsRow {
+            date: date.parse().unwrap(),
+            app_name: app.to_string(),
+            window_title: title.to_string(),
+            active_typing_ms: 100,
+            key_count: 1,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let rows = vec![
+            row("2026-02-09 10:00", "com.test.editor", "notes.md"),
+            row("2026-02-09 10:05", "com.test.editor", "notes.md"),
+            row("2026-02-09 10:06", "com.test.editor", "todo.md"),
+            // Hidden titles are recorded empty and cannot be told apart.
+            row("2026-02-09 10:07", "com.test.editor", ""),
+            row("2026-02-09 11:00", "com.test.Term", ""),
+            row("2026-02-10 09:00", "com.test.editor", "notes.md"),
+        ];
+
+        let counts = distinct_windows_by_app_day(&rows);
+        assert_eq!(counts.len(), 3);
+        assert_eq!(
+            counts[&("2026-02-09".to_string(), "com.test.editor".to_string())],
+            2
+        );
+        assert_eq!(
+            counts[&("2026-02-09".to_string(), "com.test.Term".to_string())],
+            0
+        );
+        assert_eq!(distinct_windows_of(&counts, &rows[5]), 1);
+        assert_eq!(
+            distinct_windows_of(&counts, &row("2026-02-11 09:00", "com.test.editor", "x")),
+            0
+        );
+    }
+
+    #[test]
+    fn stats_export_rolls_rows_up_per_app_day_and_counts_sheet_rows() {
+        use super::xlsx_export::{rollup_app_days, XlsxSheetRows};
+
+        let today = MinuteKey::now().day().format("%Y-%m-%d").to_string();
+        let key = |minute: &str, app: &str, title: &str| StatsKey {
+            date: format!("{today} 00:{minute}").parse().unwrap(),
+            app_name: app.to_string(),
+            window_title: title.to_string(),
+        };
+        let value = |key_count| StatsValue {
+            active_typing_ms: key_count * 100,
+            key_count,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let state = build_state(HashMap::from([
+            (key("00", "com.test.editor", "A"), value(4)),
+            (key("01", "com.test.editor", "B"), value(6)),
+            (key("00", "com.test.Term", "A"), value(7)),
+            (
+                StatsKey {
+                    date: "2020-01-01 09:00".parse().unwrap(),
+                    app_name: "com.test.editor".to_string(),
+                    window_title: "Old".to_string(),
+                },
+                value(3),
+            ),
+        ]));
+
+        let rows = state.snapshot_rows().unwrap();
+        let rollups = rollup_app_days(&rows);
+        assert_eq!(rollups.len(), 3);
+        assert_eq!(rollups[0].date, "2020-01-01");
+        let editor_today = rollups
+            .iter()
+            .find(|rollup| rollup.date == today && rollup.app_name == "com.test.editor")
+            .unwrap();
+        assert_eq!(editor_today.key_count, 10);
+        assert_eq!(editor_today.active_typing_ms, 1000);
+        assert_eq!(editor_today.session_count, 2);
+        assert_eq!(editor_today.distinct_windows, 2);
+
+        let export = snapshot_stats_export(&state, "7d").unwrap();
+        let dir = temp_dir("export");
+        let path = dir.join("stats.xlsx");
+        let report = write_stats_xlsx(&export, &path, None).unwrap();
+        assert_eq!(report.range, "7d");
+        assert_eq!(
+            report.sheets,
+            vec![
+                XlsxSheetRows {
+                    sheet: "Daily by app".to_string(),
+                    rows: 2,
+                },
+                XlsxSheetRows {
+                    sheet: "Rows".to_string(),
+                    rows: 3,
+                },
+                XlsxSheetRows {
+                    sheet: "Shortcuts".to_string(),
+                    rows: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parquet_export_hashes_titles_and_decodes_key_down_events() {
+        use super::parquet_export::window_title_hash;
+        use super::shortcut::append_input_event;
+        use super::{CSV_COLUMNS, PARQUET_EVENT_COLUMNS, PARQUET_STATS_COLUMNS};
+
+        // Shared columns keep the names published for the CSV/StoredRow schema.
+        for (parquet, csv) in PARQUET_STATS_COLUMNS.iter().zip(CSV_COLUMNS) {
+            if csv == "window_title" {
+                assert_eq!(*parquet, "window_title_hash");
+            } else {
+                assert_eq!(*parquet, csv);
+            }
+        }
+        assert_eq!(
+            PARQUET_EVENT_COLUMNS,
+            ["ts_ms", "app_name", "key", "modifiers"]
+        );
+
+        assert_eq!(window_title_hash(""), "");
+        assert_eq!(window_title_hash("a"), "af63dc4c8601ec8c");
+        assert_ne!(window_title_hash("Doc"), window_title_hash("doc"));
+
+        let today = MinuteKey::now().day().format("%Y-%m-%d").to_string();
+        let mut state = build_state(HashMap::from([(
+            StatsKey {
+                date: format!("{today} 00:00").parse().unwrap(),
+                app_name: "com.test.editor".to_string(),
+                window_title: "Doc".to_string(),
+            },
+            StatsValue {
+                active_typing_ms: 400,
+                key_count: 4,
+                session_count: 1,
+                writing_ms: 0,
+                navigation_ms: 0,
+            },
+        )]));
+        state.storage = Arc::new(RecordingStorage::default());
+        let context = state.current_context();
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let shift = ModifierSnapshot {
+            shift: true,
+            ..ModifierSnapshot::default()
+        };
+        append_input_event(
+            &mut state,
+            &context,
+            'd',
+            "k:a",
+            shift,
+            event_time_at_ms(now_ms),
+        );
+        append_input_event(
+            &mut state,
+            &context,
+            'u',
+            "k:a",
+            shift,
+            event_time_at_ms(now_ms + 10),
+        );
+        append_input_event(
+            &mut state,
+            &context,
+            'd',
+            "k:b",
+            ModifierSnapshot::default(),
+            event_time_at_ms(now_ms + 20),
+        );
+
+        let without_events = snapshot_parquet_export(&state, "today", false).unwrap();
+        assert!(without_events.events.is_none());
+        let export = snapshot_parquet_export(&state, "today", true).unwrap();
+        assert_eq!(export.stats.len(), 1);
+        assert_eq!(export.stats[0].window_title_hash, window_title_hash("Doc"));
+        let events = export.events.unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].key, "k:a");
+        assert_eq!(events[0].modifiers, shift.bitmask());
+        assert_eq!(events[1].key, "k:b");
+        assert_eq!(events[1].modifiers, 0);
+        assert_eq!(events[0].app_name, events[1].app_name);
+    }
+
+    #[cfg(not(feature = "parquet-export"))]
+    #[test]
+    fn parquet_export_reports_missing_feature() {
+        let state = build_state(HashMap::new());
+        let export = snapshot_parquet_export(&state, "today", false).unwrap();
+        let sandbox = temp_dir("parquet-disabled");
+        let dest = sandbox.join("export");
+        let err = write_parquet_export(&export, &dest).unwrap_err();
+        assert!(err.contains("parquet-export"));
+        assert!(!dest.exists());
+    }
+
+    #[cfg(feature = "parquet-export")]
+    #[test]
+    fn parquet_export_round_trips_through_arrow_reader() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        use super::shortcut::append_input_event;
+        use super::{PARQUET_EVENT_COLUMNS, PARQUET_STATS_COLUMNS};
+
+        let today = MinuteKey::now().day().format("%Y-%m-%d").to_string();
+        let key = |minute: &str, title: &str| StatsKey {
+            date: format!("{today} 00:{minute}").parse().unwrap(),
+            app_name: "com.test.editor".to_string(),
+            window_title: title.to_string(),
+        };
+        let value = StatsValue {
+            active_typing_ms: 100,
+            key_count: 1,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let mut state = build_state(HashMap::from([
+            (key("00", "A"), value.clone()),
+            (key("01", "B"), value.clone()),
+            (key("02", ""), value),
+        ]));
+        state.storage = Arc::new(RecordingStorage::default());
+        let context = state.current_context();
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        for offset in 0..5 {
+            append_input_event(
+                &mut state,
+                &context,
+                'd',
+                "k:a",
+                ModifierSnapshot::default(),
+                event_time_at_ms(now_ms + offset),
+            );
+        }
+
+        let dir = temp_dir("parquet");
+        let export = snapshot_parquet_export(&state, "today", true).unwrap();
+        let report = write_parquet_export(&export, &dir).unwrap();
+        assert_eq!(report.files.len(), 2);
+
+        let read_back = |path: &str, columns: &[&str]| -> u64 {
+            let builder =
+                ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(path).unwrap())
+                    .unwrap();
+            let names: Vec<String> = builder
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| field.name().clone())
+                .collect();
+            assert_eq!(names, columns);
+            builder
+                .build()
+                .unwrap()
+                .map(|batch| batch.unwrap().num_rows() as u64)
+                .sum()
+        };
+        assert_eq!(report.files[0].rows, 3);
+        assert_eq!(read_back(&report.files[0].path, &PARQUET_STATS_COLUMNS), 3);
+        assert_eq!(report.files[1].rows, 5);
+        assert_eq!(read_back(&report.files[1].path, &PARQUET_EVENT_COLUMNS), 5);
+    }
+
+    #[test]
+    fn key_holds_pair_interleaved_keys_and_count_missing_ups() {
+        use super::key_hold::{
+            build_key_hold_stats, pair_key_holds, HoldChunk, HOLD_CHUNK_JOIN_MS, MAX_KEY_HOLD_MS,
+        };
+
+        let events = |raw: &[&str]| {
+            raw.iter()
+                .map(|event| event.to_string())
+                .collect::<Vec<_>>()
+        };
+        // a and b overlap (rollover typing); c is pressed twice before its only up.
+        let first = events(&[
+            "0,d,a,0",
+            "30,d,b,0",
+            "80,u,a,0",
+            "120,u,b,0",
+            "200,d,c,0",
+            "260,d,c,0",
+            "300,u,c,0",
+            "400,u,z,0",
+            "4900,d,space,0",
+        ]);
+        // Same app, right after the first chunk: the space hold spans the boundary.
+        let second = events(&["100,u,space,0", "200,d,a,0"]);
+        // Another app: the pending `a` from the second chunk never sees its up.
+        let third = events(&["0,u,a,0", "10,d,b,0", "50,u,b,0"]);
+        let mut chunks = vec![
+            HoldChunk {
+                v: 1,
+                chunk_start_ms: 10_000,
+                app_ref: 2,
+                events: &third,
+            },
+            HoldChunk {
+                v: 1,
+                chunk_start_ms: 0,
+                app_ref: 1,
+                events: &first,
+            },
+            HoldChunk {
+                v: 1,
+                chunk_start_ms: 5_000,
+                app_ref: 1,
+                events: &second,
+            },
+        ];
+        let holds = pair_key_holds(&mut chunks, 0, i64::MAX);
+        assert_eq!(holds.durations["a"], vec![80]);
+        assert_eq!(holds.durations["b"], vec![90, 40]);
+        assert_eq!(holds.durations["c"], vec![40]);
+        assert_eq!(holds.durations["space"], vec![200]);
+        assert_eq!(holds.unmatched["a"], 1);
+        assert_eq!(holds.unmatched["c"], 1);
+        assert!(!holds.durations.contains_key("z"));
+
+        let stats = build_key_hold_stats("today", holds);
+        assert_eq!(stats.matched, 5);
+        assert_eq!(stats.unmatched_downs, 2);
+        assert_eq!(stats.p95_ms, 200);
+        assert_eq!(stats.mean_ms, 90.0);
+        assert_eq!(stats.keys[0].key, "b");
+        assert_eq!(stats.keys[0].mean_ms, 65.0);
+        assert_eq!(stats.keys[0].p95_ms, 90);
+        assert_eq!(stats.keys[0].max_ms, 90);
+        let a = stats.keys.iter().find(|row| row.key == "a").unwrap();
+        assert_eq!((a.count, a.unmatched_downs), (1, 1));
+
+        // A gap longer than the join window or an overlong hold drops the pending down.
+        let late = events(&["0,d,x,0"]);
+        let up = events(&["0,u,x,0"]);
+        let mut gapped = vec![
+            HoldChunk {
+                v: 1,
+                chunk_start_ms: 0,
+                app_ref: 1,
+                events: &late,
+            },
+            HoldChunk {
+                v: 1,
+                chunk_start_ms: HOLD_CHUNK_JOIN_MS + 1,
+                app_ref: 1,
+                events: &up,
+            },
+        ];
+        let holds = pair_key_holds(&mut gapped, 0, i64::MAX);
+        assert!(holds.durations.is_empty());
+        assert_eq!(holds.unmatched["x"], 1);
+        let overlong = events(&["0,d,x,0", &format!("{},u,x,0", MAX_KEY_HOLD_MS + 1)]);
+        let mut single = vec![HoldChunk {
             v: 1,
-            chunk_start_ms: now_ms,
+            chunk_start_ms: 0,
             app_ref: 1,
-            events: vec![
-                "0,d,a,0".to_string(),
-                "50,d,a,0".to_string(),
-                "100,d,tab,0".to_string(),
-                "150,u,a,0".to_string(),
-            ],
+            events: &overlong,
+        }];
+        assert_eq!(pair_key_holds(&mut single, 0, i64::MAX).unmatched["x"], 1);
+
+        // Downs outside the window are not counted even when their up falls inside it.
+        let mut windowed = vec![HoldChunk {
+            v: 1,
+            chunk_start_ms: 0,
+            app_ref: 1,
+            events: &first,
+        }];
+        let holds = pair_key_holds(&mut windowed, 100, i64::MAX);
+        assert!(!holds.durations.contains_key("a"));
+        assert_eq!(holds.durations["c"], vec![40]);
+    }
+
+    #[test]
+    fn destructive_preview_is_read_only_and_reset_today_keeps_earlier_days() {
+        use super::shortcut::{
+            append_input_event, local_day_window_ms, update_shortcut_usage, InputEventChunk,
+        };
+        use super::{preview_destructive_action, DestructiveAction};
+        use crate::storage::StoredFile;
+
+        // Memory storage that lists whichever of its files still exist on disk.
+        struct FileListStorage {
+            files: Vec<StoredFile>,
+        }
+
+        impl DetailStorage for FileListStorage {
+            fn load_stats(&self) -> Result<HashMap<StatsKey, StatsValue>, String> {
+                Ok(HashMap::new())
+            }
+
+            fn save_stats(&self, _stats: &HashMap<StatsKey, StatsValue>) -> Result<(), String> {
+                Ok(())
+            }
+
+            fn is_rolled_up(&self, _day: NaiveDate) -> bool {
+                false
+            }
+
+            fn mark_rolled_up(&self, _days: &[NaiveDate]) {}
+
+            fn load_input_analytics(&self) -> Result<StoredInputAnalytics, String> {
+                Ok(StoredInputAnalytics::default())
+            }
+
+            fn save_input_analytics(
+                &self,
+                _analytics: &StoredInputAnalytics,
+            ) -> Result<(), String> {
+                Ok(())
+            }
+
+            fn stored_files(&self) -> Vec<StoredFile> {
+                self.files
+                    .iter()
+                    .filter(|file| file.path.exists())
+                    .cloned()
+                    .collect()
+            }
+        }
+
+        let dir = temp_dir("destructive");
+        let today = chrono::Local::now().date_naive();
+        let yesterday = today.pred_opt().unwrap();
+        let file = |day: NaiveDate, name: &str, bytes: usize| {
+            let path = dir.join(format!("{}-{name}", day.format("%Y-%m-%d")));
+            std::fs::write(&path, vec![b'x'; bytes]).unwrap();
+            StoredFile {
+                path,
+                day: Some(day),
+                bytes: bytes as u64,
+            }
+        };
+        let files = vec![
+            file(today, "details.json", 100),
+            file(today, "analytics-details.json", 40),
+            file(yesterday, "details.json", 70),
+        ];
+        let key = |day: NaiveDate, minute: &str| StatsKey {
+            date: format!("{} {minute}", day.format("%Y-%m-%d"))
+                .parse()
+                .unwrap(),
+            app_name: "com.test.editor".to_string(),
+            window_title: "Doc".to_string(),
+        };
+        let value = StatsValue {
+            active_typing_ms: 500,
+            key_count: 5,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        };
+        let mut state = build_state(HashMap::from([
+            (key(today, "00:00"), value.clone()),
+            (key(today, "00:01"), value.clone()),
+            (key(yesterday, "10:00"), value.clone()),
+        ]));
+        state.storage = Arc::new(FileListStorage {
+            files: files.clone(),
         });
+        state.log_path = dir.join("log.csv");
+        state.app_log_path = dir.join("app.log");
 
-        let rows = super::snapshot_top_keys_by_range(&state, "today");
-        assert_eq!(rows.len(), 2);
-        assert_eq!(rows[0].key, "a");
-        assert_eq!(rows[0].count, 2);
-        assert_eq!(rows[1].key, "tab");
-        assert_eq!(rows[1].count, 1);
+        let cmd = ModifierSnapshot {
+            cmd: true,
+            ..ModifierSnapshot::default()
+        };
+        let context = state.current_context();
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        append_input_event(
+            &mut state,
+            &context,
+            'd',
+            "c",
+            cmd,
+            event_time_at_ms(now_ms),
+        );
+        update_shortcut_usage(&mut state, &context, "c", cmd, now_ms);
+        update_shortcut_usage(&mut state, &context, "c", cmd, now_ms);
+        let app_ref = state.open_event_chunk.as_ref().unwrap().app_ref;
+        state.event_chunks.push(InputEventChunk {
+            v: 1,
+            chunk_start_ms: local_day_window_ms(yesterday).unwrap().0 + 60_000,
+            app_ref,
+            events: vec![format!("0,d,c,{}", cmd.bitmask())],
+        });
+
+        let preview = preview_destructive_action(&state, DestructiveAction::ResetToday);
+        assert_eq!(preview.action, "reset_today");
+        assert_eq!(
+            (
+                preview.stats_rows,
+                preview.event_chunks,
+                preview.shortcut_entries
+            ),
+            (2, 1, 1)
+        );
+        assert_eq!((preview.files, preview.file_bytes), (2, 140));
+        assert!(preview.confirm_token.is_empty());
+        // Previewing changes nothing.
+        assert_eq!(state.stats.len(), 3);
+        assert!(state.open_event_chunk.is_some());
+        assert!(files.iter().all(|file| file.path.exists()));
+
+        state
+            .run_destructive_action(DestructiveAction::ResetToday)
+            .unwrap();
+        assert_eq!(state.stats.len(), 1);
+        assert!(state.stats.contains_key(&key(yesterday, "10:00")));
+        assert!(state.open_event_chunk.is_none());
+        assert_eq!(state.event_chunks.len(), 1);
+        assert_eq!(state.shortcut_usage["cmd_c"].count, 1);
+        assert!(!files[0].path.exists() && !files[1].path.exists());
+        assert!(files[2].path.exists());
+
+        let preview = preview_destructive_action(&state, DestructiveAction::ResetAll);
+        assert_eq!(
+            (
+                preview.stats_rows,
+                preview.event_chunks,
+                preview.shortcut_entries,
+                preview.files,
+                preview.file_bytes
+            ),
+            (1, 1, 1, 1, 70)
+        );
+        state
+            .run_destructive_action(DestructiveAction::ResetAll)
+            .unwrap();
+        assert!(state.stats.is_empty() && state.event_chunks.is_empty());
+        assert!(state.shortcut_usage.is_empty());
+        assert!(!files[2].path.exists());
+    }
+
+    #[test]
+    fn log_batcher_folds_consecutive_repeats_per_file() {
+        let first = PathBuf::from("first.log");
+        let second = PathBuf::from("second.log");
+        let mut batcher = LogBatcher::default();
+        batcher.push(&first, "10:00:00", "listener retry");
+        batcher.push(&first, "10:00:01", "listener retry");
+        batcher.push(&second, "10:00:01", "flush failed");
+        batcher.push(&first, "10:00:02", "listener retry");
+        batcher.push(&first, "10:00:03", "listener started");
+        batcher.push(&second, "10:00:04", "flush failed");
+        assert_eq!(batcher.pending_lines(), 4);
+
+        let lines = batcher.take();
+        let lines_of = |path: &PathBuf| -> Vec<String> {
+            lines
+                .iter()
+                .filter(|(line_path, _)| line_path == path)
+                .map(|(_, line)| line.clone())
+                .collect()
+        };
+        assert_eq!(
+            lines_of(&first),
+            vec![
+                "10:00:00 listener retry",
+                "10:00:02 last message repeated 2 times",
+                "10:00:03 listener started",
+            ]
+        );
+        assert_eq!(
+            lines_of(&second),
+            vec![
+                "10:00:01 flush failed",
+                "10:00:04 last message repeated 1 times",
+            ]
+        );
+
+        // After a write the count starts over, without repeating the message itself.
+        batcher.push(&first, "10:00:05", "listener started");
+        assert_eq!(batcher.pending_lines(), 0);
+        assert_eq!(
+            batcher.take(),
+            vec![(
+                first.clone(),
+                "10:00:05 last message repeated 1 times".to_string()
+            )]
+        );
+        assert!(batcher.take().is_empty());
+    }
+
+    #[test]
+    fn flush_logs_writes_every_queued_app_log_line() {
+        use super::flush_app_log;
+
+        let dir = temp_dir("app-log-flush");
+        let path = dir.join("app.log");
+        for index in 0..150 {
+            append_app_log(&path, &format!("line {}", index)).unwrap();
+        }
+        for _ in 0..5 {
+            append_app_log(&path, "storage flush failed").unwrap();
+        }
+        append_app_log(&path, "done").unwrap();
+        flush_logs();
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        let messages: Vec<&str> = log
+            .lines()
+            .map(|line| line.splitn(3, ' ').nth(2).unwrap_or_default())
+            .collect();
+        let mut expected: Vec<String> = (0..150).map(|index| format!("line {}", index)).collect();
+        expected.push("storage flush failed".to_string());
+        expected.push("last message repeated 4 times".to_string());
+        expected.push("done".to_string());
+        assert_eq!(messages, expected);
+        assert_eq!(flush_app_log(&path), Ok(()));
+
+        // A line that cannot reach its file is reported by the flush, not by the append.
+        let missing = dir.join("missing").join("app.log");
+        append_app_log(&missing, "raw events exported").unwrap();
+        assert!(flush_app_log(&missing).is_err());
+        assert_eq!(flush_app_log(&path), Ok(()));
     }
 }
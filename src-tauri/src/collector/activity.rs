@@ -0,0 +1,37 @@
+//! Activity class module.
+//! Splits active typing time into writing (text entry) and navigation (arrows, page keys and
+//! counted shortcuts) by the class of the most recent key-down.
+
+use super::StatsValue;
+
+/// Keys that move the caret or the view instead of entering text.
+const NAVIGATION_KEYS: [&str; 8] = [
+    "up", "down", "left", "right", "pageup", "pagedown", "home", "end",
+];
+
+/// Class of a key-down; held time after it accrues to the matching `StatsValue` field.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(super) enum ActivityClass {
+    #[default]
+    Writing,
+    Navigation,
+}
+
+/// Classify one key-down. Counted shortcuts and navigation keys are navigation; everything
+/// else, editing keys such as space, enter and backspace included, is writing.
+pub(super) fn classify_key_down(key: &str, counted_shortcut: bool) -> ActivityClass {
+    if counted_shortcut || NAVIGATION_KEYS.contains(&key) {
+        ActivityClass::Navigation
+    } else {
+        ActivityClass::Writing
+    }
+}
+
+/// Add held time to `value`, both to the total and to the split field of `class`.
+pub(super) fn accrue_active_ms(value: &mut StatsValue, class: ActivityClass, elapsed_ms: u64) {
+    value.active_typing_ms += elapsed_ms;
+    match class {
+        ActivityClass::Writing => value.writing_ms += elapsed_ms,
+        ActivityClass::Navigation => value.navigation_ms += elapsed_ms,
+    }
+}
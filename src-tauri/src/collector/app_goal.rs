@@ -0,0 +1,130 @@
+//! App time goal module.
+//! Measures per-app daily active typing targets against the cached today totals and tracks
+//! which met targets were already announced today.
+
+use std::collections::HashMap;
+
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+
+use super::{CollectorState, TodayTotals, DAY_KEY_FORMAT};
+
+const MS_PER_MINUTE: u64 = 60_000;
+
+/// Today's progress toward one app's active typing target.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AppGoalProgress {
+    pub bundle_id: String,
+    pub target_min: u64,
+    pub actual_min: u64,
+    /// Share of the target reached in percent; exceeds 100 once the target is passed.
+    pub pct: f64,
+}
+
+impl AppGoalProgress {
+    fn is_met(&self) -> bool {
+        self.pct >= 100.0
+    }
+}
+
+// Progress of every goal from the day totals, sorted by bundle id. Targets are at least one
+// minute; a zero target is skipped rather than reported as met.
+pub(super) fn goal_progress(
+    goals: &HashMap<String, u64>,
+    totals: &TodayTotals,
+) -> Vec<AppGoalProgress> {
+    let mut progress: Vec<AppGoalProgress> = goals
+        .iter()
+        .filter(|(_, target_min)| **target_min > 0)
+        .map(|(bundle_id, target_min)| {
+            let actual_ms = totals
+                .app_active_typing_ms
+                .get(bundle_id)
+                .copied()
+                .unwrap_or(0);
+            AppGoalProgress {
+                bundle_id: bundle_id.clone(),
+                target_min: *target_min,
+                actual_min: actual_ms / MS_PER_MINUTE,
+                pct: actual_ms as f64 * 100.0 / (target_min * MS_PER_MINUTE) as f64,
+            }
+        })
+        .collect();
+    progress.sort_by(|a, b| a.bundle_id.cmp(&b.bundle_id));
+    progress
+}
+
+impl CollectorState {
+    /// Set the daily active typing target (minutes) for one app.
+    pub fn set_app_time_goal(&mut self, bundle_id: &str, target_min: u64) {
+        let bundle_id = bundle_id.trim().to_ascii_lowercase();
+        if bundle_id.is_empty() || target_min == 0 {
+            return;
+        }
+        self.app_time_goals.insert(bundle_id, target_min);
+    }
+
+    /// Drop one app's target together with its announcement flag.
+    pub fn remove_app_time_goal(&mut self, bundle_id: &str) {
+        let bundle_id = bundle_id.trim().to_ascii_lowercase();
+        self.app_time_goals.remove(&bundle_id);
+        self.app_goal_notified.remove(&bundle_id);
+    }
+
+    pub fn set_app_time_goal_notifications(&mut self, enabled: bool) {
+        self.app_time_goal_notifications = enabled;
+    }
+
+    /// Goal map in config form (lowercase bundle id -> minutes).
+    pub(crate) fn app_time_goals(&self) -> HashMap<String, u64> {
+        self.app_time_goals.clone()
+    }
+
+    /// Announcement flags in config form (lowercase bundle id -> `YYYY-MM-DD`).
+    pub(crate) fn app_goal_notified(&self) -> HashMap<String, String> {
+        self.app_goal_notified
+            .iter()
+            .map(|(bundle_id, day)| {
+                let day = day.format(DAY_KEY_FORMAT).to_string();
+                (bundle_id.clone(), day)
+            })
+            .collect()
+    }
+
+    /// Goals met today that have not been announced yet, marked as announced for today.
+    /// Returns nothing while goal notifications are turned off.
+    pub(crate) fn take_newly_met_app_goals(&mut self) -> Vec<AppGoalProgress> {
+        if !self.app_time_goal_notifications || self.app_time_goals.is_empty() {
+            return vec![];
+        }
+        let today = Local::now().date_naive();
+        let newly_met: Vec<AppGoalProgress> = self
+            .today_summary()
+            .goal_progress
+            .into_iter()
+            .filter(|progress| {
+                let announced = self.app_goal_notified.get(&progress.bundle_id) == Some(&today);
+                progress.is_met() && !announced
+            })
+            .collect();
+        for progress in &newly_met {
+            self.app_goal_notified
+                .insert(progress.bundle_id.clone(), today);
+        }
+        newly_met
+    }
+}
+
+// Parse persisted announcement flags, dropping entries with an unreadable date.
+pub(super) fn parse_goal_notified(
+    notified: &HashMap<String, String>,
+) -> HashMap<String, NaiveDate> {
+    notified
+        .iter()
+        .filter_map(|(bundle_id, day)| {
+            NaiveDate::parse_from_str(day, DAY_KEY_FORMAT)
+                .ok()
+                .map(|day| (bundle_id.to_ascii_lowercase(), day))
+        })
+        .collect()
+}
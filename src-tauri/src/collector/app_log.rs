@@ -0,0 +1,276 @@
+//! Buffered app log module.
+//! `append_app_log` only queues the line; a writer thread appends batches to the log files,
+//! at least once a second or every 64 lines, and folds identical consecutive messages into a
+//! "last message repeated N times" line so hot paths cannot flood the log.
+//! Failed batch writes go to stderr and are kept per file for `flush_app_log`.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+const LOG_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+const LOG_FLUSH_LINES: usize = 64;
+// Upper bound on waiting for the writer in `flush_logs`, so a stuck disk cannot hang exit.
+const LOG_FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Set for demo mode: lines go to stdout and no log file is created or appended.
+static STDOUT_ONLY: AtomicBool = AtomicBool::new(false);
+// Error of the latest batch written to each file, cleared by the next successful batch.
+static WRITE_ERRORS: OnceLock<Mutex<HashMap<PathBuf, String>>> = OnceLock::new();
+
+enum LogCommand {
+    Line {
+        path: PathBuf,
+        stamp: String,
+        message: String,
+    },
+    Flush(Sender<()>),
+}
+
+// Last message written to one file and how often it repeated since.
+struct LastMessage {
+    message: String,
+    repeats: u64,
+    last_stamp: String,
+}
+
+/// Pending log lines per file, with consecutive duplicates folded into a repeat count.
+#[derive(Default)]
+pub(super) struct LogBatcher {
+    pending: Vec<(PathBuf, String)>,
+    last: HashMap<PathBuf, LastMessage>,
+}
+
+impl LogBatcher {
+    /// Queue `message` stamped `stamp`; a repeat of the file's previous message only counts.
+    pub(super) fn push(&mut self, path: &Path, stamp: &str, message: &str) {
+        if let Some(last) = self.last.get_mut(path) {
+            if last.message == message {
+                last.repeats += 1;
+                last.last_stamp = stamp.to_string();
+                return;
+            }
+        }
+        self.close_repeats(path);
+        self.pending
+            .push((path.to_path_buf(), format!("{} {}", stamp, message)));
+        self.last.insert(
+            path.to_path_buf(),
+            LastMessage {
+                message: message.to_string(),
+                repeats: 0,
+                last_stamp: String::new(),
+            },
+        );
+    }
+
+    fn close_repeats(&mut self, path: &Path) {
+        let Some(last) = self.last.get_mut(path) else {
+            return;
+        };
+        if last.repeats == 0 {
+            return;
+        }
+        self.pending.push((
+            path.to_path_buf(),
+            format!(
+                "{} last message repeated {} times",
+                last.last_stamp, last.repeats
+            ),
+        ));
+        last.repeats = 0;
+    }
+
+    /// Lines waiting to be written, repeat counts not included.
+    pub(super) fn pending_lines(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Everything due for writing, open repeat counts closed, in arrival order per file.
+    /// Repeats of the same message after this are counted again from zero.
+    pub(super) fn take(&mut self) -> Vec<(PathBuf, String)> {
+        let paths: Vec<PathBuf> = self.last.keys().cloned().collect();
+        for path in paths {
+            self.close_repeats(&path);
+        }
+        std::mem::take(&mut self.pending)
+    }
+}
+
+fn write_lines(lines: Vec<(PathBuf, String)>) {
+    let mut by_path: Vec<(PathBuf, Vec<String>)> = Vec::new();
+    for (path, line) in lines {
+        match by_path.iter_mut().find(|(existing, _)| *existing == path) {
+            Some((_, batch)) => batch.push(line),
+            None => by_path.push((path, vec![line])),
+        }
+    }
+    for (path, batch) in by_path {
+        let result = write_batch(&path, &batch);
+        if let Err(err) = &result {
+            eprintln!(
+                "[TypePulse] failed to write app log {}: {}",
+                path.display(),
+                err
+            );
+        }
+        if let Ok(mut errors) = WRITE_ERRORS.get_or_init(Default::default).lock() {
+            match result {
+                Ok(()) => errors.remove(&path),
+                Err(err) => errors.insert(path, err),
+            };
+        }
+    }
+}
+
+fn write_batch(path: &Path, lines: &[String]) -> Result<(), String> {
+    if STDOUT_ONLY.load(Ordering::Relaxed) {
+        for line in lines {
+            println!("[TypePulse] {}", line);
+        }
+        return Ok(());
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    let mut text = String::new();
+    for line in lines {
+        text.push_str(line);
+        text.push('\n');
+        print_line_if_dev(line);
+    }
+    file.write_all(text.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn print_line_if_dev(line: &str) {
+    if cfg!(debug_assertions) {
+        println!("[TypePulse] {}", line);
+    }
+}
+
+fn run_writer(commands: Receiver<LogCommand>) {
+    let mut batcher = LogBatcher::default();
+    let mut first_pending: Option<Instant> = None;
+    loop {
+        let command = match first_pending {
+            Some(since) => {
+                commands.recv_timeout(LOG_FLUSH_INTERVAL.saturating_sub(since.elapsed()))
+            }
+            None => commands.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match command {
+            Ok(LogCommand::Line {
+                path,
+                stamp,
+                message,
+            }) => {
+                batcher.push(&path, &stamp, &message);
+                first_pending.get_or_insert_with(Instant::now);
+                if batcher.pending_lines() >= LOG_FLUSH_LINES {
+                    write_lines(batcher.take());
+                    first_pending = None;
+                }
+            }
+            Ok(LogCommand::Flush(done)) => {
+                write_lines(batcher.take());
+                first_pending = None;
+                let _ = done.send(());
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                write_lines(batcher.take());
+                first_pending = None;
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                write_lines(batcher.take());
+                return;
+            }
+        }
+    }
+}
+
+// Queue of the writer thread, started on first use. None when the thread could not start.
+fn writer() -> Option<&'static Mutex<Sender<LogCommand>>> {
+    static WRITER: OnceLock<Option<Mutex<Sender<LogCommand>>>> = OnceLock::new();
+    WRITER
+        .get_or_init(|| {
+            let (sender, receiver) = mpsc::channel();
+            std::thread::Builder::new()
+                .name("app-log-writer".to_string())
+                .spawn(move || run_writer(receiver))
+                .ok()
+                .map(|_| Mutex::new(sender))
+        })
+        .as_ref()
+}
+
+/// Append one line into runtime app log file. The line is stamped now and written by the
+/// log writer thread; it is written directly when that thread is unavailable. `Ok` only means
+/// the line was queued; `flush_app_log` tells whether it reached the file.
+pub fn append_app_log(path: &PathBuf, message: &str) -> Result<(), String> {
+    let stamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let command = LogCommand::Line {
+        path: PathBuf::from(path),
+        stamp,
+        message: message.to_string(),
+    };
+    let unsent = match writer().map(|sender| sender.lock()) {
+        Some(Ok(sender)) => sender.send(command).err().map(|err| err.0),
+        _ => Some(command),
+    };
+    match unsent {
+        Some(LogCommand::Line {
+            path,
+            stamp,
+            message,
+        }) => write_batch(&path, &[format!("{} {}", stamp, message)]),
+        _ => Ok(()),
+    }
+}
+
+/// Print app log lines to stdout from now on instead of writing them to the log files.
+pub fn log_to_stdout_only() {
+    STDOUT_ONLY.store(true, Ordering::Relaxed);
+}
+
+/// Write every queued app log line before returning, e.g. before exit or reading the log.
+pub fn flush_logs() {
+    let _ = flush_writer();
+}
+
+/// `flush_logs`, then report whether the lines queued for `path` reached the file, for log
+/// entries that must not be lost silently such as audit lines.
+pub fn flush_app_log(path: &Path) -> Result<(), String> {
+    if !flush_writer() {
+        return Err("app log writer did not confirm the flush".to_string());
+    }
+    let errors = WRITE_ERRORS
+        .get_or_init(Default::default)
+        .lock()
+        .map_err(|_| "app log error lock failed".to_string())?;
+    match errors.get(path) {
+        Some(err) => Err(format!("failed to write app log: {}", err)),
+        None => Ok(()),
+    }
+}
+
+// Whether the writer wrote everything queued so far; true when there is no writer, since
+// lines are then written directly by `append_app_log`.
+fn flush_writer() -> bool {
+    let Some(sender) = writer() else {
+        return true;
+    };
+    let (done, flushed) = mpsc::channel();
+    let sent = sender
+        .lock()
+        .map(|sender| sender.send(LogCommand::Flush(done)).is_ok())
+        .unwrap_or(false);
+    sent && flushed.recv_timeout(LOG_FLUSH_TIMEOUT).is_ok()
+}
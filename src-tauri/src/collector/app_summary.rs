@@ -0,0 +1,123 @@
+//! App summary module.
+//! Per-app totals over a range. Apps below the `min_keys_to_display` threshold are folded into
+//! one "Other" row at query time, so stray keypresses in rarely used apps do not clutter the
+//! list; the stats rows themselves are never changed.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::shortcut::{days_in_window, shortcut_range_window_ms};
+use super::{CollectorState, StatsValue, DAY_KEY_FORMAT};
+
+// App name of the row that apps below the threshold are folded into.
+const OTHER_APP_NAME: &str = "Other";
+
+/// Totals of one app, or of the folded apps, over the range.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct AppSummaryRow {
+    pub app_name: String,
+    pub key_count: u64,
+    pub active_typing_ms: u64,
+    pub session_count: u64,
+    pub writing_ms: u64,
+    pub navigation_ms: u64,
+}
+
+impl AppSummaryRow {
+    fn add(&mut self, value: &StatsValue) {
+        self.key_count += value.key_count;
+        self.active_typing_ms += value.active_typing_ms;
+        self.session_count += value.session_count;
+        self.writing_ms += value.writing_ms;
+        self.navigation_ms += value.navigation_ms;
+    }
+
+    fn add_row(&mut self, row: &AppSummaryRow) {
+        self.key_count += row.key_count;
+        self.active_typing_ms += row.active_typing_ms;
+        self.session_count += row.session_count;
+        self.writing_ms += row.writing_ms;
+        self.navigation_ms += row.navigation_ms;
+    }
+}
+
+/// Per-app totals for a range, most keys first. With the other bucket included, `apps` plus
+/// `other` add up to the unfiltered totals exactly.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AppSummary {
+    pub range: String,
+    pub min_keys_to_display: u64,
+    pub apps: Vec<AppSummaryRow>,
+    /// Apps with fewer than `min_keys_to_display` keys in the range, summed under
+    /// `OTHER_APP_NAME`. None when no app was folded or the bucket was not requested.
+    pub other: Option<AppSummaryRow>,
+    /// Number of apps below the threshold, reported even when `other` is left out.
+    pub other_app_count: u64,
+}
+
+// Split per-app totals into the apps shown and the "Other" bucket of apps with fewer than
+// `min_keys` keys. A threshold of 0 shows every app.
+fn fold_small_apps(
+    totals: HashMap<String, AppSummaryRow>,
+    min_keys: u64,
+) -> (Vec<AppSummaryRow>, Option<AppSummaryRow>, u64) {
+    let mut apps = Vec::with_capacity(totals.len());
+    let mut other: Option<AppSummaryRow> = None;
+    let mut other_app_count = 0;
+    for row in totals.into_values() {
+        if row.key_count >= min_keys {
+            apps.push(row);
+            continue;
+        }
+        other_app_count += 1;
+        other
+            .get_or_insert_with(|| AppSummaryRow {
+                app_name: OTHER_APP_NAME.to_string(),
+                ..AppSummaryRow::default()
+            })
+            .add_row(&row);
+    }
+    apps.sort_by(|a, b| {
+        b.key_count
+            .cmp(&a.key_count)
+            .then_with(|| a.app_name.cmp(&b.app_name))
+    });
+    (apps, other, other_app_count)
+}
+
+/// Build per-app totals by range: `today` / `yesterday` / `7d`.
+pub fn snapshot_app_summary(
+    state: &CollectorState,
+    range: &str,
+    include_other_bucket: bool,
+) -> AppSummary {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let days: Vec<NaiveDate> = days_in_window(start_ms, end_ms)
+        .iter()
+        .filter_map(|day| NaiveDate::parse_from_str(day, DAY_KEY_FORMAT).ok())
+        .collect();
+    let mut totals: HashMap<String, AppSummaryRow> = HashMap::new();
+    for (key, value) in &state.stats {
+        if !days.contains(&key.date.day()) {
+            continue;
+        }
+        totals
+            .entry(key.app_name.clone())
+            .or_insert_with(|| AppSummaryRow {
+                app_name: key.app_name.clone(),
+                ..AppSummaryRow::default()
+            })
+            .add(value);
+    }
+    let (apps, other, other_app_count) = fold_small_apps(totals, state.min_keys_to_display);
+    AppSummary {
+        range: range.to_string(),
+        min_keys_to_display: state.min_keys_to_display,
+        apps,
+        other: other.filter(|_| include_other_bucket),
+        other_app_count,
+    }
+}
@@ -0,0 +1,199 @@
+//! App switch module.
+//! Counts frontmost app changes made while actively typing, per local day and per
+//! app→app transition.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use chrono::Local;
+use serde::Serialize;
+
+use crate::storage::{StoredAppSwitchDay, StoredAppSwitchPair};
+
+use super::shortcut::{days_in_window, shortcut_range_window_ms};
+use super::{CaptureContext, CollectorState};
+
+/// Distinct transitions kept per day; a new pair beyond this evicts the least frequent one.
+pub(super) const MAX_SWITCH_PAIRS_PER_DAY: usize = 200;
+/// Transitions returned by `snapshot_app_switch_stats`.
+const TOP_SWITCH_PAIRS: usize = 10;
+
+/// Runtime app switch counters of one local day.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct AppSwitchDay {
+    pub(super) total: u64,
+    pub(super) pairs: HashMap<(String, String), u64>,
+}
+
+impl AppSwitchDay {
+    pub(super) fn record(&mut self, from: String, to: String) {
+        self.total = self.total.saturating_add(1);
+        let pair = (from, to);
+        if let Some(count) = self.pairs.get_mut(&pair) {
+            *count = count.saturating_add(1);
+            return;
+        }
+        if self.pairs.len() >= MAX_SWITCH_PAIRS_PER_DAY {
+            self.evict_least_frequent();
+        }
+        self.pairs.insert(pair, 1);
+    }
+
+    // Ties evict the lexicographically largest pair so eviction does not depend on hash order.
+    fn evict_least_frequent(&mut self) {
+        let victim = self
+            .pairs
+            .iter()
+            .min_by(|(pair_a, count_a), (pair_b, count_b)| {
+                count_a.cmp(count_b).then_with(|| pair_b.cmp(pair_a))
+            })
+            .map(|(pair, _)| pair.clone());
+        if let Some(victim) = victim {
+            self.pairs.remove(&victim);
+        }
+    }
+
+    pub(super) fn to_stored(&self) -> StoredAppSwitchDay {
+        let mut pairs: Vec<StoredAppSwitchPair> = self
+            .pairs
+            .iter()
+            .map(|((from, to), count)| StoredAppSwitchPair {
+                from: from.clone(),
+                to: to.clone(),
+                count: *count,
+            })
+            .collect();
+        pairs.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.from.cmp(&b.from))
+                .then_with(|| a.to.cmp(&b.to))
+        });
+        StoredAppSwitchDay {
+            total: self.total,
+            pairs,
+        }
+    }
+
+    /// Add another record of the same day, keeping the pair cap.
+    pub(super) fn merge(&mut self, other: AppSwitchDay) {
+        self.total = self.total.saturating_add(other.total);
+        for (pair, count) in other.pairs {
+            let entry = self.pairs.entry(pair).or_insert(0);
+            *entry = entry.saturating_add(count);
+        }
+        while self.pairs.len() > MAX_SWITCH_PAIRS_PER_DAY {
+            self.evict_least_frequent();
+        }
+    }
+
+    pub(super) fn from_stored(stored: StoredAppSwitchDay) -> Self {
+        let mut day = Self {
+            total: stored.total,
+            pairs: HashMap::new(),
+        };
+        for pair in stored.pairs {
+            let count = day.pairs.entry((pair.from, pair.to)).or_insert(0);
+            *count = count.saturating_add(pair.count);
+        }
+        while day.pairs.len() > MAX_SWITCH_PAIRS_PER_DAY {
+            day.evict_least_frequent();
+        }
+        day
+    }
+}
+
+/// One app→app transition and how often it happened in the requested range.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct AppSwitchPairRow {
+    pub from: String,
+    pub to: String,
+    pub count: u64,
+}
+
+/// App switch total for a range with its most common transitions first.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct AppSwitchStats {
+    pub range: String,
+    pub total: u64,
+    pub top_pairs: Vec<AppSwitchPairRow>,
+}
+
+// Track the frontmost app seen by tick and key-down events and count a change as a switch.
+// The app is tracked even while paused so leaving an excluded app is attributed correctly,
+// but only switches made within the session gap of the last key-down are counted. Returns
+// whether the frontmost app changed, counted or not.
+pub(super) fn record_app_switch(
+    state: &mut CollectorState,
+    capture_context: &CaptureContext,
+    at: Instant,
+) -> bool {
+    let app_id = capture_context
+        .bundle_id
+        .clone()
+        .unwrap_or_else(|| capture_context.app_name.clone());
+    if app_id.is_empty() {
+        return false;
+    }
+    let previous = state.last_foreground_app.replace(app_id.clone());
+    let Some(previous) = previous.filter(|previous| *previous != app_id) else {
+        return false;
+    };
+    if state.paused
+        || state.auto_paused
+        || at.saturating_duration_since(state.last_typing_instant) > state.session_gap
+    {
+        return true;
+    }
+    let day = Local::now().format("%Y-%m-%d").to_string();
+    state
+        .app_switches
+        .entry(day)
+        .or_default()
+        .record(previous, app_id);
+    true
+}
+
+// Sum per-day counters over the given local days and keep the most common transitions.
+pub(super) fn build_app_switch_stats(
+    app_switches: &HashMap<String, AppSwitchDay>,
+    range: &str,
+    days: &[String],
+) -> AppSwitchStats {
+    let mut total = 0u64;
+    let mut pairs: HashMap<&(String, String), u64> = HashMap::new();
+    for day in days.iter().filter_map(|day| app_switches.get(day)) {
+        total = total.saturating_add(day.total);
+        for (pair, count) in &day.pairs {
+            *pairs.entry(pair).or_insert(0) += count;
+        }
+    }
+    let mut top_pairs: Vec<AppSwitchPairRow> = pairs
+        .into_iter()
+        .map(|((from, to), count)| AppSwitchPairRow {
+            from: from.clone(),
+            to: to.clone(),
+            count,
+        })
+        .collect();
+    top_pairs.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.from.cmp(&b.from))
+            .then_with(|| a.to.cmp(&b.to))
+    });
+    top_pairs.truncate(TOP_SWITCH_PAIRS);
+    AppSwitchStats {
+        range: range.to_string(),
+        total,
+        top_pairs,
+    }
+}
+
+/// Build app switch stats by range: `today` / `yesterday` / `7d`.
+pub fn snapshot_app_switch_stats(state: &CollectorState, range: &str) -> AppSwitchStats {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let days = days_in_window(start_ms, end_ms);
+    build_app_switch_stats(&state.app_switches, range, &days)
+}
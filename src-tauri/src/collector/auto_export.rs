@@ -0,0 +1,238 @@
+//! Auto export module.
+//! Writes each finished local day to the configured directory after midnight and hands the
+//! file to an optional user script.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use chrono::{Duration as ChronoDuration, NaiveDate};
+
+use crate::app_config::{AutoExportConfig, AutoExportFormat};
+use crate::storage::{save_auto_export_state, StoredAutoExportState};
+
+use super::export_chain::sign_text_export;
+use super::io::{write_csv, CsvDialect};
+use super::{append_app_log, CollectorState, StatsRow, DAY_KEY_FORMAT};
+
+/// Marker file next to the detail files recording the last exported day.
+pub(super) const AUTO_EXPORT_STATE_FILE: &str = "auto-export-state.json";
+// Pause after a failed export so an unavailable directory is not retried on every tick.
+const AUTO_EXPORT_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+// Missed days older than this many days are skipped instead of exported after a long gap.
+const AUTO_EXPORT_MAX_CATCH_UP_DAYS: i64 = 31;
+
+/// Export file name of `day`: `typepulse-YYYY-MM-DD.<format>`.
+pub(super) fn export_file_name(day: NaiveDate, format: AutoExportFormat) -> String {
+    format!(
+        "typepulse-{}.{}",
+        day.format(DAY_KEY_FORMAT),
+        format.as_str()
+    )
+}
+
+// Write rows in `format` through a temp file so a watcher never sees a partial export. With a
+// chain path the file is signed before it is moved into place.
+fn write_day_export(
+    path: &Path,
+    rows: &[StatsRow],
+    format: AutoExportFormat,
+    dialect: CsvDialect,
+    sign_chain: Option<&Path>,
+) -> Result<(), String> {
+    let tmp_path = path.with_extension(format!("{}.tmp", format.as_str()));
+    match format {
+        AutoExportFormat::Csv => write_csv(&tmp_path, rows, dialect)?,
+        AutoExportFormat::Json => {
+            let bytes = serde_json::to_vec_pretty(rows).map_err(|e| e.to_string())?;
+            std::fs::write(&tmp_path, bytes).map_err(|e| e.to_string())?;
+        }
+    }
+    if let Some(chain_path) = sign_chain {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        sign_text_export(&tmp_path, format, chain_path, &file_name)?;
+    }
+    std::fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+}
+
+/// Finished days after `last_exported` up to yesterday, oldest first, limited to the catch-up
+/// window. The second value counts missed days dropped by that limit.
+pub(super) fn pending_export_days(
+    last_exported: NaiveDate,
+    today: NaiveDate,
+) -> (Vec<NaiveDate>, i64) {
+    let first = last_exported + ChronoDuration::days(1);
+    let oldest_allowed = today - ChronoDuration::days(AUTO_EXPORT_MAX_CATCH_UP_DAYS);
+    let start = first.max(oldest_allowed);
+    let skipped = (start - first).num_days().max(0);
+    let days = start.iter_days().take_while(|day| *day < today).collect();
+    (days, skipped)
+}
+
+/// Run the export script with the exported file as its only argument and return the app log
+/// lines describing the run: captured output, then the outcome. The script must still be a
+/// regular file; it was checked when configured but may have been replaced since.
+pub(super) fn run_export_script(script_path: &Path, export_path: &Path) -> Vec<String> {
+    if !std::fs::metadata(script_path).is_ok_and(|metadata| metadata.is_file()) {
+        return vec![format!(
+            "auto export script {} is not a regular file, not run",
+            script_path.display()
+        )];
+    }
+    let output = match Command::new(script_path).arg(export_path).output() {
+        Ok(output) => output,
+        Err(err) => {
+            return vec![format!(
+                "auto export script {} failed to start: {}",
+                script_path.display(),
+                err
+            )];
+        }
+    };
+    let mut lines = Vec::new();
+    for (stream, bytes) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+        let text = String::from_utf8_lossy(bytes);
+        let text = text.trim();
+        if !text.is_empty() {
+            lines.push(format!("auto export script {}: {}", stream, text));
+        }
+    }
+    lines.push(if output.status.success() {
+        format!("auto export script finished for {}", export_path.display())
+    } else {
+        format!(
+            "auto export script error: {} exited with {}",
+            script_path.display(),
+            output.status
+        )
+    });
+    lines
+}
+
+// Scripts may run for a while; run them off the tick loop and log when they finish.
+fn spawn_export_script(script_path: PathBuf, export_path: PathBuf, app_log_path: PathBuf) {
+    std::thread::spawn(move || {
+        for line in run_export_script(&script_path, &export_path) {
+            let _ = append_app_log(&app_log_path, &line);
+        }
+    });
+}
+
+impl CollectorState {
+    /// Replace the auto-export settings. Turning exports on starts from the current day instead
+    /// of back-filling the days it was off.
+    pub(crate) fn set_auto_export(&mut self, config: AutoExportConfig) {
+        if config.enabled && !self.auto_export.enabled {
+            self.last_auto_export_day = None;
+        }
+        self.auto_export = config;
+        self.auto_export_retry_at = None;
+    }
+
+    // Persist the marker; a failed write is logged and retried with the next exported day.
+    fn save_auto_export_marker(&self) {
+        let marker = StoredAutoExportState {
+            last_exported_day: self
+                .last_auto_export_day
+                .map(|day| day.format(DAY_KEY_FORMAT).to_string()),
+        };
+        if let Err(err) = save_auto_export_state(&self.auto_export_state_path, &marker) {
+            let _ = append_app_log(
+                &self.app_log_path,
+                &format!("auto export marker save failed: {}", err),
+            );
+        }
+    }
+
+    // Write one day's rows into the destination directory and return the file path.
+    fn export_day(&self, day: NaiveDate) -> Result<PathBuf, String> {
+        let dest_dir = PathBuf::from(self.auto_export.dest_dir.trim());
+        std::fs::create_dir_all(&dest_dir).map_err(|e| e.to_string())?;
+        let path = dest_dir.join(export_file_name(day, self.auto_export.format));
+        let rows: Vec<StatsRow> = self
+            .snapshot_rows()?
+            .into_iter()
+            .filter(|row| row.date.day() == day)
+            .collect();
+        let sign_chain = self
+            .auto_export
+            .sign
+            .then_some(self.export_chain_path.as_path());
+        write_day_export(
+            &path,
+            &rows,
+            self.auto_export.format,
+            self.csv_dialect,
+            sign_chain,
+        )?;
+        Ok(path)
+    }
+}
+
+/// Export every finished day not exported yet. Called on each tick; it only does work after
+/// the local day changed, and never fails the tick: errors are logged and retried later.
+pub(super) fn run_auto_export(state: &mut CollectorState, today: NaiveDate, now: Instant) {
+    if !state.auto_export.enabled || state.auto_export.dest_dir.trim().is_empty() {
+        return;
+    }
+    if state
+        .auto_export_retry_at
+        .is_some_and(|retry_at| now < retry_at)
+    {
+        return;
+    }
+    let Some(last_exported) = state.last_auto_export_day else {
+        // Nothing exported yet: the day in progress is the first one to export.
+        state.last_auto_export_day = today.pred_opt();
+        state.save_auto_export_marker();
+        return;
+    };
+    let (days, skipped) = pending_export_days(last_exported, today);
+    if skipped > 0 {
+        let _ = append_app_log(
+            &state.app_log_path,
+            &format!(
+                "auto export skipped {} missed days older than {} days",
+                skipped, AUTO_EXPORT_MAX_CATCH_UP_DAYS
+            ),
+        );
+    }
+    for day in days {
+        match state.export_day(day) {
+            Ok(path) => {
+                state.last_auto_export_day = Some(day);
+                state.save_auto_export_marker();
+                let _ = append_app_log(
+                    &state.app_log_path,
+                    &format!("auto exported {} to {}", day, path.display()),
+                );
+                if let Some(script_path) = state
+                    .auto_export
+                    .script_path
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                {
+                    spawn_export_script(
+                        PathBuf::from(script_path),
+                        path,
+                        state.app_log_path.clone(),
+                    );
+                }
+            }
+            Err(err) => {
+                state.auto_export_retry_at = Some(now + AUTO_EXPORT_RETRY_DELAY);
+                let _ = append_app_log(
+                    &state.app_log_path,
+                    &format!("auto export of {} failed: {}", day, err),
+                );
+                return;
+            }
+        }
+    }
+}
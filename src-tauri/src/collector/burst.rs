@@ -0,0 +1,241 @@
+//! Typing burst module.
+//! Detects bursts (runs of rapid key-downs) as keys arrive and keeps per-day burst totals.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+
+use crate::app_config::AppConfig;
+use crate::storage::StoredBurstDay;
+
+use super::shortcut::{days_in_window, shortcut_range_window_ms};
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+/// Burst rules: at least `min_keys` key-downs, each less than `max_gap_ms` after the previous.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct BurstThresholds {
+    pub(super) min_keys: u64,
+    pub(super) max_gap_ms: i64,
+}
+
+impl BurstThresholds {
+    pub(super) fn from_config(config: &AppConfig) -> Self {
+        let effective = config.effective();
+        Self {
+            min_keys: effective.burst_min_keys,
+            max_gap_ms: effective.burst_max_gap_ms as i64,
+        }
+    }
+}
+
+/// Run of key-downs (epoch milliseconds) whose gaps all stayed under the threshold. It only
+/// counts as a burst once it reached `min_keys`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct BurstRun {
+    pub(super) start_ms: i64,
+    pub(super) last_ms: i64,
+    pub(super) keys: u64,
+}
+
+impl BurstRun {
+    fn duration_ms(&self) -> u64 {
+        (self.last_ms - self.start_ms).max(0) as u64
+    }
+}
+
+/// Bursts of one local day. The longest key count and longest duration are tracked
+/// separately and may come from different bursts.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(super) struct BurstDay {
+    pub(super) count: u64,
+    pub(super) total_keys: u64,
+    pub(super) total_ms: u64,
+    pub(super) longest_keys: u64,
+    pub(super) longest_ms: u64,
+}
+
+impl BurstDay {
+    fn record(&mut self, run: &BurstRun) {
+        let duration_ms = run.duration_ms();
+        self.count += 1;
+        self.total_keys = self.total_keys.saturating_add(run.keys);
+        self.total_ms = self.total_ms.saturating_add(duration_ms);
+        self.longest_keys = self.longest_keys.max(run.keys);
+        self.longest_ms = self.longest_ms.max(duration_ms);
+    }
+
+    pub(super) fn merge(&mut self, other: &BurstDay) {
+        self.count = self.count.saturating_add(other.count);
+        self.total_keys = self.total_keys.saturating_add(other.total_keys);
+        self.total_ms = self.total_ms.saturating_add(other.total_ms);
+        self.longest_keys = self.longest_keys.max(other.longest_keys);
+        self.longest_ms = self.longest_ms.max(other.longest_ms);
+    }
+
+    pub(super) fn to_stored(self) -> StoredBurstDay {
+        StoredBurstDay {
+            count: self.count,
+            total_keys: self.total_keys,
+            total_ms: self.total_ms,
+            longest_keys: self.longest_keys,
+            longest_ms: self.longest_ms,
+        }
+    }
+
+    pub(super) fn from_stored(stored: &StoredBurstDay) -> Self {
+        Self {
+            count: stored.count,
+            total_keys: stored.total_keys,
+            total_ms: stored.total_ms,
+            longest_keys: stored.longest_keys,
+            longest_ms: stored.longest_ms,
+        }
+    }
+}
+
+/// Burst totals of one local day (`YYYY-MM-DD`); means are None on days without bursts.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BurstDayRow {
+    pub date: String,
+    pub burst_count: u64,
+    pub mean_keys: Option<f64>,
+    pub mean_ms: Option<f64>,
+    pub longest_keys: u64,
+    pub longest_ms: u64,
+}
+
+/// Burst statistics for a range with one row per local day, plus the thresholds in effect.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BurstStats {
+    pub range: String,
+    pub min_keys: u64,
+    pub max_gap_ms: u64,
+    pub burst_count: u64,
+    pub mean_keys: Option<f64>,
+    pub mean_ms: Option<f64>,
+    pub longest_keys: u64,
+    pub longest_ms: u64,
+    pub days: Vec<BurstDayRow>,
+}
+
+// Advance the open run with a key-down at `at_ms`. Returns the run it replaced when the gap
+// broke it, whether or not that run was long enough to count.
+fn push_key(
+    run: &mut Option<BurstRun>,
+    at_ms: i64,
+    thresholds: BurstThresholds,
+) -> Option<BurstRun> {
+    if let Some(open) = run.as_mut() {
+        let gap_ms = at_ms - open.last_ms;
+        if (0..thresholds.max_gap_ms).contains(&gap_ms) {
+            open.last_ms = at_ms;
+            open.keys += 1;
+            return None;
+        }
+    }
+    run.replace(BurstRun {
+        start_ms: at_ms,
+        last_ms: at_ms,
+        keys: 1,
+    })
+}
+
+// Add a finished run to the per-day totals of the local day it started on.
+fn record_burst(days: &mut HashMap<String, BurstDay>, run: &BurstRun, thresholds: BurstThresholds) {
+    if run.keys < thresholds.min_keys {
+        return;
+    }
+    let Some(started) = DateTime::<Utc>::from_timestamp_millis(run.start_ms) else {
+        return;
+    };
+    let day = started
+        .with_timezone(&Local)
+        .format(DAY_KEY_FORMAT)
+        .to_string();
+    days.entry(day).or_default().record(run);
+}
+
+/// Feed a counted key-down into the open run, finalizing the previous run when the gap
+/// exceeded the threshold. `at_ms` is the timestamp written to the event chunk.
+pub(super) fn record_burst_key(state: &mut CollectorState, at_ms: i64) {
+    let thresholds = state.burst_thresholds;
+    if let Some(finished) = push_key(&mut state.burst_run, at_ms, thresholds) {
+        record_burst(&mut state.burst_days, &finished, thresholds);
+    }
+}
+
+/// Tick check: finalize the open run once no key arrived within the gap threshold.
+pub(super) fn expire_burst(state: &mut CollectorState, now_ms: i64) {
+    let thresholds = state.burst_thresholds;
+    let stale = state
+        .burst_run
+        .is_some_and(|run| now_ms - run.last_ms >= thresholds.max_gap_ms);
+    if !stale {
+        return;
+    }
+    if let Some(finished) = state.burst_run.take() {
+        record_burst(&mut state.burst_days, &finished, thresholds);
+    }
+}
+
+/// Offline replay: per-day burst totals from key-down timestamps, e.g. rebuilt from chunks.
+pub(super) fn bursts_from_key_times(
+    mut times: Vec<i64>,
+    thresholds: BurstThresholds,
+) -> HashMap<String, BurstDay> {
+    times.sort_unstable();
+    let mut days = HashMap::new();
+    let mut run = None;
+    for at_ms in times {
+        if let Some(finished) = push_key(&mut run, at_ms, thresholds) {
+            record_burst(&mut days, &finished, thresholds);
+        }
+    }
+    if let Some(finished) = run {
+        record_burst(&mut days, &finished, thresholds);
+    }
+    days
+}
+
+fn mean(total: u64, count: u64) -> Option<f64> {
+    (count > 0).then(|| total as f64 / count as f64)
+}
+
+/// Build burst statistics by range: `today` / `yesterday` / `7d`. A run still in progress
+/// is included once it is long enough to count.
+pub fn snapshot_burst_stats(state: &CollectorState, range: &str) -> BurstStats {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let mut by_day = state.burst_days.clone();
+    if let Some(open) = state.burst_run.as_ref() {
+        record_burst(&mut by_day, open, state.burst_thresholds);
+    }
+    let mut total = BurstDay::default();
+    let days = days_in_window(start_ms, end_ms)
+        .into_iter()
+        .map(|date| {
+            let day = by_day.get(&date).copied().unwrap_or_default();
+            total.merge(&day);
+            BurstDayRow {
+                date,
+                burst_count: day.count,
+                mean_keys: mean(day.total_keys, day.count),
+                mean_ms: mean(day.total_ms, day.count),
+                longest_keys: day.longest_keys,
+                longest_ms: day.longest_ms,
+            }
+        })
+        .collect();
+    BurstStats {
+        range: range.to_string(),
+        min_keys: state.burst_thresholds.min_keys,
+        max_gap_ms: state.burst_thresholds.max_gap_ms as u64,
+        burst_count: total.count,
+        mean_keys: mean(total.total_keys, total.count),
+        mean_ms: mean(total.total_ms, total.count),
+        longest_keys: total.longest_keys,
+        longest_ms: total.longest_ms,
+        days,
+    }
+}
@@ -0,0 +1,137 @@
+//! Chord attempt module.
+//! Detects modifier presses released without a non-modifier key ("aborted chords") and
+//! aggregates them per local day and app.
+
+use std::collections::HashMap;
+
+use chrono::Local;
+use serde::Serialize;
+
+use super::context::{auto_pause_reason, is_auto_paused};
+use super::shortcut::{days_in_window, shortcut_range_window_ms};
+use super::{CaptureContext, CollectorState, ModifierSnapshot};
+
+/// Aborted chord count of one app over the requested range.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ChordAbortAppRow {
+    pub app_name: String,
+    pub count: u64,
+}
+
+/// Aborted chord count of one local day (`YYYY-MM-DD`).
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ChordAbortDayRow {
+    pub date: String,
+    pub count: u64,
+}
+
+/// Aborted chord totals for a range, most affected apps first and days in calendar order.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ChordAbortStats {
+    pub range: String,
+    pub total: u64,
+    pub by_app: Vec<ChordAbortAppRow>,
+    pub by_day: Vec<ChordAbortDayRow>,
+}
+
+// Shift and fn alone start no shortcut (shift taps also switch input methods), so only
+// attempts that held ctrl, opt or cmd count as aborted chords.
+fn is_chord_attempt(peak: ModifierSnapshot) -> bool {
+    peak.ctrl || peak.opt || peak.cmd
+}
+
+// Advance the chord state machine with the modifier set held after a modifier transition.
+pub(super) fn apply_modifiers_changed(
+    state: &mut CollectorState,
+    modifiers: ModifierSnapshot,
+    capture_context: &CaptureContext,
+) {
+    if !state.track_chord_aborts {
+        state.chord_attempt = None;
+        return;
+    }
+    state.auto_paused = is_auto_paused(state, capture_context);
+    state.auto_pause_reason = auto_pause_reason(state, capture_context);
+    if state.paused || state.auto_paused {
+        state.chord_attempt = None;
+        return;
+    }
+    if modifiers.has_any() {
+        let peak = state
+            .chord_attempt
+            .map_or(modifiers, |peak| peak.union(modifiers));
+        state.chord_attempt = Some(peak);
+        return;
+    }
+    let Some(peak) = state.chord_attempt.take() else {
+        return;
+    };
+    if !is_chord_attempt(peak) {
+        return;
+    }
+    let app_id = capture_context
+        .bundle_id
+        .clone()
+        .unwrap_or_else(|| capture_context.app_name.clone());
+    let day = Local::now().format("%Y-%m-%d").to_string();
+    let count = state
+        .chord_aborts
+        .entry(day)
+        .or_default()
+        .entry(app_id)
+        .or_insert(0);
+    *count = count.saturating_add(1);
+}
+
+// Aggregate per-day/per-app counters over the given local days.
+pub(super) fn build_chord_abort_stats(
+    chord_aborts: &HashMap<String, HashMap<String, u64>>,
+    range: &str,
+    days: &[String],
+) -> ChordAbortStats {
+    let mut by_app: HashMap<&str, u64> = HashMap::new();
+    let mut by_day = Vec::new();
+    for day in days {
+        let Some(apps) = chord_aborts.get(day) else {
+            continue;
+        };
+        let day_total: u64 = apps.values().sum();
+        if day_total == 0 {
+            continue;
+        }
+        for (app_id, count) in apps {
+            *by_app.entry(app_id.as_str()).or_insert(0) += count;
+        }
+        by_day.push(ChordAbortDayRow {
+            date: day.clone(),
+            count: day_total,
+        });
+    }
+    let mut by_app: Vec<ChordAbortAppRow> = by_app
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(app_name, count)| ChordAbortAppRow {
+            app_name: app_name.to_string(),
+            count,
+        })
+        .collect();
+    by_app.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.app_name.cmp(&b.app_name))
+    });
+    ChordAbortStats {
+        range: range.to_string(),
+        total: by_day.iter().map(|row| row.count).sum(),
+        by_app,
+        by_day,
+    }
+}
+
+/// Build aborted chord stats by range: `today` / `yesterday` / `7d`.
+pub fn snapshot_chord_abort_stats(state: &CollectorState, range: &str) -> ChordAbortStats {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let days = days_in_window(start_ms, end_ms);
+    build_chord_abort_stats(&state.chord_aborts, range, &days)
+}
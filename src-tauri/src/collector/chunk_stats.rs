@@ -0,0 +1,131 @@
+//! Chunk statistics module.
+//! Keeps running totals over the stored input-event chunks so the analytics page can show
+//! how close history is to the chunk cap without rescanning every chunk.
+
+use std::collections::HashMap;
+
+use chrono::{Duration as ChronoDuration, Local};
+use serde::Serialize;
+
+use super::shortcut::{
+    InputEventChunk, INPUT_CHUNK_MAX_EVENTS, INPUT_CHUNK_MAX_STORED, INPUT_CHUNK_WINDOW_MS,
+};
+use super::CollectorState;
+
+/// Days listed in `events_by_day`, today included.
+const EVENTS_BY_DAY_WINDOW: i64 = 7;
+/// Serialized size of a chunk's fixed fields (`v`, `chunk_start_ms`, `app_ref`, brackets).
+const CHUNK_JSON_OVERHEAD_BYTES: u64 = 64;
+/// Per-event serialization overhead: two quotes and a separating comma.
+const EVENT_JSON_OVERHEAD_BYTES: u64 = 3;
+
+/// Running totals over `CollectorState::event_chunks`. Every push/prune of stored chunks has
+/// to go through `add`/`remove` so these stay equal to a full rescan.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct ChunkCounters {
+    pub(super) total_events: u64,
+    pub(super) approx_bytes: u64,
+    pub(super) events_by_day: HashMap<String, u64>,
+}
+
+impl ChunkCounters {
+    pub(super) fn from_chunks(chunks: &[InputEventChunk]) -> Self {
+        let mut counters = Self::default();
+        for chunk in chunks {
+            counters.add(chunk);
+        }
+        counters
+    }
+
+    pub(super) fn add(&mut self, chunk: &InputEventChunk) {
+        let events = chunk.events.len() as u64;
+        self.total_events += events;
+        self.approx_bytes += approx_chunk_bytes(chunk);
+        *self
+            .events_by_day
+            .entry(chunk_local_day(chunk.chunk_start_ms))
+            .or_default() += events;
+    }
+
+    pub(super) fn remove(&mut self, chunk: &InputEventChunk) {
+        let events = chunk.events.len() as u64;
+        self.total_events = self.total_events.saturating_sub(events);
+        self.approx_bytes = self.approx_bytes.saturating_sub(approx_chunk_bytes(chunk));
+        let day = chunk_local_day(chunk.chunk_start_ms);
+        if let Some(count) = self.events_by_day.get_mut(&day) {
+            *count = count.saturating_sub(events);
+            if *count == 0 {
+                self.events_by_day.remove(&day);
+            }
+        }
+    }
+}
+
+fn approx_chunk_bytes(chunk: &InputEventChunk) -> u64 {
+    chunk
+        .events
+        .iter()
+        .map(|event| event.len() as u64 + EVENT_JSON_OVERHEAD_BYTES)
+        .sum::<u64>()
+        + CHUNK_JSON_OVERHEAD_BYTES
+}
+
+fn chunk_local_day(chunk_start_ms: i64) -> String {
+    chrono::DateTime::<chrono::Utc>::from_timestamp_millis(chunk_start_ms)
+        .map(|value| value.with_timezone(&Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Events stored for one local day (`YYYY-MM-DD`).
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AnalyticsDayEvents {
+    pub date: String,
+    pub events: u64,
+}
+
+/// Stored chunk usage against the retention caps. The open chunk is not counted until it is
+/// sealed.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AnalyticsStats {
+    pub chunk_count: usize,
+    pub max_chunks: usize,
+    pub max_events_per_chunk: usize,
+    pub chunk_window_ms: i64,
+    pub oldest_chunk_ms: Option<i64>,
+    pub newest_chunk_ms: Option<i64>,
+    pub total_events: u64,
+    pub approx_bytes: u64,
+    pub events_by_day: Vec<AnalyticsDayEvents>,
+}
+
+/// Chunk storage statistics, with `events_by_day` covering the last 7 local days oldest first.
+pub fn snapshot_analytics_stats(state: &CollectorState) -> AnalyticsStats {
+    let today = Local::now().date_naive();
+    let events_by_day = (0..EVENTS_BY_DAY_WINDOW)
+        .rev()
+        .map(|offset| {
+            let date = (today - ChronoDuration::days(offset))
+                .format("%Y-%m-%d")
+                .to_string();
+            let events = state
+                .chunk_counters
+                .events_by_day
+                .get(&date)
+                .copied()
+                .unwrap_or(0);
+            AnalyticsDayEvents { date, events }
+        })
+        .collect();
+    // Chunks are sealed in start order and merges re-sort, so the ends are the extremes.
+    AnalyticsStats {
+        chunk_count: state.event_chunks.len(),
+        max_chunks: INPUT_CHUNK_MAX_STORED,
+        max_events_per_chunk: INPUT_CHUNK_MAX_EVENTS,
+        chunk_window_ms: INPUT_CHUNK_WINDOW_MS,
+        oldest_chunk_ms: state.event_chunks.first().map(|chunk| chunk.chunk_start_ms),
+        newest_chunk_ms: state.event_chunks.last().map(|chunk| chunk.chunk_start_ms),
+        total_events: state.chunk_counters.total_events,
+        approx_bytes: state.chunk_counters.approx_bytes,
+        events_by_day,
+    }
+}
@@ -0,0 +1,203 @@
+//! Comparison module.
+//! Builds symmetric side-by-side metrics for two days or two apps from stats and chunk replay.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::shortcut::{
+    days_in_window, local_day_window_ms, shortcut_range_window_ms,
+    snapshot_shortcut_rows_in_window, snapshot_top_keys_in_window, DEFAULT_SHORTCUT_APP_LIMIT,
+};
+use super::state_api::scan_stats_totals;
+use super::{CollectorState, KeyUsageRow, ShortcutStatRow, StatsKey, DAY_KEY_FORMAT};
+
+const COMPARISON_TOP_SHORTCUTS: usize = 5;
+const DEFAULT_APP_RANGE: &str = "7d";
+
+/// What the two compared subjects are.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ComparisonKind {
+    /// Two local days (`YYYY-MM-DD`).
+    Date,
+    /// Two app ids (bundle id, or app name when none) over a shared range.
+    App,
+}
+
+impl ComparisonKind {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ComparisonKind::Date => "date",
+            ComparisonKind::App => "app",
+        }
+    }
+
+    pub(crate) fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "date" => Some(ComparisonKind::Date),
+            "app" => Some(ComparisonKind::App),
+            _ => None,
+        }
+    }
+}
+
+/// Typed comparison failure, serialized as `{ code, ... }` for the frontend.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ComparisonError {
+    InvalidKind { kind: String },
+    InvalidDate { subject: String },
+    UnknownApp { subject: String },
+    InvalidRange { range: String },
+    StateUnavailable,
+}
+
+/// Aligned metrics for one compared subject.
+#[derive(Serialize, Clone)]
+pub struct ComparisonSide {
+    pub subject: String,
+    pub key_count: u64,
+    pub active_typing_ms: u64,
+    pub session_count: u64,
+    pub top_keys: Vec<KeyUsageRow>,
+    pub top_shortcuts: Vec<ShortcutStatRow>,
+}
+
+/// Signed difference `b - a` of the scalar metrics.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ComparisonDelta {
+    pub key_count: i64,
+    pub active_typing_ms: i64,
+    pub session_count: i64,
+}
+
+/// Side-by-side comparison payload; `range` is only set for app comparisons.
+#[derive(Serialize, Clone)]
+pub struct Comparison {
+    pub kind: String,
+    pub range: Option<String>,
+    pub a: ComparisonSide,
+    pub b: ComparisonSide,
+    pub delta: ComparisonDelta,
+}
+
+// Signed difference of two counters, saturating instead of wrapping.
+fn signed_delta(a: u64, b: u64) -> i64 {
+    i64::try_from(b)
+        .unwrap_or(i64::MAX)
+        .saturating_sub(i64::try_from(a).unwrap_or(i64::MAX))
+}
+
+// Build one side from a stats row filter and a chunk replay window.
+fn build_side(
+    state: &CollectorState,
+    subject: &str,
+    filter: impl Fn(&StatsKey) -> bool,
+    window: (i64, i64),
+    app_filter: Option<&str>,
+) -> ComparisonSide {
+    let totals = scan_stats_totals(&state.stats, filter);
+    let mut top_shortcuts = snapshot_shortcut_rows_in_window(
+        state,
+        window.0,
+        window.1,
+        app_filter,
+        DEFAULT_SHORTCUT_APP_LIMIT,
+    );
+    top_shortcuts.truncate(COMPARISON_TOP_SHORTCUTS);
+    ComparisonSide {
+        subject: subject.to_string(),
+        key_count: totals.key_count,
+        active_typing_ms: totals.active_typing_ms,
+        session_count: totals.session_count,
+        top_keys: snapshot_top_keys_in_window(state, window.0, window.1, app_filter),
+        top_shortcuts,
+    }
+}
+
+fn date_side(state: &CollectorState, subject: &str) -> Result<ComparisonSide, ComparisonError> {
+    let invalid = || ComparisonError::InvalidDate {
+        subject: subject.to_string(),
+    };
+    let day = NaiveDate::parse_from_str(subject, DAY_KEY_FORMAT).map_err(|_| invalid())?;
+    let window = local_day_window_ms(day).ok_or_else(invalid)?;
+    Ok(build_side(
+        state,
+        subject,
+        |key| key.date.day() == day,
+        window,
+        None,
+    ))
+}
+
+fn app_side(
+    state: &CollectorState,
+    subject: &str,
+    days: &[String],
+    window: (i64, i64),
+) -> Result<ComparisonSide, ComparisonError> {
+    let days: Vec<NaiveDate> = days
+        .iter()
+        .filter_map(|day| NaiveDate::parse_from_str(day, DAY_KEY_FORMAT).ok())
+        .collect();
+    let known = state.stats.keys().any(|key| key.app_name == subject)
+        || state.app_dict.values().any(|app_id| app_id == subject);
+    if !known {
+        return Err(ComparisonError::UnknownApp {
+            subject: subject.to_string(),
+        });
+    }
+    Ok(build_side(
+        state,
+        subject,
+        |key| key.app_name == subject && days.contains(&key.date.day()),
+        window,
+        Some(subject),
+    ))
+}
+
+/// Compare two days, or two apps over `range` (`today` / `yesterday` / `7d`, default `7d`).
+pub fn snapshot_comparison(
+    state: &CollectorState,
+    subject_a: &str,
+    subject_b: &str,
+    kind: &str,
+    range: Option<&str>,
+) -> Result<Comparison, ComparisonError> {
+    let kind = ComparisonKind::from_str(kind).ok_or_else(|| ComparisonError::InvalidKind {
+        kind: kind.to_string(),
+    })?;
+    let (a, b, range) = match kind {
+        ComparisonKind::Date => (
+            date_side(state, subject_a)?,
+            date_side(state, subject_b)?,
+            None,
+        ),
+        ComparisonKind::App => {
+            let range = range.unwrap_or(DEFAULT_APP_RANGE);
+            if !matches!(range, "today" | "yesterday" | "7d") {
+                return Err(ComparisonError::InvalidRange {
+                    range: range.to_string(),
+                });
+            }
+            let window = shortcut_range_window_ms(range, chrono::Utc::now().timestamp_millis());
+            let days = days_in_window(window.0, window.1);
+            (
+                app_side(state, subject_a, &days, window)?,
+                app_side(state, subject_b, &days, window)?,
+                Some(range.to_string()),
+            )
+        }
+    };
+    let delta = ComparisonDelta {
+        key_count: signed_delta(a.key_count, b.key_count),
+        active_typing_ms: signed_delta(a.active_typing_ms, b.active_typing_ms),
+        session_count: signed_delta(a.session_count, b.session_count),
+    };
+    Ok(Comparison {
+        kind: kind.as_str().to_string(),
+        range,
+        a,
+        b,
+        delta,
+    })
+}
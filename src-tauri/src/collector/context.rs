@@ -5,7 +5,10 @@ use std::path::PathBuf;
 
 use serde::Serialize;
 
+use super::display::display_of_window;
 use super::modifier::ModifierSnapshot;
+#[cfg(not(target_os = "macos"))]
+use super::window_apps::{executable_app_id, window_running_apps};
 use super::CollectorState;
 use std::time::{Duration, Instant};
 
@@ -22,6 +25,8 @@ pub(super) struct CaptureContext {
     pub(super) window_title: String,
     pub(super) bundle_id: Option<String>,
     pub(super) secure_input: bool,
+    /// Display showing most of the active window; None when displays are not known yet.
+    pub(super) display_id: Option<String>,
 }
 
 // Unified collector event model used by runtime handlers and unit tests.
@@ -34,12 +39,20 @@ pub(super) enum CollectorEvent {
         is_key_combo: bool,
         capture_context: CaptureContext,
         at: Instant,
+        /// Wall-clock time of `at` in epoch milliseconds, from the OS event when it had one.
+        at_ms: i64,
     },
     NonModifierKeyUp {
         physical_key_id: String,
         shortcut_key: String,
         modifiers: ModifierSnapshot,
         capture_context: CaptureContext,
+        at: Instant,
+        at_ms: i64,
+    },
+    ModifiersChanged {
+        modifiers: ModifierSnapshot,
+        capture_context: CaptureContext,
     },
     Tick {
         elapsed: Duration,
@@ -53,6 +66,8 @@ pub(super) fn capture_context() -> CaptureContext {
     if let Ok(window) = active_win_pos_rs::get_active_window() {
         let app_name = window.app_name;
         let window_title = window.title;
+        let position = &window.position;
+        let display_id = display_of_window(position.x, position.y, position.width, position.height);
         #[cfg(target_os = "macos")]
         {
             let bundle_id = frontmost_bundle_id_macos()
@@ -63,6 +78,7 @@ pub(super) fn capture_context() -> CaptureContext {
                 window_title,
                 bundle_id,
                 secure_input,
+                display_id,
             };
         }
         #[cfg(not(target_os = "macos"))]
@@ -72,6 +88,7 @@ pub(super) fn capture_context() -> CaptureContext {
                 window_title,
                 bundle_id: None,
                 secure_input,
+                display_id,
             };
         }
     }
@@ -80,6 +97,7 @@ pub(super) fn capture_context() -> CaptureContext {
         window_title: String::new(),
         bundle_id: None,
         secure_input,
+        display_id: None,
     }
 }
 
@@ -116,7 +134,7 @@ pub fn running_apps() -> Vec<RunningAppInfo> {
     }
     #[cfg(not(target_os = "macos"))]
     {
-        vec![]
+        window_running_apps()
     }
 }
 
@@ -127,8 +145,7 @@ pub fn bundle_id_from_app_path(path: &str) -> Option<String> {
     }
     #[cfg(not(target_os = "macos"))]
     {
-        let _ = path;
-        None
+        executable_app_id(std::path::Path::new(path))
     }
 }
 
@@ -236,7 +253,7 @@ fn workspace_running_apps() -> Vec<RunningAppInfo> {
 }
 
 #[cfg(target_os = "macos")]
-fn nsstring_to_string(value: cocoa::base::id) -> String {
+pub(super) fn nsstring_to_string(value: cocoa::base::id) -> String {
     use objc::{msg_send, sel, sel_impl};
 
     unsafe {
@@ -0,0 +1,258 @@
+//! Copy-paste module.
+//! Pairs each counted copy shortcut with the next paste inside the pairing window, across
+//! apps, and keeps per-day latencies and source→destination app pairs. Only the pending
+//! copy's time and app are held; clipboard contents are never read.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+
+use crate::storage::{StoredAppSwitchPair, StoredCopyPasteDay};
+
+use super::shortcut::{days_in_window, shortcut_range_window_ms};
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+// Copy and paste as counted shortcut ids.
+const COPY_SHORTCUT: &str = "cmd_c";
+const PASTE_SHORTCUT: &str = "cmd_v";
+
+/// Latencies kept per day for percentiles; later pastes still count toward the totals.
+pub(super) const MAX_COPY_PASTE_LATENCIES_PER_DAY: usize = 1_000;
+/// Distinct app pairs kept per day; pastes of further pairs only count toward the totals.
+pub(super) const MAX_COPY_PASTE_PAIRS_PER_DAY: usize = 200;
+/// App pairs returned by `snapshot_copy_paste_stats`.
+const TOP_COPY_PASTE_PAIRS: usize = 10;
+
+/// Copy still waiting for its paste.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct PendingCopy {
+    pub(super) at_ms: i64,
+    pub(super) app_id: String,
+}
+
+/// Copy-paste pairs of one local day.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct CopyPasteDay {
+    pub(super) pairs: u64,
+    pub(super) unmatched_copies: u64,
+    pub(super) latencies_ms: Vec<u64>,
+    pub(super) app_pairs: HashMap<(String, String), u64>,
+}
+
+impl CopyPasteDay {
+    fn record_pair(&mut self, latency_ms: u64, from: String, to: String) {
+        self.pairs = self.pairs.saturating_add(1);
+        if self.latencies_ms.len() < MAX_COPY_PASTE_LATENCIES_PER_DAY {
+            self.latencies_ms.push(latency_ms);
+        }
+        let pair = (from, to);
+        if let Some(count) = self.app_pairs.get_mut(&pair) {
+            *count = count.saturating_add(1);
+        } else if self.app_pairs.len() < MAX_COPY_PASTE_PAIRS_PER_DAY {
+            self.app_pairs.insert(pair, 1);
+        }
+    }
+
+    pub(super) fn merge(&mut self, other: CopyPasteDay) {
+        self.pairs = self.pairs.saturating_add(other.pairs);
+        self.unmatched_copies = self.unmatched_copies.saturating_add(other.unmatched_copies);
+        let room = MAX_COPY_PASTE_LATENCIES_PER_DAY.saturating_sub(self.latencies_ms.len());
+        self.latencies_ms
+            .extend(other.latencies_ms.into_iter().take(room));
+        for (pair, count) in other.app_pairs {
+            if let Some(existing) = self.app_pairs.get_mut(&pair) {
+                *existing = existing.saturating_add(count);
+            } else if self.app_pairs.len() < MAX_COPY_PASTE_PAIRS_PER_DAY {
+                self.app_pairs.insert(pair, count);
+            }
+        }
+    }
+
+    pub(super) fn to_stored(&self) -> StoredCopyPasteDay {
+        let mut app_pairs: Vec<StoredAppSwitchPair> = self
+            .app_pairs
+            .iter()
+            .map(|((from, to), count)| StoredAppSwitchPair {
+                from: from.clone(),
+                to: to.clone(),
+                count: *count,
+            })
+            .collect();
+        app_pairs.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.from.cmp(&b.from))
+                .then_with(|| a.to.cmp(&b.to))
+        });
+        StoredCopyPasteDay {
+            pairs: self.pairs,
+            unmatched_copies: self.unmatched_copies,
+            latencies_ms: self.latencies_ms.clone(),
+            app_pairs,
+        }
+    }
+
+    pub(super) fn from_stored(stored: StoredCopyPasteDay) -> Self {
+        let mut day = Self {
+            pairs: stored.pairs,
+            unmatched_copies: stored.unmatched_copies,
+            latencies_ms: stored.latencies_ms,
+            app_pairs: HashMap::new(),
+        };
+        day.latencies_ms.truncate(MAX_COPY_PASTE_LATENCIES_PER_DAY);
+        for pair in stored.app_pairs {
+            if day.app_pairs.len() >= MAX_COPY_PASTE_PAIRS_PER_DAY {
+                break;
+            }
+            let count = day.app_pairs.entry((pair.from, pair.to)).or_insert(0);
+            *count = count.saturating_add(pair.count);
+        }
+        day
+    }
+}
+
+/// One source→destination app pair and how often a copy in `from` was pasted in `to`.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct CopyPastePairRow {
+    pub from: String,
+    pub to: String,
+    pub count: u64,
+}
+
+/// Copy-to-paste latency distribution for a range with its most common app pairs first.
+/// Percentiles are None when the range has no pairs.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CopyPasteStats {
+    pub range: String,
+    pub window_ms: u64,
+    pub pairs: u64,
+    pub unmatched_copies: u64,
+    pub p50_ms: Option<u64>,
+    pub p90_ms: Option<u64>,
+    pub p99_ms: Option<u64>,
+    pub top_pairs: Vec<CopyPastePairRow>,
+}
+
+fn local_day_key(at_ms: i64) -> Option<String> {
+    DateTime::<Utc>::from_timestamp_millis(at_ms).map(|value| {
+        value
+            .with_timezone(&Local)
+            .format(DAY_KEY_FORMAT)
+            .to_string()
+    })
+}
+
+fn record_unmatched(days: &mut HashMap<String, CopyPasteDay>, copy: &PendingCopy) {
+    if let Some(day) = local_day_key(copy.at_ms) {
+        let entry = days.entry(day).or_default();
+        entry.unmatched_copies = entry.unmatched_copies.saturating_add(1);
+    }
+}
+
+/// Feed a counted shortcut at `at_ms`. A copy becomes the pending copy, settling the previous
+/// one as unmatched; a paste inside the window pairs with it, filed under the paste's day.
+pub(super) fn record_copy_paste_shortcut(
+    state: &mut CollectorState,
+    shortcut_id: &str,
+    app_id: &str,
+    at_ms: i64,
+) {
+    if shortcut_id == COPY_SHORTCUT {
+        let copy = PendingCopy {
+            at_ms,
+            app_id: app_id.to_string(),
+        };
+        if let Some(previous) = state.pending_copy.replace(copy) {
+            record_unmatched(&mut state.copy_paste_days, &previous);
+        }
+        return;
+    }
+    if shortcut_id != PASTE_SHORTCUT {
+        return;
+    }
+    let Some(copy) = state.pending_copy.take() else {
+        return;
+    };
+    let latency_ms = at_ms - copy.at_ms;
+    if !(0..=state.copy_paste_window_ms).contains(&latency_ms) {
+        record_unmatched(&mut state.copy_paste_days, &copy);
+        return;
+    }
+    let Some(day) = local_day_key(at_ms) else {
+        return;
+    };
+    state.copy_paste_days.entry(day).or_default().record_pair(
+        latency_ms as u64,
+        copy.app_id,
+        app_id.to_string(),
+    );
+}
+
+/// Tick check: settle the pending copy as unmatched once the window passed without a paste.
+pub(super) fn expire_pending_copy(state: &mut CollectorState, now_ms: i64) {
+    let expired = state
+        .pending_copy
+        .as_ref()
+        .is_some_and(|copy| now_ms - copy.at_ms > state.copy_paste_window_ms);
+    if !expired {
+        return;
+    }
+    if let Some(copy) = state.pending_copy.take() {
+        record_unmatched(&mut state.copy_paste_days, &copy);
+    }
+}
+
+// Nearest-rank percentile of sorted latencies.
+fn percentile(sorted: &[u64], pct: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    Some(sorted[rank - 1])
+}
+
+/// Build copy-paste stats by range: `today` / `yesterday` / `7d`.
+pub fn snapshot_copy_paste_stats(state: &CollectorState, range: &str) -> CopyPasteStats {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let days = days_in_window(start_ms, end_ms);
+    let mut pairs = 0u64;
+    let mut unmatched_copies = 0u64;
+    let mut latencies: Vec<u64> = Vec::new();
+    let mut app_pairs: HashMap<&(String, String), u64> = HashMap::new();
+    for day in days.iter().filter_map(|day| state.copy_paste_days.get(day)) {
+        pairs = pairs.saturating_add(day.pairs);
+        unmatched_copies = unmatched_copies.saturating_add(day.unmatched_copies);
+        latencies.extend_from_slice(&day.latencies_ms);
+        for (pair, count) in &day.app_pairs {
+            *app_pairs.entry(pair).or_insert(0) += count;
+        }
+    }
+    latencies.sort_unstable();
+    let mut top_pairs: Vec<CopyPastePairRow> = app_pairs
+        .into_iter()
+        .map(|((from, to), count)| CopyPastePairRow {
+            from: from.clone(),
+            to: to.clone(),
+            count,
+        })
+        .collect();
+    top_pairs.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.from.cmp(&b.from))
+            .then_with(|| a.to.cmp(&b.to))
+    });
+    top_pairs.truncate(TOP_COPY_PASTE_PAIRS);
+    CopyPasteStats {
+        range: range.to_string(),
+        window_ms: state.copy_paste_window_ms as u64,
+        pairs,
+        unmatched_copies,
+        p50_ms: percentile(&latencies, 50),
+        p90_ms: percentile(&latencies, 90),
+        p99_ms: percentile(&latencies, 99),
+        top_pairs,
+    }
+}
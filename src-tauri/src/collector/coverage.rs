@@ -0,0 +1,161 @@
+//! Coverage module.
+//! Keeps a per-day ledger of how long the collector was recording, paused, or running
+//! without a working keyboard listener, so gaps in the stats can be explained.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::Local;
+use serde::Serialize;
+
+use crate::storage::StoredCoverageDay;
+
+use super::runtime::{runtime_days, DayCoverage, RuntimeDay};
+use super::shortcut::shortcut_range_window_ms;
+use super::CollectorState;
+
+/// Longest tick gap credited to the ledger. Longer gaps mean the process was suspended
+/// (system sleep) and are not time the collector was running at all.
+const MAX_COVERAGE_TICK_GAP: Duration = Duration::from_secs(10);
+
+/// Runtime coverage ledger of one local day.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(super) struct CoverageDay {
+    pub(super) recording_ms: u64,
+    pub(super) paused_ms: u64,
+    pub(super) listener_down_ms: u64,
+}
+
+impl CoverageDay {
+    fn tracked_ms(&self) -> u64 {
+        self.recording_ms
+            .saturating_add(self.paused_ms)
+            .saturating_add(self.listener_down_ms)
+    }
+
+    pub(super) fn to_stored(self) -> StoredCoverageDay {
+        StoredCoverageDay {
+            recording_ms: self.recording_ms,
+            paused_ms: self.paused_ms,
+            listener_down_ms: self.listener_down_ms,
+        }
+    }
+
+    /// Add another ledger of the same day.
+    pub(super) fn merge(&mut self, other: &CoverageDay) {
+        self.recording_ms = self.recording_ms.saturating_add(other.recording_ms);
+        self.paused_ms = self.paused_ms.saturating_add(other.paused_ms);
+        self.listener_down_ms = self.listener_down_ms.saturating_add(other.listener_down_ms);
+    }
+
+    pub(super) fn from_stored(stored: &StoredCoverageDay) -> Self {
+        Self {
+            recording_ms: stored.recording_ms,
+            paused_ms: stored.paused_ms,
+            listener_down_ms: stored.listener_down_ms,
+        }
+    }
+}
+
+/// Coverage of one local day (`YYYY-MM-DD`). `coverage` tells a day the app never ran apart
+/// from a day it ran without typing.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CoverageDayRow {
+    pub date: String,
+    pub recording_ms: u64,
+    pub paused_ms: u64,
+    pub listener_down_ms: u64,
+    pub coverage_pct: Option<f64>,
+    pub runtime_ms: u64,
+    pub coverage: DayCoverage,
+}
+
+/// Coverage totals for a range with one row per local day in the range.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Coverage {
+    pub range: String,
+    pub recording_ms: u64,
+    pub paused_ms: u64,
+    pub listener_down_ms: u64,
+    pub coverage_pct: Option<f64>,
+    pub days: Vec<CoverageDayRow>,
+}
+
+// Share of tracked time spent recording, or None before anything was tracked.
+fn coverage_pct(day: &CoverageDay) -> Option<f64> {
+    let tracked_ms = day.tracked_ms();
+    if tracked_ms == 0 {
+        return None;
+    }
+    Some(day.recording_ms as f64 * 100.0 / tracked_ms as f64)
+}
+
+// Credit the time since the previous tick to the collector's current mode. Must run after
+// the tick has refreshed auto-pause; a dead listener wins over pause since nothing could
+// have been recorded either way.
+pub(super) fn record_coverage(state: &mut CollectorState, elapsed: Duration) {
+    let elapsed_ms = elapsed.min(MAX_COVERAGE_TICK_GAP).as_millis() as u64;
+    if elapsed_ms == 0 {
+        return;
+    }
+    let day = Local::now().format("%Y-%m-%d").to_string();
+    let ledger = state.coverage.entry(day).or_default();
+    let bucket = if !state.keyboard_active {
+        &mut ledger.listener_down_ms
+    } else if state.paused || state.auto_paused {
+        &mut ledger.paused_ms
+    } else {
+        &mut ledger.recording_ms
+    };
+    *bucket = bucket.saturating_add(elapsed_ms);
+}
+
+/// Today's recording share in percent, or None before the first tick.
+pub(super) fn today_coverage_pct(state: &CollectorState) -> Option<f64> {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    state.coverage.get(&today).and_then(coverage_pct)
+}
+
+// Sum the ledger over the given local days; days without ledger entries report zeros.
+pub(super) fn build_coverage(
+    coverage: &HashMap<String, CoverageDay>,
+    range: &str,
+    days: Vec<RuntimeDay>,
+) -> Coverage {
+    let mut total = CoverageDay::default();
+    let mut rows = Vec::with_capacity(days.len());
+    for runtime in days {
+        let day = coverage.get(&runtime.date).copied().unwrap_or_default();
+        total.recording_ms = total.recording_ms.saturating_add(day.recording_ms);
+        total.paused_ms = total.paused_ms.saturating_add(day.paused_ms);
+        total.listener_down_ms = total.listener_down_ms.saturating_add(day.listener_down_ms);
+        rows.push(CoverageDayRow {
+            date: runtime.date,
+            recording_ms: day.recording_ms,
+            paused_ms: day.paused_ms,
+            listener_down_ms: day.listener_down_ms,
+            coverage_pct: coverage_pct(&day),
+            runtime_ms: runtime.runtime_ms,
+            coverage: runtime.coverage,
+        });
+    }
+    Coverage {
+        range: range.to_string(),
+        recording_ms: total.recording_ms,
+        paused_ms: total.paused_ms,
+        listener_down_ms: total.listener_down_ms,
+        coverage_pct: coverage_pct(&total),
+        days: rows,
+    }
+}
+
+/// Build the coverage ledger view by range: `today` / `yesterday` / `7d`.
+pub fn snapshot_coverage(state: &CollectorState, range: &str) -> Coverage {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    build_coverage(
+        &state.coverage,
+        range,
+        runtime_days(state, start_ms, end_ms),
+    )
+}
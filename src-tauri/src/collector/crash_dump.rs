@@ -0,0 +1,185 @@
+//! Crash dump module.
+//! Builds the best-effort dump written by the panic hook and merges leftover dumps on startup.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+use crate::storage::{
+    archive_crash_dump, load_crash_dumps, write_crash_dump as write_crash_dump_file,
+    JsonFileStorage, StoredCrashDump, StoredInputEventChunk,
+};
+
+use super::shortcut::{local_day_window_ms, resolve_app_ref, InputEventChunk, INPUT_CHUNK_VERSION};
+use super::{append_app_log, compute_today_totals, CollectorState, StatsValue};
+
+/// Snapshot today's stats rows plus today's sealed and open input chunks. Reads state only,
+/// so it is safe to call from the panic hook.
+pub(super) fn build_crash_dump(state: &CollectorState, now_ms: i64) -> StoredCrashDump {
+    let today = Local::now().date_naive();
+    let day_start_ms = local_day_window_ms(today).map_or(0, |(start_ms, _)| start_ms);
+    let today_stats: HashMap<_, _> = state
+        .stats
+        .iter()
+        .filter(|(key, _)| key.date.day() == today)
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    let mut event_chunks: Vec<StoredInputEventChunk> = state
+        .event_chunks
+        .iter()
+        .filter(|chunk| chunk.chunk_start_ms >= day_start_ms)
+        .map(|chunk| StoredInputEventChunk {
+            v: chunk.v,
+            chunk_start_ms: chunk.chunk_start_ms,
+            app_ref: chunk.app_ref,
+            events: chunk.events.clone(),
+        })
+        .collect();
+    if let Some(open) = state
+        .open_event_chunk
+        .as_ref()
+        .filter(|open| !open.events.is_empty())
+    {
+        event_chunks.push(StoredInputEventChunk {
+            v: INPUT_CHUNK_VERSION,
+            chunk_start_ms: open.chunk_start_ms,
+            app_ref: open.app_ref,
+            events: open.events.clone(),
+        });
+    }
+    let app_refs: HashSet<u32> = event_chunks.iter().map(|chunk| chunk.app_ref).collect();
+    let app_dict = state
+        .app_dict
+        .iter()
+        .filter(|(app_ref, _)| app_refs.contains(app_ref))
+        .map(|(app_ref, app_id)| (*app_ref, app_id.clone()))
+        .collect();
+    StoredCrashDump {
+        written_at_ms: now_ms,
+        rows: JsonFileStorage::stats_to_rows(&today_stats),
+        app_dict,
+        event_chunks,
+    }
+}
+
+/// Write a crash dump of `state` into `dir` and return its path.
+pub fn write_crash_dump(state: &CollectorState, dir: &Path) -> Result<PathBuf, String> {
+    let dump = build_crash_dump(state, chrono::Utc::now().timestamp_millis());
+    write_crash_dump_file(dir, &dump)
+}
+
+/// Merge one dump into state and return `(rows, chunks)` that changed it.
+///
+/// Dumped rows are the same cumulative counters the last flush persisted, only newer, so each
+/// field keeps the larger value instead of summing. Chunks are re-keyed through the app
+/// dictionary and skipped when a chunk with the same start, app and length already exists.
+/// Shortcut aggregates are not rebuilt; range views replay chunks and pick the events up.
+pub(super) fn merge_crash_dump(
+    state: &mut CollectorState,
+    dump: StoredCrashDump,
+) -> (usize, usize) {
+    let mut merged_rows = 0;
+    for (key, value) in JsonFileStorage::rows_to_stats(dump.rows) {
+        let entry = state.stats.entry(key).or_insert(StatsValue {
+            active_typing_ms: 0,
+            key_count: 0,
+            session_count: 0,
+            writing_ms: 0,
+            navigation_ms: 0,
+        });
+        if value.active_typing_ms > entry.active_typing_ms
+            || value.key_count > entry.key_count
+            || value.session_count > entry.session_count
+        {
+            entry.active_typing_ms = entry.active_typing_ms.max(value.active_typing_ms);
+            entry.key_count = entry.key_count.max(value.key_count);
+            entry.session_count = entry.session_count.max(value.session_count);
+            entry.writing_ms = entry.writing_ms.max(value.writing_ms);
+            entry.navigation_ms = entry.navigation_ms.max(value.navigation_ms);
+            merged_rows += 1;
+        }
+    }
+    let mut known: HashSet<(i64, u32, usize)> = state
+        .event_chunks
+        .iter()
+        .map(|chunk| (chunk.chunk_start_ms, chunk.app_ref, chunk.events.len()))
+        .collect();
+    let mut merged_chunks = 0;
+    for chunk in dump.event_chunks {
+        let Some(app_id) = dump.app_dict.get(&chunk.app_ref) else {
+            continue;
+        };
+        let app_ref = resolve_app_ref(state, app_id);
+        if !known.insert((chunk.chunk_start_ms, app_ref, chunk.events.len())) {
+            continue;
+        }
+        let chunk = InputEventChunk {
+            v: chunk.v,
+            chunk_start_ms: chunk.chunk_start_ms,
+            app_ref,
+            events: chunk.events,
+        };
+        state.chunk_counters.add(&chunk);
+        state.event_chunks.push(chunk);
+        merged_chunks += 1;
+    }
+    if merged_chunks > 0 {
+        state.event_chunks.sort_by_key(|chunk| chunk.chunk_start_ms);
+    }
+    state.today_totals = compute_today_totals(&state.stats, Local::now().date_naive());
+    (merged_rows, merged_chunks)
+}
+
+/// Merge crash dumps left in `dir` by a previous run, persist the result and archive the
+/// dumps. Unreadable dumps are archived too so they are not retried forever; dumps stay in
+/// place when the merged state cannot be saved. Returns the number of dumps merged.
+pub fn recover_crash_dumps(state: &mut CollectorState, dir: &Path) -> usize {
+    let dumps = load_crash_dumps(dir);
+    if dumps.is_empty() {
+        return 0;
+    }
+    let mut merged = 0;
+    let mut handled = Vec::new();
+    for (path, dump) in dumps {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match dump {
+            Ok(dump) => {
+                let (rows, chunks) = merge_crash_dump(state, dump);
+                merged += 1;
+                let _ = append_app_log(
+                    &state.app_log_path,
+                    &format!("merged crash dump {name}: {rows} rows, {chunks} chunks"),
+                );
+            }
+            Err(err) => {
+                let _ = append_app_log(
+                    &state.app_log_path,
+                    &format!("skipped unreadable crash dump {name}: {err}"),
+                );
+            }
+        }
+        handled.push(path);
+    }
+    if merged > 0 {
+        if let Err(err) = state.flush_to_disk() {
+            let _ = append_app_log(
+                &state.app_log_path,
+                &format!("failed to persist merged crash dumps: {err}"),
+            );
+            return merged;
+        }
+    }
+    for path in handled {
+        if let Err(err) = archive_crash_dump(&path) {
+            let _ = append_app_log(
+                &state.app_log_path,
+                &format!("failed to archive crash dump {}: {err}", path.display()),
+            );
+        }
+    }
+    merged
+}
@@ -0,0 +1,103 @@
+//! Current typing context module.
+//! Tracks which app is receiving typing right now and since when, for the live dashboard.
+
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::app_config::WindowTitleMode;
+
+use super::event_time::epoch_ms_at;
+use super::{CaptureContext, CollectorState};
+
+/// App currently receiving typing. `since_ms` (epoch milliseconds) marks when typing in this
+/// app started and only resets on an app change, not on a window title change.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct CurrentContext {
+    pub bundle_id: Option<String>,
+    pub app_name: String,
+    pub window_title: String,
+    pub since_ms: i64,
+}
+
+// Raw context kept in state; the title privacy mode is applied when the snapshot is built, so
+// a mode change hides the title right away.
+#[derive(Clone, Debug)]
+pub(super) struct TypingContext {
+    bundle_id: Option<String>,
+    app_name: String,
+    window_title: String,
+    since_ms: i64,
+}
+
+impl TypingContext {
+    /// App id in the form stats rows use: bundle id, or app name when there is none.
+    pub(super) fn app_id(&self) -> &str {
+        self.bundle_id.as_deref().unwrap_or(&self.app_name)
+    }
+
+    fn same_app(&self, capture_context: &CaptureContext) -> bool {
+        match (&self.bundle_id, &capture_context.bundle_id) {
+            (Some(current), Some(next)) => current == next,
+            (None, None) => self.app_name == capture_context.app_name,
+            _ => false,
+        }
+    }
+}
+
+/// Point the typing context at `capture_context`, keeping `since_ms` while the app is the
+/// same. Called for every counted key-down and, once typing started, on each tick.
+pub(super) fn touch_typing_context(
+    state: &mut CollectorState,
+    capture_context: &CaptureContext,
+    at: Instant,
+) {
+    match state.typing_context.as_mut() {
+        Some(current) if current.same_app(capture_context) => {
+            current.window_title = capture_context.window_title.clone();
+            current.app_name = capture_context.app_name.clone();
+        }
+        _ => {
+            state.typing_context = Some(TypingContext {
+                bundle_id: capture_context.bundle_id.clone(),
+                app_name: capture_context.app_name.clone(),
+                window_title: capture_context.window_title.clone(),
+                since_ms: epoch_ms_at(at),
+            });
+        }
+    }
+}
+
+/// Tick update: follow the frontmost app while typing continues and clear the context once
+/// typing stopped for longer than the session gap. Pauses clear it in the tick handler.
+pub(super) fn refresh_typing_context(
+    state: &mut CollectorState,
+    capture_context: &CaptureContext,
+    at: Instant,
+) {
+    if state.typing_context.is_none() {
+        return;
+    }
+    let idle = state.pressed_non_modifier_keys.is_empty()
+        && at.saturating_duration_since(state.last_typing_instant) > state.session_gap;
+    if idle {
+        state.typing_context = None;
+        return;
+    }
+    touch_typing_context(state, capture_context, at);
+}
+
+/// Snapshot view of the typing context with the window title privacy mode applied.
+pub(super) fn current_context(state: &CollectorState) -> Option<CurrentContext> {
+    let context = state.typing_context.as_ref()?;
+    let window_title = match state.resolve_window_title_mode(context.bundle_id.as_deref()) {
+        WindowTitleMode::Full => context.window_title.clone(),
+        WindowTitleMode::Hidden => String::new(),
+    };
+    Some(CurrentContext {
+        bundle_id: context.bundle_id.clone(),
+        app_name: context.app_name.clone(),
+        window_title,
+        since_ms: context.since_ms,
+    })
+}
@@ -0,0 +1,172 @@
+//! Deep work module.
+//! Finds deep work blocks in the minute-keyed stats rows: runs of consecutive 5-minute buckets
+//! that each kept typing above a density threshold, lasting at least the minimum block length.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::Local;
+use serde::Serialize;
+
+use crate::app_config::AppConfig;
+
+use super::shortcut::{local_day_window_ms, shortcut_range_window_ms};
+use super::timeline::local_timestamp_ms;
+use super::CollectorState;
+
+const BUCKET_MINUTES: i64 = 5;
+const MINUTE_MS: i64 = 60_000;
+// How far beyond a range rows are read, so a block crossing midnight at either end is found
+// whole instead of as a too-short part.
+const MARGIN_MS: i64 = 24 * 60 * MINUTE_MS;
+
+/// Deep work rules: at least `min_minutes` of consecutive buckets with `min_keys_per_bucket`
+/// key-downs each.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct DeepWorkThresholds {
+    pub(super) min_minutes: u64,
+    pub(super) min_keys_per_bucket: u64,
+}
+
+impl DeepWorkThresholds {
+    pub(super) fn from_config(config: &AppConfig) -> Self {
+        let effective = config.effective();
+        Self {
+            min_minutes: effective.deep_work_min_minutes,
+            min_keys_per_bucket: effective.deep_work_min_keys_per_5min,
+        }
+    }
+}
+
+/// One deep work block, aligned to 5-minute buckets.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct DeepWorkBlock {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub minutes: u64,
+    pub key_count: u64,
+    /// App with the most keys in the block.
+    pub dominant_app: String,
+}
+
+/// Deep work blocks in a series of per-minute key counts by app, keyed by minutes since the
+/// Unix epoch. Minutes missing from the series (idle, auto-paused, app not running) count as
+/// zero, so a gap breaks a block rather than being bridged; the series may span midnight.
+pub(super) fn detect_deep_work_blocks(
+    minutes: &BTreeMap<i64, HashMap<String, u64>>,
+    thresholds: DeepWorkThresholds,
+) -> Vec<DeepWorkBlock> {
+    let mut buckets: BTreeMap<i64, HashMap<&str, u64>> = BTreeMap::new();
+    for (minute, by_app) in minutes {
+        let bucket = buckets
+            .entry(minute.div_euclid(BUCKET_MINUTES))
+            .or_default();
+        for (app, keys) in by_app {
+            *bucket.entry(app.as_str()).or_default() += keys;
+        }
+    }
+    let mut blocks = Vec::new();
+    let mut run: Vec<(i64, &HashMap<&str, u64>)> = Vec::new();
+    let is_dense = |by_app: &HashMap<&str, u64>| {
+        by_app.values().sum::<u64>() >= thresholds.min_keys_per_bucket.max(1)
+    };
+    for (bucket, by_app) in &buckets {
+        let extends = run.last().is_some_and(|(last, _)| *last + 1 == *bucket);
+        if !extends || !is_dense(by_app) {
+            blocks.extend(close_run(&run, thresholds));
+            run.clear();
+        }
+        if is_dense(by_app) {
+            run.push((*bucket, by_app));
+        }
+    }
+    blocks.extend(close_run(&run, thresholds));
+    blocks
+}
+
+fn close_run(
+    run: &[(i64, &HashMap<&str, u64>)],
+    thresholds: DeepWorkThresholds,
+) -> Option<DeepWorkBlock> {
+    let (first, _) = run.first()?;
+    let (last, _) = run.last()?;
+    let minutes = (last - first + 1) * BUCKET_MINUTES;
+    if (minutes as u64) < thresholds.min_minutes {
+        return None;
+    }
+    let mut by_app: HashMap<&str, u64> = HashMap::new();
+    for (_, bucket) in run {
+        for (app, keys) in bucket.iter() {
+            *by_app.entry(app).or_default() += keys;
+        }
+    }
+    let dominant_app = by_app
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(app, _)| app.to_string())
+        .unwrap_or_default();
+    Some(DeepWorkBlock {
+        start_ms: first * BUCKET_MINUTES * MINUTE_MS,
+        end_ms: (last + 1) * BUCKET_MINUTES * MINUTE_MS,
+        minutes: minutes as u64,
+        key_count: by_app.values().sum(),
+        dominant_app,
+    })
+}
+
+// Per-minute key counts by app of the stats rows in [start_ms, end_ms). Days rolled up to
+// hours put a whole hour into one minute and never form a block.
+fn minute_series(
+    state: &CollectorState,
+    start_ms: i64,
+    end_ms: i64,
+) -> BTreeMap<i64, HashMap<String, u64>> {
+    let mut minutes: BTreeMap<i64, HashMap<String, u64>> = BTreeMap::new();
+    for (key, value) in &state.stats {
+        if value.key_count == 0 {
+            continue;
+        }
+        let Some(row_ms) = local_timestamp_ms(&key.date.to_naive_datetime()) else {
+            continue;
+        };
+        if row_ms < start_ms || row_ms >= end_ms {
+            continue;
+        }
+        *minutes
+            .entry(row_ms.div_euclid(MINUTE_MS))
+            .or_default()
+            .entry(key.app_name.clone())
+            .or_default() += value.key_count;
+    }
+    minutes
+}
+
+// Blocks overlapping [start_ms, end_ms), whole even when they cross either end.
+fn blocks_in_window(state: &CollectorState, start_ms: i64, end_ms: i64) -> Vec<DeepWorkBlock> {
+    let minutes = minute_series(state, start_ms - MARGIN_MS, end_ms + MARGIN_MS);
+    detect_deep_work_blocks(&minutes, state.deep_work_thresholds)
+        .into_iter()
+        .filter(|block| block.end_ms > start_ms && block.start_ms < end_ms)
+        .collect()
+}
+
+/// Deep work blocks by range (`today` / `yesterday` / `7d`), oldest first. A block that
+/// crosses a range end is included whole.
+pub fn snapshot_deep_work_blocks(state: &CollectorState, range: &str) -> Vec<DeepWorkBlock> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    blocks_in_window(state, start_ms, end_ms)
+}
+
+/// Minutes of deep work that fall on today; a block that began yesterday counts from midnight.
+pub(super) fn deep_work_minutes_today(state: &CollectorState) -> u64 {
+    let Some((start_ms, end_ms)) = local_day_window_ms(Local::now().date_naive()) else {
+        return 0;
+    };
+    blocks_in_window(state, start_ms, end_ms)
+        .iter()
+        .map(|block| {
+            let overlap_ms = block.end_ms.min(end_ms) - block.start_ms.max(start_ms);
+            (overlap_ms.max(0) / MINUTE_MS) as u64
+        })
+        .sum()
+}
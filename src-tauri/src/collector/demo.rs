@@ -0,0 +1,41 @@
+//! Demo mode module.
+//! The memory-only collector state demo mode runs on, seeded with a week of generated history
+//! from the fixture generators.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+
+use crate::app_config::AppConfig;
+use crate::shutdown::Shutdown;
+
+use super::fixtures::{generate_history, seeded_collector_state};
+use super::CollectorState;
+
+/// Seed of the history demo mode starts with.
+pub(crate) const DEMO_SEED: u64 = 0x5459_5045_5055_4c53;
+/// Days of history demo mode starts with, today included.
+pub(crate) const DEMO_DAYS: u32 = 7;
+
+/// Collector state for demo mode: `config` applied, the demo history loaded from memory
+/// storage and `demo_mode` set, so nothing is written to the data files.
+pub fn new_demo_collector_state(
+    log_path: PathBuf,
+    app_log_path: PathBuf,
+    detail_path: PathBuf,
+    config: &AppConfig,
+    shutdown: Arc<Shutdown>,
+    end: NaiveDateTime,
+) -> CollectorState {
+    let mut state = seeded_collector_state(
+        log_path,
+        app_log_path,
+        detail_path,
+        config,
+        shutdown,
+        generate_history(DEMO_SEED, end, DEMO_DAYS),
+    );
+    state.demo_mode = true;
+    state
+}
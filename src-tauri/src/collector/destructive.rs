@@ -0,0 +1,259 @@
+//! Destructive action module.
+//! Previews what `reset_all`, `reset_today` and `prune_retention` would touch without changing
+//! anything, and runs them once the caller confirmed the preview.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+
+use super::shortcut::{local_day_window_ms, shortcut_usage_in_window, InputEventChunk};
+use super::{append_app_log, CollectorState, TodayTotals, DAY_KEY_FORMAT};
+use crate::storage::{DetailStorage, StoredFile};
+
+/// A destructive command that must be previewed and confirmed first.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DestructiveAction {
+    /// Delete every stats row, event chunk and shortcut total.
+    ResetAll,
+    /// Delete what was recorded on the current local day.
+    ResetToday,
+    /// Merge minute rows older than the minute resolution window into hour rows.
+    PruneRetention,
+}
+
+impl DestructiveAction {
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "reset_all" => Some(Self::ResetAll),
+            "reset_today" => Some(Self::ResetToday),
+            "prune_retention" => Some(Self::PruneRetention),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ResetAll => "reset_all",
+            Self::ResetToday => "reset_today",
+            Self::PruneRetention => "prune_retention",
+        }
+    }
+}
+
+/// What an action would delete or rewrite. Files are the detail and analytics files of the
+/// affected days; `confirm_token` is filled in by the command that issued the preview.
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DestructivePreview {
+    pub action: String,
+    pub stats_rows: u64,
+    pub event_chunks: u64,
+    pub shortcut_entries: u64,
+    pub files: u64,
+    pub file_bytes: u64,
+    pub confirm_token: String,
+    pub expires_in_ms: u64,
+}
+
+// Files holding the given days; None selects every file, legacy monolithic files included.
+fn files_for_days(state: &CollectorState, days: Option<&HashSet<NaiveDate>>) -> Vec<StoredFile> {
+    state
+        .storage
+        .stored_files()
+        .into_iter()
+        .filter(|file| match days {
+            None => true,
+            Some(days) => file.day.is_some_and(|day| days.contains(&day)),
+        })
+        .collect()
+}
+
+fn is_in_window(chunk_start_ms: i64, (start_ms, end_ms): (i64, i64)) -> bool {
+    chunk_start_ms >= start_ms && chunk_start_ms < end_ms
+}
+
+// Count what the action touches; shared by the preview and the audit log line.
+fn measure(
+    state: &CollectorState,
+    action: DestructiveAction,
+    today: NaiveDate,
+) -> DestructivePreview {
+    let mut preview = DestructivePreview {
+        action: action.as_str().to_string(),
+        ..DestructivePreview::default()
+    };
+    let files = match action {
+        DestructiveAction::ResetAll => {
+            preview.stats_rows = state.stats.len() as u64;
+            preview.event_chunks =
+                (state.event_chunks.len() + usize::from(state.open_event_chunk.is_some())) as u64;
+            preview.shortcut_entries = state.shortcut_usage.len() as u64;
+            files_for_days(state, None)
+        }
+        DestructiveAction::ResetToday => {
+            preview.stats_rows = state
+                .stats
+                .keys()
+                .filter(|key| key.date.day() == today)
+                .count() as u64;
+            if let Some(window) = local_day_window_ms(today) {
+                preview.event_chunks = state
+                    .event_chunks
+                    .iter()
+                    .map(|chunk| chunk.chunk_start_ms)
+                    .chain(
+                        state
+                            .open_event_chunk
+                            .iter()
+                            .map(|chunk| chunk.chunk_start_ms),
+                    )
+                    .filter(|start_ms| is_in_window(*start_ms, window))
+                    .count() as u64;
+                preview.shortcut_entries =
+                    shortcut_usage_in_window(state, window.0, window.1, None).len() as u64;
+            }
+            files_for_days(state, Some(&HashSet::from([today])))
+        }
+        DestructiveAction::PruneRetention => {
+            let days = state.rollup_candidate_days();
+            preview.stats_rows = state
+                .stats
+                .keys()
+                .filter(|key| days.contains(&key.date.day()))
+                .count() as u64;
+            // Only the detail files are rewritten; analytics files stay as they are.
+            files_for_days(state, Some(&days))
+                .into_iter()
+                .filter(|file| !is_analytics_file(&file.path))
+                .collect()
+        }
+    };
+    preview.files = files.len() as u64;
+    preview.file_bytes = files.iter().map(|file| file.bytes).sum();
+    preview
+}
+
+fn is_analytics_file(path: &std::path::Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.contains("-analytics-") || name.starts_with("analytics-"))
+}
+
+// Delete files listed by `storage`, which then stops watching them for external changes.
+fn remove_files(storage: &dyn DetailStorage, paths: &[PathBuf]) -> Result<(), String> {
+    for path in paths {
+        match std::fs::remove_file(path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(format!("failed to remove {}: {err}", path.display())),
+        }
+    }
+    storage.forget_files(paths);
+    Ok(())
+}
+
+// Drop everything recorded on `today`: its rows, chunks, shortcut uses and per-day ledgers.
+fn reset_day(state: &mut CollectorState, today: NaiveDate) {
+    if let Some(window) = local_day_window_ms(today) {
+        let day_usage = shortcut_usage_in_window(state, window.0, window.1, None);
+        for (shortcut_id, removed) in day_usage {
+            let Some(usage) = state.shortcut_usage.get_mut(&shortcut_id) else {
+                continue;
+            };
+            usage.count = usage.count.saturating_sub(removed.count);
+            for (app_id, count) in removed.by_app {
+                if let Some(app_count) = usage.by_app.get_mut(&app_id) {
+                    *app_count = app_count.saturating_sub(count);
+                }
+            }
+            usage.by_app.retain(|_, count| *count > 0);
+            if usage.count == 0 {
+                state.shortcut_usage.remove(&shortcut_id);
+            }
+        }
+        let (removed, kept): (Vec<InputEventChunk>, Vec<InputEventChunk>) =
+            std::mem::take(&mut state.event_chunks)
+                .into_iter()
+                .partition(|chunk| is_in_window(chunk.chunk_start_ms, window));
+        state.event_chunks = kept;
+        for chunk in &removed {
+            state.chunk_counters.remove(chunk);
+        }
+        if state
+            .open_event_chunk
+            .as_ref()
+            .is_some_and(|chunk| is_in_window(chunk.chunk_start_ms, window))
+        {
+            state.open_event_chunk = None;
+        }
+    }
+    state.stats.retain(|key, _| key.date.day() != today);
+    let day_key = today.format(DAY_KEY_FORMAT).to_string();
+    state.chord_aborts.remove(&day_key);
+    state.app_switches.remove(&day_key);
+    state.focus_days.remove(&day_key);
+    state.coverage.remove(&day_key);
+    state.burst_days.remove(&day_key);
+    state.effort_days.remove(&day_key);
+    state.display_days.remove(&day_key);
+    state.copy_paste_days.remove(&day_key);
+    state.key_spans.remove(&day_key);
+    state.today_totals = TodayTotals::default();
+    state.active_stats_key = None;
+    state.chord_attempt = None;
+    state.burst_run = None;
+    state.pending_copy = None;
+}
+
+/// Count what `action` would delete without changing anything.
+pub fn preview_destructive_action(
+    state: &CollectorState,
+    action: DestructiveAction,
+) -> DestructivePreview {
+    measure(state, action, Local::now().date_naive())
+}
+
+impl CollectorState {
+    /// Run a confirmed destructive action: delete the affected files, apply it in memory, write
+    /// back what is left and log an audit line with the counts from the preview.
+    pub fn run_destructive_action(&mut self, action: DestructiveAction) -> Result<(), String> {
+        self.ensure_history_loaded()?;
+        let today = Local::now().date_naive();
+        let report = measure(self, action, today);
+        let result = match action {
+            DestructiveAction::ResetAll => {
+                let files: Vec<PathBuf> = files_for_days(self, None)
+                    .into_iter()
+                    .map(|file| file.path)
+                    .collect();
+                self.clear_stats();
+                remove_files(self.storage.as_ref(), &files).and_then(|_| self.flush_to_disk())
+            }
+            DestructiveAction::ResetToday => {
+                let files: Vec<PathBuf> = files_for_days(self, Some(&HashSet::from([today])))
+                    .into_iter()
+                    .map(|file| file.path)
+                    .collect();
+                reset_day(self, today);
+                remove_files(self.storage.as_ref(), &files).and_then(|_| self.flush_to_disk())
+            }
+            DestructiveAction::PruneRetention => self.rollup_old_stats().map(|_| ()),
+        };
+        let _ = append_app_log(
+            &self.app_log_path,
+            &format!(
+                "destructive action {}: stats_rows={} event_chunks={} shortcut_entries={} \
+                 files={} file_bytes={} result={}",
+                report.action,
+                report.stats_rows,
+                report.event_chunks,
+                report.shortcut_entries,
+                report.files,
+                report.file_bytes,
+                result.as_ref().map_or_else(|err| err.as_str(), |_| "ok"),
+            ),
+        );
+        result
+    }
+}
@@ -0,0 +1,277 @@
+//! Display attribution module.
+//! Maps the active window to the display showing most of it and keeps per-day keystroke
+//! counts per display. Display bounds are cached and only re-read when the display
+//! configuration changes, never per keystroke.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+use super::shortcut::{days_in_window, local_day_of_ms, shortcut_range_window_ms};
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+/// One display in the coordinate space of active window positions (points from the top-left
+/// of the main display on macOS, physical pixels elsewhere).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayBounds {
+    pub id: String,
+    pub name: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+static DISPLAYS: Mutex<Vec<DisplayBounds>> = Mutex::new(Vec::new());
+
+/// Replace the cached display list after a display configuration change.
+pub fn set_displays(displays: Vec<DisplayBounds>) {
+    if let Ok(mut cached) = DISPLAYS.lock() {
+        *cached = displays;
+    }
+}
+
+fn cached_display_name(display_id: &str) -> Option<String> {
+    let cached = DISPLAYS.lock().ok()?;
+    cached
+        .iter()
+        .find(|display| display.id == display_id)
+        .map(|display| display.name.clone())
+}
+
+/// Display of a window from the cached list; None until displays are known.
+pub(super) fn display_of_window(x: f64, y: f64, width: f64, height: f64) -> Option<String> {
+    let cached = DISPLAYS.lock().ok()?;
+    display_at(&cached, x, y, width, height)
+}
+
+// Display sharing the largest area with the window, the first listed on ties. A window
+// without size counts as a one-unit square at its origin.
+pub(super) fn display_at(
+    displays: &[DisplayBounds],
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) -> Option<String> {
+    let (width, height) = (width.max(1.0), height.max(1.0));
+    let mut best: Option<(&DisplayBounds, f64)> = None;
+    for display in displays {
+        let overlap_w = (x + width).min(display.x + display.width) - x.max(display.x);
+        let overlap_h = (y + height).min(display.y + display.height) - y.max(display.y);
+        if overlap_w <= 0.0 || overlap_h <= 0.0 {
+            continue;
+        }
+        let area = overlap_w * overlap_h;
+        if best.is_none_or(|(_, best_area)| area > best_area) {
+            best = Some((display, area));
+        }
+    }
+    best.map(|(display, _)| display.id.clone())
+}
+
+/// Count one key-down on `display_id` for the local day of `at_ms`.
+pub(super) fn record_display_key(state: &mut CollectorState, display_id: Option<&str>, at_ms: i64) {
+    let (Some(display_id), Some(day)) = (display_id, local_day_of_ms(at_ms)) else {
+        return;
+    };
+    *state
+        .display_days
+        .entry(day.format(DAY_KEY_FORMAT).to_string())
+        .or_default()
+        .entry(display_id.to_string())
+        .or_default() += 1;
+}
+
+/// Keystrokes on one display over the range.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DisplayRow {
+    pub display_id: String,
+    /// User label from `display_labels`, else the system display name, else the id.
+    pub label: String,
+    pub key_count: u64,
+    pub share_pct: f64,
+}
+
+/// Keystrokes per display on one local day (`YYYY-MM-DD`).
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DisplayDayRow {
+    pub date: String,
+    pub counts: BTreeMap<String, u64>,
+}
+
+/// Keystrokes per display for a range, most used display first, with one row per local day.
+/// Keys typed while the display was unknown are not counted.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DisplayStats {
+    pub range: String,
+    pub total_keys: u64,
+    pub displays: Vec<DisplayRow>,
+    pub days: Vec<DisplayDayRow>,
+}
+
+/// Build display statistics by range: `today` / `yesterday` / `7d`.
+pub fn snapshot_display_stats(
+    state: &CollectorState,
+    range: &str,
+    labels: &BTreeMap<String, String>,
+) -> DisplayStats {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let mut totals: HashMap<&str, u64> = HashMap::new();
+    let days = days_in_window(start_ms, end_ms)
+        .into_iter()
+        .map(|date| {
+            let mut counts = BTreeMap::new();
+            for (display_id, count) in state.display_days.get(&date).into_iter().flatten() {
+                *totals.entry(display_id.as_str()).or_default() += count;
+                counts.insert(display_id.clone(), *count);
+            }
+            DisplayDayRow { date, counts }
+        })
+        .collect();
+    let total_keys: u64 = totals.values().sum();
+    let mut displays: Vec<DisplayRow> = totals
+        .into_iter()
+        .map(|(display_id, key_count)| DisplayRow {
+            display_id: display_id.to_string(),
+            label: labels
+                .get(display_id)
+                .cloned()
+                .or_else(|| cached_display_name(display_id))
+                .unwrap_or_else(|| display_id.to_string()),
+            key_count,
+            share_pct: key_count as f64 * 100.0 / total_keys.max(1) as f64,
+        })
+        .collect();
+    displays.sort_by(|a, b| {
+        b.key_count
+            .cmp(&a.key_count)
+            .then_with(|| a.display_id.cmp(&b.display_id))
+    });
+    DisplayStats {
+        range: range.to_string(),
+        total_keys,
+        displays,
+        days,
+    }
+}
+
+/// Read the displays now and refresh the cache whenever the display configuration changes.
+/// macOS only; elsewhere the app feeds `set_displays` from its monitor list.
+pub fn watch_displays() {
+    platform::watch_displays();
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::sync::Once;
+
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSRect, NSString};
+    use objc::declare::ClassDecl;
+    use objc::runtime::{Object, Sel};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    use super::{set_displays, DisplayBounds};
+
+    pub(super) fn watch_displays() {
+        static INSTALL: Once = Once::new();
+        refresh_displays();
+        INSTALL.call_once(|| unsafe {
+            let Some(mut decl) = ClassDecl::new("TypePulseScreenObserver", class!(NSObject)) else {
+                return;
+            };
+            decl.add_method(
+                sel!(screenParametersChanged:),
+                screen_parameters_changed as extern "C" fn(&Object, Sel, id),
+            );
+            let class = decl.register();
+            // Never released: it observes until exit.
+            let observer: id = msg_send![class, new];
+            let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let name =
+                NSString::alloc(nil).init_str("NSApplicationDidChangeScreenParametersNotification");
+            let _: () = msg_send![center,
+                addObserver: observer
+                selector: sel!(screenParametersChanged:)
+                name: name
+                object: nil];
+            let _: () = msg_send![name, release];
+        });
+    }
+
+    extern "C" fn screen_parameters_changed(_this: &Object, _cmd: Sel, _notification: id) {
+        refresh_displays();
+    }
+
+    // NSScreen frames have their origin at the bottom-left of the main display; window
+    // positions use the top-left, so frames are flipped against the main display's height.
+    // Must run on the main thread, like every NSScreen call.
+    fn refresh_displays() {
+        let mut displays = Vec::new();
+        unsafe {
+            let screens: id = msg_send![class!(NSScreen), screens];
+            if screens == nil {
+                return;
+            }
+            let count: usize = msg_send![screens, count];
+            let mut main_height = 0.0;
+            for index in 0..count {
+                let screen: id = msg_send![screens, objectAtIndex: index];
+                let frame: NSRect = msg_send![screen, frame];
+                if index == 0 {
+                    main_height = frame.size.height;
+                }
+                let key = NSString::alloc(nil).init_str("NSScreenNumber");
+                let description: id = msg_send![screen, deviceDescription];
+                let number: id = msg_send![description, objectForKey: key];
+                let _: () = msg_send![key, release];
+                let display_number: u32 = if number == nil {
+                    index as u32
+                } else {
+                    msg_send![number, unsignedIntValue]
+                };
+                let responds: bool = msg_send![screen, respondsToSelector: sel!(localizedName)];
+                let name = if responds {
+                    let name: id = msg_send![screen, localizedName];
+                    nsstring_to_string(name)
+                } else {
+                    String::new()
+                };
+                displays.push(DisplayBounds {
+                    id: format!("display-{}", display_number),
+                    name: if name.is_empty() {
+                        format!("Display {}", index + 1)
+                    } else {
+                        name
+                    },
+                    x: frame.origin.x,
+                    y: main_height - frame.origin.y - frame.size.height,
+                    width: frame.size.width,
+                    height: frame.size.height,
+                });
+            }
+        }
+        set_displays(displays);
+    }
+
+    unsafe fn nsstring_to_string(value: id) -> String {
+        if value == nil {
+            return String::new();
+        }
+        let bytes: *const std::os::raw::c_char = msg_send![value, UTF8String];
+        if bytes.is_null() {
+            return String::new();
+        }
+        std::ffi::CStr::from_ptr(bytes)
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    pub(super) fn watch_displays() {}
+}
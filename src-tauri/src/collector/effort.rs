@@ -0,0 +1,104 @@
+//! Typing effort module.
+//! Weighs every counted key-down by how far the finger travels from the home row and keeps
+//! per-day effort totals. The weights are a rough finger-distance proxy, not a measurement.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+
+use super::shortcut::{days_in_window, shortcut_range_window_ms};
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+// Weights are kept in thousandths so per-day totals stay exact integers.
+const EFFORT_SCALE: f64 = 1000.0;
+// Resting keys under the fingers and the thumb on the space bar.
+const WEIGHT_HOME: u64 = 1000;
+// Index fingers reaching inward on the home row.
+const WEIGHT_HOME_REACH: u64 = 1200;
+const WEIGHT_ADJACENT_ROW: u64 = 1500;
+const WEIGHT_NUMBER_ROW: u64 = 2000;
+// Keys that take the hand off the home row: function row, navigation cluster, arrows.
+const WEIGHT_OFF_HOME: u64 = 2500;
+// Added on top of the row weight for keys the pinky reaches outside its resting key.
+const PINKY_EXTRA: u64 = 500;
+
+/// Effort weight of one key-down in thousandths of a home-row press, looked up by the
+/// normalized key name. Unknown keys weigh as much as a home-row key.
+pub(super) fn key_effort_milli(key: &str) -> u64 {
+    match key {
+        "a" | "s" | "d" | "f" | "j" | "k" | "l" | ";" | "space" => WEIGHT_HOME,
+        "g" | "h" => WEIGHT_HOME_REACH,
+        "w" | "e" | "r" | "t" | "y" | "u" | "i" | "o" | "x" | "c" | "v" | "b" | "n" | "m" | ","
+        | "." => WEIGHT_ADJACENT_ROW,
+        "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => WEIGHT_NUMBER_ROW,
+        "q" | "p" | "z" | "/" | "'" | "[" | "]" | "\\" | "tab" | "capslock" | "enter"
+        | "intlbackslash" => WEIGHT_ADJACENT_ROW + PINKY_EXTRA,
+        "`" | "1" | "0" | "-" | "=" | "backspace" => WEIGHT_NUMBER_ROW + PINKY_EXTRA,
+        "esc" | "f1" | "f2" | "f3" | "f4" | "f5" | "f6" | "f7" | "f8" | "f9" | "f10" | "f11"
+        | "f12" | "insert" | "delete" | "home" | "end" | "pageup" | "pagedown" | "up" | "down"
+        | "left" | "right" => WEIGHT_OFF_HOME,
+        _ => WEIGHT_HOME,
+    }
+}
+
+/// Add one key-down to the per-day totals (thousandths) of the local day of `at_ms`.
+pub(super) fn add_key_effort(days: &mut HashMap<String, u64>, key: &str, at_ms: i64) {
+    let Some(at) = DateTime::<Utc>::from_timestamp_millis(at_ms) else {
+        return;
+    };
+    let day = at.with_timezone(&Local).format(DAY_KEY_FORMAT).to_string();
+    let total = days.entry(day).or_default();
+    *total = total.saturating_add(key_effort_milli(key));
+}
+
+/// Add a counted key-down to the effort totals. `at_ms` is the timestamp written to the event
+/// chunk.
+pub(super) fn record_key_effort(state: &mut CollectorState, key: &str, at_ms: i64) {
+    add_key_effort(&mut state.effort_days, key, at_ms);
+}
+
+/// Effort of today's local day, relative to home-row presses.
+pub(super) fn today_effort(state: &CollectorState) -> f64 {
+    let today = Local::now().format(DAY_KEY_FORMAT).to_string();
+    state.effort_days.get(&today).copied().unwrap_or_default() as f64 / EFFORT_SCALE
+}
+
+/// Typing effort of one local day (`YYYY-MM-DD`).
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct EffortDayRow {
+    pub date: String,
+    pub effort: f64,
+}
+
+/// Typing effort for a range with one row per local day. Effort is the key-down count
+/// weighted by finger travel, so one home-row press adds 1.0.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct EffortStats {
+    pub range: String,
+    pub effort: f64,
+    pub days: Vec<EffortDayRow>,
+}
+
+/// Build effort statistics by range: `today` / `yesterday` / `7d`.
+pub fn snapshot_effort_stats(state: &CollectorState, range: &str) -> EffortStats {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let mut total = 0u64;
+    let days = days_in_window(start_ms, end_ms)
+        .into_iter()
+        .map(|date| {
+            let milli = state.effort_days.get(&date).copied().unwrap_or_default();
+            total = total.saturating_add(milli);
+            EffortDayRow {
+                date,
+                effort: milli as f64 / EFFORT_SCALE,
+            }
+        })
+        .collect();
+    EffortStats {
+        range: range.to_string(),
+        effort: total as f64 / EFFORT_SCALE,
+        days,
+    }
+}
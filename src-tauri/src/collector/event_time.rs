@@ -0,0 +1,80 @@
+//! Key event time module.
+//! Converts the timestamp the OS put on a keyboard event into wall-clock milliseconds and a
+//! matching monotonic instant, so lock waits or a listener backlog do not skew inter-key
+//! intervals. Events without a usable timestamp fall back to the time they are processed.
+
+use std::time::{Duration, Instant};
+
+// Oldest event timestamp accepted, relative to processing time; anything older is treated as
+// a bad clock reading rather than a real backlog.
+const MAX_EVENT_LAG_MS: i64 = 10_000;
+
+/// Time of one key event in both clocks the collector uses.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct EventTime {
+    pub(super) at: Instant,
+    pub(super) at_ms: i64,
+}
+
+impl EventTime {
+    /// Event time from an OS timestamp in epoch milliseconds, clamped to the last
+    /// `MAX_EVENT_LAG_MS` before now; now when the OS gave none.
+    pub(super) fn from_event_ms(event_ms: Option<i64>) -> Self {
+        let now = Instant::now();
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let lag_ms = event_ms.map_or(0, |event_ms| (now_ms - event_ms).clamp(0, MAX_EVENT_LAG_MS));
+        Self {
+            at: now
+                .checked_sub(Duration::from_millis(lag_ms as u64))
+                .unwrap_or(now),
+            at_ms: now_ms - lag_ms,
+        }
+    }
+}
+
+/// Wall-clock time of a monotonic instant; test harnesses tick with instants ahead of now.
+pub(super) fn epoch_ms_at(at: Instant) -> i64 {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let now = Instant::now();
+    if at >= now {
+        now_ms + (at - now).as_millis() as i64
+    } else {
+        now_ms - (now - at).as_millis() as i64
+    }
+}
+
+/// Epoch milliseconds of an event `SystemTime` such as rdev's `Event::time`.
+#[cfg(not(target_os = "macos"))]
+pub(super) fn system_time_ms(time: std::time::SystemTime) -> Option<i64> {
+    let since_epoch = time.duration_since(std::time::UNIX_EPOCH).ok()?;
+    i64::try_from(since_epoch.as_millis()).ok()
+}
+
+/// Epoch milliseconds of a `CGEventGetTimestamp` value (mach absolute time). Mach time stops
+/// while the machine sleeps, so the offset to the wall clock is calibrated at every call
+/// instead of once at startup.
+#[cfg(target_os = "macos")]
+pub(super) fn mach_event_ms(timestamp: u64) -> Option<i64> {
+    #[repr(C)]
+    struct MachTimebaseInfo {
+        numer: u32,
+        denom: u32,
+    }
+    extern "C" {
+        fn mach_absolute_time() -> u64;
+        fn mach_timebase_info(info: *mut MachTimebaseInfo) -> i32;
+    }
+
+    if timestamp == 0 {
+        return None;
+    }
+    let mut timebase = MachTimebaseInfo { numer: 0, denom: 0 };
+    let (now_ticks, status) = unsafe { (mach_absolute_time(), mach_timebase_info(&mut timebase)) };
+    if status != 0 || timebase.denom == 0 {
+        return None;
+    }
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let lag_ticks = now_ticks.saturating_sub(timestamp) as u128;
+    let lag_ns = lag_ticks * timebase.numer as u128 / timebase.denom as u128;
+    Some(now_ms - i64::try_from(lag_ns / 1_000_000).ok()?)
+}
@@ -6,42 +6,147 @@ use std::{
     time::{Duration, Instant},
 };
 
-use chrono::Local;
+use crate::app_config::WindowTitleMode;
+use crate::app_nap::should_prevent_app_nap;
 
+use super::activity::{accrue_active_ms, classify_key_down, ActivityClass};
+use super::app_switch::record_app_switch;
+use super::burst::record_burst_key;
+use super::chord::apply_modifiers_changed;
 use super::context::{auto_pause_reason, is_auto_paused};
+use super::coverage::record_coverage;
+use super::current_context::{refresh_typing_context, touch_typing_context};
+use super::display::record_display_key;
+use super::effort::record_key_effort;
+use super::event_time::EventTime;
+use super::key_names::canonical_key_name;
+use super::key_span::record_key_span;
+use super::live_session::record_live_session_key;
+use super::minute_series::record_minute_key;
 #[cfg(not(target_os = "macos"))]
 use super::modifier::ModifierState;
-use super::shortcut::{append_input_event, update_shortcut_usage};
+use super::sensitive_field::{cached_sensitive_field, likely_sensitive_field};
+use super::shortcut::{append_input_event, is_counted_shortcut, update_shortcut_usage};
+use super::state_api::compute_today_totals;
+use super::title_alias::alias_window_title;
 use super::{
-    capture_context, CaptureContext, CollectorEvent, CollectorState, ModifierSnapshot, StatsKey,
-    StatsValue,
+    append_app_log, CaptureContext, CollectorEvent, CollectorState, MinuteKey, ModifierSnapshot,
+    StatsKey, StatsValue,
 };
 
+/// A key held this long without a key-up or repeat is assumed to have lost its key-up.
+pub(super) const MAX_KEY_HOLD: Duration = Duration::from_secs(30);
+
 // Reset runtime key states when capture is paused to avoid stale key-down state.
 pub(super) fn reset_active_typing_state(state: &mut CollectorState) {
     state.pressed_non_modifier_keys.clear();
     state.active_stats_key = None;
+    state.chord_attempt = None;
+    state.pending_copy = None;
+    state.typing_context = None;
     #[cfg(not(target_os = "macos"))]
     {
         state.modifier_state = ModifierState::default();
     }
 }
 
+// Hold the App Nap assertion while capture runs and typing is recent; log each transition.
+pub(super) fn sync_app_nap(state: &mut CollectorState, now: Instant) {
+    let hold = should_prevent_app_nap(
+        state.prevent_app_nap,
+        !state.paused && !state.auto_paused,
+        now.saturating_duration_since(state.last_typing_instant),
+    );
+    if let Some(held) = state.app_nap.sync(hold) {
+        let message = if held {
+            "app nap assertion taken"
+        } else {
+            "app nap assertion released"
+        };
+        let _ = append_app_log(&state.app_log_path, message);
+    }
+}
+
+// Forget held keys whose key-up went missing (secure input toggles, tap re-enables), so
+// active time stops accruing and the next press of the key counts again.
+fn expire_stale_pressed_keys(state: &mut CollectorState, now: Instant) {
+    let before = state.pressed_non_modifier_keys.len();
+    state
+        .pressed_non_modifier_keys
+        .retain(|_, touched| now.saturating_duration_since(*touched) <= MAX_KEY_HOLD);
+    let expired = before - state.pressed_non_modifier_keys.len();
+    if expired == 0 {
+        return;
+    }
+    if state.pressed_non_modifier_keys.is_empty() {
+        state.active_stats_key = None;
+    }
+    let _ = append_app_log(
+        &state.app_log_path,
+        &format!(
+            "expired {expired} held key(s) without key-up after {}s",
+            MAX_KEY_HOLD.as_secs()
+        ),
+    );
+}
+
+// Key-ups of keys held while the frontmost app changes may be delivered to no one; start the
+// new app with no keys held.
+fn clear_pressed_keys_on_app_change(state: &mut CollectorState) {
+    if state.pressed_non_modifier_keys.is_empty() {
+        return;
+    }
+    state.pressed_non_modifier_keys.clear();
+    state.active_stats_key = None;
+}
+
 // Build the current aggregation key from capture context.
-fn stats_key_from_context(capture_context: &CaptureContext) -> StatsKey {
+fn stats_key_from_context(state: &CollectorState, capture_context: &CaptureContext) -> StatsKey {
     let app_name = capture_context
         .bundle_id
         .clone()
         .unwrap_or_else(|| capture_context.app_name.clone());
+    let window_title = match state.resolve_window_title_mode(capture_context.bundle_id.as_deref()) {
+        WindowTitleMode::Full => {
+            alias_window_title(state, &app_name, capture_context.window_title.clone())
+        }
+        WindowTitleMode::Hidden => String::new(),
+    };
     StatsKey {
-        date: current_minute(),
+        date: MinuteKey::now(),
         app_name,
-        window_title: capture_context.window_title.clone(),
+        window_title,
     }
 }
 
-fn current_minute() -> String {
-    Local::now().format("%Y-%m-%d %H:%M").to_string()
+// Keep cached day totals aligned with a stats write. Must run after the stats entry is updated:
+// when the write lands on a different day than the cache, the cache is rebuilt from stats.
+// `active_ms` is split by `class` like the stats entry.
+fn record_today_totals(
+    state: &mut CollectorState,
+    key: &StatsKey,
+    keys: u64,
+    active_ms: u64,
+    class: ActivityClass,
+) {
+    let day = key.date.day();
+    if state.today_totals.date != Some(day) {
+        state.today_totals = compute_today_totals(&state.stats, day);
+        return;
+    }
+    state.today_totals.key_count += keys;
+    state.today_totals.active_typing_ms += active_ms;
+    match class {
+        ActivityClass::Writing => state.today_totals.writing_ms += active_ms,
+        ActivityClass::Navigation => state.today_totals.navigation_ms += active_ms,
+    }
+    if active_ms > 0 {
+        *state
+            .today_totals
+            .app_active_typing_ms
+            .entry(key.app_name.to_ascii_lowercase())
+            .or_default() += active_ms;
+    }
 }
 
 // Apply a non-modifier key-down event. Repeated key-down of the same physical key is ignored.
@@ -52,42 +157,75 @@ fn apply_non_modifier_key_down(
     modifiers: ModifierSnapshot,
     is_key_combo: bool,
     capture_context: CaptureContext,
-    now: Instant,
+    time: EventTime,
 ) {
+    let EventTime {
+        at: now,
+        at_ms: now_ms,
+    } = time;
+    // Heavy disk work waits for a pause in typing, whether or not this key is counted.
+    state.maintenance.note_key_down(now);
+    // Both listeners' spellings of a symbol key are recorded under one name.
+    let shortcut_key = canonical_key_name(&shortcut_key, modifiers.shift, state.symbol_key_names);
+    // Any non-modifier key completes the pending chord attempt, even when it is not counted.
+    state.chord_attempt = None;
     state.auto_paused = is_auto_paused(state, &capture_context);
     state.auto_pause_reason = auto_pause_reason(state, &capture_context);
+    if record_app_switch(state, &capture_context, now) {
+        clear_pressed_keys_on_app_change(state);
+    }
     if state.paused
         || state.auto_paused
         || should_ignore_keypress(state.ignore_key_combos, is_key_combo)
     {
         return;
     }
-    if !state.pressed_non_modifier_keys.insert(physical_key_id) {
+    expire_stale_pressed_keys(state, now);
+    // An auto-repeat only refreshes the hold.
+    if state
+        .pressed_non_modifier_keys
+        .insert(physical_key_id, now)
+        .is_some()
+    {
         return;
     }
-    append_input_event(
-        state,
-        &capture_context,
-        'd',
-        &shortcut_key,
-        modifiers,
-        chrono::Utc::now().timestamp_millis(),
-    );
-    update_shortcut_usage(state, &capture_context, &shortcut_key, modifiers);
-    let key = stats_key_from_context(&capture_context);
+    // In a password-like field the key still counts, but leaves no event or shortcut behind.
+    let sensitive_field = likely_sensitive_field(state, &capture_context, now);
+    if !sensitive_field {
+        append_input_event(state, &capture_context, 'd', &shortcut_key, modifiers, time);
+    }
+    record_burst_key(state, now_ms);
+    record_key_effort(state, &shortcut_key, now_ms);
+    record_key_span(state, now_ms);
+    record_minute_key(state, now_ms);
+    record_display_key(state, capture_context.display_id.as_deref(), now_ms);
+    let counted_shortcut = if sensitive_field {
+        is_counted_shortcut(state, &shortcut_key, modifiers)
+    } else {
+        update_shortcut_usage(state, &capture_context, &shortcut_key, modifiers, now_ms)
+    };
+    state.activity_class = classify_key_down(&shortcut_key, counted_shortcut);
+    let key = stats_key_from_context(state, &capture_context);
     let delta = now.duration_since(state.last_typing_instant);
     let session_gap = state.session_gap;
     let entry = state.stats.entry(key.clone()).or_insert(StatsValue {
         active_typing_ms: 0,
         key_count: 0,
         session_count: 0,
+        writing_ms: 0,
+        navigation_ms: 0,
     });
     entry.key_count += 1;
-    if delta > session_gap {
+    let new_session = delta > session_gap;
+    if new_session {
         entry.session_count += 1;
     }
+    record_today_totals(state, &key, 1, 0, ActivityClass::default());
+    record_live_session_key(state, new_session, now);
     state.last_typing_instant = now;
     state.active_stats_key = Some(key);
+    touch_typing_context(state, &capture_context, now);
+    sync_app_nap(state, now);
 }
 
 // Apply a non-modifier key-up event and clear active typing key when all keys are released.
@@ -97,21 +235,36 @@ fn apply_non_modifier_key_up(
     shortcut_key: &str,
     modifiers: ModifierSnapshot,
     capture_context: &CaptureContext,
+    time: EventTime,
 ) {
-    append_input_event(
-        state,
-        capture_context,
-        'u',
-        shortcut_key,
-        modifiers,
-        chrono::Utc::now().timestamp_millis(),
-    );
-    state.pressed_non_modifier_keys.remove(physical_key_id);
+    let shortcut_key = &canonical_key_name(shortcut_key, modifiers.shift, state.symbol_key_names);
+    let held = state
+        .pressed_non_modifier_keys
+        .remove(physical_key_id)
+        .is_some();
+    // The key-up of a key dropped by a pause or exclusion change is not recorded, so nothing
+    // reaches an app once it is excluded.
+    let recordable = held || !(state.paused || is_auto_paused(state, capture_context));
+    if recordable && !cached_sensitive_field(state, capture_context) {
+        append_input_event(state, capture_context, 'u', shortcut_key, modifiers, time);
+    }
     if state.pressed_non_modifier_keys.is_empty() {
         state.active_stats_key = None;
     }
 }
 
+// Re-point the held-key stats row when the frontmost app or window title changed mid-hold, so
+// held time accrues to the document now in front instead of the one active at key-down.
+fn repoint_active_stats_key(state: &mut CollectorState, capture_context: &CaptureContext) {
+    let Some(active) = state.active_stats_key.as_ref() else {
+        return;
+    };
+    let current = stats_key_from_context(state, capture_context);
+    if current.app_name != active.app_name || current.window_title != active.window_title {
+        state.active_stats_key = Some(current);
+    }
+}
+
 // Accumulate active typing time from wall-clock tick while there is at least one key held down.
 fn accumulate_active_typing_for_tick(state: &mut CollectorState, elapsed: Duration, now: Instant) {
     if state.pressed_non_modifier_keys.is_empty() {
@@ -120,12 +273,17 @@ fn accumulate_active_typing_for_tick(state: &mut CollectorState, elapsed: Durati
     let Some(key) = state.active_stats_key.clone() else {
         return;
     };
-    let entry = state.stats.entry(key).or_insert(StatsValue {
+    let elapsed_ms = elapsed.as_millis() as u64;
+    let entry = state.stats.entry(key.clone()).or_insert(StatsValue {
         active_typing_ms: 0,
         key_count: 0,
         session_count: 0,
+        writing_ms: 0,
+        navigation_ms: 0,
     });
-    entry.active_typing_ms += elapsed.as_millis() as u64;
+    let class = state.activity_class;
+    accrue_active_ms(entry, class, elapsed_ms);
+    record_today_totals(state, &key, 0, elapsed_ms, class);
     state.last_typing_instant = now;
 }
 
@@ -135,8 +293,15 @@ pub(super) fn on_non_modifier_key_down(
     shortcut_key: String,
     modifiers: ModifierSnapshot,
     is_key_combo: bool,
+    event_ms: Option<i64>,
 ) {
+    let time = EventTime::from_event_ms(event_ms);
     if let Ok(mut locked) = state.lock() {
+        // Listener threads outlive shutdown; drop their events once it is requested.
+        if locked.shutdown.is_requested() {
+            return;
+        }
+        let capture_context = locked.current_context();
         apply_collector_event(
             &mut locked,
             CollectorEvent::NonModifierKeyDown {
@@ -144,8 +309,9 @@ pub(super) fn on_non_modifier_key_down(
                 shortcut_key,
                 modifiers,
                 is_key_combo,
-                capture_context: capture_context(),
-                at: Instant::now(),
+                capture_context,
+                at: time.at,
+                at_ms: time.at_ms,
             },
         );
     }
@@ -156,15 +322,43 @@ pub(super) fn on_non_modifier_key_up(
     physical_key_id: &str,
     shortcut_key: &str,
     modifiers: ModifierSnapshot,
+    event_ms: Option<i64>,
 ) {
+    let time = EventTime::from_event_ms(event_ms);
     if let Ok(mut locked) = state.lock() {
+        if locked.shutdown.is_requested() {
+            return;
+        }
+        let capture_context = locked.current_context();
         apply_collector_event(
             &mut locked,
             CollectorEvent::NonModifierKeyUp {
                 physical_key_id: physical_key_id.to_string(),
                 shortcut_key: shortcut_key.to_string(),
                 modifiers,
-                capture_context: capture_context(),
+                capture_context,
+                at: time.at,
+                at_ms: time.at_ms,
+            },
+        );
+    }
+}
+
+pub(super) fn on_modifiers_changed(
+    state: &Arc<Mutex<CollectorState>>,
+    modifiers: ModifierSnapshot,
+) {
+    if let Ok(mut locked) = state.lock() {
+        // Skip the context capture on every modifier transition unless tracking is on.
+        if locked.shutdown.is_requested() || !locked.track_chord_aborts {
+            return;
+        }
+        let capture_context = locked.current_context();
+        apply_collector_event(
+            &mut locked,
+            CollectorEvent::ModifiersChanged {
+                modifiers,
+                capture_context,
             },
         );
     }
@@ -180,6 +374,7 @@ pub(super) fn apply_collector_event(state: &mut CollectorState, event: Collector
             is_key_combo,
             capture_context,
             at,
+            at_ms,
         } => apply_non_modifier_key_down(
             state,
             physical_key_id,
@@ -187,20 +382,27 @@ pub(super) fn apply_collector_event(state: &mut CollectorState, event: Collector
             modifiers,
             is_key_combo,
             capture_context,
-            at,
+            EventTime { at, at_ms },
         ),
         CollectorEvent::NonModifierKeyUp {
             physical_key_id,
             shortcut_key,
             modifiers,
             capture_context,
+            at,
+            at_ms,
         } => apply_non_modifier_key_up(
             state,
             &physical_key_id,
             &shortcut_key,
             modifiers,
             &capture_context,
+            EventTime { at, at_ms },
         ),
+        CollectorEvent::ModifiersChanged {
+            modifiers,
+            capture_context,
+        } => apply_modifiers_changed(state, modifiers, &capture_context),
         CollectorEvent::Tick {
             elapsed,
             capture_context,
@@ -208,11 +410,19 @@ pub(super) fn apply_collector_event(state: &mut CollectorState, event: Collector
         } => {
             state.auto_paused = is_auto_paused(state, &capture_context);
             state.auto_pause_reason = auto_pause_reason(state, &capture_context);
+            if record_app_switch(state, &capture_context, at) {
+                clear_pressed_keys_on_app_change(state);
+            }
+            record_coverage(state, elapsed);
+            sync_app_nap(state, at);
             if state.paused || state.auto_paused {
                 reset_active_typing_state(state);
                 return;
             }
+            expire_stale_pressed_keys(state, at);
+            repoint_active_stats_key(state, &capture_context);
             accumulate_active_typing_for_tick(state, elapsed, at);
+            refresh_typing_context(state, &capture_context, at);
         }
     }
 }
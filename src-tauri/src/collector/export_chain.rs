@@ -0,0 +1,342 @@
+//! Export chain module.
+//! Optional signing of exports: a SHA-256 over the canonical content of the file and the hash
+//! of the previous signed export, so each profile keeps a hash chain in `export-chain.json`.
+//! This is tamper evidence, not security: anyone can recompute the hashes of an edited file,
+//! only the local chain shows that a re-signed file is not the one exported.
+
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::app_config::AutoExportFormat;
+use crate::storage::{load_export_chain, save_export_chain, StoredExportChainEntry};
+
+/// Hash chain file next to the detail files.
+pub(super) const EXPORT_CHAIN_FILE: &str = "export-chain.json";
+/// Custom document property holding the signature of a signed workbook.
+pub(super) const XLSX_SIGNATURE_PROPERTY: &str = "typepulse_signature";
+const SIGNATURE_VERSION: u32 = 1;
+// Footer line of a signed CSV export; spreadsheet tools read it as one extra row.
+const CSV_SIGNATURE_PREFIX: &str = "# typepulse-signature ";
+// Top-level field of a signed JSON export; the rows move under `rows`.
+const JSON_SIGNATURE_FIELD: &str = "signature";
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+// Serializes chain updates so two exports signed at once cannot take the same position.
+static CHAIN_LOCK: Mutex<()> = Mutex::new(());
+
+/// Signature embedded in a signed export.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ExportSignature {
+    pub v: u32,
+    /// 1-based position in the chain of the exporting profile.
+    pub position: u64,
+    /// Hash of the previous signed export; all zeros for the first one.
+    pub prev_hash: String,
+    /// SHA-256 (hex) of the canonical content, `prev_hash` and `position`.
+    pub hash: String,
+}
+
+impl ExportSignature {
+    /// `v=1 position=N prev=HEX hash=HEX`, as stored in CSV footers and workbook properties.
+    pub(super) fn to_text(&self) -> String {
+        format!(
+            "v={} position={} prev={} hash={}",
+            self.v, self.position, self.prev_hash, self.hash
+        )
+    }
+
+    fn parse_text(text: &str) -> Option<Self> {
+        let mut signature = ExportSignature {
+            v: 0,
+            position: 0,
+            prev_hash: String::new(),
+            hash: String::new(),
+        };
+        for field in text.split_whitespace() {
+            let (key, value) = field.split_once('=')?;
+            match key {
+                "v" => signature.v = value.parse().ok()?,
+                "position" => signature.position = value.parse().ok()?,
+                "prev" => signature.prev_hash = value.to_string(),
+                "hash" => signature.hash = value.to_string(),
+                _ => {}
+            }
+        }
+        (signature.v > 0 && !signature.hash.is_empty()).then_some(signature)
+    }
+}
+
+/// Result of checking an export against its embedded signature.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ExportVerification {
+    pub path: String,
+    pub signed: bool,
+    /// The content still hashes to the embedded signature.
+    pub valid: bool,
+    pub signature: Option<ExportSignature>,
+    /// Whether this profile's chain has the same hash at the signature's position. None when the
+    /// chain has no entry there, e.g. for a file exported by another profile or machine.
+    pub in_chain: Option<bool>,
+    /// Why the file did not verify.
+    pub reason: Option<String>,
+}
+
+fn chain_hash(prev_hash: &str, position: u64, canonical: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!(
+        "typepulse-export-v{SIGNATURE_VERSION}\n{prev_hash}\n{position}\n"
+    ));
+    hasher.update(canonical);
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Sign `canonical` as the next export in the chain at `chain_path`. `write` embeds the
+/// signature into the file; the chain only advances once it succeeded.
+pub(super) fn sign_export<T>(
+    chain_path: &Path,
+    file_name: &str,
+    canonical: &[u8],
+    write: impl FnOnce(&ExportSignature) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = CHAIN_LOCK
+        .lock()
+        .map_err(|_| "export chain lock failed".to_string())?;
+    let mut chain = load_export_chain(chain_path)?;
+    let (position, prev_hash) = chain
+        .entries
+        .last()
+        .map(|entry| (entry.position + 1, entry.hash.clone()))
+        .unwrap_or_else(|| (1, GENESIS_HASH.to_string()));
+    let signature = ExportSignature {
+        v: SIGNATURE_VERSION,
+        position,
+        hash: chain_hash(&prev_hash, position, canonical),
+        prev_hash,
+    };
+    let written = write(&signature)?;
+    chain.entries.push(StoredExportChainEntry {
+        position,
+        hash: signature.hash.clone(),
+        prev_hash: signature.prev_hash.clone(),
+        file_name: file_name.to_string(),
+        signed_at_ms: chrono::Utc::now().timestamp_millis(),
+    });
+    save_export_chain(chain_path, &chain)?;
+    Ok(written)
+}
+
+// CSV content as hashed: BOM, line endings, trailing blank lines and the signature footer do
+// not count, so a file re-saved with other line endings still verifies.
+fn canonical_csv(content: &str) -> String {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut lines: Vec<&str> = content
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .filter(|line| !line.starts_with(CSV_SIGNATURE_PREFIX))
+        .collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let mut canonical = lines.join("\n");
+    canonical.push('\n');
+    canonical
+}
+
+// Compact JSON with object keys sorted, so formatting and key order do not count.
+fn write_canonical_json(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical_json(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical_json(value, &mut out);
+    out
+}
+
+/// Worksheets and shared strings of a workbook, by part name. Document properties are left
+/// out so the signature can be stored in them after hashing.
+pub(super) fn canonical_xlsx(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let mut names: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.starts_with("xl/worksheets/") || *name == "xl/sharedStrings.xml")
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    let mut canonical = Vec::new();
+    for name in names {
+        let mut part = archive.by_name(&name).map_err(|e| e.to_string())?;
+        canonical.extend_from_slice(name.as_bytes());
+        canonical.push(b'\n');
+        part.read_to_end(&mut canonical)
+            .map_err(|e| e.to_string())?;
+        canonical.push(b'\n');
+    }
+    Ok(canonical)
+}
+
+// Signature property of a workbook, read from `docProps/custom.xml`.
+fn xlsx_signature(bytes: &[u8]) -> Result<Option<ExportSignature>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let mut custom = String::new();
+    match archive.by_name("docProps/custom.xml") {
+        Ok(mut part) => {
+            part.read_to_string(&mut custom)
+                .map_err(|e| e.to_string())?;
+        }
+        Err(_) => return Ok(None),
+    }
+    let marker = format!("name=\"{XLSX_SIGNATURE_PROPERTY}\"");
+    let Some(start) = custom.find(&marker) else {
+        return Ok(None);
+    };
+    let property = &custom[start..];
+    let value = property
+        .split_once("<vt:lpwstr>")
+        .and_then(|(_, rest)| rest.split_once("</vt:lpwstr>"))
+        .map(|(value, _)| value);
+    Ok(value.and_then(ExportSignature::parse_text))
+}
+
+/// Sign the CSV or JSON export at `path` in place, as the auto export does before handing the
+/// file over. A JSON export becomes `{"rows": [...], "signature": {...}}`.
+pub(super) fn sign_text_export(
+    path: &Path,
+    format: AutoExportFormat,
+    chain_path: &Path,
+    file_name: &str,
+) -> Result<ExportSignature, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    match format {
+        AutoExportFormat::Csv => {
+            let canonical = canonical_csv(&content);
+            sign_export(chain_path, file_name, canonical.as_bytes(), |signature| {
+                let mut signed = content.clone();
+                if !signed.ends_with('\n') {
+                    signed.push('\n');
+                }
+                signed.push_str(CSV_SIGNATURE_PREFIX);
+                signed.push_str(&signature.to_text());
+                signed.push('\n');
+                std::fs::write(path, signed).map_err(|e| e.to_string())?;
+                Ok(signature.clone())
+            })
+        }
+        AutoExportFormat::Json => {
+            let rows: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+            let mut document = serde_json::json!({ "rows": rows });
+            let canonical = canonical_json(&document);
+            sign_export(chain_path, file_name, canonical.as_bytes(), |signature| {
+                document[JSON_SIGNATURE_FIELD] =
+                    serde_json::to_value(signature).map_err(|e| e.to_string())?;
+                let bytes = serde_json::to_vec_pretty(&document).map_err(|e| e.to_string())?;
+                std::fs::write(path, bytes).map_err(|e| e.to_string())?;
+                Ok(signature.clone())
+            })
+        }
+    }
+}
+
+// Embedded signature and recomputed canonical content of an export, by file extension.
+fn read_signed_export(path: &Path) -> Result<(Option<ExportSignature>, Vec<u8>), String> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "csv" => {
+            let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let signature = content
+                .lines()
+                .rev()
+                .find_map(|line| line.strip_prefix(CSV_SIGNATURE_PREFIX))
+                .and_then(ExportSignature::parse_text);
+            Ok((signature, canonical_csv(&content).into_bytes()))
+        }
+        "json" => {
+            let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let mut document: Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+            let signature = document
+                .as_object_mut()
+                .and_then(|object| object.remove(JSON_SIGNATURE_FIELD))
+                .and_then(|value| serde_json::from_value(value).ok());
+            Ok((signature, canonical_json(&document).into_bytes()))
+        }
+        "xlsx" => {
+            let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+            Ok((xlsx_signature(&bytes)?, canonical_xlsx(&bytes)?))
+        }
+        _ => Err(format!("unsupported export format: .{}", extension)),
+    }
+}
+
+/// Check the export at `path` against its embedded signature and the chain at `chain_path`.
+/// Edits to a signed file make it invalid; a missing or unreadable chain only leaves
+/// `in_chain` unknown.
+pub fn verify_export(path: &Path, chain_path: &Path) -> Result<ExportVerification, String> {
+    let (signature, canonical) = read_signed_export(path)?;
+    let mut verification = ExportVerification {
+        path: path.to_string_lossy().to_string(),
+        signed: signature.is_some(),
+        valid: false,
+        signature: None,
+        in_chain: None,
+        reason: None,
+    };
+    let Some(signature) = signature else {
+        verification.reason = Some("export is not signed".to_string());
+        return Ok(verification);
+    };
+    if signature.v != SIGNATURE_VERSION {
+        verification.reason = Some(format!("unsupported signature version {}", signature.v));
+    } else if chain_hash(&signature.prev_hash, signature.position, &canonical) != signature.hash {
+        verification.reason = Some("content does not match the signature".to_string());
+    } else {
+        verification.valid = true;
+    }
+    verification.in_chain = load_export_chain(chain_path).ok().and_then(|chain| {
+        chain
+            .entries
+            .iter()
+            .find(|entry| entry.position == signature.position)
+            .map(|entry| entry.hash == signature.hash && entry.prev_hash == signature.prev_hash)
+    });
+    verification.signature = Some(signature);
+    Ok(verification)
+}
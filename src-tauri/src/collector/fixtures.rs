@@ -0,0 +1,389 @@
+//! Fixtures module.
+//! Deterministic generators of plausible history (several apps over working-hour minutes,
+//! shortcut usage and, for large states, input event chunks) and the memory-only collector
+//! state they load into. Demo mode, tests and the benchmarks share them; the output only
+//! depends on the seed, end time and size.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{Datelike, Duration as ChronoDuration, NaiveDateTime, Timelike, Weekday};
+
+use crate::app_config::AppConfig;
+use crate::shutdown::Shutdown;
+#[cfg(any(test, feature = "bench-support"))]
+use crate::storage::StoredInputEventChunk;
+use crate::storage::{LoadProgress, MemoryStorage, StoredInputAnalytics, StoredShortcutUsage};
+
+use super::history_load::{load_history, merge_history};
+#[cfg(feature = "bench-support")]
+use super::shortcut::build_stored_input_analytics;
+#[cfg(any(test, feature = "bench-support"))]
+use super::shortcut::{local_day_window_ms, INPUT_CHUNK_VERSION};
+use super::{
+    append_app_log, empty_collector_state, CollectorState, MinuteKey, StatsKey, StatsValue,
+};
+
+// Apps with their window titles and relative share of typing minutes.
+const FIXTURE_APPS: [(&str, &[&str], u64); 5] = [
+    (
+        "com.microsoft.VSCode",
+        &[
+            "main.rs — typepulse",
+            "collector.rs — typepulse",
+            "README.md",
+        ],
+        40,
+    ),
+    ("com.apple.Terminal", &["zsh", "cargo test"], 20),
+    (
+        "com.google.Chrome",
+        &["Pull requests · GitHub", "Rust docs"],
+        15,
+    ),
+    ("com.tinyspeck.slackmacgap", &["#general", "#dev"], 15),
+    ("com.apple.Notes", &["Ideas"], 10),
+];
+// Shortcut ids with their relative share of shortcut uses.
+const FIXTURE_SHORTCUTS: [(&str, u64); 8] = [
+    ("cmd_c", 22),
+    ("cmd_v", 24),
+    ("cmd_s", 16),
+    ("cmd_z", 12),
+    ("cmd_f", 8),
+    ("cmd_t", 6),
+    ("cmd_w", 6),
+    ("shift_cmd_z", 6),
+];
+// Plain keys typed between shortcuts in generated event chunks.
+#[cfg(any(test, feature = "bench-support"))]
+const FIXTURE_KEYS: [&str; 12] = [
+    "e",
+    "t",
+    "a",
+    "o",
+    "i",
+    "n",
+    "s",
+    "r",
+    "space",
+    "backspace",
+    "enter",
+    "left",
+];
+// Local working hours as `[start, end)` minutes of the day: 09:00–12:00 and 13:30–18:30.
+const WORK_PERIODS: [(u32, u32); 2] = [(9 * 60, 12 * 60), (13 * 60 + 30, 18 * 60 + 30)];
+// Minutes of the day large histories spread their rows over: 08:00–20:00.
+#[cfg(any(test, feature = "bench-support"))]
+const LONG_DAY: (u32, u32) = (8 * 60, 20 * 60);
+// Percent chance that a working minute has typing, on weekdays and on weekends.
+const WEEKDAY_ACTIVE_PCT: u64 = 55;
+const WEEKEND_ACTIVE_PCT: u64 = 12;
+// Percent chance that a typing minute stays in the app of the previous one.
+const SAME_APP_PCT: u64 = 80;
+// Percent chance of one shortcut use in a typing minute, and of a shortcut among the
+// key-downs of an event chunk.
+const SHORTCUT_PCT: u64 = 30;
+#[cfg(any(test, feature = "bench-support"))]
+const CHUNK_SHORTCUT_PCT: u64 = 5;
+// Chunks start at least this long before local midnight, so none crosses into the next day.
+#[cfg(any(test, feature = "bench-support"))]
+const CHUNK_DAY_MARGIN_MS: i64 = 10 * 60 * 1_000;
+
+/// History produced by the generators, shaped like a storage load.
+pub(crate) struct SeededHistory {
+    pub(crate) stats: HashMap<StatsKey, StatsValue>,
+    pub(crate) analytics: StoredInputAnalytics,
+}
+
+/// Size of a large generated history.
+#[cfg(any(test, feature = "bench-support"))]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HistorySize {
+    /// Local days covered, today included.
+    pub(crate) days: u32,
+    pub(crate) stats_rows: usize,
+    pub(crate) chunks: usize,
+    /// Events per chunk, key-downs and key-ups alternating.
+    pub(crate) events_per_chunk: usize,
+}
+
+/// The state the benchmarks run on: a heavy user after a month with event chunks recorded.
+#[cfg(feature = "bench-support")]
+pub(crate) const BENCH_HISTORY: HistorySize = HistorySize {
+    days: 30,
+    stats_rows: 100_000,
+    chunks: 20_000,
+    events_per_chunk: 500,
+};
+
+// SplitMix64: small, fast and identical on every platform, which is all a fixture needs.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in `low..=high`.
+    fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next() % (high - low + 1)
+    }
+
+    fn chance(&mut self, pct: u64) -> bool {
+        self.next() % 100 < pct
+    }
+
+    // Index into `weights`, each picked in proportion to its weight.
+    fn weighted(&mut self, weights: impl Iterator<Item = u64> + Clone) -> usize {
+        let total: u64 = weights.clone().sum();
+        let mut roll = self.next() % total.max(1);
+        for (index, weight) in weights.enumerate() {
+            if roll < weight {
+                return index;
+            }
+            roll -= weight;
+        }
+        0
+    }
+
+    fn app(&mut self) -> usize {
+        self.weighted(FIXTURE_APPS.iter().map(|(_, _, weight)| *weight))
+    }
+
+    fn shortcut(&mut self) -> &'static str {
+        FIXTURE_SHORTCUTS[self.weighted(FIXTURE_SHORTCUTS.iter().map(|(_, weight)| *weight))].0
+    }
+}
+
+// Row of one typing minute with plausible key count and writing/navigation split.
+fn typing_minute(rng: &mut SplitMix64, new_session: bool) -> StatsValue {
+    let key_count = rng.between(20, 140);
+    let active_typing_ms = (key_count * rng.between(180, 420)).min(60_000);
+    let writing_ms = active_typing_ms * rng.between(70, 90) / 100;
+    StatsValue {
+        active_typing_ms,
+        key_count,
+        session_count: u64::from(new_session),
+        writing_ms,
+        navigation_ms: active_typing_ms - writing_ms,
+    }
+}
+
+fn record_shortcut(
+    usage: &mut HashMap<String, StoredShortcutUsage>,
+    shortcut: &str,
+    app_name: &str,
+) {
+    let usage = usage.entry(shortcut.to_string()).or_default();
+    usage.count += 1;
+    *usage.by_app.entry(app_name.to_string()).or_insert(0) += 1;
+}
+
+/// Generate `days` days of working-hours typing ending before `end`, today's minutes included
+/// up to `end`. The same seed and end always give the same history.
+pub(crate) fn generate_history(seed: u64, end: NaiveDateTime, days: u32) -> SeededHistory {
+    let mut rng = SplitMix64(seed);
+    let mut stats = HashMap::new();
+    let mut shortcut_usage: HashMap<String, StoredShortcutUsage> = HashMap::new();
+    let last_day = end.date();
+    for offset in (0..days as i64).rev() {
+        let day = last_day - ChronoDuration::days(offset);
+        let active_pct = match day.weekday() {
+            Weekday::Sat | Weekday::Sun => WEEKEND_ACTIVE_PCT,
+            _ => WEEKDAY_ACTIVE_PCT,
+        };
+        let mut previous_app: Option<usize> = None;
+        for (start, stop) in WORK_PERIODS {
+            for minute_of_day in start..stop {
+                if day == last_day && minute_of_day >= end.hour() * 60 + end.minute() {
+                    break;
+                }
+                if !rng.chance(active_pct) {
+                    previous_app = None;
+                    continue;
+                }
+                let app = match previous_app {
+                    Some(app) if rng.chance(SAME_APP_PCT) => app,
+                    _ => rng.app(),
+                };
+                let (app_name, titles, _) = FIXTURE_APPS[app];
+                let title = titles[rng.between(0, titles.len() as u64 - 1) as usize];
+                let Some(date) = MinuteKey::new(
+                    day,
+                    (minute_of_day / 60) as u16,
+                    (minute_of_day % 60) as u16,
+                ) else {
+                    continue;
+                };
+                stats.insert(
+                    StatsKey {
+                        date,
+                        app_name: app_name.to_string(),
+                        window_title: title.to_string(),
+                    },
+                    typing_minute(&mut rng, previous_app.is_none()),
+                );
+                if rng.chance(SHORTCUT_PCT) {
+                    let shortcut = rng.shortcut();
+                    record_shortcut(&mut shortcut_usage, shortcut, app_name);
+                }
+                previous_app = Some(app);
+            }
+        }
+    }
+    SeededHistory {
+        stats,
+        analytics: StoredInputAnalytics {
+            shortcut_usage,
+            ..StoredInputAnalytics::default()
+        },
+    }
+}
+
+/// Generate a large history of `size` over the days ending on `end`'s date: stats rows for
+/// every app and title in 08:00–20:00 minutes, and event chunks spread evenly over the days
+/// (whole days, so today always has its share). Shortcut totals follow the chunks.
+#[cfg(any(test, feature = "bench-support"))]
+pub(crate) fn generate_large_history(
+    seed: u64,
+    end: NaiveDateTime,
+    size: HistorySize,
+) -> SeededHistory {
+    let mut rng = SplitMix64(seed);
+    let days = size.days.max(1);
+    let last_day = end.date();
+    let windows: Vec<(&str, &str)> = FIXTURE_APPS
+        .iter()
+        .flat_map(|(app_name, titles, _)| titles.iter().map(move |title| (*app_name, *title)))
+        .collect();
+    let day_minutes = (LONG_DAY.1 - LONG_DAY.0) as usize;
+    let per_minute = size
+        .stats_rows
+        .div_ceil(days as usize * day_minutes)
+        .clamp(1, windows.len());
+
+    let mut stats = HashMap::with_capacity(size.stats_rows);
+    'days: for offset in 0..days as i64 {
+        let day = last_day - ChronoDuration::days(offset);
+        for minute_of_day in LONG_DAY.0..LONG_DAY.1 {
+            let Some(date) = MinuteKey::new(
+                day,
+                (minute_of_day / 60) as u16,
+                (minute_of_day % 60) as u16,
+            ) else {
+                continue;
+            };
+            let first = rng.between(0, windows.len() as u64 - 1) as usize;
+            for step in 0..per_minute {
+                if stats.len() >= size.stats_rows {
+                    break 'days;
+                }
+                let (app_name, title) = windows[(first + step) % windows.len()];
+                let key = StatsKey {
+                    date,
+                    app_name: app_name.to_string(),
+                    window_title: title.to_string(),
+                };
+                stats.insert(key, typing_minute(&mut rng, step == 0));
+            }
+        }
+    }
+
+    let app_dict: HashMap<u32, String> = FIXTURE_APPS
+        .iter()
+        .enumerate()
+        .map(|(index, (app_name, _, _))| (index as u32 + 1, app_name.to_string()))
+        .collect();
+    let mut shortcut_usage: HashMap<String, StoredShortcutUsage> = HashMap::new();
+    let mut event_chunks = Vec::with_capacity(size.chunks);
+    for index in 0..size.chunks {
+        let day = last_day - ChronoDuration::days((index % days as usize) as i64);
+        let Some((day_start_ms, day_end_ms)) = local_day_window_ms(day) else {
+            continue;
+        };
+        let span_ms = (day_end_ms - day_start_ms - CHUNK_DAY_MARGIN_MS).max(1) as u64;
+        let chunk_start_ms = day_start_ms + rng.between(0, span_ms - 1) as i64;
+        let app = rng.app();
+        let app_name = FIXTURE_APPS[app].0;
+        let mut events = Vec::with_capacity(size.events_per_chunk);
+        let mut dt = 0u64;
+        while events.len() < size.events_per_chunk {
+            let (key, mask) = if rng.chance(CHUNK_SHORTCUT_PCT) {
+                let shortcut = rng.shortcut();
+                record_shortcut(&mut shortcut_usage, shortcut, app_name);
+                match shortcut.split_once("_cmd_") {
+                    // Cmd (8) plus shift (4).
+                    Some((_, key)) => (key, 12),
+                    None => (shortcut.trim_start_matches("cmd_"), 8),
+                }
+            } else {
+                (
+                    FIXTURE_KEYS[rng.between(0, FIXTURE_KEYS.len() as u64 - 1) as usize],
+                    0,
+                )
+            };
+            events.push(format!("{dt},d,{mask},{key}"));
+            if events.len() < size.events_per_chunk {
+                events.push(format!("{},u,{mask},{key}", dt + rng.between(30, 90)));
+            }
+            dt += rng.between(120, 260);
+        }
+        event_chunks.push(StoredInputEventChunk {
+            v: INPUT_CHUNK_VERSION,
+            chunk_start_ms,
+            app_ref: app as u32 + 1,
+            events,
+        });
+    }
+    event_chunks.sort_by_key(|chunk| chunk.chunk_start_ms);
+
+    SeededHistory {
+        stats,
+        analytics: StoredInputAnalytics {
+            shortcut_usage,
+            next_app_ref: app_dict.len() as u32 + 1,
+            app_dict,
+            event_chunks,
+            ..StoredInputAnalytics::default()
+        },
+    }
+}
+
+/// Collector state with `config` applied and `history` loaded from memory storage. The status
+/// file and auto export are off, so nothing is written unless the caller swaps the storage.
+pub(crate) fn seeded_collector_state(
+    log_path: PathBuf,
+    app_log_path: PathBuf,
+    detail_path: PathBuf,
+    config: &AppConfig,
+    shutdown: Arc<Shutdown>,
+    history: SeededHistory,
+) -> CollectorState {
+    let load_log_path = app_log_path.clone();
+    let log = |message: &str| {
+        let _ = append_app_log(&load_log_path, message);
+    };
+    let mut state =
+        empty_collector_state(log_path, app_log_path, detail_path, config, shutdown, &log);
+    state.storage = Arc::new(MemoryStorage::with_history(
+        history.stats,
+        history.analytics,
+    ));
+    state.write_status_file = false;
+    state.auto_export.enabled = false;
+    let history = load_history(state.storage.as_ref(), &LoadProgress::default());
+    merge_history(&mut state, history, &log);
+    state
+}
+
+/// Analytics as the next flush would save them, for timing the save on its own.
+#[cfg(feature = "bench-support")]
+pub(crate) fn stored_input_analytics(state: &mut CollectorState) -> StoredInputAnalytics {
+    build_stored_input_analytics(state)
+}
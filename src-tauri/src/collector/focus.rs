@@ -0,0 +1,196 @@
+//! Focus score module.
+//! Scores each local day from typing sessions, foreground app switches and typing density.
+
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+
+use crate::storage::StoredFocusDay;
+
+use super::shortcut::{
+    days_in_window, for_each_key_down_in_window, local_day_window_ms, shortcut_range_window_ms,
+};
+use super::CollectorState;
+
+/// Pauses up to this long (thinking, reading) stay inside one focus session.
+const FOCUS_SESSION_GAP_MS: i64 = 2 * 60_000;
+/// Session length at which continuity saturates.
+const DEEP_SESSION_MS: f64 = 25.0 * 60_000.0;
+/// Switch rate (per focused hour) that halves the calm component.
+const HALF_CALM_SWITCHES_PER_HOUR: f64 = 12.0;
+/// Floor for the focused time used in the switch rate, so quiet days are not amplified.
+const MIN_SWITCH_RATE_HOURS: f64 = 0.5;
+/// Typing density (keys per in-session minute) at which density saturates.
+const DENSE_KEYS_PER_MINUTE: f64 = 60.0;
+const CONTINUITY_WEIGHT: f64 = 0.5;
+const CALM_WEIGHT: f64 = 0.3;
+const DENSITY_WEIGHT: f64 = 0.2;
+
+/// A run of key-downs with no gap longer than `FOCUS_SESSION_GAP_MS`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct FocusSession {
+    pub(super) start_ms: i64,
+    pub(super) end_ms: i64,
+    pub(super) key_count: u64,
+}
+
+impl FocusSession {
+    fn duration_ms(&self) -> f64 {
+        (self.end_ms - self.start_ms).max(0) as f64
+    }
+}
+
+/// Focus summary of one local day (`YYYY-MM-DD`).
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct FocusDayScore {
+    pub date: String,
+    pub score: u8,
+    pub session_count: u64,
+    pub longest_session_ms: u64,
+    pub focused_ms: u64,
+    pub app_switches: u64,
+}
+
+impl FocusDayScore {
+    pub(super) fn to_stored(&self) -> StoredFocusDay {
+        StoredFocusDay {
+            score: self.score,
+            session_count: self.session_count,
+            longest_session_ms: self.longest_session_ms,
+            focused_ms: self.focused_ms,
+            app_switches: self.app_switches,
+        }
+    }
+
+    pub(super) fn from_stored(date: String, stored: &StoredFocusDay) -> Self {
+        Self {
+            date,
+            score: stored.score.min(100),
+            session_count: stored.session_count,
+            longest_session_ms: stored.longest_session_ms,
+            focused_ms: stored.focused_ms,
+            app_switches: stored.app_switches,
+        }
+    }
+}
+
+/// Split sorted key-down timestamps into focus sessions.
+pub(super) fn sessions_from_key_times(times: &[i64]) -> Vec<FocusSession> {
+    let mut sessions: Vec<FocusSession> = Vec::new();
+    for &at in times {
+        match sessions.last_mut() {
+            Some(session) if at - session.end_ms <= FOCUS_SESSION_GAP_MS => {
+                session.end_ms = at;
+                session.key_count += 1;
+            }
+            _ => sessions.push(FocusSession {
+                start_ms: at,
+                end_ms: at,
+                key_count: 1,
+            }),
+        }
+    }
+    sessions
+}
+
+/// Daily focus score in 0–100:
+///
+/// `score = 100 × (0.5 × continuity + 0.3 × calm + 0.2 × density)`
+///
+/// - continuity = min(1, Σdᵢ² / Σdᵢ / 25 min): the duration-weighted mean session length,
+///   so one long session beats many short ones adding up to the same time.
+/// - calm = 1 / (1 + switches_per_hour / 12), with switches divided by in-session hours
+///   (at least 0.5 h): twelve app switches per focused hour halve it.
+/// - density = min(1, keys per in-session minute / 60).
+///
+/// A day without any session scores 0.
+pub(super) fn focus_score(sessions: &[FocusSession], app_switches: u64) -> u8 {
+    let focused_ms: f64 = sessions.iter().map(FocusSession::duration_ms).sum();
+    let key_count: u64 = sessions.iter().map(|session| session.key_count).sum();
+    if sessions.is_empty() || key_count == 0 {
+        return 0;
+    }
+    let continuity = if focused_ms > 0.0 {
+        let weighted: f64 = sessions
+            .iter()
+            .map(|session| session.duration_ms().powi(2))
+            .sum();
+        (weighted / focused_ms / DEEP_SESSION_MS).min(1.0)
+    } else {
+        0.0
+    };
+    let focused_hours = focused_ms / 3_600_000.0;
+    let switches_per_hour = app_switches as f64 / focused_hours.max(MIN_SWITCH_RATE_HOURS);
+    let calm = 1.0 / (1.0 + switches_per_hour / HALF_CALM_SWITCHES_PER_HOUR);
+    let focused_minutes = (focused_ms / 60_000.0).max(1.0);
+    let density = (key_count as f64 / focused_minutes / DENSE_KEYS_PER_MINUTE).min(1.0);
+    let score =
+        100.0 * (CONTINUITY_WEIGHT * continuity + CALM_WEIGHT * calm + DENSITY_WEIGHT * density);
+    score.round().clamp(0.0, 100.0) as u8
+}
+
+// Score one local day from replayed key-downs and the day's app switch count.
+// Returns None when the day has neither typing nor switches.
+fn compute_focus_day(state: &CollectorState, date: &str) -> Option<FocusDayScore> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let (start_ms, end_ms) = local_day_window_ms(day)?;
+    let mut times = Vec::new();
+    for_each_key_down_in_window(state, start_ms, end_ms, None, |at, _| times.push(at));
+    times.sort_unstable();
+    let sessions = sessions_from_key_times(&times);
+    let app_switches = state.app_switches.get(date).map_or(0, |day| day.total);
+    if sessions.is_empty() && app_switches == 0 {
+        return None;
+    }
+    Some(FocusDayScore {
+        date: date.to_string(),
+        score: focus_score(&sessions, app_switches),
+        session_count: sessions.len() as u64,
+        longest_session_ms: sessions
+            .iter()
+            .map(|session| session.duration_ms() as u64)
+            .max()
+            .unwrap_or(0),
+        focused_ms: sessions
+            .iter()
+            .map(|session| session.duration_ms() as u64)
+            .sum(),
+        app_switches,
+    })
+}
+
+/// Recompute and keep today's and yesterday's scores, so the day that just ended is
+/// finalized by the first flush after midnight.
+pub(super) fn refresh_recent_focus_days(state: &mut CollectorState) {
+    let today = Local::now().date_naive();
+    for day in [today.pred_opt(), Some(today)].into_iter().flatten() {
+        let date = day.format("%Y-%m-%d").to_string();
+        if let Some(score) = compute_focus_day(state, &date) {
+            state.focus_days.insert(date, score);
+        }
+    }
+}
+
+/// Today's live focus score, or None before any activity.
+pub(super) fn today_focus_score(state: &CollectorState) -> Option<u8> {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    compute_focus_day(state, &today).map(|day| day.score)
+}
+
+/// Focus scores by range (`today` / `yesterday` / `7d`), oldest day first. Today is always
+/// computed live; earlier days use the persisted score and fall back to replaying chunks.
+pub fn snapshot_focus_scores(state: &CollectorState, range: &str) -> Vec<FocusDayScore> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    days_in_window(start_ms, end_ms)
+        .into_iter()
+        .filter_map(|date| {
+            if date != today {
+                if let Some(stored) = state.focus_days.get(&date) {
+                    return Some(stored.clone());
+                }
+            }
+            compute_focus_day(state, &date)
+        })
+        .collect()
+}
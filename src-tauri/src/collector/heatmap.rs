@@ -0,0 +1,372 @@
+//! Key heat map module.
+//! Joins per-key press counts with physical ANSI/ISO key positions and renders them as SVG.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use super::key_names::physical_key_name;
+use super::runtime::{runtime_days, RuntimeDay};
+use super::shortcut::{key_counts_in_window, shortcut_range_window_ms};
+use super::{CollectorState, KeyUsageRow};
+
+// SVG geometry: pixels per key unit, gap between keys, and outer padding.
+const SVG_KEY_UNIT_PX: f64 = 40.0;
+const SVG_KEY_GAP_PX: f64 = 4.0;
+const SVG_PADDING_PX: f64 = 12.0;
+// Log-scale color ramp from idle keys to the most pressed key.
+const SVG_COLD_RGB: (u8, u8, u8) = (241, 245, 249);
+const SVG_HOT_RGB: (u8, u8, u8) = (220, 38, 38);
+
+/// Physical keyboard layout used to place keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeyboardLayout {
+    Ansi,
+    Iso,
+}
+
+impl KeyboardLayout {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            KeyboardLayout::Ansi => "ansi",
+            KeyboardLayout::Iso => "iso",
+        }
+    }
+
+    pub(crate) fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "ansi" => Some(KeyboardLayout::Ansi),
+            "iso" => Some(KeyboardLayout::Iso),
+            _ => None,
+        }
+    }
+}
+
+// One physical key: normalized key name, row from the top, x offset and size in key units.
+struct KeyPosition {
+    key: &'static str,
+    row: u8,
+    x: f64,
+    width: f64,
+    height: f64,
+}
+
+const fn key(key: &'static str, row: u8, x: f64, width: f64) -> KeyPosition {
+    KeyPosition {
+        key,
+        row,
+        x,
+        width,
+        height: 1.0,
+    }
+}
+
+// Rows shared by both layouts: function row, number row, bottom row and the navigation cluster.
+const COMMON_KEYS: &[KeyPosition] = &[
+    key("esc", 0, 0.0, 1.0),
+    key("f1", 0, 2.0, 1.0),
+    key("f2", 0, 3.0, 1.0),
+    key("f3", 0, 4.0, 1.0),
+    key("f4", 0, 5.0, 1.0),
+    key("f5", 0, 6.5, 1.0),
+    key("f6", 0, 7.5, 1.0),
+    key("f7", 0, 8.5, 1.0),
+    key("f8", 0, 9.5, 1.0),
+    key("f9", 0, 11.0, 1.0),
+    key("f10", 0, 12.0, 1.0),
+    key("f11", 0, 13.0, 1.0),
+    key("f12", 0, 14.0, 1.0),
+    key("`", 1, 0.0, 1.0),
+    key("1", 1, 1.0, 1.0),
+    key("2", 1, 2.0, 1.0),
+    key("3", 1, 3.0, 1.0),
+    key("4", 1, 4.0, 1.0),
+    key("5", 1, 5.0, 1.0),
+    key("6", 1, 6.0, 1.0),
+    key("7", 1, 7.0, 1.0),
+    key("8", 1, 8.0, 1.0),
+    key("9", 1, 9.0, 1.0),
+    key("0", 1, 10.0, 1.0),
+    key("-", 1, 11.0, 1.0),
+    key("=", 1, 12.0, 1.0),
+    key("backspace", 1, 13.0, 2.0),
+    key("tab", 2, 0.0, 1.5),
+    key("q", 2, 1.5, 1.0),
+    key("w", 2, 2.5, 1.0),
+    key("e", 2, 3.5, 1.0),
+    key("r", 2, 4.5, 1.0),
+    key("t", 2, 5.5, 1.0),
+    key("y", 2, 6.5, 1.0),
+    key("u", 2, 7.5, 1.0),
+    key("i", 2, 8.5, 1.0),
+    key("o", 2, 9.5, 1.0),
+    key("p", 2, 10.5, 1.0),
+    key("[", 2, 11.5, 1.0),
+    key("]", 2, 12.5, 1.0),
+    key("capslock", 3, 0.0, 1.75),
+    key("a", 3, 1.75, 1.0),
+    key("s", 3, 2.75, 1.0),
+    key("d", 3, 3.75, 1.0),
+    key("f", 3, 4.75, 1.0),
+    key("g", 3, 5.75, 1.0),
+    key("h", 3, 6.75, 1.0),
+    key("j", 3, 7.75, 1.0),
+    key("k", 3, 8.75, 1.0),
+    key("l", 3, 9.75, 1.0),
+    key(";", 3, 10.75, 1.0),
+    key("'", 3, 11.75, 1.0),
+    key("control", 5, 0.0, 1.25),
+    key("option", 5, 1.25, 1.25),
+    key("command", 5, 2.5, 1.25),
+    key("space", 5, 3.75, 6.25),
+    key("command_r", 5, 10.0, 1.25),
+    key("option_r", 5, 11.25, 1.25),
+    key("menu", 5, 12.5, 1.25),
+    key("control_r", 5, 13.75, 1.25),
+    key("insert", 1, 15.5, 1.0),
+    key("home", 1, 16.5, 1.0),
+    key("pageup", 1, 17.5, 1.0),
+    key("delete", 2, 15.5, 1.0),
+    key("end", 2, 16.5, 1.0),
+    key("pagedown", 2, 17.5, 1.0),
+    key("up", 4, 16.5, 1.0),
+    key("left", 5, 15.5, 1.0),
+    key("down", 5, 16.5, 1.0),
+    key("right", 5, 17.5, 1.0),
+];
+
+// ANSI: wide backslash above a single-row enter, one long left shift.
+const ANSI_KEYS: &[KeyPosition] = &[
+    key("\\", 2, 13.5, 1.5),
+    key("enter", 3, 12.75, 2.25),
+    key("shift", 4, 0.0, 2.25),
+    key("z", 4, 2.25, 1.0),
+    key("x", 4, 3.25, 1.0),
+    key("c", 4, 4.25, 1.0),
+    key("v", 4, 5.25, 1.0),
+    key("b", 4, 6.25, 1.0),
+    key("n", 4, 7.25, 1.0),
+    key("m", 4, 8.25, 1.0),
+    key(",", 4, 9.25, 1.0),
+    key(".", 4, 10.25, 1.0),
+    key("/", 4, 11.25, 1.0),
+    key("shift_r", 4, 12.25, 2.75),
+];
+
+// ISO: two-row enter (drawn as its lower, narrower part), backslash beside it on the home row,
+// and the extra key between a short left shift and `z`.
+const ISO_KEYS: &[KeyPosition] = &[
+    KeyPosition {
+        key: "enter",
+        row: 2,
+        x: 13.75,
+        width: 1.25,
+        height: 2.0,
+    },
+    key("\\", 3, 12.75, 1.0),
+    key("shift", 4, 0.0, 1.25),
+    key("intlbackslash", 4, 1.25, 1.0),
+    key("z", 4, 2.25, 1.0),
+    key("x", 4, 3.25, 1.0),
+    key("c", 4, 4.25, 1.0),
+    key("v", 4, 5.25, 1.0),
+    key("b", 4, 6.25, 1.0),
+    key("n", 4, 7.25, 1.0),
+    key("m", 4, 8.25, 1.0),
+    key(",", 4, 9.25, 1.0),
+    key(".", 4, 10.25, 1.0),
+    key("/", 4, 11.25, 1.0),
+    key("shift_r", 4, 12.25, 2.75),
+];
+
+fn layout_keys(layout: KeyboardLayout) -> impl Iterator<Item = &'static KeyPosition> {
+    let specific = match layout {
+        KeyboardLayout::Ansi => ANSI_KEYS,
+        KeyboardLayout::Iso => ISO_KEYS,
+    };
+    COMMON_KEYS.iter().chain(specific.iter())
+}
+
+/// One positioned key with its press count.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct KeyHeatmapKey {
+    pub key: String,
+    pub row: u8,
+    pub x: f64,
+    pub width: f64,
+    pub height: f64,
+    pub count: u64,
+}
+
+/// Heat map payload; keys missing from the layout are listed in `other`, most pressed first.
+/// `days` flags the days of the range the app was not running, so an empty map is not read
+/// as a day without typing.
+#[derive(Serialize, Clone)]
+pub struct KeyHeatmap {
+    pub range: String,
+    pub layout: String,
+    pub max_count: u64,
+    pub keys: Vec<KeyHeatmapKey>,
+    pub other: Vec<KeyUsageRow>,
+    pub days: Vec<RuntimeDay>,
+}
+
+// Join raw key counts with the layout table.
+pub(super) fn build_key_heatmap(
+    counts: &HashMap<String, u64>,
+    range: &str,
+    layout: KeyboardLayout,
+) -> KeyHeatmap {
+    let mut merged: HashMap<&str, u64> = HashMap::new();
+    for (key, count) in counts {
+        // The map shows physical keys, so produced symbols count on the key that typed them.
+        *merged.entry(physical_key_name(key)).or_insert(0) += count;
+    }
+    let keys: Vec<KeyHeatmapKey> = layout_keys(layout)
+        .map(|position| KeyHeatmapKey {
+            key: position.key.to_string(),
+            row: position.row,
+            x: position.x,
+            width: position.width,
+            height: position.height,
+            count: merged.remove(position.key).unwrap_or(0),
+        })
+        .collect();
+    let mut other: Vec<KeyUsageRow> = merged
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(key, count)| KeyUsageRow {
+            key: key.to_string(),
+            count,
+            apps: Vec::new(),
+            other_count: 0,
+        })
+        .collect();
+    other.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    KeyHeatmap {
+        range: range.to_string(),
+        layout: layout.as_str().to_string(),
+        max_count: keys.iter().map(|key| key.count).max().unwrap_or(0),
+        keys,
+        other,
+        days: Vec::new(),
+    }
+}
+
+/// Build the key heat map for `range` (`today` / `yesterday` / `7d`) and `layout` (`ansi` / `iso`).
+pub fn snapshot_key_heatmap(
+    state: &CollectorState,
+    range: &str,
+    layout: &str,
+) -> Result<KeyHeatmap, String> {
+    let layout = KeyboardLayout::from_str(layout)
+        .ok_or_else(|| format!("unknown keyboard layout: {layout}"))?;
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let counts = key_counts_in_window(state, start_ms, end_ms, None);
+    let mut heatmap = build_key_heatmap(&counts, range, layout);
+    heatmap.days = runtime_days(state, start_ms, end_ms);
+    Ok(heatmap)
+}
+
+// Short label drawn on a key cap.
+fn key_cap_label(key: &str) -> String {
+    match key {
+        "backspace" => "⌫".to_string(),
+        "capslock" => "caps".to_string(),
+        "command" | "command_r" => "cmd".to_string(),
+        "control" | "control_r" => "ctrl".to_string(),
+        "option" | "option_r" => "opt".to_string(),
+        "shift" | "shift_r" => "shift".to_string(),
+        "intlbackslash" => "§".to_string(),
+        "pageup" => "pgup".to_string(),
+        "pagedown" => "pgdn".to_string(),
+        "insert" => "ins".to_string(),
+        "delete" => "del".to_string(),
+        "left" => "←".to_string(),
+        "right" => "→".to_string(),
+        "up" => "↑".to_string(),
+        "down" => "↓".to_string(),
+        "space" => String::new(),
+        _ if key.chars().count() == 1 => key.to_uppercase(),
+        _ => key.to_string(),
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+// Color a count on a log scale so a few hot keys do not wash out everything else.
+fn heat_color(count: u64, max_count: u64) -> String {
+    let ratio = if count == 0 || max_count == 0 {
+        0.0
+    } else {
+        (count as f64).ln_1p() / (max_count as f64).ln_1p()
+    };
+    let mix = |cold: u8, hot: u8| -> u8 {
+        (f64::from(cold) + (f64::from(hot) - f64::from(cold)) * ratio).round() as u8
+    };
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        mix(SVG_COLD_RGB.0, SVG_HOT_RGB.0),
+        mix(SVG_COLD_RGB.1, SVG_HOT_RGB.1),
+        mix(SVG_COLD_RGB.2, SVG_HOT_RGB.2)
+    )
+}
+
+/// Render a heat map as a standalone SVG document. Pure: the same heat map yields the same bytes.
+pub fn render_key_heatmap_svg(heatmap: &KeyHeatmap) -> String {
+    let columns = heatmap
+        .keys
+        .iter()
+        .map(|key| key.x + key.width)
+        .fold(0.0, f64::max);
+    let rows = heatmap
+        .keys
+        .iter()
+        .map(|key| f64::from(key.row) + key.height)
+        .fold(0.0, f64::max);
+    let width = columns * SVG_KEY_UNIT_PX + SVG_PADDING_PX * 2.0;
+    let height = rows * SVG_KEY_UNIT_PX + SVG_PADDING_PX * 2.0;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="-apple-system, Helvetica, Arial, sans-serif">"#
+    );
+    let _ = writeln!(
+        svg,
+        r##"<rect width="{width}" height="{height}" fill="#ffffff"/>"##
+    );
+    for key in &heatmap.keys {
+        let x = SVG_PADDING_PX + key.x * SVG_KEY_UNIT_PX + SVG_KEY_GAP_PX / 2.0;
+        let y = SVG_PADDING_PX + f64::from(key.row) * SVG_KEY_UNIT_PX + SVG_KEY_GAP_PX / 2.0;
+        let key_width = key.width * SVG_KEY_UNIT_PX - SVG_KEY_GAP_PX;
+        let key_height = key.height * SVG_KEY_UNIT_PX - SVG_KEY_GAP_PX;
+        let label = escape_xml(&key_cap_label(&key.key));
+        let _ = writeln!(
+            svg,
+            r##"<g><title>{}: {}</title><rect x="{x}" y="{y}" width="{key_width}" height="{key_height}" rx="4" fill="{}" stroke="#cbd5e1"/><text x="{}" y="{}" font-size="11" text-anchor="middle" fill="#1e293b">{label}</text></g>"##,
+            escape_xml(&key.key),
+            key.count,
+            heat_color(key.count, heatmap.max_count),
+            x + key_width / 2.0,
+            y + key_height / 2.0 + 4.0,
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
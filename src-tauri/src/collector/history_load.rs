@@ -0,0 +1,384 @@
+//! History load module.
+//! Loads the stored stats and analytics of every day and merges them into the live state. At
+//! startup this runs on a background thread while keys are recorded into a fresh state, so the
+//! merge is additive: counters are summed and chunks recorded meanwhile are re-keyed into the
+//! stored app dictionary.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use chrono::Local;
+
+use crate::storage::{DetailStorage, LoadProgress, StoredInputAnalytics};
+
+use super::app_switch::AppSwitchDay;
+use super::burst::BurstDay;
+use super::chunk_stats::ChunkCounters;
+use super::copy_paste::CopyPasteDay;
+use super::coverage::CoverageDay;
+use super::focus::FocusDayScore;
+use super::key_names::canonicalize_shortcut_usage;
+use super::key_span::merge_key_span;
+use super::rolling_avg::rolling_averages;
+use super::runtime::runtime_from_stored;
+use super::shortcut::{
+    rebuild_shortcut_usage_from_chunks, resolve_app_ref, split_cross_day_chunks, InputEventChunk,
+};
+use super::state_api::compute_today_totals;
+use super::{
+    append_app_log, recover_crash_dumps, CollectorState, ShortcutUsageValue, StatsKey, StatsValue,
+};
+
+/// Stored stats and analytics of every day, read but not merged yet.
+pub(super) struct LoadedHistory {
+    stats: HashMap<StatsKey, StatsValue>,
+    analytics: StoredInputAnalytics,
+}
+
+/// Read the stored history, counting files into `progress`. Unreadable stores load as empty.
+pub(super) fn load_history(storage: &dyn DetailStorage, progress: &LoadProgress) -> LoadedHistory {
+    let (stats, analytics) = storage.load_history(progress);
+    LoadedHistory {
+        stats: stats.unwrap_or_default(),
+        analytics: analytics.unwrap_or_default(),
+    }
+}
+
+fn add_stats_value(into: &mut StatsValue, value: &StatsValue) {
+    into.active_typing_ms = into.active_typing_ms.saturating_add(value.active_typing_ms);
+    into.key_count = into.key_count.saturating_add(value.key_count);
+    into.session_count = into.session_count.saturating_add(value.session_count);
+    into.writing_ms = into.writing_ms.saturating_add(value.writing_ms);
+    into.navigation_ms = into.navigation_ms.saturating_add(value.navigation_ms);
+}
+
+// Add per-day counters recorded live into the loaded ones.
+fn merge_days<T>(
+    loaded: &mut HashMap<String, T>,
+    live: HashMap<String, T>,
+    merge: impl Fn(&mut T, T),
+) {
+    for (date, day) in live {
+        match loaded.get_mut(&date) {
+            Some(existing) => merge(existing, day),
+            None => {
+                loaded.insert(date, day);
+            }
+        }
+    }
+}
+
+/// Merge loaded history into `state`. Everything the state recorded so far is added on top,
+/// so merging into a fresh state reproduces the stored data exactly. Load notes go to `log`.
+pub(super) fn merge_history(
+    state: &mut CollectorState,
+    history: LoadedHistory,
+    log: &dyn Fn(&str),
+) {
+    let LoadedHistory {
+        mut stats,
+        analytics:
+            StoredInputAnalytics {
+                shortcut_usage: stored_shortcut_usage,
+                app_dict,
+                next_app_ref,
+                event_chunks: stored_event_chunks,
+                chord_aborts: mut loaded_chord_aborts,
+                app_switches: stored_app_switches,
+                focus_days: stored_focus_days,
+                coverage: stored_coverage,
+                runtime: stored_runtime,
+                bursts: stored_bursts,
+                effort: mut loaded_effort,
+                displays: mut loaded_displays,
+                copy_pastes: stored_copy_pastes,
+                key_spans: mut loaded_key_spans,
+                weekly_comparisons: mut loaded_weekly_comparisons,
+                duplicate_chunks_dropped,
+            },
+    } = history;
+    if duplicate_chunks_dropped > 0 {
+        log(&format!(
+            "dropped {} duplicate analytics chunks at load",
+            duplicate_chunks_dropped
+        ));
+    }
+    if !stats.is_empty() {
+        log(&format!("loaded {} detail rows from storage", stats.len()));
+    }
+
+    // The loaded data is the bulk, so live records are added into it rather than the reverse.
+    for (key, value) in std::mem::take(&mut state.stats) {
+        match stats.get_mut(&key) {
+            Some(existing) => add_stats_value(existing, &value),
+            None => {
+                stats.insert(key, value);
+            }
+        }
+    }
+    state.stats = stats;
+
+    // Stored chunks reference the stored dictionary, so it stays as is; apps first seen while
+    // loading get new refs past it and their chunks are re-keyed.
+    let live_app_dict = std::mem::replace(&mut state.app_dict, app_dict);
+    state.app_ref_by_app = state
+        .app_dict
+        .iter()
+        .map(|(app_ref, app_id)| (app_id.clone(), *app_ref))
+        .collect();
+    state.next_app_ref = next_app_ref.max(1);
+    let mut app_refs: HashMap<u32, u32> = HashMap::new();
+    for (live_ref, app_id) in live_app_dict {
+        app_refs.insert(live_ref, resolve_app_ref(state, &app_id));
+    }
+    let remap = |app_ref: u32| app_refs.get(&app_ref).copied().unwrap_or(app_ref);
+    let (mut event_chunks, split_chunks) = split_cross_day_chunks(
+        stored_event_chunks
+            .into_iter()
+            .map(|chunk| InputEventChunk {
+                v: chunk.v,
+                chunk_start_ms: chunk.chunk_start_ms,
+                app_ref: chunk.app_ref,
+                events: chunk.events,
+            })
+            .collect(),
+    );
+    // Repaired chunks are filed under the right day by the next flush.
+    if split_chunks > 0 {
+        log(&format!(
+            "split {} analytics chunks crossing midnight at load",
+            split_chunks
+        ));
+    }
+    let loaded_chunks = !event_chunks.is_empty();
+    for mut chunk in std::mem::take(&mut state.event_chunks) {
+        chunk.app_ref = remap(chunk.app_ref);
+        event_chunks.push(chunk);
+    }
+    event_chunks.sort_by_key(|chunk| chunk.chunk_start_ms);
+    state.chunk_counters = ChunkCounters::from_chunks(&event_chunks);
+    state.event_chunks = event_chunks;
+    if let Some(open) = state.open_event_chunk.as_mut() {
+        open.app_ref = remap(open.app_ref);
+    }
+
+    let mut shortcut_usage: HashMap<String, ShortcutUsageValue> = stored_shortcut_usage
+        .into_iter()
+        .map(|(shortcut_id, usage)| {
+            (
+                shortcut_id,
+                ShortcutUsageValue {
+                    count: usage.count,
+                    by_app: usage.by_app,
+                },
+            )
+        })
+        .collect();
+    let rebuild_usage = shortcut_usage.is_empty() && loaded_chunks;
+    merge_days(
+        &mut shortcut_usage,
+        std::mem::take(&mut state.shortcut_usage),
+        |into, usage| {
+            into.count = into.count.saturating_add(usage.count);
+            for (app_id, count) in usage.by_app {
+                let entry = into.by_app.entry(app_id).or_insert(0);
+                *entry = entry.saturating_add(count);
+            }
+        },
+    );
+    state.shortcut_usage = shortcut_usage;
+    // Rebuild shortcut aggregates when historical analytics only contains event chunks.
+    if rebuild_usage {
+        rebuild_shortcut_usage_from_chunks(state);
+    }
+    // Ids stored before symbol keys had one name, or under the other naming, are merged.
+    let renamed = canonicalize_shortcut_usage(&mut state.shortcut_usage, state.symbol_key_names);
+    if renamed > 0 {
+        log(&format!(
+            "renamed {} shortcut ids to {} key names at load",
+            renamed,
+            state.symbol_key_names.as_str()
+        ));
+    }
+
+    merge_days(
+        &mut loaded_chord_aborts,
+        std::mem::take(&mut state.chord_aborts),
+        |into, apps| {
+            for (app_id, count) in apps {
+                let entry = into.entry(app_id).or_insert(0);
+                *entry = entry.saturating_add(count);
+            }
+        },
+    );
+    state.chord_aborts = loaded_chord_aborts;
+
+    let focus_days: HashMap<String, FocusDayScore> = stored_focus_days
+        .into_iter()
+        .map(|(date, day)| (date.clone(), FocusDayScore::from_stored(date, &day)))
+        .collect();
+    let mut app_switches: HashMap<String, AppSwitchDay> = stored_app_switches
+        .into_iter()
+        .map(|(date, day)| (date, AppSwitchDay::from_stored(day)))
+        .collect();
+    // Analytics written before pairs were tracked only kept the total in focus summaries.
+    for (date, day) in &focus_days {
+        if day.app_switches > 0 && !app_switches.contains_key(date) {
+            app_switches.insert(
+                date.clone(),
+                AppSwitchDay {
+                    total: day.app_switches,
+                    pairs: HashMap::new(),
+                },
+            );
+        }
+    }
+    merge_days(
+        &mut app_switches,
+        std::mem::take(&mut state.app_switches),
+        AppSwitchDay::merge,
+    );
+    state.app_switches = app_switches;
+    // Focus summaries are recomputed from stats for recent days, so a live one just replaces.
+    let mut focus_days = focus_days;
+    focus_days.extend(std::mem::take(&mut state.focus_days));
+    state.focus_days = focus_days;
+
+    let mut coverage: HashMap<String, CoverageDay> = stored_coverage
+        .iter()
+        .map(|(date, day)| (date.clone(), CoverageDay::from_stored(day)))
+        .collect();
+    merge_days(
+        &mut coverage,
+        std::mem::take(&mut state.coverage),
+        |into, day| into.merge(&day),
+    );
+    state.coverage = coverage;
+
+    // The span opened at startup stays last, so runtime checkpoints keep extending it.
+    let mut runtime_spans = runtime_from_stored(&stored_runtime);
+    runtime_spans.append(&mut state.runtime_spans);
+    runtime_spans.sort_by_key(|span| span.start_ms);
+    state.runtime_spans = runtime_spans;
+
+    let mut burst_days: HashMap<String, BurstDay> = stored_bursts
+        .iter()
+        .map(|(date, day)| (date.clone(), BurstDay::from_stored(day)))
+        .collect();
+    merge_days(
+        &mut burst_days,
+        std::mem::take(&mut state.burst_days),
+        |into, day| into.merge(&day),
+    );
+    state.burst_days = burst_days;
+
+    merge_days(
+        &mut loaded_effort,
+        std::mem::take(&mut state.effort_days),
+        |into, milli| *into = into.saturating_add(milli),
+    );
+    state.effort_days = loaded_effort;
+
+    merge_days(
+        &mut loaded_displays,
+        std::mem::take(&mut state.display_days),
+        |into, displays| {
+            for (display_id, count) in displays {
+                let entry = into.entry(display_id).or_insert(0);
+                *entry = entry.saturating_add(count);
+            }
+        },
+    );
+    state.display_days = loaded_displays;
+
+    let mut copy_paste_days: HashMap<String, CopyPasteDay> = stored_copy_pastes
+        .into_iter()
+        .map(|(date, day)| (date, CopyPasteDay::from_stored(day)))
+        .collect();
+    merge_days(
+        &mut copy_paste_days,
+        std::mem::take(&mut state.copy_paste_days),
+        CopyPasteDay::merge,
+    );
+    state.copy_paste_days = copy_paste_days;
+
+    merge_days(
+        &mut loaded_key_spans,
+        std::mem::take(&mut state.key_spans),
+        merge_key_span,
+    );
+    state.key_spans = loaded_key_spans;
+
+    loaded_weekly_comparisons.extend(std::mem::take(&mut state.weekly_comparisons));
+    state.weekly_comparisons = loaded_weekly_comparisons;
+
+    let today = Local::now().date_naive();
+    state.today_totals = compute_today_totals(&state.stats, today);
+    state.rolling_averages = rolling_averages(&state.stats, &state.coverage, today);
+}
+
+impl CollectorState {
+    /// Whether the stored history is still loading in the background.
+    pub fn history_loading(&self) -> bool {
+        self.history_load.is_some()
+    }
+
+    /// Writes are refused until the stored history is merged: a save now would replace the
+    /// stored days with the few rows recorded since startup.
+    pub(crate) fn ensure_history_loaded(&self) -> Result<(), String> {
+        if self.history_loading() {
+            return Err("history is still loading".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Load the stored history of `state` on a background thread and merge it in, then merge
+/// crash dumps left in `data_dir`, which need the full history underneath. Does nothing when
+/// the state has no load pending.
+pub fn start_history_load(state: Arc<Mutex<CollectorState>>, data_dir: PathBuf) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let (storage, progress, app_log_path) = match state.lock() {
+            Ok(locked) => match locked.history_load.clone() {
+                Some(progress) => (
+                    locked.storage.clone(),
+                    progress,
+                    locked.app_log_path.clone(),
+                ),
+                None => return,
+            },
+            Err(_) => return,
+        };
+        let started = Instant::now();
+        let history = load_history(storage.as_ref(), &progress);
+        let Ok(mut locked) = state.lock() else {
+            return;
+        };
+        // A reset or data set switch while loading replaced what this load belonged to.
+        if !locked
+            .history_load
+            .as_ref()
+            .is_some_and(|pending| Arc::ptr_eq(pending, &progress))
+        {
+            let _ = append_app_log(&app_log_path, "history load discarded");
+            return;
+        }
+        merge_history(&mut locked, history, &|message| {
+            let _ = append_app_log(&app_log_path, message);
+        });
+        locked.history_load = None;
+        let (files, _) = progress.files();
+        let _ = append_app_log(
+            &app_log_path,
+            &format!(
+                "history loaded: {} files in {}ms",
+                files,
+                started.elapsed().as_millis()
+            ),
+        );
+        recover_crash_dumps(&mut locked, &data_dir);
+    })
+}
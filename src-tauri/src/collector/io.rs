@@ -1,59 +1,111 @@
 //! Collector file I/O module.
-//! Handles CSV/debug log persistence only; business aggregation stays elsewhere.
+//! Handles CSV persistence only; business aggregation stays elsewhere.
 
 use std::{fs::File, io::Write, path::PathBuf};
 
-use chrono::Local;
+use crate::app_config::{AppConfig, CsvDelimiter};
 
+use super::window_count::{distinct_windows_by_app_day, distinct_windows_of};
 use super::StatsRow;
 
-// Persist aggregated rows into CSV for external inspection/debugging.
-pub(super) fn write_csv(path: &PathBuf, rows: &[StatsRow]) -> Result<(), String> {
-    let mut file = File::create(path).map_err(|e| e.to_string())?;
-    writeln!(
-        file,
-        "date,app_name,window_title,active_typing_ms,key_count,session_count"
-    )
-    .map_err(|e| e.to_string())?;
-    for row in rows {
-        let line = format!(
-            "{},{},{},{},{},{}",
-            escape_csv(&row.date),
-            escape_csv(&row.app_name),
-            escape_csv(&row.window_title),
-            row.active_typing_ms,
-            row.key_count,
-            row.session_count
-        );
-        writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+/// Column order of the CSV summary file.
+pub(crate) const CSV_COLUMNS: [&str; 9] = [
+    "date",
+    "app_name",
+    "window_title",
+    "active_typing_ms",
+    "key_count",
+    "session_count",
+    "distinct_windows",
+    "writing_ms",
+    "navigation_ms",
+];
+
+/// Localized header of the CSV summary file, same order as `CSV_COLUMNS`.
+const CSV_COLUMNS_LOCALIZED: [&str; 9] = [
+    "日期",
+    "应用",
+    "窗口标题",
+    "活跃打字时长（毫秒）",
+    "按键数",
+    "会话数",
+    "当日窗口数",
+    "书写时长（毫秒）",
+    "导航时长（毫秒）",
+];
+
+const UTF8_BOM: &str = "\u{feff}";
+
+/// How CSV files are written: field separator, optional UTF-8 BOM and header language.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct CsvDialect {
+    pub(crate) delimiter: CsvDelimiter,
+    pub(crate) utf8_bom: bool,
+    pub(crate) headers_localized: bool,
+}
+
+impl CsvDialect {
+    pub(crate) fn from_config(config: &AppConfig) -> Self {
+        Self {
+            delimiter: config.csv_delimiter,
+            utf8_bom: config.csv_utf8_bom,
+            headers_localized: config.csv_headers_localized,
+        }
     }
-    Ok(())
 }
 
-fn escape_csv(value: &str) -> String {
-    if value.contains(',') || value.contains('"') || value.contains('\n') {
-        let escaped = value.replace('"', "\"\"");
-        format!("\"{}\"", escaped)
+// Render rows as CSV text in the given dialect.
+pub(super) fn format_csv(rows: &[StatsRow], dialect: CsvDialect) -> String {
+    let delimiter = dialect.delimiter.as_char();
+    let separator = delimiter.to_string();
+    let mut out = String::new();
+    if dialect.utf8_bom {
+        out.push_str(UTF8_BOM);
+    }
+    let header = if dialect.headers_localized {
+        CSV_COLUMNS_LOCALIZED
     } else {
-        value.to_string()
+        CSV_COLUMNS
+    };
+    out.push_str(&header.join(&separator));
+    out.push('\n');
+    // Every row of an app-day repeats that day's distinct window count.
+    let distinct_windows = distinct_windows_by_app_day(rows);
+    for row in rows {
+        let fields = [
+            escape_csv(&row.date.to_string(), delimiter),
+            escape_csv(&row.app_name, delimiter),
+            escape_csv(&row.window_title, delimiter),
+            row.active_typing_ms.to_string(),
+            row.key_count.to_string(),
+            row.session_count.to_string(),
+            distinct_windows_of(&distinct_windows, row).to_string(),
+            row.writing_ms.to_string(),
+            row.navigation_ms.to_string(),
+        ];
+        out.push_str(&fields.join(&separator));
+        out.push('\n');
     }
+    out
 }
 
-/// Append one line into runtime app log file.
-pub fn append_app_log(path: &PathBuf, message: &str) -> Result<(), String> {
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)
-        .map_err(|e| e.to_string())?;
-    let line = format!("{} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), message);
-    writeln!(file, "{}", line).map_err(|e| e.to_string())?;
-    print_line_if_dev(&line);
-    Ok(())
+// Persist aggregated rows into CSV for external inspection/debugging.
+pub(super) fn write_csv(
+    path: &PathBuf,
+    rows: &[StatsRow],
+    dialect: CsvDialect,
+) -> Result<(), String> {
+    let mut file = File::create(path).map_err(|e| e.to_string())?;
+    file.write_all(format_csv(rows, dialect).as_bytes())
+        .map_err(|e| e.to_string())
 }
 
-fn print_line_if_dev(line: &str) {
-    if cfg!(debug_assertions) {
-        println!("[TypePulse] {}", line);
+// Quote a field that contains the active delimiter, a quote or a line break.
+fn escape_csv(value: &str, delimiter: char) -> String {
+    if value.contains(delimiter) || value.contains(['"', '\n', '\r']) {
+        let escaped = value.replace('"', "\"\"");
+        format!("\"{}\"", escaped)
+    } else {
+        value.to_string()
     }
 }
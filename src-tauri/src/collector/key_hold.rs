@@ -0,0 +1,203 @@
+//! Key hold module.
+//! Pairs each key-down with the next key-up of the same key to measure how long keys are held,
+//! per key and overall.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::key_names::physical_key_name;
+use super::shortcut::{parse_compact_event, shortcut_range_window_ms, INPUT_CHUNK_VERSION};
+use super::CollectorState;
+
+/// A key-down still pending when the next chunk of the same app starts more than this long
+/// after the previous chunk's last event is dropped as unmatched.
+pub(super) const HOLD_CHUNK_JOIN_MS: i64 = 2_000;
+/// Holds longer than this are treated as a lost key-up (crash, pause, focus loss) rather
+/// than a real hold.
+pub(super) const MAX_KEY_HOLD_MS: i64 = 10_000;
+
+/// Hold time statistics of one normalized key.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct KeyHoldRow {
+    pub key: String,
+    pub count: u64,
+    pub unmatched_downs: u64,
+    pub mean_ms: f64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Hold time statistics for a range, most pressed keys first.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct KeyHoldStats {
+    pub range: String,
+    pub matched: u64,
+    pub unmatched_downs: u64,
+    pub mean_ms: f64,
+    pub p95_ms: u64,
+    pub keys: Vec<KeyHoldRow>,
+}
+
+/// One event chunk as replayed for hold pairing.
+pub(super) struct HoldChunk<'a> {
+    pub(super) v: u8,
+    pub(super) chunk_start_ms: i64,
+    pub(super) app_ref: u32,
+    pub(super) events: &'a [String],
+}
+
+/// Paired hold durations and unmatched key-down counts per key.
+#[derive(Default, Debug)]
+pub(super) struct KeyHolds {
+    pub(super) durations: HashMap<String, Vec<u64>>,
+    pub(super) unmatched: HashMap<String, u64>,
+}
+
+impl KeyHolds {
+    // Settle a pending key-down; only downs inside the requested window are counted.
+    fn settle(&mut self, key: &str, down_ms: i64, up_ms: Option<i64>, window: (i64, i64)) {
+        if down_ms < window.0 || down_ms >= window.1 {
+            return;
+        }
+        match up_ms.map(|up_ms| up_ms - down_ms) {
+            Some(held_ms) if (0..=MAX_KEY_HOLD_MS).contains(&held_ms) => self
+                .durations
+                .entry(key.to_string())
+                .or_default()
+                .push(held_ms as u64),
+            _ => *self.unmatched.entry(key.to_string()).or_insert(0) += 1,
+        }
+    }
+
+    fn flush(&mut self, pending: &mut HashMap<String, i64>, window: (i64, i64)) {
+        for (key, down_ms) in pending.drain() {
+            self.settle(&key, down_ms, None, window);
+        }
+    }
+}
+
+/// Replay chunks in start order and pair every key-down with the next key-up of the same key.
+/// Pending downs carry into the next chunk only when it belongs to the same app and starts
+/// within `HOLD_CHUNK_JOIN_MS`; a repeated down before the up counts the earlier one as
+/// unmatched.
+pub(super) fn pair_key_holds(chunks: &mut [HoldChunk<'_>], start_ms: i64, end_ms: i64) -> KeyHolds {
+    let window = (start_ms, end_ms);
+    chunks.sort_by_key(|chunk| chunk.chunk_start_ms);
+    let mut holds = KeyHolds::default();
+    let mut pending: HashMap<String, i64> = HashMap::new();
+    let mut previous: Option<(u32, i64)> = None;
+    for chunk in chunks.iter() {
+        let joins = previous.is_some_and(|(app_ref, last_ms)| {
+            app_ref == chunk.app_ref && chunk.chunk_start_ms - last_ms <= HOLD_CHUNK_JOIN_MS
+        });
+        if !joins {
+            holds.flush(&mut pending, window);
+        }
+        let mut last_ms = chunk.chunk_start_ms;
+        for raw_event in chunk.events {
+            let Some((dt, event_type, key, _)) = parse_compact_event(chunk.v, raw_event) else {
+                continue;
+            };
+            // Shift may be released before the key, so downs and ups pair by physical key.
+            let key = physical_key_name(&key).to_string();
+            let event_ms = chunk.chunk_start_ms.saturating_add(dt.max(0));
+            last_ms = last_ms.max(event_ms);
+            match event_type {
+                'd' => {
+                    if let Some(earlier_ms) = pending.insert(key.clone(), event_ms) {
+                        holds.settle(&key, earlier_ms, None, window);
+                    }
+                }
+                // Ups without a pending down (pressed while paused) are ignored.
+                'u' => {
+                    if let Some(down_ms) = pending.remove(&key) {
+                        holds.settle(&key, down_ms, Some(event_ms), window);
+                    }
+                }
+                _ => {}
+            }
+        }
+        previous = Some((chunk.app_ref, last_ms));
+    }
+    holds.flush(&mut pending, window);
+    holds
+}
+
+// Nearest-rank percentile of sorted durations.
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn mean(durations: &[u64]) -> f64 {
+    if durations.is_empty() {
+        return 0.0;
+    }
+    durations.iter().sum::<u64>() as f64 / durations.len() as f64
+}
+
+// Summarize paired holds into per-key rows sorted by hold count, then key.
+pub(super) fn build_key_hold_stats(range: &str, holds: KeyHolds) -> KeyHoldStats {
+    let KeyHolds {
+        mut durations,
+        unmatched,
+    } = holds;
+    for key in unmatched.keys() {
+        durations.entry(key.clone()).or_default();
+    }
+    let mut all: Vec<u64> = Vec::new();
+    let mut keys: Vec<KeyHoldRow> = durations
+        .into_iter()
+        .map(|(key, mut held)| {
+            held.sort_unstable();
+            all.extend_from_slice(&held);
+            KeyHoldRow {
+                unmatched_downs: unmatched.get(&key).copied().unwrap_or(0),
+                count: held.len() as u64,
+                mean_ms: mean(&held),
+                p95_ms: percentile(&held, 95),
+                max_ms: held.last().copied().unwrap_or(0),
+                key,
+            }
+        })
+        .collect();
+    keys.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    all.sort_unstable();
+    KeyHoldStats {
+        range: range.to_string(),
+        matched: all.len() as u64,
+        unmatched_downs: unmatched.values().sum(),
+        mean_ms: mean(&all),
+        p95_ms: percentile(&all, 95),
+        keys,
+    }
+}
+
+/// Build key hold statistics of `today` / `yesterday` / `7d` from stored and open chunks.
+pub fn snapshot_key_hold_stats(state: &CollectorState, range: &str) -> KeyHoldStats {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let mut chunks: Vec<HoldChunk<'_>> = state
+        .event_chunks
+        .iter()
+        .map(|chunk| HoldChunk {
+            v: chunk.v,
+            chunk_start_ms: chunk.chunk_start_ms,
+            app_ref: chunk.app_ref,
+            events: &chunk.events,
+        })
+        .collect();
+    if let Some(open_chunk) = state.open_event_chunk.as_ref() {
+        chunks.push(HoldChunk {
+            v: INPUT_CHUNK_VERSION,
+            chunk_start_ms: open_chunk.chunk_start_ms,
+            app_ref: open_chunk.app_ref,
+            events: &open_chunk.events,
+        });
+    }
+    build_key_hold_stats(range, pair_key_holds(&mut chunks, start_ms, end_ms))
+}
@@ -0,0 +1,165 @@
+//! Key names module.
+//! Canonical names of symbol keys. Shift plus a digit or punctuation key can reach the
+//! collector as the physical key (`1` with shift held) or as the character it produces (`!`),
+//! and older rdev builds named punctuation keys after their `rdev::Key` variant (`semicolon`).
+//! Every spelling is mapped onto one name, so top keys and shortcut counts do not split.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::app_config::SymbolKeyNames;
+
+use super::shortcut::normalize_shortcut_id;
+use super::{CollectorState, ModifierSnapshot, ShortcutUsageValue};
+
+// Unshifted and shifted symbol of each key on the US layout, which both listeners name keys by.
+const SHIFTED_SYMBOLS: &[(&str, &str)] = &[
+    ("`", "~"),
+    ("1", "!"),
+    ("2", "@"),
+    ("3", "#"),
+    ("4", "$"),
+    ("5", "%"),
+    ("6", "^"),
+    ("7", "&"),
+    ("8", "*"),
+    ("9", "("),
+    ("0", ")"),
+    ("-", "_"),
+    ("=", "+"),
+    ("[", "{"),
+    ("]", "}"),
+    ("\\", "|"),
+    (";", ":"),
+    ("'", "\""),
+    (",", "<"),
+    (".", ">"),
+    ("/", "?"),
+];
+
+// Lowercased `rdev::Key` names of symbol keys, as recorded before the listener mapped them.
+const RDEV_SYMBOL_NAMES: &[(&str, &str)] = &[
+    ("backquote", "`"),
+    ("minus", "-"),
+    ("equal", "="),
+    ("leftbracket", "["),
+    ("rightbracket", "]"),
+    ("semicolon", ";"),
+    ("quote", "'"),
+    ("backslash", "\\"),
+    ("comma", ","),
+    ("dot", "."),
+    ("slash", "/"),
+];
+
+fn unalias(key: &str) -> &str {
+    RDEV_SYMBOL_NAMES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map_or(key, |(_, symbol)| symbol)
+}
+
+/// Name of the physical key behind `key`: shifted symbols map to their unshifted key and
+/// rdev names to the symbol. Other keys are returned as given.
+pub(super) fn physical_key_name(key: &str) -> &str {
+    let key = unalias(key);
+    SHIFTED_SYMBOLS
+        .iter()
+        .find(|(_, shifted)| *shifted == key)
+        .map_or(key, |(base, _)| base)
+}
+
+/// Canonical name of `key` pressed with or without shift. `Physical` names the key (`1`),
+/// `Produced` the character it types (`!` with shift held). Either way the shift flag stays
+/// with the event, so shortcut ids keep their `shift` part.
+pub(super) fn canonical_key_name(key: &str, shift: bool, names: SymbolKeyNames) -> String {
+    match names {
+        SymbolKeyNames::Physical => physical_key_name(key).to_string(),
+        SymbolKeyNames::Produced => {
+            let key = unalias(key);
+            if !shift {
+                return key.to_string();
+            }
+            SHIFTED_SYMBOLS
+                .iter()
+                .find(|(base, _)| *base == key)
+                .map_or(key, |(_, shifted)| shifted)
+                .to_string()
+        }
+    }
+}
+
+// Modifiers and key of a shortcut id built by `normalize_shortcut_id`. Prefixes are stripped
+// rather than split on `_`, which is a key of its own.
+fn split_shortcut_id(shortcut_id: &str) -> (ModifierSnapshot, &str) {
+    let mut modifiers = ModifierSnapshot::default();
+    let mut rest = shortcut_id;
+    // In `normalize_shortcut_id` order; the last part is always the key.
+    for (prefix, flag) in [
+        ("ctrl_", &mut modifiers.ctrl),
+        ("opt_", &mut modifiers.opt),
+        ("shift_", &mut modifiers.shift),
+        ("cmd_", &mut modifiers.cmd),
+    ] {
+        if rest.len() > prefix.len() {
+            if let Some(stripped) = rest.strip_prefix(prefix) {
+                rest = stripped;
+                *flag = true;
+            }
+        }
+    }
+    (modifiers, rest)
+}
+
+/// `shortcut_id` with its key renamed by `names`.
+pub(super) fn canonical_shortcut_key_id(shortcut_id: &str, names: SymbolKeyNames) -> String {
+    let (modifiers, key) = split_shortcut_id(shortcut_id);
+    normalize_shortcut_id(modifiers, &canonical_key_name(key, modifiers.shift, names))
+}
+
+/// Re-key lifetime shortcut usage by `names`, summing ids that become the same. Returns the
+/// number of ids renamed.
+pub(super) fn canonicalize_shortcut_usage(
+    usage: &mut HashMap<String, ShortcutUsageValue>,
+    names: SymbolKeyNames,
+) -> usize {
+    let mut renamed = 0;
+    let mut canonical: HashMap<String, ShortcutUsageValue> = HashMap::with_capacity(usage.len());
+    for (shortcut_id, value) in usage.drain() {
+        let canonical_id = canonical_shortcut_key_id(&shortcut_id, names);
+        if canonical_id != shortcut_id {
+            renamed += 1;
+        }
+        let entry = canonical.entry(canonical_id).or_default();
+        entry.count = entry.count.saturating_add(value.count);
+        for (app_id, count) in value.by_app {
+            let app_count = entry.by_app.entry(app_id).or_insert(0);
+            *app_count = app_count.saturating_add(count);
+        }
+    }
+    *usage = canonical;
+    renamed
+}
+
+/// Shortcut allow/block list entries, trimmed, lowercased and renamed by `names`.
+pub(super) fn canonical_shortcut_id_set<'a>(
+    shortcut_ids: impl IntoIterator<Item = &'a String>,
+    names: SymbolKeyNames,
+) -> HashSet<String> {
+    shortcut_ids
+        .into_iter()
+        .map(|id| id.trim().to_ascii_lowercase())
+        .filter(|id| !id.is_empty())
+        .map(|id| canonical_shortcut_key_id(&id, names))
+        .collect()
+}
+
+impl CollectorState {
+    /// Name symbol keys by `names` from now on and migrate the lifetime shortcut counts and
+    /// shortcut lists to it. Event chunks are renamed when replayed, so ranges follow too.
+    pub(crate) fn set_symbol_key_names(&mut self, names: SymbolKeyNames) -> usize {
+        self.symbol_key_names = names;
+        self.shortcut_allowlist = canonical_shortcut_id_set(&self.shortcut_allowlist, names);
+        self.shortcut_blocklist = canonical_shortcut_id_set(&self.shortcut_blocklist, names);
+        canonicalize_shortcut_usage(&mut self.shortcut_usage, names)
+    }
+}
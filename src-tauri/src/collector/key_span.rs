@@ -0,0 +1,53 @@
+//! Key span module.
+//! First and last counted key-down of each local day ("started at 08:42, ended at 19:10").
+//! Spans are keyed by local day, so the first key after midnight opens a new pair, and they
+//! are kept with the analytics so a restart in the afternoon keeps the morning.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local, NaiveDate, Timelike, Utc};
+
+use crate::storage::StoredKeySpan;
+
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+/// Widen the span of the local day of `at_ms` to include it.
+pub(super) fn add_key_span(days: &mut HashMap<String, StoredKeySpan>, at_ms: i64) {
+    let Some(at) = DateTime::<Utc>::from_timestamp_millis(at_ms) else {
+        return;
+    };
+    let day = at.with_timezone(&Local).format(DAY_KEY_FORMAT).to_string();
+    let span = StoredKeySpan {
+        first_ms: at_ms,
+        last_ms: at_ms,
+    };
+    days.entry(day)
+        .and_modify(|existing| merge_key_span(existing, span))
+        .or_insert(span);
+}
+
+/// Add a counted key-down to the day spans. `at_ms` is the timestamp written to the event
+/// chunk.
+pub(super) fn record_key_span(state: &mut CollectorState, at_ms: i64) {
+    add_key_span(&mut state.key_spans, at_ms);
+}
+
+/// Widen `into` to cover `other`.
+pub(super) fn merge_key_span(into: &mut StoredKeySpan, other: StoredKeySpan) {
+    into.first_ms = into.first_ms.min(other.first_ms);
+    into.last_ms = into.last_ms.max(other.last_ms);
+}
+
+/// Span of local `day`; None before its first counted key.
+pub(super) fn key_span_on(state: &CollectorState, day: NaiveDate) -> Option<StoredKeySpan> {
+    state
+        .key_spans
+        .get(&day.format(DAY_KEY_FORMAT).to_string())
+        .copied()
+}
+
+/// Minutes since local midnight of `at_ms`.
+pub(super) fn local_minute_of_day(at_ms: i64) -> Option<u32> {
+    let local = DateTime::<Utc>::from_timestamp_millis(at_ms)?.with_timezone(&Local);
+    Some(local.hour() * 60 + local.minute())
+}
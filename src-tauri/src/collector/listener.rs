@@ -3,10 +3,14 @@
 
 use std::sync::{Arc, Mutex};
 
+#[cfg(target_os = "macos")]
+use super::event_time::mach_event_ms;
 use super::modifier::ModifierSnapshot;
 #[cfg(not(target_os = "macos"))]
 use super::modifier::ModifierState;
-use super::{on_non_modifier_key_down, on_non_modifier_key_up, CollectorState};
+use super::{
+    on_modifiers_changed, on_non_modifier_key_down, on_non_modifier_key_up, CollectorState,
+};
 
 #[cfg(not(target_os = "macos"))]
 fn normalize_non_macos_key(key: rdev::Key) -> Option<String> {
@@ -48,6 +52,17 @@ fn normalize_non_macos_key(key: rdev::Key) -> Option<String> {
         Key::Num7 => "7",
         Key::Num8 => "8",
         Key::Num9 => "9",
+        Key::BackQuote => "`",
+        Key::Minus => "-",
+        Key::Equal => "=",
+        Key::LeftBracket => "[",
+        Key::RightBracket => "]",
+        Key::SemiColon => ";",
+        Key::Quote => "'",
+        Key::BackSlash => "\\",
+        Key::Comma => ",",
+        Key::Dot => ".",
+        Key::Slash => "/",
         Key::Space => "space",
         Key::Return => "enter",
         Key::Tab => "tab",
@@ -68,17 +83,24 @@ pub(super) fn on_key_event_non_macos(
     state: &Arc<Mutex<CollectorState>>,
     key: rdev::Key,
     pressed: bool,
+    event_ms: Option<i64>,
 ) {
-    let (is_modifier_key, modifiers_before) = if let Ok(mut locked) = state.lock() {
+    let (is_modifier_key, modifiers_before, modifiers_after) = if let Ok(mut locked) = state.lock()
+    {
         let is_modifier_key = ModifierState::is_modifier_key(key);
         let modifiers_before = locked.modifier_state.snapshot();
         locked.modifier_state.update(key, pressed);
-        (is_modifier_key, modifiers_before)
+        (
+            is_modifier_key,
+            modifiers_before,
+            locked.modifier_state.snapshot(),
+        )
     } else {
         return;
     };
 
     if is_modifier_key {
+        on_modifiers_changed(state, modifiers_after);
         return;
     }
 
@@ -92,9 +114,16 @@ pub(super) fn on_key_event_non_macos(
             shortcut_key,
             modifiers_before,
             modifiers_before.has_any(),
+            event_ms,
         );
     } else {
-        on_non_modifier_key_up(state, &physical_key_id, &shortcut_key, modifiers_before);
+        on_non_modifier_key_up(
+            state,
+            &physical_key_id,
+            &shortcut_key,
+            modifiers_before,
+            event_ms,
+        );
     }
 }
 
@@ -122,6 +151,7 @@ pub(super) fn listen_keypress_macos(state: Arc<Mutex<CollectorState>>) -> Result
     const CG_EVENT_TAP_OPTION_LISTEN_ONLY: CGEventTapOptions = 1;
     const CG_EVENT_TYPE_KEY_DOWN: CGEventType = 10;
     const CG_EVENT_TYPE_KEY_UP: CGEventType = 11;
+    const CG_EVENT_TYPE_FLAGS_CHANGED: CGEventType = 12;
     type CGEventFlags = u64;
     const CG_EVENT_FLAG_MASK_SHIFT: CGEventFlags = 1 << 17;
     const CG_EVENT_FLAG_MASK_CONTROL: CGEventFlags = 1 << 18;
@@ -194,6 +224,10 @@ pub(super) fn listen_keypress_macos(state: Arc<Mutex<CollectorState>>) -> Result
             49 => "space",
             51 => "backspace",
             53 => "esc",
+            115 => "home",
+            116 => "pageup",
+            119 => "end",
+            121 => "pagedown",
             123 => "left",
             124 => "right",
             125 => "down",
@@ -228,6 +262,7 @@ pub(super) fn listen_keypress_macos(state: Arc<Mutex<CollectorState>>) -> Result
         fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
         fn CGEventGetFlags(event: CGEventRef) -> CGEventFlags;
         fn CGEventGetIntegerValueField(event: CGEventRef, field: CGEventField) -> i64;
+        fn CGEventGetTimestamp(event: CGEventRef) -> u64;
         static kCFRunLoopCommonModes: CFRunLoopMode;
     }
 
@@ -245,6 +280,7 @@ pub(super) fn listen_keypress_macos(state: Arc<Mutex<CollectorState>>) -> Result
             let physical_key_id = format!("mac:{key_code}");
             let shortcut_key = normalize_macos_keycode(key_code);
             let modifiers = snapshot_from_macos_flags(flags);
+            let event_ms = mach_event_ms(CGEventGetTimestamp(event));
             if type_ == CG_EVENT_TYPE_KEY_DOWN {
                 on_non_modifier_key_down(
                     state,
@@ -252,21 +288,35 @@ pub(super) fn listen_keypress_macos(state: Arc<Mutex<CollectorState>>) -> Result
                     shortcut_key,
                     modifiers,
                     modifiers.has_any(),
+                    event_ms,
                 );
             } else {
-                on_non_modifier_key_up(state, &physical_key_id, &shortcut_key, modifiers);
+                on_non_modifier_key_up(state, &physical_key_id, &shortcut_key, modifiers, event_ms);
             }
+        } else if type_ == CG_EVENT_TYPE_FLAGS_CHANGED {
+            let state = &*(user_info as *const Arc<Mutex<CollectorState>>);
+            on_modifiers_changed(state, snapshot_from_macos_flags(CGEventGetFlags(event)));
         }
         event
     }
 
+    // Modifier transitions are only tapped when chord tracking is enabled at startup.
+    let mut event_mask = (1u64 << CG_EVENT_TYPE_KEY_DOWN) | (1u64 << CG_EVENT_TYPE_KEY_UP);
+    if state
+        .lock()
+        .map(|locked| locked.track_chord_aborts)
+        .unwrap_or(false)
+    {
+        event_mask |= 1u64 << CG_EVENT_TYPE_FLAGS_CHANGED;
+    }
+
     let user_info = Box::into_raw(Box::new(state)) as *mut c_void;
     unsafe {
         let tap = CGEventTapCreate(
             CG_EVENT_TAP_LOCATION_HID,
             CG_EVENT_TAP_PLACEMENT_HEAD_INSERT,
             CG_EVENT_TAP_OPTION_LISTEN_ONLY,
-            (1u64 << CG_EVENT_TYPE_KEY_DOWN) | (1u64 << CG_EVENT_TYPE_KEY_UP),
+            event_mask,
             callback,
             user_info,
         );
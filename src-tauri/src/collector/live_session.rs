@@ -0,0 +1,58 @@
+//! Live typing session module.
+//! Tracks the running session's start and counters for the tray's second overview line.
+
+use std::time::Instant;
+
+use super::CollectorState;
+
+// Counters of the session in progress. Replaced exactly when a counted key-down crosses the
+// session gap, the same boundary that bumps `session_count`, so short pauses keep it.
+#[derive(Clone, Debug)]
+pub(super) struct LiveSession {
+    started_at: Instant,
+    key_count: u64,
+}
+
+/// Running session as rendered by the tray: elapsed time since its first key and keys typed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct LiveSessionSummary {
+    pub(crate) duration_ms: u64,
+    pub(crate) key_count: u64,
+}
+
+/// Count one key-down into the live session, starting a new one when `new_session` is set
+/// (the key crossed the session gap) or no session was started since launch.
+pub(super) fn record_live_session_key(state: &mut CollectorState, new_session: bool, at: Instant) {
+    match state.live_session.as_mut() {
+        Some(session) if !new_session => session.key_count += 1,
+        _ => {
+            state.live_session = Some(LiveSession {
+                started_at: at,
+                key_count: 1,
+            });
+        }
+    }
+}
+
+/// Tray view of the live session at `now`. None while paused or auto-paused, and once typing
+/// stopped for longer than the session gap: the next key would start a new session anyway.
+pub(crate) fn live_session_summary(
+    state: &CollectorState,
+    now: Instant,
+) -> Option<LiveSessionSummary> {
+    if state.paused || state.auto_paused {
+        return None;
+    }
+    let session = state.live_session.as_ref()?;
+    let idle = state.pressed_non_modifier_keys.is_empty()
+        && now.saturating_duration_since(state.last_typing_instant) > state.session_gap;
+    if idle {
+        return None;
+    }
+    Some(LiveSessionSummary {
+        duration_ms: now
+            .saturating_duration_since(session.started_at)
+            .as_millis() as u64,
+        key_count: session.key_count,
+    })
+}
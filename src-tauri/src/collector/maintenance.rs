@@ -0,0 +1,199 @@
+//! Maintenance module.
+//! Holds heavy disk work back while the user types: large analytics saves, the stats rollup
+//! and the monthly archive are queued and run once no key went down for the idle window, or
+//! once the oldest queued job has waited the maximum deferral. Stats saves keep their cadence.
+
+use std::time::{Duration, Instant};
+
+use super::app_log::append_app_log;
+use super::CollectorState;
+
+/// Estimated size of the stored event chunks above which saving the input analytics counts
+/// as heavy work and waits for an idle moment.
+pub(super) const HEAVY_ANALYTICS_SAVE_BYTES: u64 = 1024 * 1024;
+
+/// Disk work the scheduler defers, in the order queued jobs run.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum MaintenanceJob {
+    AnalyticsSave,
+    Rollup,
+    Archive,
+}
+
+impl MaintenanceJob {
+    fn label(self) -> &'static str {
+        match self {
+            MaintenanceJob::AnalyticsSave => "analytics save",
+            MaintenanceJob::Rollup => "stats rollup",
+            MaintenanceJob::Archive => "monthly archive",
+        }
+    }
+}
+
+/// Queued maintenance jobs and when the last key went down.
+pub(super) struct MaintenanceScheduler {
+    idle: Duration,
+    max_defer: Duration,
+    last_key_down: Option<Instant>,
+    // Each job at most once, with when it was first queued.
+    queue: Vec<(MaintenanceJob, Instant)>,
+}
+
+impl MaintenanceScheduler {
+    pub(super) fn new(idle: Duration, max_defer: Duration) -> Self {
+        Self {
+            idle,
+            max_defer,
+            last_key_down: None,
+            queue: Vec::new(),
+        }
+    }
+
+    pub(super) fn set_windows(&mut self, idle: Duration, max_defer: Duration) {
+        self.idle = idle;
+        self.max_defer = max_defer;
+    }
+
+    pub(super) fn note_key_down(&mut self, at: Instant) {
+        self.last_key_down = Some(at);
+    }
+
+    /// Whether no key went down within the idle window before `now`.
+    pub(super) fn is_idle(&self, now: Instant) -> bool {
+        self.last_key_down
+            .is_none_or(|at| now.saturating_duration_since(at) >= self.idle)
+    }
+
+    /// Queue `job` unless it already waits; returns whether it was newly queued.
+    pub(super) fn enqueue(&mut self, job: MaintenanceJob, now: Instant) -> bool {
+        if self.is_queued(job) {
+            return false;
+        }
+        self.queue.push((job, now));
+        true
+    }
+
+    pub(super) fn is_queued(&self, job: MaintenanceJob) -> bool {
+        self.queue.iter().any(|(queued, _)| *queued == job)
+    }
+
+    // Drop `job` from the queue, e.g. after a full flush saved the analytics anyway.
+    fn forget(&mut self, job: MaintenanceJob) {
+        self.queue.retain(|(queued, _)| *queued != job);
+    }
+
+    /// Take every queued job, in run order with how long each waited, when the user is idle
+    /// or the oldest job waited the maximum deferral; nothing otherwise.
+    pub(super) fn take_due(&mut self, now: Instant) -> Vec<(MaintenanceJob, Duration)> {
+        let overdue = self
+            .queue
+            .iter()
+            .any(|(_, queued_at)| now.saturating_duration_since(*queued_at) >= self.max_defer);
+        if self.queue.is_empty() || !(overdue || self.is_idle(now)) {
+            return Vec::new();
+        }
+        let mut due: Vec<(MaintenanceJob, Duration)> = self
+            .queue
+            .drain(..)
+            .map(|(job, queued_at)| (job, now.saturating_duration_since(queued_at)))
+            .collect();
+        due.sort_by_key(|(job, _)| *job as u8);
+        due
+    }
+}
+
+/// Whether the periodic flush at `now` should leave the input analytics for later: they are
+/// large and a key went down within the idle window. Queues the save when it defers and
+/// drops a queued one when the flush saves them itself.
+pub(super) fn defer_analytics_save(state: &mut CollectorState, now: Instant) -> bool {
+    let heavy = state.chunk_counters.approx_bytes >= HEAVY_ANALYTICS_SAVE_BYTES;
+    if !heavy || state.maintenance.is_idle(now) {
+        state.maintenance.forget(MaintenanceJob::AnalyticsSave);
+        return false;
+    }
+    queue_maintenance(state, MaintenanceJob::AnalyticsSave, now);
+    true
+}
+
+/// Queue `job` to run at the next idle moment, logging the deferral once.
+pub(super) fn queue_maintenance(state: &mut CollectorState, job: MaintenanceJob, now: Instant) {
+    if state.maintenance.enqueue(job, now) {
+        let _ = append_app_log(
+            &state.app_log_path,
+            &format!("maintenance deferred until idle: {}", job.label()),
+        );
+    }
+}
+
+/// Run the queued jobs once the user is idle or the oldest waited too long. Nothing runs
+/// while the data directory is unavailable or the stored history is still loading.
+pub(super) fn run_maintenance_if_due(state: &mut CollectorState, now: Instant) {
+    if !state.storage_writable || state.history_loading() {
+        return;
+    }
+    for (job, waited) in state.maintenance.take_due(now) {
+        let _ = append_app_log(
+            &state.app_log_path,
+            &format!(
+                "maintenance running: {} after {}s deferred",
+                job.label(),
+                waited.as_secs()
+            ),
+        );
+        match job {
+            MaintenanceJob::AnalyticsSave => run_analytics_save(state),
+            MaintenanceJob::Rollup => run_rollup(state),
+            MaintenanceJob::Archive => run_archive(state),
+        }
+    }
+}
+
+fn run_analytics_save(state: &mut CollectorState) {
+    if let Err(err) = state.save_input_analytics() {
+        let _ = append_app_log(
+            &state.app_log_path,
+            &format!("deferred analytics save failed: {}", err),
+        );
+    }
+}
+
+fn run_rollup(state: &mut CollectorState) {
+    match state.rollup_old_stats() {
+        Ok(0) => {}
+        Ok(days) => {
+            let _ = append_app_log(
+                &state.app_log_path,
+                &format!("rolled up {} days of stats to hour resolution", days),
+            );
+        }
+        Err(err) => {
+            let _ = append_app_log(
+                &state.app_log_path,
+                &format!("stats rollup failed: {}", err),
+            );
+        }
+    }
+}
+
+fn run_archive(state: &mut CollectorState) {
+    match state.archive_old_months() {
+        Ok(report) if report.files == 0 => {}
+        Ok(report) => {
+            let _ = append_app_log(
+                &state.app_log_path,
+                &format!(
+                    "archived {} daily files ({} bytes) into {} monthly archives",
+                    report.files,
+                    report.bytes,
+                    report.months.len()
+                ),
+            );
+        }
+        Err(err) => {
+            let _ = append_app_log(
+                &state.app_log_path,
+                &format!("monthly archive failed: {}", err),
+            );
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Minute key module.
+//! Typed local `(day, minute of day)` bucket behind stats keys. Stored rows, CSV and snapshot
+//! payloads keep the legacy `YYYY-MM-DD HH:MM` string, produced by `Display` and parsed back
+//! by `FromStr`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const MINUTE_KEY_FORMAT: &str = "%Y-%m-%d %H:%M";
+/// Local day format used for day-keyed maps and daily file names.
+pub(crate) const DAY_KEY_FORMAT: &str = "%Y-%m-%d";
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// One local minute. Orders by day, then minute, matching the legacy string ordering.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct MinuteKey {
+    day: NaiveDate,
+    minute_of_day: u16,
+}
+
+impl MinuteKey {
+    /// Key for `hour:minute` of `day`, or None when the time is out of range.
+    pub(crate) fn new(day: NaiveDate, hour: u16, minute: u16) -> Option<Self> {
+        let minute_of_day = hour.checked_mul(60)?.checked_add(minute)?;
+        (minute < 60 && minute_of_day < MINUTES_PER_DAY).then_some(Self { day, minute_of_day })
+    }
+
+    pub(crate) fn from_datetime(value: NaiveDateTime) -> Self {
+        Self {
+            day: value.date(),
+            minute_of_day: (value.hour() * 60 + value.minute()) as u16,
+        }
+    }
+
+    /// Current local minute.
+    pub(crate) fn now() -> Self {
+        Self::from_datetime(Local::now().naive_local())
+    }
+
+    pub(crate) fn day(self) -> NaiveDate {
+        self.day
+    }
+
+    pub(crate) fn minute_of_day(self) -> u16 {
+        self.minute_of_day
+    }
+
+    /// First minute of this key's hour, the bucket hour rollups aggregate into.
+    pub(crate) fn hour_start(self) -> Self {
+        Self {
+            day: self.day,
+            minute_of_day: self.minute_of_day - self.minute_of_day % 60,
+        }
+    }
+
+    /// Local wall-clock start of this minute.
+    pub(crate) fn to_naive_datetime(self) -> NaiveDateTime {
+        let time = NaiveTime::from_hms_opt(
+            u32::from(self.minute_of_day / 60),
+            u32::from(self.minute_of_day % 60),
+            0,
+        )
+        .unwrap_or(NaiveTime::MIN);
+        self.day.and_time(time)
+    }
+}
+
+impl fmt::Display for MinuteKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_naive_datetime().format(MINUTE_KEY_FORMAT))
+    }
+}
+
+impl FromStr for MinuteKey {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        NaiveDateTime::parse_from_str(value, MINUTE_KEY_FORMAT)
+            .map(Self::from_datetime)
+            .map_err(|e| format!("invalid minute key {value:?}: {e}"))
+    }
+}
+
+impl Serialize for MinuteKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MinuteKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,97 @@
+//! Minute series module.
+//! Keeps per-minute key counts of the last hour in a ring buffer for the tray sparkline and
+//! the dashboard's live chart.
+
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+use super::CollectorState;
+
+/// Minutes kept in the ring buffer.
+pub(crate) const MINUTE_SERIES_LEN: usize = 60;
+const MINUTE_MS: i64 = 60_000;
+
+/// Ring buffer of key counts per wall-clock minute, oldest first; the back slot is
+/// `head_minute`.
+#[derive(Clone, Debug)]
+pub(crate) struct MinuteRing {
+    counts: VecDeque<u64>,
+    head_minute: i64,
+}
+
+impl Default for MinuteRing {
+    fn default() -> Self {
+        Self {
+            counts: VecDeque::from(vec![0; MINUTE_SERIES_LEN]),
+            head_minute: 0,
+        }
+    }
+}
+
+impl MinuteRing {
+    // Shift in empty minutes up to `minute`. A clock stepping back keeps the current head,
+    // so its keys land in the newest slot.
+    fn advance(&mut self, minute: i64) {
+        if minute <= self.head_minute {
+            return;
+        }
+        let shift = (minute - self.head_minute).min(MINUTE_SERIES_LEN as i64);
+        for _ in 0..shift {
+            self.counts.pop_front();
+            self.counts.push_back(0);
+        }
+        self.head_minute = minute;
+    }
+
+    // Counts of the `minutes` minutes ending at `minute`, oldest first. Minutes the ring has
+    // not rotated to yet read as zero, so readers need no mutable access.
+    fn counts_until(&self, minute: i64, minutes: usize) -> Vec<u64> {
+        let lag = (minute - self.head_minute).clamp(0, MINUTE_SERIES_LEN as i64) as usize;
+        let mut counts: Vec<u64> = self.counts.iter().skip(lag).copied().collect();
+        counts.resize(MINUTE_SERIES_LEN, 0);
+        counts.split_off(MINUTE_SERIES_LEN - minutes.min(MINUTE_SERIES_LEN))
+    }
+}
+
+/// Key counts per minute for `get_minute_series`, oldest first.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MinuteSeries {
+    /// Start of the newest (current, still filling) minute in epoch millis.
+    pub end_minute_ms: i64,
+    pub counts: Vec<u64>,
+}
+
+fn minute_of_ms(now_ms: i64) -> i64 {
+    now_ms.div_euclid(MINUTE_MS)
+}
+
+/// Count one key-down into the minute containing `now_ms`.
+pub(super) fn record_minute_key(state: &mut CollectorState, now_ms: i64) {
+    let ring = &mut state.minute_ring;
+    ring.advance(minute_of_ms(now_ms));
+    if let Some(newest) = ring.counts.back_mut() {
+        *newest += 1;
+    }
+}
+
+/// Tick hook: rotate idle minutes out of the ring.
+pub(super) fn rotate_minute_ring(state: &mut CollectorState, now_ms: i64) {
+    state.minute_ring.advance(minute_of_ms(now_ms));
+}
+
+/// Counts of the last `minutes` minutes (1–60) up to `now_ms`, oldest first.
+pub(crate) fn minute_counts(state: &CollectorState, now_ms: i64, minutes: usize) -> Vec<u64> {
+    state
+        .minute_ring
+        .counts_until(minute_of_ms(now_ms), minutes.clamp(1, MINUTE_SERIES_LEN))
+}
+
+/// Minute series payload of the last `minutes` minutes (clamped to 1–60).
+pub fn snapshot_minute_series(state: &CollectorState, minutes: usize) -> MinuteSeries {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    MinuteSeries {
+        end_minute_ms: minute_of_ms(now_ms) * MINUTE_MS,
+        counts: minute_counts(state, now_ms, minutes),
+    }
+}
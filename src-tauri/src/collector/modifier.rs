@@ -39,6 +39,17 @@ impl ModifierSnapshot {
         }
     }
 
+    // Modifiers held in either snapshot.
+    pub(super) fn union(self, other: Self) -> Self {
+        Self {
+            ctrl: self.ctrl || other.ctrl,
+            opt: self.opt || other.opt,
+            shift: self.shift || other.shift,
+            cmd: self.cmd || other.cmd,
+            function: self.function || other.function,
+        }
+    }
+
     pub(super) fn modifier_count(&self) -> u8 {
         self.ctrl as u8 + self.opt as u8 + self.shift as u8 + self.cmd as u8 + self.function as u8
     }
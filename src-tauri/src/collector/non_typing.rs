@@ -0,0 +1,138 @@
+//! Non-typing app module.
+//! Scores each app's daily key mix for game or navigation input and raises a one-time
+//! exclusion suggestion for apps that keep looking like it, like the 1Password suggestion.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Duration as ChronoDuration, NaiveDate};
+use serde::Serialize;
+
+use super::shortcut::{for_each_key_down_with_app_in_window, local_day_of_ms, local_day_window_ms};
+use super::CollectorState;
+
+/// Days with a likelihood at or above this count towards a suggestion.
+const NON_TYPING_THRESHOLD: f64 = 0.6;
+/// Flagged days within the lookback that raise a suggestion.
+const NON_TYPING_MIN_DAYS: usize = 3;
+/// Complete days (today excluded) scanned for flagged days.
+const NON_TYPING_LOOKBACK_DAYS: i64 = 7;
+/// Days with fewer keys for an app are not scored; a few arrow presses prove nothing.
+const NON_TYPING_MIN_DAY_KEYS: u64 = 200;
+/// Share of WASD among letters in ordinary prose (about a fifth in English); the likelihood
+/// is zero at this share and rises linearly to one when only movement keys are used.
+const TYPING_MOVEMENT_SHARE: f64 = 0.25;
+const MOVEMENT_KEYS: [&str; 8] = ["w", "a", "s", "d", "up", "down", "left", "right"];
+
+/// Key-down counts of one app on one day by key class.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(super) struct KeyClassCounts {
+    /// WASD and arrow keys.
+    pub(super) movement: u64,
+    /// Letters other than WASD.
+    pub(super) letters: u64,
+    /// Everything else: digits, space, punctuation, editing and function keys.
+    pub(super) other: u64,
+}
+
+impl KeyClassCounts {
+    fn add(&mut self, key: &str) {
+        if MOVEMENT_KEYS.contains(&key) {
+            self.movement += 1;
+        } else if key.len() == 1 && key.bytes().all(|byte| byte.is_ascii_alphabetic()) {
+            self.letters += 1;
+        } else {
+            self.other += 1;
+        }
+    }
+}
+
+/// Likelihood (0–1) that a day of an app's keys is game or navigation input rather than
+/// typing, from the share of movement keys among letter and movement keys. Days below
+/// `NON_TYPING_MIN_DAY_KEYS`, or without any letter or movement key, score zero.
+pub(super) fn non_typing_likelihood(counts: &KeyClassCounts) -> f64 {
+    let total = counts.movement + counts.letters + counts.other;
+    let scored = counts.movement + counts.letters;
+    if total < NON_TYPING_MIN_DAY_KEYS || scored == 0 {
+        return 0.0;
+    }
+    let share = counts.movement as f64 / scored as f64;
+    ((share - TYPING_MOVEMENT_SHARE) / (1.0 - TYPING_MOVEMENT_SHARE)).clamp(0.0, 1.0)
+}
+
+/// Pending suggestion to exclude an app whose keys look like game or navigation input.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct NonTypingSuggestion {
+    pub bundle_id: String,
+    /// Flagged days within the last week.
+    pub days: usize,
+    /// Highest daily likelihood among the flagged days.
+    pub likelihood: f64,
+}
+
+/// Once per local day, score the last week's complete days from event chunks and queue a
+/// suggestion for every app flagged on enough days that is neither excluded nor handled.
+pub(super) fn refresh_non_typing_suggestions(state: &mut CollectorState, today: NaiveDate) {
+    if state.last_non_typing_check == Some(today) {
+        return;
+    }
+    state.last_non_typing_check = Some(today);
+    let first_day = today - ChronoDuration::days(NON_TYPING_LOOKBACK_DAYS);
+    let (Some((start_ms, _)), Some((end_ms, _))) =
+        (local_day_window_ms(first_day), local_day_window_ms(today))
+    else {
+        return;
+    };
+    let mut by_app_day: HashMap<(String, NaiveDate), KeyClassCounts> = HashMap::new();
+    for_each_key_down_with_app_in_window(state, start_ms, end_ms, None, |app, at_ms, key, _| {
+        if let Some(day) = local_day_of_ms(at_ms) {
+            by_app_day
+                .entry((app.to_ascii_lowercase(), day))
+                .or_default()
+                .add(&key);
+        }
+    });
+    let mut flagged: BTreeMap<String, (usize, f64)> = BTreeMap::new();
+    for ((app, _), counts) in &by_app_day {
+        let likelihood = non_typing_likelihood(counts);
+        if likelihood >= NON_TYPING_THRESHOLD {
+            let entry = flagged.entry(app.clone()).or_default();
+            entry.0 += 1;
+            entry.1 = entry.1.max(likelihood);
+        }
+    }
+    for (bundle_id, (days, likelihood)) in flagged {
+        let known = state.excluded_bundle_ids.contains(&bundle_id)
+            || state.non_typing_handled.contains(&bundle_id)
+            || state
+                .non_typing_suggestions
+                .iter()
+                .any(|suggestion| suggestion.bundle_id == bundle_id);
+        if days >= NON_TYPING_MIN_DAYS && !known {
+            state.non_typing_suggestions.push(NonTypingSuggestion {
+                bundle_id,
+                days,
+                likelihood,
+            });
+        }
+    }
+}
+
+impl CollectorState {
+    /// Close the suggestion for `bundle_id` (accepted or dismissed) so it is never raised
+    /// again. Returns whether it was pending.
+    pub(crate) fn resolve_non_typing_suggestion(&mut self, bundle_id: &str) -> bool {
+        let bundle_id = bundle_id.trim().to_ascii_lowercase();
+        let before = self.non_typing_suggestions.len();
+        self.non_typing_suggestions
+            .retain(|suggestion| suggestion.bundle_id != bundle_id);
+        self.non_typing_handled.insert(bundle_id);
+        self.non_typing_suggestions.len() != before
+    }
+
+    /// Handled suggestion apps in config form (sorted).
+    pub(crate) fn non_typing_handled(&self) -> Vec<String> {
+        let mut handled: Vec<String> = self.non_typing_handled.iter().cloned().collect();
+        handled.sort();
+        handled
+    }
+}
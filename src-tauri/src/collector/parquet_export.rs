@@ -0,0 +1,278 @@
+//! Parquet export module.
+//! Writes stats rows and, optionally, decoded key-down events as columnar files for DuckDB or
+//! pandas. The arrow/parquet writer is only compiled with the `parquet-export` cargo feature.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::shortcut::{
+    days_in_window, for_each_key_down_with_app_in_window, shortcut_range_window_ms,
+};
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+/// Columns of the stats file. Names follow `StoredRow` in the storage schema, except that the
+/// window title is replaced by its hash.
+pub(crate) const PARQUET_STATS_COLUMNS: [&str; 6] = [
+    "date",
+    "app_name",
+    "window_title_hash",
+    "active_typing_ms",
+    "key_count",
+    "session_count",
+];
+
+/// Columns of the key event file: epoch milliseconds, app id, normalized key and the
+/// modifier bitmask stored in event chunks.
+pub(crate) const PARQUET_EVENT_COLUMNS: [&str; 4] = ["ts_ms", "app_name", "key", "modifiers"];
+
+const PARQUET_NOT_ENABLED: &str =
+    "parquet export is not enabled in this build (cargo feature `parquet-export`)";
+
+// FNV-1a parameters; unlike `DefaultHasher` the result is the same across Rust releases, so
+// hashes from different exports can be joined.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// One stats row as written to Parquet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct ParquetStatsRow {
+    pub(super) date: String,
+    pub(super) app_name: String,
+    pub(super) window_title_hash: String,
+    pub(super) active_typing_ms: u64,
+    pub(super) key_count: u64,
+    pub(super) session_count: u64,
+}
+
+/// One decoded key-down event as written to Parquet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct ParquetKeyEvent {
+    pub(super) ts_ms: i64,
+    pub(super) app_name: String,
+    pub(super) key: String,
+    pub(super) modifiers: u8,
+}
+
+/// Rows copied out of the state for a Parquet export, so files are written without holding
+/// the state lock. `events` is None when key events were not requested.
+#[derive(Clone, Default)]
+pub struct ParquetExport {
+    pub(super) range: String,
+    pub(super) stats: Vec<ParquetStatsRow>,
+    pub(super) events: Option<Vec<ParquetKeyEvent>>,
+}
+
+/// One written Parquet file and its row count.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ParquetFileRows {
+    pub path: String,
+    pub rows: u64,
+}
+
+/// Result of a Parquet export: the stats file, then the key event file when requested.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ParquetExportReport {
+    pub range: String,
+    pub files: Vec<ParquetFileRows>,
+}
+
+/// Fail fast when the build lacks the `parquet-export` feature, before any data is collected.
+pub fn ensure_parquet_export_enabled() -> Result<(), String> {
+    if cfg!(feature = "parquet-export") {
+        Ok(())
+    } else {
+        Err(PARQUET_NOT_ENABLED.to_string())
+    }
+}
+
+// 64-bit FNV-1a of a window title as 16 hex digits; empty titles stay empty.
+pub(super) fn window_title_hash(title: &str) -> String {
+    if title.is_empty() {
+        return String::new();
+    }
+    let hash = title.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    format!("{hash:016x}")
+}
+
+/// Copy stats rows of `today` / `yesterday` / `7d`, plus key-down events in that window
+/// when `include_events` is set, for a Parquet export.
+pub fn snapshot_parquet_export(
+    state: &CollectorState,
+    range: &str,
+    include_events: bool,
+) -> Result<ParquetExport, String> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let days = days_in_window(start_ms, end_ms);
+    let stats = state
+        .snapshot_rows()?
+        .into_iter()
+        .filter(|row| days.contains(&row.date.day().format(DAY_KEY_FORMAT).to_string()))
+        .map(|row| ParquetStatsRow {
+            date: row.date.to_string(),
+            window_title_hash: window_title_hash(&row.window_title),
+            app_name: row.app_name,
+            active_typing_ms: row.active_typing_ms,
+            key_count: row.key_count,
+            session_count: row.session_count,
+        })
+        .collect();
+    let events = include_events.then(|| {
+        let mut events = Vec::new();
+        for_each_key_down_with_app_in_window(
+            state,
+            start_ms,
+            end_ms,
+            None,
+            |app_id, ts_ms, key, modifiers| {
+                events.push(ParquetKeyEvent {
+                    ts_ms,
+                    app_name: app_id.to_string(),
+                    key,
+                    modifiers: modifiers.bitmask(),
+                });
+            },
+        );
+        events.sort_by_key(|event| event.ts_ms);
+        events
+    });
+    Ok(ParquetExport {
+        range: range.to_string(),
+        stats,
+        events,
+    })
+}
+
+// File names inside the destination directory, keyed by range so repeated exports of the
+// same range replace each other.
+fn parquet_file_paths(dest_dir: &Path, range: &str) -> (PathBuf, PathBuf) {
+    (
+        dest_dir.join(format!("typepulse-stats-{range}.parquet")),
+        dest_dir.join(format!("typepulse-key-events-{range}.parquet")),
+    )
+}
+
+/// Write the export into `dest_dir` as `typepulse-stats-<range>.parquet` and, when events
+/// were collected, `typepulse-key-events-<range>.parquet`.
+pub fn write_parquet_export(
+    export: &ParquetExport,
+    dest_dir: &Path,
+) -> Result<ParquetExportReport, String> {
+    ensure_parquet_export_enabled()?;
+    std::fs::create_dir_all(dest_dir).map_err(|e| e.to_string())?;
+    let (stats_path, events_path) = parquet_file_paths(dest_dir, &export.range);
+    let mut files = vec![ParquetFileRows {
+        path: stats_path.to_string_lossy().to_string(),
+        rows: writer::write_stats(&stats_path, &export.stats)?,
+    }];
+    if let Some(events) = export.events.as_ref() {
+        files.push(ParquetFileRows {
+            path: events_path.to_string_lossy().to_string(),
+            rows: writer::write_events(&events_path, events)?,
+        });
+    }
+    Ok(ParquetExportReport {
+        range: export.range.clone(),
+        files,
+    })
+}
+
+#[cfg(feature = "parquet-export")]
+mod writer {
+    use std::{fs::File, path::Path, sync::Arc};
+
+    use arrow::array::{ArrayRef, Int64Array, StringArray, UInt64Array, UInt8Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+    use parquet::arrow::ArrowWriter;
+
+    use super::{ParquetKeyEvent, ParquetStatsRow, PARQUET_EVENT_COLUMNS, PARQUET_STATS_COLUMNS};
+
+    // Write one record batch to a new file at `path` and return its row count.
+    fn write_batch(path: &Path, fields: Vec<Field>, columns: Vec<ArrayRef>) -> Result<u64, String> {
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(schema.clone(), columns).map_err(|e| e.to_string())?;
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        let mut writer = ArrowWriter::try_new(file, schema, None).map_err(|e| e.to_string())?;
+        writer.write(&batch).map_err(|e| e.to_string())?;
+        writer.close().map_err(|e| e.to_string())?;
+        Ok(batch.num_rows() as u64)
+    }
+
+    pub(super) fn write_stats(path: &Path, rows: &[ParquetStatsRow]) -> Result<u64, String> {
+        let [date, app_name, title_hash, active_ms, key_count, session_count] =
+            PARQUET_STATS_COLUMNS;
+        let fields = vec![
+            Field::new(date, DataType::Utf8, false),
+            Field::new(app_name, DataType::Utf8, false),
+            Field::new(title_hash, DataType::Utf8, false),
+            Field::new(active_ms, DataType::UInt64, false),
+            Field::new(key_count, DataType::UInt64, false),
+            Field::new(session_count, DataType::UInt64, false),
+        ];
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| row.date.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| row.app_name.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| row.window_title_hash.as_str()),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|row| row.active_typing_ms),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|row| row.key_count),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|row| row.session_count),
+            )),
+        ];
+        write_batch(path, fields, columns)
+    }
+
+    pub(super) fn write_events(path: &Path, events: &[ParquetKeyEvent]) -> Result<u64, String> {
+        let [ts_ms, app_name, key, modifiers] = PARQUET_EVENT_COLUMNS;
+        let fields = vec![
+            Field::new(ts_ms, DataType::Int64, false),
+            Field::new(app_name, DataType::Utf8, false),
+            Field::new(key, DataType::Utf8, false),
+            Field::new(modifiers, DataType::UInt8, false),
+        ];
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(
+                events.iter().map(|event| event.ts_ms),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                events.iter().map(|event| event.app_name.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                events.iter().map(|event| event.key.as_str()),
+            )),
+            Arc::new(UInt8Array::from_iter_values(
+                events.iter().map(|event| event.modifiers),
+            )),
+        ];
+        write_batch(path, fields, columns)
+    }
+}
+
+#[cfg(not(feature = "parquet-export"))]
+mod writer {
+    use std::path::Path;
+
+    use super::{ParquetKeyEvent, ParquetStatsRow, PARQUET_NOT_ENABLED};
+
+    pub(super) fn write_stats(_path: &Path, _rows: &[ParquetStatsRow]) -> Result<u64, String> {
+        Err(PARQUET_NOT_ENABLED.to_string())
+    }
+
+    pub(super) fn write_events(_path: &Path, _events: &[ParquetKeyEvent]) -> Result<u64, String> {
+        Err(PARQUET_NOT_ENABLED.to_string())
+    }
+}
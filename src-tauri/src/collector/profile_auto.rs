@@ -0,0 +1,174 @@
+//! Profile auto-switch module.
+//! Evaluates the configured time-window and frontmost-app rules on every tick and queues a
+//! profile switch once the wanted profile has held long enough. The app layer performs the
+//! queued switch through the same path as a manual one.
+
+use std::time::{Duration, Instant};
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime};
+
+use crate::app_config::{AppConfig, ProfileAutoRule};
+
+use super::CollectorState;
+
+const RULE_TIME_FORMAT: &str = "%H:%M";
+
+/// Profile switch queued by the rules, with the reason noted in the app log.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct ProfileSwitchRequest {
+    pub(crate) profile: String,
+    pub(crate) reason: String,
+}
+
+/// Rules and hysteresis state of automatic profile switching.
+pub(super) struct ProfileAutoSwitch {
+    rules: Vec<ProfileAutoRule>,
+    fallback: Option<String>,
+    hold: Duration,
+    cooldown: Duration,
+    active_profile: String,
+    // Profile the rules want right now, why, and since when it has been wanted.
+    candidate: Option<(ProfileSwitchRequest, Instant)>,
+    // A manual switch pauses the rules until then.
+    pub(super) suppressed_until: Option<Instant>,
+    pending: Option<ProfileSwitchRequest>,
+}
+
+impl ProfileAutoSwitch {
+    /// Rules and fallback naming a profile the config does not list are dropped, so a typo
+    /// cannot trigger a switch that fails on every tick.
+    pub(super) fn from_config(config: &AppConfig) -> Self {
+        let names = config.profile_names();
+        let known = |name: &str| names.iter().any(|known| known == name);
+        Self {
+            rules: config
+                .profile_auto_rules
+                .iter()
+                .filter(|rule| known(&rule.profile))
+                .cloned()
+                .collect(),
+            fallback: Some(config.profile_auto_fallback.clone()).filter(|name| known(name)),
+            hold: Duration::from_secs(config.profile_auto_hold_secs),
+            cooldown: Duration::from_secs(config.profile_auto_cooldown_mins.saturating_mul(60)),
+            active_profile: config.active_profile.clone(),
+            candidate: None,
+            suppressed_until: None,
+            pending: None,
+        }
+    }
+
+    /// Pause the rules for the configured cooldown after a manual switch.
+    pub(super) fn suppress(&mut self, now: Instant) {
+        self.suppressed_until = Some(now + self.cooldown);
+        self.candidate = None;
+        self.pending = None;
+    }
+}
+
+// Inclusive start, exclusive end; an end before the start wraps past midnight, and equal
+// ends cover the whole day.
+fn in_time_window(rule: &ProfileAutoRule, at: NaiveDateTime) -> bool {
+    let (Ok(start), Ok(end)) = (
+        NaiveTime::parse_from_str(rule.start.trim(), RULE_TIME_FORMAT),
+        NaiveTime::parse_from_str(rule.end.trim(), RULE_TIME_FORMAT),
+    ) else {
+        return false;
+    };
+    let time = at.time();
+    // The part after midnight belongs to the window that started the day before.
+    let (inside, window_day) = if start < end {
+        (time >= start && time < end, at.date())
+    } else if time >= start {
+        (true, at.date())
+    } else {
+        (time < end, at.date().pred_opt().unwrap_or(at.date()))
+    };
+    inside
+        && (rule.weekdays.is_empty()
+            || rule
+                .weekdays
+                .contains(&(window_day.weekday().number_from_monday() as u8)))
+}
+
+/// Rule that decides the profile at `at` with `frontmost` in front: the highest priority
+/// among the firing rules, the earliest listed on ties. Returns its index and the trigger
+/// that fired.
+pub(super) fn matching_rule<'a>(
+    rules: &'a [ProfileAutoRule],
+    at: NaiveDateTime,
+    frontmost: Option<&str>,
+) -> Option<(usize, &'a ProfileAutoRule, String)> {
+    let frontmost = frontmost.map(str::to_ascii_lowercase);
+    let mut best: Option<(usize, &ProfileAutoRule, String)> = None;
+    for (index, rule) in rules.iter().enumerate() {
+        let trigger = match frontmost.as_deref() {
+            Some(app) if rule.bundle_ids.iter().any(|id| id == app) => {
+                format!("frontmost {}", app)
+            }
+            _ if in_time_window(rule, at) => format!("time {}-{}", rule.start, rule.end),
+            _ => continue,
+        };
+        if best
+            .as_ref()
+            .is_none_or(|(_, current, _)| rule.priority > current.priority)
+        {
+            best = Some((index, rule, trigger));
+        }
+    }
+    best
+}
+
+/// Evaluate the rules and queue a switch once their answer differs from the active profile
+/// for `hold`. The answer has to stay the same the whole time, so alternating between a work
+/// app and another one does not flap the profile. Nothing happens while a manual switch's
+/// cooldown runs or a queued switch waits for the app layer.
+pub(super) fn evaluate_profile_rules(
+    state: &mut CollectorState,
+    now: Instant,
+    at: NaiveDateTime,
+    frontmost: Option<&str>,
+) {
+    let auto = &mut state.profile_auto;
+    if auto.rules.is_empty() || auto.pending.is_some() {
+        return;
+    }
+    if auto.suppressed_until.is_some_and(|until| now < until) {
+        return;
+    }
+    auto.suppressed_until = None;
+    let wanted = match matching_rule(&auto.rules, at, frontmost) {
+        Some((index, rule, trigger)) => Some(ProfileSwitchRequest {
+            profile: rule.profile.clone(),
+            reason: format!("rule {} ({})", index + 1, trigger),
+        }),
+        None => auto.fallback.clone().map(|profile| ProfileSwitchRequest {
+            profile,
+            reason: "no rule matched (fallback)".to_string(),
+        }),
+    };
+    let Some(wanted) = wanted.filter(|wanted| wanted.profile != auto.active_profile) else {
+        auto.candidate = None;
+        return;
+    };
+    match &auto.candidate {
+        Some((candidate, since)) if candidate.profile == wanted.profile => {
+            if now.saturating_duration_since(*since) >= auto.hold {
+                auto.pending = Some(wanted);
+                auto.candidate = None;
+            }
+        }
+        _ => auto.candidate = Some((wanted, now)),
+    }
+}
+
+impl CollectorState {
+    /// Switch queued by the profile rules, if any; taking it hands it to the caller.
+    pub(crate) fn take_profile_switch_request(&mut self) -> Option<ProfileSwitchRequest> {
+        self.profile_auto.pending.take()
+    }
+
+    /// Pause automatic switching for the configured cooldown after a manual switch.
+    pub(crate) fn suppress_profile_auto_switch(&mut self, now: Instant) {
+        self.profile_auto.suppress(now);
+    }
+}
@@ -0,0 +1,253 @@
+//! App purge module.
+//! Removes every recorded trace of one app from runtime state and the files written from it,
+//! and reports what was removed for privacy audits.
+
+use serde::Serialize;
+
+use super::rolling_avg::RollingAverages;
+use super::state_api::compute_today_totals;
+use super::{append_app_log, CollectorState};
+
+/// What a purge removed, or would remove when `dry_run` is set. Counts are per storage
+/// location so the audit entry shows exactly which data held the app.
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct AppPurgeReport {
+    pub bundle_id: String,
+    pub dry_run: bool,
+    pub purged_at_ms: i64,
+    pub stats_rows: u64,
+    pub key_count: u64,
+    pub active_typing_ms: u64,
+    pub shortcut_uses: u64,
+    pub shortcuts_dropped: u64,
+    pub event_chunks: u64,
+    pub events: u64,
+    pub app_dict_entries: u64,
+    pub chord_aborts: u64,
+    pub app_switches: u64,
+    pub copy_pastes: u64,
+}
+
+impl AppPurgeReport {
+    fn audit_line(&self) -> String {
+        format!(
+            "app data purge: app={} stats_rows={} key_count={} active_typing_ms={} \
+             shortcut_uses={} shortcuts_dropped={} event_chunks={} events={} \
+             app_dict_entries={} chord_aborts={} app_switches={} copy_pastes={}",
+            self.bundle_id,
+            self.stats_rows,
+            self.key_count,
+            self.active_typing_ms,
+            self.shortcut_uses,
+            self.shortcuts_dropped,
+            self.event_chunks,
+            self.events,
+            self.app_dict_entries,
+            self.chord_aborts,
+            self.app_switches,
+            self.copy_pastes,
+        )
+    }
+}
+
+// App ids are bundle ids or app names; bundle ids are compared case-insensitively everywhere.
+fn is_app(app_id: &str, bundle_id: &str) -> bool {
+    app_id.eq_ignore_ascii_case(bundle_id)
+}
+
+// Count what belongs to the app without changing anything.
+fn measure(state: &CollectorState, bundle_id: &str) -> AppPurgeReport {
+    let mut report = AppPurgeReport {
+        bundle_id: bundle_id.to_string(),
+        ..AppPurgeReport::default()
+    };
+    for (key, value) in &state.stats {
+        if is_app(&key.app_name, bundle_id) {
+            report.stats_rows += 1;
+            report.key_count += value.key_count;
+            report.active_typing_ms += value.active_typing_ms;
+        }
+    }
+    for usage in state.shortcut_usage.values() {
+        let removed: u64 = usage
+            .by_app
+            .iter()
+            .filter(|(app_id, _)| is_app(app_id, bundle_id))
+            .map(|(_, count)| *count)
+            .sum();
+        report.shortcut_uses += removed;
+        if removed > 0 && usage.count.saturating_sub(removed) == 0 {
+            report.shortcuts_dropped += 1;
+        }
+    }
+    let app_refs: Vec<u32> = state
+        .app_dict
+        .iter()
+        .filter(|(_, app_id)| is_app(app_id, bundle_id))
+        .map(|(app_ref, _)| *app_ref)
+        .collect();
+    report.app_dict_entries = app_refs.len() as u64;
+    let chunks = state
+        .event_chunks
+        .iter()
+        .map(|chunk| (chunk.app_ref, chunk.events.len()))
+        .chain(
+            state
+                .open_event_chunk
+                .iter()
+                .map(|chunk| (chunk.app_ref, chunk.events.len())),
+        );
+    for (app_ref, events) in chunks {
+        if app_refs.contains(&app_ref) {
+            report.event_chunks += 1;
+            report.events += events as u64;
+        }
+    }
+    for apps in state.chord_aborts.values() {
+        report.chord_aborts += apps
+            .iter()
+            .filter(|(app_id, _)| is_app(app_id, bundle_id))
+            .map(|(_, count)| *count)
+            .sum::<u64>();
+    }
+    for day in state.app_switches.values() {
+        report.app_switches += day
+            .pairs
+            .iter()
+            .filter(|((from, to), _)| is_app(from, bundle_id) || is_app(to, bundle_id))
+            .map(|(_, count)| *count)
+            .sum::<u64>();
+    }
+    for day in state.copy_paste_days.values() {
+        report.copy_pastes += day
+            .app_pairs
+            .iter()
+            .filter(|((from, to), _)| is_app(from, bundle_id) || is_app(to, bundle_id))
+            .map(|(_, count)| *count)
+            .sum::<u64>();
+    }
+    report
+}
+
+// Drop the app from every runtime map. Persisted files are rewritten by the caller.
+fn remove(state: &mut CollectorState, bundle_id: &str) {
+    state
+        .stats
+        .retain(|key, _| !is_app(&key.app_name, bundle_id));
+    if let Some(day) = state.today_totals.date {
+        state.today_totals = compute_today_totals(&state.stats, day);
+    }
+    state.rolling_averages = RollingAverages::default();
+    state.shortcut_usage.retain(|_, usage| {
+        let before = usage.by_app.values().sum::<u64>();
+        usage.by_app.retain(|app_id, _| !is_app(app_id, bundle_id));
+        let removed = before - usage.by_app.values().sum::<u64>();
+        usage.count = usage.count.saturating_sub(removed);
+        usage.count > 0
+    });
+    let app_refs: Vec<u32> = state
+        .app_dict
+        .iter()
+        .filter(|(_, app_id)| is_app(app_id, bundle_id))
+        .map(|(app_ref, _)| *app_ref)
+        .collect();
+    if state
+        .open_event_chunk
+        .as_ref()
+        .is_some_and(|chunk| app_refs.contains(&chunk.app_ref))
+    {
+        state.open_event_chunk = None;
+    }
+    let chunks = std::mem::take(&mut state.event_chunks);
+    for chunk in chunks {
+        if app_refs.contains(&chunk.app_ref) {
+            state.chunk_counters.remove(&chunk);
+        } else {
+            state.event_chunks.push(chunk);
+        }
+    }
+    for app_ref in &app_refs {
+        if let Some(app_id) = state.app_dict.remove(app_ref) {
+            state.app_ref_by_app.remove(&app_id);
+        }
+    }
+    state.chord_aborts.retain(|_, apps| {
+        apps.retain(|app_id, _| !is_app(app_id, bundle_id));
+        !apps.is_empty()
+    });
+    for day in state.app_switches.values_mut() {
+        let mut removed = 0;
+        day.pairs.retain(|(from, to), count| {
+            let keep = !is_app(from, bundle_id) && !is_app(to, bundle_id);
+            if !keep {
+                removed += *count;
+            }
+            keep
+        });
+        day.total = day.total.saturating_sub(removed);
+    }
+    // Latency samples are not keyed by app and stay; only the app pairs name the app.
+    for day in state.copy_paste_days.values_mut() {
+        let mut removed = 0;
+        day.app_pairs.retain(|(from, to), count| {
+            let keep = !is_app(from, bundle_id) && !is_app(to, bundle_id);
+            if !keep {
+                removed += *count;
+            }
+            keep
+        });
+        day.pairs = day.pairs.saturating_sub(removed);
+    }
+    if state
+        .pending_copy
+        .as_ref()
+        .is_some_and(|copy| is_app(&copy.app_id, bundle_id))
+    {
+        state.pending_copy = None;
+    }
+    if state
+        .last_foreground_app
+        .as_deref()
+        .is_some_and(|app_id| is_app(app_id, bundle_id))
+    {
+        state.last_foreground_app = None;
+    }
+    if state
+        .active_stats_key
+        .as_ref()
+        .is_some_and(|key| is_app(&key.app_name, bundle_id))
+    {
+        state.active_stats_key = None;
+    }
+    if state
+        .typing_context
+        .as_ref()
+        .is_some_and(|context| is_app(context.app_id(), bundle_id))
+    {
+        state.typing_context = None;
+    }
+}
+
+impl CollectorState {
+    /// Delete everything recorded about one app: stats rows, its share of shortcut totals,
+    /// event chunks and dictionary entries, chord aborts and app switches, then rewrite the
+    /// detail, analytics and CSV files and log an audit entry. With `dry_run` only the report
+    /// is built. Files already written to the auto-export directory are left as they are.
+    pub fn purge_app_data(
+        &mut self,
+        bundle_id: &str,
+        dry_run: bool,
+    ) -> Result<AppPurgeReport, String> {
+        let mut report = measure(self, bundle_id);
+        report.dry_run = dry_run;
+        report.purged_at_ms = chrono::Utc::now().timestamp_millis();
+        if dry_run {
+            return Ok(report);
+        }
+        self.ensure_history_loaded()?;
+        remove(self, bundle_id);
+        let flush_result = self.flush_to_disk();
+        let _ = append_app_log(&self.app_log_path, &report.audit_line());
+        flush_result.map(|_| report)
+    }
+}
@@ -0,0 +1,135 @@
+//! Raw event export module.
+//! Decodes the event chunks of a bounded window into JSONL for analysis outside the app. This
+//! is the full keystroke stream, so the command serving it is gated by the `debug-tools`
+//! feature and a consent string, and every export is written to the app log.
+
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::shortcut::{chunk_app_id, parse_compact_event, INPUT_CHUNK_VERSION};
+use super::{CollectorState, ModifierSnapshot};
+
+/// Decoded key event, one JSONL line of the export.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RawEventRecord {
+    /// Epoch milliseconds.
+    pub ts_ms: i64,
+    pub app_id: String,
+    pub key: String,
+    /// Held modifiers: `ctrl` / `opt` / `shift` / `cmd` / `fn`.
+    pub modifiers: Vec<&'static str>,
+    /// `down` or `up`.
+    pub event_type: &'static str,
+}
+
+/// Consent string for one raw event export, as returned by `request_raw_export_consent`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RawExportConsent {
+    pub consent: String,
+    pub expires_in_ms: u64,
+    pub max_window_hours: u64,
+}
+
+/// Result of a raw event export.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RawExportReport {
+    pub path: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub events: u64,
+}
+
+fn modifier_names(modifiers: ModifierSnapshot) -> Vec<&'static str> {
+    [
+        (modifiers.ctrl, "ctrl"),
+        (modifiers.opt, "opt"),
+        (modifiers.shift, "shift"),
+        (modifiers.cmd, "cmd"),
+        (modifiers.function, "fn"),
+    ]
+    .into_iter()
+    .filter_map(|(held, name)| held.then_some(name))
+    .collect()
+}
+
+/// Decode every key event in [start_ms, end_ms), oldest first. Windows longer than
+/// `max_window_ms` are refused.
+pub fn snapshot_raw_events(
+    state: &CollectorState,
+    start_ms: i64,
+    end_ms: i64,
+    max_window_ms: i64,
+) -> Result<Vec<RawEventRecord>, String> {
+    if end_ms <= start_ms {
+        return Err("raw export window must end after it starts".to_string());
+    }
+    if end_ms - start_ms > max_window_ms {
+        return Err(format!(
+            "raw export window is longer than the maximum of {} hours",
+            max_window_ms / 3_600_000
+        ));
+    }
+    let mut records = Vec::new();
+    let mut consume_chunk = |version: u8, chunk_start_ms: i64, app_ref: u32, events: &[String]| {
+        let app_id = chunk_app_id(state, app_ref);
+        for raw_event in events {
+            let Some((dt, event_type, key, modifiers)) = parse_compact_event(version, raw_event)
+            else {
+                continue;
+            };
+            let ts_ms = chunk_start_ms.saturating_add(dt.max(0));
+            if ts_ms < start_ms || ts_ms >= end_ms {
+                continue;
+            }
+            let event_type = match event_type {
+                'd' => "down",
+                'u' => "up",
+                _ => continue,
+            };
+            records.push(RawEventRecord {
+                ts_ms,
+                app_id: app_id.clone(),
+                key,
+                modifiers: modifier_names(modifiers),
+                event_type,
+            });
+        }
+    };
+    for chunk in &state.event_chunks {
+        consume_chunk(chunk.v, chunk.chunk_start_ms, chunk.app_ref, &chunk.events);
+    }
+    if let Some(open_chunk) = state.open_event_chunk.as_ref() {
+        consume_chunk(
+            INPUT_CHUNK_VERSION,
+            open_chunk.chunk_start_ms,
+            open_chunk.app_ref,
+            &open_chunk.events,
+        );
+    }
+    records.sort_by_key(|record| record.ts_ms);
+    Ok(records)
+}
+
+/// Write `records` to `path` as JSONL, one event per line.
+pub fn write_raw_events(
+    records: &[RawEventRecord],
+    path: &Path,
+    start_ms: i64,
+    end_ms: i64,
+) -> Result<RawExportReport, String> {
+    let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = BufWriter::new(file);
+    for record in records {
+        serde_json::to_writer(&mut writer, record).map_err(|e| e.to_string())?;
+        writer.write_all(b"\n").map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(RawExportReport {
+        path: path.to_string_lossy().to_string(),
+        start_ms,
+        end_ms,
+        events: records.len() as u64,
+    })
+}
@@ -0,0 +1,133 @@
+//! Day reconstruction module.
+//! Rebuilds one day's per-app key counts from event chunks as they stood at an earlier moment,
+//! next to the stored stats, to debug counts that disagree. Only served with `debug-tools`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::shortcut::{
+    chunk_app_id, local_day_window_ms, parse_compact_event, INPUT_CHUNK_VERSION,
+};
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+const DEBUG_TOOLS_NOT_ENABLED: &str =
+    "debug tools are not enabled in this build (cargo feature `debug-tools`)";
+
+/// Reconstructed and stored key counts of one app on the requested day.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AppReconstructionDiff {
+    pub app_name: String,
+    /// Key-downs replayed from chunks started at or before `as_of_ms`.
+    pub reconstructed_keys: u64,
+    /// Keys in the stats map now.
+    pub stored_keys: u64,
+    /// `reconstructed_keys - stored_keys`.
+    pub diff: i64,
+}
+
+/// Day reconstruction payload; apps are sorted by largest absolute diff, then app name.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct DayReconstruction {
+    pub date: String,
+    pub as_of_ms: i64,
+    /// Chunks (open chunk included) that contributed at least one key-down.
+    pub chunk_count: usize,
+    pub reconstructed_keys: u64,
+    pub stored_keys: u64,
+    pub apps: Vec<AppReconstructionDiff>,
+}
+
+/// Fail fast when the build lacks the `debug-tools` feature.
+pub fn ensure_debug_tools_enabled() -> Result<(), String> {
+    if cfg!(feature = "debug-tools") {
+        Ok(())
+    } else {
+        Err(DEBUG_TOOLS_NOT_ENABLED.to_string())
+    }
+}
+
+/// Replay the key-downs of `date` (`YYYY-MM-DD`) from chunks with `chunk_start_ms <= as_of_ms`,
+/// ignoring the stats map, and diff them per app against the stored stats of that day.
+pub fn reconstruct_day(
+    state: &CollectorState,
+    date: &str,
+    as_of_ms: i64,
+) -> Result<DayReconstruction, String> {
+    let day = NaiveDate::parse_from_str(date, DAY_KEY_FORMAT).map_err(|e| e.to_string())?;
+    let (start_ms, end_ms) =
+        local_day_window_ms(day).ok_or_else(|| "invalid local midnight".to_string())?;
+
+    let mut reconstructed: HashMap<String, u64> = HashMap::new();
+    let mut chunk_count = 0;
+    let mut consume_chunk = |version: u8, chunk_start_ms: i64, app_ref: u32, events: &[String]| {
+        if chunk_start_ms > as_of_ms {
+            return;
+        }
+        let mut key_downs = 0;
+        for raw_event in events {
+            let Some((dt, event_type, _, _)) = parse_compact_event(version, raw_event) else {
+                continue;
+            };
+            let event_ms = chunk_start_ms.saturating_add(dt.max(0));
+            if event_type == 'd' && event_ms >= start_ms && event_ms < end_ms {
+                key_downs += 1;
+            }
+        }
+        if key_downs > 0 {
+            chunk_count += 1;
+            *reconstructed
+                .entry(chunk_app_id(state, app_ref))
+                .or_default() += key_downs;
+        }
+    };
+    for chunk in &state.event_chunks {
+        consume_chunk(chunk.v, chunk.chunk_start_ms, chunk.app_ref, &chunk.events);
+    }
+    if let Some(open_chunk) = state.open_event_chunk.as_ref() {
+        consume_chunk(
+            INPUT_CHUNK_VERSION,
+            open_chunk.chunk_start_ms,
+            open_chunk.app_ref,
+            &open_chunk.events,
+        );
+    }
+
+    let mut stored: HashMap<&str, u64> = HashMap::new();
+    for (key, value) in &state.stats {
+        if key.date.day() == day {
+            *stored.entry(key.app_name.as_str()).or_default() += value.key_count;
+        }
+    }
+
+    let mut by_app: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+    for (app_name, keys) in &reconstructed {
+        by_app.entry(app_name.as_str()).or_default().0 = *keys;
+    }
+    for (app_name, keys) in stored {
+        by_app.entry(app_name).or_default().1 = keys;
+    }
+    let mut apps: Vec<AppReconstructionDiff> = by_app
+        .into_iter()
+        .map(
+            |(app_name, (reconstructed_keys, stored_keys))| AppReconstructionDiff {
+                app_name: app_name.to_string(),
+                reconstructed_keys,
+                stored_keys,
+                diff: reconstructed_keys as i64 - stored_keys as i64,
+            },
+        )
+        .collect();
+    // Stable sort keeps the BTreeMap's app name order among equal diffs.
+    apps.sort_by_key(|app| std::cmp::Reverse(app.diff.unsigned_abs()));
+
+    Ok(DayReconstruction {
+        date: date.to_string(),
+        as_of_ms,
+        chunk_count,
+        reconstructed_keys: apps.iter().map(|app| app.reconstructed_keys).sum(),
+        stored_keys: apps.iter().map(|app| app.stored_keys).sum(),
+        apps,
+    })
+}
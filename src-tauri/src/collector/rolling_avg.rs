@@ -0,0 +1,110 @@
+//! Rolling average module.
+//! Averages daily key counts and active typing time over the last 7 and 30 finished days,
+//! so today's totals can be read against a personal baseline.
+
+use std::collections::HashMap;
+
+use chrono::{Duration as ChronoDuration, NaiveDate};
+
+use super::coverage::CoverageDay;
+use super::{CollectorState, StatsKey, StatsValue, DAY_KEY_FORMAT};
+
+const SHORT_WINDOW_DAYS: i64 = 7;
+const LONG_WINDOW_DAYS: i64 = 30;
+
+/// Key count and active typing time of one local day.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(super) struct DayTotal {
+    pub(super) key_count: u64,
+    pub(super) active_typing_ms: u64,
+}
+
+/// Mean daily key count and active typing time over the days that counted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct DailyAverage {
+    pub(super) key_count: f64,
+    pub(super) active_typing_ms: f64,
+}
+
+/// 7-day and 30-day averages, computed for `date` and reused until the date changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(super) struct RollingAverages {
+    pub(super) date: Option<NaiveDate>,
+    pub(super) short: Option<DailyAverage>,
+    pub(super) long: Option<DailyAverage>,
+}
+
+fn day_totals(stats: &HashMap<StatsKey, StatsValue>) -> HashMap<NaiveDate, DayTotal> {
+    let mut totals: HashMap<NaiveDate, DayTotal> = HashMap::new();
+    for (key, value) in stats {
+        let total = totals.entry(key.date.day()).or_default();
+        total.key_count += value.key_count;
+        total.active_typing_ms += value.active_typing_ms;
+    }
+    totals
+}
+
+/// Average over the `window_days` days before `today`; today itself is still running and
+/// never counts. A day counts when the coverage ledger shows recording time, or, for days
+/// older than the ledger, when it has stats. Days the ledger shows the app never recorded
+/// are left out instead of pulling the average down. None when no day counted, so a short
+/// history averages over the days it has.
+pub(super) fn daily_average(
+    totals: &HashMap<NaiveDate, DayTotal>,
+    coverage: &HashMap<String, CoverageDay>,
+    today: NaiveDate,
+    window_days: i64,
+) -> Option<DailyAverage> {
+    let mut counted = 0u64;
+    let mut sum = DayTotal::default();
+    for offset in 1..=window_days {
+        let day = today - ChronoDuration::days(offset);
+        let total = totals.get(&day).copied();
+        let recorded = match coverage.get(&day.format(DAY_KEY_FORMAT).to_string()) {
+            Some(ledger) => ledger.recording_ms > 0,
+            None => total.is_some(),
+        };
+        if !recorded {
+            continue;
+        }
+        let total = total.unwrap_or_default();
+        counted += 1;
+        sum.key_count += total.key_count;
+        sum.active_typing_ms += total.active_typing_ms;
+    }
+    (counted > 0).then(|| DailyAverage {
+        key_count: sum.key_count as f64 / counted as f64,
+        active_typing_ms: sum.active_typing_ms as f64 / counted as f64,
+    })
+}
+
+/// Both rolling averages as of `today`.
+pub(super) fn rolling_averages(
+    stats: &HashMap<StatsKey, StatsValue>,
+    coverage: &HashMap<String, CoverageDay>,
+    today: NaiveDate,
+) -> RollingAverages {
+    let totals = day_totals(stats);
+    RollingAverages {
+        date: Some(today),
+        short: daily_average(&totals, coverage, today, SHORT_WINDOW_DAYS),
+        long: daily_average(&totals, coverage, today, LONG_WINDOW_DAYS),
+    }
+}
+
+/// Tick check: recompute the averages once the local date moved past the cached one.
+pub(super) fn refresh_rolling_averages(state: &mut CollectorState, today: NaiveDate) {
+    if state.rolling_averages.date == Some(today) {
+        return;
+    }
+    state.rolling_averages = rolling_averages(&state.stats, &state.coverage, today);
+}
+
+/// Today's key count relative to the 7-day average, in percent above (positive) or below
+/// (negative) it. None without a baseline or when the baseline is zero.
+pub(super) fn today_vs_avg_pct(averages: &RollingAverages, today_keys: u64) -> Option<f64> {
+    averages
+        .short
+        .filter(|average| average.key_count > 0.0)
+        .map(|average| (today_keys as f64 - average.key_count) * 100.0 / average.key_count)
+}
@@ -0,0 +1,169 @@
+//! Runtime ledger module.
+//! Records when the app process was running as start/stop spans, so a day without stats can
+//! be told apart as "not running" rather than "running but idle".
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::storage::StoredRuntimeSpan;
+
+use super::shortcut::{days_in_window, local_day_window_ms};
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+/// Longest wall-clock gap between two checkpoints that still extends the open span. Longer
+/// gaps mean the machine slept, and the sleep is not counted as running time.
+const MAX_RUNTIME_GAP_MS: i64 = 60_000;
+/// Runtime spans are kept this many days, independent of the event chunk limits.
+const RUNTIME_RETENTION_DAYS: i64 = 90;
+
+/// Wall-clock span (epoch milliseconds) during which the app was running.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct RuntimeSpan {
+    pub(super) start_ms: i64,
+    pub(super) end_ms: i64,
+}
+
+/// Why a day has (or lacks) data.
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DayCoverage {
+    /// The app never ran that day; missing data means untracked, not zero.
+    NotRunning,
+    /// The app ran but recorded no keystrokes.
+    Idle,
+    /// Keystrokes were recorded.
+    Active,
+}
+
+/// Runtime of one local day (`YYYY-MM-DD`) and its coverage flag.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct RuntimeDay {
+    pub date: String,
+    pub runtime_ms: u64,
+    pub coverage: DayCoverage,
+}
+
+/// Open a new span at startup.
+pub(super) fn begin_runtime_span(state: &mut CollectorState, now_ms: i64) {
+    state.runtime_spans.push(RuntimeSpan {
+        start_ms: now_ms,
+        end_ms: now_ms,
+    });
+}
+
+/// Extend the open span to `now_ms`, or start a new one when the previous checkpoint is too
+/// old (the process was suspended in between).
+pub(super) fn record_runtime(state: &mut CollectorState, now_ms: i64) {
+    match state.runtime_spans.last_mut() {
+        Some(span) if now_ms >= span.end_ms && now_ms - span.end_ms <= MAX_RUNTIME_GAP_MS => {
+            span.end_ms = now_ms;
+        }
+        _ => begin_runtime_span(state, now_ms),
+    }
+}
+
+/// Drop spans that ended before the retention window.
+pub(super) fn prune_runtime_spans(state: &mut CollectorState, now_ms: i64) {
+    let cutoff_ms = now_ms - ChronoDuration::days(RUNTIME_RETENTION_DAYS).num_milliseconds();
+    state.runtime_spans.retain(|span| span.end_ms >= cutoff_ms);
+}
+
+// Local day of an epoch timestamp.
+fn local_day(ms: i64) -> Option<NaiveDate> {
+    DateTime::<Utc>::from_timestamp_millis(ms).map(|value| value.with_timezone(&Local).date_naive())
+}
+
+/// Split spans at local midnight into per-day lists for the daily analytics files.
+pub(super) fn runtime_to_stored(spans: &[RuntimeSpan]) -> HashMap<String, Vec<StoredRuntimeSpan>> {
+    let mut by_day: HashMap<String, Vec<StoredRuntimeSpan>> = HashMap::new();
+    for span in spans {
+        let Some(mut day) = local_day(span.start_ms) else {
+            continue;
+        };
+        while let Some((day_start, day_end)) = local_day_window_ms(day) {
+            if day_start > span.end_ms {
+                break;
+            }
+            let start_ms = span.start_ms.max(day_start);
+            let end_ms = span.end_ms.min(day_end);
+            if start_ms <= end_ms {
+                by_day
+                    .entry(day.format(DAY_KEY_FORMAT).to_string())
+                    .or_default()
+                    .push(StoredRuntimeSpan { start_ms, end_ms });
+            }
+            let Some(next) = day.succ_opt() else {
+                break;
+            };
+            day = next;
+        }
+    }
+    by_day
+}
+
+/// Flatten per-day spans back into one sorted list, rejoining spans split at midnight.
+pub(super) fn runtime_from_stored(
+    stored: &HashMap<String, Vec<StoredRuntimeSpan>>,
+) -> Vec<RuntimeSpan> {
+    let mut spans: Vec<RuntimeSpan> = stored
+        .values()
+        .flatten()
+        .filter(|span| span.start_ms <= span.end_ms)
+        .map(|span| RuntimeSpan {
+            start_ms: span.start_ms,
+            end_ms: span.end_ms,
+        })
+        .collect();
+    spans.sort_by_key(|span| (span.start_ms, span.end_ms));
+    let mut joined: Vec<RuntimeSpan> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match joined.last_mut() {
+            Some(last) if span.start_ms <= last.end_ms => {
+                last.end_ms = last.end_ms.max(span.end_ms);
+            }
+            _ => joined.push(span),
+        }
+    }
+    joined
+}
+
+// Running time of the spans inside [start_ms, end_ms).
+fn runtime_ms_between(spans: &[RuntimeSpan], start_ms: i64, end_ms: i64) -> u64 {
+    spans
+        .iter()
+        .map(|span| (span.end_ms.min(end_ms) - span.start_ms.max(start_ms)).max(0) as u64)
+        .sum()
+}
+
+/// One row per local day in [start_ms, end_ms), oldest first. Days with recorded keystrokes
+/// count as active even without runtime spans, which covers data written before the ledger.
+pub(super) fn runtime_days(state: &CollectorState, start_ms: i64, end_ms: i64) -> Vec<RuntimeDay> {
+    let typed_days: HashSet<NaiveDate> = state
+        .stats
+        .iter()
+        .filter(|(_, value)| value.key_count > 0)
+        .map(|(key, _)| key.date.day())
+        .collect();
+    days_in_window(start_ms, end_ms)
+        .into_iter()
+        .filter_map(|date| {
+            let day = NaiveDate::parse_from_str(&date, DAY_KEY_FORMAT).ok()?;
+            let (day_start, day_end) = local_day_window_ms(day)?;
+            let runtime_ms = runtime_ms_between(&state.runtime_spans, day_start, day_end);
+            let coverage = if typed_days.contains(&day) {
+                DayCoverage::Active
+            } else if runtime_ms > 0 {
+                DayCoverage::Idle
+            } else {
+                DayCoverage::NotRunning
+            };
+            Some(RuntimeDay {
+                date,
+                runtime_ms,
+                coverage,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,178 @@
+//! Sensitive field module.
+//! Opt-in check of the focused UI element for password-like fields that do not turn on secure
+//! input (API keys in a terminal, token fields in a browser). Keys typed there are still
+//! counted, but no input events or shortcuts are recorded. The probe is cached per context,
+//! rate-limited and fails open: an unreadable field is never flagged.
+
+use std::time::{Duration, Instant};
+
+use super::{CaptureContext, CollectorState};
+
+/// Shortest time between two probes in one app window; focus moves within a window are
+/// picked up after at most this long.
+pub(super) const SENSITIVE_PROBE_INTERVAL: Duration = Duration::from_millis(500);
+// Longest an unresponsive app may hold up the probe, in seconds.
+#[cfg(target_os = "macos")]
+const AX_MESSAGING_TIMEOUT_SECS: f32 = 0.05;
+
+// Roles of elements text is typed into; only their labels are matched against the keywords.
+const TEXT_INPUT_ROLES: [&str; 3] = ["AXTextField", "AXTextArea", "AXComboBox"];
+const SECURE_TEXT_FIELD: &str = "AXSecureTextField";
+
+/// Role and labels of the focused UI element.
+#[derive(Clone, Debug, Default)]
+pub(super) struct FocusedField {
+    pub(super) role: String,
+    pub(super) subrole: Option<String>,
+    /// Title, description and placeholder, whichever the element has.
+    pub(super) labels: Vec<String>,
+}
+
+/// Last probe result and the app window it was taken in.
+#[derive(Clone, Debug, Default)]
+pub(super) struct SensitiveFieldCache {
+    context: Option<(String, String)>,
+    probed_at: Option<Instant>,
+    flagged: bool,
+}
+
+fn context_key(context: &CaptureContext) -> (String, String) {
+    (context.app_name.clone(), context.window_title.clone())
+}
+
+/// Configured keywords, trimmed and lowercased, blanks dropped.
+pub(super) fn sensitive_keywords(keywords: &[String]) -> Vec<String> {
+    keywords
+        .iter()
+        .map(|keyword| keyword.trim().to_lowercase())
+        .filter(|keyword| !keyword.is_empty())
+        .collect()
+}
+
+/// Whether `field` is a secure text field, or a text input labelled with one of `keywords`
+/// (lowercase).
+pub(super) fn is_sensitive_field(field: &FocusedField, keywords: &[String]) -> bool {
+    if field.role == SECURE_TEXT_FIELD || field.subrole.as_deref() == Some(SECURE_TEXT_FIELD) {
+        return true;
+    }
+    if !TEXT_INPUT_ROLES.contains(&field.role.as_str()) {
+        return false;
+    }
+    field.labels.iter().any(|label| {
+        let label = label.to_lowercase();
+        keywords
+            .iter()
+            .any(|keyword| label.contains(keyword.as_str()))
+    })
+}
+
+/// Whether the focused field in `context` looks sensitive. Probes at most once per
+/// `SENSITIVE_PROBE_INTERVAL` in the same app window; always false while detection is off.
+pub(super) fn likely_sensitive_field(
+    state: &mut CollectorState,
+    context: &CaptureContext,
+    now: Instant,
+) -> bool {
+    if !state.ax_sensitive_detection {
+        return false;
+    }
+    let key = context_key(context);
+    let cache = &state.sensitive_field;
+    let fresh = cache.context.as_ref() == Some(&key)
+        && cache
+            .probed_at
+            .is_some_and(|at| now.saturating_duration_since(at) < SENSITIVE_PROBE_INTERVAL);
+    if fresh {
+        return cache.flagged;
+    }
+    let flagged = (state.focused_field_provider)()
+        .is_some_and(|field| is_sensitive_field(&field, &state.ax_sensitive_title_keywords));
+    state.sensitive_field = SensitiveFieldCache {
+        context: Some(key),
+        probed_at: Some(now),
+        flagged,
+    };
+    flagged
+}
+
+/// Last probe result for `context` without probing again, e.g. for the key-up of a key whose
+/// key-down was probed.
+pub(super) fn cached_sensitive_field(state: &CollectorState, context: &CaptureContext) -> bool {
+    state.ax_sensitive_detection
+        && state.sensitive_field.flagged
+        && state.sensitive_field.context.as_ref() == Some(&context_key(context))
+}
+
+/// Role, subrole and labels of the focused UI element through the Accessibility API; None
+/// when it cannot be read (no permission, no focused element, timeout).
+#[cfg(target_os = "macos")]
+pub(super) fn focused_field() -> Option<FocusedField> {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{msg_send, sel, sel_impl};
+    use std::ffi::c_void;
+
+    use super::context::nsstring_to_string;
+
+    type CFTypeRef = *const c_void;
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXUIElementCreateSystemWide() -> CFTypeRef;
+        fn AXUIElementCopyAttributeValue(
+            element: CFTypeRef,
+            attribute: id,
+            value: *mut CFTypeRef,
+        ) -> i32;
+        fn AXUIElementSetMessagingTimeout(element: CFTypeRef, timeout_secs: f32) -> i32;
+    }
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(value: CFTypeRef);
+        fn CFGetTypeID(value: CFTypeRef) -> usize;
+        fn CFStringGetTypeID() -> usize;
+    }
+
+    // Copy attribute `name` of `element`; the caller releases the value.
+    unsafe fn copy_attribute(element: CFTypeRef, name: &str) -> Option<CFTypeRef> {
+        let attribute = NSString::alloc(nil).init_str(name);
+        let mut value: CFTypeRef = std::ptr::null();
+        let status = AXUIElementCopyAttributeValue(element, attribute, &mut value);
+        let _: () = msg_send![attribute, release];
+        (status == 0 && !value.is_null()).then_some(value)
+    }
+
+    unsafe fn string_attribute(element: CFTypeRef, name: &str) -> Option<String> {
+        let value = copy_attribute(element, name)?;
+        let text = (CFGetTypeID(value) == CFStringGetTypeID())
+            .then(|| nsstring_to_string(value as id))
+            .filter(|text| !text.is_empty());
+        CFRelease(value);
+        text
+    }
+
+    unsafe {
+        let system = AXUIElementCreateSystemWide();
+        if system.is_null() {
+            return None;
+        }
+        AXUIElementSetMessagingTimeout(system, AX_MESSAGING_TIMEOUT_SECS);
+        let focused = copy_attribute(system, "AXFocusedUIElement");
+        CFRelease(system);
+        let focused = focused?;
+        let field = string_attribute(focused, "AXRole").map(|role| FocusedField {
+            role,
+            subrole: string_attribute(focused, "AXSubrole"),
+            labels: ["AXTitle", "AXDescription", "AXPlaceholderValue"]
+                .iter()
+                .filter_map(|name| string_attribute(focused, name))
+                .collect(),
+        });
+        CFRelease(focused);
+        field
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(super) fn focused_field() -> Option<FocusedField> {
+    None
+}
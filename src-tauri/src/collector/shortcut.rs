@@ -1,22 +1,38 @@
 //! Shortcut analytics module.
 //! Owns shortcut normalization/filtering and compact input-event chunk persistence.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
 
-use chrono::{Duration as ChronoDuration, Local, TimeZone};
+use chrono::{Duration as ChronoDuration, Local, NaiveDate, TimeZone};
 
 use crate::storage::{StoredInputAnalytics, StoredInputEventChunk, StoredShortcutUsage};
 
+use super::copy_paste::record_copy_paste_shortcut;
+use super::event_time::EventTime;
+use super::key_names::canonical_key_name;
+use super::runtime::runtime_to_stored;
 use super::{
-    CaptureContext, CollectorState, KeyUsageRow, ModifierSnapshot, ShortcutAppUsageRow,
-    ShortcutStatRow, ShortcutUsageValue,
+    append_app_log, CaptureContext, CollectorState, KeyUsageRow, ModifierSnapshot,
+    ShortcutAppUsageRow, ShortcutRangeStats, ShortcutStatRow, ShortcutUsageValue,
+    TopKeysRangeStats,
 };
 
-const INPUT_CHUNK_WINDOW_MS: i64 = 5_000;
-const INPUT_CHUNK_MAX_EVENTS: usize = 500;
-const INPUT_CHUNK_MAX_STORED: usize = 20_000;
+pub(super) const INPUT_CHUNK_WINDOW_MS: i64 = 5_000;
+pub(super) const INPUT_CHUNK_MAX_EVENTS: usize = 500;
+pub(super) const INPUT_CHUNK_MAX_STORED: usize = 20_000;
+/// Largest gap between wall-clock and monotonic time elapsed in an open chunk that is still
+/// taken for jitter; a larger one is a clock change (or sleep) and closes the chunk.
+pub(super) const CLOCK_JUMP_TOLERANCE_MS: i64 = 2_000;
+/// Apps listed per shortcut row when the caller does not ask for a limit.
+pub(crate) const DEFAULT_SHORTCUT_APP_LIMIT: usize = 8;
+/// Upper bound for a caller-provided per-shortcut app limit.
+pub(crate) const MAX_SHORTCUT_APP_LIMIT: usize = 50;
+/// Compact event layout chunks are written with: `dt,t,m,k` from version 2 on, the key last so
+/// a `,` key keeps its field. Version 1 chunks hold `dt,t,k,m`.
+pub(super) const INPUT_CHUNK_VERSION: u8 = 2;
 
-/// Persistable input chunk that stores compact event strings `dt,t,k,m`.
+/// Persistable input chunk that stores compact event strings, laid out by its version `v`.
 #[derive(Clone)]
 pub(super) struct InputEventChunk {
     pub(super) v: u8,
@@ -25,17 +41,19 @@ pub(super) struct InputEventChunk {
     pub(super) events: Vec<String>,
 }
 
-/// Open chunk that still accepts incoming events before it is rotated/flushed.
+/// Open chunk that still accepts incoming events before it is rotated/flushed. Event `dt`s
+/// are measured from `started_at`, so a wall-clock change mid-chunk cannot skew them.
 #[derive(Clone)]
 pub(super) struct OpenInputEventChunk {
     pub(super) chunk_start_ms: i64,
+    pub(super) started_at: Instant,
     pub(super) app_ref: u32,
     pub(super) events: Vec<String>,
 }
 
 // Build canonical shortcut id with deterministic modifier order:
 // ctrl -> opt -> shift -> cmd -> key.
-fn normalize_shortcut_id(modifiers: ModifierSnapshot, key: &str) -> String {
+pub(super) fn normalize_shortcut_id(modifiers: ModifierSnapshot, key: &str) -> String {
     let mut parts: Vec<&str> = Vec::new();
     if modifiers.ctrl {
         parts.push("ctrl");
@@ -53,6 +71,46 @@ fn normalize_shortcut_id(modifiers: ModifierSnapshot, key: &str) -> String {
     parts.join("_")
 }
 
+// Canonical shortcut id of a user-typed one such as `Cmd+Shift+P` or `shift_cmd_p`: modifiers
+// (any order, common aliases) then the key, separated by `_`, `+` or spaces. None unless at
+// least one modifier and exactly one non-modifier key are given.
+pub(crate) fn canonical_shortcut_id(input: &str) -> Option<String> {
+    let lowered = input.trim().to_ascii_lowercase();
+    let mut tokens: Vec<&str> = lowered
+        .split(|ch: char| ch == '_' || ch == '+' || ch.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .collect();
+    let key = tokens.pop()?;
+    let mut modifiers = ModifierSnapshot::default();
+    for token in tokens {
+        let flag = match token {
+            "ctrl" | "control" => &mut modifiers.ctrl,
+            "opt" | "option" | "alt" => &mut modifiers.opt,
+            "shift" => &mut modifiers.shift,
+            "cmd" | "command" | "meta" | "super" => &mut modifiers.cmd,
+            _ => return None,
+        };
+        *flag = true;
+    }
+    let is_modifier = matches!(
+        key,
+        "ctrl"
+            | "control"
+            | "opt"
+            | "option"
+            | "alt"
+            | "shift"
+            | "cmd"
+            | "command"
+            | "meta"
+            | "super"
+    );
+    if !modifiers.has_any() || is_modifier {
+        return None;
+    }
+    Some(normalize_shortcut_id(modifiers, key))
+}
+
 fn app_id_from_context(capture_context: &CaptureContext) -> String {
     capture_context
         .bundle_id
@@ -61,7 +119,7 @@ fn app_id_from_context(capture_context: &CaptureContext) -> String {
 }
 
 // Resolve app_ref for the given app id and lazily register dictionary entry.
-fn resolve_app_ref(state: &mut CollectorState, app_id: &str) -> u32 {
+pub(super) fn resolve_app_ref(state: &mut CollectorState, app_id: &str) -> u32 {
     if let Some(app_ref) = state.app_ref_by_app.get(app_id) {
         return *app_ref;
     }
@@ -76,24 +134,36 @@ fn push_finished_chunk(state: &mut CollectorState, chunk: OpenInputEventChunk) {
     if chunk.events.is_empty() {
         return;
     }
-    state.event_chunks.push(InputEventChunk {
-        v: 1,
+    let chunk = InputEventChunk {
+        v: INPUT_CHUNK_VERSION,
         chunk_start_ms: chunk.chunk_start_ms,
         app_ref: chunk.app_ref,
         events: chunk.events,
-    });
+    };
+    state.chunk_counters.add(&chunk);
+    state.event_chunks.push(chunk);
     if state.event_chunks.len() > INPUT_CHUNK_MAX_STORED {
         let overflow = state.event_chunks.len() - INPUT_CHUNK_MAX_STORED;
-        state.event_chunks.drain(0..overflow);
+        for pruned in state.event_chunks.drain(0..overflow) {
+            state.chunk_counters.remove(&pruned);
+        }
+    }
+}
+
+// Close the open chunk right away, e.g. when event chunk recording is turned off, so it is
+// kept whole instead of being left half-written.
+pub(super) fn finish_open_chunk(state: &mut CollectorState) {
+    if let Some(chunk) = state.open_event_chunk.take() {
+        push_finished_chunk(state, chunk);
     }
 }
 
 // Flush an open chunk when it is stale enough, reducing in-memory drift before periodic save.
-pub(super) fn flush_expired_open_chunk(state: &mut CollectorState, now_ms: i64) {
+pub(super) fn flush_expired_open_chunk(state: &mut CollectorState, now: Instant) {
     let Some(open) = state.open_event_chunk.as_ref() else {
         return;
     };
-    if now_ms - open.chunk_start_ms < INPUT_CHUNK_WINDOW_MS {
+    if elapsed_ms(open, now) < INPUT_CHUNK_WINDOW_MS {
         return;
     }
     if let Some(chunk) = state.open_event_chunk.take() {
@@ -101,20 +171,60 @@ pub(super) fn flush_expired_open_chunk(state: &mut CollectorState, now_ms: i64)
     }
 }
 
-// Append compact input event string (`dt,t,k,m`) into 5s chunks grouped by app_ref.
+// Monotonic milliseconds since the open chunk started.
+fn elapsed_ms(open: &OpenInputEventChunk, now: Instant) -> i64 {
+    now.saturating_duration_since(open.started_at).as_millis() as i64
+}
+
+// Local calendar day of an epoch millisecond timestamp; daily analytics files are keyed by it.
+pub(super) fn local_day_of_ms(timestamp_ms: i64) -> Option<NaiveDate> {
+    chrono::DateTime::<chrono::Utc>::from_timestamp_millis(timestamp_ms)
+        .map(|value| value.with_timezone(&Local).date_naive())
+}
+
+// Append compact input event string (`dt,t,m,k`, v2) into 5s chunks grouped by app_ref. A chunk
+// never spans local midnight, so filing it under its start day keeps every event on its day.
+// No-op while event chunk recording is off. While shortcuts are paused, events that count as
+// a shortcut are left out, so the chunks cannot be mined for them either; a key-up still
+// under the shortcut's modifiers goes with its key-down. `dt` comes from the monotonic clock;
+// when the wall clock moved more than `CLOCK_JUMP_TOLERANCE_MS` against it since the chunk
+// opened, the chunk is closed and the jump logged, and the next one starts at the new time.
 pub(super) fn append_input_event(
     state: &mut CollectorState,
     capture_context: &CaptureContext,
     event_type: char,
     key: &str,
     modifiers: ModifierSnapshot,
-    now_ms: i64,
+    time: EventTime,
 ) {
+    if !state.record_event_chunks {
+        return;
+    }
+    if state.shortcuts_paused
+        && should_count_shortcut(state, modifiers, &normalize_shortcut_id(modifiers, key))
+    {
+        return;
+    }
     let app_ref = resolve_app_ref(state, &app_id_from_context(capture_context));
+    let EventTime { at, at_ms } = time;
+    let clock_jump_ms = state.open_event_chunk.as_ref().and_then(|open| {
+        let jump_ms = (at_ms - open.chunk_start_ms) - elapsed_ms(open, at);
+        (jump_ms.abs() > CLOCK_JUMP_TOLERANCE_MS).then_some(jump_ms)
+    });
+    if let Some(jump_ms) = clock_jump_ms {
+        let _ = append_app_log(
+            &state.app_log_path,
+            &format!(
+                "wall clock jumped {jump_ms}ms against the monotonic clock; input chunk closed"
+            ),
+        );
+    }
     let should_rotate = if let Some(open) = state.open_event_chunk.as_ref() {
-        open.app_ref != app_ref
-            || now_ms - open.chunk_start_ms >= INPUT_CHUNK_WINDOW_MS
+        clock_jump_ms.is_some()
+            || open.app_ref != app_ref
+            || elapsed_ms(open, at) >= INPUT_CHUNK_WINDOW_MS
             || open.events.len() >= INPUT_CHUNK_MAX_EVENTS
+            || local_day_of_ms(at_ms) != local_day_of_ms(open.chunk_start_ms)
     } else {
         true
     };
@@ -123,37 +233,118 @@ pub(super) fn append_input_event(
             push_finished_chunk(state, chunk);
         }
         state.open_event_chunk = Some(OpenInputEventChunk {
-            chunk_start_ms: now_ms,
+            chunk_start_ms: at_ms,
+            started_at: at,
             app_ref,
             events: Vec::new(),
         });
     }
     if let Some(open) = state.open_event_chunk.as_mut() {
-        let dt = (now_ms - open.chunk_start_ms).max(0);
+        let dt = elapsed_ms(open, at);
         open.events
-            .push(format!("{dt},{event_type},{key},{}", modifiers.bitmask()));
+            .push(format!("{dt},{event_type},{},{key}", modifiers.bitmask()));
     }
 }
 
+// Count the key-down as a shortcut when the shortcut rules allow it; returns whether it is
+// a shortcut. Nothing is counted while shortcuts are paused, but the key still classifies as
+// one, so the writing/navigation split does not change. Counted shortcuts at `at_ms` also
+// feed copy-paste pairing.
 pub(super) fn update_shortcut_usage(
     state: &mut CollectorState,
     capture_context: &CaptureContext,
     key: &str,
     modifiers: ModifierSnapshot,
-) {
+    at_ms: i64,
+) -> bool {
     let shortcut_id = normalize_shortcut_id(modifiers, key);
     if !should_count_shortcut(state, modifiers, &shortcut_id) {
-        return;
+        return false;
+    }
+    if state.shortcuts_paused {
+        return true;
     }
     let app_id = app_id_from_context(capture_context);
-    let entry = state
-        .shortcut_usage
-        .entry(shortcut_id)
-        .or_insert_with(ShortcutUsageValue::default);
+    record_copy_paste_shortcut(state, &shortcut_id, &app_id, at_ms);
+    record_shortcut_use(&mut state.shortcut_usage, shortcut_id, app_id);
+    true
+}
+
+// Whether the key-down is a shortcut the rules count, without counting it.
+pub(super) fn is_counted_shortcut(
+    state: &CollectorState,
+    key: &str,
+    modifiers: ModifierSnapshot,
+) -> bool {
+    should_count_shortcut(state, modifiers, &normalize_shortcut_id(modifiers, key))
+}
+
+fn record_shortcut_use(
+    usage: &mut HashMap<String, ShortcutUsageValue>,
+    shortcut_id: String,
+    app_id: String,
+) {
+    let entry = usage.entry(shortcut_id).or_default();
     entry.count = entry.count.saturating_add(1);
     *entry.by_app.entry(app_id).or_insert(0) += 1;
 }
 
+/// Resolve a caller-provided per-shortcut app limit: default when absent, clamped to 1..=50.
+pub(crate) fn shortcut_app_limit(requested: Option<usize>) -> usize {
+    requested
+        .unwrap_or(DEFAULT_SHORTCUT_APP_LIMIT)
+        .clamp(1, MAX_SHORTCUT_APP_LIMIT)
+}
+
+// The `app_limit` most used apps of `usage`, and the uses in the dropped apps summed.
+fn top_apps(usage: &ShortcutUsageValue, app_limit: usize) -> (Vec<ShortcutAppUsageRow>, u64) {
+    let mut apps: Vec<ShortcutAppUsageRow> = usage
+        .by_app
+        .iter()
+        .map(|(app_name, count)| ShortcutAppUsageRow {
+            app_name: app_name.clone(),
+            count: *count,
+        })
+        .collect();
+    apps.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.app_name.cmp(&b.app_name))
+    });
+    let other_count = apps
+        .iter()
+        .skip(app_limit)
+        .map(|app| app.count)
+        .fold(0u64, u64::saturating_add);
+    apps.truncate(app_limit);
+    (apps, other_count)
+}
+
+// Build leaderboard rows sorted by frequency. Each row keeps its `app_limit` most used apps;
+// uses in the dropped apps are summed into `other_count`.
+fn build_shortcut_rows<'a>(
+    usage: impl Iterator<Item = (&'a String, &'a ShortcutUsageValue)>,
+    app_limit: usize,
+) -> Vec<ShortcutStatRow> {
+    let mut rows: Vec<ShortcutStatRow> = usage
+        .map(|(shortcut_id, usage)| {
+            let (apps, other_count) = top_apps(usage, app_limit);
+            ShortcutStatRow {
+                shortcut_id: shortcut_id.clone(),
+                count: usage.count,
+                apps,
+                other_count,
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.shortcut_id.cmp(&b.shortcut_id))
+    });
+    rows
+}
+
 // Centralized shortcut counting rule evaluator.
 // Priority: blocklist > allowlist > baseline modifier rules.
 fn should_count_shortcut(
@@ -185,10 +376,79 @@ fn should_count_shortcut(
     true
 }
 
-pub(super) fn build_stored_input_analytics(state: &mut CollectorState) -> StoredInputAnalytics {
-    if let Some(chunk) = state.open_event_chunk.take() {
-        push_finished_chunk(state, chunk);
+// Drop app dictionary entries no chunk references any more and, when `remap_dense`, renumber
+// surviving refs to 1..=n (rewriting chunk refs in place). Returns how many entries were removed.
+pub(super) fn gc_app_dict(state: &mut CollectorState, remap_dense: bool) -> usize {
+    let mut referenced: BTreeSet<u32> = state
+        .event_chunks
+        .iter()
+        .map(|chunk| chunk.app_ref)
+        .collect();
+    if let Some(open) = state.open_event_chunk.as_ref() {
+        referenced.insert(open.app_ref);
+    }
+    let before = state.app_dict.len();
+    state
+        .app_dict
+        .retain(|app_ref, _| referenced.contains(app_ref));
+    state
+        .app_ref_by_app
+        .retain(|_, app_ref| referenced.contains(app_ref));
+    let removed = before - state.app_dict.len();
+    if !remap_dense {
+        return removed;
+    }
+    // Only refs with a dictionary entry are renumbered; orphaned chunk refs keep their
+    // `app:{ref}` fallback and must not collide with the dense range.
+    let remap: HashMap<u32, u32> = referenced
+        .iter()
+        .filter(|app_ref| state.app_dict.contains_key(app_ref))
+        .zip(1u32..)
+        .map(|(old, new)| (*old, new))
+        .collect();
+    let dense_end = remap.len() as u32;
+    let orphans: Vec<u32> = referenced
+        .iter()
+        .filter(|app_ref| !remap.contains_key(app_ref))
+        .copied()
+        .collect();
+    if orphans
+        .iter()
+        .any(|app_ref| (1..=dense_end).contains(app_ref))
+    {
+        return removed;
+    }
+    for chunk in &mut state.event_chunks {
+        if let Some(new_ref) = remap.get(&chunk.app_ref) {
+            chunk.app_ref = *new_ref;
+        }
     }
+    if let Some(open) = state.open_event_chunk.as_mut() {
+        if let Some(new_ref) = remap.get(&open.app_ref) {
+            open.app_ref = *new_ref;
+        }
+    }
+    state.app_dict = std::mem::take(&mut state.app_dict)
+        .into_iter()
+        .map(|(app_ref, app_id)| (remap[&app_ref], app_id))
+        .collect();
+    state.app_ref_by_app = state
+        .app_dict
+        .iter()
+        .map(|(app_ref, app_id)| (app_id.clone(), *app_ref))
+        .collect();
+    let orphan_end = orphans.last().copied().unwrap_or(0);
+    state.next_app_ref = dense_end.max(orphan_end).saturating_add(1);
+    removed
+}
+
+// With event chunk recording off no chunks are emitted, so saved sequences leave the disk on
+// the next flush. The in-memory chunks stay intact and are saved again once recording is back.
+pub(super) fn build_stored_input_analytics(state: &mut CollectorState) -> StoredInputAnalytics {
+    finish_open_chunk(state);
+    // Compact the app dictionary together with the chunks it is persisted with, so no
+    // saved chunk references a missing entry and refs stay dense after retention pruning.
+    gc_app_dict(state, true);
     let shortcut_usage = state
         .shortcut_usage
         .iter()
@@ -202,56 +462,75 @@ pub(super) fn build_stored_input_analytics(state: &mut CollectorState) -> Stored
             )
         })
         .collect();
-    let event_chunks = state
-        .event_chunks
-        .iter()
-        .map(|chunk| StoredInputEventChunk {
-            v: chunk.v,
-            chunk_start_ms: chunk.chunk_start_ms,
-            app_ref: chunk.app_ref,
-            events: chunk.events.clone(),
-        })
-        .collect();
+    let event_chunks = if state.record_event_chunks {
+        state
+            .event_chunks
+            .iter()
+            .map(|chunk| StoredInputEventChunk {
+                v: chunk.v,
+                chunk_start_ms: chunk.chunk_start_ms,
+                app_ref: chunk.app_ref,
+                events: chunk.events.clone(),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
     StoredInputAnalytics {
         shortcut_usage,
         app_dict: state.app_dict.clone(),
         next_app_ref: state.next_app_ref,
         event_chunks,
+        chord_aborts: state.chord_aborts.clone(),
+        app_switches: state
+            .app_switches
+            .iter()
+            .map(|(date, day)| (date.clone(), day.to_stored()))
+            .collect(),
+        focus_days: state
+            .focus_days
+            .values()
+            .map(|day| (day.date.clone(), day.to_stored()))
+            .collect(),
+        coverage: state
+            .coverage
+            .iter()
+            .map(|(date, day)| (date.clone(), day.to_stored()))
+            .collect(),
+        runtime: runtime_to_stored(&state.runtime_spans),
+        bursts: state
+            .burst_days
+            .iter()
+            .map(|(date, day)| (date.clone(), day.to_stored()))
+            .collect(),
+        effort: state.effort_days.clone(),
+        displays: state.display_days.clone(),
+        copy_pastes: state
+            .copy_paste_days
+            .iter()
+            .map(|(date, day)| (date.clone(), day.to_stored()))
+            .collect(),
+        key_spans: state.key_spans.clone(),
+        weekly_comparisons: state.weekly_comparisons.clone(),
+        duplicate_chunks_dropped: 0,
     }
 }
 
 // Build shortcut rows sorted by frequency for frontend leaderboard rendering.
-pub(super) fn snapshot_shortcut_rows(state: &CollectorState) -> Vec<ShortcutStatRow> {
-    let mut rows: Vec<ShortcutStatRow> = state
-        .shortcut_usage
-        .iter()
-        .map(|(shortcut_id, usage)| {
-            let mut apps: Vec<ShortcutAppUsageRow> = usage
-                .by_app
-                .iter()
-                .map(|(app_name, count)| ShortcutAppUsageRow {
-                    app_name: app_name.clone(),
-                    count: *count,
-                })
-                .collect();
-            apps.sort_by(|a, b| {
-                b.count
-                    .cmp(&a.count)
-                    .then_with(|| a.app_name.cmp(&b.app_name))
-            });
-            ShortcutStatRow {
-                shortcut_id: shortcut_id.clone(),
-                count: usage.count,
-                apps: apps.into_iter().take(8).collect(),
-            }
-        })
-        .collect();
-    rows.sort_by(|a, b| {
-        b.count
-            .cmp(&a.count)
-            .then_with(|| a.shortcut_id.cmp(&b.shortcut_id))
-    });
-    rows
+pub(super) fn snapshot_shortcut_rows(
+    state: &CollectorState,
+    app_limit: usize,
+) -> Vec<ShortcutStatRow> {
+    build_shortcut_rows(state.shortcut_usage.iter(), app_limit)
+}
+
+// Shortcut id of a replayed key-down. Chunks keep the key name they were recorded with, so it
+// is renamed by the current setting first.
+fn replayed_shortcut_id(state: &CollectorState, modifiers: ModifierSnapshot, key: &str) -> String {
+    normalize_shortcut_id(
+        modifiers,
+        &canonical_key_name(key, modifiers.shift, state.symbol_key_names),
+    )
 }
 
 pub(super) fn rebuild_shortcut_usage_from_chunks(state: &mut CollectorState) {
@@ -263,57 +542,163 @@ pub(super) fn rebuild_shortcut_usage_from_chunks(state: &mut CollectorState) {
             .cloned()
             .unwrap_or_else(|| format!("app:{}", chunk.app_ref));
         for raw_event in &chunk.events {
-            let Some((_dt, event_type, key, modifiers)) = parse_compact_event(raw_event) else {
+            let Some((_dt, event_type, key, modifiers)) = parse_compact_event(chunk.v, raw_event)
+            else {
                 continue;
             };
             if event_type != 'd' {
                 continue;
             }
-            let shortcut_id = normalize_shortcut_id(modifiers, &key);
+            let shortcut_id = replayed_shortcut_id(state, modifiers, &key);
             if !should_count_shortcut(state, modifiers, &shortcut_id) {
                 continue;
             }
-            let usage = aggregated
-                .entry(shortcut_id)
-                .or_insert_with(ShortcutUsageValue::default);
-            usage.count = usage.count.saturating_add(1);
-            *usage.by_app.entry(app_id.clone()).or_insert(0) += 1;
+            record_shortcut_use(&mut aggregated, shortcut_id, app_id.clone());
         }
     }
     state.shortcut_usage = aggregated;
 }
 
-// Parse compact event string `dt,t,k,m`; return None when format is invalid.
-fn parse_compact_event(raw: &str) -> Option<(i64, char, String, ModifierSnapshot)> {
-    let mut segments = raw.splitn(4, ',');
+/// Split chunks written before day rotation existed wherever their events cross local midnight.
+/// Each later piece starts at its first event, with `dt` rebased; unparseable events stay with
+/// the piece being built. Returns the chunks ordered by start and the number of chunks split.
+pub(super) fn split_cross_day_chunks(
+    chunks: Vec<InputEventChunk>,
+) -> (Vec<InputEventChunk>, usize) {
+    let mut repaired = Vec::with_capacity(chunks.len());
+    let mut split_count = 0;
+    for chunk in chunks {
+        let start_day = local_day_of_ms(chunk.chunk_start_ms);
+        let crosses_day = chunk.events.iter().any(|raw| {
+            parse_compact_event(chunk.v, raw).is_some_and(|(dt, ..)| {
+                local_day_of_ms(chunk.chunk_start_ms.saturating_add(dt.max(0))) != start_day
+            })
+        });
+        if !crosses_day {
+            repaired.push(chunk);
+            continue;
+        }
+        split_count += 1;
+        let mut piece = InputEventChunk {
+            v: chunk.v,
+            chunk_start_ms: chunk.chunk_start_ms,
+            app_ref: chunk.app_ref,
+            events: Vec::new(),
+        };
+        let mut piece_day = start_day;
+        for raw in chunk.events {
+            let Some((dt, ..)) = parse_compact_event(chunk.v, &raw) else {
+                piece.events.push(raw);
+                continue;
+            };
+            let event_ms = chunk.chunk_start_ms.saturating_add(dt.max(0));
+            let event_day = local_day_of_ms(event_ms);
+            if event_day != piece_day {
+                let next = InputEventChunk {
+                    v: chunk.v,
+                    chunk_start_ms: event_ms,
+                    app_ref: chunk.app_ref,
+                    events: Vec::new(),
+                };
+                let finished = std::mem::replace(&mut piece, next);
+                if !finished.events.is_empty() {
+                    repaired.push(finished);
+                }
+                piece_day = event_day;
+            }
+            let rest = raw.find(',').map_or("", |index| &raw[index..]);
+            piece
+                .events
+                .push(format!("{}{rest}", event_ms - piece.chunk_start_ms));
+        }
+        if !piece.events.is_empty() {
+            repaired.push(piece);
+        }
+    }
+    if split_count > 0 {
+        repaired.sort_by_key(|chunk| (chunk.chunk_start_ms, chunk.app_ref));
+    }
+    (repaired, split_count)
+}
+
+// Parse one compact event of a chunk with format version `version`: `dt,t,m,k` from v2 on,
+// `dt,t,k,m` in v1, where the mask is split off the end so a `,` key still parses. None when
+// the event or the version is invalid.
+pub(super) fn parse_compact_event(
+    version: u8,
+    raw: &str,
+) -> Option<(i64, char, String, ModifierSnapshot)> {
+    let mut segments = raw.splitn(3, ',');
     let dt = segments.next()?.parse::<i64>().ok()?;
     let event_type = segments.next()?.chars().next()?;
-    let key = segments.next()?.to_string();
-    let modifier_mask = segments.next()?.parse::<u8>().ok()?;
+    let rest = segments.next()?;
+    let (key, modifier_mask) = match version {
+        1 => {
+            let (key, mask) = rest.rsplit_once(',')?;
+            (key, mask)
+        }
+        INPUT_CHUNK_VERSION => {
+            let (mask, key) = rest.split_once(',')?;
+            (key, mask)
+        }
+        _ => return None,
+    };
+    let modifier_mask = modifier_mask.parse::<u8>().ok()?;
+    if key.is_empty() {
+        return None;
+    }
     Some((
         dt,
         event_type,
-        key,
+        key.to_string(),
         ModifierSnapshot::from_bitmask(modifier_mask),
     ))
 }
 
+// Resolve local midnight of `day` to epoch milliseconds.
+fn local_midnight_ms(day: NaiveDate) -> Option<i64> {
+    let midnight_naive = day.and_hms_opt(0, 0, 0)?;
+    Local
+        .from_local_datetime(&midnight_naive)
+        .single()
+        .or_else(|| Local.from_local_datetime(&midnight_naive).earliest())
+        .or_else(|| Local.from_local_datetime(&midnight_naive).latest())
+        .map(|value| value.timestamp_millis())
+}
+
+/// Compute the local [start,end) window of one calendar day in milliseconds.
+pub(super) fn local_day_window_ms(day: NaiveDate) -> Option<(i64, i64)> {
+    Some((local_midnight_ms(day)?, local_midnight_ms(day.succ_opt()?)?))
+}
+
+// Local calendar days (`YYYY-MM-DD`) whose windows start inside [start_ms, end_ms).
+pub(super) fn days_in_window(start_ms: i64, end_ms: i64) -> Vec<String> {
+    let Some(mut day) = chrono::DateTime::<chrono::Utc>::from_timestamp_millis(start_ms)
+        .map(|value| value.with_timezone(&Local).date_naive())
+    else {
+        return Vec::new();
+    };
+    let mut days = Vec::new();
+    while let Some((day_start, _)) = local_day_window_ms(day) {
+        if day_start >= end_ms {
+            break;
+        }
+        days.push(day.format("%Y-%m-%d").to_string());
+        let Some(next) = day.succ_opt() else {
+            break;
+        };
+        day = next;
+    }
+    days
+}
+
 // Compute local [start,end) timestamp range in milliseconds by filter id.
-fn shortcut_range_window_ms(range: &str, now_ms: i64) -> (i64, i64) {
+pub(super) fn shortcut_range_window_ms(range: &str, now_ms: i64) -> (i64, i64) {
     let now_local = chrono::DateTime::<chrono::Utc>::from_timestamp_millis(now_ms)
         .map(|v| v.with_timezone(&Local))
         .unwrap_or_else(Local::now);
     let today = now_local.date_naive();
-    let midnight_naive = today
-        .and_hms_opt(0, 0, 0)
-        .unwrap_or_else(|| now_local.naive_local());
-    let today_start = Local
-        .from_local_datetime(&midnight_naive)
-        .single()
-        .or_else(|| Local.from_local_datetime(&midnight_naive).earliest())
-        .or_else(|| Local.from_local_datetime(&midnight_naive).latest())
-        .unwrap_or(now_local)
-        .timestamp_millis();
+    let today_start = local_midnight_ms(today).unwrap_or(now_ms);
     let tomorrow_start = today_start + ChronoDuration::days(1).num_milliseconds();
     if range == "today" {
         return (today_start, tomorrow_start);
@@ -326,21 +711,32 @@ fn shortcut_range_window_ms(range: &str, now_ms: i64) -> (i64, i64) {
     (seven_days_start, tomorrow_start)
 }
 
-// Rebuild shortcut usage rows from compact events for a requested time window.
-fn snapshot_shortcut_rows_in_window(
+// Resolve a chunk's app id, falling back to a synthetic id for orphaned refs.
+pub(super) fn chunk_app_id(state: &CollectorState, app_ref: u32) -> String {
+    state
+        .app_dict
+        .get(&app_ref)
+        .cloned()
+        .unwrap_or_else(|| format!("app:{app_ref}"))
+}
+
+// Replay compact events in a requested time window into per-shortcut usage, optionally
+// limited to one app id.
+pub(super) fn shortcut_usage_in_window(
     state: &CollectorState,
     start_ms: i64,
     end_ms: i64,
-) -> Vec<ShortcutStatRow> {
+    app_filter: Option<&str>,
+) -> HashMap<String, ShortcutUsageValue> {
     let mut aggregated: HashMap<String, ShortcutUsageValue> = HashMap::new();
-    let mut consume_chunk = |chunk_start_ms: i64, app_ref: u32, events: &[String]| {
-        let app_id = state
-            .app_dict
-            .get(&app_ref)
-            .cloned()
-            .unwrap_or_else(|| format!("app:{app_ref}"));
+    let mut consume_chunk = |version: u8, chunk_start_ms: i64, app_ref: u32, events: &[String]| {
+        let app_id = chunk_app_id(state, app_ref);
+        if app_filter.is_some_and(|filter| filter != app_id) {
+            return;
+        }
         for raw_event in events {
-            let Some((dt, event_type, key, modifiers)) = parse_compact_event(raw_event) else {
+            let Some((dt, event_type, key, modifiers)) = parse_compact_event(version, raw_event)
+            else {
                 continue;
             };
             if event_type != 'd' {
@@ -350,75 +746,97 @@ fn snapshot_shortcut_rows_in_window(
             if event_ms < start_ms || event_ms >= end_ms {
                 continue;
             }
-            let shortcut_id = normalize_shortcut_id(modifiers, &key);
+            let shortcut_id = replayed_shortcut_id(state, modifiers, &key);
             if !should_count_shortcut(state, modifiers, &shortcut_id) {
                 continue;
             }
-            let usage = aggregated
-                .entry(shortcut_id)
-                .or_insert_with(ShortcutUsageValue::default);
-            usage.count = usage.count.saturating_add(1);
-            *usage.by_app.entry(app_id.clone()).or_insert(0) += 1;
+            record_shortcut_use(&mut aggregated, shortcut_id, app_id.clone());
         }
     };
     for chunk in &state.event_chunks {
-        consume_chunk(chunk.chunk_start_ms, chunk.app_ref, &chunk.events);
+        consume_chunk(chunk.v, chunk.chunk_start_ms, chunk.app_ref, &chunk.events);
     }
     if let Some(open_chunk) = state.open_event_chunk.as_ref() {
         consume_chunk(
+            INPUT_CHUNK_VERSION,
             open_chunk.chunk_start_ms,
             open_chunk.app_ref,
             &open_chunk.events,
         );
     }
-    let mut rows: Vec<ShortcutStatRow> = aggregated
-        .into_iter()
-        .map(|(shortcut_id, usage)| {
-            let mut apps: Vec<ShortcutAppUsageRow> = usage
-                .by_app
-                .into_iter()
-                .map(|(app_name, count)| ShortcutAppUsageRow { app_name, count })
-                .collect();
-            apps.sort_by(|a, b| {
-                b.count
-                    .cmp(&a.count)
-                    .then_with(|| a.app_name.cmp(&b.app_name))
-            });
-            ShortcutStatRow {
-                shortcut_id,
-                count: usage.count,
-                apps: apps.into_iter().take(8).collect(),
-            }
-        })
-        .collect();
-    rows.sort_by(|a, b| {
-        b.count
-            .cmp(&a.count)
-            .then_with(|| a.shortcut_id.cmp(&b.shortcut_id))
-    });
-    rows
+    aggregated
+}
+
+// Rebuild shortcut usage rows from compact events for a requested time window,
+// optionally limited to one app id.
+pub(super) fn snapshot_shortcut_rows_in_window(
+    state: &CollectorState,
+    start_ms: i64,
+    end_ms: i64,
+    app_filter: Option<&str>,
+    app_limit: usize,
+) -> Vec<ShortcutStatRow> {
+    let aggregated = shortcut_usage_in_window(state, start_ms, end_ms, app_filter);
+    build_shortcut_rows(aggregated.iter(), app_limit)
 }
 
-/// Build shortcut leaderboard rows by selected range: `today` / `yesterday` / `7d`.
+/// Build shortcut leaderboard rows by selected range: `today` / `yesterday` / `7d`, listing
+/// up to `app_limit` apps per shortcut. Without event chunks every range falls back to the
+/// lifetime aggregates.
 pub fn snapshot_shortcut_rows_by_range(
     state: &CollectorState,
     range: &str,
-) -> Vec<ShortcutStatRow> {
+    app_limit: usize,
+) -> ShortcutRangeStats {
+    if !state.record_event_chunks {
+        return ShortcutRangeStats {
+            rows: snapshot_shortcut_rows(state, app_limit),
+            degraded_range_support: true,
+        };
+    }
     let now_ms = chrono::Utc::now().timestamp_millis();
     let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
-    snapshot_shortcut_rows_in_window(state, start_ms, end_ms)
+    ShortcutRangeStats {
+        rows: snapshot_shortcut_rows_in_window(state, start_ms, end_ms, None, app_limit),
+        degraded_range_support: false,
+    }
 }
 
-// Rebuild top-key rows from compact key-down events for a requested time window.
-fn snapshot_top_keys_in_window(
+// Replay key-down events inside a time window, optionally limited to one app id,
+// calling `visit` with each event timestamp and normalized key.
+pub(super) fn for_each_key_down_in_window(
     state: &CollectorState,
     start_ms: i64,
     end_ms: i64,
-) -> Vec<KeyUsageRow> {
-    let mut key_counts: HashMap<String, u64> = HashMap::new();
-    let mut consume_chunk = |chunk_start_ms: i64, events: &[String]| {
+    app_filter: Option<&str>,
+    mut visit: impl FnMut(i64, String),
+) {
+    for_each_key_down_with_app_in_window(
+        state,
+        start_ms,
+        end_ms,
+        app_filter,
+        |_, event_ms, key, _| visit(event_ms, key),
+    );
+}
+
+// Like `for_each_key_down_in_window`, but `visit` also receives the chunk's app id and the
+// modifiers held at the time.
+pub(super) fn for_each_key_down_with_app_in_window(
+    state: &CollectorState,
+    start_ms: i64,
+    end_ms: i64,
+    app_filter: Option<&str>,
+    mut visit: impl FnMut(&str, i64, String, ModifierSnapshot),
+) {
+    let mut consume_chunk = |version: u8, chunk_start_ms: i64, app_ref: u32, events: &[String]| {
+        let app_id = chunk_app_id(state, app_ref);
+        if app_filter.is_some_and(|filter| filter != app_id) {
+            return;
+        }
         for raw_event in events {
-            let Some((dt, event_type, key, _modifiers)) = parse_compact_event(raw_event) else {
+            let Some((dt, event_type, key, modifiers)) = parse_compact_event(version, raw_event)
+            else {
                 continue;
             };
             if event_type != 'd' {
@@ -428,28 +846,98 @@ fn snapshot_top_keys_in_window(
             if event_ms < start_ms || event_ms >= end_ms {
                 continue;
             }
-            *key_counts.entry(key).or_insert(0) += 1;
+            let key = canonical_key_name(&key, modifiers.shift, state.symbol_key_names);
+            visit(&app_id, event_ms, key, modifiers);
         }
     };
 
     for chunk in &state.event_chunks {
-        consume_chunk(chunk.chunk_start_ms, &chunk.events);
+        consume_chunk(chunk.v, chunk.chunk_start_ms, chunk.app_ref, &chunk.events);
     }
     if let Some(open_chunk) = state.open_event_chunk.as_ref() {
-        consume_chunk(open_chunk.chunk_start_ms, &open_chunk.events);
+        consume_chunk(
+            INPUT_CHUNK_VERSION,
+            open_chunk.chunk_start_ms,
+            open_chunk.app_ref,
+            &open_chunk.events,
+        );
     }
+}
+
+// Count key-down events per normalized key inside a time window, optionally limited to one app id.
+pub(super) fn key_counts_in_window(
+    state: &CollectorState,
+    start_ms: i64,
+    end_ms: i64,
+    app_filter: Option<&str>,
+) -> HashMap<String, u64> {
+    let mut key_counts: HashMap<String, u64> = HashMap::new();
+    for_each_key_down_in_window(state, start_ms, end_ms, app_filter, |_, key| {
+        *key_counts.entry(key).or_insert(0) += 1;
+    });
+    key_counts
+}
 
-    let mut rows: Vec<KeyUsageRow> = key_counts
+// Count key-down events per normalized key and app inside a time window, optionally limited to
+// one app id.
+pub(super) fn key_usage_in_window(
+    state: &CollectorState,
+    start_ms: i64,
+    end_ms: i64,
+    app_filter: Option<&str>,
+) -> HashMap<String, ShortcutUsageValue> {
+    let mut usage: HashMap<String, ShortcutUsageValue> = HashMap::new();
+    for_each_key_down_with_app_in_window(
+        state,
+        start_ms,
+        end_ms,
+        app_filter,
+        |app_id, _, key, _| {
+            let entry = usage.entry(key).or_default();
+            entry.count += 1;
+            *entry.by_app.entry(app_id.to_string()).or_insert(0) += 1;
+        },
+    );
+    usage
+}
+
+// Rebuild top-key rows from compact key-down events for a requested time window,
+// optionally limited to one app id. Each row lists its most used apps like a shortcut row.
+pub(super) fn snapshot_top_keys_in_window(
+    state: &CollectorState,
+    start_ms: i64,
+    end_ms: i64,
+    app_filter: Option<&str>,
+) -> Vec<KeyUsageRow> {
+    let mut rows: Vec<KeyUsageRow> = key_usage_in_window(state, start_ms, end_ms, app_filter)
         .into_iter()
-        .map(|(key, count)| KeyUsageRow { key, count })
+        .map(|(key, usage)| {
+            let (apps, other_count) = top_apps(&usage, DEFAULT_SHORTCUT_APP_LIMIT);
+            KeyUsageRow {
+                key,
+                count: usage.count,
+                apps,
+                other_count,
+            }
+        })
         .collect();
     rows.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
     rows.into_iter().take(5).collect()
 }
 
-/// Build top-key rows by selected range: `today` / `yesterday` / `7d`.
-pub fn snapshot_top_keys_by_range(state: &CollectorState, range: &str) -> Vec<KeyUsageRow> {
+/// Build top-key rows by selected range: `today` / `yesterday` / `7d`. There is no per-key
+/// lifetime aggregate, so without event chunks every range replays all chunks still in memory.
+pub fn snapshot_top_keys_by_range(state: &CollectorState, range: &str) -> TopKeysRangeStats {
+    if !state.record_event_chunks {
+        return TopKeysRangeStats {
+            rows: snapshot_top_keys_in_window(state, i64::MIN, i64::MAX, None),
+            degraded_range_support: true,
+        };
+    }
     let now_ms = chrono::Utc::now().timestamp_millis();
     let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
-    snapshot_top_keys_in_window(state, start_ms, end_ms)
+    TopKeysRangeStats {
+        rows: snapshot_top_keys_in_window(state, start_ms, end_ms, None),
+        degraded_range_support: false,
+    }
 }
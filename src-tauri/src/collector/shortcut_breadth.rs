@@ -0,0 +1,96 @@
+//! Shortcut breadth module.
+//! Tells universal shortcuts (used across many apps) apart from app-specific ones by
+//! replaying the range's input chunks.
+
+use serde::Serialize;
+
+use super::shortcut::{shortcut_range_window_ms, shortcut_usage_in_window};
+use super::{CollectorState, ShortcutUsageValue};
+
+/// How one shortcut's uses spread over apps within a range.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ShortcutBreadthRow {
+    pub shortcut_id: String,
+    pub count: u64,
+    pub app_count: usize,
+    /// Herfindahl index of per-app shares: 1.0 when used in a single app, 1/n when spread
+    /// evenly over n apps.
+    pub concentration: f64,
+    pub dominant_app: String,
+    pub dominant_app_count: u64,
+}
+
+/// Shortcut breadth for a range, sorted by total uses.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ShortcutBreadth {
+    pub range: String,
+    pub shortcuts: Vec<ShortcutBreadthRow>,
+    /// Set when event chunk recording is off and the rows are lifetime aggregates.
+    pub degraded_range_support: bool,
+}
+
+/// Sum of squared shares of `counts`; 0.0 when there are no uses.
+pub(super) fn concentration_index(counts: &[u64]) -> f64 {
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    counts
+        .iter()
+        .map(|count| {
+            let share = *count as f64 / total as f64;
+            share * share
+        })
+        .sum()
+}
+
+fn breadth_row(shortcut_id: String, usage: &ShortcutUsageValue) -> ShortcutBreadthRow {
+    let counts: Vec<u64> = usage.by_app.values().copied().collect();
+    // Ties go to the lexicographically smallest app so the result does not depend on hash order.
+    let (dominant_app, dominant_app_count) = usage
+        .by_app
+        .iter()
+        .max_by(|(app_a, count_a), (app_b, count_b)| {
+            count_a.cmp(count_b).then_with(|| app_b.cmp(app_a))
+        })
+        .map(|(app, count)| (app.clone(), *count))
+        .unwrap_or_default();
+    ShortcutBreadthRow {
+        shortcut_id,
+        count: usage.count,
+        app_count: usage.by_app.len(),
+        concentration: concentration_index(&counts),
+        dominant_app,
+        dominant_app_count,
+    }
+}
+
+/// Build shortcut breadth rows by selected range: `today` / `yesterday` / `7d`. Without event
+/// chunks every range falls back to the lifetime aggregates.
+pub fn snapshot_shortcut_breadth(state: &CollectorState, range: &str) -> ShortcutBreadth {
+    let degraded_range_support = !state.record_event_chunks;
+    let mut shortcuts: Vec<ShortcutBreadthRow> = if degraded_range_support {
+        state
+            .shortcut_usage
+            .iter()
+            .map(|(shortcut_id, usage)| breadth_row(shortcut_id.clone(), usage))
+            .collect()
+    } else {
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+        shortcut_usage_in_window(state, start_ms, end_ms, None)
+            .into_iter()
+            .map(|(shortcut_id, usage)| breadth_row(shortcut_id, &usage))
+            .collect()
+    };
+    shortcuts.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.shortcut_id.cmp(&b.shortcut_id))
+    });
+    ShortcutBreadth {
+        range: range.to_string(),
+        shortcuts,
+        degraded_range_support,
+    }
+}
@@ -0,0 +1,83 @@
+//! Shortcut series module.
+//! Daily use counts of one shortcut, replayed from event chunks, for charting how a newly
+//! learned shortcut is adopted.
+
+use std::collections::HashMap;
+
+use chrono::{Duration as ChronoDuration, Local, NaiveDate};
+use serde::Serialize;
+
+use super::shortcut::{
+    canonical_shortcut_id, for_each_key_down_with_app_in_window, local_day_of_ms,
+    local_day_window_ms, normalize_shortcut_id,
+};
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+/// Days returned when the caller does not ask for a length.
+pub(crate) const DEFAULT_SHORTCUT_SERIES_DAYS: usize = 30;
+/// Upper bound for a caller-provided series length.
+const MAX_SHORTCUT_SERIES_DAYS: usize = 366;
+
+/// Uses of the shortcut on one local day.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ShortcutDayCount {
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub count: u64,
+}
+
+// Dense per-day counts of `shortcut_id` for the `days` days ending on `today`, oldest first.
+pub(super) fn shortcut_daily_series_until(
+    state: &CollectorState,
+    shortcut_id: &str,
+    days: usize,
+    today: NaiveDate,
+) -> Vec<ShortcutDayCount> {
+    let Some(shortcut_id) = canonical_shortcut_id(shortcut_id) else {
+        return Vec::new();
+    };
+    let days = days.clamp(1, MAX_SHORTCUT_SERIES_DAYS);
+    let first_day = today - ChronoDuration::days(days as i64 - 1);
+    let (Some((start_ms, _)), Some((_, end_ms))) =
+        (local_day_window_ms(first_day), local_day_window_ms(today))
+    else {
+        return Vec::new();
+    };
+    // Counts follow the key-downs themselves, not the current shortcut rules, so a shortcut
+    // outside the allowlist or on the blocklist still charts.
+    let mut counts: HashMap<NaiveDate, u64> = HashMap::new();
+    for_each_key_down_with_app_in_window(
+        state,
+        start_ms,
+        end_ms,
+        None,
+        |_, event_ms, key, modifiers| {
+            if normalize_shortcut_id(modifiers, &key) != shortcut_id {
+                return;
+            }
+            if let Some(day) = local_day_of_ms(event_ms) {
+                *counts.entry(day).or_default() += 1;
+            }
+        },
+    );
+    first_day
+        .iter_days()
+        .take(days)
+        .map(|day| ShortcutDayCount {
+            date: day.format(DAY_KEY_FORMAT).to_string(),
+            count: counts.get(&day).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
+/// Daily uses of `shortcut_id` over the last `days` local days (today included, clamped to
+/// 1–366), with zero days filled in. There is no per-day shortcut aggregate, so days whose
+/// chunks were rotated out read as zero. An id that is not a valid shortcut gives an empty
+/// series.
+pub fn snapshot_shortcut_daily_series(
+    state: &CollectorState,
+    shortcut_id: &str,
+    days: usize,
+) -> Vec<ShortcutDayCount> {
+    shortcut_daily_series_until(state, shortcut_id, days, Local::now().date_naive())
+}
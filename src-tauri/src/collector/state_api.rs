@@ -1,26 +1,113 @@
 //! Collector state API module.
 //! Implements `CollectorState` methods for snapshot building and state mutations.
 
-use crate::app_config::MenuBarDisplayMode;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Instant;
 
+use chrono::{Datelike, Duration, Local, Months, NaiveDate};
+
+use crate::app_config::{
+    normalize_bundle_list, AppConfig, MenuBarDisplayMode, TrayLeftClickAction, WindowTitleMode,
+};
+
+use crate::storage::{rollup_stats_to_hours, ArchiveReport};
+
+use super::app_goal::goal_progress;
+use super::chunk_stats::ChunkCounters;
+use super::context::{auto_pause_reason, is_auto_paused};
+use super::coverage::today_coverage_pct;
+use super::current_context::current_context;
+use super::deep_work::deep_work_minutes_today;
+use super::effort::today_effort;
+use super::events::sync_app_nap;
+use super::focus::{refresh_recent_focus_days, today_focus_score};
+use super::key_names::canonical_shortcut_id_set;
+use super::key_span::key_span_on;
+use super::live_session::live_session_summary;
+use super::minute_series::{minute_counts, MINUTE_SERIES_LEN};
+use super::rolling_avg::{today_vs_avg_pct, RollingAverages};
+use super::runtime::{begin_runtime_span, prune_runtime_spans, record_runtime};
+use super::sensitive_field::SensitiveFieldCache;
+use super::shortcut::{finish_open_chunk, DEFAULT_SHORTCUT_APP_LIMIT};
+use super::stats_rows::SNAPSHOT_SHORTCUT_LIMIT;
+use super::storage_health::{check_external_changes, probe_storage};
 use super::{
-    build_stored_input_analytics, reset_active_typing_state, snapshot_shortcut_rows,
-    CollectorState, StatsRow, StatsSnapshot,
+    append_app_log, build_stored_input_analytics, load_collector_state, reset_active_typing_state,
+    snapshot_shortcut_rows, write_csv, CaptureContext, CollectorState, CsvDialect, StatsKey,
+    StatsRow, StatsSnapshot, StatsValue, TodaySummary, TodayTotals,
 };
 
+// Sum stats values of every row whose key passes `filter`.
+pub(super) fn scan_stats_totals(
+    stats: &HashMap<StatsKey, StatsValue>,
+    filter: impl Fn(&StatsKey) -> bool,
+) -> StatsValue {
+    let mut totals = StatsValue {
+        active_typing_ms: 0,
+        key_count: 0,
+        session_count: 0,
+        writing_ms: 0,
+        navigation_ms: 0,
+    };
+    for (key, value) in stats {
+        if filter(key) {
+            totals.active_typing_ms += value.active_typing_ms;
+            totals.key_count += value.key_count;
+            totals.session_count += value.session_count;
+            totals.writing_ms += value.writing_ms;
+            totals.navigation_ms += value.navigation_ms;
+        }
+    }
+    totals
+}
+
+// Sum key count and active time of the local day `today` from the stats map, overall and per
+// app. The tray, the today JSON and the cached totals all start from this scan.
+pub(super) fn compute_today_totals(
+    stats: &HashMap<StatsKey, StatsValue>,
+    today: NaiveDate,
+) -> TodayTotals {
+    let mut totals = TodayTotals {
+        date: Some(today),
+        ..TodayTotals::default()
+    };
+    for (key, value) in stats {
+        if key.date.day() == today {
+            totals.key_count += value.key_count;
+            totals.active_typing_ms += value.active_typing_ms;
+            totals.writing_ms += value.writing_ms;
+            totals.navigation_ms += value.navigation_ms;
+            *totals
+                .app_active_typing_ms
+                .entry(key.app_name.to_ascii_lowercase())
+                .or_default() += value.active_typing_ms;
+        }
+    }
+    totals
+}
+
 impl CollectorState {
     /// Build sorted row snapshots from in-memory collector stats.
     pub fn snapshot_rows(&self) -> Result<Vec<StatsRow>, String> {
+        Ok(self.rows_on_days(|_| true))
+    }
+
+    /// Sorted rows of the local days `keep` accepts.
+    pub(super) fn rows_on_days(&self, keep: impl Fn(NaiveDate) -> bool) -> Vec<StatsRow> {
         let mut rows: Vec<StatsRow> = self
             .stats
             .iter()
+            .filter(|(key, _)| keep(key.date.day()))
             .map(|(key, value)| StatsRow {
-                date: key.date.clone(),
+                date: key.date,
                 app_name: key.app_name.clone(),
                 window_title: key.window_title.clone(),
                 active_typing_ms: value.active_typing_ms,
                 key_count: value.key_count,
                 session_count: value.session_count,
+                writing_ms: value.writing_ms,
+                navigation_ms: value.navigation_ms,
             })
             .collect();
         rows.sort_by(|a, b| {
@@ -31,29 +118,132 @@ impl CollectorState {
                 b.active_typing_ms,
             ))
         });
-        Ok(rows)
+        rows
     }
 
     /// Build the frontend snapshot payload from current runtime collector state.
     pub fn snapshot(&self) -> StatsSnapshot {
-        let rows = self.snapshot_rows().unwrap_or_default();
-        let shortcut_stats = snapshot_shortcut_rows(self);
-        let mut excluded_bundle_ids: Vec<String> =
-            self.excluded_bundle_ids.iter().cloned().collect();
-        excluded_bundle_ids.sort();
+        let today = self.today_summary();
+        let averages = self.rolling_averages;
+        let key_span = key_span_on(self, Local::now().date_naive());
+        let row_total_count = self.stats.len() as u64;
+        let mut shortcut_stats = snapshot_shortcut_rows(self, DEFAULT_SHORTCUT_APP_LIMIT);
+        let shortcut_total_count = shortcut_stats.len() as u64;
+        // The v2 payload stays small however long the history gets: today's rows and the top
+        // shortcuts only. Ranges and daily totals have their own commands.
+        let rows = if self.snapshot_v2 {
+            let local_today = Local::now().date_naive();
+            shortcut_stats.truncate(SNAPSHOT_SHORTCUT_LIMIT);
+            self.rows_on_days(|day| day == local_today)
+        } else {
+            self.rows_on_days(|_| true)
+        };
+        let excluded_bundle_ids = self.excluded_bundle_ids();
+        let (load_files_processed, load_files_total) = self
+            .history_load
+            .as_ref()
+            .map(|progress| progress.files())
+            .unwrap_or_default();
         StatsSnapshot {
             rows,
             paused: self.paused,
             auto_paused: self.auto_paused,
             auto_pause_reason: self.auto_pause_reason.clone(),
+            shortcuts_paused: self.shortcuts_paused,
             keyboard_active: self.keyboard_active,
             ignore_key_combos: self.ignore_key_combos,
+            track_chord_aborts: self.track_chord_aborts,
+            prevent_app_nap: self.prevent_app_nap,
+            app_nap_assertion_held: self.app_nap.is_held(),
             excluded_bundle_ids,
             one_password_suggestion_pending: self.one_password_suggestion_pending,
+            non_typing_suggestions: self.non_typing_suggestions.clone(),
             tray_display_mode: self.menu_bar_display_mode.as_str().to_string(),
+            tray_left_click_action: self.tray_left_click_action.as_str().to_string(),
+            start_hidden: self.start_hidden,
+            min_keys_to_display: self.min_keys_to_display,
+            window_title_mode: self.window_title_mode.as_str().to_string(),
+            title_privacy_overrides: self
+                .title_privacy_overrides
+                .iter()
+                .map(|(bundle_id, mode)| (bundle_id.clone(), mode.as_str().to_string()))
+                .collect::<BTreeMap<_, _>>(),
             last_error: self.last_error.clone(),
+            storage_degraded: self.storage_health.is_degraded(),
+            storage_writable: self.storage_writable,
+            loading: self.history_loading(),
+            demo_mode: self.demo_mode,
+            load_files_processed,
+            load_files_total,
+            external_changes_detected: self.external_changes_detected,
+            tick_stalled: self.tick_watchdog.is_stalled(),
+            update_available: self.update_available,
             log_path: self.log_path.to_string_lossy().to_string(),
             shortcut_stats,
+            shortcut_total_count,
+            row_total_count,
+            snapshot_v2: self.snapshot_v2,
+            today_focus_score: today_focus_score(self),
+            today_coverage_pct: today_coverage_pct(self),
+            goal_progress: today.goal_progress,
+            current_context: current_context(self),
+            avg_keys_7d: averages.short.map(|average| average.key_count),
+            avg_keys_30d: averages.long.map(|average| average.key_count),
+            avg_active_ms_7d: averages.short.map(|average| average.active_typing_ms),
+            avg_active_ms_30d: averages.long.map(|average| average.active_typing_ms),
+            today_vs_avg_pct: today_vs_avg_pct(&averages, today.key_count),
+            effort_today: today_effort(self),
+            deep_work_minutes_today: deep_work_minutes_today(self),
+            day_first_key_ms: key_span.map(|span| span.first_ms),
+            day_last_key_ms: key_span.map(|span| span.last_ms),
+        }
+    }
+
+    /// Capture the frontmost app context through the injectable provider.
+    pub(super) fn current_context(&self) -> CaptureContext {
+        (self.context_provider)()
+    }
+
+    /// Rescan the cached day totals once the local date moved past them, so the first read
+    /// after midnight shows the new day without waiting for a key. The tick and the tray
+    /// updater call this; a fresh cache costs nothing.
+    pub(crate) fn refresh_today_totals(&mut self) {
+        let today = Local::now().date_naive();
+        if self.today_totals.date != Some(today) {
+            self.today_totals = compute_today_totals(&self.stats, today);
+        }
+    }
+
+    /// Build today's tray summary, app goal progress included, from the cached day totals.
+    /// Falls back to a stats scan when the cache still points at a previous day, which
+    /// `refresh_today_totals` keeps to the moments right after midnight.
+    pub(crate) fn today_summary(&self) -> TodaySummary {
+        let today = Local::now().date_naive();
+        let scanned;
+        let totals = if self.today_totals.date == Some(today) {
+            &self.today_totals
+        } else {
+            scanned = compute_today_totals(&self.stats, today);
+            &scanned
+        };
+        TodaySummary {
+            key_count: totals.key_count,
+            active_typing_ms: totals.active_typing_ms,
+            writing_ms: totals.writing_ms,
+            navigation_ms: totals.navigation_ms,
+            paused: self.paused,
+            auto_paused: self.auto_paused,
+            auto_pause_reason: self.auto_pause_reason.clone(),
+            shortcuts_paused: self.shortcuts_paused,
+            menu_bar_display_mode: self.menu_bar_display_mode,
+            goal_progress: goal_progress(&self.app_time_goals, totals),
+            live_session: live_session_summary(self, Instant::now()),
+            minute_counts: minute_counts(
+                self,
+                chrono::Utc::now().timestamp_millis(),
+                MINUTE_SERIES_LEN,
+            ),
+            storage_unwritable: !self.storage_writable,
         }
     }
 
@@ -65,14 +255,174 @@ impl CollectorState {
         }
     }
 
+    /// Pause/resume on behalf of `source` (command, tray), logging the transition, and
+    /// return the tray summary the caller uses to notify the UI.
+    pub(crate) fn set_paused_from(&mut self, paused: bool, source: &str) -> TodaySummary {
+        self.set_paused(paused);
+        let action = if paused { "paused" } else { "resumed" };
+        let _ = append_app_log(&self.app_log_path, &format!("{action} via {source}"));
+        sync_app_nap(self, Instant::now());
+        self.today_summary()
+    }
+
+    /// Pause or resume shortcut collection; key counts and typing time are not affected.
+    pub(crate) fn set_shortcuts_paused_from(
+        &mut self,
+        shortcuts_paused: bool,
+        source: &str,
+    ) -> TodaySummary {
+        self.shortcuts_paused = shortcuts_paused;
+        let action = if shortcuts_paused {
+            "paused"
+        } else {
+            "resumed"
+        };
+        let _ = append_app_log(
+            &self.app_log_path,
+            &format!("shortcut collection {action} via {source}"),
+        );
+        self.today_summary()
+    }
+
     pub fn set_ignore_key_combos(&mut self, ignore_key_combos: bool) {
         self.ignore_key_combos = ignore_key_combos;
     }
 
+    /// Enable or disable aborted-chord tracking; disabling drops the pending attempt.
+    pub fn set_track_chord_aborts(&mut self, track_chord_aborts: bool) {
+        self.track_chord_aborts = track_chord_aborts;
+        if !track_chord_aborts {
+            self.chord_attempt = None;
+        }
+    }
+
+    /// Enable or disable the focused-field check; the cached result is dropped either way.
+    pub fn set_ax_sensitive_detection(&mut self, ax_sensitive_detection: bool) {
+        self.ax_sensitive_detection = ax_sensitive_detection;
+        self.sensitive_field = SensitiveFieldCache::default();
+    }
+
+    /// Enable or disable App Nap prevention and take or release the assertion right away.
+    pub fn set_prevent_app_nap(&mut self, prevent_app_nap: bool) {
+        self.prevent_app_nap = prevent_app_nap;
+        sync_app_nap(self, Instant::now());
+    }
+
+    /// Turn the status file on or off. Turning it on writes on the next tick; turning it off
+    /// removes the file so scripts do not keep reading frozen numbers.
+    pub fn set_write_status_file(&mut self, write_status_file: bool) {
+        self.write_status_file = write_status_file;
+        self.last_status_write = None;
+        self.last_status_file = None;
+        if !write_status_file {
+            let _ = std::fs::remove_file(&self.status_file_path);
+        }
+    }
+
+    /// Turn event chunk recording on or off. Turning it off closes the open chunk first so no
+    /// half-written chunk is left; existing chunks are kept in memory either way.
+    pub fn set_record_event_chunks(&mut self, record_event_chunks: bool) {
+        if !record_event_chunks {
+            finish_open_chunk(self);
+        }
+        self.record_event_chunks = record_event_chunks;
+    }
+
+    /// Apply new tick, flush, session gap and tray intervals to the running loops. The tick
+    /// and tray loops read theirs before each sleep, so a change lands within one old interval;
+    /// flushes and session boundaries use the new values from the next event.
+    pub(crate) fn set_timing_intervals(
+        &mut self,
+        collector_tick_interval: std::time::Duration,
+        flush_interval: std::time::Duration,
+        session_gap: std::time::Duration,
+        tray_update_interval: std::time::Duration,
+    ) {
+        self.collector_tick_interval = collector_tick_interval;
+        self.flush_interval = flush_interval;
+        self.session_gap = session_gap;
+        self.tray_update_interval = tray_update_interval;
+    }
+
+    /// Apply a new maintenance idle window and maximum deferral; queued jobs keep waiting and
+    /// are checked against the new values from the next tick.
+    pub(crate) fn set_maintenance_window(
+        &mut self,
+        idle: std::time::Duration,
+        max_defer: std::time::Duration,
+    ) {
+        self.maintenance.set_windows(idle, max_defer);
+    }
+
+    pub(crate) fn tray_update_interval(&self) -> std::time::Duration {
+        self.tray_update_interval
+    }
+
+    /// Record the outcome of the latest update check for the snapshot badge.
+    pub fn set_update_available(&mut self, update_available: bool) {
+        self.update_available = update_available;
+    }
+
     pub fn set_menu_bar_display_mode(&mut self, mode: MenuBarDisplayMode) {
         self.menu_bar_display_mode = mode;
     }
 
+    pub fn set_tray_left_click_action(&mut self, action: TrayLeftClickAction) {
+        self.tray_left_click_action = action;
+    }
+
+    pub fn set_start_hidden(&mut self, start_hidden: bool) {
+        self.start_hidden = start_hidden;
+    }
+
+    /// Display-only: applied when app summaries are built, the stats rows are left as they are.
+    pub fn set_min_keys_to_display(&mut self, min_keys: u64) {
+        self.min_keys_to_display = min_keys;
+    }
+
+    /// Takes effect with the next flush, which rewrites the whole CSV summary file.
+    pub fn set_csv_dialect(&mut self, dialect: CsvDialect) {
+        self.csv_dialect = dialect;
+    }
+
+    pub fn set_window_title_mode(&mut self, mode: WindowTitleMode) {
+        self.window_title_mode = mode;
+    }
+
+    pub fn set_app_title_privacy(&mut self, bundle_id: &str, mode: WindowTitleMode) {
+        let bundle_id = bundle_id.trim().to_ascii_lowercase();
+        if bundle_id.is_empty() {
+            return;
+        }
+        self.title_privacy_overrides.insert(bundle_id, mode);
+    }
+
+    pub fn remove_app_title_privacy(&mut self, bundle_id: &str) {
+        self.title_privacy_overrides
+            .remove(&bundle_id.trim().to_ascii_lowercase());
+    }
+
+    /// Per-app override map in config form (lowercase bundle id -> mode).
+    pub(crate) fn title_privacy_overrides(&self) -> HashMap<String, WindowTitleMode> {
+        self.title_privacy_overrides.clone()
+    }
+
+    /// Resolve the title mode for one app: per-app override first, then the global mode.
+    pub(crate) fn resolve_window_title_mode(&self, bundle_id: Option<&str>) -> WindowTitleMode {
+        bundle_id
+            .and_then(|bundle_id| {
+                self.title_privacy_overrides
+                    .get(&bundle_id.to_ascii_lowercase())
+            })
+            .copied()
+            .unwrap_or(self.window_title_mode)
+    }
+
+    /// Current tray icon left-click behavior.
+    pub(crate) fn tray_left_click_action(&self) -> TrayLeftClickAction {
+        self.tray_left_click_action
+    }
+
     /// Update shortcut counting rules used by runtime aggregation.
     pub fn set_shortcut_rules(
         &mut self,
@@ -85,24 +435,20 @@ impl CollectorState {
         self.shortcut_require_cmd_or_ctrl = require_cmd_or_ctrl;
         self.shortcut_allow_alt_only = allow_alt_only;
         self.shortcut_min_modifiers = min_modifiers.max(1);
-        self.shortcut_allowlist = allowlist
-            .iter()
-            .map(|v| v.trim().to_ascii_lowercase())
-            .filter(|v| !v.is_empty())
-            .collect();
-        self.shortcut_blocklist = blocklist
-            .iter()
-            .map(|v| v.trim().to_ascii_lowercase())
-            .filter(|v| !v.is_empty())
-            .collect();
+        self.shortcut_allowlist = canonical_shortcut_id_set(allowlist, self.symbol_key_names);
+        self.shortcut_blocklist = canonical_shortcut_id_set(blocklist, self.symbol_key_names);
     }
 
     pub fn set_excluded_bundle_ids(&mut self, bundle_ids: &[String]) {
-        self.excluded_bundle_ids = bundle_ids
-            .iter()
-            .map(|v| v.trim().to_ascii_lowercase())
-            .filter(|v| !v.is_empty())
-            .collect();
+        self.excluded_bundle_ids = normalize_bundle_list(bundle_ids).into_iter().collect();
+        self.apply_exclusion_change();
+    }
+
+    /// Excluded apps in canonical (sorted) order, as stored in config and snapshots.
+    pub(crate) fn excluded_bundle_ids(&self) -> Vec<String> {
+        let mut bundle_ids: Vec<String> = self.excluded_bundle_ids.iter().cloned().collect();
+        bundle_ids.sort();
+        bundle_ids
     }
 
     pub fn add_excluded_bundle_id(&mut self, bundle_id: &str) -> bool {
@@ -110,24 +456,210 @@ impl CollectorState {
         if normalized.is_empty() {
             return false;
         }
-        self.excluded_bundle_ids.insert(normalized)
+        let added = self.excluded_bundle_ids.insert(normalized);
+        if added {
+            self.apply_exclusion_change();
+        }
+        added
     }
 
     pub fn remove_excluded_bundle_id(&mut self, bundle_id: &str) -> bool {
-        self.excluded_bundle_ids
-            .remove(&bundle_id.trim().to_ascii_lowercase())
+        let removed = self
+            .excluded_bundle_ids
+            .remove(&bundle_id.trim().to_ascii_lowercase());
+        if removed {
+            self.apply_exclusion_change();
+        }
+        removed
+    }
+
+    // Apply an exclusion list change now instead of on the next key event or tick: re-check
+    // auto-pause against a fresh context, and when the open chunk belongs to an app that is now
+    // excluded, close it, save the analytics and drop the held keys.
+    fn apply_exclusion_change(&mut self) {
+        let capture_context = self.current_context();
+        self.auto_paused = is_auto_paused(self, &capture_context);
+        self.auto_pause_reason = auto_pause_reason(self, &capture_context);
+        if self.auto_paused {
+            reset_active_typing_state(self);
+        }
+        let chunk_app = self.open_event_chunk.as_ref().and_then(|chunk| {
+            self.app_ref_by_app
+                .iter()
+                .find(|(_, app_ref)| **app_ref == chunk.app_ref)
+                .map(|(app_id, _)| app_id.to_ascii_lowercase())
+        });
+        if chunk_app.is_some_and(|app_id| self.excluded_bundle_ids.contains(&app_id)) {
+            reset_active_typing_state(self);
+            // The closed chunk is saved with the history once the load is merged.
+            if self.history_loading() {
+                return;
+            }
+            let analytics = build_stored_input_analytics(self);
+            if let Err(err) = self.storage.save_input_analytics(&analytics) {
+                let _ = append_app_log(
+                    &self.app_log_path,
+                    &format!("failed to save analytics after exclusion change: {}", err),
+                );
+            }
+        }
+        sync_app_nap(self, Instant::now());
     }
 
     pub fn set_one_password_suggestion_pending(&mut self, pending: bool) {
         self.one_password_suggestion_pending = pending;
     }
 
+    /// Persist stats, input analytics and the CSV summary, returning the first failure. Demo
+    /// mode has no CSV summary.
+    pub fn flush_to_disk(&mut self) -> Result<(), String> {
+        self.flush_to_disk_with(true)
+    }
+
+    /// `flush_to_disk`, leaving the input analytics unsaved unless `save_analytics`; the
+    /// periodic flush defers large analytics saves to an idle moment this way.
+    pub(super) fn flush_to_disk_with(&mut self, save_analytics: bool) -> Result<(), String> {
+        self.ensure_history_loaded()?;
+        check_external_changes(self);
+        let stats_result = self.storage.save_stats(&self.stats);
+        refresh_recent_focus_days(self);
+        // Checkpoint the running span so the exit flush records when the app stopped.
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        record_runtime(self, now_ms);
+        prune_runtime_spans(self, now_ms);
+        let analytics_result = if save_analytics {
+            self.save_input_analytics()
+        } else {
+            Ok(())
+        };
+        if self.demo_mode {
+            return stats_result.and(analytics_result);
+        }
+        let csv_result = self
+            .snapshot_rows()
+            .and_then(|rows| write_csv(&self.log_path, &rows, self.csv_dialect));
+        stats_result.and(analytics_result).and(csv_result)
+    }
+
+    /// Persist the input analytics alone.
+    pub(super) fn save_input_analytics(&mut self) -> Result<(), String> {
+        let analytics = build_stored_input_analytics(self);
+        self.storage.save_input_analytics(&analytics)
+    }
+
+    /// Days with stats older than `minute_resolution_days` that are not rolled up yet.
+    pub(super) fn rollup_candidate_days(&self) -> HashSet<NaiveDate> {
+        let cutoff =
+            (Local::now() - Duration::days(self.minute_resolution_days as i64)).date_naive();
+        self.stats
+            .keys()
+            .map(|key| key.date.day())
+            .filter(|day| *day < cutoff && !self.storage.is_rolled_up(*day))
+            .collect()
+    }
+
+    /// Roll up minute-level stats older than `minute_resolution_days` into hour rows and
+    /// persist them. Days already rolled up are skipped. Returns the number of days rewritten.
+    pub fn rollup_old_stats(&mut self) -> Result<usize, String> {
+        let dates = self.rollup_candidate_days();
+        if dates.is_empty() {
+            return Ok(0);
+        }
+        let stats = std::mem::take(&mut self.stats);
+        self.stats = rollup_stats_to_hours(stats, &dates);
+        let dates: Vec<NaiveDate> = dates.into_iter().collect();
+        self.storage.save_stats(&self.stats)?;
+        // Marked only once saved; a day marked without its hour rows would never be retried.
+        self.storage.mark_rolled_up(&dates);
+        Ok(dates.len())
+    }
+
+    /// Flush, then move the daily files of months before the previous one into monthly zip
+    /// archives. Loading reads the archives back, so this changes nothing in memory.
+    pub fn archive_old_months(&mut self) -> Result<ArchiveReport, String> {
+        self.flush_to_disk()?;
+        let today = Local::now().date_naive();
+        let Some(cutoff) = today
+            .with_day(1)
+            .and_then(|month_start| month_start.checked_sub_months(Months::new(1)))
+        else {
+            return Ok(ArchiveReport::default());
+        };
+        self.storage.archive_days_before(cutoff)
+    }
+
+    /// Flush the current data set, then load the one at `log_path` / `detail_path` with
+    /// `config` resolved for its profile. Pause, App Nap, the tick watchdog, keys held right
+    /// now and a running auto-switch cooldown belong to the process rather than to a data
+    /// set and carry over. Nothing
+    /// switches when the flush fails, so unsaved stats are never dropped.
+    pub(crate) fn switch_data_set(
+        &mut self,
+        log_path: PathBuf,
+        detail_path: PathBuf,
+        config: &AppConfig,
+    ) -> Result<(), String> {
+        if self.demo_mode {
+            return Err("profiles cannot be switched in demo mode".to_string());
+        }
+        self.flush_to_disk()?;
+        let app_log_path = self.app_log_path.clone();
+        let mut next = load_collector_state(
+            log_path,
+            app_log_path.clone(),
+            detail_path,
+            config,
+            self.shutdown.clone(),
+            &|message| {
+                let _ = append_app_log(&app_log_path, message);
+            },
+        );
+        next.paused = self.paused;
+        next.auto_paused = self.auto_paused;
+        next.auto_pause_reason = self.auto_pause_reason.take();
+        next.keyboard_active = self.keyboard_active;
+        next.update_available = self.update_available;
+        next.pressed_non_modifier_keys = std::mem::take(&mut self.pressed_non_modifier_keys);
+        std::mem::swap(&mut next.app_nap, &mut self.app_nap);
+        std::mem::swap(&mut next.tick_watchdog, &mut self.tick_watchdog);
+        std::mem::swap(&mut next.context_provider, &mut self.context_provider);
+        std::mem::swap(
+            &mut next.focused_field_provider,
+            &mut self.focused_field_provider,
+        );
+        next.profile_auto.suppressed_until = self.profile_auto.suppressed_until;
+        #[cfg(not(target_os = "macos"))]
+        std::mem::swap(&mut next.modifier_state, &mut self.modifier_state);
+        probe_storage(&mut next, Instant::now());
+        *self = next;
+        Ok(())
+    }
+
     /// Clear all collected stats and persist cleared payload back to storage.
     pub fn clear_stats(&mut self) {
         self.stats.clear();
+        self.today_totals = TodayTotals::default();
+        self.rolling_averages = RollingAverages::default();
         self.shortcut_usage.clear();
         self.event_chunks.clear();
+        self.chunk_counters = ChunkCounters::default();
         self.open_event_chunk = None;
+        self.chord_attempt = None;
+        self.chord_aborts.clear();
+        self.app_switches.clear();
+        self.focus_days.clear();
+        self.coverage.clear();
+        self.runtime_spans.clear();
+        self.burst_run = None;
+        self.burst_days.clear();
+        self.effort_days.clear();
+        self.display_days.clear();
+        self.pending_copy = None;
+        self.copy_paste_days.clear();
+        self.key_spans.clear();
+        // A load still running would bring the cleared history back when it merges.
+        self.history_load = None;
+        begin_runtime_span(self, chrono::Utc::now().timestamp_millis());
         let _ = self.storage.save_stats(&self.stats);
         let analytics = build_stored_input_analytics(self);
         let _ = self.storage.save_input_analytics(&analytics);
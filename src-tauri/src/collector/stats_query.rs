@@ -0,0 +1,171 @@
+//! Stats query module.
+//! Filtered, sorted and paged stats rows for browsing the whole history. Every stored day is
+//! in memory once the startup load has merged, so pages are cut from the live stats; while it
+//! is still running the page says so instead of reading the daily files a second time.
+
+use std::cmp::Ordering;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::{CollectorState, StatsKey, StatsRow, StatsValue, DAY_KEY_FORMAT};
+
+/// Rows per page when the caller does not ask for a size.
+pub(crate) const DEFAULT_STATS_PAGE_SIZE: usize = 100;
+/// Upper bound for a caller-provided page size.
+pub(super) const MAX_STATS_PAGE_SIZE: usize = 500;
+
+/// Which rows `snapshot_stats_row_page` returns; every field is optional.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct StatsRowFilter {
+    /// First local day, `YYYY-MM-DD`, inclusive.
+    pub from: Option<String>,
+    /// Last local day, `YYYY-MM-DD`, inclusive.
+    pub to: Option<String>,
+    /// App name, matched whole and case-insensitively.
+    pub app: Option<String>,
+    /// Case-insensitive substring of the window title.
+    pub title: Option<String>,
+}
+
+/// Stats row column to sort by.
+#[derive(Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsRowColumn {
+    #[default]
+    Date,
+    AppName,
+    WindowTitle,
+    ActiveTypingMs,
+    KeyCount,
+    SessionCount,
+    WritingMs,
+    NavigationMs,
+}
+
+/// Sort order of a stats row page. Rows that tie on the column keep date, app, title order,
+/// so a page boundary never splits or repeats rows that compare equal.
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+#[serde(default)]
+pub struct StatsRowSort {
+    pub column: StatsRowColumn,
+    pub descending: bool,
+}
+
+/// One page of matching stats rows and how many match in total.
+#[derive(Serialize, Clone)]
+pub struct StatsRowPage {
+    pub rows: Vec<StatsRow>,
+    pub total: u64,
+    /// Zero-based page index, as requested.
+    pub page: usize,
+    /// Page size after clamping to 1..=500.
+    pub page_size: usize,
+    /// The startup history load has not merged yet, so older days are still missing.
+    pub history_loading: bool,
+}
+
+fn parse_day(value: Option<&str>) -> Result<Option<NaiveDate>, String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            NaiveDate::parse_from_str(value, DAY_KEY_FORMAT)
+                .map_err(|_| format!("invalid date: {value}"))
+        })
+        .transpose()
+}
+
+fn compare_column(
+    column: StatsRowColumn,
+    (a_key, a_value): (&StatsKey, &StatsValue),
+    (b_key, b_value): (&StatsKey, &StatsValue),
+) -> Ordering {
+    match column {
+        StatsRowColumn::Date => a_key.date.cmp(&b_key.date),
+        StatsRowColumn::AppName => a_key.app_name.cmp(&b_key.app_name),
+        StatsRowColumn::WindowTitle => a_key.window_title.cmp(&b_key.window_title),
+        StatsRowColumn::ActiveTypingMs => a_value.active_typing_ms.cmp(&b_value.active_typing_ms),
+        StatsRowColumn::KeyCount => a_value.key_count.cmp(&b_value.key_count),
+        StatsRowColumn::SessionCount => a_value.session_count.cmp(&b_value.session_count),
+        StatsRowColumn::WritingMs => a_value.writing_ms.cmp(&b_value.writing_ms),
+        StatsRowColumn::NavigationMs => a_value.navigation_ms.cmp(&b_value.navigation_ms),
+    }
+}
+
+/// Page `page` (zero-based) of the stats rows matching `filter`, sorted by `sort`. Sorting by
+/// date, app or title keeps earlier pages stable while typing adds rows; counters of rows
+/// still being typed into can move them between pages when sorted by a counter.
+pub fn snapshot_stats_row_page(
+    state: &CollectorState,
+    filter: &StatsRowFilter,
+    sort: StatsRowSort,
+    page: usize,
+    page_size: usize,
+) -> Result<StatsRowPage, String> {
+    let from = parse_day(filter.from.as_deref())?;
+    let to = parse_day(filter.to.as_deref())?;
+    let app = filter
+        .app
+        .as_deref()
+        .map(str::trim)
+        .filter(|app| !app.is_empty());
+    let title = filter
+        .title
+        .as_deref()
+        .map(|title| title.trim().to_lowercase())
+        .filter(|title| !title.is_empty());
+    let page_size = page_size.clamp(1, MAX_STATS_PAGE_SIZE);
+
+    let mut matches: Vec<(&StatsKey, &StatsValue)> = state
+        .stats
+        .iter()
+        .filter(|(key, _)| {
+            let day = key.date.day();
+            from.is_none_or(|from| day >= from)
+                && to.is_none_or(|to| day <= to)
+                && app.is_none_or(|app| key.app_name.eq_ignore_ascii_case(app))
+                && title
+                    .as_deref()
+                    .is_none_or(|title| key.window_title.to_lowercase().contains(title))
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        let column = compare_column(sort.column, *a, *b);
+        let column = if sort.descending {
+            column.reverse()
+        } else {
+            column
+        };
+        column.then_with(|| {
+            (&a.0.date, &a.0.app_name, &a.0.window_title).cmp(&(
+                &b.0.date,
+                &b.0.app_name,
+                &b.0.window_title,
+            ))
+        })
+    });
+    let rows = matches
+        .iter()
+        .skip(page.saturating_mul(page_size))
+        .take(page_size)
+        .map(|(key, value)| StatsRow {
+            date: key.date,
+            app_name: key.app_name.clone(),
+            window_title: key.window_title.clone(),
+            active_typing_ms: value.active_typing_ms,
+            key_count: value.key_count,
+            session_count: value.session_count,
+            writing_ms: value.writing_ms,
+            navigation_ms: value.navigation_ms,
+        })
+        .collect();
+    Ok(StatsRowPage {
+        rows,
+        total: matches.len() as u64,
+        page,
+        page_size,
+        history_loading: state.history_load.is_some(),
+    })
+}
@@ -0,0 +1,72 @@
+//! Stats rows module.
+//! Range-bounded stats rows and per-day totals, served by their own commands so the snapshot
+//! polled every second can carry only today's rows and the top shortcuts.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{Duration as ChronoDuration, Local, NaiveDate};
+use serde::Serialize;
+
+use super::key_span::key_span_on;
+use super::shortcut::{days_in_window, shortcut_range_window_ms};
+use super::{CollectorState, StatsRow, DAY_KEY_FORMAT};
+
+/// Shortcut rows embedded in a v2 snapshot, most used first.
+pub(super) const SNAPSHOT_SHORTCUT_LIMIT: usize = 50;
+/// Days `snapshot_daily_totals` covers when the caller gives none: enough for the three
+/// months of the contribution calendar.
+pub(crate) const DEFAULT_DAILY_TOTAL_DAYS: usize = 120;
+const MAX_DAILY_TOTAL_DAYS: usize = 366;
+
+/// Key count, active typing time and sessions of one local day.
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DailyTotal {
+    /// Local day, `YYYY-MM-DD`.
+    pub date: String,
+    pub key_count: u64,
+    pub active_typing_ms: u64,
+    pub session_count: u64,
+    /// Wall-clock epoch ms of the day's first and last counted key-down; None for days
+    /// recorded before these were tracked.
+    pub day_first_key_ms: Option<i64>,
+    pub day_last_key_ms: Option<i64>,
+}
+
+/// Stats rows of a range: `today` / `yesterday` / `7d`, sorted like `snapshot_rows`.
+pub fn snapshot_stats_rows(state: &CollectorState, range: &str) -> Vec<StatsRow> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let days: HashSet<NaiveDate> = days_in_window(start_ms, end_ms)
+        .iter()
+        .filter_map(|day| NaiveDate::parse_from_str(day, DAY_KEY_FORMAT).ok())
+        .collect();
+    state.rows_on_days(|day| days.contains(&day))
+}
+
+/// Totals of each day with stats among the last `days` local days (today included, clamped
+/// to 1..=366), oldest first.
+pub fn snapshot_daily_totals(state: &CollectorState, days: usize) -> Vec<DailyTotal> {
+    let days = days.clamp(1, MAX_DAILY_TOTAL_DAYS);
+    let today = Local::now().date_naive();
+    let first_day = today - ChronoDuration::days(days as i64 - 1);
+    let mut totals: BTreeMap<NaiveDate, DailyTotal> = BTreeMap::new();
+    for (key, value) in &state.stats {
+        let day = key.date.day();
+        if day < first_day || day > today {
+            continue;
+        }
+        let total = totals.entry(day).or_insert_with(|| DailyTotal {
+            date: day.format(DAY_KEY_FORMAT).to_string(),
+            ..DailyTotal::default()
+        });
+        total.key_count += value.key_count;
+        total.active_typing_ms += value.active_typing_ms;
+        total.session_count += value.session_count;
+    }
+    for (day, total) in &mut totals {
+        let span = key_span_on(state, *day);
+        total.day_first_key_ms = span.map(|span| span.first_ms);
+        total.day_last_key_ms = span.map(|span| span.last_ms);
+    }
+    totals.into_values().collect()
+}
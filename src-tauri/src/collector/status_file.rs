@@ -0,0 +1,69 @@
+//! Status file module.
+//! Keeps `status.json` in the data directory current for scripts that would rather read a
+//! file than talk to the app: today's totals, pause flags and the writing app's version.
+
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+use crate::storage::{save_status_file, StoredStatusFile};
+
+use super::{append_app_log, CollectorState, DAY_KEY_FORMAT};
+
+/// Status file next to the detail files.
+pub(super) const STATUS_FILE: &str = "status.json";
+/// Shape version of `StoredStatusFile`. Adding a field keeps it; renaming, removing or
+/// changing the meaning of one bumps it.
+const STATUS_FILE_VERSION: u32 = 1;
+/// Shortest gap between two writes, whatever the tick or tray interval.
+pub(super) const STATUS_FILE_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+// Current status; `updated_at_ms` is the write time.
+fn build_status_file(state: &CollectorState, now_ms: i64) -> StoredStatusFile {
+    let summary = state.today_summary();
+    StoredStatusFile {
+        v: STATUS_FILE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        updated_at_ms: now_ms,
+        date: Local::now().date_naive().format(DAY_KEY_FORMAT).to_string(),
+        keys: summary.key_count,
+        active_ms: summary.active_typing_ms,
+        paused: summary.paused,
+        auto_paused: summary.auto_paused,
+    }
+}
+
+/// Tick hook: rewrite the status file when it is enabled, at most once per
+/// `STATUS_FILE_MIN_INTERVAL`, and only when something other than the timestamp changed.
+pub(super) fn write_status_file_if_due(state: &mut CollectorState, now: Instant, now_ms: i64) {
+    if !state.write_status_file {
+        return;
+    }
+    if state
+        .last_status_write
+        .is_some_and(|at| now.saturating_duration_since(at) < STATUS_FILE_MIN_INTERVAL)
+    {
+        return;
+    }
+    let status = build_status_file(state, now_ms);
+    let unchanged = state.last_status_file.as_ref().is_some_and(|last| {
+        *last
+            == StoredStatusFile {
+                updated_at_ms: last.updated_at_ms,
+                ..status.clone()
+            }
+    });
+    state.last_status_write = Some(now);
+    if unchanged {
+        return;
+    }
+    match save_status_file(&state.status_file_path, &status) {
+        Ok(()) => state.last_status_file = Some(status),
+        Err(err) => {
+            let _ = append_app_log(
+                &state.app_log_path,
+                &format!("status file write failed: {err}"),
+            );
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Storage health module.
+//! Tracks failed flushes so the tick loop retries with backoff instead of silently dropping
+//! save errors, and reports a degraded state once the data directory stays unwritable.
+//! `flush_now` saves outside the interval, on quit, on pause and on request.
+//! Probes the data directory itself so a missing or read-only volume shows up right away.
+//! Before each flush, daily files deleted or edited outside the app are logged and flagged.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::app_log::append_app_log;
+use super::maintenance::defer_analytics_save;
+use super::CollectorState;
+
+/// First retry delay after a failed flush; doubles per consecutive failure.
+const STORAGE_RETRY_BASE: Duration = Duration::from_secs(5);
+/// Upper bound of the retry delay.
+const STORAGE_RETRY_MAX: Duration = Duration::from_secs(5 * 60);
+/// Saves failing for this long mark storage as degraded and notify the user once.
+pub(super) const STORAGE_DEGRADED_AFTER: Duration = Duration::from_secs(10 * 60);
+/// How often the tick loop checks that the data directory exists and accepts writes.
+const STORAGE_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+const STORAGE_PROBE_FILE: &str = ".write-probe.tmp";
+/// Prefix of the error recorded while the data directory is unavailable.
+const STORAGE_UNAVAILABLE_PREFIX: &str = "storage unavailable: ";
+
+/// Failure bookkeeping of the periodic flush.
+#[derive(Default)]
+pub(crate) struct StorageHealth {
+    consecutive_failures: u32,
+    failing_since: Option<Instant>,
+    retry_at: Option<Instant>,
+    last_error: Option<String>,
+    degraded: bool,
+    degraded_announced: bool,
+}
+
+impl StorageHealth {
+    /// Whether saves have kept failing for longer than `STORAGE_DEGRADED_AFTER`.
+    pub(crate) fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    // A failed save is retried once its backoff elapsed, ahead of the regular interval.
+    fn flush_due(&self, now: Instant, last_flush: Instant, interval: Duration) -> bool {
+        match self.retry_at {
+            Some(retry_at) => now >= retry_at,
+            None => now.duration_since(last_flush) >= interval,
+        }
+    }
+
+    fn backoff(failures: u32) -> Duration {
+        let shift = failures.saturating_sub(1).min(16);
+        STORAGE_RETRY_BASE
+            .saturating_mul(1 << shift)
+            .min(STORAGE_RETRY_MAX)
+    }
+}
+
+// Flush when the interval (or a pending retry) is due and return whether a flush ran. Stats
+// stay in memory until a save succeeds, so a failing window only delays persistence. No flush
+// runs while the data directory probe fails, so nothing is created under a missing volume's
+// mount point, or while the stored history is still loading.
+pub(super) fn flush_if_due(state: &mut CollectorState, now: Instant) -> bool {
+    if !state.storage_writable
+        || state.history_loading()
+        || !state
+            .storage_health
+            .flush_due(now, state.last_flush_instant, state.flush_interval)
+    {
+        return false;
+    }
+    flush_periodic(state, now);
+    true
+}
+
+// Large analytics saves wait for an idle moment here; see `defer_analytics_save`.
+fn flush_periodic(state: &mut CollectorState, now: Instant) {
+    let save_analytics = !defer_analytics_save(state, now);
+    let _ = flush_now_with(state, now, save_analytics);
+}
+
+fn flush_now_with(
+    state: &mut CollectorState,
+    now: Instant,
+    save_analytics: bool,
+) -> Result<(), String> {
+    state.last_flush_instant = now;
+    let result = state.flush_to_disk_with(save_analytics);
+    match &result {
+        Ok(()) => record_flush_success(state, now),
+        Err(err) => record_flush_failure(state, now, err.clone()),
+    }
+    result
+}
+
+/// Flush stats, input analytics and the CSV summary right away, outside the regular interval:
+/// on quit, on pause and on request before an export. The open event chunk is finished so its
+/// events reach the analytics file. Failures count towards storage health like those of the
+/// periodic flush; nothing is written while the data directory probe fails or the stored
+/// history is still loading.
+pub fn flush_now(state: &mut CollectorState) -> Result<(), String> {
+    if !state.storage_writable {
+        return Err("data directory is unavailable".to_string());
+    }
+    state.ensure_history_loaded()?;
+    flush_now_with(state, Instant::now(), true)
+}
+
+// Check that `dir` exists and accepts a tiny write. The directory is never created here.
+// Log daily files deleted or edited outside the app since the last flush and flag them in the
+// snapshot. Saves only write days that changed in memory, so such files are left as they are.
+pub(super) fn check_external_changes(state: &mut CollectorState) {
+    let changes = state.storage.take_external_changes();
+    if changes.is_empty() {
+        return;
+    }
+    state.external_changes_detected = true;
+    let names = |paths: &[PathBuf]| {
+        paths
+            .iter()
+            .map(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.display().to_string())
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut message = "data files changed outside TypePulse:".to_string();
+    if !changes.deleted.is_empty() {
+        message.push_str(&format!(" deleted [{}]", names(&changes.deleted)));
+    }
+    if !changes.modified.is_empty() {
+        message.push_str(&format!(" modified [{}]", names(&changes.modified)));
+    }
+    let _ = append_app_log(&state.app_log_path, &message);
+}
+
+fn probe_data_dir(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("data directory {} is missing", dir.display()));
+    }
+    let probe_path = dir.join(STORAGE_PROBE_FILE);
+    std::fs::write(&probe_path, b"ok")
+        .map_err(|err| format!("data directory {} is not writable: {err}", dir.display()))?;
+    let _ = std::fs::remove_file(&probe_path);
+    Ok(())
+}
+
+// Probe the data directory and record when it becomes unavailable or comes back. Coming back
+// flushes at once so everything kept in memory meanwhile reaches disk.
+pub(super) fn probe_storage(state: &mut CollectorState, now: Instant) {
+    state.last_storage_probe = now;
+    // Demo mode writes nothing, so there is no data directory to probe.
+    if state.demo_mode {
+        return;
+    }
+    let dir = match state.log_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => Path::new(".").to_path_buf(),
+    };
+    match (state.storage_writable, probe_data_dir(&dir)) {
+        (true, Err(err)) => {
+            state.storage_writable = false;
+            let error = format!(
+                "{STORAGE_UNAVAILABLE_PREFIX}{err}; stats are kept in memory until it is back"
+            );
+            let _ = append_app_log(&state.app_log_path, &error);
+            state.last_error = Some(error);
+        }
+        (false, Ok(())) => {
+            state.storage_writable = true;
+            if state
+                .last_error
+                .as_deref()
+                .is_some_and(|error| error.starts_with(STORAGE_UNAVAILABLE_PREFIX))
+            {
+                state.last_error = None;
+            }
+            let _ = append_app_log(
+                &state.app_log_path,
+                "data directory writable again, flushing stats kept in memory",
+            );
+            flush_periodic(state, now);
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn probe_storage_if_due(state: &mut CollectorState, now: Instant) {
+    if now.duration_since(state.last_storage_probe) >= STORAGE_PROBE_INTERVAL {
+        probe_storage(state, now);
+    }
+}
+
+fn record_flush_success(state: &mut CollectorState, now: Instant) {
+    let health = &mut state.storage_health;
+    if health.consecutive_failures == 0 {
+        return;
+    }
+    let failed_for = health
+        .failing_since
+        .map(|since| now.duration_since(since))
+        .unwrap_or_default();
+    let message = format!(
+        "storage save recovered after {} failed attempts ({}s)",
+        health.consecutive_failures,
+        failed_for.as_secs()
+    );
+    if state.last_error.is_some() && state.last_error == health.last_error {
+        state.last_error = None;
+    }
+    state.storage_health = StorageHealth::default();
+    let _ = append_app_log(&state.app_log_path, &message);
+}
+
+fn record_flush_failure(state: &mut CollectorState, now: Instant, err: String) {
+    let health = &mut state.storage_health;
+    health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+    let failing_since = *health.failing_since.get_or_insert(now);
+    let delay = StorageHealth::backoff(health.consecutive_failures);
+    health.retry_at = Some(now + delay);
+    let error = format!("storage save failed: {err}");
+    health.last_error = Some(error.clone());
+    let newly_degraded =
+        !health.degraded && now.duration_since(failing_since) >= STORAGE_DEGRADED_AFTER;
+    health.degraded |= newly_degraded;
+    let attempts = health.consecutive_failures;
+    state.last_error = Some(error.clone());
+    let _ = append_app_log(
+        &state.app_log_path,
+        &format!(
+            "{error} (attempt {attempts}, retry in {}s)",
+            delay.as_secs()
+        ),
+    );
+    if newly_degraded {
+        let _ = append_app_log(&state.app_log_path, "storage marked degraded");
+    }
+}
+
+impl CollectorState {
+    /// The latest save error, returned once per degraded episode so the caller can notify
+    /// the user.
+    pub(crate) fn take_storage_degraded_announcement(&mut self) -> Option<String> {
+        let health = &mut self.storage_health;
+        if !health.degraded || health.degraded_announced {
+            return None;
+        }
+        health.degraded_announced = true;
+        health.last_error.clone()
+    }
+}
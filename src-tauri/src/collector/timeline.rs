@@ -0,0 +1,144 @@
+//! App timeline module.
+//! Buckets one local day of minute-keyed stats rows into per-app activity strips.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::Serialize;
+
+use super::{CollectorState, StatsKey, StatsValue};
+
+/// Apps with fewer keys than this over the whole day are left out to keep the payload bounded.
+const TIMELINE_MIN_APP_KEY_COUNT: u64 = 10;
+const MINUTE_MS: i64 = 60_000;
+const DAY_MINUTES: u32 = 24 * 60;
+
+/// One non-empty bucket of an app strip.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct AppTimelineBucket {
+    pub bucket_start_ms: i64,
+    pub key_count: u64,
+    pub active_ms: u64,
+}
+
+/// Activity strip for one app, buckets sorted by start time.
+#[derive(Serialize, Clone, Debug)]
+pub struct AppTimelineRow {
+    pub app_name: String,
+    pub key_count: u64,
+    pub active_ms: u64,
+    pub buckets: Vec<AppTimelineBucket>,
+}
+
+/// Timeline payload; bucket size and day bounds are echoed so empty buckets can be omitted.
+#[derive(Serialize, Clone, Debug)]
+pub struct AppTimeline {
+    pub date: String,
+    pub bucket_minutes: u32,
+    pub day_start_ms: i64,
+    pub day_end_ms: i64,
+    pub apps: Vec<AppTimelineRow>,
+}
+
+// Resolve a local wall-clock time to epoch millis, picking the earliest instant on DST overlap.
+pub(super) fn local_timestamp_ms(naive: &NaiveDateTime) -> Option<i64> {
+    Local
+        .from_local_datetime(naive)
+        .single()
+        .or_else(|| Local.from_local_datetime(naive).earliest())
+        .or_else(|| Local.from_local_datetime(naive).latest())
+        .map(|value| value.timestamp_millis())
+}
+
+/// Bucket stats rows of `date` (`YYYY-MM-DD`) into per-app strips aligned to local midnight.
+/// Minute rows already match the smallest bucket, so event chunks are not consulted; days
+/// rolled up to hours land in the bucket containing the hour start.
+pub(super) fn build_app_timeline(
+    stats: &HashMap<StatsKey, StatsValue>,
+    date: &str,
+    bucket_minutes: u32,
+) -> Result<AppTimeline, String> {
+    if bucket_minutes == 0 || bucket_minutes > DAY_MINUTES {
+        return Err(format!(
+            "bucket_minutes must be between 1 and {DAY_MINUTES}"
+        ));
+    }
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    let next_day = day
+        .succ_opt()
+        .ok_or_else(|| "date out of range".to_string())?;
+    let day_start_ms = day
+        .and_hms_opt(0, 0, 0)
+        .as_ref()
+        .and_then(local_timestamp_ms)
+        .ok_or_else(|| "invalid local midnight".to_string())?;
+    let day_end_ms = next_day
+        .and_hms_opt(0, 0, 0)
+        .as_ref()
+        .and_then(local_timestamp_ms)
+        .ok_or_else(|| "invalid local midnight".to_string())?;
+    let bucket_ms = i64::from(bucket_minutes) * MINUTE_MS;
+
+    let mut by_app: HashMap<&str, BTreeMap<i64, AppTimelineBucket>> = HashMap::new();
+    for (key, value) in stats {
+        if key.date.day() != day {
+            continue;
+        }
+        let Some(row_ms) = local_timestamp_ms(&key.date.to_naive_datetime()) else {
+            continue;
+        };
+        if row_ms < day_start_ms || row_ms >= day_end_ms {
+            continue;
+        }
+        let bucket_start_ms = day_start_ms + (row_ms - day_start_ms) / bucket_ms * bucket_ms;
+        let bucket = by_app
+            .entry(key.app_name.as_str())
+            .or_default()
+            .entry(bucket_start_ms)
+            .or_insert(AppTimelineBucket {
+                bucket_start_ms,
+                key_count: 0,
+                active_ms: 0,
+            });
+        bucket.key_count += value.key_count;
+        bucket.active_ms += value.active_typing_ms;
+    }
+
+    let mut apps: Vec<AppTimelineRow> = by_app
+        .into_iter()
+        .map(|(app_name, buckets)| {
+            let buckets: Vec<AppTimelineBucket> = buckets
+                .into_values()
+                .filter(|bucket| bucket.key_count > 0 || bucket.active_ms > 0)
+                .collect();
+            AppTimelineRow {
+                app_name: app_name.to_string(),
+                key_count: buckets.iter().map(|bucket| bucket.key_count).sum(),
+                active_ms: buckets.iter().map(|bucket| bucket.active_ms).sum(),
+                buckets,
+            }
+        })
+        .filter(|row| row.key_count >= TIMELINE_MIN_APP_KEY_COUNT)
+        .collect();
+    apps.sort_by(|a, b| {
+        b.key_count
+            .cmp(&a.key_count)
+            .then_with(|| a.app_name.cmp(&b.app_name))
+    });
+    Ok(AppTimeline {
+        date: date.to_string(),
+        bucket_minutes,
+        day_start_ms,
+        day_end_ms,
+        apps,
+    })
+}
+
+/// Build the app timeline for one local day from the collector's in-memory stats.
+pub fn snapshot_app_timeline(
+    state: &CollectorState,
+    date: &str,
+    bucket_minutes: u32,
+) -> Result<AppTimeline, String> {
+    build_app_timeline(&state.stats, date, bucket_minutes)
+}
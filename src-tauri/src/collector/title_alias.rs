@@ -0,0 +1,127 @@
+//! Window title alias module.
+//! Merges window-title groups of one app into a single title: recorded rows are rewritten and
+//! summed where they collide, and an alias table rewrites future captures at key time.
+
+use std::collections::{HashMap, HashSet};
+
+use super::state_api::compute_today_totals;
+use super::{append_app_log, CollectorState, StatsKey, StatsValue};
+
+// Alias tables are keyed by lowercase app id, like the other per-app settings.
+fn app_key(app_id: &str) -> String {
+    app_id.trim().to_ascii_lowercase()
+}
+
+/// Title a capture of `window_title` in `app_id` is recorded under.
+pub(super) fn alias_window_title(
+    state: &CollectorState,
+    app_id: &str,
+    window_title: String,
+) -> String {
+    if state.window_title_aliases.is_empty() {
+        return window_title;
+    }
+    state
+        .window_title_aliases
+        .get(&app_key(app_id))
+        .and_then(|aliases| aliases.get(&window_title))
+        .cloned()
+        .unwrap_or(window_title)
+}
+
+fn add_value(into: &mut StatsValue, value: &StatsValue) {
+    into.active_typing_ms += value.active_typing_ms;
+    into.key_count += value.key_count;
+    into.session_count += value.session_count;
+    into.writing_ms += value.writing_ms;
+    into.navigation_ms += value.navigation_ms;
+}
+
+impl CollectorState {
+    /// Merge the window titles `from_titles` of one app into `to_title`: rewrite its stats rows
+    /// (summing rows that collide with an existing `to_title` row), record the aliases so later
+    /// captures are rewritten too, and rewrite the affected daily files. Returns the number of
+    /// rows rewritten.
+    pub fn merge_window_titles(
+        &mut self,
+        bundle_id: &str,
+        from_titles: &[String],
+        to_title: &str,
+    ) -> Result<u64, String> {
+        let app = app_key(bundle_id);
+        if app.is_empty() {
+            return Err("bundle id is empty".to_string());
+        }
+        self.ensure_history_loaded()?;
+        let aliases = self.window_title_aliases.entry(app.clone()).or_default();
+        // Merging into a title that was itself merged away continues to its target.
+        let to_title = aliases
+            .get(to_title)
+            .cloned()
+            .unwrap_or_else(|| to_title.to_string());
+        let from: HashSet<&str> = from_titles
+            .iter()
+            .map(String::as_str)
+            .filter(|title| *title != to_title)
+            .collect();
+        for target in aliases.values_mut() {
+            if from.contains(target.as_str()) {
+                *target = to_title.clone();
+            }
+        }
+        for title in &from {
+            aliases.insert(title.to_string(), to_title.clone());
+        }
+        if aliases.is_empty() {
+            self.window_title_aliases.remove(&app);
+        }
+
+        let mut rewritten = 0;
+        let stats = std::mem::take(&mut self.stats);
+        let mut merged: HashMap<StatsKey, StatsValue> = HashMap::with_capacity(stats.len());
+        for (key, value) in stats {
+            let key = if app_key(&key.app_name) == app && from.contains(key.window_title.as_str()) {
+                rewritten += 1;
+                StatsKey {
+                    window_title: to_title.clone(),
+                    ..key
+                }
+            } else {
+                key
+            };
+            match merged.get_mut(&key) {
+                Some(existing) => add_value(existing, &value),
+                None => {
+                    merged.insert(key, value);
+                }
+            }
+        }
+        self.stats = merged;
+        if let Some(active) = self.active_stats_key.as_mut() {
+            if app_key(&active.app_name) == app && from.contains(active.window_title.as_str()) {
+                active.window_title = to_title.clone();
+            }
+        }
+        if let Some(day) = self.today_totals.date {
+            self.today_totals = compute_today_totals(&self.stats, day);
+        }
+        let _ = append_app_log(
+            &self.app_log_path,
+            &format!(
+                "window titles merged: app={} titles={} rows={}",
+                app,
+                from.len(),
+                rewritten
+            ),
+        );
+        if rewritten > 0 {
+            self.flush_to_disk()?;
+        }
+        Ok(rewritten)
+    }
+
+    /// Window title aliases in config form (lowercase app id -> merged title -> target title).
+    pub(crate) fn window_title_aliases(&self) -> HashMap<String, HashMap<String, String>> {
+        self.window_title_aliases.clone()
+    }
+}
@@ -0,0 +1,117 @@
+//! Today JSON module.
+//! Small, versioned summary of today's numbers for launcher scripts (Raycast, Alfred), served
+//! by the `get_today_summary_json` command and the `--today-json` CLI flag.
+
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+
+use crate::app_config::AppConfig;
+use crate::shutdown::Shutdown;
+
+use super::app_goal::goal_progress;
+use super::key_span::key_span_on;
+use super::shortcut::{local_day_window_ms, snapshot_shortcut_rows_in_window};
+use super::state_api::compute_today_totals;
+use super::{load_collector_state, AppGoalProgress, CollectorState};
+
+/// Shape version of `TodaySummaryJson`. Adding a field keeps it; renaming, removing or
+/// changing the meaning of one bumps it.
+pub(crate) const TODAY_JSON_VERSION: u32 = 1;
+
+/// Launch argument that prints today's summary JSON and exits without starting the app.
+pub(crate) const TODAY_JSON_ARG: &str = "--today-json";
+
+/// Today's numbers for scripts.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TodaySummaryJson {
+    pub v: u32,
+    /// Key presses counted today.
+    pub keys: u64,
+    /// Active typing time today in milliseconds.
+    pub active_ms: u64,
+    /// Typing sessions started today.
+    pub sessions: u64,
+    /// App id (bundle id, or name when there is none) with the most keys today.
+    pub top_app: Option<String>,
+    /// Most used shortcut id today, e.g. `cmd_c`, replayed from today's event chunks.
+    pub top_shortcut: Option<String>,
+    /// Manual or automatic pause of the running collector. None from the CLI, which reads the
+    /// saved files and cannot see the running app.
+    pub paused: Option<bool>,
+    pub goal_progress: Vec<AppGoalProgress>,
+    /// Epoch ms of today's first and last counted key-down; None before the first.
+    pub day_first_key_ms: Option<i64>,
+    pub day_last_key_ms: Option<i64>,
+}
+
+// Build the summary for `day` from the state's stats, event chunks and app goals.
+fn build_today_summary_json(
+    state: &CollectorState,
+    day: NaiveDate,
+    paused: Option<bool>,
+) -> TodaySummaryJson {
+    let totals = compute_today_totals(&state.stats, day);
+    let mut sessions = 0;
+    let mut app_keys: HashMap<&str, u64> = HashMap::new();
+    for (key, value) in &state.stats {
+        if key.date.day() == day {
+            sessions += value.session_count;
+            *app_keys.entry(key.app_name.as_str()).or_default() += value.key_count;
+        }
+    }
+    // Ties go to the smallest app id so the answer does not depend on hash order.
+    let top_app = app_keys
+        .into_iter()
+        .filter(|(_, keys)| *keys > 0)
+        .max_by(|(app_a, keys_a), (app_b, keys_b)| {
+            keys_a.cmp(keys_b).then_with(|| app_b.cmp(app_a))
+        })
+        .map(|(app, _)| app.to_string());
+    let top_shortcut = local_day_window_ms(day).and_then(|(start_ms, end_ms)| {
+        snapshot_shortcut_rows_in_window(state, start_ms, end_ms, None, 1)
+            .into_iter()
+            .next()
+            .map(|row| row.shortcut_id)
+    });
+    let key_span = key_span_on(state, day);
+    TodaySummaryJson {
+        v: TODAY_JSON_VERSION,
+        keys: totals.key_count,
+        active_ms: totals.active_typing_ms,
+        sessions,
+        top_app,
+        top_shortcut,
+        paused,
+        goal_progress: goal_progress(&state.app_time_goals, &totals),
+        day_first_key_ms: key_span.map(|span| span.first_ms),
+        day_last_key_ms: key_span.map(|span| span.last_ms),
+    }
+}
+
+impl CollectorState {
+    /// Today's summary JSON from the live state, pause state included.
+    pub fn today_summary_json(&self) -> TodaySummaryJson {
+        build_today_summary_json(
+            self,
+            Local::now().date_naive(),
+            Some(self.paused || self.auto_paused),
+        )
+    }
+}
+
+/// Today's summary JSON read straight from the saved files, for the `--today-json` CLI. Starts
+/// no listener or thread and writes nothing, so scripts can call it every few seconds; numbers
+/// lag the running app by up to one flush interval.
+pub fn load_today_summary_json(detail_path: PathBuf, config: &AppConfig) -> TodaySummaryJson {
+    let state = load_collector_state(
+        PathBuf::new(),
+        PathBuf::new(),
+        detail_path,
+        config,
+        Arc::new(Shutdown::default()),
+        &|_| {},
+    );
+    build_today_summary_json(&state, Local::now().date_naive(), None)
+}
@@ -0,0 +1,98 @@
+//! Top windows module.
+//! Per-title totals of one app over a range, for finding the documents that got the most
+//! typing. Titles are grouped as recorded, so rows captured with titles hidden by privacy mode
+//! share one group and are flagged in the response.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use super::shortcut::{days_in_window, shortcut_range_window_ms};
+use super::{CollectorState, DAY_KEY_FORMAT};
+
+const DEFAULT_TOP_WINDOWS_LIMIT: usize = 10;
+const MAX_TOP_WINDOWS_LIMIT: usize = 100;
+
+/// Totals of one window title of the app over the range.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TopWindowRow {
+    pub window_title: String,
+    /// Keys recorded while window titles were hidden for the app; `window_title` is empty.
+    pub title_hidden: bool,
+    pub key_count: u64,
+    pub active_typing_ms: u64,
+    /// Latest day (`YYYY-MM-DD`) in the range with keys under this title.
+    pub last_seen_date: String,
+}
+
+/// Most typed window titles of one app, most keys first.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TopWindows {
+    pub bundle_id: String,
+    pub range: String,
+    pub windows: Vec<TopWindowRow>,
+    /// Titles with keys in the range, including those past the limit.
+    pub window_count: u64,
+}
+
+/// Resolve a caller-provided row limit: default when absent, clamped to 1..=100.
+pub(crate) fn top_windows_limit(requested: Option<usize>) -> usize {
+    requested
+        .unwrap_or(DEFAULT_TOP_WINDOWS_LIMIT)
+        .clamp(1, MAX_TOP_WINDOWS_LIMIT)
+}
+
+/// Build the `limit` most typed window titles of `bundle_id` by range: `today` / `yesterday` /
+/// `7d`. The app is matched case-insensitively, like the other per-app queries.
+pub fn snapshot_top_windows(
+    state: &CollectorState,
+    bundle_id: &str,
+    range: &str,
+    limit: usize,
+) -> TopWindows {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let days: Vec<NaiveDate> = days_in_window(start_ms, end_ms)
+        .iter()
+        .filter_map(|day| NaiveDate::parse_from_str(day, DAY_KEY_FORMAT).ok())
+        .collect();
+    let mut totals: HashMap<&str, (u64, u64, NaiveDate)> = HashMap::new();
+    for (key, value) in &state.stats {
+        let day = key.date.day();
+        if !days.contains(&day) || !key.app_name.eq_ignore_ascii_case(bundle_id) {
+            continue;
+        }
+        let entry = totals
+            .entry(key.window_title.as_str())
+            .or_insert((0, 0, day));
+        entry.0 += value.key_count;
+        entry.1 += value.active_typing_ms;
+        entry.2 = entry.2.max(day);
+    }
+    let window_count = totals.len() as u64;
+    let mut windows: Vec<TopWindowRow> = totals
+        .into_iter()
+        .map(
+            |(window_title, (key_count, active_typing_ms, last_seen))| TopWindowRow {
+                window_title: window_title.to_string(),
+                title_hidden: window_title.is_empty(),
+                key_count,
+                active_typing_ms,
+                last_seen_date: last_seen.format(DAY_KEY_FORMAT).to_string(),
+            },
+        )
+        .collect();
+    windows.sort_by(|a, b| {
+        b.key_count
+            .cmp(&a.key_count)
+            .then_with(|| a.window_title.cmp(&b.window_title))
+    });
+    windows.truncate(limit);
+    TopWindows {
+        bundle_id: bundle_id.to_string(),
+        range: range.to_string(),
+        windows,
+        window_count,
+    }
+}
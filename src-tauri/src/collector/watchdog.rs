@@ -0,0 +1,124 @@
+//! Tick watchdog module.
+//! Restarts the tick/flush loop once its heartbeat goes stale, so a dead tick thread cannot
+//! silently stop active time, flushing and auto-pause while key counting goes on.
+
+use std::{
+    any::Any,
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::shutdown::Shutdown;
+
+use super::app_log::append_app_log;
+use super::{start_tick_loop, CollectorState};
+
+/// How often the watchdog looks at the heartbeat; also the shortest staleness it reports.
+const WATCHDOG_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// A heartbeat is stale after this many missed ticks when ticks are slower than the check.
+const STALE_TICK_MULTIPLIER: u32 = 3;
+
+/// Heartbeat of the running tick loop and the generation that identifies it.
+pub(crate) struct TickWatchdog {
+    heartbeat: Instant,
+    // Bumped on every restart; a loop whose generation is no longer current exits, so at
+    // most one loop ticks.
+    generation: u64,
+    stalled: bool,
+    check_interval: Duration,
+}
+
+impl TickWatchdog {
+    pub(super) fn new(now: Instant) -> Self {
+        Self {
+            heartbeat: now,
+            generation: 0,
+            stalled: false,
+            check_interval: WATCHDOG_CHECK_INTERVAL,
+        }
+    }
+
+    /// Whether the tick loop had to be restarted since launch.
+    pub(crate) fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    pub(super) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub(super) fn beat(&mut self, now: Instant) {
+        self.heartbeat = now;
+    }
+
+    fn is_stale(&self, now: Instant, tick_interval: Duration) -> bool {
+        let stale_after = self
+            .check_interval
+            .max(tick_interval.saturating_mul(STALE_TICK_MULTIPLIER));
+        now.saturating_duration_since(self.heartbeat) > stale_after
+    }
+}
+
+// Readable text of a caught panic payload.
+pub(super) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+// Record a stale heartbeat: log it, surface it as the last error, set the stalled flag and
+// retire the current loop generation. Returns whether a replacement loop should be spawned.
+pub(super) fn check_tick_heartbeat(state: &mut CollectorState, now: Instant) -> bool {
+    let tick_interval = state.collector_tick_interval;
+    let watchdog = &mut state.tick_watchdog;
+    if !watchdog.is_stale(now, tick_interval) {
+        return false;
+    }
+    let stale_for = now.saturating_duration_since(watchdog.heartbeat);
+    watchdog.generation += 1;
+    watchdog.stalled = true;
+    // The replacement gets a full interval to report in before it is judged.
+    watchdog.heartbeat = now;
+    let error = format!(
+        "tick loop stalled for {}s; restarting it",
+        stale_for.as_secs()
+    );
+    state.last_error = Some(error.clone());
+    let _ = append_app_log(&state.app_log_path, &error);
+    true
+}
+
+/// Spawn the watchdog of the tick loop. It sleeps on the shutdown signal between checks and,
+/// on the way out, joins the replacement loop it started, if any.
+pub fn start_tick_watchdog(state: Arc<Mutex<CollectorState>>) -> JoinHandle<()> {
+    let shutdown = match state.lock() {
+        Ok(locked) => locked.shutdown.clone(),
+        Err(_) => Arc::new(Shutdown::default()),
+    };
+    std::thread::spawn(move || {
+        let mut replacement: Option<JoinHandle<()>> = None;
+        loop {
+            let check_interval = match state.lock() {
+                Ok(locked) => locked.tick_watchdog.check_interval,
+                Err(_) => WATCHDOG_CHECK_INTERVAL,
+            };
+            if shutdown.wait_timeout(check_interval) {
+                break;
+            }
+            let restart = match state.lock() {
+                Ok(mut locked) => check_tick_heartbeat(&mut locked, Instant::now()),
+                Err(_) => false,
+            };
+            if restart {
+                // The retired loop, if still alive, exits on its next tick.
+                replacement = Some(start_tick_loop(state.clone()));
+            }
+        }
+        if let Some(handle) = replacement {
+            let _ = handle.join();
+        }
+    })
+}
@@ -0,0 +1,329 @@
+//! Weekly comparison module.
+//! Compares one Monday-to-Sunday week with the week before: keys, active typing time, top app
+//! and the shortcut that gained the most uses. The Monday notification and the weekly view
+//! share the summary text, which is built by a pure function from the comparison alone.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, Timelike};
+use serde::Serialize;
+
+use super::key_span::{key_span_on, local_minute_of_day};
+use super::shortcut::{local_day_window_ms, snapshot_shortcut_rows_in_window};
+use super::xlsx_export::rollup_app_days;
+use super::{CollectorState, StatsRow, DAY_KEY_FORMAT};
+
+// Local hour on Monday from which last week's comparison is announced.
+const WEEKLY_NOTIFY_HOUR: u32 = 9;
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+/// Totals of one Monday-to-Sunday week.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct WeekTotals {
+    /// Monday of the week, `YYYY-MM-DD`.
+    pub week_start: String,
+    pub key_count: u64,
+    pub active_typing_ms: u64,
+    /// App with the most keys in the week.
+    pub top_app: Option<String>,
+    /// Mean local time of the days' first and last key-down, in minutes since midnight, over
+    /// the week's days with a recorded span.
+    pub avg_day_start_min: Option<u32>,
+    pub avg_day_end_min: Option<u32>,
+}
+
+/// Shortcut whose use grew the most from the previous week.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ShortcutGain {
+    pub shortcut_id: String,
+    pub count: u64,
+    pub previous_count: u64,
+}
+
+/// One week against the week before it.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct WeeklyComparison {
+    pub week: WeekTotals,
+    pub previous_week: WeekTotals,
+    /// Key count change in percent; None when the previous week has no keys.
+    pub key_change_pct: Option<f64>,
+    pub top_shortcut_gain: Option<ShortcutGain>,
+    /// `weekly_comparison_text` of this comparison.
+    pub summary: String,
+}
+
+/// Monday of the week containing `day`.
+pub(super) fn week_start_of(day: NaiveDate) -> NaiveDate {
+    day - ChronoDuration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+// Week totals from the stats rows and shortcut counts replayed over the week's local window.
+fn week_totals(
+    state: &CollectorState,
+    rows: &[StatsRow],
+    week_start: NaiveDate,
+) -> (WeekTotals, HashMap<String, u64>) {
+    let week_end = week_start + ChronoDuration::days(7);
+    let week_rows: Vec<StatsRow> = rows
+        .iter()
+        .filter(|row| (week_start..week_end).contains(&row.date.day()))
+        .cloned()
+        .collect();
+    let mut totals = WeekTotals {
+        week_start: week_start.format(DAY_KEY_FORMAT).to_string(),
+        ..WeekTotals::default()
+    };
+    let mut keys_by_app: HashMap<String, u64> = HashMap::new();
+    for rollup in rollup_app_days(&week_rows) {
+        totals.key_count += rollup.key_count;
+        totals.active_typing_ms += rollup.active_typing_ms;
+        *keys_by_app.entry(rollup.app_name).or_insert(0) += rollup.key_count;
+    }
+    totals.top_app = keys_by_app
+        .into_iter()
+        .filter(|(_, key_count)| *key_count > 0)
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(app_name, _)| app_name);
+    let day_minutes: Vec<(u32, u32)> = (0..7)
+        .filter_map(|offset| key_span_on(state, week_start + ChronoDuration::days(offset)))
+        .filter_map(|span| {
+            Some((
+                local_minute_of_day(span.first_ms)?,
+                local_minute_of_day(span.last_ms)?,
+            ))
+        })
+        .collect();
+    if !day_minutes.is_empty() {
+        let days = day_minutes.len() as u32;
+        totals.avg_day_start_min =
+            Some(day_minutes.iter().map(|(start, _)| start).sum::<u32>() / days);
+        totals.avg_day_end_min = Some(day_minutes.iter().map(|(_, end)| end).sum::<u32>() / days);
+    }
+    let shortcuts = match (
+        local_day_window_ms(week_start),
+        local_day_window_ms(week_end - ChronoDuration::days(1)),
+    ) {
+        (Some((start_ms, _)), Some((_, end_ms))) => {
+            snapshot_shortcut_rows_in_window(state, start_ms, end_ms, None, 0)
+                .into_iter()
+                .map(|row| (row.shortcut_id, row.count))
+                .collect()
+        }
+        _ => HashMap::new(),
+    };
+    (totals, shortcuts)
+}
+
+/// Compare the week containing `week_start` (`YYYY-MM-DD`, last week when absent) with the
+/// week before it.
+pub fn snapshot_weekly_comparison(
+    state: &CollectorState,
+    week_start: Option<&str>,
+    localized: bool,
+) -> Result<WeeklyComparison, String> {
+    let day = match week_start {
+        Some(value) => NaiveDate::parse_from_str(value, DAY_KEY_FORMAT)
+            .map_err(|_| format!("invalid week start: {}", value))?,
+        None => Local::now().date_naive() - ChronoDuration::days(7),
+    };
+    compare_weeks(state, week_start_of(day), localized)
+}
+
+// Comparison of the week starting on Monday `week_start` with the week before it.
+fn compare_weeks(
+    state: &CollectorState,
+    week_start: NaiveDate,
+    localized: bool,
+) -> Result<WeeklyComparison, String> {
+    let rows = state.snapshot_rows()?;
+    let (week, shortcuts) = week_totals(state, &rows, week_start);
+    let (previous_week, previous_shortcuts) =
+        week_totals(state, &rows, week_start - ChronoDuration::days(7));
+    let key_change_pct = (previous_week.key_count > 0).then(|| {
+        (week.key_count as f64 - previous_week.key_count as f64) * 100.0
+            / previous_week.key_count as f64
+    });
+    let top_shortcut_gain = shortcuts
+        .into_iter()
+        .map(|(shortcut_id, count)| {
+            let previous_count = previous_shortcuts.get(&shortcut_id).copied().unwrap_or(0);
+            ShortcutGain {
+                shortcut_id,
+                count,
+                previous_count,
+            }
+        })
+        .filter(|gain| gain.count > gain.previous_count)
+        .max_by(|a, b| {
+            (a.count - a.previous_count)
+                .cmp(&(b.count - b.previous_count))
+                .then_with(|| b.shortcut_id.cmp(&a.shortcut_id))
+        });
+    let mut comparison = WeeklyComparison {
+        week,
+        previous_week,
+        key_change_pct,
+        top_shortcut_gain,
+        summary: String::new(),
+    };
+    comparison.summary = weekly_comparison_text(&comparison, localized);
+    Ok(comparison)
+}
+
+// `12345` as `12,345`.
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+fn hours(ms: u64) -> String {
+    format!("{:.1}", ms as f64 / MS_PER_HOUR)
+}
+
+// Minutes since midnight as `HH:MM`.
+fn clock(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Summary sentence of a comparison, in Chinese when `localized` and English otherwise.
+pub(super) fn weekly_comparison_text(comparison: &WeeklyComparison, localized: bool) -> String {
+    let week = &comparison.week;
+    let previous = &comparison.previous_week;
+    let change_pct = comparison.key_change_pct.map(|pct| pct.round() as i64);
+    let mut parts: Vec<String> = Vec::new();
+    if localized {
+        let change = match change_pct {
+            None => "前一周没有记录".to_string(),
+            Some(0) => "与前一周持平".to_string(),
+            Some(pct) if pct > 0 => format!("比前一周多 {}%", pct),
+            Some(pct) => format!("比前一周少 {}%", -pct),
+        };
+        parts.push(format!(
+            "{} 起的一周输入 {} 键，{}",
+            week.week_start,
+            group_thousands(week.key_count),
+            change
+        ));
+        parts.push(format!(
+            "活跃打字 {} 小时（前一周 {} 小时）",
+            hours(week.active_typing_ms),
+            hours(previous.active_typing_ms)
+        ));
+        if let (Some(start), Some(end)) = (week.avg_day_start_min, week.avg_day_end_min) {
+            parts.push(format!("平均 {} 开始、{} 结束", clock(start), clock(end)));
+        }
+        match (&previous.top_app, &week.top_app) {
+            (Some(before), Some(after)) if before == after => {
+                parts.push(format!("最常用应用仍是 {}", after));
+            }
+            (Some(before), Some(after)) => {
+                parts.push(format!("最常用应用从 {} 变为 {}", before, after));
+            }
+            (None, Some(after)) => parts.push(format!("最常用应用是 {}", after)),
+            _ => {}
+        }
+        if let Some(gain) = &comparison.top_shortcut_gain {
+            parts.push(format!(
+                "增长最多的快捷键是 {}（+{}）",
+                gain.shortcut_id,
+                gain.count - gain.previous_count
+            ));
+        }
+        format!("{}。", parts.join("；"))
+    } else {
+        let change = match change_pct {
+            None => "nothing recorded the week before".to_string(),
+            Some(0) => "the same as the week before".to_string(),
+            Some(pct) if pct > 0 => format!("{}% more than the week before", pct),
+            Some(pct) => format!("{}% fewer than the week before", -pct),
+        };
+        parts.push(format!(
+            "Week of {}: {} keys, {}",
+            week.week_start,
+            group_thousands(week.key_count),
+            change
+        ));
+        parts.push(format!(
+            "{} active typing hours ({} the week before)",
+            hours(week.active_typing_ms),
+            hours(previous.active_typing_ms)
+        ));
+        if let (Some(start), Some(end)) = (week.avg_day_start_min, week.avg_day_end_min) {
+            parts.push(format!(
+                "days ran {} to {} on average",
+                clock(start),
+                clock(end)
+            ));
+        }
+        match (&previous.top_app, &week.top_app) {
+            (Some(before), Some(after)) if before == after => {
+                parts.push(format!("top app still {}", after));
+            }
+            (Some(before), Some(after)) => {
+                parts.push(format!("top app changed from {} to {}", before, after));
+            }
+            (None, Some(after)) => parts.push(format!("top app {}", after)),
+            _ => {}
+        }
+        if let Some(gain) = &comparison.top_shortcut_gain {
+            parts.push(format!(
+                "biggest shortcut gainer {} (+{})",
+                gain.shortcut_id,
+                gain.count - gain.previous_count
+            ));
+        }
+        format!("{}.", parts.join("; "))
+    }
+}
+
+impl CollectorState {
+    pub fn set_weekly_comparison_notifications(&mut self, enabled: bool) {
+        self.weekly_comparison_notifications = enabled;
+    }
+
+    // Latest week announced, from the markers kept with the analytics.
+    fn last_announced_week(&self) -> Option<NaiveDate> {
+        self.weekly_comparisons
+            .values()
+            .filter_map(|week| NaiveDate::parse_from_str(week, DAY_KEY_FORMAT).ok())
+            .max()
+    }
+
+    /// Last week's comparison once per week, from Monday morning on, marked as announced on
+    /// the day it fires. Nothing is returned while history loads, while the notification is
+    /// turned off, or for two weeks without keys.
+    pub(crate) fn take_weekly_comparison_due(
+        &mut self,
+        now: DateTime<Local>,
+    ) -> Option<WeeklyComparison> {
+        if !self.weekly_comparison_notifications || self.history_loading() {
+            return None;
+        }
+        let today = now.date_naive();
+        let this_week = week_start_of(today);
+        if today == this_week && now.hour() < WEEKLY_NOTIFY_HOUR {
+            return None;
+        }
+        let last_week = this_week - ChronoDuration::days(7);
+        if self
+            .last_announced_week()
+            .is_some_and(|announced| announced >= last_week)
+        {
+            return None;
+        }
+        let comparison = compare_weeks(self, last_week, true).ok()?;
+        self.weekly_comparisons.insert(
+            today.format(DAY_KEY_FORMAT).to_string(),
+            comparison.week.week_start.clone(),
+        );
+        let has_keys = comparison.week.key_count > 0 || comparison.previous_week.key_count > 0;
+        has_keys.then_some(comparison)
+    }
+}
@@ -0,0 +1,372 @@
+//! Window apps module.
+//! Lists the running apps that show a window on Windows and Linux, for the exclusion picker.
+//! Apps are identified by their executable file name, lowercased (`code.exe`, `firefox`),
+//! which stands in for the bundle id these platforms do not have.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::context::RunningAppInfo;
+
+/// App id of an executable: its file name, lowercased. None for paths without a file name.
+pub(super) fn executable_app_id(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?.trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some(name.to_ascii_lowercase())
+}
+
+// Display name of an executable: the file name without extension, case kept.
+fn executable_name(path: &Path) -> Option<String> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(str::to_string)
+}
+
+// One entry per app id, sorted by id; several windows or processes of one app collapse.
+fn apps_from_paths(paths: impl IntoIterator<Item = std::path::PathBuf>) -> Vec<RunningAppInfo> {
+    let mut apps: BTreeMap<String, String> = BTreeMap::new();
+    for path in paths {
+        let Some(bundle_id) = executable_app_id(&path) else {
+            continue;
+        };
+        let name = executable_name(&path).unwrap_or_else(|| bundle_id.clone());
+        apps.entry(bundle_id).or_insert(name);
+    }
+    apps.into_iter()
+        .map(|(bundle_id, name)| RunningAppInfo { bundle_id, name })
+        .collect()
+}
+
+/// Running apps with a visible top-level window, deduplicated by app id and sorted by it.
+pub(super) fn window_running_apps() -> Vec<RunningAppInfo> {
+    apps_from_paths(platform::window_executables())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStringExt;
+    use std::path::PathBuf;
+
+    type Hwnd = *mut c_void;
+    type Handle = *mut c_void;
+
+    const GW_OWNER: u32 = 4;
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+    // Long enough for extended-length paths.
+    const MAX_IMAGE_PATH: usize = 32_768;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn EnumWindows(callback: extern "system" fn(Hwnd, isize) -> i32, param: isize) -> i32;
+        fn IsWindowVisible(hwnd: Hwnd) -> i32;
+        fn GetWindow(hwnd: Hwnd, cmd: u32) -> Hwnd;
+        fn GetWindowTextLengthW(hwnd: Hwnd) -> i32;
+        fn GetWindowThreadProcessId(hwnd: Hwnd, process_id: *mut u32) -> u32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenProcess(access: u32, inherit: i32, process_id: u32) -> Handle;
+        fn QueryFullProcessImageNameW(
+            process: Handle,
+            flags: u32,
+            name: *mut u16,
+            size: *mut u32,
+        ) -> i32;
+        fn CloseHandle(handle: Handle) -> i32;
+    }
+
+    // Visible, unowned, titled top-level windows: what the taskbar shows. Tool windows and
+    // hidden helper windows of background processes fail one of the checks.
+    extern "system" fn collect_window(hwnd: Hwnd, param: isize) -> i32 {
+        let process_ids = unsafe { &mut *(param as *mut Vec<u32>) };
+        unsafe {
+            if IsWindowVisible(hwnd) == 0
+                || !GetWindow(hwnd, GW_OWNER).is_null()
+                || GetWindowTextLengthW(hwnd) == 0
+            {
+                return 1;
+            }
+            let mut process_id = 0;
+            GetWindowThreadProcessId(hwnd, &mut process_id);
+            if process_id != 0 && !process_ids.contains(&process_id) {
+                process_ids.push(process_id);
+            }
+        }
+        1
+    }
+
+    fn process_image_path(process_id: u32) -> Option<PathBuf> {
+        unsafe {
+            let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id);
+            if process.is_null() {
+                return None;
+            }
+            let mut buffer = vec![0u16; MAX_IMAGE_PATH];
+            let mut size = buffer.len() as u32;
+            let ok = QueryFullProcessImageNameW(process, 0, buffer.as_mut_ptr(), &mut size);
+            CloseHandle(process);
+            if ok == 0 {
+                return None;
+            }
+            buffer.truncate(size as usize);
+            Some(PathBuf::from(std::ffi::OsString::from_wide(&buffer)))
+        }
+    }
+
+    pub(super) fn window_executables() -> Vec<PathBuf> {
+        let mut process_ids: Vec<u32> = Vec::new();
+        unsafe {
+            EnumWindows(collect_window, &mut process_ids as *mut Vec<u32> as isize);
+        }
+        process_ids
+            .into_iter()
+            .filter_map(process_image_path)
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::PathBuf;
+
+    /// Executable of a process owned by the current user; None for kernel threads, other
+    /// users' processes and processes that exited.
+    pub(in crate::collector) fn process_executable(process_id: u32) -> Option<PathBuf> {
+        let own_uid = fs::metadata("/proc/self").ok()?.uid();
+        let proc_dir = PathBuf::from(format!("/proc/{process_id}"));
+        if fs::metadata(&proc_dir).ok()?.uid() != own_uid {
+            return None;
+        }
+        let exe = fs::read_link(proc_dir.join("exe")).ok()?;
+        // A replaced binary reads as "/usr/bin/app (deleted)"; the app is still the same.
+        let exe = exe.to_string_lossy();
+        Some(PathBuf::from(exe.trim_end_matches(" (deleted)")))
+    }
+
+    fn process_ids() -> Vec<u32> {
+        let Ok(entries) = fs::read_dir("/proc") else {
+            return vec![];
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+            .collect()
+    }
+
+    // Without an X11 window list (Wayland, no display server) fall back to processes that
+    // were started inside the graphical session. Coarser: terminal children inherit the
+    // display variables too.
+    fn has_display_env(process_id: u32) -> bool {
+        let Ok(environ) = fs::read(format!("/proc/{process_id}/environ")) else {
+            return false;
+        };
+        environ
+            .split(|byte| *byte == 0)
+            .any(|var| var.starts_with(b"WAYLAND_DISPLAY=") || var.starts_with(b"DISPLAY="))
+    }
+
+    pub(super) fn window_executables() -> Vec<PathBuf> {
+        let process_ids: Vec<u32> = match x11::client_window_pids() {
+            Some(pids) => pids,
+            None => process_ids()
+                .into_iter()
+                .filter(|pid| has_display_env(*pid))
+                .collect(),
+        };
+        process_ids
+            .into_iter()
+            .filter_map(process_executable)
+            .collect()
+    }
+
+    // libX11 is loaded at runtime so the app still starts, and falls back, where it is missing.
+    mod x11 {
+        use std::ffi::{c_char, c_int, c_long, c_uchar, c_ulong, c_void, CStr};
+
+        type Display = c_void;
+        type Atom = c_ulong;
+        type Window = c_ulong;
+        type ErrorHandler = Option<unsafe extern "C" fn(*mut Display, *mut c_void) -> c_int>;
+
+        const RTLD_NOW: c_int = 2;
+        const SUCCESS: c_int = 0;
+        const ANY_PROPERTY_TYPE: Atom = 0;
+        // Upper bound on properties read, in 32-bit items.
+        const MAX_ITEMS: c_long = 1 << 16;
+
+        extern "C" {
+            fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+            fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+            fn dlclose(handle: *mut c_void) -> c_int;
+        }
+
+        type OpenDisplayFn = unsafe extern "C" fn(*const c_char) -> *mut Display;
+        type CloseDisplayFn = unsafe extern "C" fn(*mut Display) -> c_int;
+        type DefaultRootWindowFn = unsafe extern "C" fn(*mut Display) -> Window;
+        type InternAtomFn = unsafe extern "C" fn(*mut Display, *const c_char, c_int) -> Atom;
+        type GetWindowPropertyFn = unsafe extern "C" fn(
+            *mut Display,
+            Window,
+            Atom,
+            c_long,
+            c_long,
+            c_int,
+            Atom,
+            *mut Atom,
+            *mut c_int,
+            *mut c_ulong,
+            *mut c_ulong,
+            *mut *mut c_uchar,
+        ) -> c_int;
+        type FreeFn = unsafe extern "C" fn(*mut c_void) -> c_int;
+        type SetErrorHandlerFn = unsafe extern "C" fn(ErrorHandler) -> ErrorHandler;
+
+        struct Xlib {
+            handle: *mut c_void,
+            open_display: OpenDisplayFn,
+            close_display: CloseDisplayFn,
+            default_root_window: DefaultRootWindowFn,
+            intern_atom: InternAtomFn,
+            get_window_property: GetWindowPropertyFn,
+            free: FreeFn,
+            set_error_handler: SetErrorHandlerFn,
+        }
+
+        // Function pointer `T` of a symbol in a dlopen'ed library.
+        unsafe fn symbol<T: Copy>(handle: *mut c_void, name: &CStr) -> Option<T> {
+            let address = dlsym(handle, name.as_ptr());
+            if address.is_null() {
+                return None;
+            }
+            Some(std::mem::transmute_copy(&address))
+        }
+
+        impl Xlib {
+            fn load() -> Option<Self> {
+                unsafe {
+                    let handle = dlopen(c"libX11.so.6".as_ptr(), RTLD_NOW);
+                    if handle.is_null() {
+                        return None;
+                    }
+                    let loaded = (|| {
+                        Some(Xlib {
+                            handle,
+                            open_display: symbol(handle, c"XOpenDisplay")?,
+                            close_display: symbol(handle, c"XCloseDisplay")?,
+                            default_root_window: symbol(handle, c"XDefaultRootWindow")?,
+                            intern_atom: symbol(handle, c"XInternAtom")?,
+                            get_window_property: symbol(handle, c"XGetWindowProperty")?,
+                            free: symbol(handle, c"XFree")?,
+                            set_error_handler: symbol(handle, c"XSetErrorHandler")?,
+                        })
+                    })();
+                    if loaded.is_none() {
+                        dlclose(handle);
+                    }
+                    loaded
+                }
+            }
+
+            // 32-bit items of a window property, which Xlib hands out as C longs.
+            unsafe fn cardinals(
+                &self,
+                display: *mut Display,
+                window: Window,
+                property: Atom,
+            ) -> Vec<c_ulong> {
+                let mut actual_type: Atom = 0;
+                let mut actual_format: c_int = 0;
+                let mut item_count: c_ulong = 0;
+                let mut bytes_after: c_ulong = 0;
+                let mut data: *mut c_uchar = std::ptr::null_mut();
+                let status = (self.get_window_property)(
+                    display,
+                    window,
+                    property,
+                    0,
+                    MAX_ITEMS,
+                    0,
+                    ANY_PROPERTY_TYPE,
+                    &mut actual_type,
+                    &mut actual_format,
+                    &mut item_count,
+                    &mut bytes_after,
+                    &mut data,
+                );
+                if status != SUCCESS || data.is_null() {
+                    return vec![];
+                }
+                let items = if actual_format == 32 {
+                    std::slice::from_raw_parts(data as *const c_ulong, item_count as usize).to_vec()
+                } else {
+                    vec![]
+                };
+                (self.free)(data as *mut c_void);
+                items
+            }
+        }
+
+        impl Drop for Xlib {
+            fn drop(&mut self) {
+                unsafe {
+                    dlclose(self.handle);
+                }
+            }
+        }
+
+        // A window closed between listing and reading its pid raises BadWindow, whose default
+        // handler exits the process.
+        unsafe extern "C" fn ignore_error(_display: *mut Display, _event: *mut c_void) -> c_int {
+            0
+        }
+
+        /// Process ids of the windows the window manager lists in `_NET_CLIENT_LIST`; None
+        /// when there is no X server or window manager to ask.
+        pub(super) fn client_window_pids() -> Option<Vec<u32>> {
+            let xlib = Xlib::load()?;
+            unsafe {
+                let display = (xlib.open_display)(std::ptr::null());
+                if display.is_null() {
+                    return None;
+                }
+                let previous_handler = (xlib.set_error_handler)(Some(ignore_error));
+                let client_list = (xlib.intern_atom)(display, c"_NET_CLIENT_LIST".as_ptr(), 1);
+                let wm_pid = (xlib.intern_atom)(display, c"_NET_WM_PID".as_ptr(), 1);
+                let pids = if client_list == 0 || wm_pid == 0 {
+                    None
+                } else {
+                    let root = (xlib.default_root_window)(display);
+                    let windows = xlib.cardinals(display, root, client_list);
+                    Some(
+                        windows
+                            .into_iter()
+                            .filter_map(|window| {
+                                let pid = *xlib.cardinals(display, window, wm_pid).first()?;
+                                u32::try_from(pid).ok().filter(|pid| *pid != 0)
+                            })
+                            .collect(),
+                    )
+                };
+                (xlib.set_error_handler)(previous_handler);
+                (xlib.close_display)(display);
+                pids
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    pub(super) fn window_executables() -> Vec<std::path::PathBuf> {
+        vec![]
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+pub(super) use platform::process_executable;
@@ -0,0 +1,42 @@
+//! Window count module.
+//! Counts distinct window titles (documents, tabs) per app and local day as a fragmentation
+//! signal, computed at query time from stats rows.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{StatsRow, DAY_KEY_FORMAT};
+
+/// Distinct window counts keyed by (local day `YYYY-MM-DD`, app name).
+pub(super) type DistinctWindows = HashMap<(String, String), u64>;
+
+/// Count distinct window titles per (day, app). Titles recorded while the title mode was
+/// hidden are empty and cannot be told apart, so they do not add to the count.
+pub(super) fn distinct_windows_by_app_day(rows: &[StatsRow]) -> DistinctWindows {
+    let mut titles: HashMap<(String, String), HashSet<&str>> = HashMap::new();
+    for row in rows {
+        let seen = titles
+            .entry((
+                row.date.day().format(DAY_KEY_FORMAT).to_string(),
+                row.app_name.clone(),
+            ))
+            .or_default();
+        if !row.window_title.is_empty() {
+            seen.insert(row.window_title.as_str());
+        }
+    }
+    titles
+        .into_iter()
+        .map(|(key, seen)| (key, seen.len() as u64))
+        .collect()
+}
+
+/// Look up the count of the row's own day and app.
+pub(super) fn distinct_windows_of(counts: &DistinctWindows, row: &StatsRow) -> u64 {
+    counts
+        .get(&(
+            row.date.day().format(DAY_KEY_FORMAT).to_string(),
+            row.app_name.clone(),
+        ))
+        .copied()
+        .unwrap_or(0)
+}
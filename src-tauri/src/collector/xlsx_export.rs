@@ -0,0 +1,287 @@
+//! Excel export module.
+//! Builds a multi-sheet workbook (per-app daily rollups, raw rows, shortcuts) for sharing
+//! stats with people who work in spreadsheets.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use rust_xlsxwriter::{DocProperties, Format, Workbook, Worksheet, XlsxError};
+use serde::Serialize;
+
+use super::export_chain::{canonical_xlsx, sign_export, ExportSignature, XLSX_SIGNATURE_PROPERTY};
+use super::shortcut::{days_in_window, shortcut_range_window_ms, snapshot_shortcut_rows_in_window};
+use super::window_count::distinct_windows_by_app_day;
+use super::{CollectorState, ShortcutStatRow, StatsRow, DAY_KEY_FORMAT};
+
+// Sheet names in workbook order.
+const SHEET_APP_DAILY: &str = "Daily by app";
+const SHEET_ROWS: &str = "Rows";
+const SHEET_SHORTCUTS: &str = "Shortcuts";
+// Excel stores durations as fractions of a day; `[h]` keeps counting past 24 hours.
+const MS_PER_DAY: f64 = 86_400_000.0;
+const DURATION_NUM_FORMAT: &str = "[h]:mm";
+
+/// One app's totals on one local day (`YYYY-MM-DD`).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(super) struct AppDayRollup {
+    pub(super) date: String,
+    pub(super) app_name: String,
+    pub(super) active_typing_ms: u64,
+    pub(super) key_count: u64,
+    pub(super) session_count: u64,
+    pub(super) distinct_windows: u64,
+}
+
+/// Everything the workbook contains, copied out of the state so the file can be written
+/// without holding the state lock.
+#[derive(Clone, Default)]
+pub struct StatsExport {
+    range: String,
+    app_days: Vec<AppDayRollup>,
+    rows: Vec<StatsRow>,
+    shortcuts: Vec<ShortcutStatRow>,
+}
+
+/// Data rows written to one sheet, excluding the header row.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct XlsxSheetRows {
+    pub sheet: String,
+    pub rows: u64,
+}
+
+/// Result of an XLSX export: the written file and the row count of each sheet.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct XlsxExportReport {
+    pub path: String,
+    pub range: String,
+    pub sheets: Vec<XlsxSheetRows>,
+    /// Signature stored in the workbook properties, for signed exports.
+    pub signature: Option<ExportSignature>,
+}
+
+// Sum rows per (local day, app), ordered by day then app.
+pub(super) fn rollup_app_days(rows: &[StatsRow]) -> Vec<AppDayRollup> {
+    let mut by_app_day: BTreeMap<(String, String), AppDayRollup> = BTreeMap::new();
+    for row in rows {
+        let date = row.date.day().format(DAY_KEY_FORMAT).to_string();
+        let rollup = by_app_day
+            .entry((date.clone(), row.app_name.clone()))
+            .or_insert_with(|| AppDayRollup {
+                date,
+                app_name: row.app_name.clone(),
+                ..AppDayRollup::default()
+            });
+        rollup.active_typing_ms += row.active_typing_ms;
+        rollup.key_count += row.key_count;
+        rollup.session_count += row.session_count;
+    }
+    let distinct_windows = distinct_windows_by_app_day(rows);
+    by_app_day
+        .into_iter()
+        .map(|(key, mut rollup)| {
+            rollup.distinct_windows = distinct_windows.get(&key).copied().unwrap_or(0);
+            rollup
+        })
+        .collect()
+}
+
+/// Copy the rows and shortcuts of `today` / `yesterday` / `7d` for an XLSX export. Shortcut
+/// rows list every app, without the leaderboard's per-shortcut app limit.
+pub fn snapshot_stats_export(state: &CollectorState, range: &str) -> Result<StatsExport, String> {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let (start_ms, end_ms) = shortcut_range_window_ms(range, now_ms);
+    let days = days_in_window(start_ms, end_ms);
+    let rows: Vec<StatsRow> = state
+        .snapshot_rows()?
+        .into_iter()
+        .filter(|row| days.contains(&row.date.day().format(DAY_KEY_FORMAT).to_string()))
+        .collect();
+    Ok(StatsExport {
+        range: range.to_string(),
+        app_days: rollup_app_days(&rows),
+        rows,
+        shortcuts: snapshot_shortcut_rows_in_window(state, start_ms, end_ms, None, usize::MAX),
+    })
+}
+
+fn write_header(sheet: &mut Worksheet, columns: &[&str], bold: &Format) -> Result<(), XlsxError> {
+    for (col, name) in columns.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *name, bold)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+fn write_duration(
+    sheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    ms: u64,
+    duration: &Format,
+) -> Result<(), XlsxError> {
+    sheet.write_number_with_format(row, col, ms as f64 / MS_PER_DAY, duration)?;
+    Ok(())
+}
+
+fn write_app_day_sheet(
+    sheet: &mut Worksheet,
+    app_days: &[AppDayRollup],
+    bold: &Format,
+    duration: &Format,
+) -> Result<u64, XlsxError> {
+    sheet.set_name(SHEET_APP_DAILY)?;
+    write_header(
+        sheet,
+        &[
+            "date",
+            "app_name",
+            "active_typing",
+            "key_count",
+            "session_count",
+            "distinct_windows",
+        ],
+        bold,
+    )?;
+    for (index, rollup) in app_days.iter().enumerate() {
+        let row = index as u32 + 1;
+        sheet.write_string(row, 0, rollup.date.as_str())?;
+        sheet.write_string(row, 1, rollup.app_name.as_str())?;
+        write_duration(sheet, row, 2, rollup.active_typing_ms, duration)?;
+        sheet.write_number(row, 3, rollup.key_count as f64)?;
+        sheet.write_number(row, 4, rollup.session_count as f64)?;
+        sheet.write_number(row, 5, rollup.distinct_windows as f64)?;
+    }
+    Ok(app_days.len() as u64)
+}
+
+fn write_rows_sheet(
+    sheet: &mut Worksheet,
+    rows: &[StatsRow],
+    bold: &Format,
+    duration: &Format,
+) -> Result<u64, XlsxError> {
+    sheet.set_name(SHEET_ROWS)?;
+    write_header(
+        sheet,
+        &[
+            "date",
+            "app_name",
+            "window_title",
+            "active_typing",
+            "key_count",
+            "session_count",
+        ],
+        bold,
+    )?;
+    for (index, stats_row) in rows.iter().enumerate() {
+        let row = index as u32 + 1;
+        sheet.write_string(row, 0, stats_row.date.to_string())?;
+        sheet.write_string(row, 1, stats_row.app_name.as_str())?;
+        sheet.write_string(row, 2, stats_row.window_title.as_str())?;
+        write_duration(sheet, row, 3, stats_row.active_typing_ms, duration)?;
+        sheet.write_number(row, 4, stats_row.key_count as f64)?;
+        sheet.write_number(row, 5, stats_row.session_count as f64)?;
+    }
+    Ok(rows.len() as u64)
+}
+
+// One row per shortcut and app, so the sheet can be filtered or pivoted by either.
+fn write_shortcut_sheet(
+    sheet: &mut Worksheet,
+    shortcuts: &[ShortcutStatRow],
+    bold: &Format,
+) -> Result<u64, XlsxError> {
+    sheet.set_name(SHEET_SHORTCUTS)?;
+    write_header(
+        sheet,
+        &["shortcut_id", "total_count", "app_name", "app_count"],
+        bold,
+    )?;
+    let mut row = 0;
+    for shortcut in shortcuts {
+        // Uses recorded before per-app tracking have no app breakdown; keep them as one row.
+        if shortcut.apps.is_empty() {
+            row += 1;
+            sheet.write_string(row, 0, shortcut.shortcut_id.as_str())?;
+            sheet.write_number(row, 1, shortcut.count as f64)?;
+        }
+        for app in &shortcut.apps {
+            row += 1;
+            sheet.write_string(row, 0, shortcut.shortcut_id.as_str())?;
+            sheet.write_number(row, 1, shortcut.count as f64)?;
+            sheet.write_string(row, 2, app.app_name.as_str())?;
+            sheet.write_number(row, 3, app.count as f64)?;
+        }
+    }
+    Ok(row as u64)
+}
+
+/// Write the export as an `.xlsx` workbook at `path`. Durations are stored as Excel times and
+/// shown as h:mm; each sheet has a bold header row frozen in place. With a chain path the
+/// workbook is signed; saving it again from a spreadsheet tool rewrites the sheets and breaks
+/// the signature.
+pub fn write_stats_xlsx(
+    export: &StatsExport,
+    path: &Path,
+    sign_chain: Option<&Path>,
+) -> Result<XlsxExportReport, String> {
+    let bold = Format::new().set_bold();
+    let duration = Format::new().set_num_format(DURATION_NUM_FORMAT);
+    let mut workbook = Workbook::new();
+    let build = |workbook: &mut Workbook| -> Result<Vec<XlsxSheetRows>, XlsxError> {
+        let app_days =
+            write_app_day_sheet(workbook.add_worksheet(), &export.app_days, &bold, &duration)?;
+        let rows = write_rows_sheet(workbook.add_worksheet(), &export.rows, &bold, &duration)?;
+        let shortcuts = write_shortcut_sheet(workbook.add_worksheet(), &export.shortcuts, &bold)?;
+        Ok(vec![
+            XlsxSheetRows {
+                sheet: SHEET_APP_DAILY.to_string(),
+                rows: app_days,
+            },
+            XlsxSheetRows {
+                sheet: SHEET_ROWS.to_string(),
+                rows,
+            },
+            XlsxSheetRows {
+                sheet: SHEET_SHORTCUTS.to_string(),
+                rows: shortcuts,
+            },
+        ])
+    };
+    let sheets = build(&mut workbook).map_err(|e| e.to_string())?;
+    let signature = match sign_chain {
+        Some(chain_path) => {
+            // The sheets are hashed as first written, then saved again with the signature in
+            // the document properties, which the hash leaves out.
+            let unsigned = workbook.save_to_buffer().map_err(|e| e.to_string())?;
+            let canonical = canonical_xlsx(&unsigned)?;
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let signature = sign_export(chain_path, &file_name, &canonical, |signature| {
+                workbook.set_properties(
+                    &DocProperties::new()
+                        .set_custom_property(XLSX_SIGNATURE_PROPERTY, signature.to_text()),
+                );
+                let signed = workbook.save_to_buffer().map_err(|e| e.to_string())?;
+                if canonical_xlsx(&signed)? != canonical {
+                    return Err("workbook changed while signing".to_string());
+                }
+                std::fs::write(path, signed).map_err(|e| e.to_string())?;
+                Ok(signature.clone())
+            })?;
+            Some(signature)
+        }
+        None => {
+            workbook.save(path).map_err(|e| e.to_string())?;
+            None
+        }
+    };
+    Ok(XlsxExportReport {
+        path: path.to_string_lossy().to_string(),
+        range: export.range.clone(),
+        sheets,
+        signature,
+    })
+}
@@ -1,324 +1,2215 @@
-use std::{fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
 
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, LogicalSize, Manager, State};
+use tauri_plugin_dialog::DialogExt;
 use tauri_plugin_opener::OpenerExt;
 
 use crate::{
-    app_config::{save_app_config, MenuBarDisplayMode},
-    apply_menu_bar_mode_immediately,
+    app_config::{
+        save_app_config, AutoExportConfig, AutoExportFormat, CsvDelimiter, EffectiveConfig,
+        MenuBarDisplayMode, ShortcutRules, SymbolKeyNames, TrayLeftClickAction, WindowTitleMode,
+    },
+    apply_menu_bar_mode_immediately, check_for_updates_blocking,
     collector::{
-        self, bundle_id_from_app_path, running_apps, snapshot_shortcut_rows_by_range,
-        snapshot_top_keys_by_range, KeyUsageRow, RunningAppInfo, ShortcutStatRow, StatsSnapshot,
+        self, bundle_id_from_app_path, ensure_debug_tools_enabled, ensure_parquet_export_enabled,
+        render_key_heatmap_svg, running_apps, shortcut_app_limit, snapshot_analytics_stats,
+        snapshot_app_summary, snapshot_app_switch_stats, snapshot_app_timeline,
+        snapshot_burst_stats, snapshot_chord_abort_stats, snapshot_comparison,
+        snapshot_copy_paste_stats, snapshot_coverage, snapshot_daily_totals,
+        snapshot_deep_work_blocks, snapshot_display_stats, snapshot_effort_stats,
+        snapshot_focus_scores, snapshot_key_heatmap, snapshot_key_hold_stats,
+        snapshot_minute_series, snapshot_parquet_export, snapshot_raw_events,
+        snapshot_shortcut_breadth, snapshot_shortcut_daily_series, snapshot_shortcut_rows_by_range,
+        snapshot_stats_export, snapshot_stats_row_page, snapshot_stats_rows,
+        snapshot_top_keys_by_range, snapshot_top_windows, snapshot_weekly_comparison,
+        top_windows_limit, write_parquet_export, write_raw_events, write_stats_xlsx,
+        AnalyticsStats, AppPurgeReport, AppSummary, AppSwitchStats, AppTimeline, BurstStats,
+        ChordAbortStats, Comparison, ComparisonError, CopyPasteStats, Coverage, CsvDialect,
+        DailyTotal, DayReconstruction, DeepWorkBlock, DestructiveAction, DestructivePreview,
+        DisplayStats, EffortStats, ExportVerification, FocusDayScore, KeyHeatmap, KeyHoldStats,
+        MinuteSeries, ParquetExportReport, RawExportConsent, RawExportReport, RunningAppInfo,
+        ShortcutBreadth, ShortcutDayCount, ShortcutRangeStats, StatsRow, StatsRowFilter,
+        StatsRowPage, StatsRowSort, StatsSnapshot, TodaySummaryJson, TopKeysRangeStats, TopWindows,
+        WeeklyComparison, XlsxExportReport, DEFAULT_DAILY_TOTAL_DAYS, DEFAULT_SHORTCUT_SERIES_DAYS,
+        DEFAULT_STATS_PAGE_SIZE,
     },
-    show_main_window, AppState,
+    command_metrics::{tracked, CommandMetric, COMMAND_METRICS},
+    confirm::CONFIRM_TOKEN_TTL,
+    flush_and_exit, notify_capture_state_changed, set_paused_and_notify,
+    set_shortcuts_paused_and_notify,
+    shortcut_preset::{ShortcutRulesDiff, ShortcutRulesPreset},
+    show_main_window,
+    storage::{is_archive_name, ArchiveReport, DataDirSize},
+    switch_profile_and_notify,
+    telemetry::{self, TelemetryPreview},
+    update_check::UpdateInfo,
+    validation::{
+        app_bundle_path, export_script_path, BundleId, ExportRoots, RangeSpec, ShortcutId,
+        WindowTitle,
+    },
+    AppState,
 };
 
-/// 获取当前采集快照，供前端轮询刷新仪表盘。
+// Matches the main window size declared in tauri.conf.json.
+const MAIN_WINDOW_DEFAULT_WIDTH: f64 = 1280.0;
+const MAIN_WINDOW_DEFAULT_HEIGHT: f64 = 800.0;
+// A daily target cannot exceed the minutes in a day.
+const MAX_APP_TIME_GOAL_MIN: u64 = 24 * 60;
+const MAX_DISPLAY_LABEL_CHARS: usize = 64;
+
+tracked! {
+/// 获取当前采集快照，供前端轮询刷新仪表盘。
+#[tauri::command]
+pub(crate) fn get_snapshot(state: State<AppState>) -> StatsSnapshot {
+    snapshot_of(&state)
+}
+}
+
+// Snapshot returned by the commands that change state; counted under the calling command.
+fn snapshot_of(state: &AppState) -> StatsSnapshot {
+    if let Ok(locked) = state.inner.lock() {
+        return locked.snapshot();
+    }
+    StatsSnapshot {
+        rows: vec![],
+        paused: false,
+        auto_paused: false,
+        auto_pause_reason: None,
+        shortcuts_paused: false,
+        keyboard_active: false,
+        ignore_key_combos: false,
+        track_chord_aborts: false,
+        prevent_app_nap: false,
+        app_nap_assertion_held: false,
+        excluded_bundle_ids: vec![],
+        one_password_suggestion_pending: false,
+        non_typing_suggestions: vec![],
+        tray_display_mode: MenuBarDisplayMode::default().as_str().to_string(),
+        tray_left_click_action: TrayLeftClickAction::default().as_str().to_string(),
+        start_hidden: false,
+        min_keys_to_display: 0,
+        window_title_mode: WindowTitleMode::default().as_str().to_string(),
+        title_privacy_overrides: BTreeMap::new(),
+        last_error: Some("state lock failed".to_string()),
+        storage_degraded: false,
+        storage_writable: true,
+        loading: false,
+        demo_mode: false,
+        load_files_processed: 0,
+        load_files_total: 0,
+        external_changes_detected: false,
+        tick_stalled: false,
+        update_available: false,
+        log_path: "".to_string(),
+        shortcut_stats: vec![],
+        shortcut_total_count: 0,
+        row_total_count: 0,
+        snapshot_v2: false,
+        today_focus_score: None,
+        today_coverage_pct: None,
+        goal_progress: vec![],
+        current_context: None,
+        avg_keys_7d: None,
+        avg_keys_30d: None,
+        avg_active_ms_7d: None,
+        avg_active_ms_30d: None,
+        today_vs_avg_pct: None,
+        effort_today: 0.0,
+        deep_work_minutes_today: 0,
+        day_first_key_ms: None,
+        day_last_key_ms: None,
+    }
+}
+
+tracked! {
+/// 按时间范围返回快捷键排行榜（today / yesterday / 7d）。
+/// `app_limit` 为每个快捷键列出的应用数（默认 8，最多 50），其余应用的次数计入 `other_count`。
+/// 关闭事件 chunk 记录时返回全部历史汇总，并置 `degraded_range_support`。
+#[tauri::command]
+pub(crate) fn get_shortcut_stats_by_range(
+    state: State<AppState>,
+    range: String,
+    app_limit: Option<usize>,
+) -> Result<ShortcutRangeStats, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_shortcut_rows_by_range(
+        &locked,
+        range.as_str(),
+        shortcut_app_limit(app_limit),
+    ))
+}
+}
+
+tracked! {
+/// 返回今天的精简统计 JSON（带版本号 `v`），供 Raycast/Alfred 等脚本读取；与 `--today-json` 命令行输出同形。
+#[tauri::command]
+pub(crate) fn get_today_summary_json(state: State<AppState>) -> Result<TodaySummaryJson, String> {
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(locked.today_summary_json())
+}
+}
+
+tracked! {
+/// 按时间范围返回快捷键使用广度（today / yesterday / 7d）：每个快捷键用过的应用数、集中度与主要应用，按总次数降序。
+#[tauri::command]
+pub(crate) fn get_shortcut_breadth(
+    state: State<AppState>,
+    range: String,
+) -> Result<ShortcutBreadth, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_shortcut_breadth(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 按时间范围返回 Top5 按键（today / yesterday / 7d，聚合展示）。
+/// 关闭事件 chunk 记录时不区分范围，并置 `degraded_range_support`。
+#[tauri::command]
+pub(crate) fn get_daily_top_keys_by_range(
+    state: State<AppState>,
+    range: String,
+) -> Result<TopKeysRangeStats, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_top_keys_by_range(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 按时间范围返回未完成组合键统计（today / yesterday / 7d），按应用与日期汇总。
+#[tauri::command]
+pub(crate) fn get_chord_abort_stats(
+    state: State<AppState>,
+    range: String,
+) -> Result<ChordAbortStats, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_chord_abort_stats(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 按时间范围返回各应用的按键数、活跃时长与会话数（按键数降序）。
+/// 范围内按键数低于 `min_keys_to_display` 的应用合并为“其他”一行；`include_other_bucket` 为 false 时不返回该行（默认返回）。
+#[tauri::command]
+pub(crate) fn get_app_summary(
+    state: State<AppState>,
+    range: String,
+    include_other_bucket: Option<bool>,
+) -> Result<AppSummary, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_app_summary(
+        &locked,
+        range.as_str(),
+        include_other_bucket.unwrap_or(true),
+    ))
+}
+}
+
+tracked! {
+/// 按时间范围返回某个应用中按键数最多的窗口标题（按键数降序），含活跃时长与最近出现日期。
+/// `limit` 为返回的标题数（默认 10，最多 100）；隐藏标题模式下记录的按键合并为一行并置 `title_hidden`。
+#[tauri::command]
+pub(crate) fn get_top_windows(
+    state: State<AppState>,
+    bundle_id: String,
+    range: String,
+    limit: Option<usize>,
+) -> Result<TopWindows, String> {
+    let bundle_id = BundleId::parse(&bundle_id)?;
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_top_windows(
+        &locked,
+        bundle_id.as_str(),
+        range.as_str(),
+        top_windows_limit(limit),
+    ))
+}
+}
+
+tracked! {
+/// 按时间范围返回统计明细行（按时间、应用、窗口标题排序）。快照只携带今天的明细行时，范围视图从这里取数。
+#[tauri::command]
+pub(crate) fn get_stats_rows(
+    state: State<AppState>,
+    range: String,
+) -> Result<Vec<StatsRow>, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_stats_rows(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 分页浏览全部历史统计明细：可按日期范围、应用与窗口标题筛选，按任一列排序（同值再按时间、应用、窗口标题排列，翻页顺序稳定）。page 从 0 开始，page_size 默认 100、最多 500，返回匹配总数。
+#[tauri::command]
+pub(crate) fn query_stats_rows(
+    state: State<AppState>,
+    filter: Option<StatsRowFilter>,
+    sort: Option<StatsRowSort>,
+    page: Option<usize>,
+    page_size: Option<usize>,
+) -> Result<StatsRowPage, String> {
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    snapshot_stats_row_page(
+        &locked,
+        &filter.unwrap_or_default(),
+        sort.unwrap_or_default(),
+        page.unwrap_or(0),
+        page_size.unwrap_or(DEFAULT_STATS_PAGE_SIZE),
+    )
+}
+}
+
+tracked! {
+/// 返回最近 days 天（默认 120，最多 366，含今天）中有数据的每一天的按键数、活跃时长与会话数，按日期从旧到新，供活跃日历使用。
+#[tauri::command]
+pub(crate) fn get_daily_totals(
+    state: State<AppState>,
+    days: Option<usize>,
+) -> Result<Vec<DailyTotal>, String> {
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_daily_totals(
+        &locked,
+        days.unwrap_or(DEFAULT_DAILY_TOTAL_DAYS),
+    ))
+}
+}
+
+tracked! {
+/// 按时间范围返回输入过程中的应用切换次数，以及最常见的 10 组“来源 → 目标”应用切换。
+#[tauri::command]
+pub(crate) fn get_app_switch_stats(
+    state: State<AppState>,
+    range: String,
+) -> Result<AppSwitchStats, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_app_switch_stats(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 按时间范围返回复制到粘贴的配对数、未配对的复制数、延迟分位数（p50/p90/p99）与最常见的“来源 → 目标”应用对。
+#[tauri::command]
+pub(crate) fn get_copy_paste_stats(
+    state: State<AppState>,
+    range: String,
+) -> Result<CopyPasteStats, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_copy_paste_stats(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 返回输入事件 chunk 的存储情况（数量、时间跨度、事件总数、估算体积、近 7 天每日事件数与上限）。
+#[tauri::command]
+pub(crate) fn get_analytics_stats(state: State<AppState>) -> Result<AnalyticsStats, String> {
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_analytics_stats(&locked))
+}
+}
+
+tracked! {
+/// 按时间范围返回采集覆盖情况（记录中 / 暂停 / 键盘监听失效的时长），用于解释数据缺口。
+#[tauri::command]
+pub(crate) fn get_coverage(state: State<AppState>, range: String) -> Result<Coverage, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_coverage(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 按时间范围返回连续输入段（burst）统计：段数、平均长度、最长段，以及每日明细。
+#[tauri::command]
+pub(crate) fn get_burst_stats(state: State<AppState>, range: String) -> Result<BurstStats, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_burst_stats(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 按时间范围返回输入负荷估算：按键位与主键区的距离加权累加按键数，并附每日明细。
+#[tauri::command]
+pub(crate) fn get_effort_stats(
+    state: State<AppState>,
+    range: String,
+) -> Result<EffortStats, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_effort_stats(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 按时间范围返回深度工作块（连续 5 分钟桶均达到按键密度且总时长达到阈值的时段）：
+/// 起止时间、主要应用与按键数，按开始时间升序；跨越范围起点的块完整返回。
+#[tauri::command]
+pub(crate) fn get_deep_work_blocks(
+    state: State<AppState>,
+    range: String,
+) -> Result<Vec<DeepWorkBlock>, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_deep_work_blocks(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 按时间范围返回各显示器的按键数与占比（按键数降序），并附每日明细；显示器名称优先使用 display_labels。
+#[tauri::command]
+pub(crate) fn get_display_stats(
+    state: State<AppState>,
+    range: String,
+) -> Result<DisplayStats, String> {
+    let range = RangeSpec::parse(&range)?;
+    let labels = state
+        .config
+        .lock()
+        .map_err(|_| "config lock failed".to_string())?
+        .display_labels
+        .clone();
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_display_stats(&locked, range.as_str(), &labels))
+}
+}
+
+tracked! {
+/// 设置显示器名称（label 为空时恢复系统名称），持久化配置。
+#[tauri::command]
+pub(crate) fn update_display_label(
+    state: State<AppState>,
+    display_id: String,
+    label: String,
+) -> Result<(), String> {
+    let display_id = display_id.trim();
+    if display_id.is_empty() {
+        return Err("display id is empty".to_string());
+    }
+    let label = label.trim();
+    if label.chars().count() > MAX_DISPLAY_LABEL_CHARS {
+        return Err(format!(
+            "display label is longer than {} characters",
+            MAX_DISPLAY_LABEL_CHARS
+        ));
+    }
+    let mut config = state
+        .config
+        .lock()
+        .map_err(|_| "config lock failed".to_string())?;
+    if label.is_empty() {
+        config.display_labels.remove(display_id);
+    } else {
+        config
+            .display_labels
+            .insert(display_id.to_string(), label.to_string());
+    }
+    save_app_config(&state.config_path, &config)
+}
+}
+
+tracked! {
+/// 按时间范围返回每日专注度评分（today / yesterday / 7d），按日期升序，无活动的日期不返回。
+#[tauri::command]
+pub(crate) fn get_focus_scores(
+    state: State<AppState>,
+    range: String,
+) -> Result<Vec<FocusDayScore>, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_focus_scores(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 按时间范围返回键盘热力图（layout 为 ansi / iso），布局外的按键归入 other。
+#[tauri::command]
+pub(crate) fn get_key_heatmap(
+    state: State<AppState>,
+    range: String,
+    layout: String,
+) -> Result<KeyHeatmap, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    snapshot_key_heatmap(&locked, range.as_str(), &layout)
+}
+}
+
+tracked! {
+/// 按时间范围返回按键按住时长统计：每个按键的平均 / p95 / 最长按住时长，以及缺少抬起事件的按下次数。
+#[tauri::command]
+pub(crate) fn get_key_hold_stats(
+    state: State<AppState>,
+    range: String,
+) -> Result<KeyHoldStats, String> {
+    let range = RangeSpec::parse(&range)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_key_hold_stats(&locked, range.as_str()))
+}
+}
+
+tracked! {
+/// 将键盘热力图渲染为 SVG 并写入 dest，返回写入路径。
+/// dest 为相对路径时写入数据目录；绝对路径须位于数据目录或本次运行中通过 `pick_export_path` 选择的目录内。
+#[tauri::command]
+pub(crate) fn export_key_heatmap_svg(
+    state: State<AppState>,
+    range: String,
+    layout: String,
+    dest: String,
+) -> Result<String, String> {
+    let range = RangeSpec::parse(&range)?;
+    let heatmap = {
+        let locked = state
+            .inner
+            .lock()
+            .map_err(|_| "state lock failed".to_string())?;
+        snapshot_key_heatmap(&locked, range.as_str(), &layout)?
+    };
+    let dest = export_roots(&state).resolve(&dest)?;
+    fs::write(&dest, render_key_heatmap_svg(&heatmap)).map_err(|e| e.to_string())?;
+    Ok(dest.to_string_lossy().to_string())
+}
+}
+
+tracked! {
+/// 将指定范围的统计导出为 Excel 工作簿（应用日汇总、明细行、快捷键三个工作表），
+/// 写入 dest 并返回各工作表的行数（dest 的限制同 SVG 导出）。文件在后台线程生成，不阻塞命令线程。
+/// sign 为 true 时工作簿加入本数据集的签名哈希链，签名写入自定义文档属性。
+#[tauri::command]
+pub(crate) async fn export_stats_xlsx(
+    state: State<'_, AppState>,
+    range: String,
+    dest: String,
+    sign: Option<bool>,
+) -> Result<XlsxExportReport, String> {
+    let range = RangeSpec::parse(&range)?;
+    let (export, sign_chain) = {
+        let locked = state
+            .inner
+            .lock()
+            .map_err(|_| "state lock failed".to_string())?;
+        let sign_chain = sign
+            .unwrap_or(false)
+            .then(|| locked.export_chain_path.clone());
+        (snapshot_stats_export(&locked, range.as_str())?, sign_chain)
+    };
+    let dest = export_roots(&state).resolve(&dest)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        write_stats_xlsx(&export, &dest, sign_chain.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+}
+
+tracked! {
+/// 校验签名导出（CSV / JSON / XLSX）：重新计算内容哈希并与文件内签名及本数据集的哈希链比对。
+/// 签名只用于发现改动，任何人都能为改过的文件重新计算哈希，因此不是安全机制。path 的限制同 SVG 导出。
+#[tauri::command]
+pub(crate) fn verify_export(
+    state: State<AppState>,
+    path: String,
+) -> Result<ExportVerification, String> {
+    let path = export_roots(&state).resolve(&path)?;
+    let chain_path = {
+        let locked = state
+            .inner
+            .lock()
+            .map_err(|_| "state lock failed".to_string())?;
+        locked.export_chain_path.clone()
+    };
+    collector::verify_export(&path, &chain_path)
+}
+}
+
+tracked! {
+/// 将指定范围的统计行（窗口标题仅保留哈希）导出为 Parquet 文件写入 dest_dir；
+/// include_events 为 true 时另写一份按键事件文件。dest_dir 的限制同 SVG 导出。需以 parquet-export 特性构建。
+#[tauri::command]
+pub(crate) async fn export_stats_parquet(
+    state: State<'_, AppState>,
+    range: String,
+    dest_dir: String,
+    include_events: Option<bool>,
+) -> Result<ParquetExportReport, String> {
+    let range = RangeSpec::parse(&range)?;
+    ensure_parquet_export_enabled()?;
+    let export = {
+        let locked = state
+            .inner
+            .lock()
+            .map_err(|_| "state lock failed".to_string())?;
+        snapshot_parquet_export(&locked, range.as_str(), include_events.unwrap_or(false))?
+    };
+    let dest_dir = export_roots(&state).resolve(&dest_dir)?;
+    tauri::async_runtime::spawn_blocking(move || write_parquet_export(&export, &dest_dir))
+        .await
+        .map_err(|e| e.to_string())?
+}
+}
+
+tracked! {
+/// 打开系统对话框选择导出位置：kind 为 folder（目录）、save_file（要写入的文件）或 open_file（要读取的文件）。
+/// 返回所选路径，取消时返回 null。所选目录（选择文件时为其所在目录）在本次运行中可用于导出与校验，
+/// open_file 选中的文件还可用作自动导出脚本。
+#[tauri::command]
+pub(crate) async fn pick_export_path(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    kind: String,
+) -> Result<Option<String>, String> {
+    if !matches!(kind.as_str(), "folder" | "save_file" | "open_file") {
+        return Err(format!("unknown pick kind: {}", kind));
+    }
+    let picked = {
+        let kind = kind.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let dialog = app.dialog().file();
+            match kind.as_str() {
+                "folder" => dialog.blocking_pick_folder(),
+                "save_file" => dialog.blocking_save_file(),
+                _ => dialog.blocking_pick_file(),
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    };
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(|e| e.to_string())?;
+    let dir = if kind == "folder" {
+        path.clone()
+    } else {
+        path.parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| format!("picked path has no directory: {}", path.display()))?
+    };
+    let mut picked_dirs = state
+        .picked_dirs
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    if !picked_dirs.contains(&dir) {
+        picked_dirs.push(dir);
+    }
+    if kind == "open_file" {
+        let mut picked_files = state
+            .picked_files
+            .lock()
+            .map_err(|_| "state lock failed".to_string())?;
+        if !picked_files.contains(&path) {
+            picked_files.push(path.clone());
+        }
+    }
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+}
+
+// Export roots for this session: the data dir plus the directories picked in a dialog.
+fn export_roots(state: &AppState) -> ExportRoots {
+    let picked_dirs = state
+        .picked_dirs
+        .lock()
+        .map(|dirs| dirs.clone())
+        .unwrap_or_default();
+    ExportRoots::for_data_dir(&state.data_dir, &picked_dirs)
+}
+
+tracked! {
+/// 返回最近 minutes 分钟（默认 60，最多 60）每分钟的按键数，按时间从旧到新。
+#[tauri::command]
+pub(crate) fn get_minute_series(
+    state: State<AppState>,
+    minutes: Option<usize>,
+) -> Result<MinuteSeries, String> {
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_minute_series(&locked, minutes.unwrap_or(60)))
+}
+}
+
+tracked! {
+/// 返回某个快捷键最近 days 天（默认 30，最多 366，含今天）每天的使用次数，缺失的日期补 0；
+/// 快捷键 id 无效时返回错误。
+#[tauri::command]
+pub(crate) fn get_shortcut_daily_series(
+    state: State<AppState>,
+    shortcut_id: String,
+    days: Option<usize>,
+) -> Result<Vec<ShortcutDayCount>, String> {
+    let shortcut_id = ShortcutId::parse(&shortcut_id)?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(snapshot_shortcut_daily_series(
+        &locked,
+        shortcut_id.as_str(),
+        days.unwrap_or(DEFAULT_SHORTCUT_SERIES_DAYS),
+    ))
+}
+}
+
+tracked! {
+/// 返回指定日期（YYYY-MM-DD）各应用的输入时间轴，按 bucket_minutes 分桶并对齐本地零点。
+#[tauri::command]
+pub(crate) fn get_app_timeline(
+    state: State<AppState>,
+    date: String,
+    bucket_minutes: u32,
+) -> Result<AppTimeline, String> {
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    snapshot_app_timeline(&locked, &date, bucket_minutes)
+}
+}
+
+tracked! {
+/// 对比两天（kind=date，YYYY-MM-DD）或两个应用（kind=app，Bundle ID，按 range 共享时间范围）。
+#[tauri::command]
+pub(crate) fn get_comparison(
+    state: State<AppState>,
+    subject_a: String,
+    subject_b: String,
+    kind: String,
+    range: Option<String>,
+) -> Result<Comparison, ComparisonError> {
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| ComparisonError::StateUnavailable)?;
+    snapshot_comparison(&locked, &subject_a, &subject_b, &kind, range.as_deref())
+}
+}
+
+tracked! {
+/// 调试用：仅用 chunk_start_ms <= as_of_ms 的按键序列重建某天（YYYY-MM-DD）各应用按键数，
+/// 并与当前统计逐应用对比。需以 debug-tools 特性构建。
+#[tauri::command]
+pub(crate) fn reconstruct_day(
+    state: State<AppState>,
+    date: String,
+    as_of_ms: i64,
+) -> Result<DayReconstruction, String> {
+    ensure_debug_tools_enabled()?;
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    collector::reconstruct_day(&locked, &date, as_of_ms)
+}
+}
+
+// Confirm-token action of the raw event export consent.
+const RAW_EXPORT_ACTION: &str = "export_raw_events";
+
+tracked! {
+/// 调试用：申请一次原始按键事件导出的同意串（短时间内有效，只能使用一次），需传给 export_raw_events。
+/// 导出内容为完整按键序列，前端需先向用户说明。需以 debug-tools 特性构建。
+#[tauri::command]
+pub(crate) fn request_raw_export_consent(
+    state: State<AppState>,
+) -> Result<RawExportConsent, String> {
+    ensure_debug_tools_enabled()?;
+    let max_window_hours = state
+        .config
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?
+        .effective()
+        .raw_export_max_window_hours;
+    let consent = state
+        .confirm_tokens
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?
+        .issue(RAW_EXPORT_ACTION, Instant::now());
+    Ok(RawExportConsent {
+        consent,
+        expires_in_ms: CONFIRM_TOKEN_TTL.as_millis() as u64,
+        max_window_hours,
+    })
+}
+}
+
+tracked! {
+/// 调试用：将 [start_ms, end_ms) 内的原始按键事件解码为 JSONL（时间戳、应用、按键、修饰键、按下/抬起）写入 dest_path，
+/// 并在应用日志中记录本次导出（记录未能写入时删除导出文件并返回错误）。consent 需为 request_raw_export_consent 返回的同意串；
+/// 时间窗口不能超过配置的最长小时数。dest_path 的限制同 SVG 导出。需以 debug-tools 特性构建。
+#[tauri::command]
+pub(crate) fn export_raw_events(
+    state: State<AppState>,
+    start_ms: i64,
+    end_ms: i64,
+    dest_path: String,
+    consent: String,
+) -> Result<RawExportReport, String> {
+    ensure_debug_tools_enabled()?;
+    state
+        .confirm_tokens
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?
+        .consume(&consent, RAW_EXPORT_ACTION, Instant::now())?;
+    let max_window_hours = state
+        .config
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?
+        .effective()
+        .raw_export_max_window_hours;
+    let dest_path = export_roots(&state).resolve(&dest_path)?;
+    let (records, app_log_path) = {
+        let locked = state
+            .inner
+            .lock()
+            .map_err(|_| "state lock failed".to_string())?;
+        let records = snapshot_raw_events(
+            &locked,
+            start_ms,
+            end_ms,
+            max_window_hours as i64 * 3_600_000,
+        )?;
+        (records, locked.app_log_path.clone())
+    };
+    let report = write_raw_events(&records, &dest_path, start_ms, end_ms)?;
+    let audit = format!(
+        "raw events exported: {} events [{}, {}) -> {}",
+        report.events, start_ms, end_ms, report.path
+    );
+    // No raw export is kept without its audit entry on disk.
+    if let Err(err) = collector::append_app_log(&app_log_path, &audit)
+        .and_then(|_| collector::flush_app_log(&app_log_path))
+    {
+        let _ = fs::remove_file(&dest_path);
+        return Err(format!(
+            "audit entry could not be written, export removed: {}",
+            err
+        ));
+    }
+    Ok(report)
+}
+}
+
+tracked! {
+/// 更新采集暂停状态（暂停时立即写盘），通知前端与托盘后返回最新快照。
+#[tauri::command]
+pub(crate) fn update_paused(app: AppHandle, state: State<AppState>, paused: bool) -> StatsSnapshot {
+    set_paused_and_notify(&app, "command", |_| paused);
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 暂停或恢复快捷键统计（按键数与打字时长照常记录），持久化配置并刷新托盘后返回最新快照。
+#[tauri::command]
+pub(crate) fn update_shortcuts_paused(
+    app: AppHandle,
+    state: State<AppState>,
+    shortcuts_paused: bool,
+) -> StatsSnapshot {
+    set_shortcuts_paused_and_notify(&app, "command", |_| shortcuts_paused);
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 切换“忽略组合键”设置，持久化配置后返回最新快照。
+#[tauri::command]
+pub(crate) fn update_ignore_key_combos(
+    state: State<AppState>,
+    ignore_key_combos: bool,
+) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_ignore_key_combos(ignore_key_combos);
+        if let Ok(mut config) = state.config.lock() {
+            config.ignore_key_combos = ignore_key_combos;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            if ignore_key_combos {
+                "ignore key combos enabled"
+            } else {
+                "ignore key combos disabled"
+            },
+        );
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 切换“统计未完成组合键”设置，持久化配置后返回最新快照（macOS 监听范围重启后生效）。
+#[tauri::command]
+pub(crate) fn update_track_chord_aborts(
+    state: State<AppState>,
+    track_chord_aborts: bool,
+) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_track_chord_aborts(track_chord_aborts);
+        if let Ok(mut config) = state.config.lock() {
+            config.track_chord_aborts = track_chord_aborts;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            if track_chord_aborts {
+                "chord abort tracking enabled"
+            } else {
+                "chord abort tracking disabled"
+            },
+        );
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 切换“识别密码类输入框”设置（仅 macOS，需辅助功能权限），持久化配置后返回最新快照。
+#[tauri::command]
+pub(crate) fn update_ax_sensitive_detection(
+    state: State<AppState>,
+    ax_sensitive_detection: bool,
+) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_ax_sensitive_detection(ax_sensitive_detection);
+        if let Ok(mut config) = state.config.lock() {
+            config.ax_sensitive_detection = ax_sensitive_detection;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            if ax_sensitive_detection {
+                "sensitive field detection enabled"
+            } else {
+                "sensitive field detection disabled"
+            },
+        );
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 切换“采集期间阻止 App Nap”设置，立即获取或释放活动断言，持久化配置后返回最新快照。
+#[tauri::command]
+pub(crate) fn update_prevent_app_nap(
+    state: State<AppState>,
+    prevent_app_nap: bool,
+) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_prevent_app_nap(prevent_app_nap);
+        if let Ok(mut config) = state.config.lock() {
+            config.prevent_app_nap = prevent_app_nap;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            if prevent_app_nap {
+                "app nap prevention enabled"
+            } else {
+                "app nap prevention disabled"
+            },
+        );
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 切换是否在数据目录写入 status.json 供脚本读取，持久化配置后返回最新快照；关闭时删除该文件。
+#[tauri::command]
+pub(crate) fn update_write_status_file(
+    state: State<AppState>,
+    write_status_file: bool,
+) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_write_status_file(write_status_file);
+        if let Ok(mut config) = state.config.lock() {
+            config.write_status_file = write_status_file;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            if write_status_file {
+                "status file enabled"
+            } else {
+                "status file disabled"
+            },
+        );
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 开关 AppleScript 控制（暂停 / 恢复 / 切换与读取今日统计），持久化配置后返回最新快照。
+#[tauri::command]
+pub(crate) fn update_applescript_enabled(
+    state: State<AppState>,
+    applescript_enabled: bool,
+) -> StatsSnapshot {
+    if let Ok(mut config) = state.config.lock() {
+        config.applescript_enabled = applescript_enabled;
+        let _ = save_app_config(&state.config_path, &config);
+    }
+    let _ = collector::append_app_log(
+        &state.app_log_path,
+        if applescript_enabled {
+            "applescript control enabled"
+        } else {
+            "applescript control disabled"
+        },
+    );
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 更新采集轮询、刷盘、会话间隔、托盘刷新周期与重负载磁盘任务的空闲等待、最长推迟时间（秒）。
+/// 取值须在允许范围内（轮询 1–60、刷盘 5–3600、会话间隔 1–600、托盘 1–60、空闲等待 5–600、
+/// 最长推迟 60–21600），立即作用于运行中的循环并持久化。
+#[tauri::command]
+pub(crate) fn update_timing_settings(
+    state: State<AppState>,
+    collector_tick_interval_secs: u64,
+    flush_interval_secs: u64,
+    session_gap_secs: u64,
+    tray_update_interval_secs: u64,
+    maintenance_idle_secs: u64,
+    maintenance_max_defer_secs: u64,
+) -> Result<StatsSnapshot, String> {
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    let mut config = state
+        .config
+        .lock()
+        .map_err(|_| "config lock failed".to_string())?;
+    let mut updated = config.clone();
+    updated.set_timing_settings(
+        collector_tick_interval_secs,
+        flush_interval_secs,
+        session_gap_secs,
+        tray_update_interval_secs,
+        maintenance_idle_secs,
+        maintenance_max_defer_secs,
+    )?;
+    locked.set_timing_intervals(
+        updated.collector_tick_interval(),
+        updated.flush_interval(),
+        updated.session_gap(),
+        updated.tray_update_interval(),
+    );
+    locked.set_maintenance_window(updated.maintenance_idle(), updated.maintenance_max_defer());
+    *config = updated;
+    let _ = save_app_config(&state.config_path, &config);
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        &format!(
+            "timing settings changed: tick {}s, flush {}s, session gap {}s, tray {}s, \
+             maintenance idle {}s, max defer {}s",
+            collector_tick_interval_secs,
+            flush_interval_secs,
+            session_gap_secs,
+            tray_update_interval_secs,
+            maintenance_idle_secs,
+            maintenance_max_defer_secs
+        ),
+    );
+    Ok(locked.snapshot())
+}
+}
+
+tracked! {
+/// 开关按键事件 chunk 记录（快捷键汇总不受影响），持久化配置后返回最新快照。
+/// 关闭时先结束当前 chunk，已有 chunk 保留在内存中，重新开启后继续落盘。
+#[tauri::command]
+pub(crate) fn update_record_event_chunks(
+    state: State<AppState>,
+    record_event_chunks: bool,
+) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_record_event_chunks(record_event_chunks);
+        if let Ok(mut config) = state.config.lock() {
+            config.record_event_chunks = record_event_chunks;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            if record_event_chunks {
+                "event chunk recording enabled"
+            } else {
+                "event chunk recording disabled"
+            },
+        );
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 更新快捷键统计规则配置并返回最新快照。
+#[tauri::command]
+pub(crate) fn update_shortcut_rules(
+    state: State<AppState>,
+    require_cmd_or_ctrl: bool,
+    allow_alt_only: bool,
+    min_modifiers: u8,
+    allowlist: Vec<String>,
+    blocklist: Vec<String>,
+) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_shortcut_rules(
+            require_cmd_or_ctrl,
+            allow_alt_only,
+            min_modifiers,
+            &allowlist,
+            &blocklist,
+        );
+        if let Ok(mut config) = state.config.lock() {
+            config.set_shortcut_rules(ShortcutRules {
+                require_cmd_or_ctrl,
+                allow_alt_only,
+                min_modifiers,
+                allowlist,
+                blocklist,
+            });
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(&locked.app_log_path, "shortcut rules updated");
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 更新按 Shift 输入的数字与符号键的命名方式（physical / produced），
+/// 并把已有的快捷键统计与白名单、黑名单迁移到新的命名。
+#[tauri::command]
+pub(crate) fn update_symbol_key_names(
+    state: State<AppState>,
+    names: String,
+) -> Result<StatsSnapshot, String> {
+    let names = SymbolKeyNames::from_str(&names)
+        .ok_or_else(|| format!("unknown symbol key names: {}", names))?;
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    let renamed = locked.set_symbol_key_names(names);
+    if let Ok(mut config) = state.config.lock() {
+        config.symbol_key_names = names;
+        let _ = save_app_config(&state.config_path, &config);
+    }
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        &format!(
+            "symbol key names set to {} ({} shortcut ids renamed)",
+            names.as_str(),
+            renamed
+        ),
+    );
+    Ok(locked.snapshot())
+}
+}
+
+tracked! {
+/// 导出当前快捷键统计规则为可分享的 JSON 预设。
+#[tauri::command]
+pub(crate) fn export_shortcut_rules(state: State<AppState>) -> Result<String, String> {
+    let config = state
+        .config
+        .lock()
+        .map_err(|_| "config lock failed".to_string())?;
+    serde_json::to_string_pretty(&ShortcutRulesPreset::from_config(&config))
+        .map_err(|e| e.to_string())
+}
+}
+
+tracked! {
+/// 导入快捷键统计规则预设：`merge` 为 false 时整体替换，为 true 时合并列表且以导入内容为准；
+/// 返回导入前后的差异。
+#[tauri::command]
+pub(crate) fn import_shortcut_rules(
+    state: State<AppState>,
+    json: String,
+    merge: bool,
+) -> Result<ShortcutRulesDiff, String> {
+    let incoming = ShortcutRulesPreset::parse(&json)?;
+    let mut locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    let mut config = state
+        .config
+        .lock()
+        .map_err(|_| "config lock failed".to_string())?;
+    let diff = ShortcutRulesPreset::from_config(&config).import(&incoming, merge);
+    let rules = &diff.rules;
+    locked.set_shortcut_rules(
+        rules.require_cmd_or_ctrl,
+        rules.allow_alt_only,
+        rules.min_modifiers,
+        &rules.allowlist,
+        &rules.blocklist,
+    );
+    rules.write_to_config(&mut config);
+    save_app_config(&state.config_path, &config)?;
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        if merge {
+            "shortcut rules preset merged"
+        } else {
+            "shortcut rules preset imported"
+        },
+    );
+    Ok(diff)
+}
+}
+
+tracked! {
+#[tauri::command]
+pub(crate) fn get_running_apps() -> Vec<RunningAppInfo> {
+    running_apps()
+}
+}
+
+tracked! {
+#[tauri::command]
+pub(crate) fn update_app_exclusion_list(
+    state: State<AppState>,
+    app: AppHandle,
+    bundle_ids: Vec<String>,
+) -> StatsSnapshot {
+    let bundle_ids: Vec<String> = BundleId::parse_list(&bundle_ids)
+        .into_iter()
+        .map(|bundle_id| bundle_id.as_str().to_string())
+        .collect();
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_excluded_bundle_ids(&bundle_ids);
+        if let Ok(mut config) = state.config.lock() {
+            config.set_excluded_bundle_ids(locked.excluded_bundle_ids());
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(&locked.app_log_path, "app exclusion list updated");
+        notify_capture_state_changed(&app, &locked.today_summary());
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+#[tauri::command]
+pub(crate) fn add_app_exclusion(
+    state: State<AppState>,
+    app: AppHandle,
+    bundle_id: String,
+) -> StatsSnapshot {
+    let Ok(bundle_id) = BundleId::parse(&bundle_id) else {
+        return snapshot_of(&state);
+    };
+    if let Ok(mut locked) = state.inner.lock() {
+        let added = locked.add_excluded_bundle_id(bundle_id.as_str());
+        if added {
+            if let Ok(mut config) = state.config.lock() {
+                config.set_excluded_bundle_ids(locked.excluded_bundle_ids());
+                let _ = save_app_config(&state.config_path, &config);
+            }
+            let _ = collector::append_app_log(
+                &locked.app_log_path,
+                &format!("bundle id added to exclusion list: {}", bundle_id.as_str()),
+            );
+        }
+        notify_capture_state_changed(&app, &locked.today_summary());
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+#[tauri::command]
+pub(crate) fn remove_app_exclusion(
+    state: State<AppState>,
+    app: AppHandle,
+    bundle_id: String,
+) -> StatsSnapshot {
+    let Ok(bundle_id) = BundleId::parse(&bundle_id) else {
+        return snapshot_of(&state);
+    };
+    if let Ok(mut locked) = state.inner.lock() {
+        let removed = locked.remove_excluded_bundle_id(bundle_id.as_str());
+        if removed {
+            if let Ok(mut config) = state.config.lock() {
+                config.set_excluded_bundle_ids(locked.excluded_bundle_ids());
+                let _ = save_app_config(&state.config_path, &config);
+            }
+            let _ = collector::append_app_log(
+                &locked.app_log_path,
+                &format!(
+                    "bundle id removed from exclusion list: {}",
+                    bundle_id.as_str()
+                ),
+            );
+        }
+        notify_capture_state_changed(&app, &locked.today_summary());
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+#[tauri::command]
+pub(crate) fn resolve_bundle_id_from_app_path(path: String) -> Option<String> {
+    let path = app_bundle_path(&path).ok()?;
+    bundle_id_from_app_path(&path.to_string_lossy())
+}
+}
+
+tracked! {
+#[tauri::command]
+pub(crate) fn dismiss_one_password_suggestion(state: State<AppState>) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_one_password_suggestion_pending(false);
+        if let Ok(mut config) = state.config.lock() {
+            config.one_password_suggestion_handled = true;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+#[tauri::command]
+pub(crate) fn accept_one_password_suggestion(
+    state: State<AppState>,
+    app: AppHandle,
+) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        let _ = locked.add_excluded_bundle_id("com.1password.1password");
+        locked.set_one_password_suggestion_pending(false);
+        if let Ok(mut config) = state.config.lock() {
+            config.set_excluded_bundle_ids(locked.excluded_bundle_ids());
+            config.one_password_suggestion_handled = true;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            "1Password added to exclusion list via suggestion",
+        );
+        notify_capture_state_changed(&app, &locked.today_summary());
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 接受“非打字应用”建议：经忽略列表的常规路径加入该应用，并记为已处理。
+#[tauri::command]
+pub(crate) fn accept_non_typing_suggestion(
+    state: State<AppState>,
+    app: AppHandle,
+    bundle_id: String,
+) -> StatsSnapshot {
+    let Ok(bundle_id) = BundleId::parse(&bundle_id) else {
+        return snapshot_of(&state);
+    };
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.resolve_non_typing_suggestion(bundle_id.as_str());
+        let _ = locked.add_excluded_bundle_id(bundle_id.as_str());
+        if let Ok(mut config) = state.config.lock() {
+            config.set_excluded_bundle_ids(locked.excluded_bundle_ids());
+            config.non_typing_suggestions_handled = locked.non_typing_handled();
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            &format!(
+                "bundle id added to exclusion list via non-typing suggestion: {}",
+                bundle_id.as_str()
+            ),
+        );
+        notify_capture_state_changed(&app, &locked.today_summary());
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 忽略“非打字应用”建议，记为已处理后不再提示该应用。
+#[tauri::command]
+pub(crate) fn dismiss_non_typing_suggestion(
+    state: State<AppState>,
+    bundle_id: String,
+) -> StatsSnapshot {
+    let Ok(bundle_id) = BundleId::parse(&bundle_id) else {
+        return snapshot_of(&state);
+    };
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.resolve_non_typing_suggestion(bundle_id.as_str());
+        if let Ok(mut config) = state.config.lock() {
+            config.non_typing_suggestions_handled = locked.non_typing_handled();
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 更新菜单栏显示模式，立即应用到托盘并返回最新快照。
+#[tauri::command]
+pub(crate) fn update_menu_bar_display_mode(
+    state: State<AppState>,
+    app: AppHandle,
+    mode: String,
+) -> StatsSnapshot {
+    let mode = match MenuBarDisplayMode::from_str(&mode) {
+        Some(mode) => mode,
+        None => return snapshot_of(&state),
+    };
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_menu_bar_display_mode(mode);
+        if let Ok(mut config) = state.config.lock() {
+            config.menu_bar_display_mode = mode;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            &format!("menu bar display mode changed: {}", mode.as_str()),
+        );
+        apply_menu_bar_mode_immediately(&app, &locked.today_summary());
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 更新托盘图标左键单击行为并返回最新快照。
+#[tauri::command]
+pub(crate) fn update_tray_left_click_action(
+    state: State<AppState>,
+    action: String,
+) -> StatsSnapshot {
+    let action = match TrayLeftClickAction::from_str(&action) {
+        Some(action) => action,
+        None => return snapshot_of(&state),
+    };
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_tray_left_click_action(action);
+        if let Ok(mut config) = state.config.lock() {
+            config.tray_left_click_action = action;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            &format!("tray left click action changed: {}", action.as_str()),
+        );
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 更新“启动时隐藏主面板”开关并返回最新快照。
+#[tauri::command]
+pub(crate) fn update_start_hidden(state: State<AppState>, start_hidden: bool) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_start_hidden(start_hidden);
+        if let Ok(mut config) = state.config.lock() {
+            config.start_hidden = start_hidden;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            if start_hidden {
+                "start hidden enabled"
+            } else {
+                "start hidden disabled"
+            },
+        );
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 更新应用汇总中单独列出的最少按键数（0 表示全部列出）并返回最新快照；只影响展示，不修改已保存的数据。
+#[tauri::command]
+pub(crate) fn update_min_keys_to_display(
+    state: State<AppState>,
+    min_keys_to_display: u64,
+) -> StatsSnapshot {
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_min_keys_to_display(min_keys_to_display);
+        if let Ok(mut config) = state.config.lock() {
+            config.min_keys_to_display = min_keys_to_display;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            &format!("min keys to display changed: {}", min_keys_to_display),
+        );
+        return locked.snapshot();
+    }
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 更新全局窗口标题记录方式（full / hidden），未知取值返回错误。
+#[tauri::command]
+pub(crate) fn update_window_title_mode(
+    state: State<AppState>,
+    mode: String,
+) -> Result<StatsSnapshot, String> {
+    let mode = WindowTitleMode::from_str(&mode)
+        .ok_or_else(|| format!("unknown window title mode: {}", mode))?;
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    locked.set_window_title_mode(mode);
+    if let Ok(mut config) = state.config.lock() {
+        config.window_title_mode = mode;
+        let _ = save_app_config(&state.config_path, &config);
+    }
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        &format!("window title mode changed: {}", mode.as_str()),
+    );
+    Ok(locked.snapshot())
+}
+}
+
+tracked! {
+/// 为单个应用设置窗口标题记录方式（覆盖全局设置），未知取值返回错误。
+#[tauri::command]
+pub(crate) fn set_app_title_privacy(
+    state: State<AppState>,
+    bundle_id: String,
+    mode: String,
+) -> Result<StatsSnapshot, String> {
+    let mode = WindowTitleMode::from_str(&mode)
+        .ok_or_else(|| format!("unknown window title mode: {}", mode))?;
+    let bundle_id = BundleId::parse(&bundle_id)?;
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    locked.set_app_title_privacy(bundle_id.as_str(), mode);
+    if let Ok(mut config) = state.config.lock() {
+        config.title_privacy_overrides = locked.title_privacy_overrides();
+        let _ = save_app_config(&state.config_path, &config);
+    }
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        &format!(
+            "title privacy set: {} -> {}",
+            bundle_id.as_str(),
+            mode.as_str()
+        ),
+    );
+    Ok(locked.snapshot())
+}
+}
+
+tracked! {
+/// 移除单个应用的窗口标题记录覆盖，恢复使用全局设置。
 #[tauri::command]
-pub(crate) fn get_snapshot(state: State<AppState>) -> StatsSnapshot {
-    if let Ok(locked) = state.inner.lock() {
+pub(crate) fn remove_app_title_privacy(state: State<AppState>, bundle_id: String) -> StatsSnapshot {
+    let Ok(bundle_id) = BundleId::parse(&bundle_id) else {
+        return snapshot_of(&state);
+    };
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.remove_app_title_privacy(bundle_id.as_str());
+        if let Ok(mut config) = state.config.lock() {
+            config.title_privacy_overrides = locked.title_privacy_overrides();
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            &format!("title privacy removed: {}", bundle_id.as_str()),
+        );
         return locked.snapshot();
     }
-    StatsSnapshot {
-        rows: vec![],
-        paused: false,
-        auto_paused: false,
-        auto_pause_reason: None,
-        keyboard_active: false,
-        ignore_key_combos: false,
-        excluded_bundle_ids: vec![],
-        one_password_suggestion_pending: false,
-        tray_display_mode: MenuBarDisplayMode::default().as_str().to_string(),
-        last_error: Some("state lock failed".to_string()),
-        log_path: "".to_string(),
-        shortcut_stats: vec![],
-    }
+    snapshot_of(&state)
+}
 }
 
-/// 按时间范围返回快捷键排行榜（today / yesterday / 7d）。
+tracked! {
+/// 将某应用的若干窗口标题合并为一个标题：改写已有统计行（与目标标题重复的行累加）并重写受影响的日文件，
+/// 同时记录标题别名，之后采集到的旧标题直接记为目标标题。返回被改写的行数。
 #[tauri::command]
-pub(crate) fn get_shortcut_stats_by_range(
+pub(crate) fn merge_window_titles(
     state: State<AppState>,
-    range: String,
-) -> Vec<ShortcutStatRow> {
-    if let Ok(locked) = state.inner.lock() {
-        return snapshot_shortcut_rows_by_range(&locked, &range);
+    bundle_id: String,
+    from_titles: Vec<String>,
+    to_title: String,
+) -> Result<u64, String> {
+    let bundle_id = BundleId::parse(&bundle_id)?;
+    let to_title = WindowTitle::parse(&to_title)?.into_string();
+    let from_titles = from_titles
+        .iter()
+        .map(|title| WindowTitle::parse(title).map(WindowTitle::into_string))
+        .collect::<Result<Vec<String>, String>>()?;
+    if from_titles.is_empty() {
+        return Err("no window titles to merge".to_string());
+    }
+    let mut locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    let merged = locked.merge_window_titles(bundle_id.as_str(), &from_titles, &to_title);
+    if let Ok(mut config) = state.config.lock() {
+        config.window_title_aliases = locked.window_title_aliases();
+        let _ = save_app_config(&state.config_path, &config);
     }
-    vec![]
+    merged
+}
 }
 
-/// 按时间范围返回 Top5 按键（today / yesterday / 7d，聚合展示）。
+tracked! {
+/// 设置单个应用的每日活跃打字时长目标（分钟，1–1440），持久化配置后返回最新快照。
 #[tauri::command]
-pub(crate) fn get_daily_top_keys_by_range(
+pub(crate) fn update_app_time_goal(
     state: State<AppState>,
-    range: String,
-) -> Vec<KeyUsageRow> {
-    if let Ok(locked) = state.inner.lock() {
-        return snapshot_top_keys_by_range(&locked, &range);
+    bundle_id: String,
+    target_min: u64,
+) -> Result<StatsSnapshot, String> {
+    let bundle_id = BundleId::parse(&bundle_id)?;
+    if !(1..=MAX_APP_TIME_GOAL_MIN).contains(&target_min) {
+        return Err(format!(
+            "app time goal must be between 1 and {} minutes",
+            MAX_APP_TIME_GOAL_MIN
+        ));
+    }
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    locked.set_app_time_goal(bundle_id.as_str(), target_min);
+    if let Ok(mut config) = state.config.lock() {
+        config.app_time_goals = locked.app_time_goals();
+        let _ = save_app_config(&state.config_path, &config);
     }
-    vec![]
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        &format!(
+            "app time goal set: {} -> {} min",
+            bundle_id.as_str(),
+            target_min
+        ),
+    );
+    Ok(locked.snapshot())
+}
 }
 
-/// 更新采集暂停状态，并返回最新快照。
+tracked! {
+/// 移除单个应用的每日活跃打字时长目标及其当日通知标记。
 #[tauri::command]
-pub(crate) fn update_paused(state: State<AppState>, paused: bool) -> StatsSnapshot {
+pub(crate) fn remove_app_time_goal(state: State<AppState>, bundle_id: String) -> StatsSnapshot {
+    let Ok(bundle_id) = BundleId::parse(&bundle_id) else {
+        return snapshot_of(&state);
+    };
     if let Ok(mut locked) = state.inner.lock() {
-        locked.set_paused(paused);
+        locked.remove_app_time_goal(bundle_id.as_str());
+        if let Ok(mut config) = state.config.lock() {
+            config.app_time_goals = locked.app_time_goals();
+            config.app_time_goals_notified = locked.app_goal_notified();
+            let _ = save_app_config(&state.config_path, &config);
+        }
         let _ = collector::append_app_log(
             &locked.app_log_path,
-            if paused {
-                "paused via command"
-            } else {
-                "resumed via command"
-            },
+            &format!("app time goal removed: {}", bundle_id.as_str()),
         );
         return locked.snapshot();
     }
-    get_snapshot(state)
+    snapshot_of(&state)
+}
 }
 
-/// 切换“忽略组合键”设置，持久化配置后返回最新快照。
+tracked! {
+/// 切换应用时长目标达成通知，持久化配置后返回最新快照。
 #[tauri::command]
-pub(crate) fn update_ignore_key_combos(
+pub(crate) fn update_app_time_goal_notifications(
     state: State<AppState>,
-    ignore_key_combos: bool,
+    enabled: bool,
 ) -> StatsSnapshot {
     if let Ok(mut locked) = state.inner.lock() {
-        locked.set_ignore_key_combos(ignore_key_combos);
+        locked.set_app_time_goal_notifications(enabled);
         if let Ok(mut config) = state.config.lock() {
-            config.ignore_key_combos = ignore_key_combos;
+            config.app_time_goal_notifications = enabled;
             let _ = save_app_config(&state.config_path, &config);
         }
         let _ = collector::append_app_log(
             &locked.app_log_path,
-            if ignore_key_combos {
-                "ignore key combos enabled"
+            if enabled {
+                "app time goal notifications enabled"
             } else {
-                "ignore key combos disabled"
+                "app time goal notifications disabled"
             },
         );
         return locked.snapshot();
     }
-    get_snapshot(state)
+    snapshot_of(&state)
+}
 }
 
-/// 更新快捷键统计规则配置并返回最新快照。
+tracked! {
+/// 切换每周一的周对比通知，持久化配置后返回最新快照。
 #[tauri::command]
-pub(crate) fn update_shortcut_rules(
+pub(crate) fn update_weekly_comparison_notifications(
     state: State<AppState>,
-    require_cmd_or_ctrl: bool,
-    allow_alt_only: bool,
-    min_modifiers: u8,
-    allowlist: Vec<String>,
-    blocklist: Vec<String>,
+    enabled: bool,
 ) -> StatsSnapshot {
     if let Ok(mut locked) = state.inner.lock() {
-        locked.set_shortcut_rules(
-            require_cmd_or_ctrl,
-            allow_alt_only,
-            min_modifiers,
-            &allowlist,
-            &blocklist,
-        );
+        locked.set_weekly_comparison_notifications(enabled);
         if let Ok(mut config) = state.config.lock() {
-            config.shortcut_require_cmd_or_ctrl = require_cmd_or_ctrl;
-            config.shortcut_allow_alt_only = allow_alt_only;
-            config.shortcut_min_modifiers = min_modifiers.max(1);
-            config.shortcut_allowlist = allowlist
-                .iter()
-                .map(|v| v.trim().to_ascii_lowercase())
-                .filter(|v| !v.is_empty())
-                .collect();
-            config.shortcut_allowlist.sort();
-            config.shortcut_allowlist.dedup();
-            config.shortcut_blocklist = blocklist
-                .iter()
-                .map(|v| v.trim().to_ascii_lowercase())
-                .filter(|v| !v.is_empty())
-                .collect();
-            config.shortcut_blocklist.sort();
-            config.shortcut_blocklist.dedup();
+            config.weekly_comparison_notifications = enabled;
             let _ = save_app_config(&state.config_path, &config);
         }
-        let _ = collector::append_app_log(&locked.app_log_path, "shortcut rules updated");
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            if enabled {
+                "weekly comparison notifications enabled"
+            } else {
+                "weekly comparison notifications disabled"
+            },
+        );
         return locked.snapshot();
     }
-    get_snapshot(state)
+    snapshot_of(&state)
+}
 }
 
+tracked! {
+/// 对比 week_start（YYYY-MM-DD，取所在周的周一，默认上周）所在周与前一周的按键数、活跃时长、最常用应用与增长最多的快捷键；
+/// summary 为与周一通知相同的概述，localized 为 false 时使用英文（默认中文）。
 #[tauri::command]
-pub(crate) fn get_running_apps() -> Vec<RunningAppInfo> {
-    running_apps()
+pub(crate) fn get_weekly_comparison(
+    state: State<AppState>,
+    week_start: Option<String>,
+    localized: Option<bool>,
+) -> Result<WeeklyComparison, String> {
+    let locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    snapshot_weekly_comparison(&locked, week_start.as_deref(), localized.unwrap_or(true))
+}
 }
 
+tracked! {
+/// 更新每日结束后的自动导出设置；启用时目标目录必须是绝对路径，限制同 SVG 导出，保存的是解析符号链接后的目录。
+/// script_path 须为已存在的普通文件，且是本次运行中通过 `pick_export_path` 选择的文件或位于数据目录内。
+/// sign 为 true 时导出文件加入本数据集的签名哈希链（缺省为 false）。
 #[tauri::command]
-pub(crate) fn update_app_exclusion_list(
+pub(crate) fn update_auto_export(
     state: State<AppState>,
-    bundle_ids: Vec<String>,
-) -> StatsSnapshot {
-    if let Ok(mut locked) = state.inner.lock() {
-        locked.set_excluded_bundle_ids(&bundle_ids);
-        if let Ok(mut config) = state.config.lock() {
-            config.excluded_bundle_ids = bundle_ids
-                .iter()
-                .map(|v| v.trim().to_ascii_lowercase())
-                .filter(|v| !v.is_empty())
-                .collect();
-            let _ = save_app_config(&state.config_path, &config);
+    enabled: bool,
+    format: String,
+    dest_dir: String,
+    script_path: Option<String>,
+    sign: Option<bool>,
+) -> Result<StatsSnapshot, String> {
+    let format = AutoExportFormat::from_str(&format)
+        .ok_or_else(|| format!("unknown auto export format: {}", format))?;
+    let mut dest_dir = dest_dir.trim().to_string();
+    if enabled {
+        if !Path::new(&dest_dir).is_absolute() {
+            return Err("auto export directory must be an absolute path".to_string());
         }
-        let _ = collector::append_app_log(&locked.app_log_path, "app exclusion list updated");
-        return locked.snapshot();
+        dest_dir = export_roots(&state)
+            .resolve(&dest_dir)?
+            .to_string_lossy()
+            .to_string();
+    }
+    let script_path = match script_path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => {
+            let picked_files = state
+                .picked_files
+                .lock()
+                .map_err(|_| "state lock failed".to_string())?
+                .clone();
+            let path = export_script_path(&path, &state.data_dir, &picked_files)?;
+            Some(path.to_string_lossy().to_string())
+        }
+        None => None,
+    };
+    let auto_export = AutoExportConfig {
+        enabled,
+        format,
+        dest_dir,
+        script_path,
+        sign: sign.unwrap_or(false),
+    };
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    locked.set_auto_export(auto_export.clone());
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        &format!(
+            "auto export {}: {} -> {}",
+            if enabled { "enabled" } else { "disabled" },
+            format.as_str(),
+            auto_export.dest_dir
+        ),
+    );
+    if let Ok(mut config) = state.config.lock() {
+        config.auto_export = auto_export;
+        let _ = save_app_config(&state.config_path, &config);
     }
-    get_snapshot(state)
+    Ok(locked.snapshot())
+}
 }
 
+tracked! {
+/// 立即把超过保留天数的分钟级明细按小时汇总，返回本次汇总的天数。
 #[tauri::command]
-pub(crate) fn add_app_exclusion(state: State<AppState>, bundle_id: String) -> StatsSnapshot {
-    if let Ok(mut locked) = state.inner.lock() {
-        let added = locked.add_excluded_bundle_id(&bundle_id);
-        if added {
-            if let Ok(mut config) = state.config.lock() {
-                let normalized = bundle_id.trim().to_ascii_lowercase();
-                if !config
-                    .excluded_bundle_ids
-                    .iter()
-                    .any(|v| v.eq_ignore_ascii_case(&normalized))
-                {
-                    config.excluded_bundle_ids.push(normalized.clone());
-                }
-                config.excluded_bundle_ids.sort();
-                config.excluded_bundle_ids.dedup();
-                let _ = save_app_config(&state.config_path, &config);
-            }
-            let _ = collector::append_app_log(
-                &locked.app_log_path,
-                &format!("bundle id added to exclusion list: {}", bundle_id),
-            );
+pub(crate) fn rollup_old_stats_now(state: State<AppState>) -> Result<usize, String> {
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    let days = locked.rollup_old_stats()?;
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        &format!(
+            "rolled up {} days of stats to hour resolution (manual)",
+            days
+        ),
+    );
+    Ok(days)
+}
+}
+
+tracked! {
+/// 立即把上个月之前的每日明细与分析文件打包进按月的 zip 归档（archive-YYYY-MM.zip），校验后删除原文件，
+/// 返回本次归档的月份、文件数与字节数。归档后的数据仍会被加载与导出。
+#[tauri::command]
+pub(crate) fn archive_old_months_now(state: State<AppState>) -> Result<ArchiveReport, String> {
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    let report = locked.archive_old_months()?;
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        &format!(
+            "archived {} daily files ({} bytes) into {} monthly archives (manual)",
+            report.files,
+            report.bytes,
+            report.months.len()
+        ),
+    );
+    Ok(report)
+}
+}
+
+tracked! {
+/// 清除已保存的主面板位置与尺寸，恢复默认大小并居中。
+#[tauri::command]
+pub(crate) fn reset_window_position(state: State<AppState>, app: AppHandle) -> Result<(), String> {
+    match state.config.lock() {
+        Ok(mut config) => {
+            config.main_window_bounds = None;
+            save_app_config(&state.config_path, &config)?;
         }
-        return locked.snapshot();
+        Err(_) => return Err("config lock failed".to_string()),
     }
-    get_snapshot(state)
+    let Some(window) = app.get_webview_window("main") else {
+        return Err("main window not found".to_string());
+    };
+    window
+        .set_size(LogicalSize::new(
+            MAIN_WINDOW_DEFAULT_WIDTH,
+            MAIN_WINDOW_DEFAULT_HEIGHT,
+        ))
+        .map_err(|err| err.to_string())?;
+    window.center().map_err(|err| err.to_string())
+}
 }
 
+tracked! {
+/// 返回当前生效的完整配置（已归一化并补齐默认值，敏感字段脱敏），以及各配置分组是否为用户修改。
 #[tauri::command]
-pub(crate) fn remove_app_exclusion(state: State<AppState>, bundle_id: String) -> StatsSnapshot {
-    if let Ok(mut locked) = state.inner.lock() {
-        let removed = locked.remove_excluded_bundle_id(&bundle_id);
-        if removed {
-            if let Ok(mut config) = state.config.lock() {
-                config
-                    .excluded_bundle_ids
-                    .retain(|v| !v.eq_ignore_ascii_case(bundle_id.as_str()));
-                let _ = save_app_config(&state.config_path, &config);
-            }
+pub(crate) fn get_config(state: State<AppState>) -> Result<EffectiveConfig, String> {
+    let config = state
+        .config
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    EffectiveConfig::from_config(&config)
+}
+}
+
+tracked! {
+/// 同意或撤回匿名使用统计。同意时上报地址必须是 http/https 地址（可为空，表示只在本地累计）；
+/// 撤回时立即清空本地计数。持久化配置后返回最新快照。
+#[tauri::command]
+pub(crate) fn set_telemetry_consent(
+    state: State<AppState>,
+    enabled: bool,
+    endpoint: String,
+) -> Result<StatsSnapshot, String> {
+    let endpoint = endpoint.trim().to_string();
+    if !endpoint.is_empty() && !telemetry::is_valid_endpoint(&endpoint) {
+        return Err("telemetry endpoint must be an http(s) URL".to_string());
+    }
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    {
+        let Ok(mut telemetry) = state.telemetry.lock() else {
+            return Err("state lock failed".to_string());
+        };
+        telemetry.set_consent(enabled, now_ms);
+        if let Err(err) = telemetry.save(now_ms) {
             let _ = collector::append_app_log(
-                &locked.app_log_path,
-                &format!("bundle id removed from exclusion list: {}", bundle_id),
+                &state.app_log_path,
+                &format!("telemetry counters save failed: {}", err),
             );
         }
-        return locked.snapshot();
     }
-    get_snapshot(state)
+    if let Ok(mut config) = state.config.lock() {
+        config.telemetry_enabled = enabled;
+        config.telemetry_endpoint = endpoint;
+        let _ = save_app_config(&state.config_path, &config);
+    }
+    let _ = collector::append_app_log(
+        &state.app_log_path,
+        if enabled {
+            "telemetry consent given"
+        } else {
+            "telemetry consent withdrawn"
+        },
+    );
+    let Ok(locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    Ok(locked.snapshot())
+}
 }
 
+tracked! {
+/// 预览下一次匿名使用统计将上报的完整内容（命令调用次数、应用版本、系统与功能开关）及上报时间。
 #[tauri::command]
-pub(crate) fn resolve_bundle_id_from_app_path(path: String) -> Option<String> {
-    bundle_id_from_app_path(&path)
+pub(crate) fn get_telemetry_preview(state: State<AppState>) -> Result<TelemetryPreview, String> {
+    let config = state
+        .config
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?
+        .clone();
+    let telemetry = state
+        .telemetry
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    Ok(telemetry.preview(&config))
+}
 }
 
+tracked! {
+/// 返回启动以来各后端命令的调用次数、最近一次调用时间与耗时及最近一次错误，供前端调试工具查看。
 #[tauri::command]
-pub(crate) fn dismiss_one_password_suggestion(state: State<AppState>) -> StatsSnapshot {
-    if let Ok(mut locked) = state.inner.lock() {
-        locked.set_one_password_suggestion_pending(false);
-        if let Ok(mut config) = state.config.lock() {
-            config.one_password_suggestion_handled = true;
-            let _ = save_app_config(&state.config_path, &config);
-        }
-        return locked.snapshot();
-    }
-    get_snapshot(state)
+pub(crate) fn get_command_metrics() -> Vec<CommandMetric> {
+    COMMAND_METRICS.snapshot()
+}
 }
 
+tracked! {
+/// 立即检查 GitHub 上是否有新版本，返回当前版本、最新版本、是否可更新与发布说明地址。
+/// 网络或解析失败时返回未知结果并记录警告日志，不返回错误。
 #[tauri::command]
-pub(crate) fn accept_one_password_suggestion(state: State<AppState>) -> StatsSnapshot {
-    if let Ok(mut locked) = state.inner.lock() {
-        let _ = locked.add_excluded_bundle_id("com.1password.1password");
-        locked.set_one_password_suggestion_pending(false);
-        if let Ok(mut config) = state.config.lock() {
-            if !config
-                .excluded_bundle_ids
-                .iter()
-                .any(|v| v.eq_ignore_ascii_case("com.1password.1password"))
-            {
-                config
-                    .excluded_bundle_ids
-                    .push("com.1password.1password".to_string());
-            }
-            config.excluded_bundle_ids.sort();
-            config.excluded_bundle_ids.dedup();
-            config.one_password_suggestion_handled = true;
-            let _ = save_app_config(&state.config_path, &config);
-        }
-        let _ = collector::append_app_log(
-            &locked.app_log_path,
-            "1Password added to exclusion list via suggestion",
-        );
-        return locked.snapshot();
-    }
-    get_snapshot(state)
+pub(crate) async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || check_for_updates_blocking(&app))
+        .await
+        .map_err(|e| e.to_string())
+}
 }
 
-/// 更新菜单栏显示模式，立即应用到托盘并返回最新快照。
+tracked! {
+/// 开关每日自动检查更新，持久化配置后返回最新快照。
 #[tauri::command]
-pub(crate) fn update_menu_bar_display_mode(
+pub(crate) fn update_auto_update_check(
     state: State<AppState>,
-    app: AppHandle,
-    mode: String,
+    auto_update_check: bool,
 ) -> StatsSnapshot {
-    let mode = match MenuBarDisplayMode::from_str(&mode) {
-        Some(mode) => mode,
-        None => return get_snapshot(state),
-    };
-    if let Ok(mut locked) = state.inner.lock() {
-        locked.set_menu_bar_display_mode(mode);
-        if let Ok(mut config) = state.config.lock() {
-            config.menu_bar_display_mode = mode;
-            let _ = save_app_config(&state.config_path, &config);
-        }
-        let _ = collector::append_app_log(
-            &locked.app_log_path,
-            &format!("menu bar display mode changed: {}", mode.as_str()),
-        );
-        let snapshot = locked.snapshot();
-        apply_menu_bar_mode_immediately(&app, &snapshot);
-        return snapshot;
+    if let Ok(mut config) = state.config.lock() {
+        config.auto_update_check = auto_update_check;
+        let _ = save_app_config(&state.config_path, &config);
     }
-    get_snapshot(state)
+    snapshot_of(&state)
+}
+}
+
+tracked! {
+/// 返回存储文件（明细、分析、配置、CSV）的 JSON Schema，供外部工具检测格式变化。
+#[tauri::command]
+pub(crate) fn get_storage_schema() -> serde_json::Value {
+    crate::schema::storage_schema()
+}
 }
 
+tracked! {
 /// 从托盘弹层或菜单请求显示主面板并置前。
 #[tauri::command]
 pub(crate) fn show_main_panel(app: AppHandle) -> Result<(), String> {
     show_main_window(&app)
 }
+}
+
+tracked! {
+/// 立即将统计、输入分析与 CSV 写入磁盘（含未写完的事件块），供前端在打开导出对话框前调用。
+#[tauri::command]
+pub(crate) fn flush_data(state: State<AppState>) -> Result<(), String> {
+    let mut locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    collector::flush_now(&mut locked)
+}
+}
 
+tracked! {
 /// 退出应用进程。
 #[tauri::command]
 pub(crate) fn quit_app(app: AppHandle) {
-    app.exit(0);
+    flush_and_exit(&app, "quit via tray popover");
+}
 }
 
-/// 清空已采集统计数据并返回最新快照。
+tracked! {
+/// 预览破坏性操作（reset_all / reset_today / prune_retention）将影响的统计行、事件块、快捷键条目与磁盘文件，
+/// 不修改任何数据；返回的 confirm_token 短时间内有效，需传给对应的执行命令。
 #[tauri::command]
-pub(crate) fn reset_stats(state: State<AppState>) -> StatsSnapshot {
-    if let Ok(mut locked) = state.inner.lock() {
-        locked.clear_stats();
-        let _ = collector::append_app_log(&locked.app_log_path, "stats reset");
-        return locked.snapshot();
+pub(crate) fn preview_destructive_action(
+    state: State<AppState>,
+    action: String,
+) -> Result<DestructivePreview, String> {
+    let action = DestructiveAction::from_str(&action)
+        .ok_or_else(|| format!("unknown destructive action: {}", action))?;
+    let mut preview = {
+        let locked = state
+            .inner
+            .lock()
+            .map_err(|_| "state lock failed".to_string())?;
+        collector::preview_destructive_action(&locked, action)
+    };
+    let mut tokens = state
+        .confirm_tokens
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    preview.confirm_token = tokens.issue(action.as_str(), Instant::now());
+    preview.expires_in_ms = CONFIRM_TOKEN_TTL.as_millis() as u64;
+    Ok(preview)
+}
+}
+
+// Consume the preview's confirm token, then run the action and return the latest snapshot.
+fn run_confirmed_action(
+    state: &State<AppState>,
+    action: DestructiveAction,
+    confirm_token: &str,
+) -> Result<StatsSnapshot, String> {
+    state
+        .confirm_tokens
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?
+        .consume(confirm_token, action.as_str(), Instant::now())?;
+    let mut locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    locked.run_destructive_action(action)?;
+    Ok(locked.snapshot())
+}
+
+tracked! {
+/// 清空全部已采集统计数据（含磁盘上的明细与分析文件）并返回最新快照；需先调用预览获取 confirm_token。
+#[tauri::command]
+pub(crate) fn reset_stats(
+    state: State<AppState>,
+    confirm_token: String,
+) -> Result<StatsSnapshot, String> {
+    run_confirmed_action(&state, DestructiveAction::ResetAll, &confirm_token)
+}
+}
+
+tracked! {
+/// 清空今天的统计数据并返回最新快照；需先调用预览获取 confirm_token。
+#[tauri::command]
+pub(crate) fn reset_today_stats(
+    state: State<AppState>,
+    confirm_token: String,
+) -> Result<StatsSnapshot, String> {
+    run_confirmed_action(&state, DestructiveAction::ResetToday, &confirm_token)
+}
+}
+
+tracked! {
+/// 立即将超出分钟级保留天数的明细合并为小时粒度并返回最新快照；需先调用预览获取 confirm_token。
+#[tauri::command]
+pub(crate) fn prune_retention(
+    state: State<AppState>,
+    confirm_token: String,
+) -> Result<StatsSnapshot, String> {
+    run_confirmed_action(&state, DestructiveAction::PruneRetention, &confirm_token)
+}
+}
+
+tracked! {
+/// 更新 CSV 写出格式（分隔符 comma / semicolon / tab、UTF-8 BOM、中文表头），汇总 CSV 在下次刷盘时按新格式重写。
+#[tauri::command]
+pub(crate) fn update_csv_dialect(
+    state: State<AppState>,
+    delimiter: String,
+    utf8_bom: bool,
+    headers_localized: bool,
+) -> Result<StatsSnapshot, String> {
+    let delimiter = CsvDelimiter::from_str(&delimiter)
+        .ok_or_else(|| format!("unknown csv delimiter: {}", delimiter))?;
+    let dialect = CsvDialect {
+        delimiter,
+        utf8_bom,
+        headers_localized,
+    };
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    locked.set_csv_dialect(dialect);
+    let _ = collector::append_app_log(
+        &locked.app_log_path,
+        &format!(
+            "csv dialect updated: delimiter={} bom={} localized_headers={}",
+            delimiter.as_str(),
+            utf8_bom,
+            headers_localized
+        ),
+    );
+    if let Ok(mut config) = state.config.lock() {
+        config.csv_delimiter = delimiter;
+        config.csv_utf8_bom = utf8_bom;
+        config.csv_headers_localized = headers_localized;
+        let _ = save_app_config(&state.config_path, &config);
     }
-    get_snapshot(state)
+    Ok(locked.snapshot())
+}
+}
+
+tracked! {
+/// 删除指定应用的全部采集痕迹（统计行、快捷键按应用计数、事件 chunk、应用字典、组合键与应用切换记录），
+/// 重写明细、分析与 CSV 文件，并写入审计日志。dry_run 为 true 时只返回将删除的数量，不做任何修改。
+#[tauri::command]
+pub(crate) fn purge_app_data(
+    state: State<AppState>,
+    bundle_id: String,
+    dry_run: Option<bool>,
+) -> Result<AppPurgeReport, String> {
+    let bundle_id = BundleId::parse(&bundle_id)?;
+    let Ok(mut locked) = state.inner.lock() else {
+        return Err("state lock failed".to_string());
+    };
+    locked.purge_app_data(bundle_id.as_str(), dry_run.unwrap_or(false))
+}
+}
+
+tracked! {
+/// 切换数据配置（如工作 / 个人）：先保存当前数据，再加载目标配置的数据与覆盖设置，无需重启。
+#[tauri::command]
+pub(crate) fn switch_profile(app: AppHandle, name: String) -> Result<StatsSnapshot, String> {
+    switch_profile_and_notify(&app, name.trim(), None)
+}
 }
 
+tracked! {
 /// 获取汇总日志（CSV）文件路径。
 #[tauri::command]
 pub(crate) fn get_log_path(state: State<AppState>) -> String {
@@ -327,7 +2218,9 @@ pub(crate) fn get_log_path(state: State<AppState>) -> String {
     }
     "".to_string()
 }
+}
 
+tracked! {
 /// 获取应用运行日志文件路径。
 #[tauri::command]
 pub(crate) fn get_app_log_path(state: State<AppState>) -> String {
@@ -336,7 +2229,9 @@ pub(crate) fn get_app_log_path(state: State<AppState>) -> String {
     }
     "".to_string()
 }
+}
 
+tracked! {
 /// 获取汇总日志末尾内容（最多近 200 行）。
 #[tauri::command]
 pub(crate) fn get_log_tail(state: State<AppState>) -> String {
@@ -352,8 +2247,10 @@ pub(crate) fn get_log_tail(state: State<AppState>) -> String {
     }
     "".to_string()
 }
+}
 
-/// 获取应用日志末尾内容（最多近 400 行）。
+tracked! {
+/// 获取应用日志末尾内容（最多近 400 行）；最近的日志未能写入文件时在末尾附上写入错误。
 #[tauri::command]
 pub(crate) fn get_app_log_tail(state: State<AppState>) -> String {
     let path = if let Ok(locked) = state.inner.lock() {
@@ -361,14 +2258,27 @@ pub(crate) fn get_app_log_tail(state: State<AppState>) -> String {
     } else {
         return "".to_string();
     };
-    if let Ok(content) = std::fs::read_to_string(path) {
-        let lines: Vec<&str> = content.lines().collect();
-        let start = lines.len().saturating_sub(400);
-        return lines[start..].join("\n");
+    // Lines that never reached the file would otherwise just be missing from the tail.
+    let write_error = collector::flush_app_log(&path).err();
+    let mut tail = match std::fs::read_to_string(&path) {
+        Ok(content) => {
+            let lines: Vec<&str> = content.lines().collect();
+            let start = lines.len().saturating_sub(400);
+            lines[start..].join("\n")
+        }
+        Err(_) => String::new(),
+    };
+    if let Some(err) = write_error {
+        if !tail.is_empty() {
+            tail.push('\n');
+        }
+        tail.push_str(&format!("[TypePulse] {}", err));
     }
-    "".to_string()
+    tail
+}
 }
 
+tracked! {
 /// 打开本地数据目录（日志与明细文件所在目录）。
 #[tauri::command]
 pub(crate) fn open_data_dir(state: State<AppState>, app: AppHandle) -> Result<(), String> {
@@ -383,18 +2293,36 @@ pub(crate) fn open_data_dir(state: State<AppState>, app: AppHandle) -> Result<()
         .open_path(data_dir.to_string_lossy().to_string(), None::<&str>)
         .map_err(|err| err.to_string())
 }
+}
 
-/// 计算并返回数据目录总大小（字节）。
+tracked! {
+/// 计算并返回数据目录总大小（字节），其中按月 zip 归档的文件数与大小单独列出。
 #[tauri::command]
-pub(crate) fn get_data_dir_size(state: State<AppState>) -> u64 {
+pub(crate) fn get_data_dir_size(state: State<AppState>) -> DataDirSize {
     let path = if let Ok(locked) = state.inner.lock() {
         locked.log_path.clone()
     } else {
-        return 0;
+        return DataDirSize::default();
     };
     let data_dir = path.parent().unwrap_or(path.as_path()).to_path_buf();
     let _ = fs::create_dir_all(&data_dir);
-    folder_size(&data_dir)
+    let mut size = DataDirSize {
+        total_bytes: folder_size(&data_dir),
+        ..DataDirSize::default()
+    };
+    if let Ok(entries) = fs::read_dir(&data_dir) {
+        for entry in entries.flatten() {
+            if !entry.file_name().to_str().is_some_and(is_archive_name) {
+                continue;
+            }
+            if let Ok(metadata) = entry.metadata() {
+                size.archive_files += 1;
+                size.archive_bytes += metadata.len();
+            }
+        }
+    }
+    size
+}
 }
 
 fn folder_size(path: &PathBuf) -> u64 {
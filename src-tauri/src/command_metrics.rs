@@ -0,0 +1,239 @@
+//! Command invocation metrics.
+//! Counts the calls of every Tauri command with the time, duration and error of the latest
+//! one, so the frontend devtools can see which commands are hammered. Kept in memory only.
+//! Commands are counted by defining them inside `tracked!`.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// Calls of one command, as returned by `get_command_metrics`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CommandMetric {
+    pub command: String,
+    pub calls: u64,
+    /// Unix time in milliseconds when the latest call started.
+    pub last_called_ms: i64,
+    pub last_duration_ms: f64,
+    /// Error of the latest failed call as the frontend received it; kept after later
+    /// successes.
+    pub last_error: Option<String>,
+}
+
+/// Counters of every command defined through `tracked!`.
+pub(crate) static COMMAND_METRICS: CommandMetrics = CommandMetrics::new();
+
+/// Per-command counters, updated through `track` and its variants.
+#[derive(Default)]
+pub(crate) struct CommandMetrics {
+    by_command: Mutex<BTreeMap<&'static str, CommandMetric>>,
+}
+
+impl CommandMetrics {
+    const fn new() -> Self {
+        Self {
+            by_command: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Run an infallible command body and count the call.
+    pub(crate) fn track<T>(&self, command: &'static str, run: impl FnOnce() -> T) -> T {
+        let call = CallStart::now();
+        let output = run();
+        self.record(command, call, None);
+        output
+    }
+
+    /// Run a fallible command body and count the call with its error, if any.
+    pub(crate) fn track_result<T, E: Serialize>(
+        &self,
+        command: &'static str,
+        run: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let call = CallStart::now();
+        let result = run();
+        self.record(command, call, result.as_ref().err().map(error_text));
+        result
+    }
+
+    /// `track_result` for async command bodies; the duration includes the awaited work.
+    pub(crate) async fn track_async<T, E: Serialize>(
+        &self,
+        command: &'static str,
+        run: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let call = CallStart::now();
+        let result = run.await;
+        self.record(command, call, result.as_ref().err().map(error_text));
+        result
+    }
+
+    fn record(&self, command: &'static str, call: CallStart, error: Option<String>) {
+        let duration = call.started.elapsed();
+        let Ok(mut by_command) = self.by_command.lock() else {
+            return;
+        };
+        let metric = by_command.entry(command).or_insert_with(|| CommandMetric {
+            command: command.to_string(),
+            calls: 0,
+            last_called_ms: 0,
+            last_duration_ms: 0.0,
+            last_error: None,
+        });
+        metric.calls += 1;
+        metric.last_called_ms = call.at_ms;
+        metric.last_duration_ms = duration.as_secs_f64() * 1000.0;
+        if error.is_some() {
+            metric.last_error = error;
+        }
+    }
+
+    /// Every command called since launch, by name.
+    pub(crate) fn snapshot(&self) -> Vec<CommandMetric> {
+        self.by_command
+            .lock()
+            .map(|by_command| by_command.values().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Define a Tauri command whose calls are counted in `COMMAND_METRICS` under the fn name.
+/// Wraps the whole item, so the body keeps its plain form:
+/// `tracked! { #[tauri::command] pub(crate) fn name(..) -> T { .. } }`. Commands returning
+/// `Result` also record their error; async commands are timed until their future completes.
+macro_rules! tracked {
+    (
+        $(#[$meta:meta])*
+        $vis:vis async fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty $body:block
+    ) => {
+        $(#[$meta])*
+        $vis async fn $name($($arg: $ty),*) -> $ret {
+            $crate::command_metrics::COMMAND_METRICS
+                .track_async(stringify!($name), async $body)
+                .await
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> Result<$ok:ty, $err:ty> $body:block
+    ) => {
+        $(#[$meta])*
+        $vis fn $name($($arg: $ty),*) -> Result<$ok, $err> {
+            $crate::command_metrics::COMMAND_METRICS.track_result(stringify!($name), || $body)
+        }
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)? $body:block
+    ) => {
+        $(#[$meta])*
+        $vis fn $name($($arg: $ty),*) $(-> $ret)? {
+            $crate::command_metrics::COMMAND_METRICS.track(stringify!($name), || $body)
+        }
+    };
+}
+pub(crate) use tracked;
+
+struct CallStart {
+    started: Instant,
+    at_ms: i64,
+}
+
+impl CallStart {
+    fn now() -> Self {
+        Self {
+            started: Instant::now(),
+            at_ms: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+// Errors reach the frontend serialized; plain string errors are kept without JSON quotes.
+fn error_text(error: &impl Serialize) -> String {
+    match serde_json::to_value(error) {
+        Ok(serde_json::Value::String(text)) => text,
+        Ok(value) => value.to_string(),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::{CommandMetrics, COMMAND_METRICS};
+
+    #[derive(Serialize)]
+    #[serde(tag = "code", rename_all = "snake_case")]
+    enum TypedError {
+        InvalidRange,
+    }
+
+    #[test]
+    fn calls_are_counted_with_the_latest_error_kept() {
+        let metrics = CommandMetrics::default();
+        assert_eq!(metrics.track("get_snapshot", || 7), 7);
+        let failed: Result<(), String> =
+            metrics.track_result("reset_stats", || Err("confirm token expired".to_string()));
+        assert!(failed.is_err());
+        assert_eq!(
+            metrics.track_result("reset_stats", || Ok::<_, String>(())),
+            Ok(())
+        );
+        let typed: Result<(), TypedError> =
+            metrics.track_result("get_comparison", || Err(TypedError::InvalidRange));
+        assert!(typed.is_err());
+        let synced: Result<u8, String> = tauri::async_runtime::block_on(
+            metrics.track_async("export_stats_xlsx", async { Ok(1) }),
+        );
+        assert_eq!(synced, Ok(1));
+
+        let rows = metrics.snapshot();
+        let names: Vec<&str> = rows.iter().map(|row| row.command.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "export_stats_xlsx",
+                "get_comparison",
+                "get_snapshot",
+                "reset_stats"
+            ]
+        );
+        let reset = &rows[3];
+        assert_eq!(reset.calls, 2);
+        assert_eq!(reset.last_error.as_deref(), Some("confirm token expired"));
+        assert!(reset.last_called_ms > 0);
+        assert!(reset.last_duration_ms >= 0.0);
+        assert_eq!(
+            rows[1].last_error.as_deref(),
+            Some(r#"{"code":"invalid_range"}"#)
+        );
+        assert_eq!(rows[2].last_error, None);
+    }
+
+    tracked! {
+    fn tracked_probe(fail: bool) -> Result<u8, String> {
+        if fail {
+            return Err("probe failed".to_string());
+        }
+        Ok(1)
+    }
+    }
+
+    #[test]
+    fn tracked_commands_are_counted_under_their_fn_name() {
+        assert_eq!(tracked_probe(true), Err("probe failed".to_string()));
+        assert_eq!(tracked_probe(false), Ok(1));
+
+        let rows = COMMAND_METRICS.snapshot();
+        let probe = rows
+            .iter()
+            .find(|row| row.command == "tracked_probe")
+            .unwrap();
+        assert_eq!(probe.calls, 2);
+        assert_eq!(probe.last_error.as_deref(), Some("probe failed"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Confirm tokens for destructive commands.
+//! A preview issues a short-lived token bound to one action; the destructive command only runs
+//! when it presents that token, so a stray direct call cannot delete data.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, Instant};
+
+/// How long a preview stays confirmable.
+pub(crate) const CONFIRM_TOKEN_TTL: Duration = Duration::from_secs(60);
+
+struct PendingConfirm {
+    token: String,
+    action: String,
+    expires_at: Instant,
+}
+
+/// The single outstanding confirm token; a new preview replaces the previous one.
+#[derive(Default)]
+pub(crate) struct ConfirmTokens {
+    pending: Option<PendingConfirm>,
+    issued: u64,
+}
+
+impl ConfirmTokens {
+    /// Issue a token for `action`, valid until `now + CONFIRM_TOKEN_TTL`.
+    pub(crate) fn issue(&mut self, action: &str, now: Instant) -> String {
+        self.issued += 1;
+        // RandomState is seeded per instance, so tokens cannot be guessed from the counter.
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(self.issued);
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        );
+        let token = format!("{:016x}", hasher.finish());
+        self.pending = Some(PendingConfirm {
+            token: token.clone(),
+            action: action.to_string(),
+            expires_at: now + CONFIRM_TOKEN_TTL,
+        });
+        token
+    }
+
+    /// Consume the token for `action`. Any presented token clears the pending one, so a
+    /// token works at most once and a wrong guess forces a new preview.
+    pub(crate) fn consume(
+        &mut self,
+        token: &str,
+        action: &str,
+        now: Instant,
+    ) -> Result<(), String> {
+        let Some(pending) = self.pending.take() else {
+            return Err("no pending confirmation, preview the action first".to_string());
+        };
+        if now >= pending.expires_at {
+            return Err("confirmation expired, preview the action again".to_string());
+        }
+        if pending.action != action || pending.token != token {
+            return Err("confirmation does not match the previewed action".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{ConfirmTokens, CONFIRM_TOKEN_TTL};
+
+    #[test]
+    fn confirm_token_is_single_use_action_bound_and_expires() {
+        let now = Instant::now();
+        let mut tokens = ConfirmTokens::default();
+        assert!(tokens.consume("", "reset_all", now).is_err());
+
+        let token = tokens.issue("reset_all", now);
+        assert_eq!(token.len(), 16);
+        assert!(tokens
+            .consume(&token, "reset_all", now + Duration::from_secs(1))
+            .is_ok());
+        assert!(tokens.consume(&token, "reset_all", now).is_err());
+
+        let token = tokens.issue("reset_today", now);
+        assert!(tokens.consume(&token, "reset_all", now).is_err());
+        // The mismatch above used the token up.
+        assert!(tokens.consume(&token, "reset_today", now).is_err());
+
+        let token = tokens.issue("prune_retention", now);
+        assert!(tokens
+            .consume(&token, "prune_retention", now + CONFIRM_TOKEN_TTL)
+            .is_err());
+
+        let first = tokens.issue("reset_all", now);
+        let second = tokens.issue("reset_all", now);
+        assert_ne!(first, second);
+        assert!(tokens.consume(&first, "reset_all", now).is_err());
+    }
+}
@@ -1,50 +1,158 @@
 use std::{
     env,
-    path::PathBuf,
-    sync::{Arc, Mutex},
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, TryLockError, Weak,
+    },
+    time::{Duration, Instant},
 };
 
-use app_config::{load_app_config, AppConfig, MenuBarDisplayMode};
-use chrono::Local;
-use collector::{new_collector_state, start_collector, StatsSnapshot};
+use app_config::{
+    disable_config_writes, load_app_config, save_app_config, AppConfig, MenuBarDisplayMode,
+    TrayLeftClickAction, WindowBounds, DEFAULT_PROFILE,
+};
+use appearance::{current_appearance, reduce_transparency, AppearanceWatcher, SystemAppearance};
+use collector::{
+    new_collector_state, new_demo_collector_state, start_collector, start_history_load,
+    start_tick_watchdog,
+};
+use shutdown::{BackgroundThreads, Shutdown};
 #[cfg(target_os = "macos")]
 use tauri::window::{Effect, EffectState, EffectsBuilder};
 use tauri::{
     image::Image,
-    menu::{Menu, MenuItem, MenuItemBuilder, PredefinedMenuItem},
+    menu::{
+        CheckMenuItem, CheckMenuItemBuilder, IsMenuItem, Menu, MenuItem, MenuItemBuilder,
+        PredefinedMenuItem, Submenu, SubmenuBuilder,
+    },
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     window::Color,
-    Manager, PhysicalPosition, WebviewUrl, WebviewWindowBuilder, Wry,
+    Emitter, Manager, PhysicalPosition, PhysicalSize, RunEvent, WebviewUrl, WebviewWindowBuilder,
+    Wry,
 };
 
 mod app_config;
+mod app_nap;
+mod appearance;
+mod applescript;
+#[cfg(feature = "bench-support")]
+#[doc(hidden)]
+pub mod bench_support;
 mod collector;
 mod command;
+mod command_metrics;
+mod confirm;
+mod schema;
+mod shortcut_preset;
+mod shutdown;
 mod storage;
+mod telemetry;
+#[cfg(test)]
+mod test_support;
+mod update_check;
+mod validation;
 
 struct AppState {
     inner: Arc<Mutex<collector::CollectorState>>,
     config: Arc<Mutex<AppConfig>>,
     config_path: PathBuf,
+    // 数据目录根目录（默认数据配置的文件所在目录，其他配置位于其子目录）
+    data_dir: PathBuf,
+    // 应用运行日志路径（退出流程中无需持有采集状态锁即可写日志）
+    app_log_path: PathBuf,
+    // 主面板位置/尺寸变更序号，用于去抖保存
+    window_bounds_generation: Arc<AtomicU64>,
+    // 托盘双击序号：单击动作延迟执行，期间收到双击则取消
+    tray_click_generation: AtomicU64,
+    // 后台循环线程（采集 tick、托盘刷新），退出前先停止，避免与最终刷盘并发写入
+    background: Arc<BackgroundThreads>,
+    // 最终刷盘是否已开始（托盘退出与系统退出事件都会触发，只执行一次）
+    final_flush_started: AtomicBool,
+    // 破坏性操作的确认令牌：预览时签发，短时有效且只能使用一次
+    confirm_tokens: Mutex<confirm::ConfirmTokens>,
+    // 匿名使用统计（仅在用户同意后累计命令调用次数，每周上报一次）
+    telemetry: Arc<Mutex<telemetry::Telemetry>>,
+    // 版本检查的调度与最近一次结果（每天自动检查一次）
+    update_check: Mutex<update_check::UpdateCheck>,
+    // 本次运行中通过系统对话框选择的目录（导出路径只能位于数据目录或这些目录内，不持久化）
+    picked_dirs: Mutex<Vec<PathBuf>>,
+    // 本次运行中通过系统对话框打开的文件（自动导出脚本须是这些文件或位于数据目录内）
+    picked_files: Mutex<Vec<PathBuf>>,
 }
 
 type AppMenuItem = MenuItem<Wry>;
 
+// Cloned into managed state as well, so pause paths can redraw the tray immediately.
+#[derive(Clone)]
 struct TraySummaryItems {
     tray_icon: tauri::tray::TrayIcon<Wry>,
-    black_icon: Option<Image<'static>>,
     overview_item: AppMenuItem,
+    session_item: AppMenuItem,
     toggle_item: AppMenuItem,
+    // Shortcut collection toggle, only built when `tray_shortcuts_toggle` is on at launch.
+    shortcuts_toggle_item: Option<AppMenuItem>,
+    profile_menu: Option<ProfileMenu>,
+}
+
+// Tray submenu listing the profiles, titled with the active one.
+#[derive(Clone)]
+struct ProfileMenu {
+    submenu: Submenu<Wry>,
+    items: Vec<(String, CheckMenuItem<Wry>)>,
 }
 
 const TRAY_POPOVER_LABEL: &str = "tray-popover";
 const TRAY_POPOVER_WIDTH: f64 = 356.0;
 const TRAY_POPOVER_HEIGHT: f64 = 236.0;
+const MAIN_WINDOW_BOUNDS_SAVE_DELAY: Duration = Duration::from_millis(500);
+// Default double-click interval on Windows and macOS; a single click waits this long for a second.
+const TRAY_DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+const QUIT_FLUSH_TIMEOUT: Duration = Duration::from_secs(3);
+// How long shutdown waits for background loops before flushing anyway.
+const BACKGROUND_JOIN_TIMEOUT: Duration = Duration::from_secs(1);
+const QUIT_CONFIRM_WINDOW: Duration = Duration::from_secs(3);
+const QUIT_ITEM_TEXT: &str = "退出";
+const QUIT_CONFIRM_ITEM_TEXT: &str = "确认退出?";
+pub(crate) const CAPTURE_STATE_CHANGED_EVENT: &str = "capture-state-changed";
+const APP_GOAL_MET_EVENT: &str = "app-goal-met";
+const STORAGE_DEGRADED_EVENT: &str = "storage-degraded";
+const WEEKLY_COMPARISON_EVENT: &str = "weekly-comparison";
+// Matches `identifier` in tauri.conf.json; the release data dir is named after it.
+const APP_IDENTIFIER: &str = "com.tauri.typepulse";
+const DETAIL_FILE: &str = "typingstats-details.json";
+const CONFIG_FILE: &str = "typingstats-config.json";
+const LOG_FILE: &str = "typingstats.csv";
+// Tray menu ids of profile items are this prefix plus the profile name.
+const PROFILE_ITEM_PREFIX: &str = "profile:";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if env::args().any(|arg| arg == schema::DUMP_SCHEMA_ARG) {
+        match serde_json::to_string_pretty(&schema::storage_schema()) {
+            Ok(text) => println!("{}", text),
+            Err(err) => eprintln!("failed to dump storage schema: {}", err),
+        }
+        return;
+    }
+    // Script entry point: read the saved files and print, without the event tap or a window.
+    if env::args().any(|arg| arg == collector::TODAY_JSON_ARG) {
+        let data_dir = cli_data_dir();
+        let config = load_app_config(&data_dir.join(CONFIG_FILE)).unwrap_or_default();
+        let profile_dir = config
+            .profile_data_dir(&data_dir, &config.active_profile)
+            .unwrap_or(data_dir);
+        let summary = collector::load_today_summary_json(profile_dir.join(DETAIL_FILE), &config);
+        match serde_json::to_string(&summary) {
+            Ok(text) => println!("{}", text),
+            Err(err) => eprintln!("failed to serialize today summary: {}", err),
+        }
+        return;
+    }
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
         .on_window_event(|window, event| match event {
             tauri::WindowEvent::CloseRequested { api, .. } => {
                 if window.label() == "main" || window.label() == TRAY_POPOVER_LABEL {
@@ -57,6 +165,14 @@ pub fn run() {
                     let _ = window.hide();
                 }
             }
+            tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+                if window.label() == "main" {
+                    schedule_main_window_bounds_save(window.app_handle());
+                }
+            }
+            tauri::WindowEvent::ScaleFactorChanged { .. } if !cfg!(target_os = "macos") => {
+                refresh_displays(window.app_handle());
+            }
             _ => {}
         })
         .setup(|app| {
@@ -71,40 +187,171 @@ pub fn run() {
                     .unwrap_or_else(|_| PathBuf::from("."))
             };
             let _ = std::fs::create_dir_all(&data_dir);
-            let log_path = data_dir.join("typingstats.csv");
             let app_log_path = data_dir.join("typingstats-app.log");
-            let detail_path = data_dir.join("typingstats-details.json");
-            let config_path = data_dir.join("typingstats-config.json");
-            let config = load_app_config(&config_path).unwrap_or_default();
-            let tray_update_interval = config.tray_update_interval();
-            let _ = collector::append_app_log(&app_log_path, "app started");
-            let panic_log_path = app_log_path.clone();
-            std::panic::set_hook(Box::new(move |info| {
-                let _ = collector::append_app_log(&panic_log_path, &format!("panic: {}", info));
-            }));
-            let state = Arc::new(Mutex::new(new_collector_state(
-                log_path,
-                app_log_path,
-                detail_path,
-                &config,
-            )));
-            start_collector(state.clone());
+            let config_path = data_dir.join(CONFIG_FILE);
+            let mut config = load_app_config(&config_path).unwrap_or_default();
+            let main_window_bounds = config.main_window_bounds;
+            let show_main_window_on_launch = config.show_main_window_on_launch(env::args());
+            // Demo mode runs on generated stats kept in memory: no data, CSV, config or log
+            // file is written, and the app log goes to stdout.
+            let demo_mode = config.demo_mode_on_launch(env::args());
+            if demo_mode {
+                collector::log_to_stdout_only();
+                disable_config_writes();
+            }
+            let _ = collector::append_app_log(
+                &app_log_path,
+                if demo_mode {
+                    "app started in demo mode"
+                } else {
+                    "app started"
+                },
+            );
+            let (log_path, detail_path) = if demo_mode {
+                (data_dir.join(LOG_FILE), data_dir.join(DETAIL_FILE))
+            } else {
+                match profile_data_paths(&data_dir, &config, &config.active_profile) {
+                    Ok(paths) => paths,
+                    Err(err) => {
+                        let _ = collector::append_app_log(
+                            &app_log_path,
+                            &format!(
+                                "profile {} unavailable, using default: {}",
+                                config.active_profile, err
+                            ),
+                        );
+                        config.active_profile = DEFAULT_PROFILE.to_string();
+                        (data_dir.join(LOG_FILE), data_dir.join(DETAIL_FILE))
+                    }
+                }
+            };
+            let background = Arc::new(BackgroundThreads::default());
+            let collector_state = if demo_mode {
+                new_demo_collector_state(
+                    log_path,
+                    app_log_path.clone(),
+                    detail_path,
+                    &config,
+                    background.signal(),
+                    chrono::Local::now().naive_local(),
+                )
+            } else {
+                new_collector_state(
+                    log_path,
+                    app_log_path.clone(),
+                    detail_path,
+                    &config,
+                    background.signal(),
+                )
+            };
+            let telemetry_path = data_dir.join(telemetry::TELEMETRY_STATE_FILE);
+            // Demo mode counts and saves nothing, so fake usage is never reported.
+            let telemetry = if demo_mode {
+                telemetry::Telemetry::new(telemetry_path, false)
+            } else {
+                telemetry::Telemetry::load(telemetry_path.clone(), config.telemetry_enabled)
+                    .unwrap_or_else(|err| {
+                        let _ = collector::append_app_log(
+                            &app_log_path,
+                            &format!("telemetry counters load failed: {}", err),
+                        );
+                        telemetry::Telemetry::new(telemetry_path, config.telemetry_enabled)
+                    })
+            };
+            let state = Arc::new(Mutex::new(collector_state));
+            if !demo_mode {
+                install_panic_hook(
+                    app_log_path.clone(),
+                    data_dir.clone(),
+                    Arc::downgrade(&state),
+                );
+                background.register(
+                    "history-load",
+                    start_history_load(state.clone(), data_dir.clone()),
+                );
+            }
+            background.register("collector-tick", start_collector(state.clone()));
+            background.register("collector-watchdog", start_tick_watchdog(state.clone()));
             app.manage(AppState {
                 inner: state.clone(),
                 config: Arc::new(Mutex::new(config)),
                 config_path,
+                data_dir,
+                app_log_path,
+                window_bounds_generation: Arc::new(AtomicU64::new(0)),
+                tray_click_generation: AtomicU64::new(0),
+                background: background.clone(),
+                final_flush_started: AtomicBool::new(false),
+                confirm_tokens: Mutex::new(confirm::ConfirmTokens::default()),
+                telemetry: Arc::new(Mutex::new(telemetry)),
+                update_check: Mutex::new(update_check::UpdateCheck::default()),
+                picked_dirs: Mutex::new(Vec::new()),
+                picked_files: Mutex::new(Vec::new()),
             });
+            restore_main_window_bounds(
+                app.handle(),
+                main_window_bounds,
+                show_main_window_on_launch,
+            );
             let tray_items = build_tray(app)?;
-            start_tray_updater(state, tray_items, tray_update_interval);
+            app.manage(tray_items.clone());
+            applescript::install(app.handle());
+            refresh_displays(app.handle());
+            background.register(
+                "tray-updater",
+                start_tray_updater(app.handle().clone(), state, tray_items, background.signal()),
+            );
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(count_commands(tauri::generate_handler![
             command::get_snapshot,
+            command::get_today_summary_json,
             command::get_shortcut_stats_by_range,
+            command::get_shortcut_breadth,
             command::get_daily_top_keys_by_range,
+            command::get_key_heatmap,
+            command::get_key_hold_stats,
+            command::export_key_heatmap_svg,
+            command::export_stats_xlsx,
+            command::verify_export,
+            command::export_stats_parquet,
+            command::get_app_timeline,
+            command::get_minute_series,
+            command::get_shortcut_daily_series,
+            command::get_comparison,
+            command::reconstruct_day,
+            command::request_raw_export_consent,
+            command::export_raw_events,
             command::update_paused,
+            command::update_shortcuts_paused,
             command::update_ignore_key_combos,
+            command::update_track_chord_aborts,
+            command::update_ax_sensitive_detection,
+            command::update_prevent_app_nap,
+            command::update_write_status_file,
+            command::update_applescript_enabled,
+            command::update_record_event_chunks,
+            command::update_timing_settings,
+            command::get_chord_abort_stats,
+            command::get_app_summary,
+            command::get_top_windows,
+            command::get_app_switch_stats,
+            command::get_copy_paste_stats,
+            command::get_stats_rows,
+            command::get_daily_totals,
+            command::query_stats_rows,
+            command::get_analytics_stats,
+            command::get_coverage,
+            command::get_burst_stats,
+            command::get_effort_stats,
+            command::get_display_stats,
+            command::get_deep_work_blocks,
+            command::update_display_label,
+            command::get_focus_scores,
             command::update_shortcut_rules,
+            command::update_symbol_key_names,
+            command::export_shortcut_rules,
+            command::import_shortcut_rules,
             command::get_running_apps,
             command::update_app_exclusion_list,
             command::add_app_exclusion,
@@ -112,66 +359,246 @@ pub fn run() {
             command::resolve_bundle_id_from_app_path,
             command::dismiss_one_password_suggestion,
             command::accept_one_password_suggestion,
+            command::accept_non_typing_suggestion,
+            command::dismiss_non_typing_suggestion,
             command::update_menu_bar_display_mode,
+            command::update_tray_left_click_action,
+            command::update_start_hidden,
+            command::update_min_keys_to_display,
+            command::update_window_title_mode,
+            command::set_app_title_privacy,
+            command::remove_app_title_privacy,
+            command::merge_window_titles,
+            command::update_app_time_goal,
+            command::remove_app_time_goal,
+            command::update_app_time_goal_notifications,
+            command::update_weekly_comparison_notifications,
+            command::get_weekly_comparison,
+            command::update_auto_export,
+            command::update_csv_dialect,
+            command::preview_destructive_action,
             command::reset_stats,
+            command::reset_today_stats,
+            command::prune_retention,
+            command::purge_app_data,
+            command::rollup_old_stats_now,
+            command::archive_old_months_now,
+            command::switch_profile,
             command::get_log_path,
             command::get_app_log_path,
             command::get_log_tail,
             command::get_app_log_tail,
             command::open_data_dir,
             command::get_data_dir_size,
+            command::get_config,
+            command::get_storage_schema,
+            command::set_telemetry_consent,
+            command::check_for_updates,
+            command::update_auto_update_check,
+            command::get_telemetry_preview,
+            command::get_command_metrics,
+            command::pick_export_path,
             command::show_main_panel,
+            command::reset_window_position,
+            command::flush_data,
             command::quit_app
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        ]))
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Exits that bypass the quit items (Cmd+Q, logout, system shutdown) still flush;
+            // after a quit item the flush already ran and this returns right away.
+            if matches!(event, RunEvent::ExitRequested { .. } | RunEvent::Exit) {
+                final_flush(app, "exit requested");
+            }
+        });
+}
+
+// CSV and detail file paths of profile `name`, creating its directory on first use.
+fn profile_data_paths(
+    data_dir: &Path,
+    config: &AppConfig,
+    name: &str,
+) -> Result<(PathBuf, PathBuf), String> {
+    let dir = config.profile_data_dir(data_dir, name)?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok((dir.join(LOG_FILE), dir.join(DETAIL_FILE)))
+}
+
+/// Central profile switch for the command, the tray and the auto-switch rules: flush the
+/// current data set, load the one of `name` and save it as the active profile. `rule` names
+/// the rule behind an automatic switch; manual switches (`None`) pause the rules for their
+/// cooldown. The tray menu is refreshed either way.
+pub(crate) fn switch_profile_and_notify(
+    app: &tauri::AppHandle,
+    name: &str,
+    rule: Option<&str>,
+) -> Result<collector::StatsSnapshot, String> {
+    let state = app.state::<AppState>();
+    let result = switch_profile(&state, name, rule);
+    let active = state
+        .config
+        .lock()
+        .map(|config| config.active_profile.clone())
+        .unwrap_or_default();
+    if let Some(menu) = app
+        .try_state::<TraySummaryItems>()
+        .and_then(|items| items.profile_menu.clone())
+    {
+        if let Err(err) = set_tray_profile_menu(&menu, &active) {
+            append_tray_log(app, &format!("failed to refresh profile menu: {}", err));
+        }
+    }
+    result
+}
+
+fn switch_profile(
+    state: &AppState,
+    name: &str,
+    rule: Option<&str>,
+) -> Result<collector::StatsSnapshot, String> {
+    let mut locked = state
+        .inner
+        .lock()
+        .map_err(|_| "state lock failed".to_string())?;
+    let mut config = state
+        .config
+        .lock()
+        .map_err(|_| "config lock failed".to_string())?;
+    if rule.is_none() {
+        locked.suppress_profile_auto_switch(Instant::now());
+    }
+    if config.active_profile == name {
+        return Ok(locked.snapshot());
+    }
+    let (log_path, detail_path) = profile_data_paths(&state.data_dir, &config, name)?;
+    let mut next_config = config.clone();
+    next_config.active_profile = name.to_string();
+    locked.switch_data_set(log_path, detail_path, &next_config)?;
+    let previous = std::mem::replace(&mut *config, next_config).active_profile;
+    if let Err(err) = save_app_config(&state.config_path, &config) {
+        let _ = collector::append_app_log(
+            &locked.app_log_path,
+            &format!("failed to save active profile: {}", err),
+        );
+    }
+    if rule.is_none() {
+        // The reloaded state starts without the cooldown of a switch made just now.
+        locked.suppress_profile_auto_switch(Instant::now());
+    }
+    let message = match rule {
+        Some(rule) => format!(
+            "auto switched profile from {} to {} by {}",
+            previous, name, rule
+        ),
+        None => format!("switched profile from {} to {}", previous, name),
+    };
+    let _ = collector::append_app_log(&locked.app_log_path, &message);
+    Ok(locked.snapshot())
+}
+
+// Data dir for CLI paths that run without a Tauri app, mirroring setup: `_data` under the
+// working directory in debug builds, the platform data dir plus the identifier otherwise.
+fn cli_data_dir() -> PathBuf {
+    if cfg!(debug_assertions) {
+        return env::current_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join("_data");
+    }
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .or_else(|| env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+// Log panics and try to dump today's unsaved stats. The hook must never block or panic
+// itself: the state lock is only tried, and the dump runs inside `catch_unwind`.
+fn install_panic_hook(
+    app_log_path: PathBuf,
+    data_dir: PathBuf,
+    state: Weak<Mutex<collector::CollectorState>>,
+) {
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = collector::append_app_log(&app_log_path, &format!("panic: {}", info));
+        let _ = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            let Some(state) = state.upgrade() else {
+                return;
+            };
+            // A poisoned lock still holds usable counters; a held one (e.g. the panicking
+            // thread owns it) is skipped rather than waited on.
+            let locked = match state.try_lock() {
+                Ok(locked) => locked,
+                Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+                Err(TryLockError::WouldBlock) => return,
+            };
+            let message = match collector::write_crash_dump(&locked, &data_dir) {
+                Ok(path) => format!("wrote crash dump {}", path.display()),
+                Err(err) => format!("failed to write crash dump: {}", err),
+            };
+            let _ = collector::append_app_log(&app_log_path, &message);
+        }));
+        // The process may be about to abort; get the panic onto disk first.
+        collector::flush_logs();
+    }));
 }
 
 fn build_tray(app: &tauri::App) -> tauri::Result<TraySummaryItems> {
     let overview_item = MenuItemBuilder::with_id("overview", "今日时长: 0h 0m | 今日总键数: 0")
         .enabled(false)
         .build(app)?;
+    // Second overview line for the running session; left empty while there is none.
+    let session_item = MenuItemBuilder::with_id("session", "")
+        .enabled(false)
+        .build(app)?;
     let toggle_item = MenuItemBuilder::with_id("toggle", "暂停采集")
         .enabled(true)
         .build(app)?;
+    let shortcuts_toggle_item = build_shortcuts_toggle_item(app)?;
     let show_item = MenuItem::with_id(app, "show", "打开主面板", true, None::<&str>)?;
-    let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", QUIT_ITEM_TEXT, true, None::<&str>)?;
+    let quit_confirm_item = quit_item.clone();
+    let quit_armed_at: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
     let separator_middle = PredefinedMenuItem::separator(app)?;
     let separator_bottom = PredefinedMenuItem::separator(app)?;
-    let menu = Menu::with_items(
-        app,
-        &[
-            &overview_item,
-            &toggle_item,
-            &separator_middle,
-            &show_item,
-            &separator_bottom,
-            &quit_item,
-        ],
-    )?;
+    let profile_menu = build_profile_menu(app)?;
+    let mut menu_items: Vec<&dyn IsMenuItem<Wry>> =
+        vec![&overview_item, &session_item, &toggle_item];
+    if let Some(shortcuts_toggle_item) = &shortcuts_toggle_item {
+        menu_items.push(shortcuts_toggle_item);
+    }
+    if let Some(profile_menu) = &profile_menu {
+        menu_items.push(&profile_menu.submenu);
+    }
+    menu_items.extend([
+        &separator_middle as &dyn IsMenuItem<Wry>,
+        &show_item,
+        &separator_bottom,
+        &quit_item,
+    ]);
+    let menu = Menu::with_items(app, &menu_items)?;
 
     let mut builder = TrayIconBuilder::with_id("main-tray")
         .menu(&menu)
         .tooltip("TypePulse")
         .show_menu_on_left_click(false)
-        .on_menu_event(|app, event| {
+        .on_menu_event(move |app, event| {
             if event.id() == "show" {
                 let _ = show_main_window(app);
             }
             if event.id() == "quit" {
-                app.exit(0);
+                handle_tray_quit(app, &quit_confirm_item, &quit_armed_at);
             }
             if event.id() == "toggle" {
-                if let Ok(mut locked) = app.state::<AppState>().inner.lock() {
-                    let paused_now = locked.snapshot().paused;
-                    locked.set_paused(!paused_now);
-                    let _ = collector::append_app_log(
-                        &locked.app_log_path,
-                        if paused_now {
-                            "resumed via tray"
-                        } else {
-                            "paused via tray"
-                        },
+                toggle_paused_from_tray(app);
+            }
+            if event.id() == "shortcuts_toggle" {
+                set_shortcuts_paused_and_notify(app, "tray", |shortcuts_paused| !shortcuts_paused);
+            }
+            if let Some(name) = event.id().as_ref().strip_prefix(PROFILE_ITEM_PREFIX) {
+                if let Err(err) = switch_profile_and_notify(app, name, None) {
+                    append_tray_log(
+                        app,
+                        &format!("failed to switch profile to {}: {}", name, err),
                     );
                 }
             }
@@ -180,11 +607,9 @@ fn build_tray(app: &tauri::App) -> tauri::Result<TraySummaryItems> {
             handle_tray_icon_event(tray, event);
         });
 
-    let black_icon = Image::from_bytes(include_bytes!("../icons/l_black.png"))
-        .ok()
-        .or_else(|| app.default_window_icon().cloned())
-        .map(Image::to_owned);
-    if let Some(icon) = black_icon.clone() {
+    let icon = tray_icon_for(MenuBarDisplayMode::default(), current_appearance(), false)
+        .or_else(|| app.default_window_icon().cloned().map(Image::to_owned));
+    if let Some(icon) = icon {
         builder = builder.icon(icon);
     }
     #[cfg(target_os = "macos")]
@@ -196,27 +621,371 @@ fn build_tray(app: &tauri::App) -> tauri::Result<TraySummaryItems> {
 
     Ok(TraySummaryItems {
         tray_icon,
-        black_icon,
         overview_item,
+        session_item,
         toggle_item,
+        shortcuts_toggle_item,
+        profile_menu,
     })
 }
 
+// Shortcut collection toggle, only shown when `tray_shortcuts_toggle` is on; like the profile
+// menu it is read at launch.
+fn build_shortcuts_toggle_item(app: &tauri::App) -> tauri::Result<Option<AppMenuItem>> {
+    let (enabled, shortcuts_paused) = match app.state::<AppState>().config.lock() {
+        Ok(config) => (config.tray_shortcuts_toggle, config.shortcuts_paused),
+        Err(_) => return Ok(None),
+    };
+    if !enabled {
+        return Ok(None);
+    }
+    MenuItemBuilder::with_id("shortcuts_toggle", shortcuts_toggle_text(shortcuts_paused))
+        .enabled(true)
+        .build(app)
+        .map(Some)
+}
+
+fn shortcuts_toggle_text(shortcuts_paused: bool) -> &'static str {
+    if shortcuts_paused {
+        "继续快捷键统计"
+    } else {
+        "暂停快捷键统计"
+    }
+}
+
+// Profile submenu, only shown once the config lists profiles besides the default. The
+// profile list is read at launch; edits to it in the config file apply after a restart.
+fn build_profile_menu(app: &tauri::App) -> tauri::Result<Option<ProfileMenu>> {
+    let (names, active) = match app.state::<AppState>().config.lock() {
+        Ok(config) => (config.profile_names(), config.active_profile.clone()),
+        Err(_) => return Ok(None),
+    };
+    if names.len() < 2 {
+        return Ok(None);
+    }
+    let mut items = Vec::with_capacity(names.len());
+    for name in names {
+        let item = CheckMenuItemBuilder::with_id(format!("{PROFILE_ITEM_PREFIX}{name}"), &name)
+            .checked(name == active)
+            .build(app)?;
+        items.push((name, item));
+    }
+    let item_refs: Vec<&dyn IsMenuItem<Wry>> = items
+        .iter()
+        .map(|(_, item)| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let submenu = SubmenuBuilder::with_id(app, "profiles", profile_menu_title(&active))
+        .items(&item_refs)
+        .build()?;
+    Ok(Some(ProfileMenu { submenu, items }))
+}
+
+fn profile_menu_title(active: &str) -> String {
+    format!("数据配置: {}", active)
+}
+
+// Check marks toggle on click, so every item is reset to match the active profile.
+fn set_tray_profile_menu(menu: &ProfileMenu, active: &str) -> tauri::Result<()> {
+    menu.submenu.set_text(profile_menu_title(active))?;
+    for (name, item) in &menu.items {
+        item.set_checked(name == active)?;
+    }
+    Ok(())
+}
+
+// Quit from the tray menu after the final flush.
+// With `confirm_quit`, the first click relabels the item and only a second click within
+// `QUIT_CONFIRM_WINDOW` quits; the label reverts if no confirmation arrives.
+fn handle_tray_quit(
+    app: &tauri::AppHandle,
+    quit_item: &AppMenuItem,
+    armed_at: &Arc<Mutex<Option<Instant>>>,
+) {
+    let confirm_quit = app
+        .state::<AppState>()
+        .config
+        .lock()
+        .map(|config| config.confirm_quit)
+        .unwrap_or(false);
+    if confirm_quit {
+        let Ok(mut armed) = armed_at.lock() else {
+            flush_and_exit(app, "quit via tray");
+            return;
+        };
+        let confirmed = armed.is_some_and(|at| at.elapsed() <= QUIT_CONFIRM_WINDOW);
+        if !confirmed {
+            let now = Instant::now();
+            *armed = Some(now);
+            let _ = quit_item.set_text(QUIT_CONFIRM_ITEM_TEXT);
+            let quit_item = quit_item.clone();
+            let armed_at = armed_at.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(QUIT_CONFIRM_WINDOW);
+                if let Ok(mut armed) = armed_at.lock() {
+                    if *armed == Some(now) {
+                        *armed = None;
+                        let _ = quit_item.set_text(QUIT_ITEM_TEXT);
+                    }
+                }
+            });
+            return;
+        }
+    }
+    flush_and_exit(app, "quit via tray");
+}
+
+// Stop the tick and tray loops before the exit flush so nothing writes concurrently,
+// logging any loop that did not finish within `BACKGROUND_JOIN_TIMEOUT`.
+pub(crate) fn shutdown_background_threads(background: &BackgroundThreads, app_log_path: &PathBuf) {
+    let still_running = background.shutdown_background_threads(BACKGROUND_JOIN_TIMEOUT);
+    if !still_running.is_empty() {
+        let _ = collector::append_app_log(
+            app_log_path,
+            &format!(
+                "background threads still running at shutdown: {}",
+                still_running.join(", ")
+            ),
+        );
+    }
+}
+
+// Final flush of stats, analytics, CSV and config, then exit.
+pub(crate) fn flush_and_exit(app: &tauri::AppHandle, reason: &str) {
+    final_flush(app, reason);
+    app.exit(0);
+}
+
+// Final flush of stats, analytics, CSV and config before exiting, at most once per run. Runs
+// on a worker thread so a hung storage write cannot block exit past `QUIT_FLUSH_TIMEOUT`.
+fn final_flush(app: &tauri::AppHandle, reason: &str) {
+    // Exit events can arrive before setup managed the state.
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    if state.final_flush_started.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app_log_path = state.app_log_path.clone();
+    let _ = collector::append_app_log(&app_log_path, reason);
+    let inner = state.inner.clone();
+    let config = state.config.clone();
+    let config_path = state.config_path.clone();
+    let background = state.background.clone();
+    let worker_log_path = app_log_path.clone();
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        shutdown_background_threads(&background, &worker_log_path);
+        let flush_result = match inner.lock() {
+            Ok(mut locked) => collector::flush_now(&mut locked),
+            Err(_) => Err("state lock failed".to_string()),
+        };
+        let config_result = match config.lock() {
+            Ok(config) => save_app_config(&config_path, &config),
+            Err(_) => Err("config lock failed".to_string()),
+        };
+        let _ = tx.send(flush_result.and(config_result));
+    });
+    match rx.recv_timeout(QUIT_FLUSH_TIMEOUT) {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => {
+            let _ =
+                collector::append_app_log(&app_log_path, &format!("final flush failed: {}", err));
+        }
+        Err(_) => {
+            let _ = collector::append_app_log(
+                &app_log_path,
+                &format!(
+                    "final flush timed out after {}s, exiting anyway",
+                    QUIT_FLUSH_TIMEOUT.as_secs()
+                ),
+            );
+        }
+    }
+    collector::flush_logs();
+}
+
+// Cache the display layout for per-display key counts. macOS enumerates NSScreen and follows
+// screen parameter notifications itself; elsewhere the monitor list is read here, again when a
+// window's scale factor changes, which is how display changes show up there.
+fn refresh_displays(app: &tauri::AppHandle) {
+    if cfg!(target_os = "macos") {
+        collector::watch_displays();
+        return;
+    }
+    let monitors = match app.available_monitors() {
+        Ok(monitors) => monitors,
+        Err(err) => {
+            append_tray_log(app, &format!("failed to list displays: {}", err));
+            return;
+        }
+    };
+    let displays = monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| {
+            let id = monitor
+                .name()
+                .cloned()
+                .unwrap_or_else(|| format!("display-{}", index + 1));
+            collector::DisplayBounds {
+                name: id.clone(),
+                id,
+                x: monitor.position().x as f64,
+                y: monitor.position().y as f64,
+                width: monitor.size().width as f64,
+                height: monitor.size().height as f64,
+            }
+        })
+        .collect();
+    collector::set_displays(displays);
+}
+
+/// Pause/resume entry point for shortcut collection (command, tray): applies `next` to the
+/// current flag under the state lock, persists it and redraws the tray menu.
+pub(crate) fn set_shortcuts_paused_and_notify(
+    app: &tauri::AppHandle,
+    source: &str,
+    next: impl FnOnce(bool) -> bool,
+) {
+    let state = app.state::<AppState>();
+    let summary = {
+        let Ok(mut locked) = state.inner.lock() else {
+            return;
+        };
+        let shortcuts_paused = next(locked.today_summary().shortcuts_paused);
+        if let Ok(mut config) = state.config.lock() {
+            config.shortcuts_paused = shortcuts_paused;
+            let _ = save_app_config(&state.config_path, &config);
+        }
+        locked.set_shortcuts_paused_from(shortcuts_paused, source)
+    };
+    if let Some(items) = app.try_state::<TraySummaryItems>() {
+        if let Err(err) = set_tray_menu_text(&items, &summary) {
+            append_tray_log(app, &format!("failed to refresh tray menu: {}", err));
+        }
+    }
+}
+
+fn toggle_paused_from_tray(app: &tauri::AppHandle) {
+    set_paused_and_notify(app, "tray", |paused| !paused);
+}
+
+/// Central pause/resume entry point for every path (command, tray). `next` maps the current
+/// paused flag to the new one under the state lock, so toggles cannot race each other.
+pub(crate) fn set_paused_and_notify(
+    app: &tauri::AppHandle,
+    source: &str,
+    next: impl FnOnce(bool) -> bool,
+) {
+    let state = app.state::<AppState>();
+    let summary = {
+        let Ok(mut locked) = state.inner.lock() else {
+            return;
+        };
+        let was_paused = locked.today_summary().paused;
+        let paused = next(was_paused);
+        let summary = locked.set_paused_from(paused, source);
+        // Pausing often comes right before quitting or copying the data files.
+        if paused && !was_paused {
+            if let Err(err) = collector::flush_now(&mut locked) {
+                let _ = collector::append_app_log(
+                    &state.app_log_path,
+                    &format!("flush on pause failed: {}", err),
+                );
+            }
+        }
+        summary
+    };
+    notify_capture_state_changed(app, &summary);
+}
+
+// Push the capture state to every webview and redraw the tray right away instead of
+// waiting for the next frontend poll or tray updater tick.
+pub(crate) fn notify_capture_state_changed(
+    app: &tauri::AppHandle,
+    summary: &collector::TodaySummary,
+) {
+    if let Err(err) = app.emit(CAPTURE_STATE_CHANGED_EVENT, summary.capture_state()) {
+        append_tray_log(app, &format!("failed to emit capture state: {}", err));
+    }
+    apply_menu_bar_mode_immediately(app, summary);
+    if let Some(items) = app.try_state::<TraySummaryItems>() {
+        if let Err(err) = set_tray_menu_text(&items, summary) {
+            append_tray_log(app, &format!("failed to refresh tray menu: {}", err));
+        }
+    }
+}
+
 // Handle tray icon click to toggle a custom lightweight popover window.
+// Double-click always opens the main panel; single left click follows `tray_left_click_action`.
+// The single-click action waits out the double-click interval and is dropped when a double-click
+// arrives meanwhile. Platforms that never deliver double-click events simply keep the
+// single-click behavior, only delayed.
 fn handle_tray_icon_event(tray: &tauri::tray::TrayIcon<Wry>, event: TrayIconEvent) {
-    let TrayIconEvent::Click {
-        button,
-        button_state,
-        position,
-        ..
-    } = event
-    else {
-        return;
+    let app = tray.app_handle();
+    let (button, button_state, position) = match event {
+        TrayIconEvent::DoubleClick { button, .. } => {
+            if button == MouseButton::Left {
+                app.state::<AppState>()
+                    .tray_click_generation
+                    .fetch_add(1, Ordering::SeqCst);
+                if let Err(err) = show_main_window(app) {
+                    append_tray_log(app, &format!("failed to show main window: {}", err));
+                }
+            }
+            return;
+        }
+        TrayIconEvent::Click {
+            button,
+            button_state,
+            position,
+            ..
+        } => (button, button_state, position),
+        _ => return,
     };
     if button != MouseButton::Left || button_state != MouseButtonState::Up {
         return;
     }
-    let app = tray.app_handle();
+    let generation = app
+        .state::<AppState>()
+        .tray_click_generation
+        .load(Ordering::SeqCst);
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(TRAY_DOUBLE_CLICK_INTERVAL);
+        if app
+            .state::<AppState>()
+            .tray_click_generation
+            .load(Ordering::SeqCst)
+            != generation
+        {
+            return;
+        }
+        handle_tray_left_click(&app, position);
+    });
+}
+
+// Single left click that no double-click followed: run `tray_left_click_action`.
+fn handle_tray_left_click(app: &tauri::AppHandle, position: PhysicalPosition<f64>) {
+    let action = app
+        .state::<AppState>()
+        .inner
+        .lock()
+        .map(|locked| locked.tray_left_click_action())
+        .unwrap_or_default();
+    match action {
+        TrayLeftClickAction::Menu => {}
+        TrayLeftClickAction::OpenWindow => {
+            if let Err(err) = show_main_window(app) {
+                append_tray_log(app, &format!("failed to show main window: {}", err));
+            }
+            return;
+        }
+        TrayLeftClickAction::TogglePause => {
+            toggle_paused_from_tray(app);
+            return;
+        }
+    }
     if let Some(popover) = app.get_webview_window(TRAY_POPOVER_LABEL) {
         if let Ok(true) = popover.is_visible() {
             let _ = popover.hide();
@@ -234,6 +1003,7 @@ fn ensure_tray_popover_window(app: &tauri::AppHandle) -> Result<tauri::WebviewWi
         return Ok(window);
     }
 
+    let reduce_transparency = reduce_transparency();
     let window = WebviewWindowBuilder::new(
         app,
         TRAY_POPOVER_LABEL,
@@ -245,8 +1015,8 @@ fn ensure_tray_popover_window(app: &tauri::AppHandle) -> Result<tauri::WebviewWi
     .maximizable(false)
     .minimizable(false)
     .decorations(false)
-    .transparent(true)
-    .background_color(Color(0, 0, 0, 0))
+    .transparent(!reduce_transparency)
+    .background_color(tray_popover_background(reduce_transparency))
     .shadow(true)
     .always_on_top(true)
     .skip_taskbar(true)
@@ -255,7 +1025,7 @@ fn ensure_tray_popover_window(app: &tauri::AppHandle) -> Result<tauri::WebviewWi
     .map_err(|err| err.to_string())?;
 
     #[cfg(target_os = "macos")]
-    {
+    if !reduce_transparency {
         // Prefer Menu material for tray popover to better match macOS native control-center glass.
         if let Err(err) = window.set_effects(
             EffectsBuilder::new()
@@ -325,6 +1095,87 @@ pub(crate) fn hide_tray_popover(app: &tauri::AppHandle) {
     }
 }
 
+// Apply saved main window bounds (clamped to connected monitors) before the first show. The
+// saved position is the outer top-left corner and the saved size the inner size.
+// With `start_hidden` the window stays hidden and only the tray is visible.
+fn restore_main_window_bounds(app: &tauri::AppHandle, bounds: Option<WindowBounds>, show: bool) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if let Some(bounds) = bounds {
+        let monitors: Vec<WindowBounds> = window
+            .available_monitors()
+            .unwrap_or_default()
+            .iter()
+            .map(|monitor| WindowBounds {
+                x: monitor.position().x,
+                y: monitor.position().y,
+                width: monitor.size().width,
+                height: monitor.size().height,
+            })
+            .collect();
+        let bounds = bounds.clamp_to_monitors(&monitors);
+        let _ = window.set_size(PhysicalSize::new(bounds.width, bounds.height));
+        let _ = window.set_position(PhysicalPosition::new(bounds.x, bounds.y));
+    }
+    if show {
+        let _ = window.show();
+    }
+}
+
+// Debounce move/resize bursts: only the last event within the delay writes the config.
+fn schedule_main_window_bounds_save(app: &tauri::AppHandle) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let generation = state
+        .window_bounds_generation
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(MAIN_WINDOW_BOUNDS_SAVE_DELAY);
+        let state = app.state::<AppState>();
+        if state.window_bounds_generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        save_main_window_bounds(&app, &state);
+    });
+}
+
+fn save_main_window_bounds(app: &tauri::AppHandle, state: &AppState) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    // Minimized/maximized geometry is not a useful restore target.
+    if window.is_minimized().unwrap_or(true) || window.is_maximized().unwrap_or(true) {
+        return;
+    }
+    // `set_size` on restore takes the inner size, so the outer size would grow the window by
+    // its decorations on every launch.
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+        return;
+    };
+    let bounds = WindowBounds {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    if let Ok(mut config) = state.config.lock() {
+        if config.main_window_bounds == Some(bounds) {
+            return;
+        }
+        config.main_window_bounds = Some(bounds);
+        if let Err(err) = save_app_config(&state.config_path, &config) {
+            let _ = collector::append_app_log(
+                &state.app_log_path,
+                &format!("failed to save main window bounds: {}", err),
+            );
+        }
+    }
+}
+
 // Open main dashboard window from tray interactions and keep popover hidden.
 pub(crate) fn show_main_window(app: &tauri::AppHandle) -> Result<(), String> {
     let Some(window) = app.get_webview_window("main") else {
@@ -345,126 +1196,346 @@ fn append_tray_log(app: &tauri::AppHandle, message: &str) {
 }
 
 fn start_tray_updater(
+    app: tauri::AppHandle,
     state: Arc<Mutex<collector::CollectorState>>,
     items: TraySummaryItems,
-    tick_interval: std::time::Duration,
-) {
-    let mut last_total_keys = 0u64;
+    shutdown: Arc<Shutdown>,
+) -> std::thread::JoinHandle<()> {
     let mut last_title: Option<String> = None;
     let mut last_mode = MenuBarDisplayMode::default();
+    let mut last_paused = false;
+    let mut appearance = AppearanceWatcher::default();
     let _ = update_tray_summary(
         &items,
-        &get_snapshot_from_state(&state),
-        &mut last_total_keys,
+        &get_today_summary_from_state(&state),
+        &mut appearance,
         &mut last_title,
         &mut last_mode,
+        &mut last_paused,
     );
     std::thread::spawn(move || loop {
-        std::thread::sleep(tick_interval);
-        let snapshot = get_snapshot_from_state(&state);
+        // Re-read every cycle so `update_timing_settings` applies without a restart.
+        let tick_interval = match state.lock() {
+            Ok(locked) => locked.tray_update_interval(),
+            Err(_) => std::time::Duration::from_secs(1),
+        };
+        if shutdown.wait_timeout(tick_interval) {
+            break;
+        }
+        let summary = get_today_summary_from_state(&state);
         let _ = update_tray_summary(
             &items,
-            &snapshot,
-            &mut last_total_keys,
+            &summary,
+            &mut appearance,
             &mut last_title,
             &mut last_mode,
+            &mut last_paused,
+        );
+        announce_met_app_goals(&app);
+        announce_weekly_comparison(&app);
+        announce_storage_degraded(&app);
+        apply_profile_switch_request(&app);
+        send_telemetry_if_due(&app);
+        check_updates_if_due(&app);
+    })
+}
+
+// Emit one `app-goal-met` event per app goal reached since the last tick. The announcement
+// flags are saved to config first so a restart later the same day stays quiet.
+fn announce_met_app_goals(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let (newly_met, notified) = {
+        let Ok(mut locked) = state.inner.lock() else {
+            return;
+        };
+        let newly_met = locked.take_newly_met_app_goals();
+        if newly_met.is_empty() {
+            return;
+        }
+        (newly_met, locked.app_goal_notified())
+    };
+    if let Ok(mut config) = state.config.lock() {
+        config.app_time_goals_notified = notified;
+        let _ = save_app_config(&state.config_path, &config);
+    }
+    for progress in &newly_met {
+        if let Err(err) = app.emit(APP_GOAL_MET_EVENT, progress) {
+            append_tray_log(app, &format!("failed to emit app goal met: {}", err));
+        }
+    }
+}
+
+// Emit `weekly-comparison` once a week from Monday morning. The marker lives with the
+// analytics, so the next flush persists it and a restart the same week stays quiet.
+fn announce_weekly_comparison(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let comparison = {
+        let Ok(mut locked) = state.inner.lock() else {
+            return;
+        };
+        let Some(comparison) = locked.take_weekly_comparison_due(chrono::Local::now()) else {
+            return;
+        };
+        comparison
+    };
+    if let Err(err) = app.emit(WEEKLY_COMPARISON_EVENT, comparison) {
+        append_tray_log(app, &format!("failed to emit weekly comparison: {}", err));
+    }
+}
+
+// Emit `storage-degraded` once when saves keep failing, so the UI can tell the user their data
+// directory is unwritable while stats pile up in memory.
+fn announce_storage_degraded(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let error = {
+        let Ok(mut locked) = state.inner.lock() else {
+            return;
+        };
+        let Some(error) = locked.take_storage_degraded_announcement() else {
+            return;
+        };
+        error
+    };
+    if let Err(err) = app.emit(STORAGE_DEGRADED_EVENT, error) {
+        append_tray_log(app, &format!("failed to emit storage degraded: {}", err));
+    }
+}
+
+// Perform a switch queued by the profile rules on the tick loop, through the manual path.
+fn apply_profile_switch_request(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let request = {
+        let Ok(mut locked) = state.inner.lock() else {
+            return;
+        };
+        let Some(request) = locked.take_profile_switch_request() else {
+            return;
+        };
+        request
+    };
+    if let Err(err) = switch_profile_and_notify(app, &request.profile, Some(&request.reason)) {
+        append_tray_log(
+            app,
+            &format!(
+                "auto switch to profile {} by {} failed: {}",
+                request.profile, request.reason, err
+            ),
         );
+    }
+}
+
+// Count every command the handler dispatched for the opt-in telemetry. Unknown command names
+// are not dispatched and therefore never counted.
+fn count_commands(
+    handler: impl Fn(tauri::ipc::Invoke<Wry>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<Wry>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let command = invoke.message.command().to_string();
+        let webview = invoke.message.webview();
+        let handled = handler(invoke);
+        if handled {
+            if let Some(state) = webview.try_state::<AppState>() {
+                if let Ok(mut telemetry) = state.telemetry.lock() {
+                    telemetry.record_command(&command);
+                }
+            }
+        }
+        handled
+    }
+}
+
+// Save telemetry counters now and then, and post the weekly report off the tray thread once it
+// is due. Failures are logged and retried later; they never reach the UI.
+fn send_telemetry_if_due(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(config) = state.config.lock().map(|config| config.clone()) else {
+        return;
+    };
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let report = {
+        let Ok(mut telemetry) = state.telemetry.lock() else {
+            return;
+        };
+        if let Err(err) = telemetry.save_if_due(now_ms) {
+            let _ = collector::append_app_log(
+                &state.app_log_path,
+                &format!("telemetry counters save failed: {}", err),
+            );
+        }
+        telemetry.take_due_report(&config, now_ms)
+    };
+    let Some(report) = report else {
+        return;
+    };
+    let telemetry = state.telemetry.clone();
+    let app_log_path = state.app_log_path.clone();
+    let endpoint = config.telemetry_endpoint.trim().to_string();
+    std::thread::spawn(move || {
+        let result = telemetry::post_report(&endpoint, &report);
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let message = match &result {
+            Ok(()) => "telemetry report sent".to_string(),
+            Err(err) => format!("telemetry report failed: {}", err),
+        };
+        let _ = collector::append_app_log(&app_log_path, &message);
+        let Ok(mut telemetry) = telemetry.lock() else {
+            return;
+        };
+        telemetry.finish_report(&report, &result, now_ms);
+        if let Err(err) = telemetry.save(now_ms) {
+            let _ = collector::append_app_log(
+                &app_log_path,
+                &format!("telemetry counters save failed: {}", err),
+            );
+        }
     });
 }
 
-fn get_snapshot_from_state(state: &Arc<Mutex<collector::CollectorState>>) -> StatsSnapshot {
-    if let Ok(locked) = state.lock() {
-        return locked.snapshot();
+/// Check GitHub for a newer release and record the result for the snapshot badge. A failed
+/// check logs a warning and reads as unknown. Blocks on the network, so callers run it off the
+/// main and tray threads.
+pub(crate) fn check_for_updates_blocking(app: &tauri::AppHandle) -> update_check::UpdateInfo {
+    let state = app.state::<AppState>();
+    let url = state
+        .config
+        .lock()
+        .map(|config| config.update_check_url.trim().to_string())
+        .unwrap_or_else(|_| app_config::DEFAULT_UPDATE_CHECK_URL.to_string());
+    let info = update_check::fetch_update_info(&url).unwrap_or_else(|err| {
+        let _ = collector::append_app_log(
+            &state.app_log_path,
+            &format!("warning: update check failed: {}", err),
+        );
+        update_check::UpdateInfo::unknown()
+    });
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let update_available = match state.update_check.lock() {
+        Ok(mut check) => {
+            check.finish(info.clone(), now_ms);
+            check.update_available()
+        }
+        Err(_) => info.update_available == Some(true),
+    };
+    if let Ok(mut locked) = state.inner.lock() {
+        locked.set_update_available(update_available);
     }
-    StatsSnapshot {
-        rows: vec![],
-        paused: false,
-        auto_paused: false,
-        auto_pause_reason: None,
-        keyboard_active: false,
-        ignore_key_combos: false,
-        excluded_bundle_ids: vec![],
-        one_password_suggestion_pending: false,
-        tray_display_mode: MenuBarDisplayMode::default().as_str().to_string(),
-        last_error: Some("state lock failed".to_string()),
-        log_path: "".to_string(),
-        shortcut_stats: vec![],
+    info
+}
+
+// Start the daily automatic update check on its own thread once it is due.
+fn check_updates_if_due(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let enabled = state
+        .config
+        .lock()
+        .map(|config| config.auto_update_check)
+        .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let due = state
+        .update_check
+        .lock()
+        .map(|mut check| check.begin_if_due(now_ms))
+        .unwrap_or(false);
+    if !due {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        check_for_updates_blocking(&app);
+    });
+}
+
+fn get_today_summary_from_state(
+    state: &Arc<Mutex<collector::CollectorState>>,
+) -> collector::TodaySummary {
+    if let Ok(mut locked) = state.lock() {
+        locked.refresh_today_totals();
+        return locked.today_summary();
     }
+    collector::TodaySummary::default()
 }
 
+// `summary` is read for the current local day on every tick, with the day totals refreshed at
+// midnight, so the title and overview roll over without waiting for the first key.
 fn update_tray_summary(
     items: &TraySummaryItems,
-    snapshot: &StatsSnapshot,
-    last_total_keys: &mut u64,
+    summary: &collector::TodaySummary,
+    appearance: &mut AppearanceWatcher,
     last_title: &mut Option<String>,
     last_mode: &mut MenuBarDisplayMode,
+    last_paused: &mut bool,
 ) -> tauri::Result<()> {
-    let today_prefix = Local::now().format("%Y-%m-%d").to_string();
-    let (active, keys) = snapshot
-        .rows
-        .iter()
-        .filter(|row| row.date.starts_with(&today_prefix))
-        .fold((0u64, 0u64), |mut acc, row| {
-            acc.0 += row.active_typing_ms;
-            acc.1 += row.key_count;
-            acc
-        });
-
-    let mode = MenuBarDisplayMode::from_str(&snapshot.tray_display_mode).unwrap_or_default();
-    let paused = snapshot.paused || snapshot.auto_paused;
-    let toggle_text = if snapshot.paused {
-        "继续采集".to_string()
-    } else {
-        "暂停采集".to_string()
-    };
-    let compact_keys = format_compact_number(keys);
-    let title_text = if snapshot.auto_paused {
-        "暂停".to_string()
-    } else {
-        compact_keys.clone()
-    };
-    let title = match mode {
-        MenuBarDisplayMode::IconOnly => Some(String::new()),
-        MenuBarDisplayMode::TextOnly | MenuBarDisplayMode::IconText => Some(title_text),
-    };
-    let should_update_icon = mode != *last_mode;
+    let mode = summary.menu_bar_display_mode;
+    let paused = summary.paused || summary.auto_paused;
+    let title = Some(tray_title(summary));
+    // Appearance is polled every tick, so a light/dark switch redraws the icon within one interval.
+    let system_appearance = current_appearance();
+    let appearance_changed = appearance.observe(system_appearance);
+    let should_update_icon = appearance_changed || mode != *last_mode || paused != *last_paused;
     let should_update_title = mode != *last_mode || title != *last_title;
     if should_update_icon {
-        match mode {
-            MenuBarDisplayMode::TextOnly => {
-                let _ = items.tray_icon.set_icon(None);
-            }
-            MenuBarDisplayMode::IconOnly | MenuBarDisplayMode::IconText => {
-                let _ = items.tray_icon.set_icon(items.black_icon.clone());
-                #[cfg(target_os = "macos")]
-                {
-                    let _ = items.tray_icon.set_icon_as_template(true);
-                }
-            }
-        }
+        set_tray_icon(
+            &items.tray_icon,
+            tray_icon_for(mode, system_appearance, paused),
+        );
     }
     if should_update_title {
         let _ = items.tray_icon.set_title(title.clone());
     }
 
+    set_tray_menu_text(items, summary)?;
+
+    *last_title = title;
+    *last_mode = mode;
+    *last_paused = paused;
+    Ok(())
+}
+
+// Menu text for the overview lines and the pause toggle.
+fn set_tray_menu_text(
+    items: &TraySummaryItems,
+    summary: &collector::TodaySummary,
+) -> tauri::Result<()> {
+    let paused = summary.paused || summary.auto_paused;
     items.overview_item.set_text(format!(
         "今日时长: {} | 今日总键数: {}{}",
-        format_hm(active),
-        compact_keys,
+        format_hm(summary.active_typing_ms),
+        format_compact_number(summary.key_count),
         if paused {
             " | 当前状态: 暂停"
         } else {
             ""
         }
     ))?;
-    items.toggle_item.set_text(toggle_text)?;
-
-    *last_total_keys = keys;
-    *last_title = title;
-    *last_mode = mode;
+    items
+        .session_item
+        .set_text(live_session_text(summary.live_session.as_ref()))?;
+    items.toggle_item.set_text(if summary.paused {
+        "继续采集"
+    } else {
+        "暂停采集"
+    })?;
+    if let Some(shortcuts_toggle_item) = &items.shortcuts_toggle_item {
+        shortcuts_toggle_item.set_text(shortcuts_toggle_text(summary.shortcuts_paused))?;
+    }
     Ok(())
 }
 
+// Second overview line, e.g. "当前会话: 14m · 2.1k keys"; empty when no session is running.
+fn live_session_text(session: Option<&collector::LiveSessionSummary>) -> String {
+    let Some(session) = session else {
+        return String::new();
+    };
+    format!(
+        "当前会话: {}m · {} keys",
+        session.duration_ms / 1000 / 60,
+        format_compact_number(session.key_count)
+    )
+}
+
 fn format_hm(ms: u64) -> String {
     let total_minutes = ms / 1000 / 60;
     let hours = total_minutes / 60;
@@ -472,71 +1543,230 @@ fn format_hm(ms: u64) -> String {
     format!("{}h {}m", hours, minutes)
 }
 
-fn format_compact_number(value: u64) -> String {
-    if value < 1_000 {
-        return value.to_string();
+// Menu bar title of `summary` in its display mode; the tray updater and an immediate mode
+// change both use it, so they cannot disagree.
+fn tray_title(summary: &collector::TodaySummary) -> String {
+    let compact_keys = format_compact_number(summary.key_count);
+    let title_text = tray_title_text(summary, &compact_keys);
+    tray_title_for_mode(summary.menu_bar_display_mode, summary, title_text)
+}
+
+// Title shown next to the tray icon; paused states get a prefix so they stay visible.
+fn tray_title_text(summary: &collector::TodaySummary, compact_keys: &str) -> String {
+    if summary.auto_paused {
+        let prefix = if summary.auto_pause_reason.as_deref() == Some("secure_input") {
+            "🔒"
+        } else {
+            "⏸"
+        };
+        return format!("{prefix} 暂停");
     }
-    if value < 1_000_000 {
-        return format_one_decimal(value as f64 / 1_000f64, "k");
+    if summary.paused {
+        return format!("⏸ {compact_keys}");
     }
-    if value < 1_000_000_000 {
-        return format_one_decimal(value as f64 / 1_000_000f64, "m");
+    compact_keys.to_string()
+}
+
+// Title for the display mode. The sparkline gives way to the paused title, which matters more.
+// An unwritable data directory adds a warning prefix in every mode, icon-only included.
+fn tray_title_for_mode(
+    mode: MenuBarDisplayMode,
+    summary: &collector::TodaySummary,
+    title_text: String,
+) -> String {
+    let title = match mode {
+        MenuBarDisplayMode::IconOnly => String::new(),
+        MenuBarDisplayMode::TextOnly | MenuBarDisplayMode::IconText => title_text,
+        MenuBarDisplayMode::Sparkline if summary.paused || summary.auto_paused => title_text,
+        MenuBarDisplayMode::Sparkline => sparkline_text(&summary.minute_counts),
+    };
+    match (summary.storage_unwritable, title.is_empty()) {
+        (false, _) => title,
+        (true, true) => "⚠️".to_string(),
+        (true, false) => format!("⚠️ {title}"),
     }
-    format_one_decimal(value as f64 / 1_000_000_000f64, "b")
 }
 
-fn format_one_decimal(base: f64, suffix: &str) -> String {
-    let rounded = (base * 10.0).round() / 10.0;
-    if (rounded - rounded.trunc()).abs() < f64::EPSILON {
-        format!("{}{}", rounded as u64, suffix)
-    } else {
-        format!("{:.1}{}", rounded, suffix)
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+// Minutes per sparkline bar; an hour renders as 12 bars to stay narrow in the menu bar.
+const SPARKLINE_BAR_MINUTES: usize = 5;
+
+// Block-character sparkline of per-minute counts (oldest first), scaled to the busiest bar.
+// Idle bars use the lowest block; any typing lifts a bar at least one level above it.
+fn sparkline_text(minute_counts: &[u64]) -> String {
+    let bars: Vec<u64> = minute_counts
+        .chunks(SPARKLINE_BAR_MINUTES)
+        .map(|chunk| chunk.iter().sum())
+        .collect();
+    let max = bars.iter().copied().max().unwrap_or(0);
+    let top = (SPARKLINE_LEVELS.len() - 1) as u64;
+    bars.iter()
+        .map(|&count| {
+            let level = if max == 0 {
+                0
+            } else {
+                (count * top).div_ceil(max)
+            };
+            SPARKLINE_LEVELS[level as usize]
+        })
+        .collect()
+}
+
+// Tray icon for the display mode, menu bar appearance and pause state. Text-only mode has
+// no icon; dark menu bars get the white assets, and a paused asset that fails to decode
+// falls back to the regular one.
+fn tray_icon_for(
+    mode: MenuBarDisplayMode,
+    appearance: SystemAppearance,
+    paused: bool,
+) -> Option<Image<'static>> {
+    if mode == MenuBarDisplayMode::TextOnly {
+        return None;
+    }
+    let (regular, paused_bytes): (&'static [u8], &'static [u8]) = match appearance {
+        SystemAppearance::Light => (
+            include_bytes!("../icons/l_black.png"),
+            include_bytes!("../icons/l_black_paused.png"),
+        ),
+        SystemAppearance::Dark => (
+            include_bytes!("../icons/l_white.png"),
+            include_bytes!("../icons/l_white_paused.png"),
+        ),
+    };
+    let paused_icon = paused
+        .then(|| Image::from_bytes(paused_bytes).ok())
+        .flatten();
+    paused_icon
+        .or_else(|| Image::from_bytes(regular).ok())
+        .map(Image::to_owned)
+}
+
+fn set_tray_icon(tray: &tauri::tray::TrayIcon<Wry>, icon: Option<Image<'static>>) {
+    #[cfg(target_os = "macos")]
+    let template = icon.is_some();
+    let _ = tray.set_icon(icon);
+    #[cfg(target_os = "macos")]
+    if template {
+        let _ = tray.set_icon_as_template(true);
+    }
+}
+
+// Opaque popover background when vibrancy is off, matching the current appearance.
+fn tray_popover_background(reduce_transparency: bool) -> Color {
+    if !reduce_transparency {
+        return Color(0, 0, 0, 0);
+    }
+    match current_appearance() {
+        SystemAppearance::Light => Color(246, 246, 246, 255),
+        SystemAppearance::Dark => Color(40, 40, 40, 255),
+    }
+}
+
+const COMPACT_NUMBER_UNITS: [(u64, &str); 3] =
+    [(1_000, "k"), (1_000_000, "m"), (1_000_000_000, "b")];
+const COMPACT_NUMBER_MAX_PRECISION: usize = 3;
+
+fn format_compact_number(value: u64) -> String {
+    format_compact_number_with_precision(value, 1)
+}
+
+// Compact a counter with up to `precision` decimals, dropping trailing zeros (`1.50k` -> `1.5k`).
+pub(crate) fn format_compact_number_with_precision(value: u64, precision: usize) -> String {
+    if value < COMPACT_NUMBER_UNITS[0].0 {
+        return value.to_string();
+    }
+    let precision = precision.min(COMPACT_NUMBER_MAX_PRECISION);
+    let scale = 10f64.powi(precision as i32);
+    let mut index = COMPACT_NUMBER_UNITS
+        .iter()
+        .rposition(|(unit, _)| value >= *unit)
+        .unwrap_or(0);
+    loop {
+        let (unit, suffix) = COMPACT_NUMBER_UNITS[index];
+        let rounded = (value as f64 / unit as f64 * scale).round() / scale;
+        // Rounding can carry the mantissa to 1000 (999_950 -> "1000k"); promote to the next unit.
+        if rounded >= 1_000.0 && index + 1 < COMPACT_NUMBER_UNITS.len() {
+            index += 1;
+            continue;
+        }
+        let mantissa = format!("{:.*}", precision, rounded);
+        let mantissa = if mantissa.contains('.') {
+            mantissa.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            mantissa.as_str()
+        };
+        return format!("{mantissa}{suffix}");
     }
 }
 
-pub(crate) fn apply_menu_bar_mode_immediately(app: &tauri::AppHandle, snapshot: &StatsSnapshot) {
+pub(crate) fn apply_menu_bar_mode_immediately(
+    app: &tauri::AppHandle,
+    summary: &collector::TodaySummary,
+) {
     let Some(tray) = app.tray_by_id("main-tray") else {
         return;
     };
-    let today_prefix = Local::now().format("%Y-%m-%d").to_string();
-    let keys = snapshot
-        .rows
-        .iter()
-        .filter(|row| row.date.starts_with(&today_prefix))
-        .fold(0u64, |acc, row| acc + row.key_count);
-    let compact_keys = format_compact_number(keys);
-    let title_text = if snapshot.auto_paused {
-        "暂停".to_string()
-    } else {
-        compact_keys
+    let paused = summary.paused || summary.auto_paused;
+    let mode = summary.menu_bar_display_mode;
+    set_tray_icon(&tray, tray_icon_for(mode, current_appearance(), paused));
+    let _ = tray.set_title(Some(tray_title(summary)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        format_compact_number, format_compact_number_with_precision, live_session_text,
+        sparkline_text,
     };
-    let mode = MenuBarDisplayMode::from_str(&snapshot.tray_display_mode).unwrap_or_default();
-    match mode {
-        MenuBarDisplayMode::IconOnly => {
-            let _ = tray.set_title(Some(String::new()));
-            let icon = Image::from_bytes(include_bytes!("../icons/l_black.png"))
-                .ok()
-                .or_else(|| app.default_window_icon().cloned());
-            let _ = tray.set_icon(icon);
-            #[cfg(target_os = "macos")]
-            {
-                let _ = tray.set_icon_as_template(true);
-            }
-        }
-        MenuBarDisplayMode::TextOnly => {
-            let _ = tray.set_icon(None);
-            let _ = tray.set_title(Some(title_text));
-        }
-        MenuBarDisplayMode::IconText => {
-            let icon = Image::from_bytes(include_bytes!("../icons/l_black.png"))
-                .ok()
-                .or_else(|| app.default_window_icon().cloned());
-            let _ = tray.set_icon(icon);
-            #[cfg(target_os = "macos")]
-            {
-                let _ = tray.set_icon_as_template(true);
-            }
-            let _ = tray.set_title(Some(title_text));
-        }
+    use crate::collector::LiveSessionSummary;
+
+    #[test]
+    fn compact_number_promotes_when_rounding_reaches_next_unit() {
+        assert_eq!(format_compact_number(999), "999");
+        assert_eq!(format_compact_number(1_000), "1k");
+        assert_eq!(format_compact_number(1_500), "1.5k");
+        assert_eq!(format_compact_number(999_949), "999.9k");
+        assert_eq!(format_compact_number(999_950), "1m");
+        assert_eq!(format_compact_number(999_999), "1m");
+        assert_eq!(format_compact_number(1_000_000), "1m");
+        assert_eq!(format_compact_number(999_949_999), "999.9m");
+        assert_eq!(format_compact_number(999_950_000), "1b");
+        assert_eq!(format_compact_number(1_000_000_000), "1b");
+        // No unit above billions, so the mantissa is allowed to grow.
+        assert_eq!(format_compact_number(999_999_999_999), "1000b");
+    }
+
+    #[test]
+    fn compact_number_precision_controls_decimals() {
+        assert_eq!(format_compact_number_with_precision(1_234, 2), "1.23k");
+        assert_eq!(format_compact_number_with_precision(1_200, 2), "1.2k");
+        assert_eq!(format_compact_number_with_precision(999_994, 2), "999.99k");
+        assert_eq!(format_compact_number_with_precision(999_995, 2), "1m");
+        assert_eq!(format_compact_number_with_precision(1_499, 0), "1k");
+        assert_eq!(format_compact_number_with_precision(42, 2), "42");
+    }
+
+    #[test]
+    fn live_session_text_formats_minutes_and_compact_keys() {
+        assert_eq!(live_session_text(None), "");
+        let session = LiveSessionSummary {
+            duration_ms: 14 * 60_000 + 59_999,
+            key_count: 2_100,
+        };
+        assert_eq!(
+            live_session_text(Some(&session)),
+            "当前会话: 14m · 2.1k keys"
+        );
+    }
+
+    #[test]
+    fn sparkline_scales_five_minute_bars_to_the_busiest_one() {
+        assert_eq!(sparkline_text(&[0; 60]), "▁".repeat(12));
+        let mut counts = vec![0; 60];
+        counts[0] = 1;
+        counts[30] = 20;
+        counts[31] = 15;
+        counts[59] = 70;
+        assert_eq!(sparkline_text(&counts), "▂▁▁▁▁▁▅▁▁▁▁█");
     }
 }
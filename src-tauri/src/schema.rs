@@ -0,0 +1,59 @@
+//! Storage schema export.
+//! Publishes JSON Schema documents for the on-disk files so external tools can track shape changes.
+
+use schemars::schema_for;
+use serde_json::{json, Value};
+
+use crate::{
+    app_config::AppConfig,
+    collector::{CSV_COLUMNS, PARQUET_EVENT_COLUMNS, PARQUET_STATS_COLUMNS},
+    storage::{
+        StoredAutoExportState, StoredCrashDump, StoredExportChain, StoredInputAnalytics,
+        StoredInputEventChunk, StoredRow, StoredStatusFile, StoredTelemetryState,
+    },
+};
+
+/// Version of the exported storage schema. Bump whenever the snapshot in
+/// `schema/storage-schema.json` changes.
+pub(crate) const STORAGE_SCHEMA_VERSION: u32 = 42;
+
+/// Launch argument that prints the storage schema and exits.
+pub(crate) const DUMP_SCHEMA_ARG: &str = "--dump-schema";
+
+/// Build the combined schema document for daily stats, analytics, crash dump, telemetry, status,
+/// export chain, config, CSV and Parquet export files.
+pub(crate) fn storage_schema() -> Value {
+    json!({
+        "schema_version": STORAGE_SCHEMA_VERSION,
+        "csv_columns": CSV_COLUMNS,
+        "parquet_columns": {
+            "stats": PARQUET_STATS_COLUMNS,
+            "key_events": PARQUET_EVENT_COLUMNS,
+        },
+        "schemas": {
+            "stored_row": schema_for!(StoredRow),
+            "stored_input_analytics": schema_for!(StoredInputAnalytics),
+            "stored_input_event_chunk": schema_for!(StoredInputEventChunk),
+            "stored_crash_dump": schema_for!(StoredCrashDump),
+            "stored_auto_export_state": schema_for!(StoredAutoExportState),
+            "stored_export_chain": schema_for!(StoredExportChain),
+            "stored_telemetry_state": schema_for!(StoredTelemetryState),
+            "status_file": schema_for!(StoredStatusFile),
+            "config": schema_for!(AppConfig),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::storage_schema;
+
+    // Regenerate with `cargo run -- --dump-schema > schema/storage-schema.json` and bump
+    // `STORAGE_SCHEMA_VERSION` when this fails on purpose.
+    #[test]
+    fn storage_schema_matches_checked_in_snapshot() {
+        let snapshot: serde_json::Value =
+            serde_json::from_str(include_str!("../schema/storage-schema.json")).unwrap();
+        assert_eq!(storage_schema(), snapshot);
+    }
+}
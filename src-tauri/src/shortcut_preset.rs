@@ -0,0 +1,265 @@
+//! Shortcut rules presets.
+//! Exports the shortcut counting rules as a shareable JSON preset and imports one, either
+//! replacing the current rules or merging into them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_config::{AppConfig, ShortcutRules};
+
+/// Shape version of `ShortcutRulesPreset`; presets with another version are rejected.
+pub(crate) const SHORTCUT_PRESET_VERSION: u32 = 1;
+/// Upper bound of `min_modifiers`: ctrl, opt, shift and cmd.
+const MAX_MIN_MODIFIERS: u8 = 4;
+/// Upper bound of allowlist plus blocklist entries in one preset.
+const MAX_PRESET_SHORTCUTS: usize = 1_000;
+const MAX_SHORTCUT_ID_LEN: usize = 64;
+
+/// Shortcut counting rules as shared between users. Shortcut ids use the canonical form
+/// (`ctrl_opt_shift_cmd_key`, lowercase), e.g. `cmd_shift_p`.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ShortcutRulesPreset {
+    pub v: u32,
+    pub require_cmd_or_ctrl: bool,
+    pub allow_alt_only: bool,
+    pub min_modifiers: u8,
+    pub allowlist: Vec<String>,
+    pub blocklist: Vec<String>,
+}
+
+/// What an import changed, returned to the caller. On merge the import wins every conflict:
+/// its flags and `min_modifiers` replace the current ones, and an id it lists on one side is
+/// dropped from the other side of the current rules.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct ShortcutRulesDiff {
+    pub merge: bool,
+    /// Settings whose value changed, as `name: before -> after`.
+    pub changed_settings: Vec<String>,
+    pub allowlist_added: Vec<String>,
+    pub allowlist_removed: Vec<String>,
+    pub blocklist_added: Vec<String>,
+    pub blocklist_removed: Vec<String>,
+    /// Rules in effect after the import.
+    pub rules: ShortcutRulesPreset,
+}
+
+// Trim, lowercase, sort and dedupe ids, rejecting ones that cannot be canonical shortcut ids.
+fn normalize_shortcut_ids(ids: &[String], list: &str) -> Result<Vec<String>, String> {
+    let mut normalized = Vec::with_capacity(ids.len());
+    for id in ids {
+        let id = id.trim().to_ascii_lowercase();
+        if id.is_empty() {
+            continue;
+        }
+        let valid = id.len() <= MAX_SHORTCUT_ID_LEN
+            && !id.starts_with('_')
+            && !id.chars().any(|ch| ch.is_whitespace() || ch == ',');
+        if !valid {
+            return Err(format!("invalid shortcut id in {list}: {id:?}"));
+        }
+        normalized.push(id);
+    }
+    normalized.sort();
+    normalized.dedup();
+    Ok(normalized)
+}
+
+impl ShortcutRulesPreset {
+    pub(crate) fn from_config(config: &AppConfig) -> Self {
+        let rules = config.shortcut_rules();
+        Self {
+            v: SHORTCUT_PRESET_VERSION,
+            require_cmd_or_ctrl: rules.require_cmd_or_ctrl,
+            allow_alt_only: rules.allow_alt_only,
+            min_modifiers: rules.min_modifiers.max(1),
+            allowlist: rules.allowlist,
+            blocklist: rules.blocklist,
+        }
+    }
+
+    /// Parse and validate a preset, normalizing its lists.
+    pub(crate) fn parse(json: &str) -> Result<Self, String> {
+        let preset: Self =
+            serde_json::from_str(json).map_err(|e| format!("invalid preset: {e}"))?;
+        if preset.v != SHORTCUT_PRESET_VERSION {
+            return Err(format!("unsupported preset version: {}", preset.v));
+        }
+        if !(1..=MAX_MIN_MODIFIERS).contains(&preset.min_modifiers) {
+            return Err(format!(
+                "min_modifiers must be between 1 and {MAX_MIN_MODIFIERS}"
+            ));
+        }
+        if preset.allowlist.len() + preset.blocklist.len() > MAX_PRESET_SHORTCUTS {
+            return Err(format!(
+                "preset lists more than {MAX_PRESET_SHORTCUTS} shortcuts"
+            ));
+        }
+        let allowlist = normalize_shortcut_ids(&preset.allowlist, "allowlist")?;
+        let blocklist = normalize_shortcut_ids(&preset.blocklist, "blocklist")?;
+        if let Some(id) = allowlist.iter().find(|id| blocklist.contains(id)) {
+            return Err(format!("shortcut id is both allowed and blocked: {id}"));
+        }
+        Ok(Self {
+            allowlist,
+            blocklist,
+            ..preset
+        })
+    }
+
+    /// Rules after importing `incoming` over `self`: the import as is, or merged into the
+    /// current lists with the import winning conflicts.
+    pub(crate) fn import(&self, incoming: &Self, merge: bool) -> ShortcutRulesDiff {
+        let rules = if merge {
+            let union = |current: &[String], dropped: &[String], added: &[String]| {
+                let mut ids: Vec<String> = current
+                    .iter()
+                    .filter(|id| !dropped.contains(id))
+                    .chain(added)
+                    .cloned()
+                    .collect();
+                ids.sort();
+                ids.dedup();
+                ids
+            };
+            Self {
+                allowlist: union(&self.allowlist, &incoming.blocklist, &incoming.allowlist),
+                blocklist: union(&self.blocklist, &incoming.allowlist, &incoming.blocklist),
+                ..incoming.clone()
+            }
+        } else {
+            incoming.clone()
+        };
+        let mut changed_settings = Vec::new();
+        let mut note = |name: &str, before: String, after: String| {
+            if before != after {
+                changed_settings.push(format!("{name}: {before} -> {after}"));
+            }
+        };
+        note(
+            "require_cmd_or_ctrl",
+            self.require_cmd_or_ctrl.to_string(),
+            rules.require_cmd_or_ctrl.to_string(),
+        );
+        note(
+            "allow_alt_only",
+            self.allow_alt_only.to_string(),
+            rules.allow_alt_only.to_string(),
+        );
+        note(
+            "min_modifiers",
+            self.min_modifiers.to_string(),
+            rules.min_modifiers.to_string(),
+        );
+        let missing_from = |ids: &[String], other: &[String]| -> Vec<String> {
+            ids.iter()
+                .filter(|id| !other.contains(id))
+                .cloned()
+                .collect()
+        };
+        ShortcutRulesDiff {
+            merge,
+            changed_settings,
+            allowlist_added: missing_from(&rules.allowlist, &self.allowlist),
+            allowlist_removed: missing_from(&self.allowlist, &rules.allowlist),
+            blocklist_added: missing_from(&rules.blocklist, &self.blocklist),
+            blocklist_removed: missing_from(&self.blocklist, &rules.blocklist),
+            rules,
+        }
+    }
+
+    pub(crate) fn write_to_config(&self, config: &mut AppConfig) {
+        config.set_shortcut_rules(ShortcutRules {
+            require_cmd_or_ctrl: self.require_cmd_or_ctrl,
+            allow_alt_only: self.allow_alt_only,
+            min_modifiers: self.min_modifiers,
+            allowlist: self.allowlist.clone(),
+            blocklist: self.blocklist.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ShortcutRulesPreset, SHORTCUT_PRESET_VERSION};
+
+    fn preset(allowlist: &[&str], blocklist: &[&str]) -> ShortcutRulesPreset {
+        ShortcutRulesPreset {
+            v: SHORTCUT_PRESET_VERSION,
+            require_cmd_or_ctrl: true,
+            allow_alt_only: false,
+            min_modifiers: 1,
+            allowlist: allowlist.iter().map(|id| id.to_string()).collect(),
+            blocklist: blocklist.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parse_normalizes_lists_and_rejects_invalid_presets() {
+        let parsed = ShortcutRulesPreset::parse(
+            r#"{"v":1,"require_cmd_or_ctrl":false,"allow_alt_only":true,"min_modifiers":2,
+                "allowlist":[" CMD_Shift_P ","cmd_shift_p",""],"blocklist":["cmd_q"]}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.allowlist, vec!["cmd_shift_p"]);
+        assert_eq!(parsed.blocklist, vec!["cmd_q"]);
+        assert_eq!(parsed.min_modifiers, 2);
+
+        let with = |field: &str| {
+            let mut json: serde_json::Value = serde_json::from_str(
+                r#"{"v":1,"require_cmd_or_ctrl":true,"allow_alt_only":false,"min_modifiers":1,
+                    "allowlist":[],"blocklist":[]}"#,
+            )
+            .unwrap();
+            let overrides: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(&format!("{{{field}}}")).unwrap();
+            json.as_object_mut().unwrap().extend(overrides);
+            ShortcutRulesPreset::parse(&json.to_string()).unwrap_err()
+        };
+        assert_eq!(with(r#""v":2"#), "unsupported preset version: 2");
+        assert_eq!(
+            with(r#""min_modifiers":5"#),
+            "min_modifiers must be between 1 and 4"
+        );
+        assert!(with(r#""allowlist":["cmd c"]"#).starts_with("invalid shortcut id in allowlist"));
+        assert_eq!(
+            with(r#""allowlist":["cmd_c"],"blocklist":["CMD_C"]"#),
+            "shortcut id is both allowed and blocked: cmd_c"
+        );
+        assert!(ShortcutRulesPreset::parse("{}")
+            .unwrap_err()
+            .starts_with("invalid preset"));
+    }
+
+    #[test]
+    fn merge_unions_lists_and_lets_the_import_win_conflicts() {
+        let current = preset(&["cmd_c", "cmd_v"], &["cmd_q", "cmd_w"]);
+        let incoming = ShortcutRulesPreset {
+            require_cmd_or_ctrl: false,
+            min_modifiers: 2,
+            ..preset(&["cmd_q", "cmd_shift_p"], &["cmd_v"])
+        };
+
+        let merged = current.import(&incoming, true);
+        assert_eq!(
+            merged.rules.allowlist,
+            vec!["cmd_c", "cmd_q", "cmd_shift_p"]
+        );
+        assert_eq!(merged.rules.blocklist, vec!["cmd_v", "cmd_w"]);
+        assert!(!merged.rules.require_cmd_or_ctrl);
+        assert_eq!(
+            merged.changed_settings,
+            vec![
+                "require_cmd_or_ctrl: true -> false",
+                "min_modifiers: 1 -> 2"
+            ]
+        );
+        assert_eq!(merged.allowlist_added, vec!["cmd_q", "cmd_shift_p"]);
+        assert_eq!(merged.allowlist_removed, vec!["cmd_v"]);
+        assert_eq!(merged.blocklist_added, vec!["cmd_v"]);
+        assert_eq!(merged.blocklist_removed, vec!["cmd_q"]);
+
+        let replaced = current.import(&incoming, false);
+        assert_eq!(replaced.rules, incoming);
+        assert_eq!(replaced.allowlist_removed, vec!["cmd_c", "cmd_v"]);
+        assert_eq!(replaced.blocklist_removed, vec!["cmd_q", "cmd_w"]);
+    }
+}
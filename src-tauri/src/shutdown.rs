@@ -0,0 +1,138 @@
+//! Background thread shutdown.
+//! Shared stop signal for the long-running loops and a registry to join them on exit.
+
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+const JOIN_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Stop signal shared by background loops. Loops sleep through `wait_timeout`, so a
+/// request wakes them immediately instead of after their current interval.
+#[derive(Default)]
+pub(crate) struct Shutdown {
+    requested: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl Shutdown {
+    /// Ask every loop holding this signal to exit and wake the sleeping ones.
+    pub(crate) fn request(&self) {
+        if let Ok(mut requested) = self.requested.lock() {
+            *requested = true;
+        }
+        self.wake.notify_all();
+    }
+
+    pub(crate) fn is_requested(&self) -> bool {
+        // A poisoned lock means a loop panicked mid-shutdown; treat it as stopping.
+        self.requested.lock().map(|value| *value).unwrap_or(true)
+    }
+
+    /// Sleep up to `timeout`; returns true when shutdown has been requested.
+    pub(crate) fn wait_timeout(&self, timeout: Duration) -> bool {
+        let Ok(requested) = self.requested.lock() else {
+            return true;
+        };
+        match self
+            .wake
+            .wait_timeout_while(requested, timeout, |requested| !*requested)
+        {
+            Ok((requested, _)) => *requested,
+            Err(_) => true,
+        }
+    }
+}
+
+/// Join handles of the app's background loops, keyed by a name used in shutdown logs.
+#[derive(Default)]
+pub(crate) struct BackgroundThreads {
+    signal: Arc<Shutdown>,
+    handles: Mutex<Vec<(&'static str, JoinHandle<()>)>>,
+}
+
+impl BackgroundThreads {
+    pub(crate) fn signal(&self) -> Arc<Shutdown> {
+        self.signal.clone()
+    }
+
+    pub(crate) fn register(&self, name: &'static str, handle: JoinHandle<()>) {
+        if let Ok(mut handles) = self.handles.lock() {
+            handles.push((name, handle));
+        }
+    }
+
+    /// Request shutdown and wait up to `timeout` for registered loops to finish.
+    /// Returns the names of loops still running when the wait gave up.
+    pub(crate) fn shutdown_background_threads(&self, timeout: Duration) -> Vec<&'static str> {
+        self.signal.request();
+        let mut pending = match self.handles.lock() {
+            Ok(mut handles) => std::mem::take(&mut *handles),
+            Err(_) => return Vec::new(),
+        };
+        let deadline = Instant::now() + timeout;
+        loop {
+            let (finished, running): (Vec<_>, Vec<_>) = pending
+                .into_iter()
+                .partition(|(_, handle)| handle.is_finished());
+            for (_, handle) in finished {
+                let _ = handle.join();
+            }
+            pending = running;
+            if pending.is_empty() || Instant::now() >= deadline {
+                break;
+            }
+            std::thread::sleep(JOIN_POLL_INTERVAL);
+        }
+        pending.into_iter().map(|(name, _)| name).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackgroundThreads, Shutdown};
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn wait_timeout_wakes_promptly_on_request() {
+        let shutdown = Arc::new(Shutdown::default());
+        assert!(!shutdown.wait_timeout(Duration::from_millis(1)));
+        let waiter = shutdown.clone();
+        let started = Instant::now();
+        let handle = std::thread::spawn(move || waiter.wait_timeout(Duration::from_secs(10)));
+        std::thread::sleep(Duration::from_millis(20));
+        shutdown.request();
+        assert!(handle.join().unwrap());
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(shutdown.is_requested());
+    }
+
+    #[test]
+    fn loop_exits_within_one_tick_and_stragglers_are_reported() {
+        let tick = Duration::from_millis(200);
+        let threads = BackgroundThreads::default();
+        let signal = threads.signal();
+        threads.register(
+            "ticker",
+            std::thread::spawn(move || while !signal.wait_timeout(tick) {}),
+        );
+        threads.register(
+            "stubborn",
+            std::thread::spawn(|| std::thread::sleep(Duration::from_millis(400))),
+        );
+        std::thread::sleep(Duration::from_millis(20));
+
+        let started = Instant::now();
+        let still_running = threads.shutdown_background_threads(Duration::from_millis(100));
+        assert!(started.elapsed() < tick);
+        assert_eq!(still_running, vec!["stubborn"]);
+        assert!(threads
+            .shutdown_background_threads(Duration::ZERO)
+            .is_empty());
+    }
+}
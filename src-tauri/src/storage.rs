@@ -1,30 +1,51 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::SystemTime,
+};
 
-use chrono::Local;
-use serde::{Deserialize, Serialize};
+use chrono::{Local, NaiveDate};
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::collector::{StatsKey, StatsValue};
+use crate::collector::{MinuteKey, StatsKey, StatsValue, DAY_KEY_FORMAT};
 
-#[derive(Serialize, Deserialize, Clone)]
-struct StoredRow {
+mod sqlite;
+
+pub(crate) use sqlite::SqliteStorage;
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+pub(crate) struct StoredRow {
     date: String,
     app_name: String,
     window_title: String,
     active_typing_ms: u64,
     key_count: u64,
     session_count: u64,
+    /// Part of `active_typing_ms` spent writing; 0 in files written before the split.
+    #[serde(default)]
+    writing_ms: u64,
+    /// Part of `active_typing_ms` spent navigating (arrows, page keys, shortcuts).
+    #[serde(default)]
+    navigation_ms: u64,
 }
 
 /// Persisted shortcut aggregation for one normalized shortcut id.
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub(crate) struct StoredShortcutUsage {
     pub(crate) count: u64,
     #[serde(default)]
     pub(crate) by_app: HashMap<String, u64>,
 }
 
-/// Persisted input-event chunk with compact string events: `dt,t,k,m`.
-#[derive(Serialize, Deserialize, Clone, Default)]
+/// Persisted input-event chunk with compact string events: `dt,t,m,k` from version 2 on,
+/// `dt,t,k,m` in version 1.
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub(crate) struct StoredInputEventChunk {
     pub(crate) v: u8,
     pub(crate) chunk_start_ms: i64,
@@ -33,8 +54,96 @@ pub(crate) struct StoredInputEventChunk {
     pub(crate) events: Vec<String>,
 }
 
+/// Identity of a chunk across analytics files: start, app, event count and first-event hash.
+type ChunkDedupKey = (i64, u32, usize, u64);
+
+impl StoredInputEventChunk {
+    fn dedup_key(&self) -> ChunkDedupKey {
+        let mut hasher = DefaultHasher::new();
+        self.events.first().hash(&mut hasher);
+        (
+            self.chunk_start_ms,
+            self.app_ref,
+            self.events.len(),
+            hasher.finish(),
+        )
+    }
+}
+
+/// Persisted coverage ledger of one local day: time recording, paused and listener-down.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub(crate) struct StoredCoverageDay {
+    pub(crate) recording_ms: u64,
+    pub(crate) paused_ms: u64,
+    pub(crate) listener_down_ms: u64,
+}
+
+/// Persisted span (epoch milliseconds) during which the app process was running.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub(crate) struct StoredRuntimeSpan {
+    pub(crate) start_ms: i64,
+    pub(crate) end_ms: i64,
+}
+
+/// Persisted typing bursts of one local day; the longest key count and duration may come
+/// from different bursts.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub(crate) struct StoredBurstDay {
+    pub(crate) count: u64,
+    pub(crate) total_keys: u64,
+    pub(crate) total_ms: u64,
+    pub(crate) longest_keys: u64,
+    pub(crate) longest_ms: u64,
+}
+
+/// Persisted focus summary of one local day; the score is kept as computed at the last flush.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub(crate) struct StoredFocusDay {
+    pub(crate) score: u8,
+    pub(crate) session_count: u64,
+    pub(crate) longest_session_ms: u64,
+    pub(crate) focused_ms: u64,
+    pub(crate) app_switches: u64,
+}
+
+/// Persisted app→app transition count.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub(crate) struct StoredAppSwitchPair {
+    pub(crate) from: String,
+    pub(crate) to: String,
+    pub(crate) count: u64,
+}
+
+/// Persisted app switches of one local day, most common transitions first.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub(crate) struct StoredAppSwitchDay {
+    pub(crate) total: u64,
+    #[serde(default)]
+    pub(crate) pairs: Vec<StoredAppSwitchPair>,
+}
+
+/// Persisted copy-paste pairs of one local day. `latencies_ms` is capped; `pairs` counts all.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub(crate) struct StoredCopyPasteDay {
+    pub(crate) pairs: u64,
+    #[serde(default)]
+    pub(crate) unmatched_copies: u64,
+    #[serde(default)]
+    pub(crate) latencies_ms: Vec<u64>,
+    /// Source→destination app pairs, most common first.
+    #[serde(default)]
+    pub(crate) app_pairs: Vec<StoredAppSwitchPair>,
+}
+
+/// Persisted first and last counted key-down of one local day, in epoch milliseconds.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub(crate) struct StoredKeySpan {
+    pub(crate) first_ms: i64,
+    pub(crate) last_ms: i64,
+}
+
 /// Persisted analytics payload for shortcut usage and optional event replay chunks.
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub(crate) struct StoredInputAnalytics {
     #[serde(default)]
     pub(crate) shortcut_usage: HashMap<String, StoredShortcutUsage>,
@@ -44,20 +153,503 @@ pub(crate) struct StoredInputAnalytics {
     pub(crate) next_app_ref: u32,
     #[serde(default)]
     pub(crate) event_chunks: Vec<StoredInputEventChunk>,
+    /// Aborted chord counts keyed by local day (`YYYY-MM-DD`), then app id.
+    #[serde(default)]
+    pub(crate) chord_aborts: HashMap<String, HashMap<String, u64>>,
+    /// App switches made while typing, keyed by local day (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub(crate) app_switches: HashMap<String, StoredAppSwitchDay>,
+    /// Focus summaries keyed by local day (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub(crate) focus_days: HashMap<String, StoredFocusDay>,
+    /// Coverage ledgers keyed by local day (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub(crate) coverage: HashMap<String, StoredCoverageDay>,
+    /// App runtime spans keyed by local day (`YYYY-MM-DD`), split at local midnight.
+    #[serde(default)]
+    pub(crate) runtime: HashMap<String, Vec<StoredRuntimeSpan>>,
+    /// Typing burst totals keyed by the local day (`YYYY-MM-DD`) each burst started on.
+    #[serde(default)]
+    pub(crate) bursts: HashMap<String, StoredBurstDay>,
+    /// Typing effort keyed by local day (`YYYY-MM-DD`), in thousandths of a home-row press.
+    #[serde(default)]
+    pub(crate) effort: HashMap<String, u64>,
+    /// Key-downs per display id keyed by local day (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub(crate) displays: HashMap<String, HashMap<String, u64>>,
+    /// Copy-paste pairs keyed by the local day (`YYYY-MM-DD`) of the paste.
+    #[serde(default)]
+    pub(crate) copy_pastes: HashMap<String, StoredCopyPasteDay>,
+    /// First and last counted key-down keyed by local day (`YYYY-MM-DD`).
+    #[serde(default)]
+    pub(crate) key_spans: HashMap<String, StoredKeySpan>,
+    /// Week (Monday `YYYY-MM-DD`) announced by the weekly comparison, keyed by the local day
+    /// the notification fired on.
+    #[serde(default)]
+    pub(crate) weekly_comparisons: HashMap<String, String>,
+    /// Exact duplicate chunks dropped while merging legacy and daily files at load.
+    #[serde(skip)]
+    pub(crate) duplicate_chunks_dropped: usize,
+}
+
+/// Best-effort dump written by the panic hook: today's stats rows and the input chunks that may
+/// not have reached the analytics files yet. Merged into state and archived on the next start.
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
+pub(crate) struct StoredCrashDump {
+    pub(crate) written_at_ms: i64,
+    #[serde(default)]
+    pub(crate) rows: Vec<StoredRow>,
+    /// App ids of the refs used by `event_chunks`.
+    #[serde(default)]
+    pub(crate) app_dict: HashMap<u32, String>,
+    #[serde(default)]
+    pub(crate) event_chunks: Vec<StoredInputEventChunk>,
+}
+
+const CRASH_DUMP_PREFIX: &str = "crash-dump-";
+const CRASH_DUMP_ARCHIVE_DIR: &str = "crash-dumps";
+
+/// Write a crash dump as `crash-dump-<ms>.json` in `dir` through a temp file.
+pub(crate) fn write_crash_dump(dir: &Path, dump: &StoredCrashDump) -> Result<PathBuf, String> {
+    let path = dir.join(format!("{CRASH_DUMP_PREFIX}{}.json", dump.written_at_ms));
+    let bytes = serde_json::to_vec(dump).map_err(|e| e.to_string())?;
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, bytes).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Crash dumps left in `dir`, oldest first, each with its parse result.
+pub(crate) fn load_crash_dumps(dir: &Path) -> Vec<(PathBuf, Result<StoredCrashDump, String>)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        name.starts_with(CRASH_DUMP_PREFIX) && name.ends_with(".json")
+                    })
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let dump = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| {
+                    serde_json::from_str::<StoredCrashDump>(&content).map_err(|e| e.to_string())
+                });
+            (path, dump)
+        })
+        .collect()
+}
+
+/// Move a handled crash dump into the `crash-dumps` folder next to it.
+pub(crate) fn archive_crash_dump(path: &Path) -> Result<(), String> {
+    let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Err("invalid crash dump path".to_string());
+    };
+    let archive_dir = parent.join(CRASH_DUMP_ARCHIVE_DIR);
+    std::fs::create_dir_all(&archive_dir).map_err(|e| e.to_string())?;
+    std::fs::rename(path, archive_dir.join(file_name)).map_err(|e| e.to_string())
+}
+
+/// Live status for scripts, rewritten by the running app every few seconds while
+/// `write_status_file` is on.
+#[derive(Serialize, Clone, Debug, PartialEq, JsonSchema)]
+pub(crate) struct StoredStatusFile {
+    /// Shape version of this file.
+    pub(crate) v: u32,
+    /// Version of the app that wrote the file.
+    pub(crate) app_version: String,
+    /// When the file was last written, in epoch millis.
+    pub(crate) updated_at_ms: i64,
+    /// Local day (`YYYY-MM-DD`) of the totals.
+    pub(crate) date: String,
+    /// Key presses counted today.
+    pub(crate) keys: u64,
+    /// Active typing time today in milliseconds.
+    pub(crate) active_ms: u64,
+    /// Whether capture is paused manually.
+    pub(crate) paused: bool,
+    /// Whether capture is paused automatically (excluded app, secure input).
+    pub(crate) auto_paused: bool,
+}
+
+/// Read a small JSON file; a missing file reads as `T::default()`.
+fn load_json_or_default<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Write a small JSON file through a temp file so readers never see a partial one.
+fn save_json_atomic<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, bytes).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+}
+
+pub(crate) fn save_status_file(path: &Path, status: &StoredStatusFile) -> Result<(), String> {
+    save_json_atomic(path, status)
+}
+
+/// Progress of end-of-day auto exports, kept next to the detail files so a missed midnight is
+/// caught up exactly once after a restart or sleep.
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
+pub(crate) struct StoredAutoExportState {
+    /// Last local day (`YYYY-MM-DD`) whose export file was written.
+    #[serde(default)]
+    pub(crate) last_exported_day: Option<String>,
+}
+
+/// Read the auto-export marker; a missing file reads as nothing exported yet.
+pub(crate) fn load_auto_export_state(path: &Path) -> Result<StoredAutoExportState, String> {
+    load_json_or_default(path)
+}
+
+pub(crate) fn save_auto_export_state(
+    path: &Path,
+    state: &StoredAutoExportState,
+) -> Result<(), String> {
+    save_json_atomic(path, state)
+}
+
+/// One signed export in the per-profile export hash chain.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub(crate) struct StoredExportChainEntry {
+    /// 1-based position in the chain.
+    pub(crate) position: u64,
+    /// SHA-256 (hex) of the export, chained to `prev_hash`.
+    pub(crate) hash: String,
+    /// Hash of the previous signed export; all zeros for the first one.
+    pub(crate) prev_hash: String,
+    /// File name the export was written under.
+    pub(crate) file_name: String,
+    /// Epoch milliseconds the export was signed.
+    pub(crate) signed_at_ms: i64,
+}
+
+/// Hash chain of signed exports, kept next to the detail files of each profile.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub(crate) struct StoredExportChain {
+    /// Signed exports, oldest first.
+    #[serde(default)]
+    pub(crate) entries: Vec<StoredExportChainEntry>,
+}
+
+/// Read the export hash chain; a missing file reads as an empty chain.
+pub(crate) fn load_export_chain(path: &Path) -> Result<StoredExportChain, String> {
+    load_json_or_default(path)
+}
+
+pub(crate) fn save_export_chain(path: &Path, chain: &StoredExportChain) -> Result<(), String> {
+    save_json_atomic(path, chain)
+}
+
+/// Local usage counters of the opt-in telemetry, kept next to the analytics files until the
+/// weekly report sends them.
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
+pub(crate) struct StoredTelemetryState {
+    /// Invocations per Tauri command name since the last successful report.
+    #[serde(default)]
+    pub(crate) command_counts: BTreeMap<String, u64>,
+    /// Epoch milliseconds of the last successful report, or of the consent that started the
+    /// first reporting week.
+    #[serde(default)]
+    pub(crate) last_sent_ms: Option<i64>,
+}
+
+/// Read the telemetry counters; a missing file reads as nothing counted yet.
+pub(crate) fn load_telemetry_state(path: &Path) -> Result<StoredTelemetryState, String> {
+    load_json_or_default(path)
+}
+
+pub(crate) fn save_telemetry_state(
+    path: &Path,
+    state: &StoredTelemetryState,
+) -> Result<(), String> {
+    save_json_atomic(path, state)
+}
+
+/// Rolled-up daily stats file: rows are hour-keyed and the day is never re-aggregated.
+#[derive(Serialize)]
+struct RolledUpRows<'a> {
+    rolled_up: bool,
+    rows: &'a [StoredRow],
+}
+
+/// Merge minute-level rows of the given days into hour keys (`YYYY-MM-DD HH:00`) by summing.
+/// Rows of other days are kept untouched.
+pub(crate) fn rollup_stats_to_hours(
+    stats: HashMap<StatsKey, StatsValue>,
+    dates: &HashSet<NaiveDate>,
+) -> HashMap<StatsKey, StatsValue> {
+    let mut merged: HashMap<StatsKey, StatsValue> = HashMap::with_capacity(stats.len());
+    for (key, value) in stats {
+        let key = if dates.contains(&key.date.day()) {
+            StatsKey {
+                date: key.date.hour_start(),
+                ..key
+            }
+        } else {
+            key
+        };
+        let entry = merged.entry(key).or_insert(StatsValue {
+            active_typing_ms: 0,
+            key_count: 0,
+            session_count: 0,
+            writing_ms: 0,
+            navigation_ms: 0,
+        });
+        entry.active_typing_ms += value.active_typing_ms;
+        entry.key_count += value.key_count;
+        entry.session_count += value.session_count;
+        entry.writing_ms += value.writing_ms;
+        entry.navigation_ms += value.navigation_ms;
+    }
+    merged
+}
+
+/// What one archive pass moved into the monthly zip archives.
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct ArchiveReport {
+    /// Months (`YYYY-MM`) whose archive was written or updated.
+    pub(crate) months: Vec<String>,
+    /// Daily files moved into the archives.
+    pub(crate) files: u64,
+    /// Size of the moved files before compression.
+    pub(crate) bytes: u64,
+}
+
+const ARCHIVE_PREFIX: &str = "archive-";
+const ARCHIVE_EXTENSION: &str = ".zip";
+
+/// Size of the data directory, with the monthly archives counted separately.
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct DataDirSize {
+    /// Every file in the directory and its subfolders, archives included.
+    pub(crate) total_bytes: u64,
+    pub(crate) archive_files: u64,
+    pub(crate) archive_bytes: u64,
+}
+
+/// Whether `name` is a monthly archive, `archive-YYYY-MM.zip`.
+pub(crate) fn is_archive_name(name: &str) -> bool {
+    name.strip_prefix(ARCHIVE_PREFIX)
+        .and_then(|rest| rest.strip_suffix(ARCHIVE_EXTENSION))
+        .is_some_and(|month| {
+            NaiveDate::parse_from_str(&format!("{month}-01"), DAY_KEY_FORMAT).is_ok()
+        })
+}
+
+// Read the entries of an archive whose names pass `wanted`, one entry at a time.
+fn read_archive(
+    path: &Path,
+    wanted: impl Fn(&str) -> bool,
+) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut entries = BTreeMap::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| e.to_string())?;
+        let name = entry.name().to_string();
+        if !wanted(&name) {
+            continue;
+        }
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        std::io::Read::read_to_end(&mut entry, &mut bytes).map_err(|e| e.to_string())?;
+        entries.insert(name, bytes);
+    }
+    Ok(entries)
+}
+
+// Write `entries` as a deflated archive at `path` and sync it to disk.
+fn write_archive(path: &Path, entries: &BTreeMap<String, Vec<u8>>) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (name, bytes) in entries {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| e.to_string())?;
+        std::io::Write::write_all(&mut writer, bytes).map_err(|e| e.to_string())?;
+    }
+    let file = writer.finish().map_err(|e| e.to_string())?;
+    file.sync_all().map_err(|e| e.to_string())
+}
+
+// Hash of a JSON payload that ignores object key order, so a file read from an archive and
+// the same data serialized again from memory (hash map order included) compare equal.
+fn json_fingerprint(value: &serde_json::Value) -> u64 {
+    fn feed(value: &serde_json::Value, hasher: &mut DefaultHasher) {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut fields: Vec<_> = map.iter().collect();
+                fields.sort_by(|a, b| a.0.cmp(b.0));
+                ('{', fields.len()).hash(hasher);
+                for (key, value) in fields {
+                    key.hash(hasher);
+                    feed(value, hasher);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                ('[', items.len()).hash(hasher);
+                for item in items {
+                    feed(item, hasher);
+                }
+            }
+            other => other.to_string().hash(hasher),
+        }
+    }
+    let mut hasher = DefaultHasher::new();
+    feed(value, &mut hasher);
+    hasher.finish()
+}
+
+// Fingerprint that tells whether a stored daily file is still current. `next_app_ref` is
+// left out: every analytics file carries the latest one, so it changes as new apps show up.
+fn daily_fingerprint(mut value: serde_json::Value) -> u64 {
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("next_app_ref");
+    }
+    json_fingerprint(&value)
 }
 
+fn payload_fingerprint(payload: &impl Serialize) -> Result<u64, String> {
+    serde_json::to_value(payload)
+        .map(daily_fingerprint)
+        .map_err(|e| e.to_string())
+}
+
+/// Daily files deleted or modified outside the app since the storage last loaded or wrote
+/// them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct ExternalChanges {
+    pub(crate) deleted: Vec<PathBuf>,
+    pub(crate) modified: Vec<PathBuf>,
+}
+
+impl ExternalChanges {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.deleted.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// One stats or analytics file on disk, with the local day it holds (None for the legacy
+/// monolithic files and the monthly archives).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct StoredFile {
+    pub(crate) path: PathBuf,
+    pub(crate) day: Option<NaiveDate>,
+    pub(crate) bytes: u64,
+}
+
+/// Files read so far and in total by a history load; shared with the thread that reports it.
+#[derive(Debug, Default)]
+pub(crate) struct LoadProgress {
+    files_done: AtomicU64,
+    files_total: AtomicU64,
+}
+
+impl LoadProgress {
+    fn add_total(&self, files: usize) {
+        self.files_total.fetch_add(files as u64, Ordering::Relaxed);
+    }
+
+    fn file_done(&self) {
+        self.files_done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// `(files processed, files total)`; processed never exceeds total.
+    pub(crate) fn files(&self) -> (u64, u64) {
+        let total = self.files_total.load(Ordering::Relaxed);
+        (self.files_done.load(Ordering::Relaxed).min(total), total)
+    }
+}
+
+/// Stats and analytics of every stored day, as one load returns them.
+pub(crate) type StoredHistory = (
+    Result<HashMap<StatsKey, StatsValue>, String>,
+    Result<StoredInputAnalytics, String>,
+);
+
 pub(crate) trait DetailStorage: Send + Sync {
     fn load_stats(&self) -> Result<HashMap<StatsKey, StatsValue>, String>;
     fn save_stats(&self, stats: &HashMap<StatsKey, StatsValue>) -> Result<(), String>;
+    /// Whether the daily stats file for `day` has already been rolled up to hours.
+    fn is_rolled_up(&self, day: NaiveDate) -> bool;
+    /// Mark days as rolled up; later saves keep the marker in their files.
+    fn mark_rolled_up(&self, days: &[NaiveDate]);
     fn load_input_analytics(&self) -> Result<StoredInputAnalytics, String>;
     fn save_input_analytics(&self, analytics: &StoredInputAnalytics) -> Result<(), String>;
+    /// `load_stats` and `load_input_analytics` in one go, counting the files read into
+    /// `progress`; storages without files count none.
+    fn load_history(&self, _progress: &LoadProgress) -> StoredHistory {
+        (self.load_stats(), self.load_input_analytics())
+    }
+    /// Stats and analytics files currently on disk; storages without files list none.
+    fn stored_files(&self) -> Vec<StoredFile> {
+        Vec::new()
+    }
+    /// Move the daily files of days before `cutoff` into monthly archives; storages without
+    /// files archive nothing.
+    fn archive_days_before(&self, _cutoff: NaiveDate) -> Result<ArchiveReport, String> {
+        Ok(ArchiveReport::default())
+    }
+    /// Daily files deleted or modified outside the app since the last check, each change
+    /// reported once; storages without files report none.
+    fn take_external_changes(&self) -> ExternalChanges {
+        ExternalChanges::default()
+    }
+    /// Stop watching files the app deleted itself, so they are not reported as external.
+    fn forget_files(&self, _paths: &[PathBuf]) {}
+}
+
+// Fingerprint of a loose daily file as last loaded or written, with its modification time
+// then (None once it was found missing).
+struct WrittenFile {
+    fingerprint: u64,
+    modified: Option<SystemTime>,
+}
+
+fn file_modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
 }
 
 pub(crate) struct JsonFileStorage {
     pub(crate) path: PathBuf,
+    // Days whose files carry the rolled-up marker (filled on load and by rollup).
+    rolled_up_dates: Mutex<HashSet<NaiveDate>>,
+    // Fingerprints of archived daily files by file name (filled on load and by archiving).
+    archived: Mutex<HashMap<String, u64>>,
+    // Loose daily files as last loaded or written by path (filled on load and by saves).
+    written: Mutex<HashMap<PathBuf, WrittenFile>>,
 }
 
 impl JsonFileStorage {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            rolled_up_dates: Mutex::new(HashSet::new()),
+            archived: Mutex::new(HashMap::new()),
+            written: Mutex::new(HashMap::new()),
+        }
+    }
+
     fn base_name(&self) -> Option<String> {
         self.path
             .file_name()
@@ -65,42 +657,167 @@ impl JsonFileStorage {
             .map(|name| name.to_string())
     }
 
-    fn date_prefix(date: &str) -> Option<String> {
-        if date.len() < 10 {
-            return None;
-        }
-        Some(date[..10].to_string())
+    // Day of a daily file from its `YYYY-MM-DD-` name prefix.
+    fn file_day(prefix: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(prefix.get(..10)?, DAY_KEY_FORMAT).ok()
     }
 
-    fn dated_path(&self, date_prefix: &str) -> Option<PathBuf> {
+    fn dated_path(&self, day: NaiveDate) -> Option<PathBuf> {
         let parent = self.path.parent()?;
         let base = self.base_name()?;
-        Some(parent.join(format!("{date_prefix}-{base}")))
+        Some(parent.join(format!("{}-{base}", day.format(DAY_KEY_FORMAT))))
     }
 
-    fn stats_to_rows(stats: &HashMap<StatsKey, StatsValue>) -> Vec<StoredRow> {
-        let mut rows: Vec<StoredRow> = stats
-            .iter()
+    fn archive_path(&self, month: &str) -> Option<PathBuf> {
+        let parent = self.path.parent()?;
+        Some(parent.join(format!("{ARCHIVE_PREFIX}{month}{ARCHIVE_EXTENSION}")))
+    }
+
+    fn remember_archived(&self, name: &str, bytes: &[u8]) {
+        let Ok(value) = serde_json::from_slice(bytes) else {
+            return;
+        };
+        if let Ok(mut archived) = self.archived.lock() {
+            archived.insert(name.to_string(), daily_fingerprint(value));
+        }
+    }
+
+    // Whether the archived copy of `path` already holds the payload with `fingerprint`;
+    // writing it again would only bring back the loose file the archive replaced.
+    fn matches_archived(&self, path: &Path, fingerprint: u64) -> bool {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return false;
+        };
+        self.archived
+            .lock()
+            .ok()
+            .and_then(|archived| archived.get(name).copied())
+            == Some(fingerprint)
+    }
+
+    fn remember_written(&self, path: &Path, fingerprint: u64) {
+        if let Ok(mut written) = self.written.lock() {
+            written.insert(
+                path.to_path_buf(),
+                WrittenFile {
+                    fingerprint,
+                    modified: file_modified(path),
+                },
+            );
+        }
+    }
+
+    // Whether `path` was last loaded or written with the payload of `fingerprint`. The day
+    // did not change in memory since, so it is not written again and a file deleted or edited
+    // outside the app stays that way.
+    fn matches_written(&self, path: &Path, fingerprint: u64) -> bool {
+        self.written
+            .lock()
+            .ok()
+            .and_then(|written| written.get(path).map(|file| file.fingerprint))
+            == Some(fingerprint)
+    }
+
+    // Write one daily file unless the archive or the loose file already holds `payload`.
+    fn write_daily(&self, path: &Path, payload: &impl Serialize) -> Result<(), String> {
+        let fingerprint = payload_fingerprint(payload)?;
+        if self.matches_archived(path, fingerprint) || self.matches_written(path, fingerprint) {
+            return Ok(());
+        }
+        let bytes = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, bytes).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp_path, path).map_err(|e| e.to_string())?;
+        self.remember_written(path, fingerprint);
+        Ok(())
+    }
+
+    // Contents of the daily files whose names pass `wanted`, by file name: archive entries
+    // first, replaced by loose files of the same name, which are newer. Files and archives that
+    // cannot be read are skipped.
+    fn daily_contents(
+        &self,
+        wanted: impl Fn(&str) -> bool,
+    ) -> Result<BTreeMap<String, Vec<u8>>, String> {
+        let mut contents = BTreeMap::new();
+        let Some(parent) = self.path.parent() else {
+            return Ok(contents);
+        };
+        let entries = match std::fs::read_dir(parent) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(contents),
+            Err(err) => return Err(err.to_string()),
+        };
+        let mut archives = Vec::new();
+        let mut loose = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if is_archive_name(name) {
+                archives.push(path);
+            } else if wanted(name) {
+                loose.push((name.to_string(), path));
+            }
+        }
+        archives.sort();
+        for path in archives {
+            let Ok(entries) = read_archive(&path, &wanted) else {
+                continue;
+            };
+            for (name, bytes) in entries {
+                self.remember_archived(&name, &bytes);
+                contents.insert(name, bytes);
+            }
+        }
+        for (name, path) in loose {
+            if let Ok(bytes) = std::fs::read(&path) {
+                if let Ok(value) = serde_json::from_slice(&bytes) {
+                    self.remember_written(&path, daily_fingerprint(value));
+                }
+                contents.insert(name, bytes);
+            }
+        }
+        Ok(contents)
+    }
+
+    pub(crate) fn stats_to_rows(stats: &HashMap<StatsKey, StatsValue>) -> Vec<StoredRow> {
+        Self::entries_to_rows(stats.iter().collect())
+    }
+
+    // Sort by (minute, app, title) before formatting; typed order matches the string order.
+    fn entries_to_rows(mut entries: Vec<(&StatsKey, &StatsValue)>) -> Vec<StoredRow> {
+        entries.sort_by(|(a, _), (b, _)| {
+            (a.date, &a.app_name, &a.window_title).cmp(&(b.date, &b.app_name, &b.window_title))
+        });
+        entries
+            .into_iter()
             .map(|(key, value)| StoredRow {
-                date: key.date.clone(),
+                date: key.date.to_string(),
                 app_name: key.app_name.clone(),
                 window_title: key.window_title.clone(),
                 active_typing_ms: value.active_typing_ms,
                 key_count: value.key_count,
                 session_count: value.session_count,
+                writing_ms: value.writing_ms,
+                navigation_ms: value.navigation_ms,
             })
-            .collect();
-        rows.sort_by(|a, b| {
-            (&a.date, &a.app_name, &a.window_title).cmp(&(&b.date, &b.app_name, &b.window_title))
-        });
-        rows
+            .collect()
     }
 
-    fn rows_to_stats(rows: Vec<StoredRow>) -> HashMap<StatsKey, StatsValue> {
+    /// Rows whose `date` is not a `YYYY-MM-DD HH:MM` minute are skipped.
+    pub(crate) fn rows_to_stats(rows: Vec<StoredRow>) -> HashMap<StatsKey, StatsValue> {
         let mut stats: HashMap<StatsKey, StatsValue> = HashMap::new();
         for row in rows {
+            let Ok(date) = row.date.parse::<MinuteKey>() else {
+                continue;
+            };
             let key = StatsKey {
-                date: row.date,
+                date,
                 app_name: row.app_name,
                 window_title: row.window_title,
             };
@@ -108,10 +825,14 @@ impl JsonFileStorage {
                 active_typing_ms: 0,
                 key_count: 0,
                 session_count: 0,
+                writing_ms: 0,
+                navigation_ms: 0,
             });
             entry.active_typing_ms += row.active_typing_ms;
             entry.key_count += row.key_count;
             entry.session_count += row.session_count;
+            entry.writing_ms += row.writing_ms;
+            entry.navigation_ms += row.navigation_ms;
         }
         stats
     }
@@ -140,23 +861,240 @@ impl JsonFileStorage {
     }
 
     // Parse both legacy `Vec<StoredRow>` and structured payload with `rows` field.
-    fn parse_rows_content(content: &str) -> Result<Vec<StoredRow>, String> {
+    // Returns the rows plus whether the file is marked as rolled up.
+    fn parse_rows_content(content: &str) -> Result<(Vec<StoredRow>, bool), String> {
         #[derive(Deserialize)]
         struct StructuredRows {
+            #[serde(default)]
+            rolled_up: bool,
             #[serde(default)]
             rows: Vec<StoredRow>,
         }
 
         if let Ok(rows) = serde_json::from_str::<Vec<StoredRow>>(content) {
-            return Ok(rows);
+            return Ok((rows, false));
         }
         let structured: StructuredRows =
             serde_json::from_str(content).map_err(|e| e.to_string())?;
-        Ok(structured.rows)
+        Ok((structured.rows, structured.rolled_up))
+    }
+
+    // Daily stats files by name, archived ones included.
+    fn daily_stats_files(&self) -> Result<BTreeMap<String, Vec<u8>>, String> {
+        let (Some(base), Some(analytics_suffix)) =
+            (self.base_name(), self.analytics_daily_suffix())
+        else {
+            return Ok(BTreeMap::new());
+        };
+        let suffix = format!("-{base}");
+        self.daily_contents(|name| name.ends_with(&suffix) && !name.ends_with(&analytics_suffix))
+    }
+
+    // Daily analytics files by name, archived ones included.
+    fn daily_analytics_files(&self) -> Result<BTreeMap<String, Vec<u8>>, String> {
+        let Some(suffix) = self.analytics_daily_suffix() else {
+            return Ok(BTreeMap::new());
+        };
+        self.daily_contents(|name| name.ends_with(&suffix))
+    }
+
+    // Stats of the legacy monolithic file plus the daily files, counting each daily file.
+    fn stats_from_files(
+        &self,
+        daily: BTreeMap<String, Vec<u8>>,
+        progress: &LoadProgress,
+    ) -> Result<HashMap<StatsKey, StatsValue>, String> {
+        let mut rows: Vec<StoredRow> = Vec::new();
+        // Read legacy monolithic storage file first, if it exists.
+        let legacy_content = std::fs::read_to_string(&self.path);
+        match legacy_content {
+            Ok(content) => {
+                let (mut legacy_rows, _) = Self::parse_rows_content(&content)?;
+                rows.append(&mut legacy_rows);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.to_string()),
+        }
+        let suffix = format!("-{}", self.base_name().unwrap_or_default());
+        for (file_name, content) in daily {
+            progress.file_done();
+            // Skip files that cannot be parsed; keep loading what we can.
+            let Ok(content) = String::from_utf8(content) else {
+                continue;
+            };
+            if let Ok((mut day_rows, rolled_up)) = Self::parse_rows_content(&content) {
+                if rolled_up {
+                    if let Some(day) = file_name.strip_suffix(&suffix).and_then(Self::file_day) {
+                        self.mark_rolled_up(&[day]);
+                    }
+                }
+                rows.append(&mut day_rows);
+            }
+        }
+        Ok(Self::rows_to_stats(rows))
+    }
+
+    // Analytics of the legacy monolithic file plus the daily files, counting each daily file.
+    fn analytics_from_files(
+        &self,
+        daily: BTreeMap<String, Vec<u8>>,
+        progress: &LoadProgress,
+    ) -> Result<StoredInputAnalytics, String> {
+        let mut merged = StoredInputAnalytics::default();
+        let mut seen_chunks = HashSet::new();
+        // Load legacy monolithic analytics file for backward compatibility.
+        if let Some(path) = self.analytics_path() {
+            match std::fs::read_to_string(path) {
+                Ok(content) => {
+                    let payload: StoredInputAnalytics =
+                        serde_json::from_str(&content).map_err(|e| e.to_string())?;
+                    Self::merge_analytics(&mut merged, payload, &mut seen_chunks);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.to_string()),
+            }
+        }
+        // Visit daily files in name (date) order so the merge doesn't depend on directory order.
+        for content in daily.into_values() {
+            progress.file_done();
+            // Keep loading other files even if one daily file is corrupted.
+            if let Ok(payload) = serde_json::from_slice::<StoredInputAnalytics>(&content) {
+                Self::merge_analytics(&mut merged, payload, &mut seen_chunks);
+            }
+        }
+        // Stable sort keeps same-start chunks in file order after ordering by app.
+        merged
+            .event_chunks
+            .sort_by_key(|chunk| (chunk.chunk_start_ms, chunk.app_ref));
+        Ok(merged)
+    }
+
+    // Split analytics into one payload per local day: the chunks starting on it, the app ids
+    // they use and its per-day counters. Days with counters but no chunks get a payload too.
+    // Shortcut usage is left out; it can be rebuilt from chunks.
+    fn daily_analytics_payloads(
+        analytics: &StoredInputAnalytics,
+    ) -> HashMap<String, StoredInputAnalytics> {
+        let mut grouped_chunks: HashMap<String, Vec<StoredInputEventChunk>> = HashMap::new();
+        for chunk in &analytics.event_chunks {
+            let date_prefix = match Self::date_prefix_from_timestamp_ms(chunk.chunk_start_ms) {
+                Some(value) => value,
+                None => continue,
+            };
+            grouped_chunks
+                .entry(date_prefix)
+                .or_default()
+                .push(chunk.clone());
+        }
+        // Days with per-day counters or focus summaries but no chunks still get a payload.
+        for date_prefix in analytics
+            .chord_aborts
+            .keys()
+            .chain(analytics.app_switches.keys())
+            .chain(analytics.focus_days.keys())
+            .chain(analytics.coverage.keys())
+            .chain(analytics.runtime.keys())
+            .chain(analytics.bursts.keys())
+            .chain(analytics.effort.keys())
+            .chain(analytics.displays.keys())
+            .chain(analytics.copy_pastes.keys())
+            .chain(analytics.key_spans.keys())
+            .chain(analytics.weekly_comparisons.keys())
+        {
+            grouped_chunks.entry(date_prefix.clone()).or_default();
+        }
+        grouped_chunks
+            .into_iter()
+            .map(|(date_prefix, chunks)| {
+                let mut app_refs: HashSet<u32> = HashSet::new();
+                for chunk in &chunks {
+                    app_refs.insert(chunk.app_ref);
+                }
+                let app_dict = analytics
+                    .app_dict
+                    .iter()
+                    .filter_map(|(app_ref, app_id)| {
+                        if app_refs.contains(app_ref) {
+                            Some((*app_ref, app_id.clone()))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                let payload = StoredInputAnalytics {
+                    shortcut_usage: HashMap::new(),
+                    app_dict,
+                    next_app_ref: analytics.next_app_ref,
+                    chord_aborts: analytics
+                        .chord_aborts
+                        .get(&date_prefix)
+                        .map(|apps| HashMap::from([(date_prefix.clone(), apps.clone())]))
+                        .unwrap_or_default(),
+                    app_switches: analytics
+                        .app_switches
+                        .get(&date_prefix)
+                        .map(|day| HashMap::from([(date_prefix.clone(), day.clone())]))
+                        .unwrap_or_default(),
+                    focus_days: analytics
+                        .focus_days
+                        .get(&date_prefix)
+                        .map(|day| HashMap::from([(date_prefix.clone(), day.clone())]))
+                        .unwrap_or_default(),
+                    coverage: analytics
+                        .coverage
+                        .get(&date_prefix)
+                        .map(|day| HashMap::from([(date_prefix.clone(), day.clone())]))
+                        .unwrap_or_default(),
+                    runtime: analytics
+                        .runtime
+                        .get(&date_prefix)
+                        .map(|spans| HashMap::from([(date_prefix.clone(), spans.clone())]))
+                        .unwrap_or_default(),
+                    bursts: analytics
+                        .bursts
+                        .get(&date_prefix)
+                        .map(|day| HashMap::from([(date_prefix.clone(), *day)]))
+                        .unwrap_or_default(),
+                    effort: analytics
+                        .effort
+                        .get(&date_prefix)
+                        .map(|milli| HashMap::from([(date_prefix.clone(), *milli)]))
+                        .unwrap_or_default(),
+                    displays: analytics
+                        .displays
+                        .get(&date_prefix)
+                        .map(|day| HashMap::from([(date_prefix.clone(), day.clone())]))
+                        .unwrap_or_default(),
+                    copy_pastes: analytics
+                        .copy_pastes
+                        .get(&date_prefix)
+                        .map(|day| HashMap::from([(date_prefix.clone(), day.clone())]))
+                        .unwrap_or_default(),
+                    key_spans: analytics
+                        .key_spans
+                        .get(&date_prefix)
+                        .map(|span| HashMap::from([(date_prefix.clone(), *span)]))
+                        .unwrap_or_default(),
+                    weekly_comparisons: analytics
+                        .weekly_comparisons
+                        .get(&date_prefix)
+                        .map(|week| HashMap::from([(date_prefix.clone(), week.clone())]))
+                        .unwrap_or_default(),
+                    event_chunks: chunks,
+                    duplicate_chunks_dropped: 0,
+                };
+                (date_prefix, payload)
+            })
+            .collect()
     }
 
     // Merge analytics payload into accumulator, summing usage and app-level counters.
-    fn merge_analytics(into: &mut StoredInputAnalytics, from: StoredInputAnalytics) {
+    // Chunks already seen in an earlier file are dropped so shortcut counts don't double.
+    fn merge_analytics(
+        into: &mut StoredInputAnalytics,
+        from: StoredInputAnalytics,
+        seen_chunks: &mut HashSet<ChunkDedupKey>,
+    ) {
         for (shortcut_id, usage) in from.shortcut_usage {
             let entry = into
                 .shortcut_usage
@@ -172,140 +1110,119 @@ impl JsonFileStorage {
             into.app_dict.entry(app_ref).or_insert(app_id);
         }
         into.next_app_ref = into.next_app_ref.max(from.next_app_ref);
-        into.event_chunks.extend(from.event_chunks);
+        for (date, apps) in from.chord_aborts {
+            let day = into.chord_aborts.entry(date).or_default();
+            for (app_id, count) in apps {
+                let app_entry = day.entry(app_id).or_insert(0);
+                *app_entry = app_entry.saturating_add(count);
+            }
+        }
+        // A day lives in exactly one daily file, so the first entry seen wins.
+        for (date, day) in from.app_switches {
+            into.app_switches.entry(date).or_insert(day);
+        }
+        for (date, day) in from.focus_days {
+            into.focus_days.entry(date).or_insert(day);
+        }
+        for (date, day) in from.coverage {
+            into.coverage.entry(date).or_insert(day);
+        }
+        for (date, spans) in from.runtime {
+            into.runtime.entry(date).or_insert(spans);
+        }
+        for (date, day) in from.bursts {
+            into.bursts.entry(date).or_insert(day);
+        }
+        for (date, milli) in from.effort {
+            into.effort.entry(date).or_insert(milli);
+        }
+        for (date, displays) in from.displays {
+            into.displays.entry(date).or_insert(displays);
+        }
+        for (date, day) in from.copy_pastes {
+            into.copy_pastes.entry(date).or_insert(day);
+        }
+        for (date, span) in from.key_spans {
+            into.key_spans.entry(date).or_insert(span);
+        }
+        for (date, week) in from.weekly_comparisons {
+            into.weekly_comparisons.entry(date).or_insert(week);
+        }
+        for chunk in from.event_chunks {
+            if seen_chunks.insert(chunk.dedup_key()) {
+                into.event_chunks.push(chunk);
+            } else {
+                into.duplicate_chunks_dropped += 1;
+            }
+        }
     }
 }
 
 impl DetailStorage for JsonFileStorage {
     fn load_stats(&self) -> Result<HashMap<StatsKey, StatsValue>, String> {
-        let mut rows: Vec<StoredRow> = Vec::new();
-        // Read legacy monolithic storage file first, if it exists.
-        let legacy_content = std::fs::read_to_string(&self.path);
-        match legacy_content {
-            Ok(content) => {
-                let mut legacy_rows = Self::parse_rows_content(&content)?;
-                rows.append(&mut legacy_rows);
-            }
-            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
-            Err(err) => return Err(err.to_string()),
-        }
-        let parent = match self.path.parent() {
-            Some(parent) => parent,
-            None => return Ok(Self::rows_to_stats(rows)),
-        };
-        let base = match self.base_name() {
-            Some(base) => base,
-            None => return Ok(Self::rows_to_stats(rows)),
-        };
-        // Merge all daily rotated files that match the base filename.
-        let entries = match std::fs::read_dir(parent) {
-            Ok(entries) => entries,
-            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
-                return Ok(Self::rows_to_stats(rows))
-            }
-            Err(err) => return Err(err.to_string()),
-        };
-        let suffix = format!("-{base}");
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-            let file_name = match path.file_name().and_then(|name| name.to_str()) {
-                Some(name) => name,
-                None => continue,
-            };
-            if !file_name.ends_with(&suffix) {
-                continue;
-            }
-            // Skip files that cannot be parsed; keep loading what we can.
-            if let Ok(content) = std::fs::read_to_string(&path) {
-                if let Ok(mut day_rows) = Self::parse_rows_content(&content) {
-                    rows.append(&mut day_rows);
-                }
-            }
-        }
-        Ok(Self::rows_to_stats(rows))
+        self.stats_from_files(self.daily_stats_files()?, &LoadProgress::default())
     }
 
     fn save_stats(&self, stats: &HashMap<StatsKey, StatsValue>) -> Result<(), String> {
         if let Some(parent) = self.path.parent() {
             let _ = std::fs::create_dir_all(parent);
         }
-        let rows = Self::stats_to_rows(stats);
-        let mut grouped: HashMap<String, Vec<StoredRow>> = HashMap::new();
-        for row in rows {
-            if let Some(date_prefix) = Self::date_prefix(&row.date) {
-                grouped.entry(date_prefix).or_default().push(row);
-            }
+        let mut grouped: HashMap<NaiveDate, Vec<(&StatsKey, &StatsValue)>> = HashMap::new();
+        for entry in stats {
+            grouped.entry(entry.0.date.day()).or_default().push(entry);
         }
         // Write each date bucket into its own file for easier rotation.
-        for (date_prefix, day_rows) in grouped {
-            let path = match self.dated_path(&date_prefix) {
+        for (day, entries) in grouped {
+            let day_rows = Self::entries_to_rows(entries);
+            let path = match self.dated_path(day) {
                 Some(path) => path,
                 None => continue,
             };
-            let bytes = serde_json::to_vec(&day_rows).map_err(|e| e.to_string())?;
-            let tmp_path = path.with_extension("json.tmp");
-            std::fs::write(&tmp_path, bytes).map_err(|e| e.to_string())?;
-            std::fs::rename(&tmp_path, &path).map_err(|e| e.to_string())?;
+            // Only days whose rows changed are written; archived days stay in their archive.
+            if self.is_rolled_up(day) {
+                let payload = RolledUpRows {
+                    rolled_up: true,
+                    rows: &day_rows,
+                };
+                self.write_daily(&path, &payload)?;
+            } else {
+                self.write_daily(&path, &day_rows)?;
+            }
         }
         // Remove legacy monolithic file once daily files are written.
         let _ = std::fs::remove_file(&self.path);
         Ok(())
     }
 
-    fn load_input_analytics(&self) -> Result<StoredInputAnalytics, String> {
-        let mut merged = StoredInputAnalytics::default();
-        // Load legacy monolithic analytics file for backward compatibility.
-        if let Some(path) = self.analytics_path() {
-            match std::fs::read_to_string(path) {
-                Ok(content) => {
-                    let payload: StoredInputAnalytics =
-                        serde_json::from_str(&content).map_err(|e| e.to_string())?;
-                    Self::merge_analytics(&mut merged, payload);
-                }
-                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
-                Err(err) => return Err(err.to_string()),
-            }
-        }
-        // Merge all daily analytics files.
-        let parent = match self.path.parent() {
-            Some(parent) => parent,
-            None => return Ok(merged),
-        };
-        let suffix = match self.analytics_daily_suffix() {
-            Some(suffix) => suffix,
-            None => return Ok(merged),
-        };
-        let entries = match std::fs::read_dir(parent) {
-            Ok(entries) => entries,
-            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(merged),
-            Err(err) => return Err(err.to_string()),
+    // Both file sets are listed and read before parsing starts, so the total is known up front.
+    fn load_history(&self, progress: &LoadProgress) -> StoredHistory {
+        let stats_files = self.daily_stats_files();
+        let analytics_files = self.daily_analytics_files();
+        let count = |files: &Result<BTreeMap<String, Vec<u8>>, String>| {
+            files.as_ref().map_or(0, |files| files.len())
         };
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-            let file_name = match path.file_name().and_then(|name| name.to_str()) {
-                Some(name) => name,
-                None => continue,
-            };
-            if !file_name.ends_with(&suffix) {
-                continue;
-            }
-            // Keep loading other files even if one daily file is corrupted.
-            if let Ok(content) = std::fs::read_to_string(&path) {
-                if let Ok(payload) = serde_json::from_str::<StoredInputAnalytics>(&content) {
-                    Self::merge_analytics(&mut merged, payload);
-                }
-            }
+        progress.add_total(count(&stats_files) + count(&analytics_files));
+        (
+            stats_files.and_then(|files| self.stats_from_files(files, progress)),
+            analytics_files.and_then(|files| self.analytics_from_files(files, progress)),
+        )
+    }
+
+    fn is_rolled_up(&self, day: NaiveDate) -> bool {
+        self.rolled_up_dates
+            .lock()
+            .map(|dates| dates.contains(&day))
+            .unwrap_or(false)
+    }
+
+    fn mark_rolled_up(&self, days: &[NaiveDate]) {
+        if let Ok(mut dates) = self.rolled_up_dates.lock() {
+            dates.extend(days.iter().copied());
         }
-        merged
-            .event_chunks
-            .sort_by(|a, b| a.chunk_start_ms.cmp(&b.chunk_start_ms));
-        Ok(merged)
+    }
+
+    fn load_input_analytics(&self) -> Result<StoredInputAnalytics, String> {
+        self.analytics_from_files(self.daily_analytics_files()?, &LoadProgress::default())
     }
 
     fn save_input_analytics(&self, analytics: &StoredInputAnalytics) -> Result<(), String> {
@@ -320,25 +1237,14 @@ impl DetailStorage for JsonFileStorage {
             Some(suffix) => suffix,
             None => return Ok(()),
         };
-        let mut grouped_chunks: HashMap<String, Vec<StoredInputEventChunk>> = HashMap::new();
-        for chunk in &analytics.event_chunks {
-            let date_prefix = match Self::date_prefix_from_timestamp_ms(chunk.chunk_start_ms) {
-                Some(value) => value,
-                None => continue,
-            };
-            grouped_chunks
-                .entry(date_prefix)
-                .or_default()
-                .push(chunk.clone());
-        }
+        let payloads = Self::daily_analytics_payloads(analytics);
         // Remove stale daily analytics files before writing the new set.
         let entries = match std::fs::read_dir(parent) {
             Ok(entries) => entries,
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
             Err(err) => return Err(err.to_string()),
         };
-        let next_dates: std::collections::HashSet<String> =
-            grouped_chunks.keys().cloned().collect();
+        let next_dates: HashSet<&String> = payloads.keys().collect();
         for entry in entries.flatten() {
             let path = entry.path();
             if !path.is_file() {
@@ -357,39 +1263,16 @@ impl DetailStorage for JsonFileStorage {
                 .to_string();
             if !next_dates.contains(&date_prefix) {
                 std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+                self.forget_files(&[path]);
             }
         }
-        // Write one analytics payload per day. Shortcut usage can be rebuilt from chunks.
-        for (date_prefix, chunks) in grouped_chunks {
+        // Write one analytics payload per day.
+        for (date_prefix, payload) in payloads {
             let path = match self.analytics_dated_path(&date_prefix) {
                 Some(path) => path,
                 None => continue,
             };
-            let mut app_refs: std::collections::HashSet<u32> = std::collections::HashSet::new();
-            for chunk in &chunks {
-                app_refs.insert(chunk.app_ref);
-            }
-            let app_dict = analytics
-                .app_dict
-                .iter()
-                .filter_map(|(app_ref, app_id)| {
-                    if app_refs.contains(app_ref) {
-                        Some((*app_ref, app_id.clone()))
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            let payload = StoredInputAnalytics {
-                shortcut_usage: HashMap::new(),
-                app_dict,
-                next_app_ref: analytics.next_app_ref,
-                event_chunks: chunks,
-            };
-            let bytes = serde_json::to_vec(&payload).map_err(|e| e.to_string())?;
-            let tmp_path = path.with_extension("json.tmp");
-            std::fs::write(&tmp_path, bytes).map_err(|e| e.to_string())?;
-            std::fs::rename(&tmp_path, &path).map_err(|e| e.to_string())?;
+            self.write_daily(&path, &payload)?;
         }
         // Remove legacy monolithic analytics file after daily files are written.
         if let Some(path) = self.analytics_path() {
@@ -397,13 +1280,210 @@ impl DetailStorage for JsonFileStorage {
         }
         Ok(())
     }
+
+    fn stored_files(&self) -> Vec<StoredFile> {
+        let (Some(parent), Some(base), Some(analytics_suffix)) = (
+            self.path.parent(),
+            self.base_name(),
+            self.analytics_daily_suffix(),
+        ) else {
+            return Vec::new();
+        };
+        let Ok(entries) = std::fs::read_dir(parent) else {
+            return Vec::new();
+        };
+        let legacy_analytics = format!("analytics-{base}");
+        let stats_suffix = format!("-{base}");
+        let mut files: Vec<StoredFile> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let name = path.file_name()?.to_str()?.to_string();
+                let day = if name == base || name == legacy_analytics || is_archive_name(&name) {
+                    None
+                } else {
+                    let prefix = name
+                        .strip_suffix(&analytics_suffix)
+                        .or_else(|| name.strip_suffix(&stats_suffix))?;
+                    Some(Self::file_day(prefix).filter(|_| prefix.len() == 10)?)
+                };
+                let metadata = entry.metadata().ok().filter(|meta| meta.is_file())?;
+                Some(StoredFile {
+                    path,
+                    day,
+                    bytes: metadata.len(),
+                })
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+
+    fn archive_days_before(&self, cutoff: NaiveDate) -> Result<ArchiveReport, String> {
+        let mut report = ArchiveReport::default();
+        let mut by_month: BTreeMap<String, Vec<StoredFile>> = BTreeMap::new();
+        for file in self.stored_files() {
+            if let Some(day) = file.day.filter(|day| *day < cutoff) {
+                by_month
+                    .entry(day.format("%Y-%m").to_string())
+                    .or_default()
+                    .push(file);
+            }
+        }
+        for (month, files) in by_month {
+            let Some(path) = self.archive_path(&month) else {
+                continue;
+            };
+            // A rerun after an interrupted pass finds the archive and the loose files both; the
+            // loose files are the newer copies.
+            let mut entries = if path.exists() {
+                read_archive(&path, |_| true)?
+            } else {
+                BTreeMap::new()
+            };
+            let mut originals = Vec::with_capacity(files.len());
+            for file in &files {
+                let Some(name) = file.path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                let bytes = std::fs::read(&file.path).map_err(|e| e.to_string())?;
+                entries.insert(name.to_string(), bytes);
+                originals.push((name.to_string(), file));
+            }
+            let tmp_path = path.with_extension("zip.tmp");
+            write_archive(&tmp_path, &entries)?;
+            // Read the new archive back and keep the originals unless it holds them unchanged.
+            if read_archive(&tmp_path, |_| true)? != entries {
+                let _ = std::fs::remove_file(&tmp_path);
+                return Err(format!("archive {month} failed verification"));
+            }
+            std::fs::rename(&tmp_path, &path).map_err(|e| e.to_string())?;
+            for (name, file) in originals {
+                self.remember_archived(&name, &entries[&name]);
+                std::fs::remove_file(&file.path).map_err(|e| e.to_string())?;
+                self.forget_files(std::slice::from_ref(&file.path));
+                report.files += 1;
+                report.bytes += file.bytes;
+            }
+            report.months.push(month);
+        }
+        Ok(report)
+    }
+
+    fn take_external_changes(&self) -> ExternalChanges {
+        let mut changes = ExternalChanges::default();
+        let Ok(mut written) = self.written.lock() else {
+            return changes;
+        };
+        for (path, file) in written.iter_mut() {
+            let modified = file_modified(path);
+            if modified == file.modified {
+                continue;
+            }
+            match modified {
+                Some(_) => changes.modified.push(path.clone()),
+                None => changes.deleted.push(path.clone()),
+            }
+            file.modified = modified;
+        }
+        changes.deleted.sort();
+        changes.modified.sort();
+        changes
+    }
+
+    fn forget_files(&self, paths: &[PathBuf]) {
+        if let Ok(mut written) = self.written.lock() {
+            for path in paths {
+                written.remove(path);
+            }
+        }
+    }
+}
+
+/// Storage that keeps the saved history in memory and never touches disk. Demo mode runs on
+/// it so generated stats cannot reach the real data files.
+#[derive(Default)]
+pub(crate) struct MemoryStorage {
+    stats: Mutex<HashMap<StatsKey, StatsValue>>,
+    analytics: Mutex<StoredInputAnalytics>,
+    rolled_up_dates: Mutex<HashSet<NaiveDate>>,
+}
+
+impl MemoryStorage {
+    /// Storage that loads `stats` and `analytics` until the first save replaces them.
+    pub(crate) fn with_history(
+        stats: HashMap<StatsKey, StatsValue>,
+        analytics: StoredInputAnalytics,
+    ) -> Self {
+        Self {
+            stats: Mutex::new(stats),
+            analytics: Mutex::new(analytics),
+            rolled_up_dates: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl DetailStorage for MemoryStorage {
+    fn load_stats(&self) -> Result<HashMap<StatsKey, StatsValue>, String> {
+        self.stats
+            .lock()
+            .map(|stats| stats.clone())
+            .map_err(|_| "memory storage lock failed".to_string())
+    }
+
+    fn save_stats(&self, stats: &HashMap<StatsKey, StatsValue>) -> Result<(), String> {
+        *self
+            .stats
+            .lock()
+            .map_err(|_| "memory storage lock failed".to_string())? = stats.clone();
+        Ok(())
+    }
+
+    fn is_rolled_up(&self, day: NaiveDate) -> bool {
+        self.rolled_up_dates
+            .lock()
+            .is_ok_and(|dates| dates.contains(&day))
+    }
+
+    fn mark_rolled_up(&self, days: &[NaiveDate]) {
+        if let Ok(mut dates) = self.rolled_up_dates.lock() {
+            dates.extend(days.iter().copied());
+        }
+    }
+
+    fn load_input_analytics(&self) -> Result<StoredInputAnalytics, String> {
+        self.analytics
+            .lock()
+            .map(|analytics| analytics.clone())
+            .map_err(|_| "memory storage lock failed".to_string())
+    }
+
+    fn save_input_analytics(&self, analytics: &StoredInputAnalytics) -> Result<(), String> {
+        *self
+            .analytics
+            .lock()
+            .map_err(|_| "memory storage lock failed".to_string())? = analytics.clone();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{DetailStorage, JsonFileStorage, StoredInputAnalytics, StoredInputEventChunk};
-    use crate::collector::{StatsKey, StatsValue};
-    use std::{collections::HashMap, fs, path::PathBuf, time::SystemTime};
+    use super::{
+        rollup_stats_to_hours, DetailStorage, ExternalChanges, JsonFileStorage, LoadProgress,
+        SqliteStorage, StoredAppSwitchDay, StoredAppSwitchPair, StoredBurstDay, StoredCopyPasteDay,
+        StoredCoverageDay, StoredFile, StoredFocusDay, StoredInputAnalytics, StoredInputEventChunk,
+        StoredKeySpan, StoredRow, StoredRuntimeSpan, StoredShortcutUsage,
+    };
+    use crate::collector::{MinuteKey, StatsKey, StatsValue, DAY_KEY_FORMAT};
+    use crate::test_support::temp_dir;
+    use chrono::NaiveDate;
+    use std::{
+        collections::{HashMap, HashSet},
+        fs,
+        path::PathBuf,
+        time::SystemTime,
+    };
 
     fn temp_path(name: &str) -> PathBuf {
         let mut path = std::env::temp_dir();
@@ -415,6 +1495,10 @@ mod tests {
         path
     }
 
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, DAY_KEY_FORMAT).unwrap()
+    }
+
     fn dated_path(base: &PathBuf, date: &str) -> Option<PathBuf> {
         let parent = base.parent()?;
         let name = base.file_name()?.to_str()?;
@@ -424,7 +1508,7 @@ mod tests {
     #[test]
     fn load_missing_file_returns_empty() {
         let path = temp_path("missing");
-        let storage = JsonFileStorage { path };
+        let storage = JsonFileStorage::new(path);
         let loaded = storage.load_stats().unwrap();
         assert!(loaded.is_empty());
     }
@@ -432,11 +1516,11 @@ mod tests {
     #[test]
     fn save_and_load_round_trip() {
         let path = temp_path("roundtrip");
-        let storage = JsonFileStorage { path: path.clone() };
+        let storage = JsonFileStorage::new(path.clone());
         let mut stats = HashMap::new();
         stats.insert(
             StatsKey {
-                date: "2026-02-09 10:00".to_string(),
+                date: "2026-02-09 10:00".parse().unwrap(),
                 app_name: "AppA".to_string(),
                 window_title: "WindowA".to_string(),
             },
@@ -444,11 +1528,13 @@ mod tests {
                 active_typing_ms: 1200,
                 key_count: 12,
                 session_count: 2,
+                writing_ms: 0,
+                navigation_ms: 0,
             },
         );
         stats.insert(
             StatsKey {
-                date: "2026-02-10 10:01".to_string(),
+                date: "2026-02-10 10:01".parse().unwrap(),
                 app_name: "AppB".to_string(),
                 window_title: "WindowB".to_string(),
             },
@@ -456,6 +1542,8 @@ mod tests {
                 active_typing_ms: 800,
                 key_count: 8,
                 session_count: 1,
+                writing_ms: 0,
+                navigation_ms: 0,
             },
         );
         storage.save_stats(&stats).unwrap();
@@ -463,7 +1551,7 @@ mod tests {
         assert_eq!(loaded.len(), 2);
         let value = loaded
             .get(&StatsKey {
-                date: "2026-02-09 10:00".to_string(),
+                date: "2026-02-09 10:00".parse().unwrap(),
                 app_name: "AppA".to_string(),
                 window_title: "WindowA".to_string(),
             })
@@ -479,10 +1567,74 @@ mod tests {
         }
     }
 
+    fn legacy_row(date: &str, app_name: &str, key_count: u64) -> StoredRow {
+        StoredRow {
+            date: date.to_string(),
+            app_name: app_name.to_string(),
+            window_title: "Window".to_string(),
+            active_typing_ms: key_count * 100,
+            key_count,
+            session_count: 1,
+            writing_ms: 0,
+            navigation_ms: 0,
+        }
+    }
+
+    fn row_fields(row: &StoredRow) -> (String, String, String, u64, u64, u64) {
+        (
+            row.date.clone(),
+            row.app_name.clone(),
+            row.window_title.clone(),
+            row.active_typing_ms,
+            row.key_count,
+            row.session_count,
+        )
+    }
+
+    #[test]
+    fn legacy_rows_survive_typed_keys_unchanged() {
+        // Sorted the way `stats_to_rows` writes them, spanning a month and a year boundary.
+        let rows = vec![
+            legacy_row("2025-12-31 23:59", "AppA", 1),
+            legacy_row("2026-01-01 00:00", "AppA", 2),
+            legacy_row("2026-01-01 00:00", "AppB", 3),
+            legacy_row("2026-01-01 09:05", "AppA", 4),
+            legacy_row("2026-01-31 23:59", "AppA", 5),
+            legacy_row("2026-02-01 00:00", "AppA", 6),
+        ];
+        let expected: Vec<_> = rows.iter().map(row_fields).collect();
+        let stats = JsonFileStorage::rows_to_stats(rows);
+        assert!(stats.contains_key(&StatsKey {
+            date: MinuteKey::new(day("2026-01-01"), 9, 5).unwrap(),
+            app_name: "AppA".to_string(),
+            window_title: "Window".to_string(),
+        }));
+        let written: Vec<_> = JsonFileStorage::stats_to_rows(&stats)
+            .iter()
+            .map(row_fields)
+            .collect();
+        assert_eq!(written, expected);
+    }
+
+    #[test]
+    fn rows_with_unparsable_dates_are_skipped() {
+        let rows = vec![
+            legacy_row("2026-02-09 10:00", "AppA", 1),
+            legacy_row("2026-02-09", "AppA", 2),
+            legacy_row("2026-02-09 24:00", "AppA", 3),
+            legacy_row("not a date", "AppA", 4),
+        ];
+        let stats = JsonFileStorage::rows_to_stats(rows);
+        assert_eq!(stats.len(), 1);
+        let written = JsonFileStorage::stats_to_rows(&stats);
+        assert_eq!(written[0].date, "2026-02-09 10:00");
+        assert_eq!(written[0].key_count, 1);
+    }
+
     #[test]
     fn analytics_round_trip() {
         let path = temp_path("analytics");
-        let storage = JsonFileStorage { path: path.clone() };
+        let storage = JsonFileStorage::new(path.clone());
         let mut analytics = StoredInputAnalytics::default();
         analytics.app_dict.insert(1, "com.test.editor".to_string());
         analytics.next_app_ref = 2;
@@ -502,4 +1654,608 @@ mod tests {
             let _ = fs::remove_file(parent.join("2024-01-01-analytics-typepulse-analytics.json"));
         }
     }
+
+    #[test]
+    fn per_day_counters_round_trip_through_daily_files() {
+        let path = temp_path("analytics-chords");
+        let parent = path.parent().unwrap().to_path_buf();
+        let base = path.file_name().unwrap().to_str().unwrap().to_string();
+        let storage = JsonFileStorage::new(path.clone());
+        // Days with counters but no chunks still get their own daily files.
+        let mut analytics = StoredInputAnalytics::default();
+        analytics.chord_aborts.insert(
+            "2024-01-01".to_string(),
+            HashMap::from([("com.test.editor".to_string(), 3)]),
+        );
+        analytics.chord_aborts.insert(
+            "2024-01-02".to_string(),
+            HashMap::from([("com.test.term".to_string(), 1)]),
+        );
+        analytics.focus_days.insert(
+            "2024-01-02".to_string(),
+            StoredFocusDay {
+                score: 72,
+                session_count: 2,
+                longest_session_ms: 1_500_000,
+                focused_ms: 2_100_000,
+                app_switches: 4,
+            },
+        );
+        analytics.app_switches.insert(
+            "2024-01-03".to_string(),
+            StoredAppSwitchDay {
+                total: 5,
+                pairs: vec![StoredAppSwitchPair {
+                    from: "com.test.editor".to_string(),
+                    to: "com.test.term".to_string(),
+                    count: 5,
+                }],
+            },
+        );
+        analytics.coverage.insert(
+            "2024-01-03".to_string(),
+            StoredCoverageDay {
+                recording_ms: 3_600_000,
+                paused_ms: 600_000,
+                listener_down_ms: 120_000,
+            },
+        );
+        analytics.runtime.insert(
+            "2024-01-04".to_string(),
+            vec![StoredRuntimeSpan {
+                start_ms: 1_704_355_200_000,
+                end_ms: 1_704_358_800_000,
+            }],
+        );
+        analytics.bursts.insert(
+            "2024-01-04".to_string(),
+            StoredBurstDay {
+                count: 3,
+                total_keys: 54,
+                total_ms: 21_000,
+                longest_keys: 30,
+                longest_ms: 9_500,
+            },
+        );
+        analytics.effort.insert("2024-01-05".to_string(), 12_500);
+        analytics.displays.insert(
+            "2024-01-06".to_string(),
+            HashMap::from([("display-1".to_string(), 40), ("display-2".to_string(), 2)]),
+        );
+        analytics.copy_pastes.insert(
+            "2024-01-06".to_string(),
+            StoredCopyPasteDay {
+                pairs: 2,
+                unmatched_copies: 1,
+                latencies_ms: vec![800, 1_500],
+                app_pairs: vec![StoredAppSwitchPair {
+                    from: "com.test.editor".to_string(),
+                    to: "com.test.browser".to_string(),
+                    count: 2,
+                }],
+            },
+        );
+        analytics.key_spans.insert(
+            "2024-01-06".to_string(),
+            StoredKeySpan {
+                first_ms: 1_704_520_920_000,
+                last_ms: 1_704_558_600_000,
+            },
+        );
+        analytics
+            .weekly_comparisons
+            .insert("2024-01-08".to_string(), "2024-01-01".to_string());
+        storage.save_input_analytics(&analytics).unwrap();
+        let loaded = storage.load_input_analytics().unwrap();
+        assert_eq!(loaded.chord_aborts, analytics.chord_aborts);
+        assert_eq!(loaded.focus_days, analytics.focus_days);
+        assert_eq!(loaded.app_switches, analytics.app_switches);
+        assert_eq!(loaded.coverage, analytics.coverage);
+        assert_eq!(loaded.runtime, analytics.runtime);
+        assert_eq!(loaded.bursts, analytics.bursts);
+        assert_eq!(loaded.effort, analytics.effort);
+        assert_eq!(loaded.displays, analytics.displays);
+        assert_eq!(loaded.copy_pastes, analytics.copy_pastes);
+        assert_eq!(loaded.key_spans, analytics.key_spans);
+        assert_eq!(loaded.weekly_comparisons, analytics.weekly_comparisons);
+        for day in [
+            "2024-01-01",
+            "2024-01-02",
+            "2024-01-03",
+            "2024-01-04",
+            "2024-01-05",
+            "2024-01-06",
+            "2024-01-08",
+        ] {
+            let _ = fs::remove_file(parent.join(format!("{day}-analytics-{base}")));
+        }
+    }
+
+    #[test]
+    fn merge_drops_chunks_duplicated_across_legacy_and_daily_files() {
+        let path = temp_path("analytics-dedup");
+        let parent = path.parent().unwrap().to_path_buf();
+        let base = path.file_name().unwrap().to_str().unwrap().to_string();
+        let chunk = |start: i64, app_ref: u32, first: &str| StoredInputEventChunk {
+            v: 1,
+            chunk_start_ms: start,
+            app_ref,
+            events: vec![first.to_string(), "5,u,c,0".to_string()],
+        };
+        let payload = |chunks: Vec<StoredInputEventChunk>| {
+            let mut analytics = StoredInputAnalytics::default();
+            analytics.app_dict.insert(1, "com.test.editor".to_string());
+            analytics.app_dict.insert(2, "com.test.term".to_string());
+            analytics.next_app_ref = 3;
+            analytics.event_chunks = chunks;
+            serde_json::to_vec(&analytics).unwrap()
+        };
+        // Legacy file holds two chunks that the daily file repeats, plus one only it has.
+        let legacy_path = parent.join(format!("analytics-{base}"));
+        fs::write(
+            &legacy_path,
+            payload(vec![
+                chunk(1_706_054_400_000, 1, "0,d,c,8"),
+                chunk(1_706_054_460_000, 2, "0,d,v,8"),
+                chunk(1_706_054_520_000, 1, "0,d,z,8"),
+            ]),
+        )
+        .unwrap();
+        let daily_path = parent.join(format!("2024-01-01-analytics-{base}"));
+        fs::write(
+            &daily_path,
+            payload(vec![
+                chunk(1_706_054_460_000, 2, "0,d,v,8"),
+                chunk(1_706_054_400_000, 1, "0,d,c,8"),
+                // Same start and app but a different first event is a distinct chunk.
+                chunk(1_706_054_400_000, 1, "0,d,x,8"),
+            ]),
+        )
+        .unwrap();
+
+        let storage = JsonFileStorage::new(path.clone());
+        let loaded = storage.load_input_analytics().unwrap();
+        assert_eq!(loaded.duplicate_chunks_dropped, 2);
+        let order: Vec<(i64, u32, String)> = loaded
+            .event_chunks
+            .iter()
+            .map(|chunk| (chunk.chunk_start_ms, chunk.app_ref, chunk.events[0].clone()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (1_706_054_400_000, 1, "0,d,c,8".to_string()),
+                (1_706_054_400_000, 1, "0,d,x,8".to_string()),
+                (1_706_054_460_000, 2, "0,d,v,8".to_string()),
+                (1_706_054_520_000, 1, "0,d,z,8".to_string()),
+            ]
+        );
+        // Loading twice yields the same result.
+        let reloaded = storage.load_input_analytics().unwrap();
+        assert_eq!(reloaded.event_chunks.len(), loaded.event_chunks.len());
+        let _ = fs::remove_file(legacy_path);
+        let _ = fs::remove_file(daily_path);
+    }
+
+    #[test]
+    fn rollup_merges_old_minutes_into_hours_and_preserves_totals() {
+        let mut stats = HashMap::new();
+        let mut insert = |date: &str, title: &str, active: u64, keys: u64, sessions: u64| {
+            stats.insert(
+                StatsKey {
+                    date: date.parse().unwrap(),
+                    app_name: "AppA".to_string(),
+                    window_title: title.to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: active,
+                    key_count: keys,
+                    session_count: sessions,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            );
+        };
+        insert("2026-01-01 10:00", "Doc", 100, 3, 1);
+        insert("2026-01-01 10:59", "Doc", 250, 7, 2);
+        insert("2026-01-01 11:05", "Doc", 40, 1, 0);
+        insert("2026-01-01 10:30", "Other", 10, 1, 1);
+        insert("2026-02-01 10:30", "Doc", 5, 2, 1);
+        let sum = |stats: &HashMap<StatsKey, StatsValue>| {
+            stats.values().fold((0, 0, 0), |acc, v| {
+                (
+                    acc.0 + v.active_typing_ms,
+                    acc.1 + v.key_count,
+                    acc.2 + v.session_count,
+                )
+            })
+        };
+        let before = sum(&stats);
+        let dates: HashSet<NaiveDate> = [day("2026-01-01")].into_iter().collect();
+        let rolled = rollup_stats_to_hours(stats, &dates);
+
+        assert_eq!(sum(&rolled), before);
+        assert_eq!(rolled.len(), 4);
+        let hour_doc = rolled
+            .get(&StatsKey {
+                date: "2026-01-01 10:00".parse().unwrap(),
+                app_name: "AppA".to_string(),
+                window_title: "Doc".to_string(),
+            })
+            .unwrap();
+        assert_eq!(
+            (
+                hour_doc.active_typing_ms,
+                hour_doc.key_count,
+                hour_doc.session_count
+            ),
+            (350, 10, 3)
+        );
+        // Days outside the rollup set keep minute resolution.
+        assert!(rolled
+            .keys()
+            .any(|key| key.date.to_string() == "2026-02-01 10:30"));
+    }
+
+    #[test]
+    fn rolled_up_marker_survives_save_and_reload() {
+        let path = temp_path("rollup");
+        let storage = JsonFileStorage::new(path.clone());
+        let mut stats = HashMap::new();
+        stats.insert(
+            StatsKey {
+                date: "2026-01-01 10:00".parse().unwrap(),
+                app_name: "AppA".to_string(),
+                window_title: "Doc".to_string(),
+            },
+            StatsValue {
+                active_typing_ms: 350,
+                key_count: 10,
+                session_count: 3,
+                writing_ms: 0,
+                navigation_ms: 0,
+            },
+        );
+        storage.mark_rolled_up(&[day("2026-01-01")]);
+        storage.save_stats(&stats).unwrap();
+
+        let reloaded = JsonFileStorage::new(path.clone());
+        let loaded = reloaded.load_stats().unwrap();
+        assert!(reloaded.is_rolled_up(day("2026-01-01")));
+        assert!(!reloaded.is_rolled_up(day("2026-01-02")));
+        assert_eq!(loaded.values().map(|v| v.key_count).sum::<u64>(), 10);
+        if let Some(day_path) = dated_path(&path, "2026-01-01") {
+            let _ = fs::remove_file(day_path);
+        }
+    }
+
+    #[test]
+    fn external_changes_are_reported_once_and_unchanged_days_are_not_rewritten() {
+        let path = temp_path("external");
+        let stats = |key_count: u64| {
+            HashMap::from([(
+                StatsKey {
+                    date: "2026-01-01 10:00".parse().unwrap(),
+                    app_name: "AppA".to_string(),
+                    window_title: "Doc".to_string(),
+                },
+                StatsValue {
+                    active_typing_ms: 350,
+                    key_count,
+                    session_count: 1,
+                    writing_ms: 0,
+                    navigation_ms: 0,
+                },
+            )])
+        };
+        JsonFileStorage::new(path.clone())
+            .save_stats(&stats(10))
+            .unwrap();
+        let day_path = dated_path(&path, "2026-01-01").unwrap();
+
+        // Files found on load are watched too.
+        let storage = JsonFileStorage::new(path.clone());
+        storage.load_stats().unwrap();
+        assert_eq!(storage.take_external_changes(), ExternalChanges::default());
+
+        fs::write(&day_path, "[]").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&day_path)
+            .unwrap()
+            .set_modified(SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+        let changes = storage.take_external_changes();
+        assert_eq!(changes.modified, vec![day_path.clone()]);
+        assert!(changes.deleted.is_empty());
+        assert!(storage.take_external_changes().is_empty());
+        // The day did not change in memory, so the edit on disk is kept.
+        storage.save_stats(&stats(10)).unwrap();
+        assert_eq!(fs::read_to_string(&day_path).unwrap(), "[]");
+
+        fs::remove_file(&day_path).unwrap();
+        assert_eq!(
+            storage.take_external_changes().deleted,
+            vec![day_path.clone()]
+        );
+        storage.save_stats(&stats(10)).unwrap();
+        assert!(!day_path.exists());
+        storage.save_stats(&stats(11)).unwrap();
+        assert!(day_path.exists());
+        assert!(storage.take_external_changes().is_empty());
+
+        storage.forget_files(std::slice::from_ref(&day_path));
+        fs::remove_file(&day_path).unwrap();
+        assert!(storage.take_external_changes().is_empty());
+    }
+
+    #[test]
+    fn stored_files_lists_detail_and_analytics_files_with_their_day() {
+        let dir = temp_dir("stored-files");
+        let storage = JsonFileStorage::new(dir.join("details.json"));
+        for name in [
+            "details.json",
+            "analytics-details.json",
+            "2026-03-01-details.json",
+            "2026-03-01-analytics-details.json",
+            "2026-03-01-details.json.tmp",
+            "notes-details.json",
+            "typingstats.csv",
+        ] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+
+        let files = storage.stored_files();
+        let listed: Vec<(String, Option<NaiveDate>, u64)> = files
+            .iter()
+            .map(|StoredFile { path, day, bytes }| {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                (name, *day, *bytes)
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                (
+                    "2026-03-01-analytics-details.json".to_string(),
+                    Some(day("2026-03-01")),
+                    33
+                ),
+                (
+                    "2026-03-01-details.json".to_string(),
+                    Some(day("2026-03-01")),
+                    23
+                ),
+                ("analytics-details.json".to_string(), None, 22),
+                ("details.json".to_string(), None, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn archived_days_load_back_and_stay_archived_until_they_change() {
+        let dir = temp_dir("archive");
+        let path = dir.join("details.json");
+        let storage = JsonFileStorage::new(path.clone());
+        let stats_with = |keys: u64| {
+            let mut stats = HashMap::new();
+            for (date, key_count) in [("2026-01-05 09:00", keys), ("2026-03-02 09:00", 1)] {
+                stats.insert(
+                    StatsKey {
+                        date: date.parse().unwrap(),
+                        app_name: "AppA".to_string(),
+                        window_title: "Doc".to_string(),
+                    },
+                    StatsValue {
+                        active_typing_ms: 1_000,
+                        key_count,
+                        session_count: 1,
+                        writing_ms: 0,
+                        navigation_ms: 0,
+                    },
+                );
+            }
+            stats
+        };
+        let mut analytics = StoredInputAnalytics::default();
+        analytics.coverage.insert(
+            "2026-01-05".to_string(),
+            StoredCoverageDay {
+                recording_ms: 60_000,
+                paused_ms: 0,
+                listener_down_ms: 0,
+            },
+        );
+        storage.save_stats(&stats_with(5)).unwrap();
+        storage.save_input_analytics(&analytics).unwrap();
+
+        let report = storage.archive_days_before(day("2026-02-01")).unwrap();
+        assert_eq!(report.months, vec!["2026-01".to_string()]);
+        assert_eq!(report.files, 2);
+        assert!(dir.join("archive-2026-01.zip").is_file());
+        assert!(!dir.join("2026-01-05-details.json").exists());
+        assert!(!dir.join("2026-01-05-analytics-details.json").exists());
+        assert!(dir.join("2026-03-02-details.json").exists());
+        let archive = storage
+            .stored_files()
+            .into_iter()
+            .find(|file| file.path.ends_with("archive-2026-01.zip"))
+            .unwrap();
+        assert_eq!(archive.day, None);
+
+        // Loading reads the archive; saving the same data leaves the day archived.
+        let reloaded = JsonFileStorage::new(path.clone());
+        let loaded = reloaded.load_stats().unwrap();
+        assert_eq!(loaded.values().map(|v| v.key_count).sum::<u64>(), 6);
+        let loaded_analytics = reloaded.load_input_analytics().unwrap();
+        assert_eq!(loaded_analytics.coverage["2026-01-05"].recording_ms, 60_000);
+        reloaded.save_stats(&loaded).unwrap();
+        reloaded.save_input_analytics(&loaded_analytics).unwrap();
+        assert!(!dir.join("2026-01-05-details.json").exists());
+        assert!(!dir.join("2026-01-05-analytics-details.json").exists());
+
+        // A changed day is written loose again, wins over its archived copy and is re-archived.
+        reloaded.save_stats(&stats_with(9)).unwrap();
+        assert!(dir.join("2026-01-05-details.json").exists());
+        let total = |storage: &JsonFileStorage| {
+            storage
+                .load_stats()
+                .unwrap()
+                .values()
+                .map(|v| v.key_count)
+                .sum::<u64>()
+        };
+        assert_eq!(total(&JsonFileStorage::new(path.clone())), 10);
+        let report = reloaded.archive_days_before(day("2026-02-01")).unwrap();
+        assert_eq!(report.files, 1);
+        assert!(!dir.join("2026-01-05-details.json").exists());
+        assert_eq!(total(&JsonFileStorage::new(path.clone())), 10);
+    }
+
+    #[test]
+    fn sqlite_storage_migrates_the_daily_json_files_once() {
+        let dir = temp_dir("sqlite-migration");
+        let path = dir.join("details.json");
+        let row = |date: &str, key_count: u64| {
+            serde_json::json!({
+                "date": date,
+                "app_name": "com.test.editor",
+                "window_title": "Doc",
+                "active_typing_ms": key_count * 100,
+                "key_count": key_count,
+                "session_count": 1,
+                "writing_ms": key_count * 80,
+                "navigation_ms": key_count * 20,
+            })
+        };
+        let chunk_start_ms: i64 = 1_772_452_800_000;
+        for (name, payload) in [
+            (
+                "2026-03-01-details.json",
+                serde_json::json!({ "rolled_up": true, "rows": [row("2026-03-01 09:00", 7)] }),
+            ),
+            (
+                "2026-03-02-details.json",
+                serde_json::json!([row("2026-03-02 10:15", 3), row("2026-03-02 10:16", 2)]),
+            ),
+            (
+                "2026-03-02-analytics-details.json",
+                serde_json::json!({
+                    "app_dict": { "1": "com.test.editor" },
+                    "next_app_ref": 2,
+                    "event_chunks": [{
+                        "v": 2,
+                        "chunk_start_ms": chunk_start_ms,
+                        "app_ref": 1,
+                        "events": ["0,d,8,,", "40,u,8,,"],
+                    }],
+                    "coverage": {
+                        "2026-03-02": { "recording_ms": 60_000, "paused_ms": 0, "listener_down_ms": 0 },
+                    },
+                }),
+            ),
+        ] {
+            fs::write(dir.join(name), payload.to_string()).unwrap();
+        }
+        let summary = |stats: &HashMap<StatsKey, StatsValue>| {
+            let mut rows: Vec<(String, u64, u64, u64)> = stats
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        key.date.to_string(),
+                        value.key_count,
+                        value.active_typing_ms,
+                        value.writing_ms,
+                    )
+                })
+                .collect();
+            rows.sort();
+            rows
+        };
+        let chunks = |analytics: &StoredInputAnalytics| {
+            analytics
+                .event_chunks
+                .iter()
+                .map(|chunk| {
+                    (
+                        chunk.v,
+                        chunk.chunk_start_ms,
+                        chunk.app_ref,
+                        chunk.events.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let json = JsonFileStorage::new(path.clone());
+        let json_stats = json.load_stats().unwrap();
+        let json_analytics = json.load_input_analytics().unwrap();
+
+        let storage = SqliteStorage::new(path.clone(), dir.join("app.log"));
+        let progress = LoadProgress::default();
+        let (stats, analytics) = storage.load_history(&progress);
+        let (stats, analytics) = (stats.unwrap(), analytics.unwrap());
+        assert_eq!(progress.files(), (3, 3));
+        assert_eq!(summary(&stats), summary(&json_stats));
+        assert_eq!(summary(&stats).len(), 3);
+        assert_eq!(chunks(&analytics), chunks(&json_analytics));
+        assert_eq!(analytics.app_dict, json_analytics.app_dict);
+        assert_eq!(analytics.next_app_ref, 2);
+        assert_eq!(analytics.coverage, json_analytics.coverage);
+        assert!(storage.is_rolled_up(day("2026-03-01")));
+        assert!(!storage.is_rolled_up(day("2026-03-02")));
+        assert!(dir.join("details.sqlite").exists());
+        assert!(dir.join("2026-03-02-analytics-details.json").exists());
+
+        // Saves behave like the JSON storage: a day missing from the stats is gone.
+        let mut next_stats = stats.clone();
+        next_stats.retain(|key, _| key.date.day() == day("2026-03-02"));
+        let mut next_analytics = analytics.clone();
+        next_analytics.shortcut_usage.insert(
+            "cmd_c".to_string(),
+            StoredShortcutUsage {
+                count: 4,
+                by_app: HashMap::from([("com.test.editor".to_string(), 4)]),
+            },
+        );
+        storage.save_stats(&next_stats).unwrap();
+        storage.save_input_analytics(&next_analytics).unwrap();
+        // Daily files written after the migration are not picked up again.
+        fs::write(
+            dir.join("2026-03-03-details.json"),
+            serde_json::json!([row("2026-03-03 08:00", 9)]).to_string(),
+        )
+        .unwrap();
+
+        let reopened = SqliteStorage::new(path.clone(), dir.join("app.log"));
+        let progress = LoadProgress::default();
+        let (stats, analytics) = reopened.load_history(&progress);
+        let (stats, analytics) = (stats.unwrap(), analytics.unwrap());
+        assert_eq!(progress.files(), (0, 0));
+        assert_eq!(summary(&stats), summary(&next_stats));
+        assert_eq!(chunks(&analytics), chunks(&json_analytics));
+        assert_eq!(analytics.shortcut_usage["cmd_c"].count, 4);
+        assert_eq!(
+            analytics.shortcut_usage["cmd_c"].by_app["com.test.editor"],
+            4
+        );
+        assert!(reopened.is_rolled_up(day("2026-03-01")));
+
+        // A corrupt per-app breakdown keeps the rest of the analytics and is logged.
+        rusqlite::Connection::open(dir.join("details.sqlite"))
+            .unwrap()
+            .execute(
+                "UPDATE shortcut_usage SET by_app = '{' WHERE shortcut_id = 'cmd_c'",
+                [],
+            )
+            .unwrap();
+        let corrupted = SqliteStorage::new(path.clone(), dir.join("app.log"));
+        let analytics = corrupted.load_input_analytics().unwrap();
+        assert_eq!(analytics.shortcut_usage["cmd_c"].count, 4);
+        assert!(analytics.shortcut_usage["cmd_c"].by_app.is_empty());
+        assert_eq!(chunks(&analytics), chunks(&json_analytics));
+        crate::collector::flush_logs();
+        assert!(fs::read_to_string(dir.join("app.log"))
+            .unwrap()
+            .contains("unreadable per-app usage of shortcut cmd_c"));
+    }
 }
@@ -0,0 +1,541 @@
+//! SQLite storage module.
+//! Keeps stats rows, shortcut usage, the app dictionary and event chunks in one database next
+//! to the JSON detail file. The JSON daily files found on first use are migrated once and then
+//! left in place, so switching back to JSON storage finds the history as it was then.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+use super::{
+    payload_fingerprint, DetailStorage, JsonFileStorage, LoadProgress, StoredHistory,
+    StoredInputAnalytics, StoredInputEventChunk, StoredRow, StoredShortcutUsage,
+};
+use crate::collector::{append_app_log, StatsKey, StatsValue, DAY_KEY_FORMAT};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS stats_rows (
+        day TEXT NOT NULL,
+        date TEXT NOT NULL,
+        app_name TEXT NOT NULL,
+        window_title TEXT NOT NULL,
+        active_typing_ms INTEGER NOT NULL,
+        key_count INTEGER NOT NULL,
+        session_count INTEGER NOT NULL,
+        writing_ms INTEGER NOT NULL,
+        navigation_ms INTEGER NOT NULL,
+        PRIMARY KEY (date, app_name, window_title)
+    );
+    CREATE INDEX IF NOT EXISTS stats_rows_day ON stats_rows (day);
+    CREATE TABLE IF NOT EXISTS rolled_up_days (
+        day TEXT PRIMARY KEY
+    );
+    CREATE TABLE IF NOT EXISTS shortcut_usage (
+        shortcut_id TEXT PRIMARY KEY,
+        count INTEGER NOT NULL,
+        by_app TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS app_dict (
+        app_ref INTEGER PRIMARY KEY,
+        app_id TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS day_analytics (
+        day TEXT PRIMARY KEY,
+        payload TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS event_chunks (
+        day TEXT NOT NULL,
+        seq INTEGER NOT NULL,
+        v INTEGER NOT NULL,
+        chunk_start_ms INTEGER NOT NULL,
+        app_ref INTEGER NOT NULL,
+        events TEXT NOT NULL,
+        PRIMARY KEY (day, seq)
+    );
+";
+
+// Meta key set once the JSON daily files were copied in, to the epoch milliseconds it happened.
+const JSON_MIGRATED_KEY: &str = "json_migrated_ms";
+const NEXT_APP_REF_KEY: &str = "next_app_ref";
+
+fn sql_error(err: rusqlite::Error) -> String {
+    err.to_string()
+}
+
+fn day_key(day: NaiveDate) -> String {
+    day.format(DAY_KEY_FORMAT).to_string()
+}
+
+// Open connection with the payload fingerprints of the days as last written, so a save only
+// rewrites days that changed since.
+struct Database {
+    conn: Connection,
+    stats_written: HashMap<NaiveDate, u64>,
+    analytics_written: HashMap<String, u64>,
+}
+
+/// Storage that keeps the history in a SQLite database, `<detail name>.sqlite`. Load and save
+/// behave as for `JsonFileStorage`; days missing from a save are deleted, which is what
+/// removing their daily files does there.
+pub(crate) struct SqliteStorage {
+    path: PathBuf,
+    json_path: PathBuf,
+    // Failures no caller can see, such as unreadable columns or a lost rolled-up marker, are
+    // logged here.
+    app_log_path: PathBuf,
+    db: Mutex<Option<Database>>,
+}
+
+impl SqliteStorage {
+    /// Storage for the JSON detail file `detail_path`. The database is opened, and the JSON
+    /// daily files migrated, on first use, so an unavailable data directory surfaces as a
+    /// failed load or save rather than here.
+    pub(crate) fn new(detail_path: PathBuf, app_log_path: PathBuf) -> Self {
+        Self {
+            path: detail_path.with_extension("sqlite"),
+            json_path: detail_path,
+            app_log_path,
+            db: Mutex::new(None),
+        }
+    }
+
+    // Run `f` on the open database, opening it and migrating the JSON daily files first if
+    // that has not happened yet. Files read by the migration are counted into `progress`.
+    fn with_database<T>(
+        &self,
+        progress: &LoadProgress,
+        f: impl FnOnce(&mut Database) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut slot = self
+            .db
+            .lock()
+            .map_err(|_| "sqlite storage lock failed".to_string())?;
+        let db = match &mut *slot {
+            Some(db) => db,
+            empty => empty.insert(Database {
+                conn: open_database(&self.path)?,
+                stats_written: HashMap::new(),
+                analytics_written: HashMap::new(),
+            }),
+        };
+        self.migrate_json_files(db, progress)?;
+        f(db)
+    }
+
+    // Copy the JSON daily files into an unmigrated database in one transaction. Until it
+    // succeeds every load and save fails, so the two never hold diverging histories.
+    fn migrate_json_files(&self, db: &mut Database, progress: &LoadProgress) -> Result<(), String> {
+        let Database {
+            conn,
+            stats_written,
+            analytics_written,
+        } = db;
+        if read_meta(conn, JSON_MIGRATED_KEY)?.is_some() {
+            return Ok(());
+        }
+        let json = JsonFileStorage::new(self.json_path.clone());
+        let (stats, analytics) = json.load_history(progress);
+        let (stats, analytics) = (stats?, analytics?);
+        let rolled_up: HashSet<NaiveDate> = stats
+            .keys()
+            .map(|key| key.date.day())
+            .filter(|day| json.is_rolled_up(*day))
+            .collect();
+        let tx = conn.transaction().map_err(sql_error)?;
+        let next_stats = write_stats(&tx, &stats, &HashMap::new())?;
+        let next_analytics = write_analytics(&tx, &analytics, &HashMap::new())?;
+        for day in rolled_up {
+            insert_rolled_up(&tx, day)?;
+        }
+        write_meta(
+            &tx,
+            JSON_MIGRATED_KEY,
+            &chrono::Utc::now().timestamp_millis().to_string(),
+        )?;
+        tx.commit().map_err(sql_error)?;
+        *stats_written = next_stats;
+        *analytics_written = next_analytics;
+        Ok(())
+    }
+
+    fn read_stats(conn: &Connection) -> Result<HashMap<StatsKey, StatsValue>, String> {
+        let mut statement = conn
+            .prepare(
+                "SELECT date, app_name, window_title, active_typing_ms, key_count, \
+                 session_count, writing_ms, navigation_ms FROM stats_rows",
+            )
+            .map_err(sql_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(StoredRow {
+                    date: row.get(0)?,
+                    app_name: row.get(1)?,
+                    window_title: row.get(2)?,
+                    active_typing_ms: row.get(3)?,
+                    key_count: row.get(4)?,
+                    session_count: row.get(5)?,
+                    writing_ms: row.get(6)?,
+                    navigation_ms: row.get(7)?,
+                })
+            })
+            .map_err(sql_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(sql_error)?;
+        Ok(JsonFileStorage::rows_to_stats(rows))
+    }
+
+    fn read_analytics(&self, conn: &Connection) -> Result<StoredInputAnalytics, String> {
+        let mut merged = StoredInputAnalytics {
+            next_app_ref: read_meta(conn, NEXT_APP_REF_KEY)?
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(0),
+            ..StoredInputAnalytics::default()
+        };
+        let mut statement = conn
+            .prepare("SELECT shortcut_id, count, by_app FROM shortcut_usage")
+            .map_err(sql_error)?;
+        let usage = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, u64>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .map_err(sql_error)?;
+        for entry in usage {
+            let (shortcut_id, count, by_app) = entry.map_err(sql_error)?;
+            // Failing the whole load would drop every analytics table, so only the per-app
+            // breakdown of this shortcut is lost, and logged.
+            let by_app = serde_json::from_str(&by_app).unwrap_or_else(|err| {
+                let _ = append_app_log(
+                    &self.app_log_path,
+                    &format!(
+                        "unreadable per-app usage of shortcut {} in {}, keeping its total only: {}",
+                        shortcut_id,
+                        self.path.display(),
+                        err
+                    ),
+                );
+                HashMap::new()
+            });
+            let usage = StoredShortcutUsage { count, by_app };
+            merged.shortcut_usage.insert(shortcut_id, usage);
+        }
+        let mut statement = conn
+            .prepare("SELECT app_ref, app_id FROM app_dict")
+            .map_err(sql_error)?;
+        merged.app_dict = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(sql_error)?
+            .collect::<Result<_, _>>()
+            .map_err(sql_error)?;
+        // Per-day counters merge like the daily files; a payload that does not parse is
+        // skipped like a corrupted file.
+        let mut statement = conn
+            .prepare("SELECT payload FROM day_analytics ORDER BY day")
+            .map_err(sql_error)?;
+        let payloads = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(sql_error)?;
+        let mut seen_chunks = HashSet::new();
+        for payload in payloads {
+            let payload = payload.map_err(sql_error)?;
+            if let Ok(payload) = serde_json::from_str::<StoredInputAnalytics>(&payload) {
+                JsonFileStorage::merge_analytics(&mut merged, payload, &mut seen_chunks);
+            }
+        }
+        let mut statement = conn
+            .prepare(
+                "SELECT v, chunk_start_ms, app_ref, events FROM event_chunks \
+                 ORDER BY chunk_start_ms, app_ref, day, seq",
+            )
+            .map_err(sql_error)?;
+        let chunks = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, u8>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, u32>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .map_err(sql_error)?;
+        for chunk in chunks {
+            let (v, chunk_start_ms, app_ref, events) = chunk.map_err(sql_error)?;
+            let Ok(events) = serde_json::from_str::<Vec<String>>(&events) else {
+                continue;
+            };
+            merged.event_chunks.push(StoredInputEventChunk {
+                v,
+                chunk_start_ms,
+                app_ref,
+                events,
+            });
+        }
+        Ok(merged)
+    }
+}
+
+fn open_database(path: &Path) -> Result<Connection, String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let conn = Connection::open(path).map_err(sql_error)?;
+    conn.execute_batch(SCHEMA).map_err(sql_error)?;
+    Ok(conn)
+}
+
+fn read_meta(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .optional()
+    .map_err(sql_error)
+}
+
+fn write_meta(tx: &Transaction, key: &str, value: &str) -> Result<(), String> {
+    tx.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+        [key, value],
+    )
+    .map(|_| ())
+    .map_err(sql_error)
+}
+
+fn insert_rolled_up(tx: &Transaction, day: NaiveDate) -> Result<(), String> {
+    tx.execute(
+        "INSERT OR IGNORE INTO rolled_up_days (day) VALUES (?1)",
+        [day_key(day)],
+    )
+    .map(|_| ())
+    .map_err(sql_error)
+}
+
+// Days stored in `table`, by their `day` column.
+fn stored_days(tx: &Transaction, table: &str) -> Result<Vec<String>, String> {
+    let mut statement = tx
+        .prepare(&format!("SELECT DISTINCT day FROM {table}"))
+        .map_err(sql_error)?;
+    let days = statement
+        .query_map([], |row| row.get(0))
+        .map_err(sql_error)?
+        .collect::<Result<_, _>>()
+        .map_err(sql_error);
+    days
+}
+
+// Rewrite the days of `stats` whose rows differ from `written` and delete the days it no
+// longer has. Returns the fingerprints to remember once the transaction commits.
+fn write_stats(
+    tx: &Transaction,
+    stats: &HashMap<StatsKey, StatsValue>,
+    written: &HashMap<NaiveDate, u64>,
+) -> Result<HashMap<NaiveDate, u64>, String> {
+    let mut grouped: BTreeMap<NaiveDate, Vec<(&StatsKey, &StatsValue)>> = BTreeMap::new();
+    for entry in stats {
+        grouped.entry(entry.0.date.day()).or_default().push(entry);
+    }
+    let kept: HashSet<String> = grouped.keys().map(|day| day_key(*day)).collect();
+    for day in stored_days(tx, "stats_rows")? {
+        if !kept.contains(&day) {
+            tx.execute("DELETE FROM stats_rows WHERE day = ?1", [&day])
+                .map_err(sql_error)?;
+        }
+    }
+    let mut next = HashMap::with_capacity(grouped.len());
+    for (day, entries) in grouped {
+        let rows = JsonFileStorage::entries_to_rows(entries);
+        let fingerprint = payload_fingerprint(&rows)?;
+        next.insert(day, fingerprint);
+        if written.get(&day) == Some(&fingerprint) {
+            continue;
+        }
+        let day = day_key(day);
+        tx.execute("DELETE FROM stats_rows WHERE day = ?1", [&day])
+            .map_err(sql_error)?;
+        let mut insert = tx
+            .prepare_cached(
+                "INSERT INTO stats_rows (day, date, app_name, window_title, active_typing_ms, \
+                 key_count, session_count, writing_ms, navigation_ms) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )
+            .map_err(sql_error)?;
+        for row in rows {
+            insert
+                .execute(params![
+                    day,
+                    row.date,
+                    row.app_name,
+                    row.window_title,
+                    row.active_typing_ms,
+                    row.key_count,
+                    row.session_count,
+                    row.writing_ms,
+                    row.navigation_ms,
+                ])
+                .map_err(sql_error)?;
+        }
+    }
+    Ok(next)
+}
+
+// Replace shortcut usage and the app dictionary, rewrite the analytics days that differ from
+// `written` and delete the days `analytics` no longer has. Returns the fingerprints to
+// remember once the transaction commits.
+fn write_analytics(
+    tx: &Transaction,
+    analytics: &StoredInputAnalytics,
+    written: &HashMap<String, u64>,
+) -> Result<HashMap<String, u64>, String> {
+    tx.execute("DELETE FROM shortcut_usage", [])
+        .map_err(sql_error)?;
+    for (shortcut_id, usage) in &analytics.shortcut_usage {
+        let by_app = serde_json::to_string(&usage.by_app).map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT INTO shortcut_usage (shortcut_id, count, by_app) VALUES (?1, ?2, ?3)",
+            params![shortcut_id, usage.count, by_app],
+        )
+        .map_err(sql_error)?;
+    }
+    tx.execute("DELETE FROM app_dict", []).map_err(sql_error)?;
+    for (app_ref, app_id) in &analytics.app_dict {
+        tx.execute(
+            "INSERT INTO app_dict (app_ref, app_id) VALUES (?1, ?2)",
+            params![app_ref, app_id],
+        )
+        .map_err(sql_error)?;
+    }
+    write_meta(tx, NEXT_APP_REF_KEY, &analytics.next_app_ref.to_string())?;
+
+    let payloads = JsonFileStorage::daily_analytics_payloads(analytics);
+    for day in stored_days(tx, "day_analytics")? {
+        if !payloads.contains_key(&day) {
+            tx.execute("DELETE FROM day_analytics WHERE day = ?1", [&day])
+                .map_err(sql_error)?;
+            tx.execute("DELETE FROM event_chunks WHERE day = ?1", [&day])
+                .map_err(sql_error)?;
+        }
+    }
+    let mut next = HashMap::with_capacity(payloads.len());
+    for (day, mut payload) in payloads {
+        // The app dictionary lives in its own table.
+        payload.app_dict.clear();
+        payload.next_app_ref = 0;
+        let fingerprint = payload_fingerprint(&payload)?;
+        next.insert(day.clone(), fingerprint);
+        if written.get(&day) == Some(&fingerprint) {
+            continue;
+        }
+        let chunks = std::mem::take(&mut payload.event_chunks);
+        let counters = serde_json::to_string(&payload).map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT OR REPLACE INTO day_analytics (day, payload) VALUES (?1, ?2)",
+            [&day, &counters],
+        )
+        .map_err(sql_error)?;
+        tx.execute("DELETE FROM event_chunks WHERE day = ?1", [&day])
+            .map_err(sql_error)?;
+        let mut insert = tx
+            .prepare_cached(
+                "INSERT INTO event_chunks (day, seq, v, chunk_start_ms, app_ref, events) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(sql_error)?;
+        for (seq, chunk) in chunks.iter().enumerate() {
+            let events = serde_json::to_string(&chunk.events).map_err(|e| e.to_string())?;
+            insert
+                .execute(params![
+                    day,
+                    seq as i64,
+                    chunk.v,
+                    chunk.chunk_start_ms,
+                    chunk.app_ref,
+                    events,
+                ])
+                .map_err(sql_error)?;
+        }
+    }
+    Ok(next)
+}
+
+impl DetailStorage for SqliteStorage {
+    fn load_stats(&self) -> Result<HashMap<StatsKey, StatsValue>, String> {
+        self.with_database(&LoadProgress::default(), |db| Self::read_stats(&db.conn))
+    }
+
+    fn save_stats(&self, stats: &HashMap<StatsKey, StatsValue>) -> Result<(), String> {
+        self.with_database(&LoadProgress::default(), |db| {
+            let tx = db.conn.transaction().map_err(sql_error)?;
+            let next = write_stats(&tx, stats, &db.stats_written)?;
+            tx.commit().map_err(sql_error)?;
+            db.stats_written = next;
+            Ok(())
+        })
+    }
+
+    fn is_rolled_up(&self, day: NaiveDate) -> bool {
+        self.with_database(&LoadProgress::default(), |db| {
+            db.conn
+                .query_row(
+                    "SELECT 1 FROM rolled_up_days WHERE day = ?1",
+                    [day_key(day)],
+                    |_| Ok(()),
+                )
+                .optional()
+                .map(|found| found.is_some())
+                .map_err(sql_error)
+        })
+        .unwrap_or(false)
+    }
+
+    fn mark_rolled_up(&self, days: &[NaiveDate]) {
+        let marked = self.with_database(&LoadProgress::default(), |db| {
+            let tx = db.conn.transaction().map_err(sql_error)?;
+            for day in days {
+                insert_rolled_up(&tx, *day)?;
+            }
+            tx.commit().map_err(sql_error)
+        });
+        if let Err(err) = marked {
+            let _ = append_app_log(
+                &self.app_log_path,
+                &format!("failed to mark rolled-up days: {}", err),
+            );
+        }
+    }
+
+    fn load_input_analytics(&self) -> Result<StoredInputAnalytics, String> {
+        self.with_database(&LoadProgress::default(), |db| self.read_analytics(&db.conn))
+    }
+
+    fn save_input_analytics(&self, analytics: &StoredInputAnalytics) -> Result<(), String> {
+        self.with_database(&LoadProgress::default(), |db| {
+            let tx = db.conn.transaction().map_err(sql_error)?;
+            let next = write_analytics(&tx, analytics, &db.analytics_written)?;
+            tx.commit().map_err(sql_error)?;
+            db.analytics_written = next;
+            Ok(())
+        })
+    }
+
+    // Only the first load counts files, those of the JSON migration.
+    fn load_history(&self, progress: &LoadProgress) -> StoredHistory {
+        let loaded = self.with_database(progress, |db| {
+            Ok((Self::read_stats(&db.conn), self.read_analytics(&db.conn)))
+        });
+        match loaded {
+            Ok(history) => history,
+            Err(err) => (Err(err.clone()), Err(err)),
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Opt-in usage telemetry.
+//! While the user has consented, command invocations are counted locally and once a week the
+//! counts are posted together with the app version, OS and enabled feature flags. The report is
+//! built from closed structs, so typing data, app names and window titles have no field to end
+//! up in.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::app_config::{AppConfig, WindowTitleMode};
+use crate::storage::{load_telemetry_state, save_telemetry_state, StoredTelemetryState};
+
+/// Counter file next to the analytics files.
+pub(crate) const TELEMETRY_STATE_FILE: &str = "telemetry-state.json";
+/// Time between two reports.
+const TELEMETRY_SEND_INTERVAL_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+// A failed report is retried after this delay instead of on every tray tick.
+const TELEMETRY_RETRY_DELAY_MS: i64 = 60 * 60 * 1000;
+// Counters change on every command, so they are written back at most this often.
+const TELEMETRY_SAVE_INTERVAL_MS: i64 = 5 * 60 * 1000;
+const TELEMETRY_HTTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// On/off state of the features worth knowing about. Only switches, never their values, so
+/// goal apps, exclusions and title overrides stay on this machine.
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TelemetryFeatures {
+    pub ignore_key_combos: bool,
+    pub track_chord_aborts: bool,
+    pub prevent_app_nap: bool,
+    pub window_titles_hidden: bool,
+    pub title_privacy_overrides: bool,
+    pub app_time_goals: bool,
+    pub auto_export: bool,
+    pub shortcut_rules: bool,
+    pub start_hidden: bool,
+}
+
+impl TelemetryFeatures {
+    fn from_config(config: &AppConfig) -> Self {
+        Self {
+            ignore_key_combos: config.ignore_key_combos,
+            track_chord_aborts: config.track_chord_aborts,
+            prevent_app_nap: config.prevent_app_nap,
+            window_titles_hidden: config.window_title_mode == WindowTitleMode::Hidden,
+            title_privacy_overrides: !config.title_privacy_overrides.is_empty(),
+            app_time_goals: !config.app_time_goals.is_empty(),
+            auto_export: config.auto_export.enabled,
+            shortcut_rules: !config.shortcut_allowlist.is_empty()
+                || !config.shortcut_blocklist.is_empty(),
+            start_hidden: config.start_hidden,
+        }
+    }
+}
+
+/// The exact body of one report. Command names are the registered Tauri commands, counted only
+/// when the invoke handler dispatched them.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct TelemetryPayload {
+    pub app_version: String,
+    pub os: String,
+    pub command_counts: BTreeMap<String, u64>,
+    pub features: TelemetryFeatures,
+}
+
+/// What `get_telemetry_preview` returns: consent, schedule and the payload the next report
+/// would send.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct TelemetryPreview {
+    pub enabled: bool,
+    pub endpoint: String,
+    pub last_sent_ms: Option<i64>,
+    pub next_send_ms: Option<i64>,
+    pub payload: TelemetryPayload,
+}
+
+/// Consent mirror, counters and send schedule. Config owns the consent; this copy lets the
+/// invoke handler count without taking the config lock.
+pub(crate) struct Telemetry {
+    path: PathBuf,
+    enabled: bool,
+    command_counts: BTreeMap<String, u64>,
+    last_sent_ms: Option<i64>,
+    retry_at_ms: Option<i64>,
+    sending: bool,
+    dirty: bool,
+    last_save_ms: i64,
+}
+
+impl Telemetry {
+    /// Empty counters stored at `path`.
+    pub(crate) fn new(path: PathBuf, enabled: bool) -> Self {
+        Self {
+            path,
+            enabled,
+            command_counts: BTreeMap::new(),
+            last_sent_ms: None,
+            retry_at_ms: None,
+            sending: false,
+            dirty: false,
+            last_save_ms: 0,
+        }
+    }
+
+    /// Counters saved at `path`.
+    pub(crate) fn load(path: PathBuf, enabled: bool) -> Result<Self, String> {
+        let stored = load_telemetry_state(&path)?;
+        Ok(Self {
+            command_counts: stored.command_counts,
+            last_sent_ms: stored.last_sent_ms,
+            ..Self::new(path, enabled)
+        })
+    }
+
+    /// Write the counters back through a temp file.
+    pub(crate) fn save(&mut self, now_ms: i64) -> Result<(), String> {
+        save_telemetry_state(
+            &self.path,
+            &StoredTelemetryState {
+                command_counts: self.command_counts.clone(),
+                last_sent_ms: self.last_sent_ms,
+            },
+        )?;
+        self.dirty = false;
+        self.last_save_ms = now_ms;
+        Ok(())
+    }
+
+    /// Save changed counters once the save interval elapsed.
+    pub(crate) fn save_if_due(&mut self, now_ms: i64) -> Result<(), String> {
+        if !self.dirty || now_ms - self.last_save_ms < TELEMETRY_SAVE_INTERVAL_MS {
+            return Ok(());
+        }
+        self.save(now_ms)
+    }
+
+    /// Count one dispatched command; nothing is counted without consent.
+    pub(crate) fn record_command(&mut self, command: &str) {
+        if !self.enabled {
+            return;
+        }
+        *self.command_counts.entry(command.to_string()).or_default() += 1;
+        self.dirty = true;
+    }
+
+    /// Apply a consent change. Consenting starts the first reporting week; withdrawing drops
+    /// every counter so nothing collected earlier can be sent later.
+    pub(crate) fn set_consent(&mut self, enabled: bool, now_ms: i64) {
+        if enabled && !self.enabled {
+            self.last_sent_ms = Some(now_ms);
+        }
+        if !enabled {
+            self.command_counts.clear();
+            self.last_sent_ms = None;
+        }
+        self.enabled = enabled;
+        self.retry_at_ms = None;
+        self.dirty = true;
+    }
+
+    /// The report that would be sent now for `config`.
+    pub(crate) fn payload(&self, config: &AppConfig) -> TelemetryPayload {
+        TelemetryPayload {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            command_counts: self.command_counts.clone(),
+            features: TelemetryFeatures::from_config(config),
+        }
+    }
+
+    /// Consent, schedule and the next payload.
+    pub(crate) fn preview(&self, config: &AppConfig) -> TelemetryPreview {
+        TelemetryPreview {
+            enabled: self.enabled,
+            endpoint: config.telemetry_endpoint.clone(),
+            last_sent_ms: self.last_sent_ms,
+            next_send_ms: self.next_send_ms(),
+            payload: self.payload(config),
+        }
+    }
+
+    fn next_send_ms(&self) -> Option<i64> {
+        if !self.enabled {
+            return None;
+        }
+        let due = self.last_sent_ms? + TELEMETRY_SEND_INTERVAL_MS;
+        Some(self.retry_at_ms.map_or(due, |retry_at| retry_at.max(due)))
+    }
+
+    /// Take the report if a week passed since the last one and an endpoint is configured. The
+    /// caller posts it and hands the outcome to `finish_report`.
+    pub(crate) fn take_due_report(
+        &mut self,
+        config: &AppConfig,
+        now_ms: i64,
+    ) -> Option<TelemetryPayload> {
+        if self.sending || config.telemetry_endpoint.trim().is_empty() {
+            return None;
+        }
+        if now_ms < self.next_send_ms()? {
+            return None;
+        }
+        self.sending = true;
+        Some(self.payload(config))
+    }
+
+    /// Record the outcome of a report taken by `take_due_report`. Sent counts are subtracted,
+    /// so commands counted while the request was in flight go into the next report.
+    pub(crate) fn finish_report(
+        &mut self,
+        sent: &TelemetryPayload,
+        result: &Result<(), String>,
+        now_ms: i64,
+    ) {
+        self.sending = false;
+        match result {
+            Ok(()) => {
+                for (command, count) in &sent.command_counts {
+                    if let Some(current) = self.command_counts.get_mut(command) {
+                        *current = current.saturating_sub(*count);
+                    }
+                }
+                self.command_counts.retain(|_, count| *count > 0);
+                self.last_sent_ms = Some(now_ms);
+                self.retry_at_ms = None;
+                self.dirty = true;
+            }
+            Err(_) => self.retry_at_ms = Some(now_ms + TELEMETRY_RETRY_DELAY_MS),
+        }
+    }
+}
+
+/// Post one report as JSON to `endpoint`.
+pub(crate) fn post_report(endpoint: &str, payload: &TelemetryPayload) -> Result<(), String> {
+    let body = serde_json::to_string(payload).map_err(|e| e.to_string())?;
+    ureq::post(endpoint)
+        .set("Content-Type", "application/json")
+        .timeout(TELEMETRY_HTTP_TIMEOUT)
+        .send_string(&body)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Whether `endpoint` is an absolute http(s) URL.
+pub(crate) fn is_valid_endpoint(endpoint: &str) -> bool {
+    ["https://", "http://"].iter().any(|scheme| {
+        endpoint
+            .strip_prefix(scheme)
+            .is_some_and(|rest| !rest.is_empty() && !rest.contains(char::is_whitespace))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+
+    use super::{is_valid_endpoint, Telemetry, TelemetryFeatures, TELEMETRY_SEND_INTERVAL_MS};
+    use crate::app_config::{AppConfig, WindowTitleMode};
+
+    #[test]
+    fn telemetry_payload_carries_only_counts_version_os_and_feature_flags() {
+        let config = AppConfig {
+            telemetry_enabled: true,
+            telemetry_endpoint: "https://telemetry.example.com/v1".to_string(),
+            window_title_mode: WindowTitleMode::Hidden,
+            app_time_goals: HashMap::from([("com.secret.editor".to_string(), 30)]),
+            title_privacy_overrides: HashMap::from([(
+                "com.secret.browser".to_string(),
+                WindowTitleMode::Hidden,
+            )]),
+            excluded_bundle_ids: vec!["com.secret.vault".to_string()],
+            ..AppConfig::default()
+        };
+        let mut telemetry = Telemetry::new(PathBuf::from("telemetry-state.json"), false);
+        // Nothing is counted before consent.
+        telemetry.record_command("get_snapshot");
+        let now_ms = 1_700_000_000_000;
+        telemetry.set_consent(true, now_ms);
+        telemetry.record_command("get_snapshot");
+        telemetry.record_command("get_snapshot");
+        telemetry.record_command("export_stats_xlsx");
+
+        let payload = telemetry.payload(&config);
+        assert_eq!(payload.app_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(payload.os, std::env::consts::OS);
+        assert_eq!(
+            payload.command_counts,
+            BTreeMap::from([
+                ("export_stats_xlsx".to_string(), 1),
+                ("get_snapshot".to_string(), 2),
+            ])
+        );
+        assert_eq!(
+            payload.features,
+            TelemetryFeatures {
+                prevent_app_nap: true,
+                window_titles_hidden: true,
+                title_privacy_overrides: true,
+                app_time_goals: true,
+                ..TelemetryFeatures::default()
+            }
+        );
+        let rendered = format!("{payload:?}");
+        for secret in ["com.secret", "telemetry.example.com"] {
+            assert!(
+                !rendered.contains(secret),
+                "{secret} leaked into {rendered}"
+            );
+        }
+    }
+
+    #[test]
+    fn telemetry_reports_weekly_and_withdrawing_consent_drops_counts() {
+        let config = AppConfig {
+            telemetry_endpoint: "https://telemetry.example.com/v1".to_string(),
+            ..AppConfig::default()
+        };
+        let start_ms = 1_700_000_000_000;
+        let mut telemetry = Telemetry::new(PathBuf::from("telemetry-state.json"), false);
+        assert!(telemetry
+            .take_due_report(&config, start_ms + TELEMETRY_SEND_INTERVAL_MS)
+            .is_none());
+
+        telemetry.set_consent(true, start_ms);
+        telemetry.record_command("get_snapshot");
+        assert!(telemetry.take_due_report(&config, start_ms + 1).is_none());
+        let week_ms = start_ms + TELEMETRY_SEND_INTERVAL_MS;
+        let no_endpoint = AppConfig::default();
+        assert!(telemetry.take_due_report(&no_endpoint, week_ms).is_none());
+
+        let report = telemetry.take_due_report(&config, week_ms).unwrap();
+        // Only one report is in flight at a time.
+        assert!(telemetry.take_due_report(&config, week_ms).is_none());
+        telemetry.record_command("get_snapshot");
+        telemetry.finish_report(&report, &Err("offline".to_string()), week_ms);
+        assert!(telemetry.take_due_report(&config, week_ms + 1).is_none());
+
+        let retry_ms = telemetry.preview(&config).next_send_ms.unwrap();
+        let report = telemetry.take_due_report(&config, retry_ms).unwrap();
+        assert_eq!(report.command_counts["get_snapshot"], 2);
+        telemetry.record_command("get_snapshot");
+        telemetry.finish_report(&report, &Ok(()), retry_ms);
+        let preview = telemetry.preview(&config);
+        assert_eq!(preview.last_sent_ms, Some(retry_ms));
+        assert_eq!(
+            preview.next_send_ms,
+            Some(retry_ms + TELEMETRY_SEND_INTERVAL_MS)
+        );
+        assert_eq!(preview.payload.command_counts["get_snapshot"], 1);
+
+        telemetry.set_consent(false, retry_ms);
+        let preview = telemetry.preview(&config);
+        assert!(!preview.enabled);
+        assert!(preview.payload.command_counts.is_empty());
+        assert_eq!(preview.next_send_ms, None);
+
+        assert!(is_valid_endpoint("https://telemetry.example.com/v1"));
+        assert!(!is_valid_endpoint("https://"));
+        assert!(!is_valid_endpoint("ftp://telemetry.example.com"));
+    }
+}
@@ -0,0 +1,35 @@
+//! Test support module.
+//! Scratch directories for tests that read and write real files.
+
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Fresh directory under the system temp dir, removed with its contents when dropped, so a
+/// failing assertion leaves nothing behind either.
+pub(crate) struct TempDir(PathBuf);
+
+impl Deref for TempDir {
+    type Target = PathBuf;
+
+    fn deref(&self) -> &PathBuf {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Create `typepulse-<name>-<nanos>` under the system temp dir.
+pub(crate) fn temp_dir(name: &str) -> TempDir {
+    let stamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("typepulse-{name}-{stamp}"));
+    std::fs::create_dir_all(&path).unwrap();
+    TempDir(path)
+}
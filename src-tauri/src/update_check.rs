@@ -0,0 +1,241 @@
+//! Update check.
+//! Fetches the latest GitHub release and compares its tag with the embedded version. Network
+//! and parse failures degrade to an unknown result; they are logged and never surface as errors.
+
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Version this build was compiled as.
+pub(crate) const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Time between two automatic checks.
+const UPDATE_CHECK_INTERVAL_MS: i64 = 24 * 60 * 60 * 1000;
+// An offline start retries within the hour instead of waiting a full day.
+const UPDATE_CHECK_RETRY_MS: i64 = 60 * 60 * 1000;
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of one check. `update_available` is None when the latest release is unknown.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct UpdateInfo {
+    pub current: String,
+    pub latest: Option<String>,
+    pub update_available: Option<bool>,
+    pub release_notes_url: Option<String>,
+}
+
+impl UpdateInfo {
+    /// The result when the latest release could not be determined.
+    pub(crate) fn unknown() -> Self {
+        Self {
+            current: CURRENT_VERSION.to_string(),
+            latest: None,
+            update_available: None,
+            release_notes_url: None,
+        }
+    }
+
+    /// Compare the release tag `latest_tag` with `current`. A tag that is not a semantic
+    /// version leaves availability unknown.
+    pub(crate) fn compare(
+        current: &str,
+        latest_tag: &str,
+        release_notes_url: Option<String>,
+    ) -> Self {
+        let update_available = match (Version::parse(current), Version::parse(latest_tag)) {
+            (Some(current), Some(latest)) => Some(latest > current),
+            _ => None,
+        };
+        Self {
+            current: current.to_string(),
+            latest: Some(latest_tag.trim_start_matches(['v', 'V']).to_string()),
+            update_available,
+            release_notes_url,
+        }
+    }
+}
+
+/// The fields read from the GitHub "latest release" response.
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    html_url: Option<String>,
+}
+
+/// Fetch the latest release from `url` and compare it with this build.
+pub(crate) fn fetch_update_info(url: &str) -> Result<UpdateInfo, String> {
+    let body = ureq::get(url)
+        .set("Accept", "application/vnd.github+json")
+        .set(
+            "User-Agent",
+            concat!("TypePulse/", env!("CARGO_PKG_VERSION")),
+        )
+        .timeout(UPDATE_CHECK_TIMEOUT)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let release: GithubRelease = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+    Ok(UpdateInfo::compare(
+        CURRENT_VERSION,
+        &release.tag_name,
+        release.html_url,
+    ))
+}
+
+/// A semantic version `MAJOR.MINOR.PATCH[-PRERELEASE][+BUILD]`; a leading `v` is accepted.
+/// Build metadata is ignored when comparing.
+#[derive(Debug, Eq, PartialEq)]
+struct Version {
+    core: [u64; 3],
+    pre: Vec<String>,
+}
+
+impl Version {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_start_matches(['v', 'V']);
+        let text = text.split_once('+').map_or(text, |(version, _)| version);
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) if !pre.is_empty() => {
+                (core, pre.split('.').map(str::to_string).collect())
+            }
+            Some(_) => return None,
+            None => (text, Vec::new()),
+        };
+        let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+        let version = Self {
+            core: [parts.next()??, parts.next()??, parts.next()??],
+            pre,
+        };
+        parts.next().is_none().then_some(version)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.core.cmp(&other.core).then_with(|| {
+            // A pre-release sorts before the release it leads up to.
+            match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_pre_release(&self.pre, &other.pre),
+            }
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Numeric identifiers compare numerically and sort before alphanumeric ones.
+fn compare_pre_release(left: &[String], right: &[String]) -> Ordering {
+    for (a, b) in left.iter().zip(right) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    left.len().cmp(&right.len())
+}
+
+/// Schedule of the automatic daily check and its latest result.
+#[derive(Default)]
+pub(crate) struct UpdateCheck {
+    next_check_ms: i64,
+    checking: bool,
+    latest: Option<UpdateInfo>,
+}
+
+impl UpdateCheck {
+    /// Claim the automatic check when it is due; the caller runs it and reports `finish`.
+    pub(crate) fn begin_if_due(&mut self, now_ms: i64) -> bool {
+        if self.checking || now_ms < self.next_check_ms {
+            return false;
+        }
+        self.checking = true;
+        true
+    }
+
+    /// Store a check result and schedule the next automatic check.
+    pub(crate) fn finish(&mut self, info: UpdateInfo, now_ms: i64) {
+        self.checking = false;
+        self.next_check_ms = now_ms
+            + if info.update_available.is_some() {
+                UPDATE_CHECK_INTERVAL_MS
+            } else {
+                UPDATE_CHECK_RETRY_MS
+            };
+        self.latest = Some(info);
+    }
+
+    /// Whether the latest known release is newer than this build.
+    pub(crate) fn update_available(&self) -> bool {
+        self.latest
+            .as_ref()
+            .is_some_and(|info| info.update_available == Some(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        UpdateCheck, UpdateInfo, Version, UPDATE_CHECK_INTERVAL_MS, UPDATE_CHECK_RETRY_MS,
+    };
+
+    #[test]
+    fn release_tags_compare_as_semantic_versions() {
+        let parse = |text| Version::parse(text).unwrap();
+        assert!(parse("v0.10.0") > parse("0.9.9"));
+        assert!(parse("1.0.0") > parse("1.0.0-rc.1"));
+        assert!(parse("1.0.0-rc.10") > parse("1.0.0-rc.2"));
+        assert!(parse("1.0.0-rc.1") > parse("1.0.0-1"));
+        assert_eq!(parse("1.2.3+build.5"), parse("1.2.3"));
+        for invalid in ["1.2", "1.2.3.4", "latest", "1.2.x", "1.2.3-"] {
+            assert_eq!(Version::parse(invalid), None, "{invalid}");
+        }
+
+        let newer = UpdateInfo::compare(
+            "0.1.0",
+            "v0.2.0",
+            Some("https://github.com/Fullstop000/typepulse/releases/tag/v0.2.0".to_string()),
+        );
+        assert_eq!(newer.latest.as_deref(), Some("0.2.0"));
+        assert_eq!(newer.update_available, Some(true));
+        assert_eq!(
+            UpdateInfo::compare("0.2.0", "v0.2.0", None).update_available,
+            Some(false)
+        );
+        assert_eq!(
+            UpdateInfo::compare("0.2.0", "nightly", None).update_available,
+            None
+        );
+    }
+
+    #[test]
+    fn failed_update_checks_retry_sooner_and_stay_unknown() {
+        let now_ms = 1_700_000_000_000;
+        let mut check = UpdateCheck::default();
+        assert!(check.begin_if_due(now_ms));
+        assert!(!check.begin_if_due(now_ms));
+        check.finish(UpdateInfo::unknown(), now_ms);
+        assert!(!check.update_available());
+        assert!(!check.begin_if_due(now_ms + UPDATE_CHECK_RETRY_MS - 1));
+        assert!(check.begin_if_due(now_ms + UPDATE_CHECK_RETRY_MS));
+
+        let later_ms = now_ms + UPDATE_CHECK_RETRY_MS;
+        check.finish(UpdateInfo::compare("0.1.0", "v0.1.1", None), later_ms);
+        assert!(check.update_available());
+        assert!(!check.begin_if_due(later_ms + UPDATE_CHECK_RETRY_MS));
+        assert!(check.begin_if_due(later_ms + UPDATE_CHECK_INTERVAL_MS));
+    }
+}
@@ -0,0 +1,453 @@
+//! Command input validation.
+//! Typed values parsed from the strings the webview sends, at the command boundary, so range
+//! matching, config lists and file operations only ever see well-formed input. Export paths are
+//! confined to the data dir and the directories the user picked in a dialog this session.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::collector;
+
+// Longest id or path accepted from the frontend; anything longer is a bug or an attack.
+const MAX_INPUT_CHARS: usize = 1024;
+const MAX_ID_CHARS: usize = 255;
+
+// Echo of rejected input in errors, cut so a huge string cannot flood logs and the UI.
+fn preview(raw: &str) -> String {
+    const PREVIEW_CHARS: usize = 40;
+    let mut shown: String = raw.chars().take(PREVIEW_CHARS).collect();
+    if raw.chars().count() > PREVIEW_CHARS {
+        shown.push('…');
+    }
+    format!("{:?}", shown)
+}
+
+fn check_text(raw: &str, what: &str, max_chars: usize) -> Result<(), String> {
+    if raw.is_empty() {
+        return Err(format!("{} is empty", what));
+    }
+    if raw.chars().count() > max_chars {
+        return Err(format!("{} is longer than {} characters", what, max_chars));
+    }
+    if raw.chars().any(char::is_control) {
+        return Err(format!(
+            "{} contains control characters: {}",
+            what,
+            preview(raw)
+        ));
+    }
+    Ok(())
+}
+
+/// Stats range a command aggregates over: `today`, `yesterday` or `7d`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct RangeSpec(&'static str);
+
+impl RangeSpec {
+    const ALL: [&'static str; 3] = ["today", "yesterday", "7d"];
+
+    pub(crate) fn parse(raw: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|range| *range == raw)
+            .map(RangeSpec)
+            .ok_or_else(|| format!("unknown range: {}", preview(raw)))
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        self.0
+    }
+}
+
+/// App id as stats rows and config lists key apps: a bundle id, or the app name when the app
+/// has none. Trimmed; case is left to the caller.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct BundleId(String);
+
+impl BundleId {
+    pub(crate) fn parse(raw: &str) -> Result<Self, String> {
+        let trimmed = raw.trim();
+        check_text(trimmed, "bundle id", MAX_ID_CHARS)?;
+        Ok(Self(trimmed.to_string()))
+    }
+
+    /// The valid ids of a list, dropping the rest.
+    pub(crate) fn parse_list(raw: &[String]) -> Vec<Self> {
+        raw.iter().filter_map(|id| Self::parse(id).ok()).collect()
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Canonical shortcut id such as `shift_cmd_p`, parsed from any spelling the series lookup
+/// accepts (`Cmd+Shift+P`, `shift_cmd_p`, ...).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct ShortcutId(String);
+
+impl ShortcutId {
+    pub(crate) fn parse(raw: &str) -> Result<Self, String> {
+        check_text(raw.trim(), "shortcut id", MAX_ID_CHARS)?;
+        collector::canonical_shortcut_id(raw)
+            .map(Self)
+            .ok_or_else(|| format!("invalid shortcut id: {}", preview(raw)))
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Window title as stats rows key it. Kept verbatim: titles are compared exactly.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct WindowTitle(String);
+
+impl WindowTitle {
+    pub(crate) fn parse(raw: &str) -> Result<Self, String> {
+        check_text(raw, "window title", MAX_INPUT_CHARS)?;
+        Ok(Self(raw.to_string()))
+    }
+
+    pub(crate) fn into_string(self) -> String {
+        self.0
+    }
+}
+
+/// Relative path that stays below whatever directory it is joined to: no root, drive prefix
+/// or `..` component.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct SafeRelativePath(PathBuf);
+
+impl SafeRelativePath {
+    pub(crate) fn parse(raw: &str) -> Result<Self, String> {
+        check_text(raw, "path", MAX_INPUT_CHARS)?;
+        let mut path = PathBuf::new();
+        for component in Path::new(raw).components() {
+            match component {
+                Component::Normal(part) => path.push(part),
+                Component::CurDir => {}
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Err(format!("path escapes its directory: {}", preview(raw)));
+                }
+            }
+        }
+        if path.as_os_str().is_empty() {
+            return Err(format!("path names no file: {}", preview(raw)));
+        }
+        Ok(Self(path))
+    }
+
+    pub(crate) fn join_to(&self, base: &Path) -> PathBuf {
+        base.join(&self.0)
+    }
+}
+
+/// Directories export commands may write into.
+pub(crate) struct ExportRoots {
+    data_dir: PathBuf,
+    roots: Vec<PathBuf>,
+}
+
+impl ExportRoots {
+    /// The data dir and the directories picked in a dialog this session.
+    pub(crate) fn for_data_dir(data_dir: &Path, picked_dirs: &[PathBuf]) -> Self {
+        let mut roots = vec![data_dir.to_path_buf()];
+        roots.extend_from_slice(picked_dirs);
+        Self::new(data_dir, roots)
+    }
+
+    pub(crate) fn new(data_dir: &Path, roots: Vec<PathBuf>) -> Self {
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            roots: roots.iter().map(|root| resolve_existing(root)).collect(),
+        }
+    }
+
+    /// Where an export path from the frontend points: relative paths land in the data dir,
+    /// and either kind must lie inside a root once symlinks are resolved.
+    pub(crate) fn resolve(&self, raw: &str) -> Result<PathBuf, String> {
+        let raw = raw.trim();
+        check_text(raw, "path", MAX_INPUT_CHARS)?;
+        let path = Path::new(raw);
+        let path = if path.is_absolute() {
+            if path
+                .components()
+                .any(|component| component == Component::ParentDir)
+            {
+                return Err(format!("path escapes its directory: {}", preview(raw)));
+            }
+            path.to_path_buf()
+        } else {
+            SafeRelativePath::parse(raw)?.join_to(&self.data_dir)
+        };
+        let resolved = resolve_existing(&path);
+        if self.roots.iter().any(|root| resolved.starts_with(root)) {
+            Ok(resolved)
+        } else {
+            Err(format!(
+                "path is outside the data dir and the picked directories: {}",
+                preview(raw)
+            ))
+        }
+    }
+}
+
+/// Auto-export script path from the frontend: an existing regular file that was picked in a
+/// dialog this session or lies in the data dir. Returned with symlinks resolved.
+pub(crate) fn export_script_path(
+    raw: &str,
+    data_dir: &Path,
+    picked_files: &[PathBuf],
+) -> Result<PathBuf, String> {
+    let raw = raw.trim();
+    check_text(raw, "path", MAX_INPUT_CHARS)?;
+    let path = Path::new(raw);
+    if !path.is_absolute()
+        || path
+            .components()
+            .any(|component| component == Component::ParentDir)
+    {
+        return Err("auto export script must be an absolute path without ..".to_string());
+    }
+    let resolved = resolve_existing(path);
+    if !std::fs::metadata(&resolved).is_ok_and(|metadata| metadata.is_file()) {
+        return Err(format!(
+            "auto export script is not an existing file: {}",
+            preview(raw)
+        ));
+    }
+    let picked = picked_files
+        .iter()
+        .any(|file| resolve_existing(file) == resolved);
+    if picked || resolved.starts_with(resolve_existing(data_dir)) {
+        Ok(resolved)
+    } else {
+        Err(format!(
+            "auto export script was not picked in a dialog and is outside the data dir: {}",
+            preview(raw)
+        ))
+    }
+}
+
+// `path` with its longest existing ancestor canonicalized, so a symlink cannot point an export
+// outside the roots. Paths with no existing ancestor are returned as given.
+fn resolve_existing(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest: Vec<&std::ffi::OsStr> = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |joined, part| joined.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// App bundle path to read a bundle id from: absolute, no `..`, with an `.app` component.
+pub(crate) fn app_bundle_path(raw: &str) -> Result<PathBuf, String> {
+    let raw = raw.trim();
+    check_text(raw, "path", MAX_INPUT_CHARS)?;
+    let path = Path::new(raw);
+    let mut has_bundle = false;
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                return Err(format!("path escapes its directory: {}", preview(raw)));
+            }
+            Component::Normal(part) => {
+                has_bundle |= part.to_string_lossy().ends_with(".app");
+            }
+            _ => {}
+        }
+    }
+    if !path.is_absolute() || !has_bundle {
+        return Err(format!("not an app bundle path: {}", preview(raw)));
+    }
+    Ok(path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use proptest::prelude::*;
+
+    use super::{
+        app_bundle_path, export_script_path, BundleId, ExportRoots, RangeSpec, SafeRelativePath,
+        ShortcutId, WindowTitle,
+    };
+    use crate::test_support::{temp_dir, TempDir};
+
+    // Arbitrary strings, half of them built from separators, dots and odd characters so that
+    // path-shaped input is common.
+    fn raw_input() -> impl Strategy<Value = String> {
+        const PIECES: [&str; 16] = [
+            "/", "\\", "..", ".", "a", "Z", "_", "+", " ", "\0", "\n", "é", "🔑", "cmd", "today",
+            "C:",
+        ];
+        let path_like = prop::collection::vec(prop::sample::select(&PIECES[..]), 0..24)
+            .prop_map(|pieces| pieces.concat());
+        prop_oneof![any::<String>(), path_like]
+    }
+
+    // Data dir plus one more allowed dir under a fresh sandbox; on unix both hold a symlink
+    // to `/etc` (`data/x` and `picked/etc-link`).
+    struct ExportSandbox {
+        _dir: TempDir,
+        data_dir: PathBuf,
+        picked: PathBuf,
+        roots: ExportRoots,
+        allowed: [PathBuf; 2],
+    }
+
+    fn export_sandbox() -> ExportSandbox {
+        let dir = temp_dir("validation");
+        let data_dir = dir.join("data");
+        let picked = dir.join("picked");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::create_dir_all(&picked).unwrap();
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/etc", data_dir.join("x")).unwrap();
+            std::os::unix::fs::symlink("/etc", picked.join("etc-link")).unwrap();
+        }
+        let roots = ExportRoots::new(&data_dir, vec![data_dir.clone(), picked.clone()]);
+        let allowed = [
+            data_dir.canonicalize().unwrap(),
+            picked.canonicalize().unwrap(),
+        ];
+        ExportSandbox {
+            _dir: dir,
+            data_dir,
+            picked,
+            roots,
+            allowed,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn parsers_never_panic_on_arbitrary_strings(raw in raw_input()) {
+            let _ = RangeSpec::parse(&raw);
+            if let Ok(title) = WindowTitle::parse(&raw) {
+                prop_assert_eq!(title.into_string(), raw.clone());
+            }
+            let _ = ShortcutId::parse(&raw);
+            let _ = app_bundle_path(&raw);
+            if let Ok(id) = BundleId::parse(&raw) {
+                prop_assert!(!id.as_str().is_empty());
+                prop_assert!(!id.as_str().chars().any(char::is_control));
+            }
+            if let Ok(path) = SafeRelativePath::parse(&raw) {
+                prop_assert!(path.join_to(Path::new("/base")).starts_with("/base"));
+            }
+        }
+
+        #[test]
+        fn export_paths_never_escape_the_allowed_roots(raw in raw_input()) {
+            let sandbox = export_sandbox();
+            let picked = sandbox.picked.display();
+            for candidate in [
+                raw.clone(),
+                format!("{picked}/{raw}"),
+                format!("{picked}/etc-link/{raw}"),
+            ] {
+                if let Ok(path) = sandbox.roots.resolve(&candidate) {
+                    prop_assert!(
+                        sandbox.allowed.iter().any(|root| path.starts_with(root)),
+                        "{:?} escaped to {:?}",
+                        candidate,
+                        path
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parsers_accept_the_documented_forms() {
+        assert_eq!(RangeSpec::parse("7d").unwrap().as_str(), "7d");
+        assert!(RangeSpec::parse("../7d").is_err());
+        assert_eq!(
+            ShortcutId::parse("Cmd+Shift+P").unwrap().as_str(),
+            "shift_cmd_p"
+        );
+        assert!(BundleId::parse(&"a".repeat(256)).is_err());
+    }
+
+    #[test]
+    fn export_paths_resolve_inside_the_roots_only() {
+        let sandbox = export_sandbox();
+        let (roots, picked) = (&sandbox.roots, &sandbox.picked);
+        assert_eq!(
+            roots.resolve("stats.xlsx").unwrap(),
+            sandbox.allowed[0].join("stats.xlsx")
+        );
+        assert_eq!(
+            roots
+                .resolve(&picked.join("exports/stats.xlsx").to_string_lossy())
+                .unwrap(),
+            sandbox.allowed[1].join("exports/stats.xlsx")
+        );
+        assert!(roots.resolve("../stats.xlsx").is_err());
+        assert!(roots.resolve("/etc/stats.xlsx").is_err());
+        assert!(roots
+            .resolve(&format!("{}/../stats.xlsx", picked.display()))
+            .is_err());
+        assert!(roots
+            .resolve(&sandbox.data_dir.join("../stats.xlsx").to_string_lossy())
+            .is_err());
+        #[cfg(unix)]
+        {
+            assert!(roots
+                .resolve(&picked.join("etc-link/stats.xlsx").to_string_lossy())
+                .is_err());
+            // Relative paths are joined to the data dir first, then checked the same way.
+            assert!(roots.resolve("x/stats.xlsx").is_err());
+        }
+    }
+
+    #[test]
+    fn export_scripts_must_be_picked_files_or_live_in_the_data_dir() {
+        let sandbox = export_sandbox();
+        let in_data_dir = sandbox.data_dir.join("upload.sh");
+        let picked = sandbox.picked.join("picked.sh");
+        let unpicked = sandbox.picked.join("unpicked.sh");
+        for script in [&in_data_dir, &picked, &unpicked] {
+            std::fs::write(script, "#!/bin/sh\n").unwrap();
+        }
+        let picked_files = vec![picked.clone()];
+        let check = |path: &Path| {
+            export_script_path(&path.to_string_lossy(), &sandbox.data_dir, &picked_files)
+        };
+
+        assert_eq!(
+            check(&in_data_dir).unwrap(),
+            sandbox.allowed[0].join("upload.sh")
+        );
+        assert_eq!(
+            check(&picked).unwrap(),
+            sandbox.allowed[1].join("picked.sh")
+        );
+        assert!(check(&unpicked).is_err());
+        assert!(check(&sandbox.data_dir).is_err());
+        assert!(check(&sandbox.data_dir.join("missing.sh")).is_err());
+        assert!(check(&sandbox.data_dir.join("../picked/unpicked.sh")).is_err());
+        assert!(export_script_path("upload.sh", &sandbox.data_dir, &picked_files).is_err());
+        #[cfg(unix)]
+        {
+            // A link in the data dir to a file elsewhere counts as the file it points to.
+            let link = sandbox.data_dir.join("link.sh");
+            std::os::unix::fs::symlink(&unpicked, &link).unwrap();
+            assert!(check(&link).is_err());
+        }
+    }
+}
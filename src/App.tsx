@@ -6,19 +6,51 @@ import PageHeader from "./components/layout/PageHeader";
 import SettingsPage from "./components/settings/page/SettingsPage";
 import Sidebar from "./components/layout/Sidebar";
 import StatsPage from "./components/stats/StatsPage";
+import WeeklyPage from "./components/stats/WeeklyPage";
 import TrayPopover from "./components/tray/TrayPopover";
 import {
+  AppSwitchStats,
+  AppTimeline,
+  BurstStats,
+  ChordAbortStats,
+  Comparison,
+  Coverage,
+  DailyTotal,
   FilterRange,
+  FocusDayScore,
   GroupedRow,
+  KeyboardLayout,
+  KeyHeatmap,
+  KeyHoldStats,
   KeyUsageRow,
+  ShortcutBreadth,
+  ShortcutRangeStats,
   ShortcutStatRow,
   Snapshot,
+  StatsRow,
+  TopKeysRangeStats,
   Totals,
   TrendGranularity,
 } from "./types";
-import { buildTrendSeries, parseRowDate } from "./utils/stats";
+import { listenAppGoalMet } from "./utils/appGoals";
+import { applyCaptureState, listenCaptureStateChanged } from "./utils/captureState";
+import { buildTrendSeries } from "./utils/stats";
+import { listenStorageDegraded } from "./utils/storageHealth";
+import { listenWeeklyComparison } from "./utils/weeklyComparison";
 import { glassSurfaceStyle } from "./styles/glass";
 
+// App timeline strips use a fixed bucket size; 7d falls back to today.
+const APP_TIMELINE_BUCKET_MINUTES = 15;
+// Shortcut rows list this many apps; the rest is folded into `other_count`.
+const SHORTCUT_APP_LIMIT = 5;
+
+// Format a local calendar date as `YYYY-MM-DD` for date-keyed commands.
+function localDateKey(date: Date): string {
+  const month = `${date.getMonth() + 1}`.padStart(2, "0");
+  const day = `${date.getDate()}`.padStart(2, "0");
+  return `${date.getFullYear()}-${month}-${day}`;
+}
+
 // Keep trend granularity options aligned with the overview date filter.
 const trendGranularityOptionsByRange: Record<FilterRange, TrendGranularity[]> = {
   today: ["1m", "5m", "1h"],
@@ -28,7 +60,7 @@ const trendGranularityOptionsByRange: Record<FilterRange, TrendGranularity[]> =
 
 function DesktopApp() {
   const [snapshot, setSnapshot] = useState<Snapshot | null>(null);
-  const [activeTab, setActiveTab] = useState<"stats" | "logs" | "settings">(
+  const [activeTab, setActiveTab] = useState<"stats" | "weekly" | "logs" | "settings">(
     "stats",
   );
   const [typingLogText, setTypingLogText] = useState("");
@@ -40,29 +72,132 @@ function DesktopApp() {
     ShortcutStatRow[]
   >([]);
   const [topKeysRows, setTopKeysRows] = useState<KeyUsageRow[]>([]);
+  const [rangeDegraded, setRangeDegraded] = useState(false);
+  const [chordAborts, setChordAborts] = useState<ChordAbortStats | null>(null);
+  const [focusScores, setFocusScores] = useState<FocusDayScore[]>([]);
+  const [appSwitches, setAppSwitches] = useState<AppSwitchStats | null>(null);
+  const [shortcutBreadth, setShortcutBreadth] = useState<ShortcutBreadth | null>(null);
+  const [coverage, setCoverage] = useState<Coverage | null>(null);
+  const [burstStats, setBurstStats] = useState<BurstStats | null>(null);
+  const [keyHolds, setKeyHolds] = useState<KeyHoldStats | null>(null);
+  const [appTimeline, setAppTimeline] = useState<AppTimeline | null>(null);
+  const [dayComparison, setDayComparison] = useState<Comparison | null>(null);
+  const [keyboardLayout, setKeyboardLayout] = useState<KeyboardLayout>("ansi");
+  const [keyHeatmap, setKeyHeatmap] = useState<KeyHeatmap | null>(null);
+  const [rangeRows, setRangeRows] = useState<StatsRow[]>([]);
+  const [dailyTotals, setDailyTotals] = useState<DailyTotal[]>([]);
 
   useEffect(() => {
     let mounted = true;
     const fetchSnapshot = async () => {
       try {
-        const [data, shortcutRows, topRows] = await Promise.all([
-          invoke<Snapshot>("get_snapshot"),
-          invoke<ShortcutStatRow[]>("get_shortcut_stats_by_range", {
-            range: filterRange,
-          }),
-          invoke<KeyUsageRow[]>("get_daily_top_keys_by_range", {
-            range: filterRange,
-          }),
-        ]);
+        const timelineDate = new Date();
+        if (filterRange === "yesterday") {
+          timelineDate.setDate(timelineDate.getDate() - 1);
+        }
+        const yesterday = new Date();
+        yesterday.setDate(yesterday.getDate() - 1);
+        const [
+          data,
+          shortcutRows,
+          topRows,
+          timeline,
+          comparison,
+          heatmap,
+          chordStats,
+          focusRows,
+          switchStats,
+          coverageStats,
+          breadth,
+          bursts,
+          holds,
+          statsRows,
+          dayTotals,
+        ] = await Promise.all([
+            invoke<Snapshot>("get_snapshot"),
+            invoke<ShortcutRangeStats>("get_shortcut_stats_by_range", {
+              range: filterRange,
+              appLimit: SHORTCUT_APP_LIMIT,
+            }),
+            invoke<TopKeysRangeStats>("get_daily_top_keys_by_range", {
+              range: filterRange,
+            }),
+            invoke<AppTimeline>("get_app_timeline", {
+              date: localDateKey(timelineDate),
+              bucketMinutes: APP_TIMELINE_BUCKET_MINUTES,
+            }),
+            invoke<Comparison>("get_comparison", {
+              subjectA: localDateKey(yesterday),
+              subjectB: localDateKey(new Date()),
+              kind: "date",
+            }),
+            invoke<KeyHeatmap>("get_key_heatmap", {
+              range: filterRange,
+              layout: keyboardLayout,
+            }),
+            invoke<ChordAbortStats>("get_chord_abort_stats", {
+              range: filterRange,
+            }),
+            invoke<FocusDayScore[]>("get_focus_scores", {
+              range: filterRange,
+            }),
+            invoke<AppSwitchStats>("get_app_switch_stats", {
+              range: filterRange,
+            }),
+            invoke<Coverage>("get_coverage", {
+              range: filterRange,
+            }),
+            invoke<ShortcutBreadth>("get_shortcut_breadth", {
+              range: filterRange,
+            }),
+            invoke<BurstStats>("get_burst_stats", {
+              range: filterRange,
+            }),
+            invoke<KeyHoldStats>("get_key_hold_stats", {
+              range: filterRange,
+            }),
+            invoke<StatsRow[]>("get_stats_rows", {
+              range: filterRange,
+            }),
+            invoke<DailyTotal[]>("get_daily_totals", {}),
+          ]);
         if (mounted) {
           setSnapshot(data);
-          setFilteredShortcutStats(shortcutRows);
-          setTopKeysRows(topRows);
+          setFilteredShortcutStats(shortcutRows.rows);
+          setTopKeysRows(topRows.rows);
+          setRangeDegraded(
+            shortcutRows.degraded_range_support || topRows.degraded_range_support,
+          );
+          setAppTimeline(timeline);
+          setDayComparison(comparison);
+          setKeyHeatmap(heatmap);
+          setChordAborts(chordStats);
+          setFocusScores(focusRows);
+          setAppSwitches(switchStats);
+          setCoverage(coverageStats);
+          setShortcutBreadth(breadth);
+          setBurstStats(bursts);
+          setKeyHolds(holds);
+          setRangeRows(statsRows);
+          setDailyTotals(dayTotals);
         }
       } catch (error) {
         if (mounted) {
           setFilteredShortcutStats([]);
           setTopKeysRows([]);
+          setRangeDegraded(false);
+          setAppTimeline(null);
+          setDayComparison(null);
+          setKeyHeatmap(null);
+          setChordAborts(null);
+          setFocusScores([]);
+          setAppSwitches(null);
+          setCoverage(null);
+          setShortcutBreadth(null);
+          setBurstStats(null);
+          setKeyHolds(null);
+          setRangeRows([]);
+          setDailyTotals([]);
         }
         console.error("failed to refresh snapshot", error);
       }
@@ -73,7 +208,37 @@ function DesktopApp() {
       mounted = false;
       clearInterval(id);
     };
-  }, [filterRange]);
+  }, [filterRange, keyboardLayout]);
+
+  useEffect(() => {
+    const unlisten = listenCaptureStateChanged((state) =>
+      setSnapshot((current) => applyCaptureState(current, state)),
+    );
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listenAppGoalMet();
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listenStorageDegraded();
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listenWeeklyComparison(() => setActiveTab("weekly"));
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
 
   useEffect(() => {
     // Force selected granularity to remain valid after overview range changes.
@@ -110,43 +275,38 @@ function DesktopApp() {
   }, [activeTab]);
 
   const pageTitle =
-    activeTab === "stats" ? "数据" : activeTab === "logs" ? "日志" : "设置";
-
-  const filteredRows = useMemo(() => {
-    const rows = snapshot?.rows ?? [];
-    const todayStart = new Date();
-    todayStart.setHours(0, 0, 0, 0);
-    const tomorrowStart = new Date(todayStart);
-    tomorrowStart.setDate(tomorrowStart.getDate() + 1);
-    const yesterdayStart = new Date(todayStart);
-    yesterdayStart.setDate(yesterdayStart.getDate() - 1);
-    const sevenDaysStart = new Date(todayStart);
-    sevenDaysStart.setDate(sevenDaysStart.getDate() - 6);
+    activeTab === "stats"
+      ? "数据"
+      : activeTab === "weekly"
+        ? "周报"
+        : activeTab === "logs"
+          ? "日志"
+          : "设置";
 
-    return rows.filter((row) => {
-      const date = parseRowDate(row.date);
-      if (filterRange === "today") {
-        return date >= todayStart && date < tomorrowStart;
-      }
-      if (filterRange === "yesterday") {
-        return date >= yesterdayStart && date < todayStart;
-      }
-      return date >= sevenDaysStart && date < tomorrowStart;
-    });
-  }, [snapshot, filterRange]);
+  // The snapshot only carries today's rows; the selected range comes from get_stats_rows.
+  const filteredRows = rangeRows;
 
   const groupedRows = useMemo(() => {
     const grouped = new Map<string, GroupedRow>();
+    // Distinct (day, title) pairs per app; hidden titles are empty and not counted.
+    const windows = new Map<string, Set<string>>();
     for (const row of filteredRows) {
       const entry = grouped.get(row.app_name) || {
         app_name: row.app_name,
         active_typing_ms: 0,
         key_count: 0,
         session_count: 0,
+        distinct_windows: 0,
       };
       entry.active_typing_ms += row.active_typing_ms;
       entry.key_count += row.key_count;
       entry.session_count += row.session_count;
+      if (row.window_title) {
+        const seen = windows.get(row.app_name) ?? new Set<string>();
+        seen.add(`${row.date.slice(0, 10)}\n${row.window_title}`);
+        windows.set(row.app_name, seen);
+        entry.distinct_windows = seen.size;
+      }
       grouped.set(row.app_name, entry);
     }
     return Array.from(grouped.values()).sort(
@@ -272,15 +432,34 @@ function DesktopApp() {
             <StatsPage
               filterRange={filterRange}
               onFilterChange={setFilterRange}
-              allRows={snapshot.rows}
+              dailyTotals={dailyTotals}
               totals={totals}
+              todayFocusScore={snapshot.today_focus_score}
+              currentContext={snapshot.current_context}
+              dayFirstKeyMs={snapshot.day_first_key_ms}
+              dayLastKeyMs={snapshot.day_last_key_ms}
+              focusScores={focusScores}
               groupedRows={groupedRows}
               trendSeries={trendSeries}
               trendGranularity={trendGranularity}
               onTrendGranularityChange={setTrendGranularity}
               shortcutRows={filteredShortcutStats}
+              chordAborts={chordAborts}
               topKeysRows={topKeysRows}
+              rangeDegraded={rangeDegraded}
+              appTimeline={appTimeline}
+              appSwitches={appSwitches}
+              shortcutBreadth={shortcutBreadth}
+              coverage={coverage}
+              burstStats={burstStats}
+              keyHolds={keyHolds}
+              dayComparison={dayComparison}
+              keyHeatmap={keyHeatmap}
+              keyboardLayout={keyboardLayout}
+              onKeyboardLayoutChange={setKeyboardLayout}
             />
+          ) : activeTab === "weekly" ? (
+            <WeeklyPage />
           ) : activeTab === "logs" ? (
             <LogsPage
               typingLogText={typingLogText}
@@ -2,8 +2,8 @@ import { Box, Button, HStack, Stack, Switch, Text } from "@chakra-ui/react";
 import { ReactNode } from "react";
 
 type SidebarProps = {
-  activeTab: "stats" | "logs" | "settings";
-  onChange: (tab: "stats" | "logs" | "settings") => void;
+  activeTab: "stats" | "weekly" | "logs" | "settings";
+  onChange: (tab: "stats" | "weekly" | "logs" | "settings") => void;
   isCollecting: boolean;
   onTogglePause: () => void;
 };
@@ -21,6 +21,17 @@ function ChartIcon({ color }: IconProps) {
   );
 }
 
+function CalendarIcon({ color }: IconProps) {
+  return (
+    <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke={color} strokeWidth="1.8">
+      <path d="M4 6h16v14H4z" />
+      <path d="M4 10h16" />
+      <path d="M8 3v5" />
+      <path d="M16 3v5" />
+    </svg>
+  );
+}
+
 function LogIcon({ color }: IconProps) {
   return (
     <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke={color} strokeWidth="1.8">
@@ -102,6 +113,7 @@ function Sidebar({ activeTab, onChange, isCollecting, onTogglePause }: SidebarPr
       </Box>
       <Stack gap="1">
         <NavButton active={activeTab === "stats"} label="数据" icon={<ChartIcon color={activeTab === "stats" ? "#1f2328" : "#6b7280"} />} onClick={() => onChange("stats")} />
+        <NavButton active={activeTab === "weekly"} label="周报" icon={<CalendarIcon color={activeTab === "weekly" ? "#1f2328" : "#6b7280"} />} onClick={() => onChange("weekly")} />
         <NavButton active={activeTab === "logs"} label="日志" icon={<LogIcon color={activeTab === "logs" ? "#1f2328" : "#6b7280"} />} onClick={() => onChange("logs")} />
         <NavButton active={activeTab === "settings"} label="设置" icon={<SettingsIcon color={activeTab === "settings" ? "#1f2328" : "#6b7280"} />} onClick={() => onChange("settings")} />
       </Stack>
@@ -1,18 +1,28 @@
 import { openUrl } from "@tauri-apps/plugin-opener";
-import { Badge, Box, Button, HStack, Stack, Switch, Text } from "@chakra-ui/react";
+import { Badge, Box, Button, ButtonGroup, HStack, Stack, Switch, Text } from "@chakra-ui/react";
 import { useMemo, useState } from "react";
-import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+import { SymbolKeyNames } from "../../types";
+import { glassPillStyle, glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
 import { useSettingsContext } from "./SettingsContext";
 
 function CaptureSettingsSection() {
   const {
     snapshot,
+    config,
     toggleIgnoreKeyCombos,
+    toggleTrackChordAborts,
+    toggleShortcutsPaused,
+    updateSymbolKeyNames,
+    togglePreventAppNap,
+    toggleRecordEventChunks,
+    toggleAxSensitiveDetection,
     addAppExclusion,
     removeAppExclusion,
     loadRunningApps,
     dismissOnePasswordSuggestion,
     acceptOnePasswordSuggestion,
+    acceptNonTypingSuggestion,
+    dismissNonTypingSuggestion,
   } = useSettingsContext();
 
   const [runningAppsOpen, setRunningAppsOpen] = useState(false);
@@ -21,9 +31,21 @@ function CaptureSettingsSection() {
 
   const hasPermission = snapshot.keyboard_active;
 
+  const symbolNamesButton = (names: SymbolKeyNames, label: string) => (
+    <Button
+      variant="ghost"
+      borderRadius="999px"
+      bg={config.symbol_key_names === names ? "rgba(255,255,255,0.84)" : "transparent"}
+      boxShadow={config.symbol_key_names === names ? "sm" : "none"}
+      onClick={() => void updateSymbolKeyNames(names)}
+    >
+      {label}
+    </Button>
+  );
+
   const excludedSet = useMemo(
-    () => new Set(snapshot.excluded_bundle_ids.map((item) => item.toLowerCase())),
-    [snapshot.excluded_bundle_ids],
+    () => new Set(config.excluded_bundle_ids.map((item) => item.toLowerCase())),
+    [config.excluded_bundle_ids],
   );
 
   const handleOpenPermission = async () => {
@@ -54,7 +76,67 @@ function CaptureSettingsSection() {
               <Text fontWeight="medium" color="#111827">忽略组合键</Text>
               <Text fontSize="sm" color="#6b7280">开启后不记录 Ctrl/Alt/Fn/Shift/Cmd + 任意键。</Text>
             </Box>
-            <Switch.Root checked={snapshot.ignore_key_combos} onCheckedChange={toggleIgnoreKeyCombos}>
+            <Switch.Root checked={config.ignore_key_combos} onCheckedChange={toggleIgnoreKeyCombos}>
+              <Switch.HiddenInput />
+              <Switch.Control />
+            </Switch.Root>
+          </HStack>
+
+          <HStack justify="space-between" align="center" flexWrap="wrap" gap="3" px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
+            <Box>
+              <Text fontWeight="medium" color="#111827">统计未完成组合键</Text>
+              <Text fontSize="sm" color="#6b7280">记录按下 Cmd/Ctrl/Opt 后未按其他键就松开的次数。macOS 需重启应用后生效。</Text>
+            </Box>
+            <Switch.Root checked={config.track_chord_aborts} onCheckedChange={toggleTrackChordAborts}>
+              <Switch.HiddenInput />
+              <Switch.Control />
+            </Switch.Root>
+          </HStack>
+
+          <HStack justify="space-between" align="center" flexWrap="wrap" gap="3" px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
+            <Box>
+              <Text fontWeight="medium" color="#111827">暂停快捷键统计</Text>
+              <Text fontSize="sm" color="#6b7280">不记录快捷键次数与快捷键按键事件，打字时长和按键数照常统计。适合结对编程等场景。</Text>
+            </Box>
+            <Switch.Root checked={snapshot.shortcuts_paused} onCheckedChange={toggleShortcutsPaused}>
+              <Switch.HiddenInput />
+              <Switch.Control />
+            </Switch.Root>
+          </HStack>
+
+          <HStack justify="space-between" align="center" flexWrap="wrap" gap="3" px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
+            <Box maxW="520px">
+              <Text fontWeight="medium" color="#111827">符号键命名</Text>
+              <Text fontSize="sm" color="#6b7280">Shift+1 与 ! 合并为同一个键统计：按按键记为 1，按字符记为 !。切换后已有的快捷键统计会一并改名。</Text>
+            </Box>
+            <ButtonGroup size="sm" gap="1" {...glassPillStyle} borderRadius="999px" p="1">
+              {symbolNamesButton("physical", "按键")}
+              {symbolNamesButton("produced", "字符")}
+            </ButtonGroup>
+          </HStack>
+
+          <HStack justify="space-between" align="center" flexWrap="wrap" gap="3" px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
+            <Box>
+              <HStack gap="2">
+                <Text fontWeight="medium" color="#111827">阻止 App Nap</Text>
+                {snapshot.app_nap_assertion_held ? (
+                  <Badge bg="#dff2e2" color="#166534">生效中</Badge>
+                ) : null}
+              </HStack>
+              <Text fontSize="sm" color="#6b7280">主面板长时间隐藏时保持计时准确；暂停或 5 分钟无输入时自动放开。关闭可更省电。</Text>
+            </Box>
+            <Switch.Root checked={config.prevent_app_nap} onCheckedChange={togglePreventAppNap}>
+              <Switch.HiddenInput />
+              <Switch.Control />
+            </Switch.Root>
+          </HStack>
+
+          <HStack justify="space-between" align="center" flexWrap="wrap" gap="3" px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
+            <Box>
+              <Text fontWeight="medium" color="#111827">记录按键序列</Text>
+              <Text fontSize="sm" color="#6b7280">保存逐键的输入顺序，用于按天查看快捷键和热门按键。关闭后只留快捷键累计次数，已存的序列会从磁盘移除，也更省空间。</Text>
+            </Box>
+            <Switch.Root checked={config.record_event_chunks} onCheckedChange={toggleRecordEventChunks}>
               <Switch.HiddenInput />
               <Switch.Control />
             </Switch.Root>
@@ -68,6 +150,17 @@ function CaptureSettingsSection() {
             <Badge bg="#dff2e2" color="#166534">已启用</Badge>
           </HStack>
 
+          <HStack justify="space-between" align="center" flexWrap="wrap" gap="3" px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
+            <Box maxW="520px">
+              <Text fontWeight="medium" color="#111827">识别密码类输入框</Text>
+              <Text fontSize="sm" color="#6b7280">通过辅助功能读取当前输入框，安全文本框或标题含“密码”“token”等关键词时不记录按键序列和快捷键，按键数与时长照常统计。仅 macOS，需辅助功能权限。</Text>
+            </Box>
+            <Switch.Root checked={config.ax_sensitive_detection} onCheckedChange={toggleAxSensitiveDetection}>
+              <Switch.HiddenInput />
+              <Switch.Control />
+            </Switch.Root>
+          </HStack>
+
           <HStack justify="space-between" align="start" flexWrap="wrap" gap="3" px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
             <Box>
               <Text fontWeight="medium" color="#111827">系统采集授权</Text>
@@ -137,15 +230,47 @@ function CaptureSettingsSection() {
           </Box>
         ) : null}
 
+        {snapshot.non_typing_suggestions.map((suggestion) => (
+          <Box key={suggestion.bundle_id} m="5" {...glassSubtleStyle} borderRadius="10px" p="4">
+            <Text mb="3">
+              {suggestion.bundle_id} 最近 {suggestion.days} 天的按键以 WASD/方向键为主，更像游戏或导航操作，是否加入忽略列表？
+            </Text>
+            <HStack>
+              <Button
+                size="sm"
+                variant="ghost"
+                borderWidth="1px"
+                borderColor="glass.borderSoft"
+                bg="rgba(255,255,255,0.62)"
+                _hover={{ bg: "rgba(255,255,255,0.8)" }}
+                onClick={() => acceptNonTypingSuggestion(suggestion.bundle_id)}
+              >
+                加入忽略列表
+              </Button>
+              <Button
+                size="sm"
+                variant="ghost"
+                borderWidth="1px"
+                borderColor="glass.borderSoft"
+                bg="rgba(255,255,255,0.5)"
+                _hover={{ bg: "rgba(255,255,255,0.7)" }}
+                onClick={() => dismissNonTypingSuggestion(suggestion.bundle_id)}
+              >
+                继续统计
+              </Button>
+            </HStack>
+          </Box>
+        ))}
+
         <Box m="5" {...glassSubtleStyle} borderRadius="12px" overflow="hidden">
           <HStack px="4" py="3" bg="rgba(255,255,255,0.46)" fontWeight="semibold" fontSize="sm" justify="space-between">
             <Text flex="1">Bundle ID</Text>
             <Text flex="0 0 auto">操作</Text>
           </HStack>
-          {snapshot.excluded_bundle_ids.length === 0 ? (
+          {config.excluded_bundle_ids.length === 0 ? (
             <Text px="4" py="6" color="#8b939f" textAlign="center">暂无忽略应用</Text>
           ) : (
-            snapshot.excluded_bundle_ids.map((bundleId) => (
+            config.excluded_bundle_ids.map((bundleId) => (
               <HStack key={bundleId} px="4" py="3" borderTopWidth="1px" borderColor="glass.borderSoft" justify="space-between" gap="3">
                 <Text fontFamily="mono" fontSize="sm" truncate title={bundleId}>
                   {bundleId}
@@ -1,11 +1,43 @@
-import { Box, Button, ButtonGroup, HStack, Text } from "@chakra-ui/react";
-import { MenuBarDisplayMode } from "../../types";
+import { Box, Button, ButtonGroup, HStack, Switch, Text } from "@chakra-ui/react";
+import { useState } from "react";
+import { openUrl } from "@tauri-apps/plugin-opener";
+import { MenuBarDisplayMode, TrayLeftClickAction, UpdateInfo } from "../../types";
 import { glassPillStyle, glassSurfaceStyle } from "../../styles/glass";
 import { useSettingsContext } from "./SettingsContext";
 
 function DisplaySettingsSection() {
-  const { snapshot, updateTrayDisplayMode } = useSettingsContext();
+  const {
+    config,
+    updateTrayDisplayMode,
+    updateTrayLeftClickAction,
+    toggleStartHidden,
+    toggleAutoUpdateCheck,
+    checkForUpdates,
+    resetWindowPosition,
+  } = useSettingsContext();
+  const [updateInfo, setUpdateInfo] = useState<UpdateInfo | null>(null);
+  const [checkingUpdates, setCheckingUpdates] = useState(false);
   const handleModeChange = (mode: MenuBarDisplayMode) => updateTrayDisplayMode(mode);
+  const handleClickActionChange = (action: TrayLeftClickAction) =>
+    updateTrayLeftClickAction(action);
+
+  const handleCheckForUpdates = async () => {
+    setCheckingUpdates(true);
+    try {
+      setUpdateInfo(await checkForUpdates());
+    } finally {
+      setCheckingUpdates(false);
+    }
+  };
+
+  const updateSummary = (info: UpdateInfo) => {
+    if (info.update_available === null) {
+      return `当前版本 ${info.current}，暂时无法获取最新版本`;
+    }
+    return info.update_available
+      ? `发现新版本 ${info.latest}（当前 ${info.current}）`
+      : `已是最新版本 ${info.current}`;
+  };
 
   return (
     <Box {...glassSurfaceStyle} borderRadius="12px" p="0" overflow="hidden">
@@ -15,14 +47,14 @@ function DisplaySettingsSection() {
       <HStack justify="space-between" align="center" gap="4" px="5" py="4" flexWrap="wrap">
         <Box maxW="520px">
           <Text fontWeight="medium" color="#111827" mb="1">菜单栏显示模式</Text>
-          <Text fontSize="sm" color="#6b7280">控制菜单栏小组件展示为图标、数字或图标+数字。</Text>
+          <Text fontSize="sm" color="#6b7280">控制菜单栏小组件展示为图标、数字、图标+数字，或图标+最近一小时的按键迷你图。</Text>
         </Box>
         <ButtonGroup size="sm" gap="1" {...glassPillStyle} borderRadius="999px" p="1">
           <Button
             variant="ghost"
             borderRadius="999px"
-            bg={snapshot.tray_display_mode === "icon_only" ? "rgba(255,255,255,0.84)" : "transparent"}
-            boxShadow={snapshot.tray_display_mode === "icon_only" ? "sm" : "none"}
+            bg={config.menu_bar_display_mode === "icon_only" ? "rgba(255,255,255,0.84)" : "transparent"}
+            boxShadow={config.menu_bar_display_mode === "icon_only" ? "sm" : "none"}
             onClick={() => handleModeChange("icon_only")}
           >
             仅图标
@@ -30,8 +62,8 @@ function DisplaySettingsSection() {
           <Button
             variant="ghost"
             borderRadius="999px"
-            bg={snapshot.tray_display_mode === "text_only" ? "rgba(255,255,255,0.84)" : "transparent"}
-            boxShadow={snapshot.tray_display_mode === "text_only" ? "sm" : "none"}
+            bg={config.menu_bar_display_mode === "text_only" ? "rgba(255,255,255,0.84)" : "transparent"}
+            boxShadow={config.menu_bar_display_mode === "text_only" ? "sm" : "none"}
             onClick={() => handleModeChange("text_only")}
           >
             仅数字
@@ -39,14 +71,146 @@ function DisplaySettingsSection() {
           <Button
             variant="ghost"
             borderRadius="999px"
-            bg={snapshot.tray_display_mode === "icon_text" ? "rgba(255,255,255,0.84)" : "transparent"}
-            boxShadow={snapshot.tray_display_mode === "icon_text" ? "sm" : "none"}
+            bg={config.menu_bar_display_mode === "icon_text" ? "rgba(255,255,255,0.84)" : "transparent"}
+            boxShadow={config.menu_bar_display_mode === "icon_text" ? "sm" : "none"}
             onClick={() => handleModeChange("icon_text")}
           >
             图标 + 数字
           </Button>
+          <Button
+            variant="ghost"
+            borderRadius="999px"
+            bg={config.menu_bar_display_mode === "sparkline" ? "rgba(255,255,255,0.84)" : "transparent"}
+            boxShadow={config.menu_bar_display_mode === "sparkline" ? "sm" : "none"}
+            onClick={() => handleModeChange("sparkline")}
+          >
+            迷你图
+          </Button>
+        </ButtonGroup>
+      </HStack>
+      <HStack
+        justify="space-between"
+        align="center"
+        gap="4"
+        px="5"
+        py="4"
+        flexWrap="wrap"
+        borderTopWidth="1px"
+        borderColor="glass.borderSoft"
+      >
+        <Box maxW="520px">
+          <Text fontWeight="medium" color="#111827" mb="1">托盘图标单击行为</Text>
+          <Text fontSize="sm" color="#6b7280">单击托盘图标时执行的操作，双击始终打开主面板。</Text>
+        </Box>
+        <ButtonGroup size="sm" gap="1" {...glassPillStyle} borderRadius="999px" p="1">
+          <Button
+            variant="ghost"
+            borderRadius="999px"
+            bg={config.tray_left_click_action === "menu" ? "rgba(255,255,255,0.84)" : "transparent"}
+            boxShadow={config.tray_left_click_action === "menu" ? "sm" : "none"}
+            onClick={() => handleClickActionChange("menu")}
+          >
+            弹出菜单
+          </Button>
+          <Button
+            variant="ghost"
+            borderRadius="999px"
+            bg={config.tray_left_click_action === "open_window" ? "rgba(255,255,255,0.84)" : "transparent"}
+            boxShadow={config.tray_left_click_action === "open_window" ? "sm" : "none"}
+            onClick={() => handleClickActionChange("open_window")}
+          >
+            打开主面板
+          </Button>
+          <Button
+            variant="ghost"
+            borderRadius="999px"
+            bg={config.tray_left_click_action === "toggle_pause" ? "rgba(255,255,255,0.84)" : "transparent"}
+            boxShadow={config.tray_left_click_action === "toggle_pause" ? "sm" : "none"}
+            onClick={() => handleClickActionChange("toggle_pause")}
+          >
+            暂停/继续
+          </Button>
         </ButtonGroup>
       </HStack>
+      <HStack
+        justify="space-between"
+        align="center"
+        gap="4"
+        px="5"
+        py="4"
+        flexWrap="wrap"
+        borderTopWidth="1px"
+        borderColor="glass.borderSoft"
+      >
+        <Box maxW="520px">
+          <Text fontWeight="medium" color="#111827" mb="1">启动时隐藏主面板</Text>
+          <Text fontSize="sm" color="#6b7280">启动后仅显示菜单栏图标，可通过托盘“打开主面板”进入。</Text>
+        </Box>
+        <Switch.Root checked={config.start_hidden} onCheckedChange={toggleStartHidden}>
+          <Switch.HiddenInput />
+          <Switch.Control />
+        </Switch.Root>
+      </HStack>
+      <HStack
+        justify="space-between"
+        align="center"
+        gap="4"
+        px="5"
+        py="4"
+        flexWrap="wrap"
+        borderTopWidth="1px"
+        borderColor="glass.borderSoft"
+      >
+        <Box maxW="520px">
+          <Text fontWeight="medium" color="#111827" mb="1">主面板窗口位置</Text>
+          <Text fontSize="sm" color="#6b7280">窗口位置与尺寸会自动记住；窗口跑到屏幕外时可在此重置。</Text>
+        </Box>
+        <Button size="sm" variant="outline" borderRadius="999px" onClick={() => void resetWindowPosition()}>
+          重置窗口位置
+        </Button>
+      </HStack>
+      <HStack
+        justify="space-between"
+        align="center"
+        gap="4"
+        px="5"
+        py="4"
+        flexWrap="wrap"
+        borderTopWidth="1px"
+        borderColor="glass.borderSoft"
+      >
+        <Box maxW="520px">
+          <Text fontWeight="medium" color="#111827" mb="1">每天自动检查更新</Text>
+          <Text fontSize="sm" color="#6b7280">
+            {updateInfo ? updateSummary(updateInfo) : "在后台对比 GitHub 上的最新发布，有新版本时在状态卡片上提示。"}
+          </Text>
+        </Box>
+        <HStack gap="3">
+          {updateInfo?.update_available && updateInfo.release_notes_url ? (
+            <Button
+              size="sm"
+              variant="ghost"
+              borderRadius="999px"
+              onClick={() => void openUrl(updateInfo.release_notes_url ?? "")}
+            >
+              查看发布说明
+            </Button>
+          ) : null}
+          <Button
+            size="sm"
+            variant="outline"
+            borderRadius="999px"
+            loading={checkingUpdates}
+            onClick={() => void handleCheckForUpdates()}
+          >
+            立即检查
+          </Button>
+          <Switch.Root checked={config.auto_update_check} onCheckedChange={toggleAutoUpdateCheck}>
+            <Switch.HiddenInput />
+            <Switch.Control />
+          </Switch.Root>
+        </HStack>
+      </HStack>
     </Box>
   );
 }
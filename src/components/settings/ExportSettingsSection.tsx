@@ -0,0 +1,483 @@
+import { invoke } from "@tauri-apps/api/core";
+import { Badge, Box, Button, ButtonGroup, HStack, Input, Stack, Switch, Text } from "@chakra-ui/react";
+import { useEffect, useState } from "react";
+import {
+  AutoExportFormat,
+  CsvDelimiter,
+  CsvDialect,
+  ExportVerification,
+  FilterRange,
+  ParquetExportReport,
+  PathPickKind,
+  XlsxExportReport,
+} from "../../types";
+import { glassPillStyle, glassSurfaceStyle } from "../../styles/glass";
+import { useSettingsContext } from "./SettingsContext";
+
+function ExportSettingsSection() {
+  const {
+    config,
+    configSource,
+    updateAutoExport,
+    updateCsvDialect,
+    toggleWriteStatusFile,
+    toggleAppleScriptEnabled,
+  } = useSettingsContext();
+  const autoExport = config.auto_export;
+  const [destDirDraft, setDestDirDraft] = useState(autoExport.dest_dir);
+  const [scriptDraft, setScriptDraft] = useState(autoExport.script_path ?? "");
+  const [error, setError] = useState<string | null>(null);
+  const [xlsxRange, setXlsxRange] = useState<FilterRange>("7d");
+  const [xlsxDest, setXlsxDest] = useState("");
+  const [xlsxExporting, setXlsxExporting] = useState(false);
+  const [xlsxMessage, setXlsxMessage] = useState<string | null>(null);
+  const [xlsxSign, setXlsxSign] = useState(false);
+  const [verifyPath, setVerifyPath] = useState("");
+  const [verifyMessage, setVerifyMessage] = useState<string | null>(null);
+  const [parquetDir, setParquetDir] = useState("");
+  const [parquetEvents, setParquetEvents] = useState(false);
+  const [parquetExporting, setParquetExporting] = useState(false);
+  const [parquetMessage, setParquetMessage] = useState<string | null>(null);
+
+  useEffect(() => {
+    setDestDirDraft(autoExport.dest_dir);
+    setScriptDraft(autoExport.script_path ?? "");
+  }, [autoExport.dest_dir, autoExport.script_path]);
+
+  const save = async (changes: { enabled?: boolean; format?: AutoExportFormat; sign?: boolean }) => {
+    try {
+      await updateAutoExport({
+        enabled: changes.enabled ?? autoExport.enabled,
+        format: changes.format ?? autoExport.format,
+        dest_dir: destDirDraft.trim(),
+        script_path: scriptDraft.trim() === "" ? null : scriptDraft.trim(),
+        sign: changes.sign ?? autoExport.sign,
+      });
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  // Absolute paths outside the data dir are only accepted once picked in a system dialog.
+  const pickPath = async (kind: PathPickKind, apply: (path: string) => void) => {
+    try {
+      const path = await invoke<string | null>("pick_export_path", { kind });
+      if (path !== null) {
+        apply(path);
+      }
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const pickButton = (kind: PathPickKind, apply: (path: string) => void) => (
+    <Button size="sm" variant="outline" flexShrink={0} onClick={() => void pickPath(kind, apply)}>
+      选择…
+    </Button>
+  );
+
+  const saveCsvDialect = async (changes: Partial<CsvDialect>) => {
+    try {
+      await updateCsvDialect({
+        delimiter: changes.delimiter ?? config.csv_delimiter,
+        utf8_bom: changes.utf8_bom ?? config.csv_utf8_bom,
+        headers_localized: changes.headers_localized ?? config.csv_headers_localized,
+      });
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const delimiterButton = (delimiter: CsvDelimiter, label: string) => (
+    <Button
+      variant="ghost"
+      borderRadius="999px"
+      bg={config.csv_delimiter === delimiter ? "rgba(255,255,255,0.84)" : "transparent"}
+      boxShadow={config.csv_delimiter === delimiter ? "sm" : "none"}
+      onClick={() => void saveCsvDialect({ delimiter })}
+    >
+      {label}
+    </Button>
+  );
+
+  const exportXlsx = async () => {
+    setXlsxExporting(true);
+    try {
+      const report = await invoke<XlsxExportReport>("export_stats_xlsx", {
+        range: xlsxRange,
+        dest: xlsxDest.trim(),
+        sign: xlsxSign,
+      });
+      const counts = report.sheets.map((sheet) => `${sheet.sheet} ${sheet.rows} 行`).join("，");
+      const signed = report.signature ? `，签名链第 ${report.signature.position} 项` : "";
+      setXlsxMessage(`已导出到 ${report.path}：${counts}${signed}。`);
+    } catch (err) {
+      setXlsxMessage(`导出失败：${String(err)}`);
+    } finally {
+      setXlsxExporting(false);
+    }
+  };
+
+  const verifyExport = async () => {
+    try {
+      const result = await invoke<ExportVerification>("verify_export", { path: verifyPath.trim() });
+      if (!result.signed) {
+        setVerifyMessage("该文件没有签名。");
+      } else if (!result.valid) {
+        setVerifyMessage(`校验失败：文件在签名后被改动（${result.reason ?? "内容不匹配"}）。`);
+      } else {
+        const chain =
+          result.in_chain === null
+            ? "本数据集的哈希链中没有这一项"
+            : result.in_chain
+              ? "与本数据集的哈希链一致"
+              : "与本数据集的哈希链不一致";
+        setVerifyMessage(`签名有效（第 ${result.signature?.position} 项），${chain}。`);
+      }
+    } catch (err) {
+      setVerifyMessage(`校验失败：${String(err)}`);
+    }
+  };
+
+  const exportParquet = async () => {
+    setParquetExporting(true);
+    try {
+      const report = await invoke<ParquetExportReport>("export_stats_parquet", {
+        range: xlsxRange,
+        destDir: parquetDir.trim(),
+        includeEvents: parquetEvents,
+      });
+      const files = report.files.map((file) => `${file.path}（${file.rows} 行）`).join("，");
+      setParquetMessage(`已导出：${files}。`);
+    } catch (err) {
+      setParquetMessage(`导出失败：${String(err)}`);
+    } finally {
+      setParquetExporting(false);
+    }
+  };
+
+  const xlsxRangeButton = (range: FilterRange, label: string) => (
+    <Button
+      variant="ghost"
+      borderRadius="999px"
+      bg={xlsxRange === range ? "rgba(255,255,255,0.84)" : "transparent"}
+      boxShadow={xlsxRange === range ? "sm" : "none"}
+      onClick={() => setXlsxRange(range)}
+    >
+      {label}
+    </Button>
+  );
+
+  const formatButton = (format: AutoExportFormat, label: string) => (
+    <Button
+      variant="ghost"
+      borderRadius="999px"
+      bg={autoExport.format === format ? "rgba(255,255,255,0.84)" : "transparent"}
+      boxShadow={autoExport.format === format ? "sm" : "none"}
+      onClick={() => void save({ format })}
+    >
+      {label}
+    </Button>
+  );
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="12px" overflow="hidden">
+      <HStack px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft" gap="2">
+        <Text fontSize="lg" fontWeight="semibold" color="#111827">Auto Export</Text>
+        {configSource.export === "user" ? (
+          <Badge bg="#e0ecff" color="#1d4ed8">自定义</Badge>
+        ) : null}
+      </HStack>
+      <HStack justify="space-between" align="center" flexWrap="wrap" gap="3" px="5" py="4">
+        <Box maxW="520px">
+          <Text fontWeight="medium" color="#111827">每日自动导出</Text>
+          <Text fontSize="sm" color="#6b7280">
+            每天结束后把当天明细写入导出目录，睡眠错过的日期会在下次唤醒时补导出。
+          </Text>
+        </Box>
+        <Switch.Root
+          checked={autoExport.enabled}
+          onCheckedChange={(details) => void save({ enabled: details.checked })}
+        >
+          <Switch.HiddenInput />
+          <Switch.Control />
+        </Switch.Root>
+      </HStack>
+      <Stack gap="3" px="5" pb="5">
+        <HStack justify="space-between" align="center" flexWrap="wrap" gap="3">
+          <Text fontSize="sm" color="#374151">导出格式</Text>
+          <ButtonGroup size="sm" gap="1" {...glassPillStyle} borderRadius="999px" p="1">
+            {formatButton("csv", "CSV")}
+            {formatButton("json", "JSON")}
+          </ButtonGroup>
+        </HStack>
+        <HStack justify="space-between" align="center" flexWrap="wrap" gap="3">
+          <Text fontSize="sm" color="#374151">CSV 分隔符</Text>
+          <ButtonGroup size="sm" gap="1" {...glassPillStyle} borderRadius="999px" p="1">
+            {delimiterButton("comma", "逗号 ,")}
+            {delimiterButton("semicolon", "分号 ;")}
+            {delimiterButton("tab", "Tab")}
+          </ButtonGroup>
+        </HStack>
+        <HStack justify="space-between" align="center" gap="3">
+          <Box>
+            <Text fontSize="sm" color="#374151">写入 UTF-8 BOM</Text>
+            <Text fontSize="xs" color="#8b939f">Windows 版 Excel 需要 BOM 才能正确识别中文。</Text>
+          </Box>
+          <Switch.Root
+            checked={config.csv_utf8_bom}
+            onCheckedChange={(details) => void saveCsvDialect({ utf8_bom: details.checked })}
+          >
+            <Switch.HiddenInput />
+            <Switch.Control />
+          </Switch.Root>
+        </HStack>
+        <HStack justify="space-between" align="center" gap="3">
+          <Text fontSize="sm" color="#374151">中文表头</Text>
+          <Switch.Root
+            checked={config.csv_headers_localized}
+            onCheckedChange={(details) =>
+              void saveCsvDialect({ headers_localized: details.checked })
+            }
+          >
+            <Switch.HiddenInput />
+            <Switch.Control />
+          </Switch.Root>
+        </HStack>
+        <Text fontSize="xs" color="#8b939f">
+          CSV 格式同时用于汇总 CSV 与自动导出，汇总 CSV 会在下次刷盘时按新格式重写。
+        </Text>
+        <Box>
+          <Text fontSize="sm" color="#374151" mb="1">导出目录（绝对路径）</Text>
+          <HStack gap="2">
+            <Input
+              size="sm"
+              fontFamily="mono"
+              placeholder="/Users/me/Documents/typepulse"
+              value={destDirDraft}
+              onChange={(event) => setDestDirDraft(event.target.value)}
+            />
+            {pickButton("folder", setDestDirDraft)}
+          </HStack>
+        </Box>
+        <Box>
+          <Text fontSize="sm" color="#374151" mb="1">导出后执行脚本（可选）</Text>
+          <HStack gap="2">
+            <Input
+              size="sm"
+              fontFamily="mono"
+              placeholder="/Users/me/bin/upload-typepulse.sh"
+              value={scriptDraft}
+              onChange={(event) => setScriptDraft(event.target.value)}
+            />
+            {pickButton("open_file", setScriptDraft)}
+          </HStack>
+          <Text fontSize="xs" color="#8b939f" mt="1">
+            脚本以导出文件路径作为唯一参数运行，输出与退出状态写入应用日志。脚本须通过“选择…”选取，或位于数据目录内。
+          </Text>
+        </Box>
+        <HStack justify="space-between" align="center" gap="3">
+          <Box>
+            <Text fontSize="sm" color="#374151">签名导出文件</Text>
+            <Text fontSize="xs" color="#8b939f">
+              为每个导出文件写入 SHA-256 哈希并串成哈希链，之后可校验文件是否被改动。仅用于发现改动，不是安全机制。
+            </Text>
+          </Box>
+          <Switch.Root
+            checked={autoExport.sign}
+            onCheckedChange={(details) => void save({ sign: details.checked })}
+          >
+            <Switch.HiddenInput />
+            <Switch.Control />
+          </Switch.Root>
+        </HStack>
+        <HStack gap="3">
+          <Button size="sm" variant="outline" onClick={() => void save({})}>
+            保存路径
+          </Button>
+          {error ? (
+            <Text fontSize="sm" color="#b91c1c">{error}</Text>
+          ) : null}
+        </HStack>
+      </Stack>
+      <HStack
+        justify="space-between"
+        align="center"
+        flexWrap="wrap"
+        gap="3"
+        px="5"
+        py="4"
+        borderTopWidth="1px"
+        borderColor="glass.borderSoft"
+      >
+        <Box maxW="520px">
+          <Text fontWeight="medium" color="#111827">写入状态文件</Text>
+          <Text fontSize="sm" color="#6b7280">
+            在数据目录持续更新 status.json（今日按键数、活跃时长、暂停状态），供脚本直接读取；最多每 5 秒写一次，关闭时删除该文件。
+          </Text>
+        </Box>
+        <Switch.Root checked={config.write_status_file} onCheckedChange={() => void toggleWriteStatusFile()}>
+          <Switch.HiddenInput />
+          <Switch.Control />
+        </Switch.Root>
+      </HStack>
+      <HStack
+        justify="space-between"
+        align="center"
+        flexWrap="wrap"
+        gap="3"
+        px="5"
+        py="4"
+        borderTopWidth="1px"
+        borderColor="glass.borderSoft"
+      >
+        <Box maxW="520px">
+          <Text fontWeight="medium" color="#111827">AppleScript 控制</Text>
+          <Text fontSize="sm" color="#6b7280">
+            允许脚本或快捷指令通过 tell application "TypePulse" 暂停、恢复采集并读取今日按键数与活跃分钟数（仅 macOS）；每次调用都会记录来源应用。
+          </Text>
+        </Box>
+        <Switch.Root checked={config.applescript_enabled} onCheckedChange={() => void toggleAppleScriptEnabled()}>
+          <Switch.HiddenInput />
+          <Switch.Control />
+        </Switch.Root>
+      </HStack>
+      <Stack gap="3" px="5" py="4" borderTopWidth="1px" borderColor="glass.borderSoft">
+        <Box>
+          <Text fontWeight="medium" color="#111827">导出 Excel</Text>
+          <Text fontSize="sm" color="#6b7280">
+            生成包含应用日汇总、明细行、快捷键三个工作表的 .xlsx 文件，时长按 时:分 显示。
+          </Text>
+        </Box>
+        <HStack justify="space-between" align="center" flexWrap="wrap" gap="3">
+          <Text fontSize="sm" color="#374151">时间范围</Text>
+          <ButtonGroup size="sm" gap="1" {...glassPillStyle} borderRadius="999px" p="1">
+            {xlsxRangeButton("today", "今天")}
+            {xlsxRangeButton("yesterday", "昨天")}
+            {xlsxRangeButton("7d", "近 7 天")}
+          </ButtonGroup>
+        </HStack>
+        <Box>
+          <Text fontSize="sm" color="#374151" mb="1">文件路径（绝对路径）</Text>
+          <HStack gap="2">
+            <Input
+              size="sm"
+              fontFamily="mono"
+              placeholder="/Users/me/Documents/typepulse.xlsx"
+              value={xlsxDest}
+              onChange={(event) => setXlsxDest(event.target.value)}
+            />
+            {pickButton("save_file", setXlsxDest)}
+          </HStack>
+        </Box>
+        <HStack justify="space-between" align="center" gap="3">
+          <Box>
+            <Text fontSize="sm" color="#374151">签名工作簿</Text>
+            <Text fontSize="xs" color="#8b939f">签名写入文档属性；在表格软件中另存后签名会失效。</Text>
+          </Box>
+          <Switch.Root
+            checked={xlsxSign}
+            onCheckedChange={(details) => setXlsxSign(details.checked)}
+          >
+            <Switch.HiddenInput />
+            <Switch.Control />
+          </Switch.Root>
+        </HStack>
+        <HStack gap="3">
+          <Button
+            size="sm"
+            variant="outline"
+            loading={xlsxExporting}
+            disabled={xlsxDest.trim() === ""}
+            onClick={() => void exportXlsx()}
+          >
+            导出
+          </Button>
+          {xlsxMessage ? (
+            <Text fontSize="sm" color="#4b5563">{xlsxMessage}</Text>
+          ) : null}
+        </HStack>
+      </Stack>
+      <Stack gap="3" px="5" py="4" borderTopWidth="1px" borderColor="glass.borderSoft">
+        <Box>
+          <Text fontWeight="medium" color="#111827">校验签名导出</Text>
+          <Text fontSize="sm" color="#6b7280">
+            重新计算签名导出（CSV / JSON / Excel）的哈希，检查文件签名后是否被改动，以及是否属于本数据集的哈希链。
+          </Text>
+        </Box>
+        <HStack gap="2">
+          <Input
+            size="sm"
+            fontFamily="mono"
+            placeholder="/Users/me/Documents/typepulse/typepulse-2026-02-10.csv"
+            value={verifyPath}
+            onChange={(event) => setVerifyPath(event.target.value)}
+          />
+          {pickButton("open_file", setVerifyPath)}
+        </HStack>
+        <HStack gap="3">
+          <Button
+            size="sm"
+            variant="outline"
+            disabled={verifyPath.trim() === ""}
+            onClick={() => void verifyExport()}
+          >
+            校验
+          </Button>
+          {verifyMessage ? (
+            <Text fontSize="sm" color="#4b5563">{verifyMessage}</Text>
+          ) : null}
+        </HStack>
+      </Stack>
+      <Stack gap="3" px="5" py="4" borderTopWidth="1px" borderColor="glass.borderSoft">
+        <Box>
+          <Text fontWeight="medium" color="#111827">导出 Parquet</Text>
+          <Text fontSize="sm" color="#6b7280">
+            按上方时间范围写出列式文件，供 DuckDB / pandas 分析；窗口标题只保留哈希。需要以 parquet-export 特性构建。
+          </Text>
+        </Box>
+        <Box>
+          <Text fontSize="sm" color="#374151" mb="1">导出目录（绝对路径）</Text>
+          <HStack gap="2">
+            <Input
+              size="sm"
+              fontFamily="mono"
+              placeholder="/Users/me/Documents/typepulse-parquet"
+              value={parquetDir}
+              onChange={(event) => setParquetDir(event.target.value)}
+            />
+            {pickButton("folder", setParquetDir)}
+          </HStack>
+        </Box>
+        <HStack justify="space-between" align="center" gap="3">
+          <Text fontSize="sm" color="#374151">同时导出按键事件</Text>
+          <Switch.Root
+            checked={parquetEvents}
+            onCheckedChange={(details) => setParquetEvents(details.checked)}
+          >
+            <Switch.HiddenInput />
+            <Switch.Control />
+          </Switch.Root>
+        </HStack>
+        <HStack gap="3">
+          <Button
+            size="sm"
+            variant="outline"
+            loading={parquetExporting}
+            disabled={parquetDir.trim() === ""}
+            onClick={() => void exportParquet()}
+          >
+            导出
+          </Button>
+          {parquetMessage ? (
+            <Text fontSize="sm" color="#4b5563">{parquetMessage}</Text>
+          ) : null}
+        </HStack>
+      </Stack>
+    </Box>
+  );
+}
+
+export default ExportSettingsSection;
@@ -0,0 +1,140 @@
+import { Box, Button, HStack, Input, Stack, Switch, Text } from "@chakra-ui/react";
+import { useState } from "react";
+import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+import { useSettingsContext } from "./SettingsContext";
+
+const MAX_GOAL_MIN = 24 * 60;
+
+function GoalSettingsSection() {
+  const {
+    snapshot,
+    config,
+    updateAppTimeGoal,
+    removeAppTimeGoal,
+    toggleAppTimeGoalNotifications,
+    toggleWeeklyComparisonNotifications,
+  } = useSettingsContext();
+  const [bundleIdDraft, setBundleIdDraft] = useState("");
+  const [minutesDraft, setMinutesDraft] = useState("120");
+  const progressById = new Map(snapshot.goal_progress.map((item) => [item.bundle_id, item]));
+  const goals = Object.entries(config.app_time_goals).sort(([a], [b]) => a.localeCompare(b));
+
+  const minutes = Number(minutesDraft);
+  const canAdd =
+    bundleIdDraft.trim().length > 0 &&
+    Number.isInteger(minutes) &&
+    minutes >= 1 &&
+    minutes <= MAX_GOAL_MIN;
+
+  const handleAddGoal = async () => {
+    if (!canAdd) {
+      return;
+    }
+    await updateAppTimeGoal(bundleIdDraft.trim(), minutes);
+    setBundleIdDraft("");
+  };
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="12px" overflow="hidden">
+      <Box px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
+        <Text fontSize="lg" fontWeight="semibold" color="#111827">Goals</Text>
+      </Box>
+      <Stack gap="0">
+        <HStack justify="space-between" align="center" flexWrap="wrap" gap="3" px="5" py="4">
+          <Box maxW="520px">
+            <Text fontWeight="medium" color="#111827">应用时长目标通知</Text>
+            <Text fontSize="sm" color="#6b7280">当天某个应用的活跃打字时长达到目标时提醒一次。</Text>
+          </Box>
+          <Switch.Root
+            checked={config.app_time_goal_notifications}
+            onCheckedChange={toggleAppTimeGoalNotifications}
+          >
+            <Switch.HiddenInput />
+            <Switch.Control />
+          </Switch.Root>
+        </HStack>
+        <HStack justify="space-between" align="center" flexWrap="wrap" gap="3" px="5" py="4" borderTopWidth="1px" borderColor="glass.borderSoft">
+          <Box maxW="520px">
+            <Text fontWeight="medium" color="#111827">每周对比通知</Text>
+            <Text fontSize="sm" color="#6b7280">每周一上午对比上周与前一周的按键数、活跃时长、最常用应用和增长最多的快捷键，点击通知打开周报。</Text>
+          </Box>
+          <Switch.Root
+            checked={config.weekly_comparison_notifications}
+            onCheckedChange={toggleWeeklyComparisonNotifications}
+          >
+            <Switch.HiddenInput />
+            <Switch.Control />
+          </Switch.Root>
+        </HStack>
+      </Stack>
+
+      <Box m="5" mt="0" {...glassSubtleStyle} borderRadius="12px" overflow="hidden">
+        <HStack px="4" py="3" bg="rgba(255,255,255,0.46)" fontWeight="semibold" fontSize="sm" justify="space-between">
+          <Text flex="1">Bundle ID</Text>
+          <Text flex="1">今日进度</Text>
+          <Text flex="0 0 auto">操作</Text>
+        </HStack>
+        {goals.length === 0 ? (
+          <Text px="4" py="6" color="#8b939f" textAlign="center">暂无应用时长目标</Text>
+        ) : (
+          goals.map(([bundleId, targetMin]) => {
+            const progress = progressById.get(bundleId);
+            const actualMin = progress?.actual_min ?? 0;
+            const pct = Math.min(progress?.pct ?? 0, 100);
+            return (
+              <HStack key={bundleId} px="4" py="3" borderTopWidth="1px" borderColor="glass.borderSoft" justify="space-between" gap="3">
+                <Text flex="1" fontFamily="mono" fontSize="sm" truncate title={bundleId}>
+                  {bundleId}
+                </Text>
+                <Box flex="1">
+                  <Text fontSize="sm" color={pct >= 100 ? "#166534" : "#374151"}>
+                    {actualMin} / {targetMin} 分钟
+                  </Text>
+                  <Box mt="1" h="4px" borderRadius="999px" bg="rgba(17,24,39,0.08)" overflow="hidden">
+                    <Box h="100%" w={`${pct}%`} bg={pct >= 100 ? "#22c55e" : "#3b82f6"} />
+                  </Box>
+                </Box>
+                <Button
+                  size="sm"
+                  variant="ghost"
+                  borderWidth="1px"
+                  borderColor="glass.borderSoft"
+                  bg="rgba(255,255,255,0.56)"
+                  _hover={{ bg: "rgba(255,255,255,0.74)" }}
+                  onClick={() => removeAppTimeGoal(bundleId)}
+                >
+                  移除
+                </Button>
+              </HStack>
+            );
+          })
+        )}
+        <HStack px="4" py="3" borderTopWidth="1px" borderColor="glass.borderSoft" gap="3">
+          <Input
+            size="sm"
+            flex="1"
+            fontFamily="mono"
+            placeholder="com.example.app"
+            value={bundleIdDraft}
+            onChange={(event) => setBundleIdDraft(event.target.value)}
+          />
+          <Input
+            size="sm"
+            w="96px"
+            type="number"
+            min={1}
+            max={MAX_GOAL_MIN}
+            value={minutesDraft}
+            onChange={(event) => setMinutesDraft(event.target.value)}
+          />
+          <Text fontSize="sm" color="#6b7280">分钟</Text>
+          <Button size="sm" variant="outline" disabled={!canAdd} onClick={handleAddGoal}>
+            设置目标
+          </Button>
+        </HStack>
+      </Box>
+    </Box>
+  );
+}
+
+export default GoalSettingsSection;
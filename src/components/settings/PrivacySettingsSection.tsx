@@ -0,0 +1,184 @@
+import { Box, Button, ButtonGroup, HStack, Input, Stack, Text } from "@chakra-ui/react";
+import { useState } from "react";
+import { TelemetryPreview, WindowTitleMode } from "../../types";
+import { glassPillStyle, glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+import { useSettingsContext } from "./SettingsContext";
+
+const TITLE_MODE_LABELS: Record<WindowTitleMode, string> = {
+  full: "记录完整标题",
+  hidden: "仅按应用统计",
+};
+
+function PrivacySettingsSection() {
+  const {
+    config,
+    updateWindowTitleMode,
+    setAppTitlePrivacy,
+    removeAppTitlePrivacy,
+    setTelemetryConsent,
+    loadTelemetryPreview,
+  } = useSettingsContext();
+  const [bundleIdDraft, setBundleIdDraft] = useState("");
+  const [endpointDraft, setEndpointDraft] = useState(config.telemetry_endpoint);
+  const [telemetryError, setTelemetryError] = useState<string | null>(null);
+  const [telemetryPreview, setTelemetryPreview] = useState<TelemetryPreview | null>(null);
+  const overrides = Object.entries(config.title_privacy_overrides);
+
+  const handleAddOverride = async (mode: WindowTitleMode) => {
+    const bundleId = bundleIdDraft.trim();
+    if (!bundleId) {
+      return;
+    }
+    await setAppTitlePrivacy(bundleId, mode);
+    setBundleIdDraft("");
+  };
+
+  const handleTelemetryConsent = async (enabled: boolean) => {
+    setTelemetryError(null);
+    try {
+      await setTelemetryConsent(enabled, endpointDraft);
+      setTelemetryPreview(null);
+    } catch (error) {
+      setTelemetryError(String(error));
+    }
+  };
+
+  const handleTelemetryPreview = async () => {
+    setTelemetryError(null);
+    try {
+      setTelemetryPreview(await loadTelemetryPreview());
+    } catch (error) {
+      setTelemetryError(String(error));
+    }
+  };
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="12px" overflow="hidden">
+      <Box px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
+        <Text fontSize="lg" fontWeight="semibold" color="#111827">Privacy</Text>
+      </Box>
+      <Stack gap="0">
+        <HStack justify="space-between" align="center" gap="4" px="5" py="4" flexWrap="wrap">
+          <Box maxW="520px">
+            <Text fontWeight="medium" color="#111827" mb="1">窗口标题记录</Text>
+            <Text fontSize="sm" color="#6b7280">隐藏后仅按应用统计，不保存窗口标题；可按应用单独覆盖。</Text>
+          </Box>
+          <ButtonGroup size="sm" gap="1" {...glassPillStyle} borderRadius="999px" p="1">
+            {(Object.keys(TITLE_MODE_LABELS) as WindowTitleMode[]).map((mode) => (
+              <Button
+                key={mode}
+                variant="ghost"
+                borderRadius="999px"
+                bg={config.window_title_mode === mode ? "rgba(255,255,255,0.84)" : "transparent"}
+                boxShadow={config.window_title_mode === mode ? "sm" : "none"}
+                onClick={() => updateWindowTitleMode(mode)}
+              >
+                {TITLE_MODE_LABELS[mode]}
+              </Button>
+            ))}
+          </ButtonGroup>
+        </HStack>
+        <Stack gap="3" px="5" py="4" borderTopWidth="1px" borderColor="glass.borderSoft">
+          <HStack justify="space-between" align="center" gap="4" flexWrap="wrap">
+            <Box maxW="520px">
+              <Text fontWeight="medium" color="#111827" mb="1">匿名使用统计</Text>
+              <Text fontSize="sm" color="#6b7280">
+                默认关闭。同意后只在本机累计各功能的使用次数，每周发送一次，附带版本号与系统类型；
+                打字内容、应用名和窗口标题永远不会离开这台电脑。
+              </Text>
+            </Box>
+            <Button
+              size="sm"
+              variant="outline"
+              onClick={() => handleTelemetryConsent(!config.telemetry_enabled)}
+            >
+              {config.telemetry_enabled ? "撤回同意" : "同意并开启"}
+            </Button>
+          </HStack>
+          <HStack gap="3">
+            <Input
+              size="sm"
+              flex="1"
+              fontFamily="mono"
+              placeholder="https://telemetry.example.com/report（留空则只在本地累计）"
+              value={endpointDraft}
+              onChange={(event) => setEndpointDraft(event.target.value)}
+            />
+            {config.telemetry_enabled ? (
+              <Button size="sm" variant="outline" onClick={() => handleTelemetryConsent(true)}>
+                保存地址
+              </Button>
+            ) : null}
+            <Button size="sm" variant="ghost" onClick={handleTelemetryPreview}>
+              查看将发送的内容
+            </Button>
+          </HStack>
+          {telemetryError ? (
+            <Text fontSize="sm" color="red.600">{telemetryError}</Text>
+          ) : null}
+          {telemetryPreview ? (
+            <Box {...glassSubtleStyle} borderRadius="12px" p="3">
+              <Text fontSize="xs" color="#6b7280" mb="2">
+                {telemetryPreview.next_send_ms
+                  ? `下次发送：${new Date(telemetryPreview.next_send_ms).toLocaleString()}`
+                  : "未开启，不会发送"}
+              </Text>
+              <Text as="pre" fontFamily="mono" fontSize="xs" whiteSpace="pre-wrap" maxH="240px" overflowY="auto">
+                {JSON.stringify(telemetryPreview.payload, null, 2)}
+              </Text>
+            </Box>
+          ) : null}
+        </Stack>
+      </Stack>
+
+      <Box m="5" mt="0" {...glassSubtleStyle} borderRadius="12px" overflow="hidden">
+        <HStack px="4" py="3" bg="rgba(255,255,255,0.46)" fontWeight="semibold" fontSize="sm" justify="space-between">
+          <Text flex="1">Bundle ID</Text>
+          <Text flex="1">标题记录</Text>
+          <Text flex="0 0 auto">操作</Text>
+        </HStack>
+        {overrides.length === 0 ? (
+          <Text px="4" py="6" color="#8b939f" textAlign="center">暂无按应用覆盖</Text>
+        ) : (
+          overrides.map(([bundleId, mode]) => (
+            <HStack key={bundleId} px="4" py="3" borderTopWidth="1px" borderColor="glass.borderSoft" justify="space-between" gap="3">
+              <Text flex="1" fontFamily="mono" fontSize="sm" truncate title={bundleId}>
+                {bundleId}
+              </Text>
+              <Text flex="1" fontSize="sm">{TITLE_MODE_LABELS[mode]}</Text>
+              <Button
+                size="sm"
+                variant="ghost"
+                borderWidth="1px"
+                borderColor="glass.borderSoft"
+                bg="rgba(255,255,255,0.56)"
+                _hover={{ bg: "rgba(255,255,255,0.74)" }}
+                onClick={() => removeAppTitlePrivacy(bundleId)}
+              >
+                移除
+              </Button>
+            </HStack>
+          ))
+        )}
+        <HStack px="4" py="3" borderTopWidth="1px" borderColor="glass.borderSoft" gap="3">
+          <Input
+            size="sm"
+            flex="1"
+            fontFamily="mono"
+            placeholder="com.example.app"
+            value={bundleIdDraft}
+            onChange={(event) => setBundleIdDraft(event.target.value)}
+          />
+          <Button size="sm" variant="outline" onClick={() => handleAddOverride("full")}>
+            完整标题
+          </Button>
+          <Button size="sm" variant="outline" onClick={() => handleAddOverride("hidden")}>
+            仅应用
+          </Button>
+        </HStack>
+      </Box>
+    </Box>
+  );
+}
+
+export default PrivacySettingsSection;
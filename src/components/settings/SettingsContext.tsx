@@ -1,26 +1,109 @@
-import { createContext, type ReactNode, useContext } from "react";
+import { createContext, type ReactNode, useCallback, useContext, useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { MenuBarDisplayMode, RunningAppInfo, Snapshot } from "../../types";
+import {
+  AppConfig,
+  AutoExportConfig,
+  ConfigGroup,
+  ConfigSource,
+  CsvDialect,
+  DestructiveAction,
+  DestructivePreview,
+  EffectiveConfig,
+  MenuBarDisplayMode,
+  RunningAppInfo,
+  Snapshot,
+  SymbolKeyNames,
+  TelemetryPreview,
+  TimingSettings,
+  TrayLeftClickAction,
+  UpdateInfo,
+  WindowTitleMode,
+} from "../../types";
+
+// Backend command that runs each previewed destructive action.
+const DESTRUCTIVE_ACTION_COMMANDS: Record<DestructiveAction, string> = {
+  reset_all: "reset_stats",
+  reset_today: "reset_today_stats",
+  prune_retention: "prune_retention",
+};
 
 type SettingsContextValue = {
-  // Latest settings snapshot from backend, used as the single source of truth in UI.
+  // Latest runtime snapshot from backend (permission, pause and suggestion state).
   snapshot: Snapshot;
+  // Effective config from backend, the source of truth for every settings value.
+  config: AppConfig;
+  // Whether each config group still uses defaults or carries user changes.
+  configSource: Record<ConfigGroup, ConfigSource>;
   // Toggle manual pause/resume collection and refresh snapshot.
   togglePause: () => Promise<void>;
   // Toggle combo-key filtering (Ctrl/Alt/Fn/Shift/Cmd + key) and refresh snapshot.
   toggleIgnoreKeyCombos: () => Promise<void>;
+  // Toggle counting modifier presses released without a key (aborted chords) and refresh snapshot.
+  toggleTrackChordAborts: () => Promise<void>;
+  toggleShortcutsPaused: () => Promise<void>;
+  // Name shifted symbol keys by the key pressed or the character typed; stored shortcut counts follow.
+  updateSymbolKeyNames: (names: SymbolKeyNames) => Promise<void>;
+  togglePreventAppNap: () => Promise<void>;
+  // Toggle recording per-keystroke event chunks (shortcut totals are kept) and refresh snapshot.
+  toggleRecordEventChunks: () => Promise<void>;
+  // Toggle the macOS Accessibility check for password-like fields and refresh snapshot.
+  toggleAxSensitiveDetection: () => Promise<void>;
+  // Toggle writing status.json for scripts into the data dir and refresh snapshot.
+  toggleWriteStatusFile: () => Promise<void>;
+  toggleAppleScriptEnabled: () => Promise<void>;
+  // Save tick/flush/session/tray intervals; rejects with the backend error when out of range.
+  updateTimingSettings: (timing: TimingSettings) => Promise<void>;
   // Update tray display mode and refresh snapshot.
   updateTrayDisplayMode: (mode: MenuBarDisplayMode) => Promise<void>;
+  // Update tray icon left-click behavior and refresh snapshot.
+  updateTrayLeftClickAction: (action: TrayLeftClickAction) => Promise<void>;
+  // Toggle hiding the main window on launch and refresh snapshot.
+  toggleStartHidden: () => Promise<void>;
+  // Toggle the daily automatic update check and refresh snapshot.
+  toggleAutoUpdateCheck: () => Promise<void>;
+  // Check GitHub for a newer release now; unknown when offline. Refreshes snapshot for the badge.
+  checkForUpdates: () => Promise<UpdateInfo>;
+  // Forget saved main window bounds and re-center the window.
+  resetWindowPosition: () => Promise<void>;
+  // Update global window title recording mode and refresh snapshot.
+  updateWindowTitleMode: (mode: WindowTitleMode) => Promise<void>;
+  // Override window title recording for one app and refresh snapshot.
+  setAppTitlePrivacy: (bundleId: string, mode: WindowTitleMode) => Promise<void>;
+  // Drop one app's title override (falls back to global mode) and refresh snapshot.
+  removeAppTitlePrivacy: (bundleId: string) => Promise<void>;
+  // Set one app's daily active typing target in minutes and refresh snapshot.
+  updateAppTimeGoal: (bundleId: string, targetMin: number) => Promise<void>;
+  // Drop one app's daily target and refresh snapshot.
+  removeAppTimeGoal: (bundleId: string) => Promise<void>;
+  // Toggle notifications for reached app targets and refresh snapshot.
+  toggleAppTimeGoalNotifications: () => Promise<void>;
+  toggleWeeklyComparisonNotifications: () => Promise<void>;
+  // Save end-of-day auto export settings; rejects with the backend error on invalid paths.
+  updateAutoExport: (autoExport: AutoExportConfig) => Promise<void>;
+  // Save the CSV dialect (delimiter, BOM, header language); the CSV is rewritten on next flush.
+  updateCsvDialect: (dialect: CsvDialect) => Promise<void>;
   // Add an app bundle ID to exclusion list and refresh snapshot.
   addAppExclusion: (bundleId: string) => Promise<void>;
   // Remove an app bundle ID from exclusion list and refresh snapshot.
   removeAppExclusion: (bundleId: string) => Promise<void>;
+  // Save the current data set and load another profile's; rejects with the backend error.
+  switchProfile: (name: string) => Promise<void>;
+  // Run a previewed reset/prune with its confirm token and refresh snapshot.
+  runDestructiveAction: (preview: DestructivePreview) => Promise<void>;
+  // Give or withdraw consent for anonymous usage statistics and refresh snapshot.
+  setTelemetryConsent: (enabled: boolean, endpoint: string) => Promise<void>;
+  // Read the exact payload the next usage statistics report would send.
+  loadTelemetryPreview: () => Promise<TelemetryPreview>;
   // Read currently running applications for manual selection.
   loadRunningApps: () => Promise<RunningAppInfo[]>;
   // Dismiss first-run 1Password suggestion and refresh snapshot.
   dismissOnePasswordSuggestion: () => Promise<void>;
   // Accept 1Password suggestion (add exclusion) and refresh snapshot.
   acceptOnePasswordSuggestion: () => Promise<void>;
+  // Exclude an app suggested as game/navigation input; it is not suggested again.
+  acceptNonTypingSuggestion: (bundleId: string) => Promise<void>;
+  // Keep counting a suggested app; it is not suggested again.
+  dismissNonTypingSuggestion: (bundleId: string) => Promise<void>;
 };
 
 const SettingsContext = createContext<SettingsContextValue | null>(null);
@@ -36,37 +119,223 @@ export function SettingsProvider({
   onSnapshotChange,
   children,
 }: SettingsProviderProps) {
+  const [effective, setEffective] = useState<EffectiveConfig | null>(null);
+
+  const refreshConfig = useCallback(async () => {
+    setEffective(await invoke<EffectiveConfig>("get_config"));
+  }, []);
+
+  useEffect(() => {
+    refreshConfig().catch(() => undefined);
+  }, [refreshConfig]);
+
+  if (!effective) {
+    return null;
+  }
+  const config = effective.config;
+
   // All settings mutations go through backend commands and return the latest snapshot.
-  // We always write that snapshot back to keep UI state in sync with Rust-side state.
+  // We write that snapshot back and re-read the effective config to stay in sync with Rust.
+  const applySnapshot = async (data: Snapshot) => {
+    onSnapshotChange(data);
+    await refreshConfig();
+  };
+
   const togglePause = async () => {
     const data = await invoke<Snapshot>("update_paused", {
       paused: !snapshot.paused,
     });
-    onSnapshotChange(data);
+    await applySnapshot(data);
   };
 
   const toggleIgnoreKeyCombos = async () => {
     const data = await invoke<Snapshot>("update_ignore_key_combos", {
-      ignoreKeyCombos: !snapshot.ignore_key_combos,
+      ignoreKeyCombos: !config.ignore_key_combos,
     });
-    onSnapshotChange(data);
+    await applySnapshot(data);
+  };
+
+  const toggleTrackChordAborts = async () => {
+    const data = await invoke<Snapshot>("update_track_chord_aborts", {
+      trackChordAborts: !config.track_chord_aborts,
+    });
+    await applySnapshot(data);
+  };
+
+  const toggleShortcutsPaused = async () => {
+    const data = await invoke<Snapshot>("update_shortcuts_paused", {
+      shortcutsPaused: !snapshot.shortcuts_paused,
+    });
+    await applySnapshot(data);
+  };
+
+  const updateSymbolKeyNames = async (names: SymbolKeyNames) => {
+    const data = await invoke<Snapshot>("update_symbol_key_names", { names });
+    await applySnapshot(data);
+  };
+
+  const togglePreventAppNap = async () => {
+    const data = await invoke<Snapshot>("update_prevent_app_nap", {
+      preventAppNap: !config.prevent_app_nap,
+    });
+    await applySnapshot(data);
+  };
+
+  const toggleRecordEventChunks = async () => {
+    const data = await invoke<Snapshot>("update_record_event_chunks", {
+      recordEventChunks: !config.record_event_chunks,
+    });
+    await applySnapshot(data);
+  };
+
+  const toggleAxSensitiveDetection = async () => {
+    const data = await invoke<Snapshot>("update_ax_sensitive_detection", {
+      axSensitiveDetection: !config.ax_sensitive_detection,
+    });
+    await applySnapshot(data);
+  };
+
+  const toggleWriteStatusFile = async () => {
+    const data = await invoke<Snapshot>("update_write_status_file", {
+      writeStatusFile: !config.write_status_file,
+    });
+    await applySnapshot(data);
+  };
+
+  const toggleAppleScriptEnabled = async () => {
+    const data = await invoke<Snapshot>("update_applescript_enabled", {
+      applescriptEnabled: !config.applescript_enabled,
+    });
+    await applySnapshot(data);
   };
 
   const updateTrayDisplayMode = async (mode: MenuBarDisplayMode) => {
     const data = await invoke<Snapshot>("update_menu_bar_display_mode", {
       mode,
     });
-    onSnapshotChange(data);
+    await applySnapshot(data);
+  };
+
+  const updateTrayLeftClickAction = async (action: TrayLeftClickAction) => {
+    const data = await invoke<Snapshot>("update_tray_left_click_action", {
+      action,
+    });
+    await applySnapshot(data);
+  };
+
+  const toggleStartHidden = async () => {
+    const data = await invoke<Snapshot>("update_start_hidden", {
+      startHidden: !config.start_hidden,
+    });
+    await applySnapshot(data);
+  };
+
+  const toggleAutoUpdateCheck = async () => {
+    const data = await invoke<Snapshot>("update_auto_update_check", {
+      autoUpdateCheck: !config.auto_update_check,
+    });
+    await applySnapshot(data);
+  };
+
+  const checkForUpdates = async () => {
+    const info = await invoke<UpdateInfo>("check_for_updates");
+    onSnapshotChange(await invoke<Snapshot>("get_snapshot"));
+    return info;
+  };
+
+  const resetWindowPosition = async () => {
+    await invoke("reset_window_position");
+    await refreshConfig();
+  };
+
+  const updateTimingSettings = async (timing: TimingSettings) => {
+    const data = await invoke<Snapshot>("update_timing_settings", {
+      collectorTickIntervalSecs: timing.collector_tick_interval_secs,
+      flushIntervalSecs: timing.flush_interval_secs,
+      sessionGapSecs: timing.session_gap_secs,
+      trayUpdateIntervalSecs: timing.tray_update_interval_secs,
+      maintenanceIdleSecs: timing.maintenance_idle_secs,
+      maintenanceMaxDeferSecs: timing.maintenance_max_defer_secs,
+    });
+    await applySnapshot(data);
+  };
+
+  const updateWindowTitleMode = async (mode: WindowTitleMode) => {
+    const data = await invoke<Snapshot>("update_window_title_mode", { mode });
+    await applySnapshot(data);
+  };
+
+  const setAppTitlePrivacy = async (bundleId: string, mode: WindowTitleMode) => {
+    const data = await invoke<Snapshot>("set_app_title_privacy", { bundleId, mode });
+    await applySnapshot(data);
+  };
+
+  const removeAppTitlePrivacy = async (bundleId: string) => {
+    const data = await invoke<Snapshot>("remove_app_title_privacy", { bundleId });
+    await applySnapshot(data);
+  };
+
+  const updateAppTimeGoal = async (bundleId: string, targetMin: number) => {
+    const data = await invoke<Snapshot>("update_app_time_goal", { bundleId, targetMin });
+    await applySnapshot(data);
+  };
+
+  const removeAppTimeGoal = async (bundleId: string) => {
+    const data = await invoke<Snapshot>("remove_app_time_goal", { bundleId });
+    await applySnapshot(data);
+  };
+
+  const toggleAppTimeGoalNotifications = async () => {
+    const data = await invoke<Snapshot>("update_app_time_goal_notifications", {
+      enabled: !config.app_time_goal_notifications,
+    });
+    await applySnapshot(data);
+  };
+
+  const toggleWeeklyComparisonNotifications = async () => {
+    const data = await invoke<Snapshot>("update_weekly_comparison_notifications", {
+      enabled: !config.weekly_comparison_notifications,
+    });
+    await applySnapshot(data);
+  };
+
+  const updateAutoExport = async (autoExport: AutoExportConfig) => {
+    const data = await invoke<Snapshot>("update_auto_export", {
+      enabled: autoExport.enabled,
+      format: autoExport.format,
+      destDir: autoExport.dest_dir,
+      scriptPath: autoExport.script_path,
+      sign: autoExport.sign,
+    });
+    await applySnapshot(data);
+  };
+
+  const updateCsvDialect = async (dialect: CsvDialect) => {
+    const data = await invoke<Snapshot>("update_csv_dialect", {
+      delimiter: dialect.delimiter,
+      utf8Bom: dialect.utf8_bom,
+      headersLocalized: dialect.headers_localized,
+    });
+    await applySnapshot(data);
   };
 
   const addAppExclusion = async (bundleId: string) => {
     const data = await invoke<Snapshot>("add_app_exclusion", { bundleId });
-    onSnapshotChange(data);
+    await applySnapshot(data);
   };
 
   const removeAppExclusion = async (bundleId: string) => {
     const data = await invoke<Snapshot>("remove_app_exclusion", { bundleId });
-    onSnapshotChange(data);
+    await applySnapshot(data);
+  };
+
+  const setTelemetryConsent = async (enabled: boolean, endpoint: string) => {
+    const data = await invoke<Snapshot>("set_telemetry_consent", { enabled, endpoint });
+    await applySnapshot(data);
+  };
+
+  const loadTelemetryPreview = async () => {
+    return invoke<TelemetryPreview>("get_telemetry_preview");
   };
 
   const loadRunningApps = async () => {
@@ -75,26 +344,79 @@ export function SettingsProvider({
 
   const dismissOnePasswordSuggestion = async () => {
     const data = await invoke<Snapshot>("dismiss_one_password_suggestion");
-    onSnapshotChange(data);
+    await applySnapshot(data);
+  };
+
+  const switchProfile = async (name: string) => {
+    const data = await invoke<Snapshot>("switch_profile", { name });
+    await applySnapshot(data);
+  };
+
+  const runDestructiveAction = async (preview: DestructivePreview) => {
+    const data = await invoke<Snapshot>(DESTRUCTIVE_ACTION_COMMANDS[preview.action], {
+      confirmToken: preview.confirm_token,
+    });
+    await applySnapshot(data);
   };
 
   const acceptOnePasswordSuggestion = async () => {
     const data = await invoke<Snapshot>("accept_one_password_suggestion");
-    onSnapshotChange(data);
+    await applySnapshot(data);
+  };
+
+  const acceptNonTypingSuggestion = async (bundleId: string) => {
+    const data = await invoke<Snapshot>("accept_non_typing_suggestion", { bundleId });
+    await applySnapshot(data);
+  };
+
+  const dismissNonTypingSuggestion = async (bundleId: string) => {
+    const data = await invoke<Snapshot>("dismiss_non_typing_suggestion", { bundleId });
+    await applySnapshot(data);
   };
 
   return (
     <SettingsContext.Provider
       value={{
         snapshot,
+        config,
+        configSource: effective.config_source,
         togglePause,
         toggleIgnoreKeyCombos,
+        toggleTrackChordAborts,
+        toggleShortcutsPaused,
+        updateSymbolKeyNames,
+        togglePreventAppNap,
+        toggleRecordEventChunks,
+        toggleAxSensitiveDetection,
+        toggleWriteStatusFile,
+        toggleAppleScriptEnabled,
+        updateTimingSettings,
         updateTrayDisplayMode,
+        updateTrayLeftClickAction,
+        toggleStartHidden,
+        toggleAutoUpdateCheck,
+        checkForUpdates,
+        resetWindowPosition,
+        updateWindowTitleMode,
+        setAppTitlePrivacy,
+        removeAppTitlePrivacy,
+        updateAppTimeGoal,
+        removeAppTimeGoal,
+        toggleAppTimeGoalNotifications,
+        toggleWeeklyComparisonNotifications,
+        updateAutoExport,
+        updateCsvDialect,
         addAppExclusion,
         removeAppExclusion,
+        switchProfile,
+        runDestructiveAction,
+        setTelemetryConsent,
+        loadTelemetryPreview,
         loadRunningApps,
         dismissOnePasswordSuggestion,
         acceptOnePasswordSuggestion,
+        acceptNonTypingSuggestion,
+        dismissNonTypingSuggestion,
       }}
     >
       {children}
@@ -1,15 +1,180 @@
 import { invoke } from "@tauri-apps/api/core";
-import { Box, Button, Text } from "@chakra-ui/react";
+import { Badge, Box, Button, HStack, Input, Stack, Text } from "@chakra-ui/react";
 import { useEffect, useState } from "react";
+import {
+  AnalyticsStats,
+  AppPurgeReport,
+  ArchiveReport,
+  ConfigSource,
+  DataDirSize,
+  DestructiveAction,
+  DestructivePreview,
+  TimingSettings,
+} from "../../types";
 import { glassSurfaceStyle } from "../../styles/glass";
+import { useSettingsContext } from "./SettingsContext";
+
+function ConfigSourceBadge({ source }: { source: ConfigSource }) {
+  return source === "user" ? (
+    <Badge bg="#e0ecff" color="#1d4ed8">自定义</Badge>
+  ) : (
+    <Badge bg="rgba(255,255,255,0.62)" color="#6b7280">默认</Badge>
+  );
+}
+
+// One-line summary of a purge report, shared by the preview and the final result.
+function purgeSummary(report: AppPurgeReport): string {
+  return [
+    `${report.stats_rows} 行统计（${report.key_count} 次按键）`,
+    `${report.shortcut_uses} 次快捷键`,
+    `${report.event_chunks} 个事件分段（${report.events} 个事件）`,
+    `${report.chord_aborts} 次未完成组合键`,
+    `${report.app_switches} 次应用切换`,
+  ].join("，");
+}
+
+// Editable timing fields with their accepted ranges (seconds), mirroring the backend checks.
+const TIMING_FIELDS: { key: keyof TimingSettings; label: string; min: number; max: number }[] = [
+  { key: "collector_tick_interval_secs", label: "采集轮询", min: 1, max: 60 },
+  { key: "flush_interval_secs", label: "刷盘周期", min: 5, max: 3600 },
+  { key: "session_gap_secs", label: "会话间隔", min: 1, max: 600 },
+  { key: "tray_update_interval_secs", label: "托盘刷新", min: 1, max: 60 },
+  { key: "maintenance_idle_secs", label: "维护空闲等待", min: 5, max: 600 },
+  { key: "maintenance_max_defer_secs", label: "维护最长推迟", min: 60, max: 21600 },
+];
+
+const DESTRUCTIVE_ACTION_LABELS: Record<DestructiveAction, string> = {
+  reset_today: "清空今天",
+  reset_all: "清空全部",
+  prune_retention: "立即执行保留策略",
+};
 
 function StorageSettingsSection() {
-  const [dataSize, setDataSize] = useState<number | null>(null);
+  const { config, configSource, runDestructiveAction, switchProfile, updateTimingSettings } =
+    useSettingsContext();
+  const [timingDraft, setTimingDraft] = useState<TimingSettings>({
+    collector_tick_interval_secs: config.collector_tick_interval_secs,
+    flush_interval_secs: config.flush_interval_secs,
+    session_gap_secs: config.session_gap_secs,
+    tray_update_interval_secs: config.tray_update_interval_secs,
+    maintenance_idle_secs: config.maintenance_idle_secs,
+    maintenance_max_defer_secs: config.maintenance_max_defer_secs,
+  });
+  const [timingMessage, setTimingMessage] = useState<string | null>(null);
+  const [dataSize, setDataSize] = useState<DataDirSize | null>(null);
+  const [rollupMessage, setRollupMessage] = useState<string | null>(null);
+  const [analyticsStats, setAnalyticsStats] = useState<AnalyticsStats | null>(null);
+  const [purgeBundleId, setPurgeBundleId] = useState("");
+  const [purgePreview, setPurgePreview] = useState<AppPurgeReport | null>(null);
+  const [purgeMessage, setPurgeMessage] = useState<string | null>(null);
+  const [resetPreview, setResetPreview] = useState<DestructivePreview | null>(null);
+  const [resetMessage, setResetMessage] = useState<string | null>(null);
+  const [profileMessage, setProfileMessage] = useState<string | null>(null);
+  // The implicit default profile first, then the configured ones, as in the tray menu.
+  const profileNames = [
+    "default",
+    ...config.profiles
+      .map((profile) => profile.name)
+      .filter((name) => name && name !== "default"),
+  ];
 
   const handleOpenDataDir = async () => {
     await invoke("open_data_dir");
   };
 
+  const handleSwitchProfile = async (name: string) => {
+    try {
+      await switchProfile(name);
+      setProfileMessage(`已切换到数据配置 ${name}。`);
+    } catch (error) {
+      setProfileMessage(`切换失败：${String(error)}`);
+    }
+  };
+
+  const handleRollupNow = async () => {
+    try {
+      const days = await invoke<number>("rollup_old_stats_now");
+      setRollupMessage(days > 0 ? `已将 ${days} 天的历史数据按小时汇总。` : "没有需要汇总的历史数据。");
+    } catch (error) {
+      setRollupMessage(`汇总失败：${String(error)}`);
+    }
+  };
+
+  const handleArchiveNow = async () => {
+    try {
+      const report = await invoke<ArchiveReport>("archive_old_months_now");
+      setRollupMessage(
+        report.files > 0
+          ? `已将 ${report.files} 个每日文件归档到 ${report.months.join("、")} 的 zip 归档。`
+          : "没有需要归档的旧文件。",
+      );
+      setDataSize(await invoke<DataDirSize>("get_data_dir_size"));
+    } catch (error) {
+      setRollupMessage(`归档失败：${String(error)}`);
+    }
+  };
+
+  const handleSaveTiming = async () => {
+    try {
+      await updateTimingSettings(timingDraft);
+      setTimingMessage("已保存，无需重启即可生效。");
+    } catch (error) {
+      setTimingMessage(`保存失败：${String(error)}`);
+    }
+  };
+
+  // Preview first: the purge button only appears for the bundle ID that was previewed.
+  const handlePurgePreview = async () => {
+    try {
+      const report = await invoke<AppPurgeReport>("purge_app_data", {
+        bundleId: purgeBundleId.trim(),
+        dryRun: true,
+      });
+      setPurgePreview(report);
+      setPurgeMessage(`将删除：${purgeSummary(report)}。`);
+    } catch (error) {
+      setPurgePreview(null);
+      setPurgeMessage(`预览失败：${String(error)}`);
+    }
+  };
+
+  const handlePurge = async () => {
+    if (!purgePreview) return;
+    try {
+      const report = await invoke<AppPurgeReport>("purge_app_data", {
+        bundleId: purgePreview.bundle_id,
+        dryRun: false,
+      });
+      setPurgeMessage(`已删除 ${report.bundle_id}：${purgeSummary(report)}，审计记录已写入应用日志。`);
+    } catch (error) {
+      setPurgeMessage(`删除失败：${String(error)}`);
+    }
+    setPurgePreview(null);
+  };
+
+  // Reset buttons only preview; the backend refuses the reset without the preview's token.
+  const handleResetPreview = async (action: DestructiveAction) => {
+    try {
+      const preview = await invoke<DestructivePreview>("preview_destructive_action", { action });
+      setResetPreview(preview);
+      setResetMessage(null);
+    } catch (error) {
+      setResetPreview(null);
+      setResetMessage(`预览失败：${String(error)}`);
+    }
+  };
+
+  const handleResetConfirm = async () => {
+    if (!resetPreview) return;
+    try {
+      await runDestructiveAction(resetPreview);
+      setResetMessage(`${DESTRUCTIVE_ACTION_LABELS[resetPreview.action]}已完成，记录已写入应用日志。`);
+    } catch (error) {
+      setResetMessage(`执行失败：${String(error)}`);
+    }
+    setResetPreview(null);
+  };
+
   const formatBytes = (bytes: number) => {
     if (bytes < 1024) return `${bytes} B`;
     const units = ["KB", "MB", "GB", "TB"];
@@ -25,18 +190,27 @@ function StorageSettingsSection() {
 
   useEffect(() => {
     let mounted = true;
-    invoke<number>("get_data_dir_size")
+    invoke<DataDirSize>("get_data_dir_size")
       .then((size) => {
         if (mounted) setDataSize(size);
       })
       .catch(() => {
         if (mounted) setDataSize(null);
       });
+    invoke<AnalyticsStats>("get_analytics_stats")
+      .then((stats) => {
+        if (mounted) setAnalyticsStats(stats);
+      })
+      .catch(() => {
+        if (mounted) setAnalyticsStats(null);
+      });
     return () => {
       mounted = false;
     };
   }, []);
 
+  const formatDay = (ms: number | null) => (ms === null ? "—" : new Date(ms).toLocaleDateString());
+
   return (
     <Box {...glassSurfaceStyle} borderRadius="12px" p="0" overflow="hidden">
       <Box px="5" py="4" borderBottomWidth="1px" borderColor="glass.borderSoft">
@@ -45,18 +219,201 @@ function StorageSettingsSection() {
       <Box px="5" py="4">
         <Text fontSize="sm" color="#6b7280" mb="1">数据与日志保存在本机应用数据目录。</Text>
         {dataSize !== null ? (
-          <Text fontSize="sm" color="#6b7280" mb="4">已用空间：{formatBytes(dataSize)}</Text>
+          <Text fontSize="sm" color="#6b7280" mb="4">
+            已用空间：{formatBytes(dataSize.total_bytes)}
+            {dataSize.archive_files > 0
+              ? `（其中 ${dataSize.archive_files} 个月度归档 ${formatBytes(dataSize.archive_bytes)}）`
+              : null}
+          </Text>
+        ) : null}
+        {analyticsStats ? (
+          <Stack gap="1" mb="4">
+            <Text fontSize="sm" color="#6b7280">
+              输入事件分段：{analyticsStats.chunk_count} / {analyticsStats.max_chunks}（
+              {analyticsStats.total_events} 个事件，约 {formatBytes(analyticsStats.approx_bytes)}）
+            </Text>
+            <Text fontSize="sm" color="#6b7280">
+              可回放范围：{formatDay(analyticsStats.oldest_chunk_ms)} – {formatDay(analyticsStats.newest_chunk_ms)}
+            </Text>
+            <Text fontSize="xs" color="#8b939f">
+              近 7 天事件数：{analyticsStats.events_by_day.map((day) => day.events).join(" / ")}
+            </Text>
+            {analyticsStats.chunk_count >= analyticsStats.max_chunks ? (
+              <Text fontSize="xs" color="#b45309">已达到分段上限，最早的可回放记录会被丢弃。</Text>
+            ) : null}
+          </Stack>
+        ) : null}
+        <HStack gap="2" flexWrap="wrap">
+          <Button
+            onClick={handleOpenDataDir}
+            bg="rgba(255,255,255,0.62)"
+            color="#1f2328"
+            borderWidth="1px"
+            borderColor="glass.borderSoft"
+            _hover={{ bg: "rgba(255,255,255,0.8)" }}
+          >
+            前往数据目录
+          </Button>
+          <Button
+            onClick={handleRollupNow}
+            bg="rgba(255,255,255,0.62)"
+            color="#1f2328"
+            borderWidth="1px"
+            borderColor="glass.borderSoft"
+            _hover={{ bg: "rgba(255,255,255,0.8)" }}
+          >
+            按小时汇总旧数据
+          </Button>
+          <Button
+            onClick={handleArchiveNow}
+            bg="rgba(255,255,255,0.62)"
+            color="#1f2328"
+            borderWidth="1px"
+            borderColor="glass.borderSoft"
+            _hover={{ bg: "rgba(255,255,255,0.8)" }}
+          >
+            归档旧月份文件
+          </Button>
+        </HStack>
+        <Stack gap="1" mt="4">
+          <HStack gap="2" flexWrap="wrap">
+            {TIMING_FIELDS.map((field) => (
+              <HStack key={field.key} gap="1">
+                <Text fontSize="sm" color="#6b7280">{field.label}</Text>
+                <Input
+                  size="xs"
+                  width="72px"
+                  type="number"
+                  min={field.min}
+                  max={field.max}
+                  title={`${field.min}–${field.max} 秒`}
+                  value={timingDraft[field.key]}
+                  onChange={(event) =>
+                    setTimingDraft({ ...timingDraft, [field.key]: Number(event.target.value) })
+                  }
+                />
+                <Text fontSize="sm" color="#6b7280">秒</Text>
+              </HStack>
+            ))}
+            <Button size="xs" onClick={handleSaveTiming}>保存</Button>
+            <ConfigSourceBadge source={configSource.timing} />
+          </HStack>
+          {timingMessage ? (
+            <Text fontSize="xs" color="#6b7280">{timingMessage}</Text>
+          ) : null}
+          <HStack gap="2">
+            <Text fontSize="sm" color="#6b7280">分钟级明细保留：{config.minute_resolution_days} 天</Text>
+            <ConfigSourceBadge source={configSource.storage} />
+          </HStack>
+        </Stack>
+        {config.profiles.length > 0 ? (
+          <Stack gap="1" mt="4">
+            <HStack gap="2" flexWrap="wrap">
+              <Text fontSize="sm" color="#6b7280">数据配置</Text>
+              {profileNames.map((name) => (
+                <Button
+                  key={name}
+                  size="xs"
+                  variant={name === config.active_profile ? "solid" : "outline"}
+                  disabled={name === config.active_profile}
+                  onClick={() => handleSwitchProfile(name)}
+                >
+                  {name}
+                </Button>
+              ))}
+              <ConfigSourceBadge source={configSource.profiles} />
+            </HStack>
+            <Text fontSize="xs" color="#8b939f">各配置使用独立的数据子目录，可在配置文件中为其单独设置忽略应用与快捷键规则，或按时间段与前台应用自动切换；切换前会先保存当前数据，手动切换后 {config.profile_auto_cooldown_mins} 分钟内不自动切换。</Text>
+            {profileMessage ? (
+              <Text fontSize="xs" color="#6b7280">{profileMessage}</Text>
+            ) : null}
+          </Stack>
+        ) : null}
+        <Text fontSize="xs" color="#8b939f" mt="2">超过保留天数的分钟级明细会每天自动按小时汇总以缩小存储；上个月之前的每日文件每月自动打包为 zip 归档。</Text>
+        {rollupMessage ? (
+          <Text fontSize="sm" color="#6b7280" mt="2">{rollupMessage}</Text>
         ) : null}
-        <Button
-          onClick={handleOpenDataDir}
-          bg="rgba(255,255,255,0.62)"
-          color="#1f2328"
-          borderWidth="1px"
-          borderColor="glass.borderSoft"
-          _hover={{ bg: "rgba(255,255,255,0.8)" }}
-        >
-          前往数据目录
-        </Button>
+        <Box mt="5">
+          <Text fontSize="sm" color="#374151" mb="1">删除某个应用的全部数据</Text>
+          <HStack gap="2">
+            <Input
+              size="sm"
+              flex="1"
+              fontFamily="mono"
+              placeholder="com.example.app"
+              value={purgeBundleId}
+              onChange={(event) => {
+                setPurgeBundleId(event.target.value);
+                setPurgePreview(null);
+              }}
+            />
+            <Button
+              size="sm"
+              onClick={handlePurgePreview}
+              disabled={!purgeBundleId.trim()}
+              bg="rgba(255,255,255,0.62)"
+              color="#1f2328"
+              borderWidth="1px"
+              borderColor="glass.borderSoft"
+              _hover={{ bg: "rgba(255,255,255,0.8)" }}
+            >
+              预览
+            </Button>
+            {purgePreview ? (
+              <Button size="sm" colorPalette="red" onClick={handlePurge}>
+                确认删除
+              </Button>
+            ) : null}
+          </HStack>
+          <Text fontSize="xs" color="#8b939f" mt="1">
+            从统计、快捷键、事件记录与 CSV 中移除该应用，已导出到自动导出目录的文件不受影响。
+          </Text>
+          {purgeMessage ? (
+            <Text fontSize="sm" color="#6b7280" mt="2">{purgeMessage}</Text>
+          ) : null}
+        </Box>
+        <Box mt="5">
+          <Text fontSize="sm" color="#374151" mb="1">清空或精简数据</Text>
+          <HStack gap="2" flexWrap="wrap">
+            {(Object.keys(DESTRUCTIVE_ACTION_LABELS) as DestructiveAction[]).map((action) => (
+              <Button
+                key={action}
+                size="sm"
+                onClick={() => handleResetPreview(action)}
+                bg="rgba(255,255,255,0.62)"
+                color="#1f2328"
+                borderWidth="1px"
+                borderColor="glass.borderSoft"
+                _hover={{ bg: "rgba(255,255,255,0.8)" }}
+              >
+                {DESTRUCTIVE_ACTION_LABELS[action]}
+              </Button>
+            ))}
+          </HStack>
+          {resetPreview ? (
+            <Stack gap="2" mt="2">
+              <Text fontSize="sm" color="#6b7280">
+                {DESTRUCTIVE_ACTION_LABELS[resetPreview.action]}将影响：{resetPreview.stats_rows} 行统计，
+                {resetPreview.event_chunks} 个事件分段，{resetPreview.shortcut_entries} 个快捷键条目，
+                {resetPreview.files} 个文件（{formatBytes(resetPreview.file_bytes)}）。
+              </Text>
+              <HStack gap="2">
+                <Button size="sm" colorPalette="red" onClick={handleResetConfirm}>
+                  确认执行
+                </Button>
+                <Button size="sm" variant="ghost" onClick={() => setResetPreview(null)}>
+                  取消
+                </Button>
+              </HStack>
+              <Text fontSize="xs" color="#8b939f">
+                确认需在 {Math.round(resetPreview.expires_in_ms / 1000)} 秒内完成，超时请重新预览。
+              </Text>
+            </Stack>
+          ) : null}
+          {resetMessage ? (
+            <Text fontSize="sm" color="#6b7280" mt="2">{resetMessage}</Text>
+          ) : null}
+        </Box>
       </Box>
     </Box>
   );
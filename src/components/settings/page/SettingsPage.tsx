@@ -2,6 +2,9 @@ import { Box, Button, HStack, Stack, Text } from "@chakra-ui/react";
 import { Snapshot } from "../../../types";
 import CaptureSettingsSection from "../CaptureSettingsSection";
 import DisplaySettingsSection from "../DisplaySettingsSection";
+import ExportSettingsSection from "../ExportSettingsSection";
+import GoalSettingsSection from "../GoalSettingsSection";
+import PrivacySettingsSection from "../PrivacySettingsSection";
 import { SettingsProvider } from "../SettingsContext";
 import StorageSettingsSection from "../StorageSettingsSection";
 
@@ -57,6 +60,26 @@ function SettingsPage({ snapshot, onSnapshotChange }: SettingsPageProps) {
             >
               Appearance
             </Button>
+            <Button
+              size="sm"
+              variant="ghost"
+              borderRadius="999px"
+              bg="rgba(255,255,255,0.68)"
+              _hover={{ bg: "rgba(255,255,255,0.8)" }}
+              onClick={() => scrollToAnchor("settings-goals")}
+            >
+              Goals
+            </Button>
+            <Button
+              size="sm"
+              variant="ghost"
+              borderRadius="999px"
+              bg="rgba(255,255,255,0.68)"
+              _hover={{ bg: "rgba(255,255,255,0.8)" }}
+              onClick={() => scrollToAnchor("settings-privacy")}
+            >
+              Privacy
+            </Button>
             <Button
               size="sm"
               variant="ghost"
@@ -76,8 +99,17 @@ function SettingsPage({ snapshot, onSnapshotChange }: SettingsPageProps) {
         <Box id="settings-appearance">
           <DisplaySettingsSection />
         </Box>
+        <Box id="settings-goals">
+          <GoalSettingsSection />
+        </Box>
+        <Box id="settings-privacy">
+          <PrivacySettingsSection />
+        </Box>
         <Box id="settings-storage">
-          <StorageSettingsSection />
+          <Stack gap="4">
+            <StorageSettingsSection />
+            <ExportSettingsSection />
+          </Stack>
         </Box>
       </Stack>
     </SettingsProvider>
@@ -1 +1 @@
-export type SettingSection = "capture" | "display" | "storage";
+export type SettingSection = "capture" | "display" | "goals" | "privacy" | "storage";
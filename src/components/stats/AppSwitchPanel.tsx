@@ -0,0 +1,65 @@
+import { Badge, Box, HStack, Stack, Text } from "@chakra-ui/react";
+import { AppSwitchStats } from "../../types";
+import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+
+type AppSwitchPanelProps = {
+  stats: AppSwitchStats | null;
+};
+
+function AppSwitchPanel({ stats }: AppSwitchPanelProps) {
+  const pairs = stats?.top_pairs ?? [];
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="16px" p="6" h="full">
+      <HStack justify="space-between" mb="4" align="center">
+        <Text fontSize="xl" fontWeight="semibold">
+          应用切换
+        </Text>
+        <Text fontSize="sm" color="gray.600">
+          输入过程中共 {stats?.total ?? 0} 次
+        </Text>
+      </HStack>
+
+      {pairs.length === 0 ? (
+        <Text color="gray.500" py="2">
+          当前时间范围内暂无输入过程中的应用切换。
+        </Text>
+      ) : (
+        <Box {...glassSubtleStyle} borderRadius="12px" p="4">
+          <Text fontSize="sm" fontWeight="semibold" color="gray.700" mb="3">
+            最常见的切换
+          </Text>
+          <Stack gap="2">
+            {pairs.map((pair, index) => (
+              <HStack
+                key={`${pair.from}-${pair.to}`}
+                justify="space-between"
+                gap="3"
+                px="3"
+                py="2"
+                borderRadius="8px"
+                bg="rgba(255,255,255,0.24)"
+                borderWidth="1px"
+                borderColor="glass.borderSoft"
+              >
+                <HStack gap="2.5" minW="0">
+                  <Badge colorPalette="purple" variant="subtle" size="sm">
+                    {index + 1}
+                  </Badge>
+                  <Text fontSize="sm" truncate title={`${pair.from} → ${pair.to}`}>
+                    {pair.from} → {pair.to}
+                  </Text>
+                </HStack>
+                <Badge variant="outline" color="gray.700" flexShrink={0}>
+                  {pair.count}
+                </Badge>
+              </HStack>
+            ))}
+          </Stack>
+        </Box>
+      )}
+    </Box>
+  );
+}
+
+export default AppSwitchPanel;
@@ -34,6 +34,9 @@ function AppTable({ rows }: AppTableProps) {
           <Text flex="1" textAlign="right">
             会话
           </Text>
+          <Text flex="1" textAlign="right" title="每天打开过的不同窗口数之和">
+            窗口
+          </Text>
         </HStack>
         {rows.length === 0 ? (
           <Text px="4" py="6" color="gray.500" textAlign="center">
@@ -77,6 +80,9 @@ function AppTable({ rows }: AppTableProps) {
                 <Text flex="1" textAlign="right">
                   {row.session_count}
                 </Text>
+                <Text flex="1" textAlign="right">
+                  {row.distinct_windows}
+                </Text>
               </HStack>
             );
           })
@@ -0,0 +1,105 @@
+import { Box, HStack, Stack, Text } from "@chakra-ui/react";
+import { AppTimeline } from "../../types";
+import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+import { formatMs } from "../../utils/stats";
+
+type AppTimelinePanelProps = {
+  timeline: AppTimeline | null;
+};
+
+const STRIP_LIMIT = 8;
+const HOUR_TICKS = [0, 6, 12, 18, 24];
+
+function AppTimelinePanel({ timeline }: AppTimelinePanelProps) {
+  const apps = timeline?.apps.slice(0, STRIP_LIMIT) ?? [];
+  const daySpan = timeline ? timeline.day_end_ms - timeline.day_start_ms : 0;
+  const bucketMs = (timeline?.bucket_minutes ?? 0) * 60_000;
+  const maxBucketKeys = Math.max(
+    ...apps.flatMap((app) => app.buckets.map((bucket) => bucket.key_count)),
+    0,
+  );
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="16px" p="6">
+      <HStack justify="space-between" mb="4" align="center">
+        <Text fontSize="xl" fontWeight="semibold">
+          应用时间轴
+        </Text>
+        <Text fontSize="sm" color="gray.600">
+          {timeline ? `${timeline.date} · 每 ${timeline.bucket_minutes} 分钟` : ""}
+        </Text>
+      </HStack>
+
+      {!timeline || apps.length === 0 || daySpan <= 0 ? (
+        <Text color="gray.500" py="2">
+          当天暂无可展示的应用输入时间轴。
+        </Text>
+      ) : (
+        <Box {...glassSubtleStyle} borderRadius="12px" p="4">
+          <Stack gap="2.5">
+            {apps.map((app) => (
+              <HStack key={app.app_name} gap="3" align="center">
+                <Text
+                  w="140px"
+                  flexShrink={0}
+                  fontSize="sm"
+                  fontWeight="semibold"
+                  truncate
+                  title={`${app.app_name} · ${app.key_count} 键 · ${formatMs(app.active_ms)}`}
+                >
+                  {app.app_name}
+                </Text>
+                <Box
+                  position="relative"
+                  flex="1"
+                  h="18px"
+                  borderRadius="6px"
+                  bg="rgba(255,255,255,0.24)"
+                  borderWidth="1px"
+                  borderColor="glass.borderSoft"
+                  overflow="hidden"
+                >
+                  {app.buckets.map((bucket) => {
+                    const left =
+                      ((bucket.bucket_start_ms - timeline.day_start_ms) / daySpan) * 100;
+                    const width = (bucketMs / daySpan) * 100;
+                    const intensity =
+                      maxBucketKeys > 0 ? bucket.key_count / maxBucketKeys : 0;
+                    return (
+                      <Box
+                        key={bucket.bucket_start_ms}
+                        position="absolute"
+                        top="0"
+                        bottom="0"
+                        left={`${left}%`}
+                        width={`${width}%`}
+                        bg="blue.400"
+                        opacity={0.25 + intensity * 0.75}
+                        title={`${new Date(bucket.bucket_start_ms).toLocaleTimeString([], {
+                          hour: "2-digit",
+                          minute: "2-digit",
+                        })} · ${bucket.key_count} 键 · ${formatMs(bucket.active_ms)}`}
+                      />
+                    );
+                  })}
+                </Box>
+              </HStack>
+            ))}
+            <HStack gap="3">
+              <Box w="140px" flexShrink={0} />
+              <HStack flex="1" justify="space-between">
+                {HOUR_TICKS.map((hour) => (
+                  <Text key={hour} fontSize="xs" color="gray.500">
+                    {`${hour}`.padStart(2, "0")}:00
+                  </Text>
+                ))}
+              </HStack>
+            </HStack>
+          </Stack>
+        </Box>
+      )}
+    </Box>
+  );
+}
+
+export default AppTimelinePanel;
@@ -0,0 +1,91 @@
+import { Badge, Box, Grid, HStack, Stack, Text } from "@chakra-ui/react";
+import { BurstStats } from "../../types";
+import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+import { formatMs } from "../../utils/stats";
+
+type BurstPanelProps = {
+  stats: BurstStats | null;
+};
+
+function BurstPanel({ stats }: BurstPanelProps) {
+  const days = (stats?.days ?? []).filter((day) => day.burst_count > 0);
+  const metrics = [
+    { label: "连续输入段", value: `${stats?.burst_count ?? 0}` },
+    {
+      label: "平均长度",
+      value:
+        stats?.mean_keys != null
+          ? `${stats.mean_keys.toFixed(1)} 键 · ${formatMs(stats.mean_ms ?? 0)}`
+          : "—",
+    },
+    {
+      label: "最长",
+      value:
+        stats && stats.burst_count > 0
+          ? `${stats.longest_keys} 键 · ${formatMs(stats.longest_ms)}`
+          : "—",
+    },
+  ];
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="16px" p="6" h="full">
+      <HStack justify="space-between" mb="4" align="center">
+        <Text fontSize="xl" fontWeight="semibold">
+          连续输入
+        </Text>
+        {stats ? (
+          <Text fontSize="xs" color="gray.600">
+            ≥{stats.min_keys} 键，间隔 &lt;{(stats.max_gap_ms / 1000).toFixed(1)} 秒
+          </Text>
+        ) : null}
+      </HStack>
+
+      <Grid templateColumns="repeat(3, 1fr)" gap="3" mb="4">
+        {metrics.map((metric) => (
+          <Box key={metric.label} {...glassSubtleStyle} borderRadius="12px" p="3">
+            <Text fontSize="xs" color="gray.600">
+              {metric.label}
+            </Text>
+            <Text fontSize="sm" fontWeight="semibold">
+              {metric.value}
+            </Text>
+          </Box>
+        ))}
+      </Grid>
+
+      {days.length === 0 ? (
+        <Text color="gray.500" py="2">
+          当前时间范围内暂无连续输入段。
+        </Text>
+      ) : (
+        <Stack gap="2">
+          {days.map((day) => (
+            <HStack
+              key={day.date}
+              justify="space-between"
+              gap="3"
+              px="3"
+              py="2"
+              borderRadius="8px"
+              bg="rgba(255,255,255,0.24)"
+              borderWidth="1px"
+              borderColor="glass.borderSoft"
+            >
+              <Text fontSize="sm">{day.date}</Text>
+              <HStack gap="1.5" flexShrink={0}>
+                <Badge variant="subtle" colorPalette="purple" title="最长连续输入段（按键数）">
+                  最长 {day.longest_keys}
+                </Badge>
+                <Badge variant="outline" color="gray.700">
+                  {day.burst_count}
+                </Badge>
+              </HStack>
+            </HStack>
+          ))}
+        </Stack>
+      )}
+    </Box>
+  );
+}
+
+export default BurstPanel;
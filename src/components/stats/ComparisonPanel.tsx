@@ -0,0 +1,99 @@
+import { Box, Grid, HStack, Stack, Text } from "@chakra-ui/react";
+import { Comparison, ComparisonSide } from "../../types";
+import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+import { formatMs } from "../../utils/stats";
+
+type ComparisonPanelProps = {
+  comparison: Comparison | null;
+};
+
+type MetricKey = "key_count" | "active_typing_ms" | "session_count";
+
+const METRICS: { key: MetricKey; label: string; format: (value: number) => string }[] = [
+  { key: "key_count", label: "按键数", format: (value) => `${value}` },
+  { key: "active_typing_ms", label: "活跃时长", format: formatMs },
+  { key: "session_count", label: "会话数", format: (value) => `${value}` },
+];
+
+// Render a signed delta with an explicit sign so gains and drops read at a glance.
+function formatDelta(value: number, format: (value: number) => string): string {
+  if (value === 0) {
+    return "±0";
+  }
+  return `${value > 0 ? "+" : "-"}${format(Math.abs(value))}`;
+}
+
+function SideColumn({ side }: { side: ComparisonSide }) {
+  return (
+    <Box {...glassSubtleStyle} borderRadius="12px" p="4">
+      <Text fontSize="sm" fontWeight="semibold" color="gray.700" mb="3" truncate>
+        {side.subject}
+      </Text>
+      <Stack gap="1.5">
+        {METRICS.map((metric) => (
+          <HStack key={metric.key} justify="space-between">
+            <Text fontSize="sm" color="gray.600">
+              {metric.label}
+            </Text>
+            <Text fontSize="sm" fontWeight="semibold">
+              {metric.format(side[metric.key])}
+            </Text>
+          </HStack>
+        ))}
+        <HStack justify="space-between" align="start">
+          <Text fontSize="sm" color="gray.600">
+            Top 按键
+          </Text>
+          <Text fontSize="sm" textAlign="right">
+            {side.top_keys.map((row) => row.key).join(" · ") || "—"}
+          </Text>
+        </HStack>
+        <HStack justify="space-between" align="start">
+          <Text fontSize="sm" color="gray.600">
+            Top 快捷键
+          </Text>
+          <Text fontSize="sm" textAlign="right">
+            {side.top_shortcuts.map((row) => row.shortcut_id).join(" · ") || "—"}
+          </Text>
+        </HStack>
+      </Stack>
+    </Box>
+  );
+}
+
+function ComparisonPanel({ comparison }: ComparisonPanelProps) {
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="16px" p="6" h="full">
+      <HStack justify="space-between" mb="4" align="center">
+        <Text fontSize="xl" fontWeight="semibold">
+          对比
+        </Text>
+        <Text fontSize="sm" color="gray.600">
+          {comparison ? `${comparison.a.subject} → ${comparison.b.subject}` : ""}
+        </Text>
+      </HStack>
+
+      {!comparison ? (
+        <Text color="gray.500" py="2">
+          暂无可对比的数据。
+        </Text>
+      ) : (
+        <Stack gap="3">
+          <Grid templateColumns="1fr 1fr" gap="3">
+            <SideColumn side={comparison.a} />
+            <SideColumn side={comparison.b} />
+          </Grid>
+          <HStack gap="4" justify="center">
+            {METRICS.map((metric) => (
+              <Text key={metric.key} fontSize="sm" color="gray.700">
+                {metric.label} {formatDelta(comparison.delta[metric.key], metric.format)}
+              </Text>
+            ))}
+          </HStack>
+        </Stack>
+      )}
+    </Box>
+  );
+}
+
+export default ComparisonPanel;
@@ -1,6 +1,6 @@
 import { useMemo } from "react";
 import { Box, HStack, Portal, Stack, Text, Tooltip } from "@chakra-ui/react";
-import { StatsRow } from "../../types";
+import { DailyTotal } from "../../types";
 import { glassSurfaceStyle } from "../../styles/glass";
 import { buildContributionHeatmapData } from "../../utils/contribution";
 import { formatMs } from "../../utils/stats";
@@ -10,7 +10,7 @@ import {
 } from "./contributionTheme";
 
 type ContributionHeatmapProps = {
-  rows: StatsRow[];
+  rows: DailyTotal[];
   embedded?: boolean;
 };
 
@@ -1,41 +1,18 @@
 import { Badge, Box, HStack, Stack, Text } from "@chakra-ui/react";
 import { KeyUsageRow } from "../../types";
 import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+import { keyLabel } from "../../utils/stats";
 
 type DailyTopKeysPanelProps = {
   rows: KeyUsageRow[];
 };
 
+// Apps named under each key; the rest only count toward the row total.
+const APP_HINT_LIMIT = 2;
+
 const BAR_FILL_GRADIENT =
   "linear-gradient(90deg, rgba(147, 197, 253, 0.27) 0%, rgba(191, 219, 254, 0.20) 100%)";
 
-// Convert stored key ids to readable labels in daily top-key cards.
-function keyLabel(key: string): string {
-  const baseMap: Record<string, string> = {
-    space: "Space",
-    enter: "Enter",
-    tab: "Tab",
-    esc: "Esc",
-    backspace: "Backspace",
-    delete: "Delete",
-    left: "←",
-    right: "→",
-    up: "↑",
-    down: "↓",
-    command: "Cmd",
-    shift: "Shift",
-    control: "Ctrl",
-    option: "Opt",
-  };
-  if (baseMap[key]) {
-    return baseMap[key];
-  }
-  if (key.length === 1 && /[a-z]/.test(key)) {
-    return key.toUpperCase();
-  }
-  return key;
-}
-
 function DailyTopKeysPanel({ rows }: DailyTopKeysPanelProps) {
   const maxCount = Math.max(...rows.map((item) => item.count), 0);
   const totalCount = rows.reduce((sum, item) => sum + item.count, 0);
@@ -109,9 +86,19 @@ function DailyTopKeysPanel({ rows }: DailyTopKeysPanelProps) {
                       >
                         {index + 1}
                       </Badge>
-                      <Text fontSize="md" fontWeight="semibold">
-                        {keyLabel(item.key)}
-                      </Text>
+                      <Stack gap="0">
+                        <Text fontSize="md" fontWeight="semibold">
+                          {keyLabel(item.key)}
+                        </Text>
+                        {item.apps.length > 0 && (
+                          <Text fontSize="xs" color="gray.600" lineClamp={1}>
+                            {item.apps
+                              .slice(0, APP_HINT_LIMIT)
+                              .map((app) => `${app.app_name} ${app.count}`)
+                              .join(" · ")}
+                          </Text>
+                        )}
+                      </Stack>
                     </HStack>
                     <Badge
                       variant="outline"
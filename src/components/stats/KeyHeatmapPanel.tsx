@@ -0,0 +1,126 @@
+import { Badge, Box, Button, HStack, Text, Wrap } from "@chakra-ui/react";
+import { KeyboardLayout, KeyHeatmap } from "../../types";
+import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+
+type KeyHeatmapPanelProps = {
+  heatmap: KeyHeatmap | null;
+  layout: KeyboardLayout;
+  onLayoutChange: (value: KeyboardLayout) => void;
+};
+
+const LAYOUT_OPTIONS: { value: KeyboardLayout; label: string }[] = [
+  { value: "ansi", label: "ANSI" },
+  { value: "iso", label: "ISO" },
+];
+const OTHER_KEYS_LIMIT = 8;
+// Hatched fill for days the app was not running, so they do not read as days without typing.
+const UNTRACKED_DAY_BG =
+  "repeating-linear-gradient(135deg, rgba(107,114,128,0.28) 0 3px, transparent 3px 6px)";
+const DAY_COVERAGE_LABELS = {
+  not_running: "未运行",
+  idle: "运行中无输入",
+  active: "有输入",
+} as const;
+
+// Match the backend SVG: log-scale intensity so a few hot keys do not wash out the rest.
+function heatIntensity(count: number, maxCount: number): number {
+  if (count <= 0 || maxCount <= 0) return 0;
+  return Math.log1p(count) / Math.log1p(maxCount);
+}
+
+function KeyHeatmapPanel({ heatmap, layout, onLayoutChange }: KeyHeatmapPanelProps) {
+  const keys = heatmap?.keys ?? [];
+  const columns = Math.max(...keys.map((key) => key.x + key.width), 0);
+  const rows = Math.max(...keys.map((key) => key.row + key.height), 0);
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="16px" p="6">
+      <HStack justify="space-between" mb="4" align="center">
+        <Text fontSize="xl" fontWeight="semibold">
+          键盘热力图
+        </Text>
+        <HStack gap="1">
+          {LAYOUT_OPTIONS.map((option) => (
+            <Button
+              key={option.value}
+              size="xs"
+              variant={layout === option.value ? "solid" : "ghost"}
+              onClick={() => onLayoutChange(option.value)}
+            >
+              {option.label}
+            </Button>
+          ))}
+        </HStack>
+      </HStack>
+
+      {heatmap && heatmap.days.length > 1 ? (
+        <HStack gap="1" mb="3">
+          {heatmap.days.map((day) => (
+            <Box
+              key={day.date}
+              flex="1"
+              h="6px"
+              borderRadius="999px"
+              bg={day.coverage === "active" ? "rgba(220, 38, 38, 0.5)" : "rgba(17, 24, 39, 0.08)"}
+              backgroundImage={day.coverage === "not_running" ? UNTRACKED_DAY_BG : undefined}
+              title={`${day.date} · ${DAY_COVERAGE_LABELS[day.coverage]}`}
+            />
+          ))}
+        </HStack>
+      ) : null}
+
+      {!heatmap || heatmap.max_count === 0 || columns <= 0 ? (
+        <Text color="gray.500" py="2">
+          {heatmap && heatmap.days.length > 0 && heatmap.days.every((day) => day.coverage === "not_running")
+            ? "当前时间范围内应用未运行，没有采集数据。"
+            : "当前时间范围内暂无可展示的按键数据。"}
+        </Text>
+      ) : (
+        <Box {...glassSubtleStyle} borderRadius="12px" p="4">
+          <Box position="relative" w="full" style={{ aspectRatio: `${columns} / ${rows}` }}>
+            {keys.map((key) => {
+              const intensity = heatIntensity(key.count, heatmap.max_count);
+              return (
+                <Box
+                  key={key.key}
+                  position="absolute"
+                  left={`${(key.x / columns) * 100}%`}
+                  top={`${(key.row / rows) * 100}%`}
+                  width={`${(key.width / columns) * 100}%`}
+                  height={`${(key.height / rows) * 100}%`}
+                  p="2px"
+                  title={`${key.key} · ${key.count}`}
+                >
+                  <Box
+                    w="full"
+                    h="full"
+                    borderRadius="4px"
+                    borderWidth="1px"
+                    borderColor="glass.borderSoft"
+                    bg={`rgba(220, 38, 38, ${0.06 + intensity * 0.84})`}
+                  />
+                </Box>
+              );
+            })}
+          </Box>
+          {heatmap.other.length > 0 ? (
+            <HStack mt="3" align="start" gap="2">
+              <Text fontSize="xs" color="gray.600" flexShrink={0}>
+                布局外按键
+              </Text>
+              <Wrap gap="1.5">
+                {heatmap.other.slice(0, OTHER_KEYS_LIMIT).map((row) => (
+                  <Badge key={row.key} variant="outline" size="sm">
+                    {row.key} · {row.count}
+                  </Badge>
+                ))}
+              </Wrap>
+            </HStack>
+          ) : null}
+        </Box>
+      )}
+    </Box>
+  );
+}
+
+export default KeyHeatmapPanel;
@@ -0,0 +1,95 @@
+import { Badge, Box, Grid, HStack, Stack, Text } from "@chakra-ui/react";
+import { KeyHoldStats } from "../../types";
+import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+import { keyLabel } from "../../utils/stats";
+
+type KeyHoldPanelProps = {
+  stats: KeyHoldStats | null;
+};
+
+// Only the most pressed keys are listed; the summary covers every key.
+const KEY_HOLD_ROW_LIMIT = 10;
+
+function KeyHoldPanel({ stats }: KeyHoldPanelProps) {
+  const rows = (stats?.keys ?? []).filter((row) => row.count > 0).slice(0, KEY_HOLD_ROW_LIMIT);
+  const metrics = [
+    {
+      label: "平均按住",
+      value: stats && stats.matched > 0 ? `${Math.round(stats.mean_ms)} ms` : "—",
+    },
+    {
+      label: "P95",
+      value: stats && stats.matched > 0 ? `${stats.p95_ms} ms` : "—",
+    },
+    { label: "缺少抬起", value: `${stats?.unmatched_downs ?? 0}` },
+  ];
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="16px" p="6" h="full">
+      <HStack justify="space-between" mb="4" align="center">
+        <Text fontSize="xl" fontWeight="semibold">
+          按键按住时长
+        </Text>
+        {stats ? (
+          <Text fontSize="xs" color="gray.600">
+            共 {stats.matched} 次按键
+          </Text>
+        ) : null}
+      </HStack>
+
+      <Grid templateColumns="repeat(3, 1fr)" gap="3" mb="4">
+        {metrics.map((metric) => (
+          <Box key={metric.label} {...glassSubtleStyle} borderRadius="12px" p="3">
+            <Text fontSize="xs" color="gray.600">
+              {metric.label}
+            </Text>
+            <Text fontSize="sm" fontWeight="semibold">
+              {metric.value}
+            </Text>
+          </Box>
+        ))}
+      </Grid>
+
+      {rows.length === 0 ? (
+        <Text color="gray.500" py="2">
+          当前时间范围内暂无按键按住记录。
+        </Text>
+      ) : (
+        <Stack gap="2">
+          {rows.map((row) => (
+            <HStack
+              key={row.key}
+              justify="space-between"
+              gap="3"
+              px="3"
+              py="2"
+              borderRadius="8px"
+              bg="rgba(255,255,255,0.24)"
+              borderWidth="1px"
+              borderColor="glass.borderSoft"
+            >
+              <Text fontSize="sm" fontWeight="semibold">
+                {keyLabel(row.key)}
+              </Text>
+              <HStack gap="1.5" flexShrink={0}>
+                <Text fontSize="xs" color="gray.600">
+                  平均 {Math.round(row.mean_ms)} ms · P95 {row.p95_ms} ms · 最长 {row.max_ms} ms
+                </Text>
+                {row.unmatched_downs > 0 ? (
+                  <Badge variant="subtle" colorPalette="orange" title="缺少抬起事件的按下次数">
+                    未匹配 {row.unmatched_downs}
+                  </Badge>
+                ) : null}
+                <Badge variant="outline" color="gray.700">
+                  {row.count}
+                </Badge>
+              </HStack>
+            </HStack>
+          ))}
+        </Stack>
+      )}
+    </Box>
+  );
+}
+
+export default KeyHoldPanel;
@@ -13,15 +13,19 @@ import {
   Keyboard,
   Layers3,
   LucideIcon,
+  Target,
 } from "lucide-react";
-import { StatsRow, Totals } from "../../types";
+import { DailyTotal, FilterRange, FocusDayScore, Totals } from "../../types";
 import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
 import { formatMs } from "../../utils/stats";
 import ContributionHeatmap from "./ContributionHeatmap";
 
 type MetricsGridProps = {
   totals: Totals;
-  rows: StatsRow[];
+  rows: DailyTotal[];
+  filterRange: FilterRange;
+  todayFocusScore: number | null;
+  focusScores: FocusDayScore[];
 };
 
 type MetricProps = {
@@ -111,6 +115,22 @@ function buildSegmentInsights(totals: Totals) {
   };
 }
 
+// Today uses the live snapshot score; longer ranges average the daily scores.
+function buildFocusValue(
+  filterRange: FilterRange,
+  todayFocusScore: number | null,
+  focusScores: FocusDayScore[],
+) {
+  if (filterRange === "today") {
+    return todayFocusScore ?? "-";
+  }
+  if (focusScores.length === 0) {
+    return "-";
+  }
+  const total = focusScores.reduce((sum, day) => sum + day.score, 0);
+  return Math.round(total / focusScores.length);
+}
+
 function Metric({
   label,
   help,
@@ -205,7 +225,13 @@ function Metric({
   );
 }
 
-function MetricsGrid({ totals, rows }: MetricsGridProps) {
+function MetricsGrid({
+  totals,
+  rows,
+  filterRange,
+  todayFocusScore,
+  focusScores,
+}: MetricsGridProps) {
   const funFacts = buildFunFacts(totals);
   const segmentInsights = buildSegmentInsights(totals);
 
@@ -260,7 +286,10 @@ function MetricsGrid({ totals, rows }: MetricsGridProps) {
           </Accordion.ItemTrigger>
           <Accordion.ItemContent>
             <Accordion.ItemBody px="4" pt="2" pb="4">
-              <Grid templateColumns={{ base: "1fr", md: "repeat(3, 1fr)" }} gap="3">
+              <Grid
+                templateColumns={{ base: "1fr", md: "repeat(2, 1fr)", xl: "repeat(4, 1fr)" }}
+                gap="3"
+              >
                 <Metric
                   label="输入段数"
                   help="两次按键隔了超过5秒，就算开始了一个新的输入段。"
@@ -285,6 +314,14 @@ function MetricsGrid({ totals, rows }: MetricsGridProps) {
                   iconBg="green.100"
                   iconColor="green.700"
                 />
+                <Metric
+                  label="专注度"
+                  help="0–100 分：连续输入越久、切换应用越少、打字越密集，分数越高。多天范围取每日平均。"
+                  value={buildFocusValue(filterRange, todayFocusScore, focusScores)}
+                  icon={Target}
+                  iconBg="orange.100"
+                  iconColor="orange.700"
+                />
               </Grid>
             </Accordion.ItemBody>
           </Accordion.ItemContent>
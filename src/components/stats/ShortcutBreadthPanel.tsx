@@ -0,0 +1,92 @@
+import { Badge, Box, HStack, Stack, Text } from "@chakra-ui/react";
+import { ShortcutBreadth } from "../../types";
+import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+
+type ShortcutBreadthPanelProps = {
+  breadth: ShortcutBreadth | null;
+};
+
+// Rows listed per group; the full list is sorted by total uses on the backend.
+const BREADTH_ROW_LIMIT = 8;
+
+// A shortcut used in one app (or almost only there) is a remapping candidate for that app.
+function isAppSpecific(row: ShortcutBreadth["shortcuts"][number]): boolean {
+  return row.app_count === 1 || row.concentration >= 0.8;
+}
+
+function ShortcutBreadthPanel({ breadth }: ShortcutBreadthPanelProps) {
+  const rows = breadth?.shortcuts ?? [];
+  const universal = rows.filter((row) => !isAppSpecific(row)).slice(0, BREADTH_ROW_LIMIT);
+  const specific = rows.filter(isAppSpecific).slice(0, BREADTH_ROW_LIMIT);
+
+  const renderGroup = (title: string, groupRows: typeof rows) => (
+    <Box {...glassSubtleStyle} borderRadius="12px" p="4">
+      <Text fontSize="sm" fontWeight="semibold" color="gray.700" mb="3">
+        {title}
+      </Text>
+      {groupRows.length === 0 ? (
+        <Text fontSize="sm" color="gray.500">
+          暂无
+        </Text>
+      ) : (
+        <Stack gap="2">
+          {groupRows.map((row) => (
+            <HStack
+              key={row.shortcut_id}
+              justify="space-between"
+              gap="3"
+              px="3"
+              py="2"
+              borderRadius="8px"
+              bg="rgba(255,255,255,0.24)"
+              borderWidth="1px"
+              borderColor="glass.borderSoft"
+            >
+              <Stack gap="0" minW="0">
+                <Text fontSize="sm" fontWeight="medium">
+                  {row.shortcut_id}
+                </Text>
+                <Text fontSize="xs" color="gray.600" truncate title={row.dominant_app}>
+                  {row.app_count} 个应用 · 主要在 {row.dominant_app}（{row.dominant_app_count} 次）
+                </Text>
+              </Stack>
+              <HStack gap="1.5" flexShrink={0}>
+                <Badge variant="subtle" colorPalette="purple" title="集中度（1 表示只在一个应用中使用）">
+                  {row.concentration.toFixed(2)}
+                </Badge>
+                <Badge variant="outline" color="gray.700">
+                  {row.count}
+                </Badge>
+              </HStack>
+            </HStack>
+          ))}
+        </Stack>
+      )}
+    </Box>
+  );
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="16px" p="6" h="full">
+      <HStack justify="space-between" mb="4" align="center">
+        <Text fontSize="xl" fontWeight="semibold">
+          快捷键使用广度
+        </Text>
+        <Text fontSize="sm" color="gray.600">
+          共 {rows.length} 个快捷键
+        </Text>
+      </HStack>
+      {rows.length === 0 ? (
+        <Text color="gray.500" py="2">
+          当前时间范围内暂无快捷键记录。
+        </Text>
+      ) : (
+        <Stack gap="4">
+          {renderGroup("通用快捷键（多个应用中使用）", universal)}
+          {renderGroup("应用专属快捷键", specific)}
+        </Stack>
+      )}
+    </Box>
+  );
+}
+
+export default ShortcutBreadthPanel;
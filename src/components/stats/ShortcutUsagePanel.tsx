@@ -6,7 +6,7 @@ import {
   Stack,
   Text,
 } from "@chakra-ui/react";
-import { ShortcutStatRow } from "../../types";
+import { ChordAbortStats, ShortcutStatRow } from "../../types";
 import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
 import {
   formatShortcutLabel,
@@ -15,6 +15,7 @@ import {
 
 type ShortcutUsagePanelProps = {
   rows: ShortcutStatRow[];
+  chordAborts: ChordAbortStats | null;
 };
 
 type BadgeType = "clipboard" | "undo" | "switch";
@@ -84,7 +85,7 @@ function buildEfficiencyBadges(rows: ShortcutStatRow[]): BadgeType[] {
   return badges;
 }
 
-function ShortcutUsagePanel({ rows }: ShortcutUsagePanelProps) {
+function ShortcutUsagePanel({ rows, chordAborts }: ShortcutUsagePanelProps) {
   const topRows = rows.slice(0, 5);
   const badges = buildEfficiencyBadges(rows);
   const maxCount = Math.max(...topRows.map((r) => r.count), 0);
@@ -100,6 +101,13 @@ function ShortcutUsagePanel({ rows }: ShortcutUsagePanelProps) {
         </Text>
       </HStack>
 
+      {chordAborts && chordAborts.total > 0 && (
+        <Text fontSize="sm" color="gray.600" mb="4">
+          未完成组合键 {chordAborts.total} 次
+          {chordAborts.by_app[0] ? `，最多在 ${chordAborts.by_app[0].app_name}` : ""}
+        </Text>
+      )}
+
       {badges.length > 0 && (
         <Box mb="5">
           <Text fontSize="sm" color="gray.700" mb="3" fontWeight="semibold">
@@ -213,7 +221,7 @@ function ShortcutUsagePanel({ rows }: ShortcutUsagePanelProps) {
                       主要使用应用
                     </Text>
                     <Stack gap="2">
-                      {row.apps.slice(0, 5).map((app) => (
+                      {row.apps.map((app) => (
                         <HStack
                           key={`${row.shortcut_id}-${app.app_name}`}
                           justify="space-between"
@@ -226,6 +234,16 @@ function ShortcutUsagePanel({ rows }: ShortcutUsagePanelProps) {
                           </Badge>
                         </HStack>
                       ))}
+                      {row.other_count > 0 && (
+                        <HStack justify="space-between">
+                          <Text fontSize="sm" color="gray.500">
+                            其他应用
+                          </Text>
+                          <Badge variant="outline" colorPalette="gray">
+                            {row.other_count}
+                          </Badge>
+                        </HStack>
+                      )}
                     </Stack>
                   </Accordion.ItemBody>
                 </Accordion.ItemContent>
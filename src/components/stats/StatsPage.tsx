@@ -1,46 +1,150 @@
 import { Box, Grid, GridItem, Heading, HStack, Stack, Text } from "@chakra-ui/react";
 import {
+  AppSwitchStats,
+  AppTimeline,
+  BurstStats,
+  ChordAbortStats,
+  Comparison,
+  Coverage,
+  CurrentContext,
+  DailyTotal,
   FilterRange,
+  FocusDayScore,
   GroupedRow,
+  KeyboardLayout,
+  KeyHeatmap,
+  KeyHoldStats,
   KeyUsageRow,
-  StatsRow,
+  ShortcutBreadth,
   ShortcutStatRow,
   Totals,
   TrendGranularity,
   TrendSeries,
 } from "../../types";
+import { formatMs } from "../../utils/stats";
+import AppSwitchPanel from "./AppSwitchPanel";
 import AppTable from "./AppTable";
+import AppTimelinePanel from "./AppTimelinePanel";
+import BurstPanel from "./BurstPanel";
+import ComparisonPanel from "./ComparisonPanel";
 import DailyTopKeysPanel from "./DailyTopKeysPanel";
 import FilterBar from "./FilterBar";
+import KeyHeatmapPanel from "./KeyHeatmapPanel";
+import KeyHoldPanel from "./KeyHoldPanel";
 import MetricsGrid from "./MetricsGrid";
+import ShortcutBreadthPanel from "./ShortcutBreadthPanel";
 import ShortcutUsagePanel from "./ShortcutUsagePanel";
 import TrendChart from "./TrendChart";
 
 type StatsPageProps = {
   filterRange: FilterRange;
   onFilterChange: (value: FilterRange) => void;
-  allRows: StatsRow[];
+  dailyTotals: DailyTotal[];
   totals: Totals;
+  todayFocusScore: number | null;
+  currentContext: CurrentContext | null;
+  dayFirstKeyMs: number | null;
+  dayLastKeyMs: number | null;
+  focusScores: FocusDayScore[];
   groupedRows: GroupedRow[];
   trendSeries: TrendSeries;
   trendGranularity: TrendGranularity;
   onTrendGranularityChange: (value: TrendGranularity) => void;
   shortcutRows: ShortcutStatRow[];
+  shortcutBreadth: ShortcutBreadth | null;
+  chordAborts: ChordAbortStats | null;
   topKeysRows: KeyUsageRow[];
+  // Set when event chunk recording is off and range panels show lifetime aggregates.
+  rangeDegraded: boolean;
+  appTimeline: AppTimeline | null;
+  appSwitches: AppSwitchStats | null;
+  coverage: Coverage | null;
+  burstStats: BurstStats | null;
+  keyHolds: KeyHoldStats | null;
+  dayComparison: Comparison | null;
+  keyHeatmap: KeyHeatmap | null;
+  keyboardLayout: KeyboardLayout;
+  onKeyboardLayoutChange: (value: KeyboardLayout) => void;
 };
 
+// Explain gaps in the data: how much of the tracked time was actually recorded.
+function coverageText(coverage: Coverage | null): string | null {
+  if (!coverage || coverage.coverage_pct === null) {
+    return null;
+  }
+  const parts = [`采集覆盖率 ${coverage.coverage_pct.toFixed(1)}%`];
+  if (coverage.paused_ms > 0) {
+    parts.push(`暂停 ${formatMs(coverage.paused_ms)}`);
+  }
+  if (coverage.listener_down_ms > 0) {
+    parts.push(`键盘监听中断 ${formatMs(coverage.listener_down_ms)}`);
+  }
+  const notRunningDays = coverage.days.filter((day) => day.coverage === "not_running").length;
+  if (notRunningDays > 0) {
+    parts.push(`${notRunningDays} 天未运行`);
+  }
+  return parts.join(" · ");
+}
+
+// "Started at 08:42 · last key 19:10" for today, in local time.
+function daySpanText(firstMs: number | null, lastMs: number | null): string | null {
+  if (firstMs === null || lastMs === null) {
+    return null;
+  }
+  const clock = (ms: number) => {
+    const date = new Date(ms);
+    const pad = (value: number) => `${value}`.padStart(2, "0");
+    return `${pad(date.getHours())}:${pad(date.getMinutes())}`;
+  };
+  return `今天 ${clock(firstMs)} 开始输入 · 最后一次按键 ${clock(lastMs)}`;
+}
+
+// Live "typing in" line; the backend clears the context while paused or idle.
+function currentContextText(context: CurrentContext | null): string | null {
+  if (!context) {
+    return null;
+  }
+  const parts = [`正在输入：${context.app_name}`];
+  if (context.window_title) {
+    parts.push(context.window_title);
+  }
+  parts.push(`已持续 ${formatMs(Math.max(Date.now() - context.since_ms, 0))}`);
+  return parts.join(" · ");
+}
+
 function StatsPage({
   filterRange,
   onFilterChange,
-  allRows,
+  dailyTotals,
   totals,
+  todayFocusScore,
+  currentContext,
+  dayFirstKeyMs,
+  dayLastKeyMs,
+  focusScores,
   groupedRows,
   trendSeries,
   trendGranularity,
   onTrendGranularityChange,
   shortcutRows,
+  chordAborts,
   topKeysRows,
+  rangeDegraded,
+  appTimeline,
+  appSwitches,
+  shortcutBreadth,
+  coverage,
+  burstStats,
+  keyHolds,
+  dayComparison,
+  keyHeatmap,
+  keyboardLayout,
+  onKeyboardLayoutChange,
 }: StatsPageProps) {
+  const coverageSummary = coverageText(coverage);
+  const typingSummary = currentContextText(currentContext);
+  const daySpanSummary = daySpanText(dayFirstKeyMs, dayLastKeyMs);
+
   return (
     <Box>
       <HStack justify="space-between" align="center" mb="6">
@@ -51,13 +155,39 @@ function StatsPage({
           <Text fontSize="sm" color="gray.600">
             你的输入节奏、应用分布和效率偏好，都在这里一屏看完。
           </Text>
+          {typingSummary ? (
+            <Text fontSize="xs" color="gray.600" truncate maxW="640px">
+              {typingSummary}
+            </Text>
+          ) : null}
+          {daySpanSummary ? (
+            <Text fontSize="xs" color="gray.600">
+              {daySpanSummary}
+            </Text>
+          ) : null}
+          {coverageSummary ? (
+            <Text fontSize="xs" color="gray.500">
+              {coverageSummary}
+            </Text>
+          ) : null}
+          {rangeDegraded ? (
+            <Text fontSize="xs" color="orange.600">
+              按键序列记录已关闭：快捷键与 Top5 按键不再区分时间范围，显示的是全部历史累计。
+            </Text>
+          ) : null}
         </Stack>
         <FilterBar filterRange={filterRange} onChange={onFilterChange} />
       </HStack>
 
       <Grid templateColumns="1fr" gap="6" mb="6" alignItems="start">
         <GridItem minW="0">
-          <MetricsGrid totals={totals} rows={allRows} />
+          <MetricsGrid
+            totals={totals}
+            rows={dailyTotals}
+            filterRange={filterRange}
+            todayFocusScore={todayFocusScore}
+            focusScores={focusScores}
+          />
         </GridItem>
       </Grid>
 
@@ -80,18 +210,68 @@ function StatsPage({
         </GridItem>
       </Grid>
 
+      <Grid
+        templateColumns={{ base: "1fr", xl: "2fr 1fr" }}
+        gap="6"
+        mb="6"
+        alignItems="start"
+      >
+        <GridItem minW="0">
+          <AppTimelinePanel timeline={appTimeline} />
+        </GridItem>
+        <GridItem minW="0">
+          <ComparisonPanel comparison={dayComparison} />
+        </GridItem>
+      </Grid>
+
+      <Grid
+        templateColumns={{ base: "1fr", xl: "2fr 1fr" }}
+        gap="6"
+        mb="6"
+        alignItems="start"
+      >
+        <GridItem minW="0">
+          <KeyHeatmapPanel
+            heatmap={keyHeatmap}
+            layout={keyboardLayout}
+            onLayoutChange={onKeyboardLayoutChange}
+          />
+        </GridItem>
+        <GridItem minW="0">
+          <AppSwitchPanel stats={appSwitches} />
+        </GridItem>
+      </Grid>
+
       <Grid
         templateColumns={{ base: "1fr", xl: "1fr 1fr" }}
         gap="6"
         alignItems="start"
       >
         <GridItem minW="0">
-          <ShortcutUsagePanel rows={shortcutRows} />
+          <ShortcutUsagePanel rows={shortcutRows} chordAborts={chordAborts} />
         </GridItem>
         <GridItem minW="0">
           <AppTable rows={groupedRows} />
         </GridItem>
       </Grid>
+
+      <Grid
+        templateColumns={{ base: "1fr", xl: "2fr 1fr" }}
+        gap="6"
+        mt="6"
+        alignItems="start"
+      >
+        <GridItem minW="0">
+          <ShortcutBreadthPanel breadth={shortcutBreadth} />
+        </GridItem>
+        <GridItem minW="0">
+          <BurstPanel stats={burstStats} />
+        </GridItem>
+      </Grid>
+
+      <Box mt="6">
+        <KeyHoldPanel stats={keyHolds} />
+      </Box>
     </Box>
   );
 }
@@ -1,4 +1,4 @@
-import { Badge, Box, Flex, Stack, Text } from "@chakra-ui/react";
+import { Badge, Box, Flex, Progress, Stack, Text } from "@chakra-ui/react";
 import { Snapshot } from "../../types";
 
 type StatusCardProps = {
@@ -21,6 +21,73 @@ function StatusCard({ snapshot }: StatusCardProps) {
             {snapshot.paused ? "已暂停" : "运行中"}
           </Badge>
         </Stack>
+        {snapshot.demo_mode ? (
+          <Stack gap="1" minW="160px">
+            <Text fontSize="sm" color="gray.600">数据来源</Text>
+            <Badge colorPalette="purple" width="fit-content" title="演示模式：统计为生成的示例数据，只保存在内存中，不会写入任何文件">
+              演示数据
+            </Badge>
+          </Stack>
+        ) : null}
+        {snapshot.loading ? (
+          <Stack gap="1" minW="160px">
+            <Text fontSize="sm" color="gray.600">历史数据</Text>
+            <Progress.Root
+              size="xs"
+              width="140px"
+              value={snapshot.load_files_total > 0 ? snapshot.load_files_processed : null}
+              max={Math.max(snapshot.load_files_total, 1)}
+              title="正在后台加载历史数据，新的按键照常记录"
+            >
+              <Progress.Track>
+                <Progress.Range />
+              </Progress.Track>
+            </Progress.Root>
+            <Text fontSize="xs" color="gray.500">
+              {snapshot.load_files_processed} / {snapshot.load_files_total}
+            </Text>
+          </Stack>
+        ) : null}
+        {!snapshot.storage_writable ? (
+          <Stack gap="1" minW="160px">
+            <Text fontSize="sm" color="gray.600">数据目录</Text>
+            <Badge colorPalette="red" width="fit-content" title="数据目录缺失或只读，统计暂存在内存中，恢复后自动写入">
+              不可用
+            </Badge>
+          </Stack>
+        ) : null}
+        {snapshot.storage_degraded ? (
+          <Stack gap="1" minW="160px">
+            <Text fontSize="sm" color="gray.600">数据写入</Text>
+            <Badge colorPalette="red" width="fit-content" title="保存持续失败，统计暂存在内存中">
+              无法写入数据目录
+            </Badge>
+          </Stack>
+        ) : null}
+        {snapshot.external_changes_detected ? (
+          <Stack gap="1" minW="160px">
+            <Text fontSize="sm" color="gray.600">数据文件</Text>
+            <Badge colorPalette="orange" width="fit-content" title="运行期间有数据文件被外部删除或修改，详见应用日志；未变动的日期不会被重新写回">
+              外部改动
+            </Badge>
+          </Stack>
+        ) : null}
+        {snapshot.tick_stalled ? (
+          <Stack gap="1" minW="160px">
+            <Text fontSize="sm" color="gray.600">统计循环</Text>
+            <Badge colorPalette="orange" width="fit-content" title="统计循环曾停止响应，已自动重启">
+              已自动恢复
+            </Badge>
+          </Stack>
+        ) : null}
+        {snapshot.update_available ? (
+          <Stack gap="1" minW="160px">
+            <Text fontSize="sm" color="gray.600">版本</Text>
+            <Badge colorPalette="blue" width="fit-content" title="可在设置中查看发布说明">
+              有新版本可用
+            </Badge>
+          </Stack>
+        ) : null}
       </Flex>
       {snapshot.last_error ? (
         <Text mt="4" color="red.600" fontSize="sm">
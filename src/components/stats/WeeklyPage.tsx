@@ -0,0 +1,124 @@
+import { Box, Button, Grid, HStack, Stack, Text } from "@chakra-ui/react";
+import { invoke } from "@tauri-apps/api/core";
+import { useEffect, useState } from "react";
+import { WeeklyComparison, WeekTotals } from "../../types";
+import { glassSubtleStyle, glassSurfaceStyle } from "../../styles/glass";
+import { formatMs } from "../../utils/stats";
+
+// Monday `YYYY-MM-DD` moved by whole weeks, in local time like the backend's week keys.
+function shiftWeek(weekStart: string, weeks: number): string {
+  const [year, month, day] = weekStart.split("-").map(Number);
+  const date = new Date(year, month - 1, day + weeks * 7);
+  const pad = (value: number) => `${value}`.padStart(2, "0");
+  return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}`;
+}
+
+// Minutes since local midnight as `HH:MM`.
+function clock(minutes: number): string {
+  const pad = (value: number) => `${value}`.padStart(2, "0");
+  return `${pad(Math.floor(minutes / 60))}:${pad(minutes % 60)}`;
+}
+
+function WeekColumn({ title, totals }: { title: string; totals: WeekTotals }) {
+  return (
+    <Box {...glassSubtleStyle} borderRadius="12px" p="4">
+      <Text fontSize="sm" fontWeight="semibold" color="gray.700" mb="3">
+        {title} · {totals.week_start} 起
+      </Text>
+      <Stack gap="1.5">
+        <HStack justify="space-between">
+          <Text fontSize="sm" color="gray.600">按键数</Text>
+          <Text fontSize="sm" fontWeight="semibold">{totals.key_count.toLocaleString()}</Text>
+        </HStack>
+        <HStack justify="space-between">
+          <Text fontSize="sm" color="gray.600">活跃时长</Text>
+          <Text fontSize="sm" fontWeight="semibold">{formatMs(totals.active_typing_ms)}</Text>
+        </HStack>
+        <HStack justify="space-between">
+          <Text fontSize="sm" color="gray.600">平均开始 / 结束</Text>
+          <Text fontSize="sm" fontWeight="semibold">
+            {totals.avg_day_start_min !== null && totals.avg_day_end_min !== null
+              ? `${clock(totals.avg_day_start_min)} – ${clock(totals.avg_day_end_min)}`
+              : "—"}
+          </Text>
+        </HStack>
+        <HStack justify="space-between" align="start">
+          <Text fontSize="sm" color="gray.600">最常用应用</Text>
+          <Text fontSize="sm" textAlign="right" truncate title={totals.top_app ?? undefined}>
+            {totals.top_app ?? "—"}
+          </Text>
+        </HStack>
+      </Stack>
+    </Box>
+  );
+}
+
+// Weekly report: one week against the week before, opened from the Monday notification.
+function WeeklyPage() {
+  // null shows last week, as the notification does.
+  const [weekStart, setWeekStart] = useState<string | null>(null);
+  const [comparison, setComparison] = useState<WeeklyComparison | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let mounted = true;
+    invoke<WeeklyComparison>("get_weekly_comparison", { weekStart })
+      .then((data) => {
+        if (mounted) {
+          setComparison(data);
+          setError(null);
+        }
+      })
+      .catch((err) => {
+        if (mounted) {
+          setError(String(err));
+        }
+      });
+    return () => {
+      mounted = false;
+    };
+  }, [weekStart]);
+
+  const gain = comparison?.top_shortcut_gain ?? null;
+
+  return (
+    <Box {...glassSurfaceStyle} borderRadius="16px" p="6">
+      <HStack justify="space-between" mb="4" align="center">
+        <Text fontSize="xl" fontWeight="semibold">
+          对比前一周
+        </Text>
+        {comparison ? (
+          <HStack gap="2">
+            <Button size="xs" variant="outline" onClick={() => setWeekStart(shiftWeek(comparison.week.week_start, -1))}>
+              上一周
+            </Button>
+            <Button size="xs" variant="outline" onClick={() => setWeekStart(shiftWeek(comparison.week.week_start, 1))}>
+              下一周
+            </Button>
+          </HStack>
+        ) : null}
+      </HStack>
+
+      {error ? (
+        <Text color="red.500" fontSize="sm">{error}</Text>
+      ) : !comparison ? (
+        <Text color="gray.500" py="2">加载中…</Text>
+      ) : (
+        <Stack gap="4">
+          <Text color="gray.700">{comparison.summary}</Text>
+          <Grid templateColumns={{ base: "1fr", md: "1fr 1fr" }} gap="4">
+            <WeekColumn title="该周" totals={comparison.week} />
+            <WeekColumn title="前一周" totals={comparison.previous_week} />
+          </Grid>
+          <Text fontSize="sm" color="gray.600">
+            {gain
+              ? `增长最多的快捷键：${gain.shortcut_id}（${gain.previous_count} → ${gain.count}）`
+              : "没有使用次数增长的快捷键。"}
+          </Text>
+        </Stack>
+      )}
+    </Box>
+  );
+}
+
+export default WeeklyPage;
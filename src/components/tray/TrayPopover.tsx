@@ -10,6 +10,7 @@ import {
   trayPopoverSurfaceStyle,
 } from "../../styles/glass";
 import { Snapshot } from "../../types";
+import { applyCaptureState, listenCaptureStateChanged } from "../../utils/captureState";
 import { formatMs, parseRowDate } from "../../utils/stats";
 
 const REFRESH_INTERVAL_MS = 1_500;
@@ -78,6 +79,15 @@ function TrayPopover() {
     };
   }, []);
 
+  useEffect(() => {
+    const unlisten = listenCaptureStateChanged((state) =>
+      setSnapshot((current) => applyCaptureState(current, state)),
+    );
+    return () => {
+      void unlisten.then((stop) => stop());
+    };
+  }, []);
+
   useEffect(() => {
     // Keep all root layers transparent so macOS vibrancy is visible through the whole panel.
     const previousHtmlBackground = document.documentElement.style.background;
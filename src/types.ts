@@ -7,6 +7,50 @@ export type StatsRow = {
   active_typing_ms: number;
   key_count: number;
   session_count: number;
+  // Parts of active_typing_ms spent writing vs navigating (arrows, page keys, shortcuts).
+  writing_ms: number;
+  navigation_ms: number;
+};
+
+// Per-day totals for the contribution calendar, from get_daily_totals.
+export type DailyTotal = {
+  date: string;
+  key_count: number;
+  active_typing_ms: number;
+  session_count: number;
+  day_first_key_ms: number | null;
+  day_last_key_ms: number | null;
+};
+
+// Arguments and result of query_stats_rows, the paged history browser query.
+export type StatsRowFilter = {
+  from?: string;
+  to?: string;
+  app?: string;
+  title?: string;
+};
+
+export type StatsRowColumn =
+  | "date"
+  | "app_name"
+  | "window_title"
+  | "active_typing_ms"
+  | "key_count"
+  | "session_count"
+  | "writing_ms"
+  | "navigation_ms";
+
+export type StatsRowSort = {
+  column: StatsRowColumn;
+  descending: boolean;
+};
+
+export type StatsRowPage = {
+  rows: StatsRow[];
+  total: number;
+  page: number;
+  page_size: number;
+  history_loading: boolean;
 };
 
 export type Snapshot = {
@@ -14,23 +58,274 @@ export type Snapshot = {
   paused: boolean;
   auto_paused: boolean;
   auto_pause_reason: "blacklist" | "secure_input" | null;
+  shortcuts_paused: boolean;
   keyboard_active: boolean;
   ignore_key_combos: boolean;
+  track_chord_aborts: boolean;
+  prevent_app_nap: boolean;
+  app_nap_assertion_held: boolean;
   excluded_bundle_ids: string[];
   one_password_suggestion_pending: boolean;
+  non_typing_suggestions: NonTypingSuggestion[];
   tray_display_mode: MenuBarDisplayMode;
+  tray_left_click_action: TrayLeftClickAction;
+  start_hidden: boolean;
+  min_keys_to_display: number;
+  window_title_mode: WindowTitleMode;
+  title_privacy_overrides: Record<string, WindowTitleMode>;
   last_error: string | null;
+  storage_degraded: boolean;
+  storage_writable: boolean;
+  // Stored history is still loading; load_files_* drive the progress bar.
+  loading: boolean;
+  // Demo mode: generated stats kept in memory, nothing is written to disk.
+  demo_mode: boolean;
+  load_files_processed: number;
+  load_files_total: number;
+  external_changes_detected: boolean;
+  tick_stalled: boolean;
+  update_available: boolean;
   log_path: string;
+  // With snapshot_v2, rows holds today only and shortcut_stats the top 50; ranges come from
+  // get_stats_rows and get_daily_totals.
   shortcut_stats: ShortcutStatRow[];
+  shortcut_total_count: number;
+  row_total_count: number;
+  snapshot_v2: boolean;
+  // Epoch ms of today's first and last counted key-down; null before the first.
+  day_first_key_ms: number | null;
+  day_last_key_ms: number | null;
+  today_focus_score: number | null;
+  today_coverage_pct: number | null;
+  goal_progress: AppGoalProgress[];
+  current_context: CurrentContext | null;
+  avg_keys_7d: number | null;
+  avg_keys_30d: number | null;
+  avg_active_ms_7d: number | null;
+  avg_active_ms_30d: number | null;
+  today_vs_avg_pct: number | null;
+  effort_today: number;
+  deep_work_minutes_today: number;
+};
+
+export type CurrentContext = {
+  bundle_id: string | null;
+  app_name: string;
+  window_title: string;
+  since_ms: number;
+};
+
+export type AppGoalProgress = {
+  bundle_id: string;
+  target_min: number;
+  actual_min: number;
+  pct: number;
+};
+
+// Versioned summary for scripts (`get_today_summary_json`, `--today-json`).
+export type TodaySummaryJson = {
+  v: number;
+  keys: number;
+  active_ms: number;
+  sessions: number;
+  top_app: string | null;
+  top_shortcut: string | null;
+  paused: boolean | null;
+  goal_progress: AppGoalProgress[];
+  day_first_key_ms: number | null;
+  day_last_key_ms: number | null;
+};
+
+export type CaptureStateChanged = {
+  paused: boolean;
+  auto_paused: boolean;
+  reason: Snapshot["auto_pause_reason"];
+};
+
+export type MenuBarDisplayMode = "icon_only" | "text_only" | "icon_text" | "sparkline";
+
+export type TrayLeftClickAction = "menu" | "open_window" | "toggle_pause";
+
+export type WindowTitleMode = "full" | "hidden";
+
+export type AutoExportFormat = "csv" | "json";
+
+export type PathPickKind = "folder" | "save_file" | "open_file";
+
+export type AutoExportConfig = {
+  enabled: boolean;
+  format: AutoExportFormat;
+  dest_dir: string;
+  script_path: string | null;
+  sign: boolean;
+};
+
+export type CsvDelimiter = "comma" | "semicolon" | "tab";
+
+export type SymbolKeyNames = "physical" | "produced";
+
+export type StorageBackend = "json" | "sqlite";
+
+export type CsvDialect = {
+  delimiter: CsvDelimiter;
+  utf8_bom: boolean;
+  headers_localized: boolean;
+};
+
+export type WindowBounds = {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+};
+
+// Shortcut counting rules a profile uses in place of the top-level ones.
+export type ShortcutRules = {
+  require_cmd_or_ctrl: boolean;
+  allow_alt_only: boolean;
+  min_modifiers: number;
+  allowlist: string[];
+  blocklist: string[];
+};
+
+// Separate data set in its own data subdirectory; null overrides use the top-level settings.
+export type ProfileConfig = {
+  name: string;
+  data_subdir: string;
+  excluded_bundle_ids: string[] | null;
+  shortcut_rules: ShortcutRules | null;
+};
+
+// Picks a profile while the local time is in its window or one of its apps is frontmost.
+export type ProfileAutoRule = {
+  profile: string;
+  priority: number;
+  start: string;
+  end: string;
+  weekdays: number[];
+  bundle_ids: string[];
+};
+
+export type AppConfig = {
+  active_profile: string;
+  app_time_goal_notifications: boolean;
+  app_time_goals: Record<string, number>;
+  app_time_goals_notified: Record<string, string>;
+  auto_export: AutoExportConfig;
+  auto_update_check: boolean;
+  ax_sensitive_detection: boolean;
+  ax_sensitive_title_keywords: string[];
+  burst_max_gap_ms: number;
+  burst_min_keys: number;
+  collector_tick_interval_secs: number;
+  confirm_quit: boolean;
+  copy_paste_window_secs: number;
+  csv_delimiter: CsvDelimiter;
+  csv_headers_localized: boolean;
+  csv_utf8_bom: boolean;
+  deep_work_min_keys_per_5min: number;
+  deep_work_min_minutes: number;
+  demo_mode: boolean;
+  display_labels: Record<string, string>;
+  excluded_bundle_ids: string[];
+  flush_interval_secs: number;
+  ignore_key_combos: boolean;
+  main_window_bounds: WindowBounds | null;
+  maintenance_idle_secs: number;
+  maintenance_max_defer_secs: number;
+  menu_bar_display_mode: MenuBarDisplayMode;
+  min_keys_to_display: number;
+  minute_resolution_days: number;
+  non_typing_suggestions_handled: string[];
+  one_password_suggestion_handled: boolean;
+  prevent_app_nap: boolean;
+  profile_auto_cooldown_mins: number;
+  profile_auto_fallback: string;
+  profile_auto_hold_secs: number;
+  profile_auto_rules: ProfileAutoRule[];
+  profiles: ProfileConfig[];
+  record_event_chunks: boolean;
+  session_gap_secs: number;
+  shortcut_allow_alt_only: boolean;
+  shortcut_allowlist: string[];
+  shortcut_blocklist: string[];
+  shortcut_min_modifiers: number;
+  shortcut_require_cmd_or_ctrl: boolean;
+  shortcuts_paused: boolean;
+  snapshot_v2: boolean;
+  start_hidden: boolean;
+  storage_backend: StorageBackend;
+  symbol_key_names: SymbolKeyNames;
+  telemetry_enabled: boolean;
+  telemetry_endpoint: string;
+  title_privacy_overrides: Record<string, WindowTitleMode>;
+  track_chord_aborts: boolean;
+  tray_left_click_action: TrayLeftClickAction;
+  tray_shortcuts_toggle: boolean;
+  tray_update_interval_secs: number;
+  update_check_url: string;
+  window_title_aliases: Record<string, Record<string, string>>;
+  window_title_mode: WindowTitleMode;
+  write_status_file: boolean;
+  applescript_enabled: boolean;
+  raw_export_max_window_hours: number;
+  weekly_comparison_notifications: boolean;
+};
+
+// App whose keys look like game or navigation input, suggested for the exclusion list.
+export type NonTypingSuggestion = {
+  bundle_id: string;
+  days: number;
+  likelihood: number;
+};
+
+// Key counts per minute, oldest first; the last entry is the current, still filling minute.
+export type ShortcutDayCount = {
+  date: string;
+  count: number;
+};
+
+export type MinuteSeries = {
+  end_minute_ms: number;
+  counts: number[];
+};
+
+// Timing settings in seconds, as sent to `update_timing_settings`.
+export type TimingSettings = {
+  collector_tick_interval_secs: number;
+  flush_interval_secs: number;
+  session_gap_secs: number;
+  tray_update_interval_secs: number;
+  maintenance_idle_secs: number;
+  maintenance_max_defer_secs: number;
 };
 
-export type MenuBarDisplayMode = "icon_only" | "text_only" | "icon_text";
+export type ConfigGroup =
+  | "capture"
+  | "export"
+  | "goals"
+  | "privacy"
+  | "profiles"
+  | "shortcuts"
+  | "storage"
+  | "timing"
+  | "tray"
+  | "updates"
+  | "window";
+
+export type ConfigSource = "default" | "user";
+
+export type EffectiveConfig = {
+  config: AppConfig;
+  config_source: Record<ConfigGroup, ConfigSource>;
+};
 
 export type GroupedRow = {
   app_name: string;
   active_typing_ms: number;
   key_count: number;
   session_count: number;
+  distinct_windows: number;
 };
 
 export type Totals = {
@@ -63,9 +358,497 @@ export type ShortcutStatRow = {
   shortcut_id: string;
   count: number;
   apps: ShortcutAppUsageRow[];
+  other_count: number;
 };
 
 export type KeyUsageRow = {
   key: string;
   count: number;
+  // Empty for the heat map's unplaced keys, which are not counted per app.
+  apps: ShortcutAppUsageRow[];
+  other_count: number;
+};
+
+// Range responses fall back to lifetime aggregates when event chunk recording is off.
+export type ShortcutRulesPreset = {
+  v: number;
+  require_cmd_or_ctrl: boolean;
+  allow_alt_only: boolean;
+  min_modifiers: number;
+  allowlist: string[];
+  blocklist: string[];
+};
+
+export type ShortcutRulesDiff = {
+  merge: boolean;
+  changed_settings: string[];
+  allowlist_added: string[];
+  allowlist_removed: string[];
+  blocklist_added: string[];
+  blocklist_removed: string[];
+  rules: ShortcutRulesPreset;
+};
+
+export type ShortcutRangeStats = {
+  rows: ShortcutStatRow[];
+  degraded_range_support: boolean;
+};
+
+export type TopKeysRangeStats = {
+  rows: KeyUsageRow[];
+  degraded_range_support: boolean;
+};
+
+export type AppTimelineBucket = {
+  bucket_start_ms: number;
+  key_count: number;
+  active_ms: number;
+};
+
+export type AppTimelineRow = {
+  app_name: string;
+  key_count: number;
+  active_ms: number;
+  buckets: AppTimelineBucket[];
+};
+
+export type AppTimeline = {
+  date: string;
+  bucket_minutes: number;
+  day_start_ms: number;
+  day_end_ms: number;
+  apps: AppTimelineRow[];
+};
+
+export type ChordAbortStats = {
+  range: FilterRange;
+  total: number;
+  by_app: { app_name: string; count: number }[];
+  by_day: { date: string; count: number }[];
+};
+
+export type AppSwitchStats = {
+  range: FilterRange;
+  total: number;
+  top_pairs: { from: string; to: string; count: number }[];
+};
+
+export type CopyPasteStats = {
+  range: FilterRange;
+  window_ms: number;
+  pairs: number;
+  unmatched_copies: number;
+  p50_ms: number | null;
+  p90_ms: number | null;
+  p99_ms: number | null;
+  top_pairs: { from: string; to: string; count: number }[];
+};
+
+export type ShortcutBreadthRow = {
+  shortcut_id: string;
+  count: number;
+  app_count: number;
+  concentration: number;
+  dominant_app: string;
+  dominant_app_count: number;
+};
+
+export type ShortcutBreadth = {
+  range: FilterRange;
+  shortcuts: ShortcutBreadthRow[];
+  degraded_range_support: boolean;
+};
+
+export type DayCoverage = "not_running" | "idle" | "active";
+
+export type RuntimeDay = {
+  date: string;
+  runtime_ms: number;
+  coverage: DayCoverage;
+};
+
+export type CoverageDayRow = {
+  date: string;
+  recording_ms: number;
+  paused_ms: number;
+  listener_down_ms: number;
+  coverage_pct: number | null;
+  runtime_ms: number;
+  coverage: DayCoverage;
+};
+
+export type Coverage = {
+  range: FilterRange;
+  recording_ms: number;
+  paused_ms: number;
+  listener_down_ms: number;
+  coverage_pct: number | null;
+  days: CoverageDayRow[];
+};
+
+export type BurstDayRow = {
+  date: string;
+  burst_count: number;
+  mean_keys: number | null;
+  mean_ms: number | null;
+  longest_keys: number;
+  longest_ms: number;
+};
+
+export type BurstStats = {
+  range: FilterRange;
+  min_keys: number;
+  max_gap_ms: number;
+  burst_count: number;
+  mean_keys: number | null;
+  mean_ms: number | null;
+  longest_keys: number;
+  longest_ms: number;
+  days: BurstDayRow[];
+};
+
+export type EffortDayRow = {
+  date: string;
+  effort: number;
+};
+
+export type EffortStats = {
+  range: FilterRange;
+  effort: number;
+  days: EffortDayRow[];
+};
+
+export type AppSummaryRow = {
+  app_name: string;
+  key_count: number;
+  active_typing_ms: number;
+  session_count: number;
+  writing_ms: number;
+  navigation_ms: number;
+};
+
+export type AppSummary = {
+  range: FilterRange;
+  min_keys_to_display: number;
+  apps: AppSummaryRow[];
+  other: AppSummaryRow | null;
+  other_app_count: number;
+};
+
+export type TopWindowRow = {
+  window_title: string;
+  // Keys recorded with titles hidden; window_title is empty.
+  title_hidden: boolean;
+  key_count: number;
+  active_typing_ms: number;
+  last_seen_date: string;
+};
+
+export type TopWindows = {
+  bundle_id: string;
+  range: FilterRange;
+  windows: TopWindowRow[];
+  window_count: number;
+};
+
+export type DeepWorkBlock = {
+  start_ms: number;
+  end_ms: number;
+  minutes: number;
+  key_count: number;
+  dominant_app: string;
+};
+
+export type DisplayRow = {
+  display_id: string;
+  label: string;
+  key_count: number;
+  share_pct: number;
+};
+
+export type DisplayDayRow = {
+  date: string;
+  counts: Record<string, number>;
+};
+
+export type DisplayStats = {
+  range: FilterRange;
+  total_keys: number;
+  displays: DisplayRow[];
+  days: DisplayDayRow[];
+};
+
+export type KeyHoldRow = {
+  key: string;
+  count: number;
+  unmatched_downs: number;
+  mean_ms: number;
+  p95_ms: number;
+  max_ms: number;
+};
+
+export type KeyHoldStats = {
+  range: FilterRange;
+  matched: number;
+  unmatched_downs: number;
+  mean_ms: number;
+  p95_ms: number;
+  keys: KeyHoldRow[];
+};
+
+export type DestructiveAction = "reset_all" | "reset_today" | "prune_retention";
+
+export type DestructivePreview = {
+  action: DestructiveAction;
+  stats_rows: number;
+  event_chunks: number;
+  shortcut_entries: number;
+  files: number;
+  file_bytes: number;
+  confirm_token: string;
+  expires_in_ms: number;
+};
+
+export type TelemetryFeatures = {
+  ignore_key_combos: boolean;
+  track_chord_aborts: boolean;
+  prevent_app_nap: boolean;
+  window_titles_hidden: boolean;
+  title_privacy_overrides: boolean;
+  app_time_goals: boolean;
+  auto_export: boolean;
+  shortcut_rules: boolean;
+  start_hidden: boolean;
+};
+
+export type TelemetryPayload = {
+  app_version: string;
+  os: string;
+  command_counts: Record<string, number>;
+  features: TelemetryFeatures;
+};
+
+export type TelemetryPreview = {
+  enabled: boolean;
+  endpoint: string;
+  last_sent_ms: number | null;
+  next_send_ms: number | null;
+  payload: TelemetryPayload;
+};
+
+export type CommandMetric = {
+  command: string;
+  calls: number;
+  last_called_ms: number;
+  last_duration_ms: number;
+  last_error: string | null;
+};
+
+export type UpdateInfo = {
+  current: string;
+  latest: string | null;
+  update_available: boolean | null;
+  release_notes_url: string | null;
+};
+
+export type AppPurgeReport = {
+  bundle_id: string;
+  dry_run: boolean;
+  purged_at_ms: number;
+  stats_rows: number;
+  key_count: number;
+  active_typing_ms: number;
+  shortcut_uses: number;
+  shortcuts_dropped: number;
+  event_chunks: number;
+  events: number;
+  app_dict_entries: number;
+  chord_aborts: number;
+  app_switches: number;
+  copy_pastes: number;
+};
+
+export type XlsxSheetRows = {
+  sheet: string;
+  rows: number;
+};
+
+export type ExportSignature = {
+  v: number;
+  position: number;
+  prev_hash: string;
+  hash: string;
+};
+
+export type XlsxExportReport = {
+  path: string;
+  range: FilterRange;
+  sheets: XlsxSheetRows[];
+  signature: ExportSignature | null;
+};
+
+export type ExportVerification = {
+  path: string;
+  signed: boolean;
+  valid: boolean;
+  signature: ExportSignature | null;
+  in_chain: boolean | null;
+  reason: string | null;
+};
+
+export type ParquetFileRows = {
+  path: string;
+  rows: number;
+};
+
+export type ParquetExportReport = {
+  range: FilterRange;
+  files: ParquetFileRows[];
+};
+
+export type AnalyticsDayEvents = {
+  date: string;
+  events: number;
+};
+
+export type DataDirSize = {
+  total_bytes: number;
+  archive_files: number;
+  archive_bytes: number;
+};
+
+export type ArchiveReport = {
+  months: string[];
+  files: number;
+  bytes: number;
+};
+
+export type AnalyticsStats = {
+  chunk_count: number;
+  max_chunks: number;
+  max_events_per_chunk: number;
+  chunk_window_ms: number;
+  oldest_chunk_ms: number | null;
+  newest_chunk_ms: number | null;
+  total_events: number;
+  approx_bytes: number;
+  events_by_day: AnalyticsDayEvents[];
+};
+
+export type FocusDayScore = {
+  date: string;
+  score: number;
+  session_count: number;
+  longest_session_ms: number;
+  focused_ms: number;
+  app_switches: number;
+};
+
+export type KeyboardLayout = "ansi" | "iso";
+
+export type KeyHeatmapKey = {
+  key: string;
+  row: number;
+  x: number;
+  width: number;
+  height: number;
+  count: number;
+};
+
+export type KeyHeatmap = {
+  range: FilterRange;
+  layout: KeyboardLayout;
+  max_count: number;
+  keys: KeyHeatmapKey[];
+  other: KeyUsageRow[];
+  days: RuntimeDay[];
+};
+
+export type ComparisonKind = "date" | "app";
+
+export type ComparisonSide = {
+  subject: string;
+  key_count: number;
+  active_typing_ms: number;
+  session_count: number;
+  top_keys: KeyUsageRow[];
+  top_shortcuts: ShortcutStatRow[];
+};
+
+export type Comparison = {
+  kind: ComparisonKind;
+  range: FilterRange | null;
+  a: ComparisonSide;
+  b: ComparisonSide;
+  delta: {
+    key_count: number;
+    active_typing_ms: number;
+    session_count: number;
+  };
+};
+
+export type ComparisonError =
+  | { code: "invalid_kind"; kind: string }
+  | { code: "invalid_date"; subject: string }
+  | { code: "unknown_app"; subject: string }
+  | { code: "invalid_range"; range: string }
+  | { code: "state_unavailable" };
+
+// Debug-only (`debug-tools` build): day key counts replayed from event chunks up to as_of_ms,
+// diffed per app against the stored stats.
+export type AppReconstructionDiff = {
+  app_name: string;
+  reconstructed_keys: number;
+  stored_keys: number;
+  diff: number;
+};
+
+export type DayReconstruction = {
+  date: string;
+  as_of_ms: number;
+  chunk_count: number;
+  reconstructed_keys: number;
+  stored_keys: number;
+  apps: AppReconstructionDiff[];
+};
+
+// Debug-only (`debug-tools` build): single-use consent for one raw event export.
+export type RawExportConsent = {
+  consent: string;
+  expires_in_ms: number;
+  max_window_hours: number;
+};
+
+// One JSONL line is { ts_ms, app_id, key, modifiers, event_type: "down" | "up" }.
+export type RawExportReport = {
+  path: string;
+  start_ms: number;
+  end_ms: number;
+  events: number;
+};
+
+// Monday-to-Sunday totals; week_start is the Monday (YYYY-MM-DD).
+export type WeekTotals = {
+  week_start: string;
+  key_count: number;
+  active_typing_ms: number;
+  top_app: string | null;
+  // Mean start and end of the typing day, in minutes since local midnight.
+  avg_day_start_min: number | null;
+  avg_day_end_min: number | null;
+};
+
+export type ShortcutGain = {
+  shortcut_id: string;
+  count: number;
+  previous_count: number;
+};
+
+// One week against the week before; summary is the text of the Monday notification.
+export type WeeklyComparison = {
+  week: WeekTotals;
+  previous_week: WeekTotals;
+  key_change_pct: number | null;
+  top_shortcut_gain: ShortcutGain | null;
+  summary: string;
 };
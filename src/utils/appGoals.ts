@@ -0,0 +1,29 @@
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { AppGoalProgress } from "../types";
+
+export const APP_GOAL_MET_EVENT = "app-goal-met";
+
+// Show a system notification through the webview Notification API, asking for permission on
+// first use. Platforms whose webview lacks the API simply skip it.
+async function showAppGoalNotification(progress: AppGoalProgress) {
+  if (typeof Notification === "undefined") {
+    return;
+  }
+  let permission = Notification.permission;
+  if (permission === "default") {
+    permission = await Notification.requestPermission();
+  }
+  if (permission !== "granted") {
+    return;
+  }
+  new Notification("TypePulse", {
+    body: `${progress.bundle_id} 今日活跃打字已达 ${progress.actual_min} 分钟，完成 ${progress.target_min} 分钟目标。`,
+  });
+}
+
+// The backend emits this once per app and day when its active typing target is reached.
+export function listenAppGoalMet(): Promise<UnlistenFn> {
+  return listen<AppGoalProgress>(APP_GOAL_MET_EVENT, (event) => {
+    void showAppGoalNotification(event.payload);
+  });
+}
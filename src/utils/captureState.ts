@@ -0,0 +1,29 @@
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { CaptureStateChanged, Snapshot } from "../types";
+
+export const CAPTURE_STATE_CHANGED_EVENT = "capture-state-changed";
+
+// Merge a pushed capture state into the last polled snapshot so pause toggles from the tray
+// or another window show up before the next poll.
+export function applyCaptureState(
+  snapshot: Snapshot | null,
+  state: CaptureStateChanged,
+): Snapshot | null {
+  if (!snapshot) {
+    return snapshot;
+  }
+  return {
+    ...snapshot,
+    paused: state.paused,
+    auto_paused: state.auto_paused,
+    auto_pause_reason: state.reason,
+  };
+}
+
+export function listenCaptureStateChanged(
+  handler: (state: CaptureStateChanged) => void,
+): Promise<UnlistenFn> {
+  return listen<CaptureStateChanged>(CAPTURE_STATE_CHANGED_EVENT, (event) =>
+    handler(event.payload),
+  );
+}
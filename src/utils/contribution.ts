@@ -1,4 +1,4 @@
-import { StatsRow } from "../types";
+import { DailyTotal } from "../types";
 import { parseRowDate } from "./stats";
 
 export type ContributionCell = {
@@ -65,7 +65,7 @@ const intensityLevel = (value: number, maxValue: number): 0 | 1 | 2 | 3 | 4 => {
 
 // Build GitHub-style contribution cells from daily aggregated usage.
 export const buildContributionHeatmapData = (
-  rows: StatsRow[],
+  rows: DailyTotal[],
   options?: { monthsBack?: number },
 ): ContributionHeatmapData => {
   const monthsBack = Math.max(1, options?.monthsBack ?? 12);
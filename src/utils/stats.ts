@@ -129,3 +129,30 @@ export const buildTrendSeries = (
     averageKeysPerSession,
   };
 };
+
+// Convert stored key ids to readable labels for key cards and lists.
+export function keyLabel(key: string): string {
+  const baseMap: Record<string, string> = {
+    space: "Space",
+    enter: "Enter",
+    tab: "Tab",
+    esc: "Esc",
+    backspace: "Backspace",
+    delete: "Delete",
+    left: "←",
+    right: "→",
+    up: "↑",
+    down: "↓",
+    command: "Cmd",
+    shift: "Shift",
+    control: "Ctrl",
+    option: "Opt",
+  };
+  if (baseMap[key]) {
+    return baseMap[key];
+  }
+  if (key.length === 1 && /[a-z]/.test(key)) {
+    return key.toUpperCase();
+  }
+  return key;
+}
@@ -0,0 +1,27 @@
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+
+export const STORAGE_DEGRADED_EVENT = "storage-degraded";
+
+// Warn through the webview Notification API; the error itself stays visible in the status card.
+async function showStorageDegradedNotification(error: string) {
+  if (typeof Notification === "undefined") {
+    return;
+  }
+  let permission = Notification.permission;
+  if (permission === "default") {
+    permission = await Notification.requestPermission();
+  }
+  if (permission !== "granted") {
+    return;
+  }
+  new Notification("TypePulse 无法写入数据目录", {
+    body: `统计暂存在内存中，恢复写入后会自动保存。${error}`,
+  });
+}
+
+// The backend emits this once per failure episode after saves keep failing for several minutes.
+export function listenStorageDegraded(): Promise<UnlistenFn> {
+  return listen<string>(STORAGE_DEGRADED_EVENT, (event) => {
+    void showStorageDegradedNotification(event.payload);
+  });
+}
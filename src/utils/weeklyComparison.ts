@@ -0,0 +1,35 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import { WeeklyComparison } from "../types";
+
+export const WEEKLY_COMPARISON_EVENT = "weekly-comparison";
+
+// Show the summary through the webview Notification API; clicking it brings the main window
+// forward and hands over to `onOpen`, which switches to the weekly view.
+async function showWeeklyComparisonNotification(
+  comparison: WeeklyComparison,
+  onOpen: () => void,
+) {
+  if (typeof Notification === "undefined") {
+    return;
+  }
+  let permission = Notification.permission;
+  if (permission === "default") {
+    permission = await Notification.requestPermission();
+  }
+  if (permission !== "granted") {
+    return;
+  }
+  const notification = new Notification("TypePulse 周报", { body: comparison.summary });
+  notification.onclick = () => {
+    void invoke("show_main_panel");
+    onOpen();
+  };
+}
+
+// The backend emits this once a week from Monday morning with last week's comparison.
+export function listenWeeklyComparison(onOpen: () => void): Promise<UnlistenFn> {
+  return listen<WeeklyComparison>(WEEKLY_COMPARISON_EVENT, (event) => {
+    void showWeeklyComparisonNotification(event.payload, onOpen);
+  });
+}